@@ -66,6 +66,8 @@ Router.map(function () {
     this.route('trusted-publishing');
   });
   this.route('confirm', { path: '/confirm/:email_token' });
+  this.route('account-recovery', { path: '/account-recovery/:token' });
+  this.route('account-recovery-cancel', { path: '/account-recovery/cancel/:token' });
   this.route('accept-invite', { path: '/accept-invite/:token' });
   this.route('support');
 
@@ -0,0 +1,28 @@
+import Route from '@ember/routing/route';
+import { service } from '@ember/service';
+
+import ajax from '../utils/ajax';
+
+export default class AccountRecoveryCancelRoute extends Route {
+  @service notifications;
+  @service router;
+
+  async model(params) {
+    try {
+      await ajax(`/api/private/account_recovery/github_link/${params.token}`, { method: 'DELETE' });
+
+      this.notifications.success(
+        'The GitHub account link was cancelled. Please make sure that nobody else has access to your email address.',
+      );
+    } catch (error) {
+      let detail = error.errors?.[0]?.detail;
+      if (detail && !detail.startsWith('{')) {
+        this.notifications.error(`Error cancelling the GitHub account link: ${detail}`);
+      } else {
+        this.notifications.error(`Unknown error cancelling the GitHub account link`);
+      }
+    }
+
+    this.router.replaceWith('index');
+  }
+}
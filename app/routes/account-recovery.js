@@ -0,0 +1,28 @@
+import Route from '@ember/routing/route';
+import { service } from '@ember/service';
+
+import ajax from '../utils/ajax';
+
+export default class AccountRecoveryRoute extends Route {
+  @service notifications;
+  @service router;
+
+  async model(params) {
+    try {
+      await ajax(`/api/private/account_recovery/${params.token}`, { method: 'PUT', body: '{}' });
+
+      this.notifications.success(
+        'Your account recovery was confirmed. Please log in with the GitHub account that you want to use for crates.io from now on.',
+      );
+    } catch (error) {
+      let detail = error.errors?.[0]?.detail;
+      if (detail && !detail.startsWith('{')) {
+        this.notifications.error(`Error in account recovery: ${detail}`);
+      } else {
+        this.notifications.error(`Unknown error in account recovery`);
+      }
+    }
+
+    this.router.replaceWith('index');
+  }
+}
@@ -2,6 +2,7 @@ const DESCRIPTIONS = {
   'change-owners': 'Invite new crate owners or remove existing ones',
   'publish-new': 'Publish new crates',
   'publish-update': 'Publish new versions of existing crates',
+  'token-exchange': 'Create short-lived tokens with a subset of the other scopes of this token',
   'trusted-publishing': 'Manage trusted publishing configurations',
   yank: 'Yank and unyank crate versions',
 };
@@ -8,8 +8,11 @@ pub use self::krate_publish::{EncodableCrateDependency, PublishMetadata};
 
 use chrono::{DateTime, Utc};
 use crates_io_database::models::{
-    ApiToken, Category, Crate, Dependency, DependencyKind, Keyword, Owner, ReverseDependency, Team,
-    TopVersions, TrustpubData, User, Version, VersionDownload, VersionOwnerAction,
+    ApiToken, Category, CategoryProposal, CategoryProposalStatus, Crate, CrateStatus,
+    CrateTransferAction, CrateTransferRequest, CrateTransferRequestEvent, CrateTransferStatus,
+    Dependency, DependencyKind, DocsRsRebuildRequest, DocsRsRebuildStatus, DocsRsStatus, Keyword,
+    Owner, ReverseDependency, ScheduledCrateDeletion, Team, TopVersions, TrustpubData, User,
+    Version, VersionDownload, VersionOwnerAction,
 };
 use serde::{Deserialize, Serialize};
 
@@ -82,6 +85,72 @@ impl From<Category> for EncodableCategory {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, utoipa::ToSchema)]
+#[schema(as = CategoryProposal)]
+pub struct EncodableCategoryProposal {
+    /// An opaque identifier for the proposal.
+    #[schema(example = 42)]
+    pub id: i32,
+
+    /// The "slug" of the proposed category.
+    ///
+    /// Subcategories are separated from their parent category by `::`.
+    #[schema(example = "game-development::physics")]
+    pub slug: String,
+
+    /// The name of the proposed category, without the name of its parent
+    /// category.
+    #[schema(example = "Physics")]
+    pub name: String,
+
+    /// A description of the proposed category.
+    #[schema(example = "Physics engines for games.")]
+    pub description: String,
+
+    /// The user who proposed the category, if the account still exists.
+    pub proposed_by: Option<EncodablePublicUser>,
+
+    /// The current state of the proposal.
+    ///
+    /// One of `pending`, `approved` or `rejected`.
+    #[schema(value_type = String, example = "pending")]
+    pub status: CategoryProposalStatus,
+
+    /// The date and time the category was proposed.
+    #[schema(example = "2019-12-13T13:46:41Z")]
+    pub created_at: DateTime<Utc>,
+
+    /// The date and time the proposal was approved or rejected.
+    #[schema(example = json!(null))]
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+impl EncodableCategoryProposal {
+    pub fn from(proposal: CategoryProposal, proposed_by: Option<User>) -> Self {
+        let CategoryProposal {
+            id,
+            slug,
+            name,
+            description,
+            status,
+            created_at,
+            resolved_at,
+            ..
+        } = proposal;
+
+        Self {
+            id,
+            slug,
+            name,
+            description,
+            proposed_by: proposed_by.map(Into::into),
+            status,
+            created_at,
+            resolved_at,
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, PartialEq, Eq, utoipa::ToSchema)]
 #[schema(as = LegacyCrateOwnerInvitation)]
 pub struct EncodableCrateOwnerInvitationV1 {
@@ -180,6 +249,14 @@ pub struct EncodableDependency {
     /// The total number of downloads for the crate this dependency points to.
     #[schema(example = 123_456)]
     pub downloads: i64,
+
+    /// Whether the version requirement matches the default version of the
+    /// depended-on crate.
+    ///
+    /// This is only included for reverse dependencies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = true)]
+    pub matches_default_version: Option<bool>,
 }
 
 impl EncodableDependency {
@@ -205,6 +282,7 @@ impl EncodableDependency {
             target: dependency.target,
             kind: dependency.kind,
             downloads: downloads.unwrap_or(0),
+            matches_default_version: None,
         }
     }
 }
@@ -364,6 +442,21 @@ pub struct EncodableCrate {
 
     /// Whether this crate can only be published via Trusted Publishing.
     pub trustpub_only: bool,
+
+    /// Whether new versions of this crate have to be approved by a second
+    /// owner before they are published.
+    pub requires_publish_approval: bool,
+
+    /// The maintenance status of the crate.
+    ///
+    /// One of `active`, `passively_maintained`, `deprecated` or `archived`.
+    #[schema(value_type = String, example = "active")]
+    pub status: CrateStatus,
+
+    /// The name of the crate that replaces this crate, if it is deprecated
+    /// or archived.
+    #[schema(example = json!(null))]
+    pub successor: Option<String>,
 }
 
 impl EncodableCrate {
@@ -390,6 +483,9 @@ impl EncodableCrate {
             documentation,
             repository,
             trustpub_only,
+            requires_publish_approval,
+            status,
+            successor,
             ..
         } = krate;
         let versions_link = match versions {
@@ -456,6 +552,9 @@ impl EncodableCrate {
             description,
             repository,
             trustpub_only,
+            requires_publish_approval,
+            status,
+            successor,
             links: EncodableCrateLinks {
                 version_downloads: format!("/api/v1/crates/{name}/downloads"),
                 versions: versions_link,
@@ -811,6 +910,197 @@ pub struct EncodableAuditAction {
     pub time: DateTime<Utc>,
 }
 
+#[derive(Serialize, Deserialize, Debug, utoipa::ToSchema)]
+#[schema(as = DocsRsRebuildRequest)]
+pub struct EncodableDocsRsRebuildRequest {
+    /// An opaque identifier for the rebuild request.
+    #[schema(example = 42)]
+    pub id: i64,
+
+    /// The user who requested the rebuild, if they still exist.
+    pub requested_by: Option<EncodablePublicUser>,
+
+    /// The date and time the rebuild was requested.
+    #[schema(example = "2019-12-13T13:46:41Z")]
+    pub requested_at: DateTime<Utc>,
+
+    /// The current state of the rebuild request.
+    ///
+    /// One of `pending`, `queued`, `rate_limited`, `rejected` or `failed`.
+    #[schema(value_type = String, example = "queued")]
+    pub status: DocsRsRebuildStatus,
+
+    /// The number of times the request was sent to docs.rs.
+    #[schema(example = 1)]
+    pub attempts: i32,
+
+    /// The date and time the request was last sent to docs.rs.
+    #[schema(example = "2019-12-13T13:46:41Z")]
+    pub last_attempt_at: Option<DateTime<Utc>>,
+
+    /// The date and time after which a rate limited request will be retried.
+    #[schema(example = json!(null))]
+    pub next_attempt_at: Option<DateTime<Utc>>,
+
+    /// The error message returned by docs.rs, if any.
+    #[schema(example = json!(null))]
+    pub response: Option<String>,
+}
+
+impl EncodableDocsRsRebuildRequest {
+    pub fn from(request: DocsRsRebuildRequest, requested_by: Option<User>) -> Self {
+        let DocsRsRebuildRequest {
+            id,
+            requested_at,
+            status,
+            attempts,
+            last_attempt_at,
+            next_attempt_at,
+            response,
+            ..
+        } = request;
+
+        Self {
+            id,
+            requested_by: requested_by.map(User::into),
+            requested_at,
+            status,
+            attempts,
+            last_attempt_at,
+            next_attempt_at,
+            response,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, utoipa::ToSchema)]
+#[schema(as = CrateTransferRequest)]
+pub struct EncodableCrateTransferRequest {
+    /// An opaque identifier for the transfer request.
+    #[schema(example = 42)]
+    pub id: i32,
+
+    /// The user who wants to become an owner of the crate.
+    pub requester: EncodablePublicUser,
+
+    /// The explanation of the requester why the crate should be
+    /// transferred to them.
+    #[schema(
+        example = "The crate has not been updated in five years and I would like to maintain it."
+    )]
+    pub reason: String,
+
+    /// The current state of the transfer request.
+    ///
+    /// One of `pending`, `accepted`, `rejected`, `withdrawn`, `approved`
+    /// or `denied`.
+    #[schema(value_type = String, example = "pending")]
+    pub status: CrateTransferStatus,
+
+    /// The date and time the request was filed.
+    #[schema(example = "2019-12-13T13:46:41Z")]
+    pub created_at: DateTime<Utc>,
+
+    /// The date and time the request was resolved, if it is not pending
+    /// anymore.
+    #[schema(example = json!(null))]
+    pub resolved_at: Option<DateTime<Utc>>,
+
+    /// All actions that were performed on the request, oldest first.
+    pub events: Vec<EncodableCrateTransferRequestEvent>,
+}
+
+impl EncodableCrateTransferRequest {
+    pub fn from(
+        request: CrateTransferRequest,
+        requester: User,
+        events: Vec<EncodableCrateTransferRequestEvent>,
+    ) -> Self {
+        let CrateTransferRequest {
+            id,
+            reason,
+            status,
+            created_at,
+            resolved_at,
+            ..
+        } = request;
+
+        Self {
+            id,
+            requester: requester.into(),
+            reason,
+            status,
+            created_at,
+            resolved_at,
+            events,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, utoipa::ToSchema)]
+#[schema(as = CrateTransferRequestEvent)]
+pub struct EncodableCrateTransferRequestEvent {
+    /// The action that was performed.
+    ///
+    /// One of `created`, `accepted`, `rejected`, `withdrawn`, `approved`
+    /// or `denied`.
+    #[schema(value_type = String, example = "created")]
+    pub action: CrateTransferAction,
+
+    /// The user who performed the action, if they still exist.
+    pub actor: Option<EncodablePublicUser>,
+
+    /// An optional note explaining the action.
+    #[schema(example = json!(null))]
+    pub note: Option<String>,
+
+    /// The date and time the action was performed.
+    #[schema(example = "2019-12-13T13:46:41Z")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl EncodableCrateTransferRequestEvent {
+    pub fn from(event: CrateTransferRequestEvent, actor: Option<User>) -> Self {
+        Self {
+            action: event.action,
+            actor: actor.map(User::into),
+            note: event.note,
+            created_at: event.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, utoipa::ToSchema)]
+#[schema(as = ScheduledCrateDeletion)]
+pub struct EncodableScheduledCrateDeletion {
+    /// The user who requested the deletion, if they still exist.
+    pub requested_by: Option<EncodablePublicUser>,
+
+    /// The date and time the deletion was requested.
+    #[schema(example = "2019-12-13T13:46:41Z")]
+    pub requested_at: DateTime<Utc>,
+
+    /// The date and time the crate will be deleted permanently, unless the
+    /// deletion is cancelled before.
+    #[schema(example = "2019-12-14T13:46:41Z")]
+    pub scheduled_for: DateTime<Utc>,
+
+    /// The message left by the user who requested the deletion.
+    #[schema(example = json!(null))]
+    pub message: Option<String>,
+}
+
+impl EncodableScheduledCrateDeletion {
+    pub fn from(deletion: ScheduledCrateDeletion, requested_by: Option<User>) -> Self {
+        Self {
+            requested_by: requested_by.map(User::into),
+            requested_at: deletion.requested_at,
+            scheduled_for: deletion.scheduled_for,
+            message: deletion.message,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, utoipa::ToSchema)]
 #[schema(as = Version)]
 pub struct EncodableVersion {
@@ -934,14 +1224,29 @@ pub struct EncodableVersion {
     #[schema(value_type = Option<Object>)]
     pub trustpub_data: Option<TrustpubData>,
 
-    /// Line count statistics for this version.
+    /// Line count and composition statistics for this version.
     ///
     /// Status: **Unstable**
     ///
+    /// Besides the per-language line counts of the main source code, this
+    /// includes separate totals for tests, examples, benches, the build
+    /// script and vendored C/C++ sources, the number of `unsafe` blocks,
+    /// and whether the version has a build script or is a proc-macro.
+    ///
     /// This field may be `null` until the version has been analyzed, which
     /// happens in an asynchronous background job.
     #[schema(value_type = Object)]
     pub linecounts: Option<serde_json::Value>,
+
+    /// The status of the documentation build of this version on docs.rs.
+    ///
+    /// Status: **Unstable**
+    ///
+    /// One of `success`, `failure` or `queued`. This field may be `null`
+    /// until the status has been fetched from docs.rs, which happens in an
+    /// asynchronous background job.
+    #[schema(value_type = Option<String>, example = "success")]
+    pub docs_rs_status: Option<DocsRsStatus>,
 }
 
 impl EncodableVersion {
@@ -974,6 +1279,7 @@ impl EncodableVersion {
             repository,
             trustpub_data,
             linecounts,
+            docs_rs_status,
             ..
         } = version;
 
@@ -1014,6 +1320,7 @@ impl EncodableVersion {
             repository,
             trustpub_data,
             linecounts,
+            docs_rs_status,
             published_by: published_by.map(User::into),
             audit_actions: audit_actions
                 .into_iter()
@@ -1159,6 +1466,7 @@ mod tests {
             }],
             trustpub_data: None,
             linecounts: None,
+            docs_rs_status: None,
         };
         let json = serde_json::to_string(&ver).unwrap();
         assert_some!(json.as_str().find(r#""updated_at":"2017-01-06T14:23:11Z""#));
@@ -1207,6 +1515,9 @@ mod tests {
             },
             exact_match: false,
             trustpub_only: false,
+            requires_publish_approval: false,
+            status: CrateStatus::Active,
+            successor: None,
         };
         let json = serde_json::to_string(&crt).unwrap();
         assert_some!(json.as_str().find(r#""updated_at":"2017-01-06T14:23:11Z""#));
@@ -0,0 +1,108 @@
+use crate::schema::{account_lock_events, users};
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use crates_io_diesel_helpers::pg_enum;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+
+pg_enum! {
+    /// An action that locked or unlocked a user account.
+    ///
+    /// - `Locked`: an admin locked the account.
+    /// - `Unlocked`: an admin unlocked the account.
+    /// - `SelfLocked`: the account owner locked their own account, e.g.
+    ///   because they suspect that it was compromised.
+    /// - `Recovered`: the account owner unlocked their own account via the
+    ///   account recovery flow.
+    pub enum AccountLockAction {
+        Locked = 0,
+        Unlocked = 1,
+        SelfLocked = 2,
+        Recovered = 3,
+    }
+}
+
+impl AccountLockAction {
+    /// Returns `true` if the action locks the account.
+    pub fn is_lock(self) -> bool {
+        matches!(self, Self::Locked | Self::SelfLocked)
+    }
+}
+
+/// The model representing a row in the `account_lock_events` database
+/// table.
+#[derive(Debug, Clone, Identifiable, HasQuery)]
+pub struct AccountLockEvent {
+    pub id: i64,
+    pub user_id: i32,
+    pub action: AccountLockAction,
+    pub actor_id: Option<i32>,
+    pub reason: String,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AccountLockEvent {
+    /// Loads all events of the given user, oldest first.
+    pub async fn for_user(conn: &mut AsyncPgConnection, user_id: i32) -> QueryResult<Vec<Self>> {
+        Self::query()
+            .filter(account_lock_events::user_id.eq(user_id))
+            .order(account_lock_events::id.asc())
+            .load(conn)
+            .await
+    }
+
+    /// Loads the most recent event of the given user.
+    pub async fn latest(conn: &mut AsyncPgConnection, user_id: i32) -> QueryResult<Option<Self>> {
+        Self::query()
+            .filter(account_lock_events::user_id.eq(user_id))
+            .order(account_lock_events::id.desc())
+            .first(conn)
+            .await
+            .optional()
+    }
+}
+
+#[derive(Debug, Insertable, Builder)]
+#[diesel(table_name = account_lock_events, check_for_backend(diesel::pg::Pg))]
+pub struct NewAccountLockEvent<'a> {
+    pub user_id: i32,
+    pub action: AccountLockAction,
+    pub actor_id: Option<i32>,
+    pub reason: &'a str,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+impl NewAccountLockEvent<'_> {
+    /// Locks or unlocks the account of the user, and records the action in
+    /// the event log.
+    ///
+    /// The lock reason of the account is set to the reason of the event, so
+    /// that it is shown to the user in the error messages.
+    pub async fn apply(&self, conn: &mut AsyncPgConnection) -> QueryResult<AccountLockEvent> {
+        conn.transaction(|conn| {
+            async move {
+                let (reason, until) = match self.action.is_lock() {
+                    true => (Some(self.reason), self.locked_until),
+                    false => (None, None),
+                };
+
+                diesel::update(users::table.find(self.user_id))
+                    .set((
+                        users::account_lock_reason.eq(reason),
+                        users::account_lock_until.eq(until),
+                    ))
+                    .execute(conn)
+                    .await?;
+
+                self.insert_into(account_lock_events::table)
+                    .returning(AccountLockEvent::as_returning())
+                    .get_result(conn)
+                    .await
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+}
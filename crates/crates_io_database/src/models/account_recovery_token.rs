@@ -0,0 +1,91 @@
+use crate::schema::account_recovery_tokens;
+use crate::utils::token::{HashedToken, PlainToken};
+use chrono::{DateTime, Utc};
+use diesel::dsl::{exists, now};
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+
+/// The secret token that is sent to the verified email address of a user
+/// to recover their account.
+///
+/// Every user has at most one recovery token. Requesting a new token
+/// replaces the previous one, and tokens can only be used once.
+#[derive(Debug, Identifiable, HasQuery)]
+pub struct AccountRecoveryToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl AccountRecoveryToken {
+    /// Generates a new recovery token for the given user, replacing the
+    /// previous token if there is one.
+    ///
+    /// The plaintext token is only returned here, only its hash is saved in
+    /// the database.
+    pub async fn generate(
+        conn: &mut AsyncPgConnection,
+        user_id: i32,
+        expires_at: DateTime<Utc>,
+    ) -> QueryResult<(Self, PlainToken)> {
+        let plaintext = PlainToken::generate();
+
+        let token = conn
+            .transaction(|conn| {
+                let hashed = plaintext.hashed();
+                async move {
+                    diesel::delete(account_recovery_tokens::table)
+                        .filter(account_recovery_tokens::user_id.eq(user_id))
+                        .execute(conn)
+                        .await?;
+
+                    diesel::insert_into(account_recovery_tokens::table)
+                        .values((
+                            account_recovery_tokens::user_id.eq(user_id),
+                            account_recovery_tokens::token.eq(hashed),
+                            account_recovery_tokens::expires_at.eq(expires_at),
+                        ))
+                        .returning(Self::as_returning())
+                        .get_result(conn)
+                        .await
+                }
+                .scope_boxed()
+            })
+            .await?;
+
+        Ok((token, plaintext))
+    }
+
+    /// Returns `true` if a recovery token was generated for the given user
+    /// after the given date and time.
+    pub async fn exists_since(
+        conn: &mut AsyncPgConnection,
+        user_id: i32,
+        since: DateTime<Utc>,
+    ) -> QueryResult<bool> {
+        diesel::select(exists(
+            account_recovery_tokens::table
+                .filter(account_recovery_tokens::user_id.eq(user_id))
+                .filter(account_recovery_tokens::created_at.gt(since)),
+        ))
+        .get_result(conn)
+        .await
+    }
+
+    /// Looks up the unexpired recovery token with the given hash, and
+    /// deletes it so that it can't be used again.
+    pub async fn consume(
+        conn: &mut AsyncPgConnection,
+        token: &HashedToken,
+    ) -> QueryResult<Option<Self>> {
+        diesel::delete(account_recovery_tokens::table)
+            .filter(account_recovery_tokens::token.eq(token))
+            .filter(account_recovery_tokens::expires_at.gt(now))
+            .returning(Self::as_returning())
+            .get_result(conn)
+            .await
+            .optional()
+    }
+}
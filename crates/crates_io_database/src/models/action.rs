@@ -13,6 +13,7 @@ pg_enum! {
         Publish = 0,
         Yank = 1,
         Unyank = 2,
+        Approve = 3,
     }
 }
 
@@ -22,6 +23,7 @@ impl From<VersionAction> for &'static str {
             VersionAction::Publish => "publish",
             VersionAction::Yank => "yank",
             VersionAction::Unyank => "unyank",
+            VersionAction::Approve => "approve",
         }
     }
 }
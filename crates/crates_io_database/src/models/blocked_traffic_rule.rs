@@ -0,0 +1,74 @@
+use crate::schema::blocked_traffic_rules;
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use diesel::dsl::now;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use ipnetwork::IpNetwork;
+
+/// A rule for blocking incoming requests.
+///
+/// A request is blocked if it matches all conditions of the rule that are
+/// set. At least one condition is always set.
+#[derive(Debug, Clone, Identifiable, HasQuery)]
+pub struct BlockedTrafficRule {
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub ip_range: Option<IpNetwork>,
+    pub header_name: Option<String>,
+    pub header_pattern: Option<String>,
+    pub route_pattern: Option<String>,
+    pub message: Option<String>,
+    pub reason: String,
+}
+
+impl BlockedTrafficRule {
+    /// Loads all rules that have not expired yet.
+    pub async fn load_active(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Self>> {
+        Self::query()
+            .filter(
+                blocked_traffic_rules::expires_at
+                    .is_null()
+                    .or(blocked_traffic_rules::expires_at.gt(now)),
+            )
+            .order(blocked_traffic_rules::id)
+            .load(conn)
+            .await
+    }
+
+    /// Loads all rules, including the expired ones.
+    pub async fn load_all(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Self>> {
+        Self::query()
+            .order(blocked_traffic_rules::id)
+            .load(conn)
+            .await
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now())
+    }
+}
+
+#[derive(Debug, Insertable, Builder)]
+#[diesel(table_name = blocked_traffic_rules, check_for_backend(diesel::pg::Pg))]
+pub struct NewBlockedTrafficRule<'a> {
+    pub expires_at: Option<DateTime<Utc>>,
+    pub ip_range: Option<IpNetwork>,
+    pub header_name: Option<&'a str>,
+    pub header_pattern: Option<&'a str>,
+    pub route_pattern: Option<&'a str>,
+    pub message: Option<&'a str>,
+    pub reason: &'a str,
+}
+
+impl NewBlockedTrafficRule<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<BlockedTrafficRule> {
+        diesel::insert_into(blocked_traffic_rules::table)
+            .values(self)
+            .returning(BlockedTrafficRule::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
@@ -148,8 +148,9 @@ impl Category {
     }
 }
 
-/// Struct for inserting categories; only used in tests. Actual categories are inserted
-/// in src/boot/categories.rs.
+/// Struct for inserting categories; used in tests and for approved category
+/// proposals. The categories from `categories.toml` are inserted in
+/// src/boot/categories.rs.
 #[derive(Insertable, AsChangeset, Default, Debug)]
 #[diesel(table_name = categories, check_for_backend(diesel::pg::Pg))]
 pub struct NewCategory<'a> {
@@ -0,0 +1,203 @@
+use crate::models::{Category, NewCategory};
+use crate::schema::{categories, category_proposals};
+use chrono::{DateTime, Utc};
+use crates_io_diesel_helpers::pg_enum;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+
+pg_enum! {
+    /// The status of a category proposal.
+    ///
+    /// - `Pending`: the proposal is waiting for a review by the crates.io
+    ///   team.
+    /// - `Approved`: the category was added to the `categories` table.
+    /// - `Rejected`: the crates.io team rejected the proposal.
+    pub enum CategoryProposalStatus {
+        Pending = 0,
+        Approved = 1,
+        Rejected = 2,
+    }
+}
+
+/// The model representing a row in the `category_proposals` database table.
+#[derive(Debug, Clone, Identifiable, HasQuery)]
+#[diesel(table_name = category_proposals)]
+pub struct CategoryProposal {
+    pub id: i32,
+    pub slug: String,
+    pub name: String,
+    pub description: String,
+    pub proposed_by: Option<i32>,
+    pub status: CategoryProposalStatus,
+    pub created_at: DateTime<Utc>,
+    pub resolved_by: Option<i32>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+impl CategoryProposal {
+    pub async fn find(conn: &mut AsyncPgConnection, id: i32) -> QueryResult<Self> {
+        Self::query().find(id).first(conn).await
+    }
+
+    /// Loads all proposals, newest first.
+    pub async fn all(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Self>> {
+        Self::query()
+            .order(category_proposals::id.desc())
+            .load(conn)
+            .await
+    }
+
+    /// Loads all proposals of the given user, newest first.
+    pub async fn for_user(conn: &mut AsyncPgConnection, user_id: i32) -> QueryResult<Vec<Self>> {
+        Self::query()
+            .filter(category_proposals::proposed_by.eq(user_id))
+            .order(category_proposals::id.desc())
+            .load(conn)
+            .await
+    }
+
+    /// Returns `true` if the slug consists of one or more `::` separated
+    /// segments of lowercase ASCII letters, digits and dashes.
+    pub fn valid_slug(slug: &str) -> bool {
+        slug.split("::").all(|segment| {
+            let mut chars = segment.chars();
+            chars
+                .next()
+                .is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+                && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        })
+    }
+
+    /// Returns the slug of the parent category, if the proposed category is
+    /// a subcategory.
+    pub fn parent_slug(&self) -> Option<&str> {
+        self.slug.rsplit_once("::").map(|(parent, _)| parent)
+    }
+
+    /// Approves the pending proposal and adds the category to the
+    /// `categories` table.
+    ///
+    /// The name of a subcategory is prefixed with the name of its parent
+    /// category, the same way as for the categories in `categories.toml`.
+    ///
+    /// Returns `None` if the proposal is not pending anymore.
+    pub async fn approve(
+        &self,
+        conn: &mut AsyncPgConnection,
+        admin_id: i32,
+    ) -> QueryResult<Option<Category>> {
+        conn.transaction(|conn| {
+            async move {
+                if !self
+                    .resolve(conn, CategoryProposalStatus::Approved, admin_id)
+                    .await?
+                {
+                    return Ok(None);
+                }
+
+                let name = match self.parent_slug() {
+                    Some(parent_slug) => {
+                        let parent: String = Category::by_slug(parent_slug)
+                            .select(categories::category)
+                            .first(conn)
+                            .await?;
+
+                        format!("{parent}::{}", self.name)
+                    }
+                    None => self.name.clone(),
+                };
+
+                let new_category = NewCategory {
+                    category: &name,
+                    slug: &self.slug,
+                    description: &self.description,
+                };
+
+                let category = diesel::insert_into(categories::table)
+                    .values(&new_category)
+                    .returning(Category::as_returning())
+                    .get_result(conn)
+                    .await?;
+
+                Ok(Some(category))
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
+    /// Rejects the pending proposal.
+    ///
+    /// Returns `false` if the proposal is not pending anymore.
+    pub async fn reject(&self, conn: &mut AsyncPgConnection, admin_id: i32) -> QueryResult<bool> {
+        self.resolve(conn, CategoryProposalStatus::Rejected, admin_id)
+            .await
+    }
+
+    async fn resolve(
+        &self,
+        conn: &mut AsyncPgConnection,
+        status: CategoryProposalStatus,
+        admin_id: i32,
+    ) -> QueryResult<bool> {
+        let updated = diesel::update(self)
+            .filter(category_proposals::status.eq(CategoryProposalStatus::Pending))
+            .set((
+                category_proposals::status.eq(status),
+                category_proposals::resolved_by.eq(admin_id),
+                category_proposals::resolved_at.eq(Utc::now()),
+            ))
+            .execute(conn)
+            .await?;
+
+        Ok(updated > 0)
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = category_proposals, check_for_backend(diesel::pg::Pg))]
+pub struct NewCategoryProposal<'a> {
+    pub slug: &'a str,
+    pub name: &'a str,
+    pub description: &'a str,
+    pub proposed_by: i32,
+}
+
+impl NewCategoryProposal<'_> {
+    /// Inserts the proposal into the database.
+    ///
+    /// Returns `None` if there is already a pending proposal for the slug.
+    pub async fn insert(
+        &self,
+        conn: &mut AsyncPgConnection,
+    ) -> QueryResult<Option<CategoryProposal>> {
+        self.insert_into(category_proposals::table)
+            .on_conflict_do_nothing()
+            .returning(CategoryProposal::as_returning())
+            .get_result(conn)
+            .await
+            .optional()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_slug() {
+        assert!(CategoryProposal::valid_slug("algorithms"));
+        assert!(CategoryProposal::valid_slug("web-programming"));
+        assert!(CategoryProposal::valid_slug("web-programming::http-client"));
+        assert!(CategoryProposal::valid_slug("3d"));
+
+        assert!(!CategoryProposal::valid_slug(""));
+        assert!(!CategoryProposal::valid_slug("Algorithms"));
+        assert!(!CategoryProposal::valid_slug("-algorithms"));
+        assert!(!CategoryProposal::valid_slug("web programming"));
+        assert!(!CategoryProposal::valid_slug("web-programming::"));
+        assert!(!CategoryProposal::valid_slug("::http-client"));
+        assert!(!CategoryProposal::valid_slug("web-programming:http-client"));
+    }
+}
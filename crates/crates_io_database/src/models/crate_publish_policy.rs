@@ -0,0 +1,160 @@
+use crate::models::Crate;
+use crate::schema::{crate_publish_policies, crates};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// Restrictions on who can publish new versions of a crate, and how.
+///
+/// Crates without a policy can be published by all of their owners, using
+/// any API token with the required scopes or any matching Trusted Publishing
+/// config.
+#[derive(Debug, Clone, Identifiable, HasQuery)]
+#[diesel(table_name = crate_publish_policies, primary_key(crate_id))]
+pub struct CratePublishPolicy {
+    pub crate_id: i32,
+    /// IDs of the users that are allowed to publish the crate, or an empty
+    /// list if all owners are allowed to publish.
+    pub allowed_publishers: Vec<i32>,
+    /// Whether API tokens need a crate scope naming this crate exactly,
+    /// instead of a wildcard pattern.
+    pub require_crate_scoped_tokens: bool,
+    /// IDs of the GitHub Trusted Publishing configs that are allowed to
+    /// publish the crate.
+    pub trustpub_github_config_ids: Vec<i32>,
+    /// IDs of the GitLab Trusted Publishing configs that are allowed to
+    /// publish the crate.
+    pub trustpub_gitlab_config_ids: Vec<i32>,
+    /// Maximum age of API tokens in days.
+    pub max_token_age_days: Option<i32>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl CratePublishPolicy {
+    /// Finds the publish policy of the given crate, if there is one.
+    pub async fn find(conn: &mut AsyncPgConnection, crate_id: i32) -> QueryResult<Option<Self>> {
+        Self::query().find(crate_id).first(conn).await.optional()
+    }
+
+    /// Finds the publish policy of the crate with the given name, if there
+    /// is one.
+    pub async fn find_by_crate_name(
+        conn: &mut AsyncPgConnection,
+        name: &str,
+    ) -> QueryResult<Option<Self>> {
+        let crate_id = Crate::by_name(name).select(crates::id).single_value();
+
+        Self::query()
+            .filter(crate_publish_policies::crate_id.nullable().eq(crate_id))
+            .first(conn)
+            .await
+            .optional()
+    }
+
+    /// Removes the publish policy of the given crate.
+    pub async fn delete(conn: &mut AsyncPgConnection, crate_id: i32) -> QueryResult<()> {
+        diesel::delete(crate_publish_policies::table.find(crate_id))
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Returns `true` if the crate can only be published via Trusted
+    /// Publishing.
+    pub fn requires_trusted_publishing(&self) -> bool {
+        !self.trustpub_github_config_ids.is_empty() || !self.trustpub_gitlab_config_ids.is_empty()
+    }
+
+    /// Returns `true` if the given user is allowed to publish the crate,
+    /// assuming that they are an owner of it.
+    pub fn allows_publisher(&self, user_id: i32) -> bool {
+        self.allowed_publishers.is_empty() || self.allowed_publishers.contains(&user_id)
+    }
+
+    /// Returns `true` if a Trusted Publishing token that was issued for the
+    /// given configs is allowed to publish the crate.
+    pub fn allows_trustpub_configs(&self, github_ids: &[i32], gitlab_ids: &[i32]) -> bool {
+        if !self.requires_trusted_publishing() {
+            return true;
+        }
+
+        github_ids
+            .iter()
+            .any(|id| self.trustpub_github_config_ids.contains(id))
+            || gitlab_ids
+                .iter()
+                .any(|id| self.trustpub_gitlab_config_ids.contains(id))
+    }
+}
+
+#[derive(Debug, Insertable, AsChangeset)]
+#[diesel(table_name = crate_publish_policies, check_for_backend(diesel::pg::Pg))]
+#[diesel(treat_none_as_null = true)]
+pub struct NewCratePublishPolicy<'a> {
+    pub crate_id: i32,
+    pub allowed_publishers: &'a [i32],
+    pub require_crate_scoped_tokens: bool,
+    pub trustpub_github_config_ids: &'a [i32],
+    pub trustpub_gitlab_config_ids: &'a [i32],
+    pub max_token_age_days: Option<i32>,
+}
+
+impl NewCratePublishPolicy<'_> {
+    /// Returns `true` if the policy does not restrict publishing at all.
+    pub fn is_empty(&self) -> bool {
+        self.allowed_publishers.is_empty()
+            && !self.require_crate_scoped_tokens
+            && self.trustpub_github_config_ids.is_empty()
+            && self.trustpub_gitlab_config_ids.is_empty()
+            && self.max_token_age_days.is_none()
+    }
+
+    /// Returns `true` if the policy allows publishing in any way that the
+    /// given existing policy does not allow.
+    pub fn is_weaker_than(&self, existing: &CratePublishPolicy) -> bool {
+        // An empty list does not restrict anything, so adding any entries to
+        // an empty list makes the policy stronger, not weaker.
+        fn widens(existing: &[i32], new: &[i32]) -> bool {
+            !existing.is_empty() && (new.is_empty() || new.iter().any(|id| !existing.contains(id)))
+        }
+
+        let existing_configs =
+            existing.trustpub_github_config_ids.len() + existing.trustpub_gitlab_config_ids.len();
+        let new_configs =
+            self.trustpub_github_config_ids.len() + self.trustpub_gitlab_config_ids.len();
+
+        let widens_configs = existing_configs > 0
+            && (new_configs == 0
+                || self
+                    .trustpub_github_config_ids
+                    .iter()
+                    .any(|id| !existing.trustpub_github_config_ids.contains(id))
+                || self
+                    .trustpub_gitlab_config_ids
+                    .iter()
+                    .any(|id| !existing.trustpub_gitlab_config_ids.contains(id)));
+
+        let widens_token_age = match (existing.max_token_age_days, self.max_token_age_days) {
+            (Some(existing), Some(new)) => new > existing,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+
+        widens(&existing.allowed_publishers, self.allowed_publishers)
+            || (existing.require_crate_scoped_tokens && !self.require_crate_scoped_tokens)
+            || widens_configs
+            || widens_token_age
+    }
+
+    /// Inserts the policy, or replaces the existing policy of the crate.
+    pub async fn upsert(&self, conn: &mut AsyncPgConnection) -> QueryResult<CratePublishPolicy> {
+        self.insert_into(crate_publish_policies::table)
+            .on_conflict(crate_publish_policies::crate_id)
+            .do_update()
+            .set((self, crate_publish_policies::updated_at.eq(Utc::now())))
+            .returning(CratePublishPolicy::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
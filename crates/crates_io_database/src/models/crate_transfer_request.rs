@@ -0,0 +1,294 @@
+use crate::models::CrateOwner;
+use crate::schema::{
+    crate_owner_invitations, crate_owners, crate_publish_policies, crate_transfer_request_events,
+    crate_transfer_requests, trustpub_configs_github, trustpub_configs_gitlab, trustpub_tokens,
+};
+use chrono::{DateTime, Utc};
+use crates_io_diesel_helpers::pg_enum;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+
+pg_enum! {
+    /// The status of a crate transfer request.
+    ///
+    /// - `Pending`: the request is waiting for a response by the owners or
+    ///   the crates.io team.
+    /// - `Accepted`: the owners added the requester as an owner.
+    /// - `Rejected`: the owners rejected the request.
+    /// - `Withdrawn`: the requester withdrew the request.
+    /// - `Approved`: the crates.io team transferred the crate to the
+    ///   requester.
+    /// - `Denied`: the crates.io team denied the request.
+    pub enum CrateTransferStatus {
+        Pending = 0,
+        Accepted = 1,
+        Rejected = 2,
+        Withdrawn = 3,
+        Approved = 4,
+        Denied = 5,
+    }
+}
+
+pg_enum! {
+    /// An action that was performed on a crate transfer request.
+    ///
+    /// Except for `Created`, all actions resolve the request and move it
+    /// into the status with the same name.
+    pub enum CrateTransferAction {
+        Created = 0,
+        Accepted = 1,
+        Rejected = 2,
+        Withdrawn = 3,
+        Approved = 4,
+        Denied = 5,
+    }
+}
+
+impl CrateTransferAction {
+    /// Returns the status of a request after the action was performed.
+    pub fn status(self) -> CrateTransferStatus {
+        match self {
+            Self::Created => CrateTransferStatus::Pending,
+            Self::Accepted => CrateTransferStatus::Accepted,
+            Self::Rejected => CrateTransferStatus::Rejected,
+            Self::Withdrawn => CrateTransferStatus::Withdrawn,
+            Self::Approved => CrateTransferStatus::Approved,
+            Self::Denied => CrateTransferStatus::Denied,
+        }
+    }
+}
+
+/// The model representing a row in the `crate_transfer_requests` database
+/// table.
+#[derive(Debug, Clone, Identifiable, HasQuery)]
+#[diesel(table_name = crate_transfer_requests)]
+pub struct CrateTransferRequest {
+    pub id: i32,
+    pub crate_id: i32,
+    pub requester_id: i32,
+    pub reason: String,
+    pub status: CrateTransferStatus,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+impl CrateTransferRequest {
+    pub async fn find(conn: &mut AsyncPgConnection, id: i32) -> QueryResult<Self> {
+        Self::query().find(id).first(conn).await
+    }
+
+    /// Loads all requests for the given crate, newest first.
+    pub async fn for_crate(conn: &mut AsyncPgConnection, crate_id: i32) -> QueryResult<Vec<Self>> {
+        Self::query()
+            .filter(crate_transfer_requests::crate_id.eq(crate_id))
+            .order(crate_transfer_requests::id.desc())
+            .load(conn)
+            .await
+    }
+
+    /// Loads all pending requests, oldest first.
+    pub async fn pending(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Self>> {
+        Self::query()
+            .filter(crate_transfer_requests::status.eq(CrateTransferStatus::Pending))
+            .order(crate_transfer_requests::id.asc())
+            .load(conn)
+            .await
+    }
+
+    /// Resolves the pending request with the given action, and records the
+    /// action in the event log.
+    ///
+    /// If the request is accepted by the owners, the requester is added as
+    /// an additional owner. If the request is approved by the crates.io
+    /// team, the requester replaces all existing owners of the crate, and
+    /// everything else that would allow the previous owners to publish the
+    /// crate is removed as well: pending owner invitations, Trusted
+    /// Publishing configurations and tokens, and the publish policy.
+    ///
+    /// Returns `None` if the request is not pending anymore.
+    pub async fn resolve(
+        &self,
+        conn: &mut AsyncPgConnection,
+        action: CrateTransferAction,
+        actor_id: i32,
+        note: Option<&str>,
+    ) -> QueryResult<Option<CrateTransferRequestEvent>> {
+        debug_assert!(action != CrateTransferAction::Created);
+
+        conn.transaction(|conn| {
+            async move {
+                let updated = diesel::update(self)
+                    .filter(crate_transfer_requests::status.eq(CrateTransferStatus::Pending))
+                    .set((
+                        crate_transfer_requests::status.eq(action.status()),
+                        crate_transfer_requests::resolved_at.eq(Utc::now()),
+                    ))
+                    .execute(conn)
+                    .await?;
+
+                if updated == 0 {
+                    return Ok(None);
+                }
+
+                if action == CrateTransferAction::Approved {
+                    let crate_id = self.crate_id;
+
+                    diesel::update(crate_owners::table)
+                        .filter(crate_owners::crate_id.eq(crate_id))
+                        .set(crate_owners::deleted.eq(true))
+                        .execute(conn)
+                        .await?;
+
+                    diesel::delete(crate_owner_invitations::table)
+                        .filter(crate_owner_invitations::crate_id.eq(crate_id))
+                        .execute(conn)
+                        .await?;
+
+                    diesel::delete(trustpub_configs_github::table)
+                        .filter(trustpub_configs_github::crate_id.eq(crate_id))
+                        .execute(conn)
+                        .await?;
+
+                    diesel::delete(trustpub_configs_gitlab::table)
+                        .filter(trustpub_configs_gitlab::crate_id.eq(crate_id))
+                        .execute(conn)
+                        .await?;
+
+                    diesel::delete(trustpub_tokens::table)
+                        .filter(trustpub_tokens::crate_ids.contains(vec![Some(crate_id)]))
+                        .execute(conn)
+                        .await?;
+
+                    diesel::delete(crate_publish_policies::table)
+                        .filter(crate_publish_policies::crate_id.eq(crate_id))
+                        .execute(conn)
+                        .await?;
+                }
+
+                if matches!(
+                    action,
+                    CrateTransferAction::Accepted | CrateTransferAction::Approved
+                ) {
+                    CrateOwner::builder()
+                        .crate_id(self.crate_id)
+                        .user_id(self.requester_id)
+                        .created_by(actor_id)
+                        .build()
+                        .insert(conn)
+                        .await?;
+                }
+
+                let event = NewCrateTransferRequestEvent {
+                    request_id: self.id,
+                    action,
+                    actor_id: Some(actor_id),
+                    note,
+                }
+                .insert(conn)
+                .await?;
+
+                Ok(Some(event))
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate_transfer_requests, check_for_backend(diesel::pg::Pg))]
+pub struct NewCrateTransferRequest<'a> {
+    pub crate_id: i32,
+    pub requester_id: i32,
+    pub reason: &'a str,
+}
+
+impl NewCrateTransferRequest<'_> {
+    /// Inserts the request and records its creation in the event log.
+    ///
+    /// Returns `None` if the requester already has a pending request for
+    /// the crate.
+    pub async fn create(
+        &self,
+        conn: &mut AsyncPgConnection,
+    ) -> QueryResult<Option<(CrateTransferRequest, CrateTransferRequestEvent)>> {
+        conn.transaction(|conn| {
+            async move {
+                let request = self
+                    .insert_into(crate_transfer_requests::table)
+                    .on_conflict_do_nothing()
+                    .returning(CrateTransferRequest::as_returning())
+                    .get_result(conn)
+                    .await
+                    .optional()?;
+
+                let Some(request) = request else {
+                    return Ok(None);
+                };
+
+                let event = NewCrateTransferRequestEvent {
+                    request_id: request.id,
+                    action: CrateTransferAction::Created,
+                    actor_id: Some(self.requester_id),
+                    note: None,
+                }
+                .insert(conn)
+                .await?;
+
+                Ok(Some((request, event)))
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+}
+
+/// The model representing a row in the `crate_transfer_request_events`
+/// database table.
+#[derive(Debug, Clone, Identifiable, HasQuery)]
+#[diesel(table_name = crate_transfer_request_events)]
+pub struct CrateTransferRequestEvent {
+    pub id: i64,
+    pub request_id: i32,
+    pub action: CrateTransferAction,
+    pub actor_id: Option<i32>,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl CrateTransferRequestEvent {
+    pub async fn find(conn: &mut AsyncPgConnection, id: i64) -> QueryResult<Self> {
+        Self::query().find(id).first(conn).await
+    }
+
+    /// Loads the events of the given requests, oldest first.
+    pub async fn for_requests(
+        conn: &mut AsyncPgConnection,
+        request_ids: &[i32],
+    ) -> QueryResult<Vec<Self>> {
+        Self::query()
+            .filter(crate_transfer_request_events::request_id.eq_any(request_ids))
+            .order(crate_transfer_request_events::id.asc())
+            .load(conn)
+            .await
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate_transfer_request_events, check_for_backend(diesel::pg::Pg))]
+struct NewCrateTransferRequestEvent<'a> {
+    request_id: i32,
+    action: CrateTransferAction,
+    actor_id: Option<i32>,
+    note: Option<&'a str>,
+}
+
+impl NewCrateTransferRequestEvent<'_> {
+    async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<CrateTransferRequestEvent> {
+        self.insert_into(crate_transfer_request_events::table)
+            .returning(CrateTransferRequestEvent::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
@@ -0,0 +1,62 @@
+use crate::schema::database_dumps;
+use chrono::{DateTime, Utc};
+use crates_io_diesel_helpers::pg_enum;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+pg_enum! {
+    /// The kind of a public database dump.
+    ///
+    /// - `Full`: a snapshot of all public data in the database.
+    /// - `Incremental`: the rows that changed since the previous dump,
+    ///   including tombstones for deleted rows.
+    pub enum DatabaseDumpKind {
+        Full = 0,
+        Incremental = 1,
+    }
+}
+
+#[derive(Debug, Clone, Identifiable, HasQuery)]
+#[diesel(table_name = database_dumps)]
+pub struct DatabaseDump {
+    pub id: i32,
+    pub kind: DatabaseDumpKind,
+    pub created_at: DateTime<Utc>,
+    pub since: Option<DateTime<Utc>>,
+    pub base_id: Option<i32>,
+    pub path: String,
+}
+
+impl DatabaseDump {
+    /// Finds the most recent dump of the given kind, if there is one.
+    pub async fn latest(
+        conn: &mut AsyncPgConnection,
+        kind: DatabaseDumpKind,
+    ) -> QueryResult<Option<Self>> {
+        Self::query()
+            .filter(database_dumps::kind.eq(kind))
+            .order(database_dumps::created_at.desc())
+            .first(conn)
+            .await
+            .optional()
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = database_dumps, check_for_backend(diesel::pg::Pg))]
+pub struct NewDatabaseDump<'a> {
+    pub kind: DatabaseDumpKind,
+    pub created_at: DateTime<Utc>,
+    pub since: Option<DateTime<Utc>>,
+    pub base_id: Option<i32>,
+    pub path: &'a str,
+}
+
+impl NewDatabaseDump<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<DatabaseDump> {
+        self.insert_into(database_dumps::table)
+            .returning(DatabaseDump::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
@@ -1,4 +1,4 @@
-use crate::schema::{default_versions, versions};
+use crate::schema::{default_versions, pending_versions, versions};
 use crates_io_diesel_helpers::SemverVersion;
 use diesel::dsl::jsonb_typeof;
 use diesel::prelude::*;
@@ -121,8 +121,12 @@ async fn calculate_default_version(
     conn: &mut AsyncPgConnection,
 ) -> QueryResult<Version> {
     debug!("Loading default version for the crate…");
+    // Versions that are waiting for approval are not published yet.
+    let pending_versions = pending_versions::table.select(pending_versions::version_id);
+
     Version::query()
         .filter(versions::crate_id.eq(crate_id))
+        .filter(versions::id.ne_all(pending_versions))
         .order((
             // 1. Non-yanked first
             versions::yanked,
@@ -34,6 +34,33 @@ pub struct ReverseDependency {
     pub name: String,
 }
 
+/// Filters for the reverse dependencies of a crate.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ReverseDependencyFilter {
+    /// Only include dependents with this kind of dependency on the crate.
+    pub kind: Option<DependencyKind>,
+    /// Only include dependents with (`true`) or without (`false`) an
+    /// optional dependency on the crate.
+    pub optional: Option<bool>,
+}
+
+/// A page of the reverse dependencies of a crate.
+#[derive(Debug)]
+pub struct ReverseDependencies {
+    pub dependencies: Vec<ReverseDependency>,
+    /// The number of dependents across all pages.
+    pub total: i64,
+    /// The number of dependents per version requirement, across all pages.
+    pub requirements: Vec<ReverseDependencyRequirement>,
+}
+
+/// The number of dependents of a crate with a specific version requirement.
+#[derive(Debug)]
+pub struct ReverseDependencyRequirement {
+    pub req: String,
+    pub dependents: i64,
+}
+
 pg_enum! {
     pub enum DependencyKind {
         Normal = 0,
@@ -0,0 +1,74 @@
+use crate::schema::docs_rs_rebuild_requests;
+use chrono::{DateTime, Utc};
+use crates_io_diesel_helpers::pg_enum;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+pg_enum! {
+    /// The outcome of a docs.rs rebuild request.
+    ///
+    /// - `Pending`: the request has not been sent to docs.rs yet.
+    /// - `Queued`: docs.rs accepted the request and queued a rebuild.
+    /// - `RateLimited`: docs.rs rate limited the request. It will be retried
+    ///   after `next_attempt_at`.
+    /// - `Rejected`: docs.rs rejected the request, e.g. because a build for
+    ///   the version is already queued.
+    /// - `Failed`: the request could not be sent to docs.rs.
+    pub enum DocsRsRebuildStatus {
+        Pending = 0,
+        Queued = 1,
+        RateLimited = 2,
+        Rejected = 3,
+        Failed = 4,
+    }
+}
+
+#[derive(Debug, Clone, Identifiable, HasQuery)]
+#[diesel(table_name = docs_rs_rebuild_requests)]
+pub struct DocsRsRebuildRequest {
+    pub id: i64,
+    pub version_id: i32,
+    pub requested_by: Option<i32>,
+    pub requested_at: DateTime<Utc>,
+    pub status: DocsRsRebuildStatus,
+    pub attempts: i32,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub response: Option<String>,
+}
+
+impl DocsRsRebuildRequest {
+    /// Finds a request for the given version that has not reached a final
+    /// state yet, if there is one.
+    pub async fn find_in_flight(
+        conn: &mut AsyncPgConnection,
+        version_id: i32,
+    ) -> QueryResult<Option<Self>> {
+        Self::query()
+            .filter(docs_rs_rebuild_requests::version_id.eq(version_id))
+            .filter(docs_rs_rebuild_requests::status.eq_any([
+                DocsRsRebuildStatus::Pending,
+                DocsRsRebuildStatus::RateLimited,
+            ]))
+            .order(docs_rs_rebuild_requests::requested_at.desc())
+            .first(conn)
+            .await
+            .optional()
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = docs_rs_rebuild_requests, check_for_backend(diesel::pg::Pg))]
+pub struct NewDocsRsRebuildRequest {
+    pub version_id: i32,
+    pub requested_by: Option<i32>,
+}
+
+impl NewDocsRsRebuildRequest {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<DocsRsRebuildRequest> {
+        self.insert_into(docs_rs_rebuild_requests::table)
+            .returning(DocsRsRebuildRequest::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
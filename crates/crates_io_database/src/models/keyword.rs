@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use diesel::prelude::*;
+use diesel::sql_types::Integer;
 use diesel_async::scoped_futures::ScopedFutureExt;
 use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
 
@@ -28,10 +29,30 @@ pub struct CrateKeyword {
     keyword_id: i32,
 }
 
+/// A keyword that was merged into another keyword.
+///
+/// Crates that use the alias as a keyword are associated with the keyword
+/// that it was merged into instead.
+#[derive(Clone, Identifiable, HasQuery, Debug)]
+#[diesel(table_name = keyword_aliases, primary_key(alias))]
+pub struct KeywordAlias {
+    pub alias: String,
+    pub keyword_id: i32,
+    pub created_at: DateTime<Utc>,
+}
+
 impl Keyword {
     pub async fn find_by_keyword(conn: &mut AsyncPgConnection, name: &str) -> QueryResult<Keyword> {
+        let aliased = keyword_aliases::table
+            .filter(keyword_aliases::alias.eq(lower(name)))
+            .select(keyword_aliases::keyword_id);
+
         Keyword::query()
-            .filter(keywords::keyword.eq(lower(name)))
+            .filter(
+                keywords::keyword
+                    .eq(lower(name))
+                    .or(keywords::id.eq_any(aliased)),
+            )
             .first(conn)
             .await
     }
@@ -42,8 +63,17 @@ impl Keyword {
     ) -> QueryResult<Vec<Keyword>> {
         let lowercase_names: Vec<_> = names.iter().map(|s| s.to_lowercase()).collect();
 
+        // Aliases of merged keywords resolve to the keywords that they were
+        // merged into, so they must not be created again.
+        let aliases: Vec<String> = keyword_aliases::table
+            .filter(keyword_aliases::alias.eq_any(&lowercase_names))
+            .select(keyword_aliases::alias)
+            .load(conn)
+            .await?;
+
         let new_keywords: Vec<_> = lowercase_names
             .iter()
+            .filter(|s| !aliases.contains(s))
             .map(|s| keywords::keyword.eq(s))
             .collect();
 
@@ -53,8 +83,16 @@ impl Keyword {
             .execute(conn)
             .await?;
 
+        let aliased = keyword_aliases::table
+            .filter(keyword_aliases::alias.eq_any(&aliases))
+            .select(keyword_aliases::keyword_id);
+
         Keyword::query()
-            .filter(keywords::keyword.eq_any(&lowercase_names))
+            .filter(
+                keywords::keyword
+                    .eq_any(&lowercase_names)
+                    .or(keywords::id.eq_any(aliased)),
+            )
             .load(conn)
             .await
     }
@@ -69,6 +107,68 @@ impl Keyword {
             && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '+')
     }
 
+    /// Merges this keyword into the `target` keyword.
+    ///
+    /// The crates with this keyword are moved over to the target keyword,
+    /// and the database triggers keep the `crates_cnt` of both keywords up
+    /// to date. Afterwards this keyword is deleted, and its name is recorded
+    /// as an alias of the target keyword.
+    pub async fn merge_into(
+        &self,
+        conn: &mut AsyncPgConnection,
+        target: &Keyword,
+    ) -> QueryResult<()> {
+        conn.transaction(|conn| {
+            async move {
+                let target_crate_ids: Vec<i32> = crates_keywords::table
+                    .filter(crates_keywords::keyword_id.eq(target.id))
+                    .select(crates_keywords::crate_id)
+                    .load(conn)
+                    .await?;
+
+                // Crates that already have the target keyword are skipped
+                // explicitly, since the `crates_cnt` trigger runs before an
+                // `ON CONFLICT DO NOTHING` clause would skip them.
+                let crate_ids = crates_keywords::table
+                    .filter(crates_keywords::keyword_id.eq(self.id))
+                    .filter(crates_keywords::crate_id.ne_all(target_crate_ids))
+                    .select((crates_keywords::crate_id, target.id.into_sql::<Integer>()));
+
+                diesel::insert_into(crates_keywords::table)
+                    .values(crate_ids)
+                    .into_columns((crates_keywords::crate_id, crates_keywords::keyword_id))
+                    .execute(conn)
+                    .await?;
+
+                diesel::delete(crates_keywords::table)
+                    .filter(crates_keywords::keyword_id.eq(self.id))
+                    .execute(conn)
+                    .await?;
+
+                // Aliases of this keyword have to follow it into the target
+                diesel::update(keyword_aliases::table)
+                    .filter(keyword_aliases::keyword_id.eq(self.id))
+                    .set(keyword_aliases::keyword_id.eq(target.id))
+                    .execute(conn)
+                    .await?;
+
+                diesel::delete(self).execute(conn).await?;
+
+                diesel::insert_into(keyword_aliases::table)
+                    .values((
+                        keyword_aliases::alias.eq(&self.keyword),
+                        keyword_aliases::keyword_id.eq(target.id),
+                    ))
+                    .execute(conn)
+                    .await?;
+
+                Ok(())
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
     pub async fn update_crate(
         conn: &mut AsyncPgConnection,
         crate_id: i32,
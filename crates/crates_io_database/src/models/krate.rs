@@ -1,14 +1,16 @@
-use crate::models::helpers::with_count::*;
 use crate::models::version::TopVersions;
-use crate::models::{CrateOwner, Owner, OwnerKind, ReverseDependency, User, Version};
+use crate::models::{
+    CrateOwner, Owner, OwnerKind, ReverseDependencies, ReverseDependency, ReverseDependencyFilter,
+    ReverseDependencyRequirement, User, Version,
+};
 use crate::schema::*;
 use chrono::{DateTime, Utc};
-use crates_io_diesel_helpers::canon_crate_name;
+use crates_io_diesel_helpers::{canon_crate_name, pg_enum};
 use diesel::associations::Identifiable;
 use diesel::dsl;
 use diesel::pg::Pg;
 use diesel::prelude::*;
-use diesel::sql_types::{Bool, Integer, Text};
+use diesel::sql_types::{Array, BigInt, Bool, Integer, Text};
 use diesel_async::scoped_futures::ScopedFutureExt;
 use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
 use secrecy::SecretString;
@@ -24,6 +26,35 @@ pub struct CrateName {
     pub name: String,
 }
 
+pg_enum! {
+    /// The maintenance status of a crate, as chosen by its owners.
+    ///
+    /// - `Active`: the crate is actively maintained.
+    /// - `PassivelyMaintained`: the crate is considered feature-complete,
+    ///   but bugs and security issues are still addressed.
+    /// - `Deprecated`: the crate should not be used anymore, usually in favor
+    ///   of a successor crate.
+    /// - `Archived`: the crate is not maintained anymore and won't receive
+    ///   any further updates.
+    pub enum CrateStatus {
+        Active = 0,
+        PassivelyMaintained = 1,
+        Deprecated = 2,
+        Archived = 3,
+    }
+}
+
+impl CrateStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::PassivelyMaintained => "passively_maintained",
+            Self::Deprecated => "deprecated",
+            Self::Archived => "archived",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Identifiable, AsChangeset, HasQuery, Serialize)]
 #[diesel(table_name = crates)]
 pub struct Crate {
@@ -38,6 +69,9 @@ pub struct Crate {
     pub max_upload_size: Option<i32>,
     pub max_features: Option<i16>,
     pub trustpub_only: bool,
+    pub status: CrateStatus,
+    pub successor: Option<String>,
+    pub requires_publish_approval: bool,
 }
 
 /// We literally never want to select `textsearchable_index_col`
@@ -54,6 +88,9 @@ type AllColumns = (
     crates::max_upload_size,
     crates::max_features,
     crates::trustpub_only,
+    crates::status,
+    crates::successor,
+    crates::requires_publish_approval,
 );
 
 pub const ALL_COLUMNS: AllColumns = (
@@ -68,6 +105,9 @@ pub const ALL_COLUMNS: AllColumns = (
     crates::max_upload_size,
     crates::max_features,
     crates::trustpub_only,
+    crates::status,
+    crates::successor,
+    crates::requires_publish_approval,
 );
 
 type All = diesel::dsl::Select<crates::table, diesel::dsl::AsSelect<Crate, diesel::pg::Pg>>;
@@ -162,6 +202,22 @@ impl Crate {
         }
     }
 
+    /// SQL filter based on whether the crate's name is similar to the given
+    /// string, using the `%` trigram similarity operator of `pg_trgm`.
+    ///
+    /// This can be used to find crates even if the given string contains
+    /// typos.
+    pub fn similar_name<QS>(name: &str) -> Box<dyn BoxableExpression<QS, Pg, SqlType = Bool> + '_>
+    where
+        crates::name: SelectableExpression<QS>,
+    {
+        diesel::infix_operator!(Similar, " % ");
+        Box::new(Similar::new(
+            canon_crate_name(crates::name),
+            canon_crate_name(name),
+        ))
+    }
+
     /// SQL filter with the = binary operator
     pub fn with_name(name: &str) -> WithName<'_> {
         canon_crate_name(crates::name).eq(canon_crate_name(name))
@@ -199,11 +255,12 @@ impl Crate {
 
     /// Return both the newest (most recently updated) and
     /// highest version (in semver order) for the current crate,
-    /// where all top versions are not yanked.
+    /// where all top versions are not yanked and not pending approval.
     pub async fn top_versions(&self, conn: &mut AsyncPgConnection) -> QueryResult<TopVersions> {
         Ok(TopVersions::from_date_version_pairs(
             Version::belonging_to(self)
                 .filter(versions::yanked.eq(false))
+                .filter(Version::is_published())
                 .select((versions::created_at, versions::num))
                 .load(conn)
                 .await?,
@@ -280,29 +337,69 @@ impl Crate {
         Ok(())
     }
 
-    /// Returns (dependency, dependent crate name, dependent crate downloads)
+    /// Returns a page of (dependency, dependent crate name, dependent crate
+    /// downloads), together with the number of dependents per version
+    /// requirement across all pages.
     #[instrument(skip_all, fields(krate.name = %self.name))]
     pub async fn reverse_dependencies(
         &self,
         conn: &mut AsyncPgConnection,
+        filter: ReverseDependencyFilter,
         offset: i64,
         limit: i64,
-    ) -> QueryResult<(Vec<ReverseDependency>, i64)> {
+    ) -> QueryResult<ReverseDependencies> {
         use diesel::sql_query;
-        use diesel::sql_types::{BigInt, Integer};
+        use diesel::sql_types::Nullable;
 
-        let rows: Vec<WithCount<ReverseDependency>> =
+        let rows: Vec<ReverseDependencyRow> =
             sql_query(include_str!("krate_reverse_dependencies.sql"))
                 .bind::<Integer, _>(self.id)
                 .bind::<BigInt, _>(offset)
                 .bind::<BigInt, _>(limit)
+                .bind::<Nullable<Integer>, _>(filter.kind)
+                .bind::<Nullable<Bool>, _>(filter.optional)
                 .load(conn)
                 .await?;
 
-        Ok(rows.records_and_total())
+        let (total, requirements) = rows
+            .split_first()
+            .map(|(row, _)| {
+                let requirements = row
+                    .requirement_reqs
+                    .iter()
+                    .zip(&row.requirement_dependents)
+                    .map(|(req, &dependents)| ReverseDependencyRequirement {
+                        req: req.clone(),
+                        dependents,
+                    })
+                    .collect();
+
+                (row.total, requirements)
+            })
+            .unwrap_or_default();
+
+        let dependencies = rows.into_iter().map(|row| row.dependency).collect();
+
+        Ok(ReverseDependencies {
+            dependencies,
+            total,
+            requirements,
+        })
     }
 }
 
+#[derive(QueryableByName)]
+struct ReverseDependencyRow {
+    #[diesel(embed)]
+    dependency: ReverseDependency,
+    #[diesel(sql_type = BigInt)]
+    total: i64,
+    #[diesel(sql_type = Array<Text>)]
+    requirement_reqs: Vec<String>,
+    #[diesel(sql_type = Array<BigInt>)]
+    requirement_dependents: Vec<i64>,
+}
+
 /// Details of a newly created invite.
 #[derive(Debug)]
 pub enum NewOwnerInvite {
@@ -6,6 +6,8 @@ WITH filtered_default_versions as (
         SELECT dependencies.version_id
         FROM dependencies
         WHERE dependencies.crate_id = $1
+            AND ($4::integer IS NULL OR dependencies.kind = $4)
+            AND ($5::boolean IS NULL OR dependencies.optional = $5)
     ) AND NOT EXISTS (
         -- Filter out yanked crates
         -- (if the default version is yanked, then the whole crate is yanked)
@@ -13,24 +15,44 @@ WITH filtered_default_versions as (
         FROM versions
         WHERE id = version_id and yanked
     )
+), dependents as (
+    SELECT
+        filtered_default_versions.crate_id as dependent_crate_id,
+        dependencies.*
+    FROM filtered_default_versions
+    -- Multiple dependencies can exist, we only want first one
+    CROSS JOIN LATERAL (
+        SELECT dependencies.*
+        FROM dependencies
+        WHERE dependencies.crate_id = $1 AND dependencies.version_id = filtered_default_versions.version_id
+            AND ($4::integer IS NULL OR dependencies.kind = $4)
+            AND ($5::boolean IS NULL OR dependencies.optional = $5)
+        ORDER BY id ASC
+        LIMIT 1
+    ) dependencies
+), requirements as (
+    -- The number of dependents per version requirement, across all pages
+    SELECT
+        ARRAY_AGG(req) as requirement_reqs,
+        ARRAY_AGG(dependents) as requirement_dependents
+    FROM (
+        SELECT req, COUNT(*) as dependents
+        FROM dependents
+        GROUP BY req
+    ) grouped_dependents
 )
 SELECT
-    dependencies.*,
+    dependents.*,
     crate_downloads.downloads as crate_downloads,
     crates.name as crate_name,
-    (SELECT COUNT(*) from filtered_default_versions) as total
-FROM filtered_default_versions
+    (SELECT COUNT(*) from dependents) as total,
+    requirements.*
+FROM dependents
 INNER JOIN crates
-    ON crates.id = filtered_default_versions.crate_id
-INNER JOIN crate_downloads using (crate_id)
--- Multiple dependencies can exist, we only want first one
-CROSS JOIN LATERAL (
-    SELECT dependencies.*
-    FROM dependencies
-    WHERE dependencies.crate_id = $1 AND dependencies.version_id = filtered_default_versions.version_id
-    ORDER BY id ASC
-    LIMIT 1
-) dependencies
+    ON crates.id = dependents.dependent_crate_id
+INNER JOIN crate_downloads
+    ON crate_downloads.crate_id = dependents.dependent_crate_id
+CROSS JOIN requirements
 ORDER BY
     crate_downloads DESC,
     crate_name ASC
@@ -1,44 +1,80 @@
+pub use self::account_lock::{AccountLockAction, AccountLockEvent, NewAccountLockEvent};
+pub use self::account_recovery_token::AccountRecoveryToken;
 pub use self::action::{NewVersionOwnerAction, VersionAction, VersionOwnerAction};
+pub use self::blocked_traffic_rule::{BlockedTrafficRule, NewBlockedTrafficRule};
 pub use self::category::{Category, CrateCategory, NewCategory};
+pub use self::category_proposal::{CategoryProposal, CategoryProposalStatus, NewCategoryProposal};
 pub use self::cloudfront_invalidation_queue::{
     CloudFrontDistribution, CloudFrontInvalidationQueueItem,
 };
 pub use self::crate_owner_invitation::{
     CrateOwnerInvitation, NewCrateOwnerInvitation, NewCrateOwnerInvitationOutcome,
 };
+pub use self::crate_publish_policy::{CratePublishPolicy, NewCratePublishPolicy};
+pub use self::crate_transfer_request::{
+    CrateTransferAction, CrateTransferRequest, CrateTransferRequestEvent, CrateTransferStatus,
+    NewCrateTransferRequest,
+};
+pub use self::database_dump::{DatabaseDump, DatabaseDumpKind, NewDatabaseDump};
 pub use self::default_versions::{update_default_version, verify_default_version};
 pub use self::deleted_crate::NewDeletedCrate;
-pub use self::dependency::{Dependency, DependencyKind, ReverseDependency};
+pub use self::dependency::{
+    Dependency, DependencyKind, ReverseDependencies, ReverseDependency, ReverseDependencyFilter,
+    ReverseDependencyRequirement,
+};
+pub use self::docs_rs_rebuild_request::{
+    DocsRsRebuildRequest, DocsRsRebuildStatus, NewDocsRsRebuildRequest,
+};
 pub use self::download::VersionDownload;
 pub use self::email::{Email, NewEmail};
 pub use self::follow::Follow;
-pub use self::keyword::{CrateKeyword, Keyword};
-pub use self::krate::{Crate, CrateName, NewCrate};
+pub use self::keyword::{CrateKeyword, Keyword, KeywordAlias};
+pub use self::krate::{Crate, CrateName, CrateStatus, NewCrate};
 pub use self::owner::{CrateOwner, Owner, OwnerKind};
+pub use self::pending_github_link::PendingGitHubLink;
+pub use self::pending_version::{NewPendingVersion, PendingVersion};
+pub use self::scheduled_crate_deletion::{NewScheduledCrateDeletion, ScheduledCrateDeletion};
+pub use self::secret_scanner_key::{NewSecretScannerKey, SecretScannerKey};
 pub use self::team::{NewTeam, Team};
 pub use self::token::ApiToken;
 pub use self::trustpub::TrustpubData;
 pub use self::user::{NewOauthGithub, NewUser, OauthGithub, User};
-pub use self::version::{NewVersion, TopVersions, Version};
+pub use self::user_feed_token::UserFeedToken;
+pub use self::version::{DocsRsStatus, NewVersion, TopVersions, Version};
+pub use self::version_semver_check::{NewVersionSemverCheck, VersionSemverCheck};
 
 pub mod helpers;
 
+mod account_lock;
+mod account_recovery_token;
 mod action;
+mod blocked_traffic_rule;
 pub mod category;
+mod category_proposal;
 mod cloudfront_invalidation_queue;
 pub mod crate_owner_invitation;
+mod crate_publish_policy;
+mod crate_transfer_request;
+mod database_dump;
 pub mod default_versions;
 mod deleted_crate;
 pub mod dependency;
+mod docs_rs_rebuild_request;
 pub mod download;
 mod email;
 mod follow;
 mod keyword;
 pub mod krate;
 mod owner;
+mod pending_github_link;
+mod pending_version;
+mod scheduled_crate_deletion;
+mod secret_scanner_key;
 pub mod team;
 pub mod token;
 pub mod trustpub;
 pub mod user;
+mod user_feed_token;
 pub mod version;
+mod version_semver_check;
 pub mod versions_published_by;
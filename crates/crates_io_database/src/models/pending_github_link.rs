@@ -0,0 +1,89 @@
+use crate::schema::pending_github_links;
+use crate::utils::token::{HashedToken, PlainToken};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// A GitHub account that will replace the GitHub account of a user after a
+/// waiting period.
+///
+/// Linking a different GitHub account via account recovery only requires
+/// access to the verified email address of the user. The waiting period gives
+/// the user time to cancel the link via the cancel token that is sent to that
+/// email address, in case the address was compromised.
+#[derive(Debug, Identifiable, HasQuery)]
+#[diesel(table_name = pending_github_links, primary_key(user_id))]
+pub struct PendingGitHubLink {
+    pub user_id: i32,
+    pub gh_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub effective_at: DateTime<Utc>,
+}
+
+impl PendingGitHubLink {
+    /// Saves a pending link for the given user, replacing the previous
+    /// pending link of the user if there is one.
+    ///
+    /// The plaintext cancel token is only returned here, only its hash is
+    /// saved in the database.
+    pub async fn create(
+        conn: &mut AsyncPgConnection,
+        user_id: i32,
+        gh_id: i32,
+        effective_at: DateTime<Utc>,
+    ) -> QueryResult<(Self, PlainToken)> {
+        let plaintext = PlainToken::generate();
+
+        let link = diesel::insert_into(pending_github_links::table)
+            .values((
+                pending_github_links::user_id.eq(user_id),
+                pending_github_links::gh_id.eq(gh_id),
+                pending_github_links::cancel_token.eq(plaintext.hashed()),
+                pending_github_links::effective_at.eq(effective_at),
+            ))
+            .on_conflict(pending_github_links::user_id)
+            .do_update()
+            .set((
+                pending_github_links::gh_id.eq(excluded(pending_github_links::gh_id)),
+                pending_github_links::cancel_token.eq(excluded(pending_github_links::cancel_token)),
+                pending_github_links::created_at.eq(excluded(pending_github_links::created_at)),
+                pending_github_links::effective_at.eq(excluded(pending_github_links::effective_at)),
+            ))
+            .returning(Self::as_returning())
+            .get_result(conn)
+            .await?;
+
+        Ok((link, plaintext))
+    }
+
+    /// Looks up the pending link of the given GitHub account.
+    pub async fn find_by_gh_id(
+        conn: &mut AsyncPgConnection,
+        gh_id: i32,
+    ) -> QueryResult<Option<Self>> {
+        Self::query()
+            .filter(pending_github_links::gh_id.eq(gh_id))
+            .first(conn)
+            .await
+            .optional()
+    }
+
+    /// Deletes the pending link with the given cancel token.
+    pub async fn cancel(
+        conn: &mut AsyncPgConnection,
+        token: &HashedToken,
+    ) -> QueryResult<Option<Self>> {
+        diesel::delete(pending_github_links::table)
+            .filter(pending_github_links::cancel_token.eq(token))
+            .returning(Self::as_returning())
+            .get_result(conn)
+            .await
+            .optional()
+    }
+
+    /// Returns `true` if the waiting period of the link is over.
+    pub fn is_effective(&self) -> bool {
+        self.effective_at <= Utc::now()
+    }
+}
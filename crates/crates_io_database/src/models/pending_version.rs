@@ -0,0 +1,74 @@
+use crate::schema::pending_versions;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// A version that was uploaded for a crate that requires publish approval,
+/// but has not been approved by a second owner yet.
+///
+/// Pending versions are excluded from the index and are not downloadable.
+/// If they are not approved before they expire, they are deleted by the
+/// `ExpirePendingVersions` background job.
+#[derive(Debug, Clone, Identifiable, HasQuery)]
+#[diesel(table_name = pending_versions, primary_key(version_id))]
+pub struct PendingVersion {
+    pub version_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl PendingVersion {
+    /// Finds the pending state of the given version, if there is one.
+    pub async fn find(conn: &mut AsyncPgConnection, version_id: i32) -> QueryResult<Option<Self>> {
+        Self::query().find(version_id).first(conn).await.optional()
+    }
+
+    /// Loads all pending versions that have expired, oldest first.
+    pub async fn expired(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Self>> {
+        Self::query()
+            .filter(pending_versions::expires_at.le(Utc::now()))
+            .order(pending_versions::expires_at.asc())
+            .load(conn)
+            .await
+    }
+
+    /// Marks the version as approved, which removes its pending state.
+    ///
+    /// Returns `false` if the version was not pending anymore.
+    pub async fn approve(conn: &mut AsyncPgConnection, version_id: i32) -> QueryResult<bool> {
+        let deleted = diesel::delete(pending_versions::table.find(version_id))
+            .execute(conn)
+            .await?;
+
+        Ok(deleted > 0)
+    }
+
+    /// Lets the pending version expire right away, so that it is deleted by
+    /// the next run of the `ExpirePendingVersions` background job.
+    ///
+    /// Returns `false` if the version was not pending anymore.
+    pub async fn reject(conn: &mut AsyncPgConnection, version_id: i32) -> QueryResult<bool> {
+        let updated = diesel::update(pending_versions::table.find(version_id))
+            .set(pending_versions::expires_at.eq(Utc::now()))
+            .execute(conn)
+            .await?;
+
+        Ok(updated > 0)
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = pending_versions, check_for_backend(diesel::pg::Pg))]
+pub struct NewPendingVersion {
+    pub version_id: i32,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl NewPendingVersion {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<PendingVersion> {
+        self.insert_into(pending_versions::table)
+            .returning(PendingVersion::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
@@ -0,0 +1,73 @@
+use crate::schema::scheduled_crate_deletions;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// A crate that was deleted by one of its owners, but is kept around until
+/// the grace period ends.
+///
+/// While the deletion is pending the crate is hidden from the index and
+/// search, but all of its data stays intact, so that the owners can still
+/// cancel the deletion.
+#[derive(Debug, Clone, Identifiable, HasQuery)]
+#[diesel(table_name = scheduled_crate_deletions, primary_key(crate_id))]
+pub struct ScheduledCrateDeletion {
+    pub crate_id: i32,
+    pub requested_by: Option<i32>,
+    pub requested_at: DateTime<Utc>,
+    pub scheduled_for: DateTime<Utc>,
+    pub message: Option<String>,
+}
+
+impl ScheduledCrateDeletion {
+    /// Finds the pending deletion of the given crate, if there is one.
+    pub async fn find(conn: &mut AsyncPgConnection, crate_id: i32) -> QueryResult<Option<Self>> {
+        Self::query().find(crate_id).first(conn).await.optional()
+    }
+
+    /// Loads all deletions whose grace period has ended, oldest first.
+    pub async fn due(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Self>> {
+        Self::query()
+            .filter(scheduled_crate_deletions::scheduled_for.le(Utc::now()))
+            .order(scheduled_crate_deletions::scheduled_for.asc())
+            .load(conn)
+            .await
+    }
+
+    /// Cancels the pending deletion of the given crate.
+    ///
+    /// Returns `true` if the crate was scheduled for deletion.
+    pub async fn cancel(conn: &mut AsyncPgConnection, crate_id: i32) -> QueryResult<bool> {
+        let deleted = diesel::delete(scheduled_crate_deletions::table.find(crate_id))
+            .execute(conn)
+            .await?;
+
+        Ok(deleted > 0)
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = scheduled_crate_deletions, check_for_backend(diesel::pg::Pg))]
+pub struct NewScheduledCrateDeletion<'a> {
+    pub crate_id: i32,
+    pub requested_by: Option<i32>,
+    pub scheduled_for: DateTime<Utc>,
+    pub message: Option<&'a str>,
+}
+
+impl NewScheduledCrateDeletion<'_> {
+    /// Schedules the deletion of the crate.
+    ///
+    /// Returns `None` if the crate is already scheduled for deletion.
+    pub async fn insert(
+        &self,
+        conn: &mut AsyncPgConnection,
+    ) -> QueryResult<Option<ScheduledCrateDeletion>> {
+        self.insert_into(scheduled_crate_deletions::table)
+            .on_conflict_do_nothing()
+            .returning(ScheduledCrateDeletion::as_returning())
+            .get_result(conn)
+            .await
+            .optional()
+    }
+}
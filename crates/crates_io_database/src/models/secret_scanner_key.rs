@@ -0,0 +1,55 @@
+use crate::schema::secret_scanner_keys;
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// A public key of a secret scanner that is allowed to report leaked tokens.
+#[derive(Debug, Clone, HasQuery)]
+pub struct SecretScannerKey {
+    pub key_identifier: String,
+    pub scanner: String,
+    pub public_key: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SecretScannerKey {
+    pub async fn find(
+        conn: &mut AsyncPgConnection,
+        key_identifier: &str,
+    ) -> QueryResult<Option<Self>> {
+        Self::query()
+            .find(key_identifier)
+            .first(conn)
+            .await
+            .optional()
+    }
+
+    pub async fn load_all(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Self>> {
+        Self::query()
+            .order((
+                secret_scanner_keys::scanner,
+                secret_scanner_keys::created_at,
+            ))
+            .load(conn)
+            .await
+    }
+}
+
+#[derive(Debug, Insertable, Builder)]
+#[diesel(table_name = secret_scanner_keys, check_for_backend(diesel::pg::Pg))]
+pub struct NewSecretScannerKey<'a> {
+    pub key_identifier: &'a str,
+    pub scanner: &'a str,
+    pub public_key: &'a str,
+}
+
+impl NewSecretScannerKey<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<SecretScannerKey> {
+        diesel::insert_into(secret_scanner_keys::table)
+            .values(self)
+            .returning(SecretScannerKey::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
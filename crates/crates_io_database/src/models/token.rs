@@ -1,4 +1,5 @@
 mod scopes;
+mod usage;
 
 use bon::Builder;
 use chrono::{DateTime, Utc};
@@ -9,6 +10,7 @@ use diesel_async::scoped_futures::ScopedFutureExt;
 use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
 
 pub use self::scopes::{CrateScope, EndpointScope};
+pub use self::usage::{ApiTokenUsage, ApiTokenUsageAnomaly, NewApiTokenUsage};
 use crate::models::User;
 use crate::schema::api_tokens;
 use crate::utils::token::{HashedToken, PlainToken};
@@ -26,6 +28,8 @@ pub struct NewApiToken {
     /// A list of endpoint scopes or `None` for the `legacy` endpoint scope (see RFC #2947)
     pub endpoint_scopes: Option<Vec<EndpointScope>>,
     pub expired_at: Option<DateTime<Utc>>,
+    /// The refresh token that this short-lived token was exchanged for
+    pub parent_id: Option<i32>,
 }
 
 impl NewApiToken {
@@ -75,13 +79,55 @@ pub struct ApiToken {
     /// The date and time when the token will expire, or `null`.
     #[schema(example = "2030-10-26T11:32:12Z")]
     pub expired_at: Option<DateTime<Utc>>,
+
+    /// The refresh token that this short-lived token was exchanged for.
+    #[serde(skip)]
+    pub parent_id: Option<i32>,
 }
 
 impl ApiToken {
+    /// Returns the ID of the refresh token that this token was exchanged
+    /// for, or the ID of the token itself if it is not a short-lived token.
+    pub fn root_id(&self) -> i32 {
+        self.parent_id.unwrap_or(self.id)
+    }
+
+    /// Returns the creation date of the refresh token that this token was
+    /// exchanged for, or the creation date of the token itself if it is not
+    /// a short-lived token.
+    pub async fn root_created_at(
+        &self,
+        conn: &mut AsyncPgConnection,
+    ) -> QueryResult<DateTime<Utc>> {
+        let Some(parent_id) = self.parent_id else {
+            return Ok(self.created_at);
+        };
+
+        api_tokens::table
+            .find(parent_id)
+            .select(api_tokens::created_at)
+            .first(conn)
+            .await
+    }
+
     pub async fn find_by_api_token(
         conn: &mut AsyncPgConnection,
         token: &HashedToken,
     ) -> QueryResult<ApiToken> {
+        let parent_tokens = diesel::alias!(api_tokens as parent_tokens);
+
+        // Tokens that were exchanged for a refresh token stop working as
+        // soon as the refresh token is revoked or expires.
+        let valid_parent_ids = parent_tokens
+            .filter(parent_tokens.field(api_tokens::revoked).eq(false))
+            .filter(
+                parent_tokens
+                    .field(api_tokens::expired_at)
+                    .is_null()
+                    .or(parent_tokens.field(api_tokens::expired_at).gt(now)),
+            )
+            .select(parent_tokens.field(api_tokens::id).nullable());
+
         let tokens = api_tokens::table
             .filter(api_tokens::revoked.eq(false))
             .filter(
@@ -89,6 +135,11 @@ impl ApiToken {
                     .is_null()
                     .or(api_tokens::expired_at.gt(now)),
             )
+            .filter(
+                api_tokens::parent_id
+                    .is_null()
+                    .or(api_tokens::parent_id.eq_any(valid_parent_ids)),
+            )
             .filter(api_tokens::token.eq(token));
 
         // If the database is in read only mode, we can't update last_used_at.
@@ -140,6 +191,7 @@ mod tests {
             crate_scopes: None,
             endpoint_scopes: None,
             expired_at: None,
+            parent_id: None,
         };
         let json = serde_json::to_string(&tok).unwrap();
         assert_some!(json.as_str().find(r#""created_at":"2017-01-06T14:23:11Z""#));
@@ -16,6 +16,9 @@ pub enum EndpointScope {
     TrustedPublishing,
     Yank,
     ChangeOwners,
+    /// Allows exchanging the token for short-lived tokens with a subset of
+    /// its scopes. Tokens with this scope can't be used for anything else.
+    TokenExchange,
 }
 
 impl From<&EndpointScope> for &[u8] {
@@ -26,6 +29,7 @@ impl From<&EndpointScope> for &[u8] {
             EndpointScope::TrustedPublishing => b"trusted-publishing",
             EndpointScope::Yank => b"yank",
             EndpointScope::ChangeOwners => b"change-owners",
+            EndpointScope::TokenExchange => b"token-exchange",
         }
     }
 }
@@ -47,6 +51,7 @@ impl TryFrom<&[u8]> for EndpointScope {
             b"trusted-publishing" => Ok(EndpointScope::TrustedPublishing),
             b"yank" => Ok(EndpointScope::Yank),
             b"change-owners" => Ok(EndpointScope::ChangeOwners),
+            b"token-exchange" => Ok(EndpointScope::TokenExchange),
             _ => Err("Unrecognized enum variant".to_string()),
         }
     }
@@ -116,6 +121,29 @@ impl CrateScope {
         validate_crate_name("crate", name_without_wildcard).is_ok()
     }
 
+    /// Returns `true` if the pattern can match more than one crate name.
+    pub fn is_wildcard(&self) -> bool {
+        self.pattern.ends_with('*')
+    }
+
+    /// Returns `true` if every crate name matched by `other` is also
+    /// matched by this pattern.
+    pub fn covers(&self, other: &CrateScope) -> bool {
+        if self.pattern == "*" {
+            return true;
+        }
+
+        match (
+            self.pattern.strip_suffix('*'),
+            other.pattern.strip_suffix('*'),
+        ) {
+            (Some(prefix), Some(other_prefix)) => other_prefix.starts_with(prefix),
+            (Some(_), None) => self.matches(&other.pattern),
+            (None, Some(_)) => false,
+            (None, None) => self.pattern == other.pattern,
+        }
+    }
+
     pub fn matches(&self, crate_name: &str) -> bool {
         if self.pattern == "*" {
             return true;
@@ -145,6 +173,7 @@ mod tests {
         assert(EndpointScope::PublishUpdate, "\"publish-update\"");
         assert(EndpointScope::TrustedPublishing, "\"trusted-publishing\"");
         assert(EndpointScope::Yank, "\"yank\"");
+        assert(EndpointScope::TokenExchange, "\"token-exchange\"");
     }
 
     #[googletest::test]
@@ -213,4 +242,23 @@ mod tests {
         expect_that!(scope("foo_*").matches("foo-bar"), eq(false));
         expect_that!(scope("foo_*").matches("foo_bar"), eq(true));
     }
+
+    #[googletest::test]
+    fn crate_scope_covering() {
+        let scope = |pattern: &str| CrateScope::try_from(pattern).unwrap();
+
+        expect_that!(scope("foo").covers(&scope("foo")), eq(true));
+        expect_that!(scope("foo").covers(&scope("bar")), eq(false));
+        expect_that!(scope("foo").covers(&scope("foo*")), eq(false));
+
+        // wildcards
+        expect_that!(scope("*").covers(&scope("foo")), eq(true));
+        expect_that!(scope("*").covers(&scope("foo*")), eq(true));
+        expect_that!(scope("foo*").covers(&scope("foo")), eq(true));
+        expect_that!(scope("foo*").covers(&scope("foo-bar")), eq(true));
+        expect_that!(scope("foo*").covers(&scope("foo-*")), eq(true));
+        expect_that!(scope("foo-*").covers(&scope("foo*")), eq(false));
+        expect_that!(scope("foo*").covers(&scope("*")), eq(false));
+        expect_that!(scope("foo*").covers(&scope("bar")), eq(false));
+    }
 }
@@ -0,0 +1,192 @@
+use crate::schema::{api_token_usage_baselines, api_token_usages};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{Array, BigInt, Integer, Text};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use ipnetwork::IpNetwork;
+use serde::{Deserialize, Serialize, Serializer};
+
+/// A request that was authenticated with an API token.
+///
+/// Old entries are deleted after a while, so the usage history of a token
+/// only covers the configured retention period. The countries, networks and
+/// endpoints that a token was used from or for are kept in the
+/// `api_token_usage_baselines` table instead, which is never cleaned up.
+#[derive(Debug, Clone, HasQuery, Serialize, utoipa::ToSchema)]
+#[diesel(table_name = api_token_usages, check_for_backend(diesel::pg::Pg))]
+pub struct ApiTokenUsage {
+    #[serde(skip)]
+    pub id: i64,
+
+    #[serde(skip)]
+    pub api_token_id: i32,
+
+    /// The date and time when the token was used.
+    #[schema(example = "2021-10-26T11:32:12Z")]
+    pub created_at: DateTime<Utc>,
+
+    /// The HTTP method and route of the request.
+    #[schema(example = "PUT /api/v1/crates/new")]
+    pub endpoint: String,
+
+    /// The name of the crate that the request was for, if any.
+    #[schema(example = "serde")]
+    pub crate_name: Option<String>,
+
+    /// The IP address of the client.
+    #[serde(serialize_with = "serialize_ip")]
+    #[schema(value_type = String, example = "203.0.113.7")]
+    pub ip: IpNetwork,
+
+    /// The two-letter country code of the client, if known.
+    #[schema(example = "DE")]
+    pub country: Option<String>,
+
+    /// The autonomous system number of the client network, if known.
+    #[schema(example = 16509)]
+    pub asn: Option<i64>,
+
+    /// The user agent of the client.
+    #[schema(example = "cargo/1.90.0 (840b83a10 2025-07-30)")]
+    pub user_agent: Option<String>,
+}
+
+fn serialize_ip<S: Serializer>(ip: &IpNetwork, serializer: S) -> Result<S::Ok, S::Error> {
+    ip.ip().serialize(serializer)
+}
+
+impl ApiTokenUsage {
+    /// Finds a usage log entry by its ID.
+    pub async fn find(conn: &mut AsyncPgConnection, id: i64) -> QueryResult<Option<Self>> {
+        Self::query().find(id).first(conn).await.optional()
+    }
+
+    /// Loads the most recent usage log entries of the given token, most
+    /// recent first.
+    pub async fn recent(
+        conn: &mut AsyncPgConnection,
+        api_token_id: i32,
+        limit: i64,
+    ) -> QueryResult<Vec<Self>> {
+        Self::query()
+            .filter(api_token_usages::api_token_id.eq(api_token_id))
+            .order((
+                api_token_usages::created_at.desc(),
+                api_token_usages::id.desc(),
+            ))
+            .limit(limit)
+            .load(conn)
+            .await
+    }
+
+    /// Deletes all usage log entries that were created before the given
+    /// date and time, and returns the number of deleted entries.
+    pub async fn delete_older_than(
+        conn: &mut AsyncPgConnection,
+        cutoff: DateTime<Utc>,
+    ) -> QueryResult<usize> {
+        diesel::delete(api_token_usages::table)
+            .filter(api_token_usages::created_at.lt(cutoff))
+            .execute(conn)
+            .await
+    }
+}
+
+/// The ways in which a token usage can differ from the previous usages of
+/// the same token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiTokenUsageAnomaly {
+    /// The token was used from a country that it was not used from before.
+    NewCountry,
+    /// The token was used from a network that it was not used from before.
+    NewAsn,
+    /// The token was used for an endpoint that it was not used for before.
+    NewEndpoint,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = api_token_usages, check_for_backend(diesel::pg::Pg))]
+pub struct NewApiTokenUsage<'a> {
+    pub api_token_id: i32,
+    pub endpoint: &'a str,
+    pub crate_name: Option<&'a str>,
+    pub ip: IpNetwork,
+    pub country: Option<&'a str>,
+    pub asn: Option<i64>,
+    pub user_agent: Option<&'a str>,
+}
+
+impl NewApiTokenUsage<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<i64> {
+        self.insert_into(api_token_usages::table)
+            .returning(api_token_usages::id)
+            .get_result(conn)
+            .await
+    }
+
+    /// Compares this usage with the baseline of the previously recorded
+    /// usages of the same token.
+    ///
+    /// The first recorded usage of a token serves as the baseline, so it
+    /// does not result in any anomalies.
+    pub async fn anomalies(
+        &self,
+        conn: &mut AsyncPgConnection,
+    ) -> QueryResult<Vec<ApiTokenUsageAnomaly>> {
+        let baseline: Option<(Vec<String>, Vec<i64>, Vec<String>)> =
+            api_token_usage_baselines::table
+                .find(self.api_token_id)
+                .select((
+                    api_token_usage_baselines::countries,
+                    api_token_usage_baselines::asns,
+                    api_token_usage_baselines::endpoints,
+                ))
+                .first(conn)
+                .await
+                .optional()?;
+
+        let mut anomalies = Vec::new();
+        let Some((countries, asns, endpoints)) = baseline else {
+            return Ok(anomalies);
+        };
+
+        if let Some(country) = self.country
+            && !countries.iter().any(|known| known == country)
+        {
+            anomalies.push(ApiTokenUsageAnomaly::NewCountry);
+        }
+        if let Some(asn) = self.asn
+            && !asns.contains(&asn)
+        {
+            anomalies.push(ApiTokenUsageAnomaly::NewAsn);
+        }
+        if !endpoints.iter().any(|known| known == self.endpoint) {
+            anomalies.push(ApiTokenUsageAnomaly::NewEndpoint);
+        }
+
+        Ok(anomalies)
+    }
+
+    /// Adds the country, network and endpoint of this usage to the baseline
+    /// of the token.
+    pub async fn update_baseline(&self, conn: &mut AsyncPgConnection) -> QueryResult<()> {
+        diesel::sql_query(
+            "INSERT INTO api_token_usage_baselines AS baselines \
+                 (api_token_id, countries, asns, endpoints) \
+             VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (api_token_id) DO UPDATE SET \
+                 countries = ARRAY(SELECT DISTINCT unnest(baselines.countries || excluded.countries)), \
+                 asns = ARRAY(SELECT DISTINCT unnest(baselines.asns || excluded.asns)), \
+                 endpoints = ARRAY(SELECT DISTINCT unnest(baselines.endpoints || excluded.endpoints))",
+        )
+        .bind::<Integer, _>(self.api_token_id)
+        .bind::<Array<Text>, _>(self.country.into_iter().collect::<Vec<_>>())
+        .bind::<Array<BigInt>, _>(self.asn.into_iter().collect::<Vec<_>>())
+        .bind::<Array<Text>, _>(vec![self.endpoint])
+        .execute(conn)
+        .await?;
+
+        Ok(())
+    }
+}
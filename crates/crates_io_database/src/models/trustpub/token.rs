@@ -11,6 +11,8 @@ pub struct NewToken<'a> {
     pub hashed_token: &'a [u8],
     pub crate_ids: &'a [i32],
     pub trustpub_data: Option<&'a TrustpubData>,
+    pub github_config_ids: &'a [i32],
+    pub gitlab_config_ids: &'a [i32],
 }
 
 impl NewToken<'_> {
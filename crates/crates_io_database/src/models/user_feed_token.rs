@@ -0,0 +1,72 @@
+use crate::schema::user_feed_tokens;
+use crate::utils::token::{HashedToken, PlainToken};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// The secret token that gives feed readers access to the personalized
+/// updates feed of a user.
+///
+/// Every user has at most one feed token. Regenerating the token replaces
+/// the previous one, which immediately revokes access for all feed readers
+/// that are still using the old URL.
+#[derive(Debug, HasQuery)]
+pub struct UserFeedToken {
+    pub user_id: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl UserFeedToken {
+    /// Generates a new feed token for the given user, replacing the
+    /// previous token if there is one.
+    ///
+    /// The plaintext token is only returned here, only its hash is saved in
+    /// the database.
+    pub async fn regenerate(
+        conn: &mut AsyncPgConnection,
+        user_id: i32,
+    ) -> QueryResult<(Self, PlainToken)> {
+        let plaintext = PlainToken::generate();
+
+        let token = diesel::insert_into(user_feed_tokens::table)
+            .values((
+                user_feed_tokens::user_id.eq(user_id),
+                user_feed_tokens::token.eq(plaintext.hashed()),
+            ))
+            .on_conflict(user_feed_tokens::user_id)
+            .do_update()
+            .set((
+                user_feed_tokens::token.eq(excluded(user_feed_tokens::token)),
+                user_feed_tokens::created_at.eq(diesel::dsl::now),
+            ))
+            .returning(Self::as_returning())
+            .get_result(conn)
+            .await?;
+
+        Ok((token, plaintext))
+    }
+
+    /// Revokes the feed token of the given user.
+    ///
+    /// Returns `true` if the user had a feed token.
+    pub async fn revoke(conn: &mut AsyncPgConnection, user_id: i32) -> QueryResult<bool> {
+        let deleted = diesel::delete(user_feed_tokens::table.find(user_id))
+            .execute(conn)
+            .await?;
+
+        Ok(deleted > 0)
+    }
+
+    /// Looks up the feed token with the given hash.
+    pub async fn find_by_token(
+        conn: &mut AsyncPgConnection,
+        token: &HashedToken,
+    ) -> QueryResult<Option<Self>> {
+        Self::query()
+            .filter(user_feed_tokens::token.eq(token))
+            .first(conn)
+            .await
+            .optional()
+    }
+}
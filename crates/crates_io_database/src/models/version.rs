@@ -2,13 +2,24 @@ use std::collections::BTreeMap;
 
 use bon::Builder;
 use chrono::{DateTime, Utc};
+use crates_io_diesel_helpers::pg_enum;
 use crates_io_index::features::FeaturesMap;
+use diesel::dsl::{self, exists, not};
 use diesel::prelude::*;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use serde::Deserialize;
 
 use crate::models::{Crate, TrustpubData, User};
-use crate::schema::{readme_renderings, versions};
+use crate::schema::{pending_versions, readme_renderings, versions};
+
+pg_enum! {
+    /// The status of the documentation build of a version on docs.rs.
+    pub enum DocsRsStatus {
+        Success = 0,
+        Failure = 1,
+        Queued = 2,
+    }
+}
 
 #[derive(Clone, Identifiable, Associations, Debug, HasQuery)]
 #[diesel(belongs_to(Crate), belongs_to(crate::models::download::Version, foreign_key=id))]
@@ -37,9 +48,18 @@ pub struct Version {
     pub repository: Option<String>,
     pub trustpub_data: Option<TrustpubData>,
     pub linecounts: Option<serde_json::Value>,
+    pub docs_rs_status: Option<DocsRsStatus>,
 }
 
 impl Version {
+    /// SQL filter that excludes the versions that are still waiting for
+    /// publish approval.
+    #[dsl::auto_type(no_type_alias)]
+    pub fn is_published() -> _ {
+        let pending = pending_versions::table.filter(pending_versions::version_id.eq(versions::id));
+        not(exists(pending))
+    }
+
     pub async fn record_readme_rendering(
         version_id: i32,
         conn: &mut AsyncPgConnection,
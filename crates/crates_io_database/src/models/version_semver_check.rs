@@ -0,0 +1,52 @@
+use crate::schema::version_semver_checks;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// The result of comparing the public API of a version against the previous
+/// semver-compatible version of the crate.
+#[derive(Debug, Clone, Identifiable, HasQuery)]
+#[diesel(table_name = version_semver_checks, primary_key(version_id))]
+pub struct VersionSemverCheck {
+    pub version_id: i32,
+    pub baseline_version_id: i32,
+    /// The detected breaking changes, as a JSON array.
+    pub breaking_changes: serde_json::Value,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl VersionSemverCheck {
+    pub async fn find(conn: &mut AsyncPgConnection, version_id: i32) -> QueryResult<Option<Self>> {
+        Self::query().find(version_id).first(conn).await.optional()
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = version_semver_checks, check_for_backend(diesel::pg::Pg))]
+pub struct NewVersionSemverCheck<'a> {
+    pub version_id: i32,
+    pub baseline_version_id: i32,
+    pub breaking_changes: &'a serde_json::Value,
+}
+
+impl NewVersionSemverCheck<'_> {
+    /// Inserts the result, replacing the result of a previous check of the
+    /// same version.
+    pub async fn upsert(&self, conn: &mut AsyncPgConnection) -> QueryResult<()> {
+        self.insert_into(version_semver_checks::table)
+            .on_conflict(version_semver_checks::version_id)
+            .do_update()
+            .set((
+                version_semver_checks::baseline_version_id
+                    .eq(excluded(version_semver_checks::baseline_version_id)),
+                version_semver_checks::breaking_changes
+                    .eq(excluded(version_semver_checks::breaking_changes)),
+                version_semver_checks::checked_at.eq(diesel::dsl::now),
+            ))
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -12,6 +12,92 @@ pub mod sql_types {
     pub struct Ltree;
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Audit log of all account locks and unlocks
+    account_lock_events (id) {
+        /// The action that was performed (0=locked by an admin, 1=unlocked by an admin, 2=locked by the account owner, 3=unlocked via account recovery)
+        action -> Int4,
+        /// The user that performed the action
+        actor_id -> Nullable<Int4>,
+        /// Date and time when the action was performed
+        created_at -> Timestamptz,
+        /// Unique identifier of the event
+        id -> Int8,
+        /// Date and time when the lock expires, or NULL if the lock does not expire
+        locked_until -> Nullable<Timestamptz>,
+        /// Explanation why the account was locked or unlocked
+        reason -> Text,
+        /// The user whose account was locked or unlocked
+        user_id -> Int4,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Secret tokens that are sent to the verified email address of a user to recover their account
+    account_recovery_tokens (id) {
+        /// Date and time when the token was generated
+        created_at -> Timestamptz,
+        /// Date and time when the token expires
+        expires_at -> Timestamptz,
+        /// Unique identifier of the token
+        id -> Int4,
+        /// SHA-256 hash of the secret recovery token
+        token -> Bytea,
+        /// The user whose account can be recovered with this token
+        user_id -> Int4,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Countries, networks and endpoints that an API token was used from or for. Unlike `api_token_usages`, these are never deleted, so that unusual usages can be detected even after the usage log entries were deleted.
+    api_token_usage_baselines (api_token_id) {
+        /// The API token that was used
+        api_token_id -> Int4,
+        /// Autonomous system numbers of the client networks that used the token
+        asns -> Array<Int8>,
+        /// Two-letter country codes of the clients that used the token
+        countries -> Array<Text>,
+        /// HTTP methods and routes of the requests that the token was used for.
+        endpoints -> Array<Text>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Log of requests that were authenticated with an API token. Old entries are deleted by the `delete_old_api_token_usages` background job.
+    api_token_usages (id) {
+        /// The API token that was used
+        api_token_id -> Int4,
+        /// Autonomous system number of the client network, as determined by the CDN
+        asn -> Nullable<Int8>,
+        /// Two-letter country code of the client, as determined by the CDN
+        country -> Nullable<Varchar>,
+        /// Name of the crate that the request was for, if any
+        crate_name -> Nullable<Varchar>,
+        /// Date and time when the token was used
+        created_at -> Timestamptz,
+        /// HTTP method and route of the request, e.g. `PUT /api/v1/crates/new`
+        endpoint -> Varchar,
+        /// Unique identifier of the `api_token_usages` row
+        id -> Int8,
+        /// IP address of the client
+        ip -> Inet,
+        /// User agent of the client
+        user_agent -> Nullable<Varchar>,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -56,6 +142,8 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         name -> Varchar,
+        /// ID of the refresh token that this short-lived token was exchanged for, or NULL for regular tokens
+        parent_id -> Nullable<Int4>,
         /// The `revoked` column of the `api_tokens` table.
         ///
         /// Its SQL type is `Bool`.
@@ -130,6 +218,33 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Rules for blocking incoming requests, periodically reloaded by the web servers. A request is blocked if it matches all conditions of a rule.
+    blocked_traffic_rules (id) {
+        /// Date and time when the rule was created
+        created_at -> Timestamptz,
+        /// Date and time after which the rule is no longer applied. NULL if the rule does not expire.
+        expires_at -> Nullable<Timestamptz>,
+        /// Name of the request header that has to match `header_pattern`
+        header_name -> Nullable<Text>,
+        /// Pattern that the value of the `header_name` header has to match. `*` matches any sequence of characters.
+        header_pattern -> Nullable<Text>,
+        /// Unique identifier of the rule
+        id -> Int4,
+        /// IP address range that the client IP address has to be in
+        ip_range -> Nullable<Cidr>,
+        /// Custom message that is returned to blocked clients. NULL to use the default message.
+        message -> Nullable<Text>,
+        /// Internal note explaining why the rule was created
+        reason -> Text,
+        /// Pattern that the route of the request has to match (e.g. `/api/v1/crates/{name}/{version}/download`). `*` matches any sequence of characters.
+        route_pattern -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -178,6 +293,33 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Proposals by users for new categories, which are reviewed by the crates.io team
+    category_proposals (id) {
+        /// Date and time when the category was proposed
+        created_at -> Timestamptz,
+        /// Description of the proposed category
+        description -> Varchar,
+        /// Unique identifier of the proposal
+        id -> Int4,
+        /// The display name of the proposed category, without the name of its parent category
+        name -> Varchar,
+        /// The user that proposed the category
+        proposed_by -> Nullable<Int4>,
+        /// Date and time when the proposal was approved or rejected
+        resolved_at -> Nullable<Timestamptz>,
+        /// The admin that approved or rejected the proposal
+        resolved_by -> Nullable<Int4>,
+        /// The slug of the proposed category, using `::` to separate it from the slug of its parent category
+        slug -> Varchar,
+        /// The status of the proposal (0=pending, 1=approved, 2=rejected)
+        status -> Int4,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -302,6 +444,73 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Restrictions on who can publish new versions of a crate, and how
+    crate_publish_policies (crate_id) {
+        /// IDs of the users that are allowed to publish the crate, or an empty array if all owners are allowed to publish
+        allowed_publishers -> Array<Int4>,
+        /// The crate that the policy applies to
+        crate_id -> Int4,
+        /// Maximum age of API tokens in days, or NULL if tokens of any age can be used
+        max_token_age_days -> Nullable<Int4>,
+        /// Whether API tokens need a crate scope naming this crate exactly, instead of a wildcard pattern
+        require_crate_scoped_tokens -> Bool,
+        /// IDs of the GitHub Trusted Publishing configs that are allowed to publish the crate
+        trustpub_github_config_ids -> Array<Int4>,
+        /// IDs of the GitLab Trusted Publishing configs that are allowed to publish the crate
+        trustpub_gitlab_config_ids -> Array<Int4>,
+        /// Date and time when the policy was last changed
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Audit log of all steps of the crate transfer requests
+    crate_transfer_request_events (id) {
+        /// The action that was performed (0=created, 1=accepted, 2=rejected, 3=withdrawn, 4=approved, 5=denied)
+        action -> Int4,
+        /// The user that performed the action
+        actor_id -> Nullable<Int4>,
+        /// Date and time when the action was performed
+        created_at -> Timestamptz,
+        /// Unique identifier of the event
+        id -> Int8,
+        /// Optional note explaining the action
+        note -> Nullable<Text>,
+        /// The transfer request that the event belongs to
+        request_id -> Int4,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Requests by users to take over the ownership of a crate
+    crate_transfer_requests (id) {
+        /// The crate that the requester wants to take over
+        crate_id -> Int4,
+        /// Date and time when the request was filed
+        created_at -> Timestamptz,
+        /// Unique identifier of the transfer request
+        id -> Int4,
+        /// Explanation by the requester why the crate should be transferred to them
+        reason -> Text,
+        /// The user that filed the transfer request
+        requester_id -> Int4,
+        /// Date and time when the request left the pending status
+        resolved_at -> Nullable<Timestamptz>,
+        /// The status of the request (0=pending, 1=accepted by the owners, 2=rejected by the owners, 3=withdrawn by the requester, 4=approved by the crates.io team, 5=denied by the crates.io team)
+        status -> Int4,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -316,6 +525,8 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         created_at -> Timestamptz,
+        /// When the owners of dependent crates were notified about the deprecation of the crate, or NULL if they have not been notified yet
+        deprecation_notified_at -> Nullable<Timestamptz>,
         /// The `description` column of the `crates` table.
         ///
         /// Its SQL type is `Nullable<Varchar>`.
@@ -370,6 +581,12 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         repository -> Nullable<Varchar>,
+        /// Whether new versions of the crate have to be approved by a second owner before they are published
+        requires_publish_approval -> Bool,
+        /// The maintenance status of the crate (0=active, 1=passively maintained, 2=deprecated, 3=archived)
+        status -> Int4,
+        /// Name of the crate that replaces this crate, if it is deprecated or archived
+        successor -> Nullable<Varchar>,
         /// The `textsearchable_index_col` column of the `crates` table.
         ///
         /// Its SQL type is `Tsvector`.
@@ -433,6 +650,27 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Public database dumps that have been uploaded to the static file storage
+    database_dumps (id) {
+        /// For incremental dumps: the most recent full snapshot at the time the increment was created
+        base_id -> Nullable<Int4>,
+        /// Date and time at which the data of the dump was exported
+        created_at -> Timestamptz,
+        /// Unique identifier of the database dump
+        id -> Int4,
+        /// 0 = full snapshot, 1 = incremental
+        kind -> Int4,
+        /// Path of the dump archive in the static file storage
+        path -> Text,
+        /// For incremental dumps: the export time of the previous dump that the changes are relative to
+        since -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -542,6 +780,50 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// History of docs.rs rebuild requests for crate versions
+    docs_rs_rebuild_requests (id) {
+        /// Number of times the request was sent to docs.rs
+        attempts -> Int4,
+        /// Unique identifier of the rebuild request
+        id -> Int8,
+        /// Date and time of the last attempt to send the request to docs.rs
+        last_attempt_at -> Nullable<Timestamptz>,
+        /// Date and time after which a rate limited request will be retried
+        next_attempt_at -> Nullable<Timestamptz>,
+        /// Date and time when the rebuild was requested
+        requested_at -> Timestamptz,
+        /// The user who requested the rebuild
+        requested_by -> Nullable<Int4>,
+        /// Error message returned by docs.rs, if any
+        response -> Nullable<Text>,
+        /// 0 = pending, 1 = queued, 2 = rate limited, 3 = rejected, 4 = failed
+        status -> Int4,
+        /// The version for which a docs rebuild was requested
+        version_id -> Int4,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Rows that were deleted from tables included in incremental database dumps
+    dump_tombstones (id) {
+        /// Date and time when the row was deleted
+        deleted_at -> Timestamptz,
+        /// Unique identifier of the tombstone
+        id -> Int8,
+        /// Primary key columns of the deleted row
+        primary_key -> Jsonb,
+        /// Name of the table that the row was deleted from
+        table_name -> Text,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -612,6 +894,21 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Keywords that were merged into other keywords
+    keyword_aliases (alias) {
+        /// The name of the merged keyword, which is resolved to `keyword_id` from now on
+        alias -> Varchar,
+        /// Date and time when the keywords were merged
+        created_at -> Timestamptz,
+        /// The keyword that the alias was merged into
+        keyword_id -> Int4,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -683,6 +980,40 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// GitHub accounts that will replace the GitHub account of a user after a waiting period, as part of the account recovery
+    pending_github_links (user_id) {
+        /// SHA-256 hash of the secret token that is sent to the verified email address of the user to cancel the link
+        cancel_token -> Bytea,
+        /// Date and time when the link was requested
+        created_at -> Timestamptz,
+        /// Date and time after which the GitHub account can be used to log in to the account of the user
+        effective_at -> Timestamptz,
+        /// The ID of the GitHub account that will be linked to the user
+        gh_id -> Int4,
+        /// The user whose GitHub account will be replaced
+        user_id -> Int4,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Versions that were uploaded, but are waiting for the approval of a second owner
+    pending_versions (version_id) {
+        /// Date and time when the version was uploaded
+        created_at -> Timestamptz,
+        /// Date and time when the version will be deleted if it has not been approved
+        expires_at -> Timestamptz,
+        /// The version that is waiting for approval
+        version_id -> Int4,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -824,6 +1155,42 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Crates that were deleted by their owners, but are kept around until the grace period ends
+    scheduled_crate_deletions (crate_id) {
+        /// The crate that is scheduled for deletion
+        crate_id -> Int4,
+        /// Optional message left by the user who requested the deletion
+        message -> Nullable<Varchar>,
+        /// Date and time when the deletion was requested
+        requested_at -> Timestamptz,
+        /// ID of the user who requested the deletion, or NULL if the user was deleted
+        requested_by -> Nullable<Int4>,
+        /// Date and time when the crate will be deleted permanently
+        scheduled_for -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Public keys of secret scanners that are allowed to report leaked tokens via the `/api/secret-scanning/revoke` endpoint.
+    secret_scanner_keys (key_identifier) {
+        /// Date and time when the key was registered
+        created_at -> Timestamptz,
+        /// Identifier of the key, sent by the scanner in the `Secret-Scanning-Key-Identifier` header
+        key_identifier -> Varchar,
+        /// PEM-encoded ECDSA P-256 public key that is used to verify the request signatures
+        public_key -> Varchar,
+        /// Name of the scanner, used as the reporter in the notification emails, e.g. `GitLab`
+        scanner -> Varchar,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -921,6 +1288,10 @@ diesel::table! {
         created_at -> Timestamptz,
         /// Date and time when the token will expire
         expires_at -> Timestamptz,
+        /// IDs of the GitHub Trusted Publishing configs that matched when the token was issued
+        github_config_ids -> Array<Nullable<Int4>>,
+        /// IDs of the GitLab Trusted Publishing configs that matched when the token was issued
+        gitlab_config_ids -> Array<Nullable<Int4>>,
         /// SHA256 hash of the token that can be used to publish the crate
         hashed_token -> Bytea,
         /// Unique identifier of the `trustpub_tokens` row
@@ -947,6 +1318,21 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Secret tokens that give feed readers access to the personalized updates feed of a user
+    user_feed_tokens (user_id) {
+        /// Date and time when the token was generated
+        created_at -> Timestamptz,
+        /// SHA-256 hash of the secret feed token
+        token -> Bytea,
+        /// The user whose updates feed can be accessed with this token
+        user_id -> Int4,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -1098,6 +1484,23 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Results of comparing the public API of a version against the previous semver-compatible version
+    version_semver_checks (version_id) {
+        /// The previous semver-compatible version that the API was compared against
+        baseline_version_id -> Int4,
+        /// The detected changes that likely break users of the baseline version, as a JSON array
+        breaking_changes -> Jsonb,
+        /// Date and time when the check was performed
+        checked_at -> Timestamptz,
+        /// The version that was checked
+        version_id -> Int4,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -1137,6 +1540,10 @@ diesel::table! {
         created_at -> Timestamptz,
         /// Value of the `description` field in the `Cargo.toml` file of this version.
         description -> Nullable<Text>,
+        /// Cached status of the docs.rs documentation build (0 = success, 1 = failure, 2 = queued). NULL if the status has not been checked yet.
+        docs_rs_status -> Nullable<Int4>,
+        /// Time at which the docs.rs documentation build status was last checked.
+        docs_rs_status_checked_at -> Nullable<Timestamptz>,
         /// Value of the `documentation` field in the `Cargo.toml` file of this version.
         documentation -> Nullable<Text>,
         /// The `downloads` column of the `versions` table.
@@ -1245,12 +1652,20 @@ diesel::table! {
     }
 }
 
+diesel::joinable!(account_recovery_tokens -> users (user_id));
+diesel::joinable!(api_token_usage_baselines -> api_tokens (api_token_id));
+diesel::joinable!(api_token_usages -> api_tokens (api_token_id));
 diesel::joinable!(api_tokens -> users (user_id));
 diesel::joinable!(crate_downloads -> crates (crate_id));
 diesel::joinable!(crate_owner_invitations -> crates (crate_id));
 diesel::joinable!(crate_owners -> crates (crate_id));
 diesel::joinable!(crate_owners -> teams (owner_id));
 diesel::joinable!(crate_owners -> users (owner_id));
+diesel::joinable!(crate_publish_policies -> crates (crate_id));
+diesel::joinable!(crate_transfer_request_events -> crate_transfer_requests (request_id));
+diesel::joinable!(crate_transfer_request_events -> users (actor_id));
+diesel::joinable!(crate_transfer_requests -> crates (crate_id));
+diesel::joinable!(crate_transfer_requests -> users (requester_id));
 diesel::joinable!(crates_categories -> categories (category_id));
 diesel::joinable!(crates_categories -> crates (crate_id));
 diesel::joinable!(crates_keywords -> crates (crate_id));
@@ -1260,16 +1675,24 @@ diesel::joinable!(default_versions -> versions (version_id));
 diesel::joinable!(deleted_crates -> users (deleted_by));
 diesel::joinable!(dependencies -> crates (crate_id));
 diesel::joinable!(dependencies -> versions (version_id));
+diesel::joinable!(docs_rs_rebuild_requests -> users (requested_by));
+diesel::joinable!(docs_rs_rebuild_requests -> versions (version_id));
 diesel::joinable!(emails -> users (user_id));
 diesel::joinable!(follows -> crates (crate_id));
 diesel::joinable!(follows -> users (user_id));
+diesel::joinable!(keyword_aliases -> keywords (keyword_id));
 diesel::joinable!(oauth_github -> users (user_id));
+diesel::joinable!(pending_github_links -> users (user_id));
+diesel::joinable!(pending_versions -> versions (version_id));
 diesel::joinable!(publish_limit_buckets -> users (user_id));
 diesel::joinable!(publish_rate_overrides -> users (user_id));
 diesel::joinable!(readme_renderings -> versions (version_id));
 diesel::joinable!(recent_crate_downloads -> crates (crate_id));
+diesel::joinable!(scheduled_crate_deletions -> crates (crate_id));
+diesel::joinable!(scheduled_crate_deletions -> users (requested_by));
 diesel::joinable!(trustpub_configs_github -> crates (crate_id));
 diesel::joinable!(trustpub_configs_gitlab -> crates (crate_id));
+diesel::joinable!(user_feed_tokens -> users (user_id));
 diesel::joinable!(version_downloads -> versions (version_id));
 diesel::joinable!(version_owner_actions -> api_tokens (api_token_id));
 diesel::joinable!(version_owner_actions -> users (user_id));
@@ -1279,38 +1702,57 @@ diesel::joinable!(versions -> users (published_by));
 diesel::joinable!(versions_published_by -> versions (version_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    account_lock_events,
+    account_recovery_tokens,
+    api_token_usage_baselines,
+    api_token_usages,
     api_tokens,
     background_jobs,
+    blocked_traffic_rules,
     categories,
+    category_proposals,
     cloudfront_invalidation_queue,
     crate_downloads,
     crate_owner_invitations,
     crate_owners,
+    crate_publish_policies,
+    crate_transfer_request_events,
+    crate_transfer_requests,
     crates,
     crates_categories,
     crates_keywords,
+    database_dumps,
     default_versions,
     deleted_crates,
     dependencies,
+    docs_rs_rebuild_requests,
+    dump_tombstones,
     emails,
     follows,
+    keyword_aliases,
     keywords,
     metadata,
     oauth_github,
+    pending_github_links,
+    pending_versions,
     processed_log_files,
     publish_limit_buckets,
     publish_rate_overrides,
     readme_renderings,
     recent_crate_downloads,
     reserved_crate_names,
+    scheduled_crate_deletions,
+    secret_scanner_keys,
     teams,
     trustpub_configs_github,
     trustpub_configs_gitlab,
     trustpub_tokens,
     trustpub_used_jtis,
+    user_feed_tokens,
     users,
     version_downloads,
     version_owner_actions,
+    version_semver_checks,
     versions,
     versions_published_by,
 );
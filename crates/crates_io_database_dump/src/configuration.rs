@@ -15,7 +15,9 @@ pub enum ColumnVisibility {
 /// and should list all tables the current tables refers to with foreign key
 /// constraints on public columns. The `filter` field is a valid SQL expression
 /// used in a `WHERE` clause to filter the rows of the table. The `columns`
-/// field maps column names to their respective visibilities.
+/// field maps column names to their respective visibilities. The
+/// `incremental` field determines how the table is included in incremental
+/// dumps.
 #[derive(Clone, Debug, Default, Deserialize)]
 pub struct TableConfig {
     #[serde(default)]
@@ -24,6 +26,18 @@ pub struct TableConfig {
     pub columns: BTreeMap<String, ColumnVisibility>,
     #[serde(default)]
     pub column_defaults: BTreeMap<String, String>,
+    pub incremental: Option<IncrementalConfig>,
+}
+
+/// Configuration for tables that only include changed rows in incremental
+/// dumps. The `primary_key` field lists the primary key columns, which are
+/// used to apply tombstones and to update existing rows on import. The
+/// `changed_at` field is a valid SQL expression returning the time at which
+/// a row was last changed.
+#[derive(Clone, Debug, Deserialize)]
+pub struct IncrementalConfig {
+    pub primary_key: Vec<String>,
+    pub changed_at: String,
 }
 
 /// Maps table names to the respective configurations. Used to load `dump_db.toml`.
@@ -1,5 +1,6 @@
-use crate::configuration::{ColumnVisibility, TableConfig, VisibilityConfig};
+use crate::configuration::{ColumnVisibility, IncrementalConfig, TableConfig, VisibilityConfig};
 use anyhow::Context;
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::Serialize;
 use std::{fs::File, path::Path};
 use tracing::debug;
@@ -11,6 +12,21 @@ pub fn gen_scripts(export_script: &Path, import_script: &Path) -> anyhow::Result
     config.gen_psql_scripts(export_sql, import_sql)
 }
 
+/// Generate the scripts to export and import the changes since `since`.
+///
+/// The import script applies the changes to a database that contains the
+/// data of the previous dump.
+pub fn gen_incremental_scripts(
+    export_script: &Path,
+    import_script: &Path,
+    since: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    let config = VisibilityConfig::get();
+    let export_sql = File::create(export_script).context("Failed to create export script file")?;
+    let import_sql = File::create(import_script).context("Failed to create import script file")?;
+    config.gen_incremental_psql_scripts(export_sql, import_sql, since)
+}
+
 /// Subset of the configuration data to be passed on to the Handlbars template.
 #[derive(Debug, Serialize)]
 struct HandlebarsTableContext<'a> {
@@ -18,6 +34,20 @@ struct HandlebarsTableContext<'a> {
     filter: Option<String>,
     columns: String,
     column_defaults: Vec<ColumnDefault<'a>>,
+    incremental: Option<IncrementalContext<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct IncrementalContext<'a> {
+    changed_at: &'a str,
+    /// The quoted primary key columns, e.g. `"crate_id", "owner_id"`.
+    primary_key: String,
+    /// Builds the tombstone of a row, e.g. `jsonb_build_object('id', "id")`.
+    key_object: String,
+    /// Matches the primary key of the row `t` to the tombstone `k`.
+    key_match: String,
+    /// Updates the public non-key columns of existing rows on import.
+    update_set: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -27,12 +57,17 @@ struct ColumnDefault<'a> {
 }
 
 impl TableConfig {
-    fn template_context<'a>(&'a self, name: &'a str) -> Option<HandlebarsTableContext<'a>> {
-        let columns = self
-            .columns
+    pub(crate) fn public_columns(&self) -> impl Iterator<Item = &str> {
+        self.columns
             .iter()
             .filter(|&(_, &vis)| vis == ColumnVisibility::Public)
-            .map(|(col, _)| format!("\"{col}\""))
+            .map(|(col, _)| col.as_str())
+    }
+
+    fn template_context<'a>(&'a self, name: &'a str) -> Option<HandlebarsTableContext<'a>> {
+        let columns = self
+            .public_columns()
+            .map(|col| format!("\"{col}\""))
             .collect::<Vec<String>>()
             .join(", ");
         if columns.is_empty() {
@@ -47,14 +82,54 @@ impl TableConfig {
                     value: v.as_str(),
                 })
                 .collect();
+            let incremental = self
+                .incremental
+                .as_ref()
+                .map(|incremental| self.incremental_context(incremental));
             Some(HandlebarsTableContext {
                 name,
                 filter,
                 columns,
                 column_defaults,
+                incremental,
             })
         }
     }
+
+    fn incremental_context<'a>(&self, config: &'a IncrementalConfig) -> IncrementalContext<'a> {
+        let primary_key = config
+            .primary_key
+            .iter()
+            .map(|col| format!("\"{col}\""))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let key_object = config
+            .primary_key
+            .iter()
+            .map(|col| format!("'{col}', \"{col}\""))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let key_object = format!("jsonb_build_object({key_object})");
+        let key_match = config
+            .primary_key
+            .iter()
+            .map(|col| format!("t.\"{col}\" = k.\"{col}\""))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let update_set = self
+            .public_columns()
+            .filter(|col| !config.primary_key.iter().any(|key| key == col))
+            .map(|col| format!("\"{col}\" = EXCLUDED.\"{col}\""))
+            .collect::<Vec<_>>()
+            .join(", ");
+        IncrementalContext {
+            changed_at: &config.changed_at,
+            primary_key,
+            key_object,
+            key_match,
+            update_set,
+        }
+    }
 }
 
 /// Subset of the configuration data to be passed on to the Handlbars template.
@@ -63,6 +138,15 @@ struct TemplateContext<'a> {
     tables: Vec<HandlebarsTableContext<'a>>,
 }
 
+/// Subset of the configuration data to be passed on to the templates of
+/// incremental dumps.
+#[derive(Debug, Serialize)]
+struct IncrementalTemplateContext<'a> {
+    tables: Vec<HandlebarsTableContext<'a>>,
+    since: String,
+    tombstones_query: String,
+}
+
 impl VisibilityConfig {
     fn template_context(&self) -> TemplateContext<'_> {
         let tables = self
@@ -73,46 +157,98 @@ impl VisibilityConfig {
         TemplateContext { tables }
     }
 
-    fn gen_psql_scripts<W>(&self, mut export_writer: W, mut import_writer: W) -> anyhow::Result<()>
+    fn incremental_template_context(&self, since: DateTime<Utc>) -> IncrementalTemplateContext<'_> {
+        let since = since.to_rfc3339_opts(SecondsFormat::Micros, true);
+        let tables = self.template_context().tables;
+
+        // Rows of filtered tables that changed, but no longer match the
+        // filter, have to be removed on import just like deleted rows.
+        let filtered_queries = tables.iter().filter_map(|table| {
+            let incremental = table.incremental.as_ref()?;
+            let filter = table.filter.as_ref()?;
+            Some(format!(
+                "SELECT '{name}' AS table_name, {key_object} AS primary_key \
+                 FROM \"{name}\" WHERE ({changed_at}) > '{since}' AND NOT ({filter})",
+                name = table.name,
+                key_object = incremental.key_object,
+                changed_at = incremental.changed_at,
+            ))
+        });
+
+        let tombstones_query = std::iter::once(format!(
+            "SELECT table_name, primary_key FROM \"dump_tombstones\" WHERE deleted_at > '{since}'"
+        ))
+        .chain(filtered_queries)
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ");
+
+        IncrementalTemplateContext {
+            tables,
+            since,
+            tombstones_query,
+        }
+    }
+
+    fn gen_psql_scripts<W>(&self, export_writer: W, import_writer: W) -> anyhow::Result<()>
     where
         W: std::io::Write,
     {
-        use minijinja::Environment;
-
-        let mut env = Environment::new();
-        env.add_template("dump-export.sql", include_str!("dump-export.sql.j2"))
-            .context("Failed to load dump-export.sql.j2 template")?;
-        env.add_template("dump-import.sql", include_str!("dump-import.sql.j2"))
-            .context("Failed to load dump-import.sql.j2 template")?;
-
         let context = self.template_context();
+        let export_template = include_str!("dump-export.sql.j2");
+        render_psql_script("dump-export.sql", export_template, &context, export_writer)?;
+        let import_template = include_str!("dump-import.sql.j2");
+        render_psql_script("dump-import.sql", import_template, &context, import_writer)
+    }
 
-        debug!("Rendering dump-export.sql file…");
-        let export_sql = env
-            .get_template("dump-export.sql")
-            .unwrap()
-            .render(&context)
-            .context("Failed to render dump-export.sql file")?;
+    fn gen_incremental_psql_scripts<W>(
+        &self,
+        export_writer: W,
+        import_writer: W,
+        since: DateTime<Utc>,
+    ) -> anyhow::Result<()>
+    where
+        W: std::io::Write,
+    {
+        let context = self.incremental_template_context(since);
+        let name = "dump-export-incremental.sql";
+        let export_template = include_str!("dump-export-incremental.sql.j2");
+        render_psql_script(name, export_template, &context, export_writer)?;
+        let name = "dump-import-incremental.sql";
+        let import_template = include_str!("dump-import-incremental.sql.j2");
+        render_psql_script(name, import_template, &context, import_writer)
+    }
+}
 
-        debug!("Rendering dump-import.sql file…");
-        let import_sql = env
-            .get_template("dump-import.sql")
-            .unwrap()
-            .render(&context)
-            .context("Failed to render dump-import.sql file")?;
+/// Render the `template` and write it to the `writer`.
+fn render_psql_script<W, C>(
+    name: &str,
+    template: &str,
+    context: &C,
+    mut writer: W,
+) -> anyhow::Result<()>
+where
+    W: std::io::Write,
+    C: Serialize,
+{
+    use minijinja::Environment;
 
-        debug!("Writing dump-export.sql file…");
-        export_writer
-            .write_all(export_sql.as_bytes())
-            .context("Failed to write dump-export.sql file")?;
+    let mut env = Environment::new();
+    env.add_template(name, template)
+        .with_context(|| format!("Failed to load {name}.j2 template"))?;
 
-        debug!("Writing dump-import.sql file…");
-        import_writer
-            .write_all(import_sql.as_bytes())
-            .context("Failed to write dump-import.sql file")?;
+    debug!("Rendering {name} file…");
+    let sql = env
+        .get_template(name)
+        .unwrap()
+        .render(context)
+        .with_context(|| format!("Failed to render {name} file"))?;
 
-        Ok(())
-    }
+    debug!("Writing {name} file…");
+    writer
+        .write_all(sql.as_bytes())
+        .with_context(|| format!("Failed to write {name} file"))?;
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -168,6 +304,48 @@ mod tests {
         );
     }
 
+    /// Test whether every incrementally dumped table has a trigger that
+    /// records tombstones with the configured primary key columns.
+    #[tokio::test]
+    async fn check_tombstone_triggers() {
+        use diesel::sql_types::{Array, Text};
+        use std::collections::BTreeMap;
+
+        #[derive(QueryableByName)]
+        struct Trigger {
+            #[diesel(sql_type = Text)]
+            table_name: String,
+            #[diesel(sql_type = Array<Text>)]
+            primary_key: Vec<String>,
+        }
+
+        let test_db = TestDatabase::new();
+        let mut conn = test_db.async_connect().await;
+
+        let triggers: Vec<Trigger> = diesel::sql_query(
+            r"SELECT tgrelid::regclass::text AS table_name,
+                array_remove(string_to_array(encode(tgargs, 'escape'), E'\\000'), '') AS primary_key
+            FROM pg_trigger
+            WHERE tgfoid = 'record_dump_tombstone'::regproc",
+        )
+        .load(&mut conn)
+        .await
+        .unwrap();
+
+        let db_triggers = triggers
+            .into_iter()
+            .map(|trigger| (trigger.table_name, trigger.primary_key))
+            .collect::<BTreeMap<_, _>>();
+
+        let config_triggers = VisibilityConfig::get()
+            .0
+            .into_iter()
+            .filter_map(|(table, config)| Some((table, config.incremental?.primary_key)))
+            .collect::<BTreeMap<_, _>>();
+
+        assert_eq!(db_triggers, config_triggers);
+    }
+
     mod information_schema {
         use diesel::table;
 
@@ -10,9 +10,11 @@ use zip::write::SimpleFileOptions;
 
 mod configuration;
 mod gen_scripts;
+mod parquet_export;
 
 pub use configuration::VisibilityConfig;
-pub use gen_scripts::gen_scripts;
+pub use gen_scripts::{gen_incremental_scripts, gen_scripts};
+pub use parquet_export::export_parquet;
 
 /// Manage the export directory.
 ///
@@ -52,6 +54,26 @@ impl DumpDirectory {
             .context("Failed to create database dump")
     }
 
+    /// Populate the directory with the changes since the dump that was
+    /// started at `since`.
+    pub fn populate_incremental(
+        &self,
+        database_url: &str,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<()> {
+        self.add_readme()
+            .context("Failed to write README.md file")?;
+
+        self.add_incremental_metadata(since)
+            .context("Failed to write metadata.json file")?;
+
+        self.dump_schema(database_url)
+            .context("Failed to generate schema.sql file")?;
+
+        self.dump_db_incremental(database_url, since)
+            .context("Failed to create incremental database dump")
+    }
+
     fn add_readme(&self) -> anyhow::Result<()> {
         use std::io::Write;
 
@@ -63,13 +85,24 @@ impl DumpDirectory {
     }
 
     fn add_metadata(&self) -> anyhow::Result<()> {
+        self.write_metadata(None)
+    }
+
+    fn add_incremental_metadata(&self, since: chrono::DateTime<chrono::Utc>) -> anyhow::Result<()> {
+        self.write_metadata(Some(since))
+    }
+
+    fn write_metadata(&self, since: Option<chrono::DateTime<chrono::Utc>>) -> anyhow::Result<()> {
         #[derive(Serialize)]
         struct Metadata<'a> {
             timestamp: &'a chrono::DateTime<chrono::Utc>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            since: Option<chrono::DateTime<chrono::Utc>>,
             crates_io_commit: String,
         }
         let metadata = Metadata {
             timestamp: &self.timestamp,
+            since,
             crates_io_commit: crates_io_version::commit()
                 .ok()
                 .flatten()
@@ -121,6 +154,35 @@ impl DumpDirectory {
 
         run_psql(&export_script, database_url)
     }
+
+    pub fn dump_db_incremental(
+        &self,
+        database_url: &str,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<()> {
+        debug!("Generating incremental export.sql and import.sql files…");
+        let export_script = self.path().join("export.sql");
+        let import_script = self.path().join("import.sql");
+        gen_incremental_scripts(&export_script, &import_script, since)
+            .context("Failed to generate incremental export/import scripts")?;
+
+        debug!("Filling data folder…");
+        fs::create_dir(self.path().join("data")).context("Failed to create `data` directory")?;
+
+        run_psql(&export_script, database_url)
+    }
+
+    /// Convert the CSV files exported by [`Self::populate`] into one Parquet
+    /// file per table in the `parquet` directory. The Parquet files are not
+    /// part of the archives created by [`create_archives()`].
+    pub fn export_parquet(&self, database_url: &str) -> anyhow::Result<Vec<PathBuf>> {
+        let directory = self.path().join("parquet");
+        fs::create_dir(&directory).context("Failed to create `parquet` directory")?;
+
+        let data_dir = self.path().join("data");
+        export_parquet(&data_dir, &directory, database_url)
+            .context("Failed to export Parquet files")
+    }
 }
 
 pub fn run_psql(script: &Path, database_url: &str) -> anyhow::Result<()> {
@@ -193,11 +255,13 @@ pub fn create_archives(export_dir: &Path, tarball_prefix: &Path) -> anyhow::Resu
     }
 
     // Append topologically sorted tables to make it possible to pipeline
-    // importing with gz extraction.
+    // importing with gz extraction. The tombstones of incremental dumps are
+    // imported first.
 
     debug!("Sorting database tables");
     let visibility_config = VisibilityConfig::get();
     let sorted_tables = visibility_config.topological_sort();
+    let sorted_files = std::iter::once("tombstones").chain(sorted_tables);
 
     let path = tarball_prefix.join("data");
     debug!("Appending `data` directory to tarball…");
@@ -206,7 +270,7 @@ pub fn create_archives(export_dir: &Path, tarball_prefix: &Path) -> anyhow::Resu
     debug!("Appending `data` directory to zip file…");
     zip.add_directory("data", SimpleFileOptions::default())?;
 
-    for table in sorted_tables {
+    for table in sorted_files {
         let csv_path = export_dir.join("data").join(table).with_extension("csv");
         if csv_path.exists() {
             let name = tarball_prefix
@@ -236,6 +300,9 @@ pub fn create_archives(export_dir: &Path, tarball_prefix: &Path) -> anyhow::Resu
 mod tests {
     use super::*;
     use crates_io_test_db::TestDatabase;
+    use diesel::prelude::*;
+    use diesel::sql_query;
+    use diesel_async::RunQueryDsl;
     use flate2::read::GzDecoder;
     use insta::{assert_debug_snapshot, assert_snapshot};
     use std::io::BufReader;
@@ -311,6 +378,129 @@ mod tests {
         // TODO: Consistency checks on the re-imported data?
     }
 
+    #[tokio::test]
+    async fn dump_db_and_reimport_incremental_dump() {
+        let db_one = TestDatabase::new();
+        let mut conn_one = db_one.async_connect().await;
+
+        sql_query("INSERT INTO crates (name, description) VALUES ('foo', 'old'), ('bar', 'bar')")
+            .execute(&mut conn_one)
+            .await
+            .unwrap();
+
+        let base = DumpDirectory::create().unwrap();
+        base.populate(db_one.url()).unwrap();
+
+        let db_two = TestDatabase::empty();
+        let mut conn_two = db_two.async_connect().await;
+
+        run_psql(&base.path().join("schema.sql"), db_two.url()).unwrap();
+        run_psql(&base.path().join("import.sql"), db_two.url()).unwrap();
+
+        sql_query("UPDATE crates SET description = 'new' WHERE name = 'foo'")
+            .execute(&mut conn_one)
+            .await
+            .unwrap();
+        sql_query("DELETE FROM crates WHERE name = 'bar'")
+            .execute(&mut conn_one)
+            .await
+            .unwrap();
+        sql_query("INSERT INTO crates (name, description) VALUES ('baz', 'baz')")
+            .execute(&mut conn_one)
+            .await
+            .unwrap();
+
+        let increment = DumpDirectory::create().unwrap();
+        increment
+            .populate_incremental(db_one.url(), base.timestamp)
+            .unwrap();
+
+        let tombstones = fs::read_to_string(increment.path().join("data/tombstones.csv")).unwrap();
+        assert_snapshot!(tombstones, @r#"
+        table_name,primary_key
+        crates,"{""id"": 2}"
+        "#);
+
+        run_psql(&increment.path().join("import.sql"), db_two.url()).unwrap();
+
+        #[derive(Debug, PartialEq, QueryableByName)]
+        struct Row {
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            name: String,
+            #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+            description: Option<String>,
+            #[diesel(sql_type = diesel::sql_types::BigInt)]
+            downloads: i64,
+        }
+
+        let query = "SELECT name, description, downloads FROM crates \
+                     JOIN crate_downloads ON crate_downloads.crate_id = crates.id ORDER BY name";
+
+        let expected: Vec<Row> = sql_query(query).load(&mut conn_one).await.unwrap();
+        let actual: Vec<Row> = sql_query(query).load(&mut conn_two).await.unwrap();
+        assert_eq!(actual, expected);
+        assert_debug_snapshot!(actual.iter().map(|row| &row.name).collect::<Vec<_>>(), @r#"
+        [
+            "baz",
+            "foo",
+        ]
+        "#);
+    }
+
+    #[tokio::test]
+    async fn incremental_dump_includes_download_changes() {
+        let db_one = TestDatabase::new();
+        let mut conn_one = db_one.async_connect().await;
+
+        sql_query("INSERT INTO crates (id, name) VALUES (1, 'foo')")
+            .execute(&mut conn_one)
+            .await
+            .unwrap();
+        sql_query(
+            "INSERT INTO versions (id, crate_id, num, num_no_build, checksum, crate_size) \
+             VALUES (1, 1, '1.0.0', '1.0.0', repeat('0', 64), 0)",
+        )
+        .execute(&mut conn_one)
+        .await
+        .unwrap();
+
+        let base = DumpDirectory::create().unwrap();
+        base.populate(db_one.url()).unwrap();
+
+        let db_two = TestDatabase::empty();
+        let mut conn_two = db_two.async_connect().await;
+
+        run_psql(&base.path().join("schema.sql"), db_two.url()).unwrap();
+        run_psql(&base.path().join("import.sql"), db_two.url()).unwrap();
+
+        // Counting downloads doesn't change `versions.updated_at`
+        sql_query("INSERT INTO version_downloads (version_id, downloads) VALUES (1, 42)")
+            .execute(&mut conn_one)
+            .await
+            .unwrap();
+        sql_query("UPDATE versions SET downloads = 42 WHERE id = 1")
+            .execute(&mut conn_one)
+            .await
+            .unwrap();
+
+        let increment = DumpDirectory::create().unwrap();
+        increment
+            .populate_incremental(db_one.url(), base.timestamp)
+            .unwrap();
+
+        run_psql(&increment.path().join("import.sql"), db_two.url()).unwrap();
+
+        #[derive(Debug, PartialEq, QueryableByName)]
+        struct Row {
+            #[diesel(sql_type = diesel::sql_types::Integer)]
+            downloads: i32,
+        }
+
+        let query = "SELECT downloads FROM versions WHERE id = 1";
+        let actual: Vec<Row> = sql_query(query).load(&mut conn_two).await.unwrap();
+        assert_eq!(actual, vec![Row { downloads: 42 }]);
+    }
+
     #[test]
     fn test_sql_scripts() {
         let db = TestDatabase::new();
@@ -323,4 +513,111 @@ mod tests {
             assert_snapshot!(content);
         });
     }
+
+    #[test]
+    fn test_incremental_sql_scripts() {
+        let directory = tempfile::tempdir().unwrap();
+        let export_script = directory.path().join("export.sql");
+        let import_script = directory.path().join("import.sql");
+
+        let since = "2024-01-01T00:00:00Z".parse().unwrap();
+        gen_incremental_scripts(&export_script, &import_script, since).unwrap();
+
+        assert_snapshot!(
+            "incremental_export.sql",
+            fs::read_to_string(export_script).unwrap()
+        );
+        assert_snapshot!(
+            "incremental_import.sql",
+            fs::read_to_string(import_script).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn dump_db_to_parquet() {
+        use arrow_array::Array;
+        use arrow_array::cast::AsArray;
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let db = TestDatabase::new();
+        let mut conn = db.async_connect().await;
+
+        sql_query("INSERT INTO crates (name, description) VALUES ('foo', ''), ('bar', NULL)")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let directory = DumpDirectory::create().unwrap();
+        directory.populate(db.url()).unwrap();
+        let paths = directory.export_parquet(db.url()).unwrap();
+
+        let parquet_dir = directory.path().join("parquet");
+        let crates_path = parquet_dir.join("crates.parquet");
+        assert!(paths.contains(&crates_path));
+        assert!(!parquet_dir.join("columns.csv").exists());
+
+        let file = File::open(crates_path).unwrap();
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+
+        let schema = builder.schema().clone();
+        let field_types = schema
+            .fields()
+            .iter()
+            .filter(|field| {
+                ["id", "name", "description", "created_at"].contains(&field.name().as_str())
+            })
+            .map(|field| {
+                format!(
+                    "{}: {} (nullable: {})",
+                    field.name(),
+                    field.data_type(),
+                    field.is_nullable()
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_debug_snapshot!(field_types, @r#"
+        [
+            "created_at: Timestamp(Microsecond, Some(\"UTC\")) (nullable: false)",
+            "description: Utf8 (nullable: true)",
+            "id: Int32 (nullable: false)",
+            "name: Utf8 (nullable: false)",
+        ]
+        "#);
+
+        let batches = builder
+            .build()
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+
+        let names = batch.column_by_name("name").unwrap().as_string::<i32>();
+        let descriptions = batch
+            .column_by_name("description")
+            .unwrap()
+            .as_string::<i32>();
+        let rows = (0..batch.num_rows())
+            .map(|i| {
+                (
+                    names.value(i),
+                    descriptions.is_valid(i).then(|| descriptions.value(i)),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_debug_snapshot!(rows, @r#"
+        [
+            (
+                "foo",
+                Some(
+                    "",
+                ),
+            ),
+            (
+                "bar",
+                None,
+            ),
+        ]
+        "#);
+    }
 }
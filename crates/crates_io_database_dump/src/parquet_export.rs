@@ -0,0 +1,530 @@
+//! Conversion of the public database data into Parquet files.
+//!
+//! The Parquet files are built from the CSV files of the database dump, so
+//! that both contain the data of the same database snapshot. The schema of
+//! the Parquet files is derived from the public columns in `dump-db.toml`
+//! and the Postgres types of these columns. Types without a direct Arrow
+//! equivalent, like `jsonb` or `ltree`, are stored as strings.
+
+use crate::configuration::VisibilityConfig;
+use crate::run_psql;
+use anyhow::{Context, anyhow, bail};
+use arrow_array::builder::{
+    BooleanBuilder, Date32Builder, Float32Builder, Float64Builder, Int16Builder, Int32Builder,
+    Int64Builder, ListBuilder, StringBuilder, TimestampMicrosecondBuilder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::debug;
+
+/// The number of rows that are written to the Parquet files at once.
+const BATCH_SIZE: usize = 8192;
+
+/// Exports the types of all columns. The types are part of the schema, so
+/// they don't have to be exported in the same transaction as the data.
+const COLUMN_TYPES_SCRIPT: &str = r"\copy (SELECT table_name, column_name, udt_name, is_nullable FROM information_schema.columns WHERE table_schema = 'public') TO 'columns.csv' WITH CSV HEADER";
+
+/// Convert the CSV files of a database dump in `data_dir` into one Parquet
+/// file per table.
+///
+/// The files are written to `directory`, which has to exist already. Returns
+/// the paths of the Parquet files in the topological order of the tables.
+pub fn export_parquet(
+    data_dir: &Path,
+    directory: &Path,
+    database_url: &str,
+) -> anyhow::Result<Vec<PathBuf>> {
+    debug!("Exporting column types…");
+    let columns_script = directory.join("columns.sql");
+    fs::write(&columns_script, COLUMN_TYPES_SCRIPT)
+        .context("Failed to write column types script")?;
+    run_psql(&columns_script, database_url)?;
+
+    let columns_path = directory.join("columns.csv");
+    let column_types = load_column_types(&columns_path).context("Failed to load column types")?;
+    fs::remove_file(&columns_path)?;
+    fs::remove_file(&columns_script)?;
+
+    let config = VisibilityConfig::get();
+    let mut paths = Vec::new();
+    for table in config.topological_sort() {
+        let columns = config.0[table].public_columns().collect::<Vec<_>>();
+        if columns.is_empty() {
+            continue;
+        }
+
+        let schema = table_schema(table, &columns, &column_types)?;
+        let csv_path = data_dir.join(table).with_extension("csv");
+        let parquet_path = directory.join(table).with_extension("parquet");
+
+        debug!(?parquet_path, "Writing Parquet file…");
+        write_parquet(&csv_path, &parquet_path, schema)
+            .with_context(|| format!("Failed to write Parquet file for `{table}` table"))?;
+
+        paths.push(parquet_path);
+    }
+
+    Ok(paths)
+}
+
+#[derive(Debug, Deserialize)]
+struct ColumnType {
+    table_name: String,
+    column_name: String,
+    udt_name: String,
+    is_nullable: String,
+}
+
+fn load_column_types(path: &Path) -> anyhow::Result<HashMap<(String, String), ColumnType>> {
+    let mut reader = csv::Reader::from_path(path)?;
+    reader
+        .deserialize::<ColumnType>()
+        .map(|row| {
+            let row = row?;
+            Ok(((row.table_name.clone(), row.column_name.clone()), row))
+        })
+        .collect()
+}
+
+fn table_schema(
+    table: &str,
+    columns: &[&str],
+    column_types: &HashMap<(String, String), ColumnType>,
+) -> anyhow::Result<SchemaRef> {
+    let fields = columns
+        .iter()
+        .map(|&column| {
+            let key = (table.to_owned(), column.to_owned());
+            let column_type = column_types
+                .get(&key)
+                .ok_or_else(|| anyhow!("Column {table}.{column} does not exist in the database"))?;
+
+            let data_type = data_type(&column_type.udt_name);
+            let nullable = column_type.is_nullable == "YES";
+            Ok(Field::new(column, data_type, nullable))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(Arc::new(Schema::new(fields)))
+}
+
+/// Maps a Postgres type to the Arrow type that it is stored as.
+fn data_type(udt_name: &str) -> DataType {
+    match udt_name {
+        "bool" => DataType::Boolean,
+        "int2" => DataType::Int16,
+        "int4" => DataType::Int32,
+        "int8" => DataType::Int64,
+        "float4" => DataType::Float32,
+        "float8" => DataType::Float64,
+        "date" => DataType::Date32,
+        "timestamp" => DataType::Timestamp(TimeUnit::Microsecond, None),
+        "timestamptz" => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+        "_text" | "_varchar" => DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))),
+        _ => DataType::Utf8,
+    }
+}
+
+/// Converts a CSV file of the database dump into a Parquet file. The
+/// columns of the CSV file have to match the fields of the `schema`.
+fn write_parquet(csv_path: &Path, parquet_path: &Path, schema: SchemaRef) -> anyhow::Result<()> {
+    let mut reader = PgCsvReader::new(BufReader::new(File::open(csv_path)?));
+
+    let header = reader.read_record()?.context("Missing CSV header")?;
+    let header = header.into_iter().flatten().collect::<Vec<_>>();
+    let field_names = schema.fields().iter().map(|field| field.name());
+    if !header.iter().eq(field_names) {
+        bail!("Unexpected CSV columns: {header:?}");
+    }
+
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let file = File::create(parquet_path)?;
+    let mut writer = ArrowWriter::try_new(file, schema.clone(), Some(properties))?;
+
+    let mut builders = schema
+        .fields()
+        .iter()
+        .map(|field| ColumnBuilder::new(field.data_type()))
+        .collect::<Vec<_>>();
+
+    let mut num_rows = 0;
+    while let Some(values) = reader.read_record()? {
+        if values.len() != builders.len() {
+            bail!("Expected {} values, found {}", builders.len(), values.len());
+        }
+
+        for (builder, value) in builders.iter_mut().zip(&values) {
+            builder.append(value.as_deref())?;
+        }
+
+        num_rows += 1;
+        if num_rows == BATCH_SIZE {
+            writer.write(&finish_batch(&schema, &mut builders)?)?;
+            num_rows = 0;
+        }
+    }
+
+    if num_rows > 0 {
+        writer.write(&finish_batch(&schema, &mut builders)?)?;
+    }
+
+    writer.close()?;
+    Ok(())
+}
+
+/// Reads the records of a CSV file written by the `COPY ... WITH CSV`
+/// command of Postgres.
+///
+/// Unlike the `csv` crate, this distinguishes between `NULL` values, which
+/// Postgres writes as unquoted empty fields, and empty strings, which are
+/// always quoted.
+struct PgCsvReader<R> {
+    reader: R,
+    line: String,
+}
+
+impl<R: BufRead> PgCsvReader<R> {
+    fn new(reader: R) -> Self {
+        let line = String::new();
+        Self { reader, line }
+    }
+
+    fn read_record(&mut self) -> anyhow::Result<Option<Vec<Option<String>>>> {
+        self.line.clear();
+
+        // Quoted fields can contain line breaks, so lines are read until all
+        // quotes are closed again.
+        loop {
+            if self.reader.read_line(&mut self.line)? == 0 {
+                if self.line.is_empty() {
+                    return Ok(None);
+                }
+                bail!("Unexpected end of CSV file");
+            }
+            if self.line.matches('"').count().is_multiple_of(2) {
+                break;
+            }
+        }
+
+        let record = self.line.strip_suffix('\n').unwrap_or(&self.line);
+        parse_csv_record(record).map(Some)
+    }
+}
+
+fn parse_csv_record(record: &str) -> anyhow::Result<Vec<Option<String>>> {
+    let mut fields = Vec::new();
+    let mut chars = record.chars().peekable();
+    loop {
+        if chars.next_if_eq(&'"').is_some() {
+            let mut field = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') if chars.next_if_eq(&'"').is_some() => field.push('"'),
+                    Some('"') => break,
+                    Some(c) => field.push(c),
+                    None => bail!("Unterminated quoted CSV field"),
+                }
+            }
+            fields.push(Some(field));
+        } else {
+            let field = std::iter::from_fn(|| chars.next_if(|&c| c != ',')).collect::<String>();
+            fields.push((!field.is_empty()).then_some(field));
+        }
+
+        match chars.next() {
+            Some(',') => continue,
+            Some(c) => bail!("Unexpected character after CSV field: {c:?}"),
+            None => return Ok(fields),
+        }
+    }
+}
+
+/// Parses a one-dimensional Postgres array literal, like `{foo,"bar baz",NULL}`.
+fn parse_array(value: &str) -> anyhow::Result<Vec<Option<String>>> {
+    let inner = value
+        .strip_prefix('{')
+        .and_then(|value| value.strip_suffix('}'))
+        .ok_or_else(|| anyhow!("Invalid array literal: {value}"))?;
+
+    let mut elements = Vec::new();
+    if inner.is_empty() {
+        return Ok(elements);
+    }
+
+    let mut chars = inner.chars().peekable();
+    loop {
+        if chars.next_if_eq(&'"').is_some() {
+            let mut element = String::new();
+            loop {
+                match chars.next() {
+                    Some('\\') => element.extend(chars.next()),
+                    Some('"') => break,
+                    Some(c) => element.push(c),
+                    None => bail!("Invalid array literal: {value}"),
+                }
+            }
+            elements.push(Some(element));
+        } else {
+            let element = std::iter::from_fn(|| chars.next_if(|&c| c != ',')).collect::<String>();
+            let is_null = element.eq_ignore_ascii_case("NULL");
+            elements.push((!is_null).then_some(element));
+        }
+
+        match chars.next() {
+            Some(',') => continue,
+            Some(_) => bail!("Invalid array literal: {value}"),
+            None => return Ok(elements),
+        }
+    }
+}
+
+fn finish_batch(schema: &SchemaRef, builders: &mut [ColumnBuilder]) -> anyhow::Result<RecordBatch> {
+    let columns = builders.iter_mut().map(ColumnBuilder::finish).collect();
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+enum ColumnBuilder {
+    Boolean(BooleanBuilder),
+    Int16(Int16Builder),
+    Int32(Int32Builder),
+    Int64(Int64Builder),
+    Float32(Float32Builder),
+    Float64(Float64Builder),
+    Date32(Date32Builder),
+    Timestamp(TimestampMicrosecondBuilder),
+    Utf8(StringBuilder),
+    Utf8List(ListBuilder<StringBuilder>),
+}
+
+impl ColumnBuilder {
+    fn new(data_type: &DataType) -> Self {
+        match data_type {
+            DataType::Boolean => Self::Boolean(BooleanBuilder::new()),
+            DataType::Int16 => Self::Int16(Int16Builder::new()),
+            DataType::Int32 => Self::Int32(Int32Builder::new()),
+            DataType::Int64 => Self::Int64(Int64Builder::new()),
+            DataType::Float32 => Self::Float32(Float32Builder::new()),
+            DataType::Float64 => Self::Float64(Float64Builder::new()),
+            DataType::Date32 => Self::Date32(Date32Builder::new()),
+            DataType::Timestamp(_, timezone) => {
+                let builder = TimestampMicrosecondBuilder::new();
+                Self::Timestamp(builder.with_timezone_opt(timezone.clone()))
+            }
+            DataType::List(_) => Self::Utf8List(ListBuilder::new(StringBuilder::new())),
+            _ => Self::Utf8(StringBuilder::new()),
+        }
+    }
+
+    fn append(&mut self, value: Option<&str>) -> anyhow::Result<()> {
+        let Some(value) = value else {
+            self.append_null();
+            return Ok(());
+        };
+
+        match self {
+            Self::Boolean(builder) => match value {
+                "t" => builder.append_value(true),
+                "f" => builder.append_value(false),
+                _ => bail!("Unexpected value: {value}"),
+            },
+            Self::Int16(builder) => builder.append_value(value.parse()?),
+            Self::Int32(builder) => builder.append_value(value.parse()?),
+            Self::Int64(builder) => builder.append_value(value.parse()?),
+            Self::Float32(builder) => builder.append_value(value.parse()?),
+            Self::Float64(builder) => builder.append_value(value.parse()?),
+            Self::Date32(builder) => builder.append_value(parse_date(value)?),
+            Self::Timestamp(builder) => builder.append_value(parse_timestamp(value)?),
+            Self::Utf8(builder) => builder.append_value(value),
+            Self::Utf8List(builder) => {
+                for element in parse_array(value)? {
+                    builder.values().append_option(element);
+                }
+                builder.append(true)
+            }
+        }
+
+        Ok(())
+    }
+
+    fn append_null(&mut self) {
+        match self {
+            Self::Boolean(builder) => builder.append_null(),
+            Self::Int16(builder) => builder.append_null(),
+            Self::Int32(builder) => builder.append_null(),
+            Self::Int64(builder) => builder.append_null(),
+            Self::Float32(builder) => builder.append_null(),
+            Self::Float64(builder) => builder.append_null(),
+            Self::Date32(builder) => builder.append_null(),
+            Self::Timestamp(builder) => builder.append_null(),
+            Self::Utf8(builder) => builder.append_null(),
+            Self::Utf8List(builder) => builder.append_null(),
+        }
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            Self::Boolean(builder) => Arc::new(builder.finish()),
+            Self::Int16(builder) => Arc::new(builder.finish()),
+            Self::Int32(builder) => Arc::new(builder.finish()),
+            Self::Int64(builder) => Arc::new(builder.finish()),
+            Self::Float32(builder) => Arc::new(builder.finish()),
+            Self::Float64(builder) => Arc::new(builder.finish()),
+            Self::Date32(builder) => Arc::new(builder.finish()),
+            Self::Timestamp(builder) => Arc::new(builder.finish()),
+            Self::Utf8(builder) => Arc::new(builder.finish()),
+            Self::Utf8List(builder) => Arc::new(builder.finish()),
+        }
+    }
+}
+
+/// Parses a Postgres `date` into the number of days since the UNIX epoch.
+fn parse_date(value: &str) -> anyhow::Result<i32> {
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")?;
+    let days = date.signed_duration_since(NaiveDate::default()).num_days();
+    Ok(days.try_into()?)
+}
+
+/// Parses a Postgres `timestamp` or `timestamptz` into the number of
+/// microseconds since the UNIX epoch.
+fn parse_timestamp(value: &str) -> anyhow::Result<i64> {
+    if let Ok(timestamp) = DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f%#z") {
+        return Ok(timestamp.timestamp_micros());
+    }
+
+    let timestamp = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f")?;
+    Ok(timestamp.and_utc().timestamp_micros())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Array;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::TimestampMicrosecondType;
+
+    #[test]
+    fn test_parse_timestamp() {
+        let expected = 1_704_112_496_123_456;
+        assert_eq!(
+            parse_timestamp("2024-01-01 12:34:56.123456+00").unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_timestamp("2024-01-01 13:34:56.123456+01:00").unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_timestamp("2024-01-01 12:34:56.123456").unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_timestamp("2024-01-01 12:34:56").unwrap(),
+            1_704_112_496_000_000
+        );
+        assert!(parse_timestamp("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("1970-01-01").unwrap(), 0);
+        assert_eq!(parse_date("2024-01-01").unwrap(), 19723);
+        assert!(parse_date("2024-13-01").is_err());
+    }
+
+    #[test]
+    fn test_parse_csv_record() {
+        assert_eq!(
+            parse_csv_record(r#"1,,"","a ""b"", c""#).unwrap(),
+            vec![
+                Some("1".to_string()),
+                None,
+                Some(String::new()),
+                Some(r#"a "b", c"#.to_string()),
+            ]
+        );
+        assert!(parse_csv_record(r#""foo"bar"#).is_err());
+        assert!(parse_csv_record(r#""foo"#).is_err());
+    }
+
+    #[test]
+    fn test_pg_csv_reader() {
+        let csv = "id,description\n1,\"multiple\nlines\"\n2,\n";
+        let mut reader = PgCsvReader::new(csv.as_bytes());
+        let records = std::iter::from_fn(|| reader.read_record().unwrap()).collect::<Vec<_>>();
+        assert_eq!(
+            records,
+            vec![
+                vec![Some("id".to_string()), Some("description".to_string())],
+                vec![Some("1".to_string()), Some("multiple\nlines".to_string())],
+                vec![Some("2".to_string()), None],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_array() {
+        assert_eq!(parse_array("{}").unwrap(), vec![]);
+        assert_eq!(
+            parse_array(r#"{foo,"bar, baz","a \"b\"",NULL,"NULL"}"#).unwrap(),
+            vec![
+                Some("foo".to_string()),
+                Some("bar, baz".to_string()),
+                Some(r#"a "b""#.to_string()),
+                None,
+                Some("NULL".to_string()),
+            ]
+        );
+        assert!(parse_array("foo").is_err());
+    }
+
+    #[test]
+    fn test_column_builders() {
+        let mut builder = ColumnBuilder::new(&data_type("_text"));
+        builder.append(Some("{foo,NULL}")).unwrap();
+        builder.append(Some("{}")).unwrap();
+        builder.append(None).unwrap();
+        let array = builder.finish();
+        let array = array.as_list::<i32>();
+        assert_eq!(array.len(), 3);
+        assert_eq!(array.value(0).as_string::<i32>().value(0), "foo");
+        assert!(array.value(0).is_null(1));
+        assert_eq!(array.value(1).len(), 0);
+        assert!(array.is_null(2));
+
+        let mut builder = ColumnBuilder::new(&data_type("jsonb"));
+        builder.append(Some(r#"{"default": ["std"]}"#)).unwrap();
+        builder.append(Some("")).unwrap();
+        let array = builder.finish();
+        let array = array.as_string::<i32>();
+        assert_eq!(array.value(0), r#"{"default": ["std"]}"#);
+        assert_eq!(array.value(1), "");
+        assert!(!array.is_null(1));
+
+        let mut builder = ColumnBuilder::new(&data_type("timestamptz"));
+        builder.append(Some("1970-01-01 00:00:01+00")).unwrap();
+        let array = builder.finish();
+        let array = array.as_primitive::<TimestampMicrosecondType>();
+        assert_eq!(array.value(0), 1_000_000);
+        assert_eq!(array.timezone(), Some("UTC"));
+
+        let mut builder = ColumnBuilder::new(&data_type("bool"));
+        builder.append(Some("t")).unwrap();
+        assert!(builder.append(Some("true")).is_err());
+
+        let mut builder = ColumnBuilder::new(&data_type("int2"));
+        assert!(builder.append(Some("100000")).is_err());
+        assert!(builder.append(Some("a")).is_err());
+    }
+}
@@ -32,10 +32,30 @@ pub enum DocsRsError {
     Other(anyhow::Error),
 }
 
+/// The status of the documentation build of a release on docs.rs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocsRsBuildStatus {
+    /// The documentation was built successfully.
+    Success,
+    /// The documentation build failed.
+    Failure,
+    /// The documentation has not been built yet.
+    /// docs.rs does not know about a release until its first build was
+    /// started, so this is also returned if docs.rs does not know about
+    /// the release at all.
+    Queued,
+}
+
 #[cfg_attr(feature = "mock", mockall::automock)]
 #[async_trait]
 pub trait DocsRsClient: Send + Sync {
     async fn rebuild_docs(&self, name: &str, version: &str) -> Result<(), DocsRsError>;
+
+    async fn build_status(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Result<DocsRsBuildStatus, DocsRsError>;
 }
 
 pub struct RealDocsRsClient {
@@ -113,6 +133,50 @@ impl DocsRsClient for RealDocsRsClient {
             ))),
         }
     }
+
+    async fn build_status(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Result<DocsRsBuildStatus, DocsRsError> {
+        let target_url = self
+            .base_url
+            .join(&format!("/crate/{name}/{version}/status.json"))
+            .map_err(|err| DocsRsError::Other(err.into()))?;
+
+        let response = self
+            .client
+            .get(target_url)
+            .send()
+            .await
+            .map_err(|err| DocsRsError::Other(err.into()))?;
+
+        match response.status() {
+            StatusCode::OK => {
+                #[derive(Deserialize)]
+                struct StatusResponse {
+                    doc_status: bool,
+                }
+
+                let status_response: StatusResponse = response
+                    .json()
+                    .await
+                    .map_err(|err| DocsRsError::Other(err.into()))?;
+
+                Ok(match status_response.doc_status {
+                    true => DocsRsBuildStatus::Success,
+                    false => DocsRsBuildStatus::Failure,
+                })
+            }
+            StatusCode::NOT_FOUND => Ok(DocsRsBuildStatus::Queued),
+            StatusCode::TOO_MANY_REQUESTS => Err(DocsRsError::RateLimited),
+            _ => Err(DocsRsError::Other(anyhow::anyhow!(
+                "Unexpected response from docs.rs: {}\n{}",
+                response.status(),
+                response.text().await.unwrap_or_default()
+            ))),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -227,4 +291,83 @@ mod tests {
 
         Ok(())
     }
+
+    async fn mock_status(
+        krate: &str,
+        version: &str,
+        status: StatusCode,
+    ) -> (mockito::ServerGuard, mockito::Mock) {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", &format!("/crate/{krate}/{version}/status.json")[..])
+            .with_status(status.as_u16().into());
+
+        (server, mock)
+    }
+
+    #[tokio::test]
+    #[test_case(true, DocsRsBuildStatus::Success)]
+    #[test_case(false, DocsRsBuildStatus::Failure)]
+    async fn test_build_status(
+        doc_status: bool,
+        expected: DocsRsBuildStatus,
+    ) -> anyhow::Result<()> {
+        let (server, mock) = mock_status("krate", "0.1.0", StatusCode::OK).await;
+        let body = serde_json::to_vec(&serde_json::json!({
+            "doc_status": doc_status,
+            "version": "0.1.0",
+        }))?;
+        mock.with_body(&body).create();
+
+        let docs_rs = RealDocsRsClient::new(Url::parse(&server.url())?, "test_token");
+
+        assert_eq!(docs_rs.build_status("krate", "0.1.0").await?, expected);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_build_status_not_found() -> anyhow::Result<()> {
+        let (server, mock) = mock_status("krate", "0.1.0", StatusCode::NOT_FOUND).await;
+        mock.create();
+
+        let docs_rs = RealDocsRsClient::new(Url::parse(&server.url())?, "test_token");
+
+        assert_eq!(
+            docs_rs.build_status("krate", "0.1.0").await?,
+            DocsRsBuildStatus::Queued
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_build_status_too_many_requests() -> anyhow::Result<()> {
+        let (server, mock) = mock_status("krate", "0.1.0", StatusCode::TOO_MANY_REQUESTS).await;
+        mock.create();
+
+        let docs_rs = RealDocsRsClient::new(Url::parse(&server.url())?, "test_token");
+
+        assert_matches!(
+            docs_rs.build_status("krate", "0.1.0").await,
+            Err(DocsRsError::RateLimited)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_build_status_server_error() -> anyhow::Result<()> {
+        let (server, mock) = mock_status("krate", "0.1.0", StatusCode::INTERNAL_SERVER_ERROR).await;
+        mock.create();
+
+        let docs_rs = RealDocsRsClient::new(Url::parse(&server.url())?, "test_token");
+
+        assert_matches!(
+            docs_rs.build_status("krate", "0.1.0").await,
+            Err(DocsRsError::Other(_))
+        );
+
+        Ok(())
+    }
 }
@@ -32,6 +32,16 @@ pub struct Crate {
         serialize_with = "serialize_pubtime"
     )]
     pub pubtime: Option<DateTime<Utc>>,
+    /// The maintenance status of the crate, if it is not actively
+    /// maintained (e.g. `deprecated`).
+    ///
+    /// This is a crate-level setting, so it is the same for all entries of
+    /// the crate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// The name of the crate that replaces this crate, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub successor: Option<String>,
     /// The schema version for this entry.
     ///
     /// If this is None, it defaults to version 1. Entries with unknown
@@ -50,6 +50,8 @@ mod tests {
             links: None,
             rust_version: None,
             pubtime: Some(pubtime),
+            status: None,
+            successor: None,
             v: None,
         };
         let mut buffer = Vec::new();
@@ -78,6 +80,8 @@ mod tests {
                 links: None,
                 rust_version: None,
                 pubtime: None,
+                status: None,
+                successor: None,
                 v: None,
             })
             .collect::<Vec<_>>();
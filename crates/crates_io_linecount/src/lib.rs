@@ -1,12 +1,13 @@
 mod languages;
 mod paths;
+mod unsafe_code;
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::LazyLock;
 use tokei::Config;
 
-pub use crate::paths::PathDetails;
+pub use crate::paths::{FileCategory, PathDetails};
 
 // Re-export LanguageType for use by other crates
 pub use tokei::LanguageType;
@@ -29,6 +30,29 @@ pub struct LanguageStats {
     pub files: usize,
 }
 
+/// Line count statistics for the files of each `FileCategory`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct CategoryStats {
+    pub tests: LanguageStats,
+    pub examples: LanguageStats,
+    pub benches: LanguageStats,
+    pub build_script: LanguageStats,
+    pub vendored: LanguageStats,
+}
+
+impl CategoryStats {
+    fn get_mut(&mut self, category: FileCategory) -> &mut LanguageStats {
+        match category {
+            FileCategory::Tests => &mut self.tests,
+            FileCategory::Examples => &mut self.examples,
+            FileCategory::Benches => &mut self.benches,
+            FileCategory::BuildScript => &mut self.build_script,
+            FileCategory::Vendored => &mut self.vendored,
+        }
+    }
+}
+
 /// Complete line count statistics for a crate
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub struct LinecountStats {
@@ -38,6 +62,20 @@ pub struct LinecountStats {
     pub total_code_lines: usize,
     /// Total comment lines across all languages
     pub total_comment_lines: usize,
+    /// Line counts for files that are not part of the main source code
+    /// (tests, examples, benches), or that are tracked separately in
+    /// addition to it (build script, vendored C/C++ sources)
+    #[serde(default)]
+    pub categories: CategoryStats,
+    /// Number of `unsafe` blocks in the main Rust source code
+    #[serde(default)]
+    pub unsafe_blocks: usize,
+    /// Whether the crate has a `build.rs` file in its root directory
+    #[serde(default)]
+    pub has_build_script: bool,
+    /// Whether the crate is a procedural macro crate
+    #[serde(default)]
+    pub is_proc_macro: bool,
 }
 
 impl LinecountStats {
@@ -62,6 +100,33 @@ impl LinecountStats {
         // Update totals
         self.total_code_lines += file_stats.code;
         self.total_comment_lines += file_stats.comments;
+
+        if language_type == LanguageType::Rust {
+            self.unsafe_blocks += unsafe_code::count_unsafe_blocks(content);
+        }
+    }
+
+    /// Add a single file to the statistics of the given category
+    ///
+    /// In contrast to `add_file()`, this does not update the per-language
+    /// breakdown or the totals. Adding a `FileCategory::BuildScript` file
+    /// also sets `has_build_script`.
+    pub fn add_category_file(
+        &mut self,
+        category: FileCategory,
+        language_type: LanguageType,
+        content: &[u8],
+    ) {
+        let file_stats = language_type.parse_from_slice(content, &TOKEI_CONFIG);
+
+        if category == FileCategory::BuildScript {
+            self.has_build_script = true;
+        }
+
+        let entry = self.categories.get_mut(category);
+        entry.code_lines += file_stats.code;
+        entry.comment_lines += file_stats.comments;
+        entry.files += 1;
     }
 }
 
@@ -77,7 +142,37 @@ mod tests {
         {
           "languages": {},
           "total_code_lines": 0,
-          "total_comment_lines": 0
+          "total_comment_lines": 0,
+          "categories": {
+            "tests": {
+              "code_lines": 0,
+              "comment_lines": 0,
+              "files": 0
+            },
+            "examples": {
+              "code_lines": 0,
+              "comment_lines": 0,
+              "files": 0
+            },
+            "benches": {
+              "code_lines": 0,
+              "comment_lines": 0,
+              "files": 0
+            },
+            "build_script": {
+              "code_lines": 0,
+              "comment_lines": 0,
+              "files": 0
+            },
+            "vendored": {
+              "code_lines": 0,
+              "comment_lines": 0,
+              "files": 0
+            }
+          },
+          "unsafe_blocks": 0,
+          "has_build_script": false,
+          "is_proc_macro": false
         }
         "#);
     }
@@ -100,7 +195,37 @@ mod tests {
             }
           },
           "total_code_lines": 3,
-          "total_comment_lines": 1
+          "total_comment_lines": 1,
+          "categories": {
+            "tests": {
+              "code_lines": 0,
+              "comment_lines": 0,
+              "files": 0
+            },
+            "examples": {
+              "code_lines": 0,
+              "comment_lines": 0,
+              "files": 0
+            },
+            "benches": {
+              "code_lines": 0,
+              "comment_lines": 0,
+              "files": 0
+            },
+            "build_script": {
+              "code_lines": 0,
+              "comment_lines": 0,
+              "files": 0
+            },
+            "vendored": {
+              "code_lines": 0,
+              "comment_lines": 0,
+              "files": 0
+            }
+          },
+          "unsafe_blocks": 0,
+          "has_build_script": false,
+          "is_proc_macro": false
         }
         "#);
     }
@@ -110,8 +235,9 @@ mod tests {
         let mut stats = LinecountStats::new();
 
         let files = [
-            ("src/lib.rs", "pub fn hello() {}"),
-            ("tests/test.rs", "fn test() {}"), // Should be skipped
+            ("src/lib.rs", "pub fn hello() { unsafe { foo() } }"),
+            ("tests/test.rs", "fn test() {}"), // Should be tracked as a category
+            ("build.rs", "fn main() {}"),      // Should be counted twice
             ("README.md", "# Hello"),          // Should be skipped
         ];
 
@@ -119,25 +245,72 @@ mod tests {
             let path = Path::new(path);
             let path_details = PathDetails::from_path(path);
 
-            if !path_details.should_ignore()
-                && let Some(language_type) = path_details.language_type()
-            {
-                stats.add_file(language_type, content.as_bytes())
+            let Some(language_type) = path_details.language_type() else {
+                continue;
             };
+
+            if let Some(category) = path_details.category() {
+                stats.add_category_file(category, language_type, content.as_bytes());
+            }
+
+            if !path_details.should_ignore() {
+                stats.add_file(language_type, content.as_bytes())
+            }
         }
 
         insta::assert_json_snapshot!(stats, @r#"
         {
           "languages": {
             "Rust": {
+              "code_lines": 2,
+              "comment_lines": 0,
+              "files": 2
+            }
+          },
+          "total_code_lines": 2,
+          "total_comment_lines": 0,
+          "categories": {
+            "tests": {
+              "code_lines": 1,
+              "comment_lines": 0,
+              "files": 1
+            },
+            "examples": {
+              "code_lines": 0,
+              "comment_lines": 0,
+              "files": 0
+            },
+            "benches": {
+              "code_lines": 0,
+              "comment_lines": 0,
+              "files": 0
+            },
+            "build_script": {
               "code_lines": 1,
               "comment_lines": 0,
               "files": 1
+            },
+            "vendored": {
+              "code_lines": 0,
+              "comment_lines": 0,
+              "files": 0
             }
           },
-          "total_code_lines": 1,
-          "total_comment_lines": 0
+          "unsafe_blocks": 1,
+          "has_build_script": true,
+          "is_proc_macro": false
         }
         "#);
     }
+
+    #[test]
+    fn test_deserialize_without_composition_fields() {
+        let json = r#"{"languages":{},"total_code_lines":5,"total_comment_lines":1}"#;
+        let stats: LinecountStats = serde_json::from_str(json).unwrap();
+        assert_eq!(stats.total_code_lines, 5);
+        assert_eq!(stats.categories, CategoryStats::default());
+        assert_eq!(stats.unsafe_blocks, 0);
+        assert!(!stats.has_build_script);
+        assert!(!stats.is_proc_macro);
+    }
 }
@@ -1,13 +1,26 @@
 use crate::languages::should_ignore_language;
-use std::path::Path;
+use std::path::{Component, Path};
 use tokei::LanguageType;
 
+/// Categories of files that are tracked separately from the main source code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileCategory {
+    Tests,
+    Examples,
+    Benches,
+    BuildScript,
+    Vendored,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct PathDetails {
     is_benchmark: bool,
+    is_build_script: bool,
     is_example: bool,
     is_hidden: bool,
+    is_manifest: bool,
     is_test: bool,
+    is_vendored: bool,
     language_type: Option<LanguageType>,
 }
 
@@ -31,11 +44,29 @@ impl PathDetails {
             .and_then(|ext| ext.to_str())
             .and_then(LanguageType::from_file_extension);
 
+        let is_vendored = matches!(
+            language_type,
+            Some(
+                LanguageType::C
+                    | LanguageType::CHeader
+                    | LanguageType::Cpp
+                    | LanguageType::CppHeader
+            )
+        ) && (path_str.contains("vendor/")
+            || path_str.contains("third_party/")
+            || path_str.contains("third-party/"));
+
+        let is_build_script = is_crate_root_file(path, "build.rs");
+        let is_manifest = is_crate_root_file(path, "Cargo.toml");
+
         Self {
             is_benchmark,
+            is_build_script,
             is_example,
             is_hidden,
+            is_manifest,
             is_test,
+            is_vendored,
             language_type,
         }
     }
@@ -46,6 +77,37 @@ impl PathDetails {
         self.is_benchmark || self.is_example || self.is_hidden || self.is_test
     }
 
+    /// Get the category that this file should be tracked under, if any.
+    ///
+    /// Hidden files are not assigned to any category.
+    pub fn category(&self) -> Option<FileCategory> {
+        if self.is_hidden {
+            None
+        } else if self.is_test {
+            Some(FileCategory::Tests)
+        } else if self.is_example {
+            Some(FileCategory::Examples)
+        } else if self.is_benchmark {
+            Some(FileCategory::Benches)
+        } else if self.is_build_script {
+            Some(FileCategory::BuildScript)
+        } else if self.is_vendored {
+            Some(FileCategory::Vendored)
+        } else {
+            None
+        }
+    }
+
+    /// Whether this file is the `build.rs` file in the root of the crate.
+    pub fn is_build_script(&self) -> bool {
+        self.is_build_script
+    }
+
+    /// Whether this file is the `Cargo.toml` file in the root of the crate.
+    pub fn is_manifest(&self) -> bool {
+        self.is_manifest
+    }
+
     /// Get the actual detected language type, even if it should be ignored.
     pub fn actual_language_type(&self) -> Option<LanguageType> {
         self.language_type
@@ -58,6 +120,21 @@ impl PathDetails {
     }
 }
 
+/// Checks if the path points to a file with the given name in the root of
+/// the crate.
+///
+/// Paths inside of crate files are prefixed with a `{name}-{version}/`
+/// directory, so both `build.rs` and `foo-1.0.0/build.rs` are considered
+/// to be in the crate root.
+fn is_crate_root_file(path: &Path, file_name: &str) -> bool {
+    let components = path
+        .components()
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .count();
+
+    components <= 2 && path.file_name().is_some_and(|name| name == file_name)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -71,4 +148,46 @@ mod tests {
         assert_debug_snapshot!(PathDetails::from_path(Path::new("benches/bench.rs")));
         assert_debug_snapshot!(PathDetails::from_path(Path::new("src/lib.rs")));
     }
+
+    #[test]
+    fn test_category() {
+        let category = |path| PathDetails::from_path(Path::new(path)).category();
+
+        assert_eq!(category("foo-1.0.0/src/lib.rs"), None);
+        assert_eq!(category("foo-1.0.0/.hidden.rs"), None);
+        assert_eq!(category("foo-1.0.0/src/build.rs"), None);
+        assert_eq!(category("foo-1.0.0/tests/it.rs"), Some(FileCategory::Tests));
+        assert_eq!(
+            category("foo-1.0.0/examples/basic.rs"),
+            Some(FileCategory::Examples)
+        );
+        assert_eq!(
+            category("foo-1.0.0/benches/bench.rs"),
+            Some(FileCategory::Benches)
+        );
+        assert_eq!(
+            category("foo-1.0.0/build.rs"),
+            Some(FileCategory::BuildScript)
+        );
+        assert_eq!(category("build.rs"), Some(FileCategory::BuildScript));
+        assert_eq!(
+            category("foo-1.0.0/vendor/zlib/inflate.c"),
+            Some(FileCategory::Vendored)
+        );
+        assert_eq!(
+            category("foo-1.0.0/third_party/lib.hpp"),
+            Some(FileCategory::Vendored)
+        );
+        assert_eq!(category("foo-1.0.0/vendor/lib.rs"), None);
+        assert_eq!(category("foo-1.0.0/src/shim.c"), None);
+    }
+
+    #[test]
+    fn test_is_manifest() {
+        let is_manifest = |path| PathDetails::from_path(Path::new(path)).is_manifest();
+
+        assert!(is_manifest("foo-1.0.0/Cargo.toml"));
+        assert!(!is_manifest("foo-1.0.0/sub/Cargo.toml"));
+        assert!(!is_manifest("foo-1.0.0/Cargo.toml.orig"));
+    }
 }
@@ -0,0 +1,163 @@
+/// Count the number of `unsafe { ... }` blocks in a Rust source file.
+///
+/// This is a lightweight lexical scan rather than a full parse. It skips
+/// comments, string and character literals, so that occurrences of
+/// `unsafe {` inside of them are not counted. `unsafe fn`, `unsafe impl`
+/// and `unsafe trait` items are not counted as blocks.
+pub fn count_unsafe_blocks(content: &[u8]) -> usize {
+    let mut count = 0;
+    let mut i = 0;
+
+    while i < content.len() {
+        match content[i] {
+            b'/' if content.get(i + 1) == Some(&b'/') => {
+                i = skip_line_comment(content, i);
+            }
+            b'/' if content.get(i + 1) == Some(&b'*') => {
+                i = skip_block_comment(content, i);
+            }
+            b'"' => {
+                i = skip_string(content, i + 1);
+            }
+            b'\'' => {
+                i = skip_char_or_lifetime(content, i);
+            }
+            c if is_ident_char(c) => {
+                let start = i;
+                while i < content.len() && is_ident_char(content[i]) {
+                    i += 1;
+                }
+
+                let ident = &content[start..i];
+                if matches!(ident, b"r" | b"br" | b"cr") && is_raw_string_start(content, i) {
+                    i = skip_raw_string(content, i);
+                } else if ident == b"unsafe" && next_non_whitespace(content, i) == Some(b'{') {
+                    count += 1;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+
+    count
+}
+
+fn is_ident_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+fn next_non_whitespace(content: &[u8], start: usize) -> Option<u8> {
+    content[start..]
+        .iter()
+        .copied()
+        .find(|c| !c.is_ascii_whitespace())
+}
+
+fn skip_line_comment(content: &[u8], start: usize) -> usize {
+    content[start..]
+        .iter()
+        .position(|&c| c == b'\n')
+        .map_or(content.len(), |pos| start + pos + 1)
+}
+
+fn skip_block_comment(content: &[u8], start: usize) -> usize {
+    let mut depth = 0;
+    let mut i = start;
+    while i < content.len() {
+        if content[i..].starts_with(b"/*") {
+            depth += 1;
+            i += 2;
+        } else if content[i..].starts_with(b"*/") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                return i;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    i
+}
+
+fn skip_string(content: &[u8], mut i: usize) -> usize {
+    while i < content.len() {
+        match content[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    i
+}
+
+fn is_raw_string_start(content: &[u8], start: usize) -> bool {
+    let hashes = content[start..].iter().take_while(|&&c| c == b'#').count();
+    content.get(start + hashes) == Some(&b'"')
+}
+
+fn skip_raw_string(content: &[u8], start: usize) -> usize {
+    let hashes = content[start..].iter().take_while(|&&c| c == b'#').count();
+    let body_start = start + hashes + 1;
+
+    let mut terminator = vec![b'"'];
+    terminator.extend(std::iter::repeat_n(b'#', hashes));
+
+    content[body_start..]
+        .windows(terminator.len())
+        .position(|window| window == terminator.as_slice())
+        .map_or(content.len(), |pos| body_start + pos + terminator.len())
+}
+
+fn skip_char_or_lifetime(content: &[u8], start: usize) -> usize {
+    match content.get(start + 1) {
+        // Escaped character literal like `'\n'` or `'\u{1F600}'`
+        Some(b'\\') => content[start + 2..]
+            .iter()
+            .position(|&c| c == b'\'')
+            .map_or(content.len(), |pos| start + 2 + pos + 1),
+        // Simple character literal like `'a'`
+        Some(_) if content.get(start + 2) == Some(&b'\'') => start + 3,
+        // Lifetime or multi-byte character literal; skipping the quote is
+        // sufficient in both cases.
+        _ => start + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_unsafe_blocks() {
+        assert_eq!(count_unsafe_blocks(b""), 0);
+        assert_eq!(count_unsafe_blocks(b"fn main() { unsafe { foo() } }"), 1);
+        assert_eq!(count_unsafe_blocks(b"let x = unsafe{ foo() };"), 1);
+        assert_eq!(count_unsafe_blocks(b"unsafe\n{\n}\nunsafe {}"), 2);
+    }
+
+    #[test]
+    fn test_unsafe_items_are_not_counted() {
+        assert_eq!(count_unsafe_blocks(b"unsafe fn foo() {}"), 0);
+        assert_eq!(count_unsafe_blocks(b"unsafe impl Send for Foo {}"), 0);
+        assert_eq!(count_unsafe_blocks(b"unsafe trait Foo {}"), 0);
+        assert_eq!(count_unsafe_blocks(b"let not_unsafe = 1; my_unsafe {}"), 0);
+    }
+
+    #[test]
+    fn test_comments_and_literals_are_skipped() {
+        assert_eq!(count_unsafe_blocks(b"// unsafe {}\n"), 0);
+        assert_eq!(
+            count_unsafe_blocks(b"/* unsafe { /* nested */ unsafe {} */"),
+            0
+        );
+        assert_eq!(
+            count_unsafe_blocks(br#"let s = "unsafe {} \" unsafe {}";"#),
+            0
+        );
+        assert_eq!(count_unsafe_blocks(br##"let s = r#"unsafe {}"#;"##), 0);
+        assert_eq!(count_unsafe_blocks(br##"let s = br#"unsafe {}"#;"##), 0);
+        assert_eq!(count_unsafe_blocks(b"let c = '\"'; unsafe {}"), 1);
+        assert_eq!(count_unsafe_blocks(b"fn f<'a>(x: &'a u8) { unsafe {} }"), 1);
+    }
+}
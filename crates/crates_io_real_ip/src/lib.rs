@@ -5,9 +5,11 @@ use std::str::from_utf8;
 
 mod cloudfront;
 mod fastly;
+mod location;
 
 pub use cloudfront::CLOUDFRONT_NETWORKS;
 pub use fastly::FASTLY_NETWORKS;
+pub use location::{process_location_headers, ClientLocation};
 
 const X_FORWARDED_FOR: &str = "X-Forwarded-For";
 
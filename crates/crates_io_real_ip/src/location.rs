@@ -0,0 +1,76 @@
+use http::HeaderMap;
+
+const CLOUDFRONT_VIEWER_COUNTRY: &str = "CloudFront-Viewer-Country";
+const CLOUDFRONT_VIEWER_ASN: &str = "CloudFront-Viewer-ASN";
+
+/// The approximate network location of a client, as determined by the CDN.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ClientLocation {
+    /// The two-letter ISO 3166-1 country code, e.g. `DE`.
+    pub country: Option<String>,
+    /// The number of the autonomous system that the client IP address
+    /// belongs to.
+    pub asn: Option<u32>,
+}
+
+/// Extracts the client location from the `CloudFront-Viewer-Country` and
+/// `CloudFront-Viewer-ASN` headers.
+///
+/// Invalid header values are ignored.
+pub fn process_location_headers(headers: &HeaderMap) -> ClientLocation {
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+
+    let country = header(CLOUDFRONT_VIEWER_COUNTRY)
+        .map(str::trim)
+        .filter(|country| country.len() == 2 && country.bytes().all(|b| b.is_ascii_alphabetic()))
+        .map(str::to_ascii_uppercase);
+
+    let asn = header(CLOUDFRONT_VIEWER_ASN).and_then(|asn| asn.trim().parse().ok());
+
+    ClientLocation { country, asn }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    #[test]
+    fn test_process_location_headers() {
+        #[track_caller]
+        fn test(input: Vec<(&'static str, &[u8])>, country: Option<&str>, asn: Option<u32>) {
+            let mut headers = HeaderMap::new();
+            for (name, value) in input {
+                let value = HeaderValue::from_bytes(value).unwrap();
+                headers.append(name, value);
+            }
+
+            let expectation = ClientLocation {
+                country: country.map(String::from),
+                asn,
+            };
+
+            assert_eq!(process_location_headers(&headers), expectation)
+        }
+
+        test(vec![], None, None);
+        test(vec![(CLOUDFRONT_VIEWER_COUNTRY, b"DE")], Some("DE"), None);
+        test(vec![(CLOUDFRONT_VIEWER_COUNTRY, b" us ")], Some("US"), None);
+        test(vec![(CLOUDFRONT_VIEWER_ASN, b"16509")], None, Some(16509));
+        test(
+            vec![
+                (CLOUDFRONT_VIEWER_COUNTRY, b"FR"),
+                (CLOUDFRONT_VIEWER_ASN, b"3215"),
+            ],
+            Some("FR"),
+            Some(3215),
+        );
+
+        // Invalid values
+        test(vec![(CLOUDFRONT_VIEWER_COUNTRY, b"")], None, None);
+        test(vec![(CLOUDFRONT_VIEWER_COUNTRY, b"DEU")], None, None);
+        test(vec![(CLOUDFRONT_VIEWER_COUNTRY, b"1A")], None, None);
+        test(vec![(CLOUDFRONT_VIEWER_ASN, b"AS16509")], None, None);
+        test(vec![(CLOUDFRONT_VIEWER_ASN, b"-1")], None, None);
+    }
+}
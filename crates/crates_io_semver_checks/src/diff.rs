@@ -0,0 +1,222 @@
+use crate::surface::{ApiSurface, ItemKind};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// A public item was removed.
+    Removed,
+    /// The signature of a public item changed.
+    Changed,
+    /// A variant was added to an enum that is not `#[non_exhaustive]`.
+    VariantAdded,
+    /// A public field was added to a struct without private fields.
+    FieldAdded,
+    /// A trait item without a default implementation was added.
+    RequiredItemAdded,
+    /// A struct or enum became `#[non_exhaustive]` or got private fields.
+    NonExhaustiveAdded,
+}
+
+/// A change between two API surfaces that would likely break users.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BreakingChange {
+    /// The public path of the changed item.
+    pub path: String,
+    pub item: ItemKind,
+    pub kind: ChangeKind,
+    /// The signature of the item in the old version, if it existed.
+    pub old: Option<String>,
+    /// The signature of the item in the new version, if it exists.
+    pub new: Option<String>,
+}
+
+impl fmt::Display for BreakingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = &self.path;
+        match self.kind {
+            ChangeKind::Removed => write!(f, "`{path}` was removed"),
+            ChangeKind::Changed => {
+                let old = self.old.as_deref().unwrap_or_default();
+                let new = self.new.as_deref().unwrap_or_default();
+                write!(f, "`{path}` changed from `{old}` to `{new}`")
+            }
+            ChangeKind::VariantAdded => {
+                write!(f, "variant `{path}` was added to an exhaustive enum")
+            }
+            ChangeKind::FieldAdded => {
+                write!(
+                    f,
+                    "field `{path}` was added to a struct without private fields"
+                )
+            }
+            ChangeKind::RequiredItemAdded => {
+                write!(f, "trait item `{path}` without a default was added")
+            }
+            ChangeKind::NonExhaustiveAdded => {
+                write!(
+                    f,
+                    "`{path}` can no longer be constructed or matched exhaustively"
+                )
+            }
+        }
+    }
+}
+
+/// Compares the API surfaces of two versions of a crate and returns the
+/// changes that would likely break users of the old version, sorted by path.
+///
+/// Removals of members are not reported separately if their parent item was
+/// removed too.
+pub fn compare(old: &ApiSurface, new: &ApiSurface) -> Vec<BreakingChange> {
+    let mut changes = Vec::new();
+
+    for (path, old_item) in old.items() {
+        let parent = parent_path(path).and_then(|parent| old.get(parent).map(|_| parent));
+
+        let Some(new_item) = new.get(path) else {
+            if parent.is_some_and(|parent| new.get(parent).is_none()) {
+                continue;
+            }
+
+            changes.push(BreakingChange {
+                path: path.clone(),
+                item: old_item.kind,
+                kind: ChangeKind::Removed,
+                old: Some(old_item.signature.clone()),
+                new: None,
+            });
+            continue;
+        };
+
+        let kind = if old_item.kind != new_item.kind || old_item.signature != new_item.signature {
+            ChangeKind::Changed
+        } else if !old_item.non_exhaustive && new_item.non_exhaustive {
+            ChangeKind::NonExhaustiveAdded
+        } else {
+            continue;
+        };
+
+        changes.push(BreakingChange {
+            path: path.clone(),
+            item: new_item.kind,
+            kind,
+            old: Some(old_item.signature.clone()),
+            new: Some(new_item.signature.clone()),
+        });
+    }
+
+    for (path, new_item) in new.items() {
+        if old.get(path).is_some() {
+            continue;
+        }
+
+        let Some(old_parent) = parent_path(path).and_then(|parent| old.get(parent)) else {
+            continue;
+        };
+
+        let kind = match (old_parent.kind, new_item.kind) {
+            (ItemKind::Enum, ItemKind::Variant) if !old_parent.non_exhaustive => {
+                ChangeKind::VariantAdded
+            }
+            (ItemKind::Struct, ItemKind::Field) if !old_parent.non_exhaustive => {
+                ChangeKind::FieldAdded
+            }
+            (ItemKind::Trait, ItemKind::TraitItem) if !new_item.has_default => {
+                ChangeKind::RequiredItemAdded
+            }
+            _ => continue,
+        };
+
+        changes.push(BreakingChange {
+            path: path.clone(),
+            item: new_item.kind,
+            kind,
+            old: None,
+            new: Some(new_item.signature.clone()),
+        });
+    }
+
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    changes
+}
+
+fn parent_path(path: &str) -> Option<&str> {
+    path.rsplit_once("::").map(|(parent, _)| parent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn surface(lib: &str) -> ApiSurface {
+        let files = HashMap::from([("src/lib.rs".to_string(), lib.to_string())]);
+        ApiSurface::from_sources("src/lib.rs", &files).unwrap()
+    }
+
+    fn changes(old: &str, new: &str) -> Vec<String> {
+        compare(&surface(old), &surface(new))
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_compatible_changes() {
+        let old = r#"
+            pub fn parse(input: &str) -> u32 { 0 }
+            #[non_exhaustive]
+            pub enum Kind { A }
+            pub struct Config { pub name: String, limit: usize }
+            pub trait Visitor { fn visit(&self); }
+            fn private(a: u8) {}
+        "#;
+
+        let new = r#"
+            pub fn parse(text: &str) -> u32 { 0 }
+            pub fn parse_all() {}
+            #[non_exhaustive]
+            pub enum Kind { A, B }
+            pub struct Config { pub name: String, pub verbose: bool, limit: usize }
+            pub trait Visitor { fn visit(&self); fn finish(&self) {} }
+            fn private(a: u16) {}
+        "#;
+
+        assert!(changes(old, new).is_empty());
+    }
+
+    #[test]
+    fn test_breaking_changes() {
+        let old = r#"
+            pub fn parse(input: &str) -> u32 { 0 }
+            pub fn removed() {}
+            pub enum Kind { A }
+            pub struct Config { pub name: String }
+            pub struct Point { pub x: i32 }
+            pub trait Visitor { fn visit(&self); }
+            pub mod de { pub struct Deserializer; pub fn from_str() {} }
+        "#;
+
+        let new = r#"
+            pub fn parse(input: &str) -> Result<u32, ()> { Ok(0) }
+            pub enum Kind { A, B }
+            pub struct Config { pub name: String, pub verbose: bool }
+            pub struct Point { pub x: i32, y: i32 }
+            pub trait Visitor { fn visit(&self); fn finish(&self); }
+        "#;
+
+        insta::assert_debug_snapshot!(changes(old, new), @r#"
+        [
+            "field `Config::verbose` was added to a struct without private fields",
+            "variant `Kind::B` was added to an exhaustive enum",
+            "`Point` can no longer be constructed or matched exhaustively",
+            "trait item `Visitor::finish` without a default was added",
+            "`de` was removed",
+            "`parse` changed from `fn parse(&str) -> u32` to `fn parse(&str) -> Result<u32, ()>`",
+            "`removed` was removed",
+        ]
+        "#);
+    }
+}
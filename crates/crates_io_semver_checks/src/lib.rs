@@ -0,0 +1,8 @@
+#![doc = include_str!("../README.md")]
+
+mod diff;
+mod surface;
+mod tidy;
+
+pub use crate::diff::{BreakingChange, ChangeKind, compare};
+pub use crate::surface::{ApiItem, ApiSurface, Error, ItemKind};
@@ -0,0 +1,1032 @@
+use crate::tidy::tidy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use syn::{
+    Attribute, Fields, FnArg, ForeignItem, ImplItem, Item, Meta, ReturnType, Signature,
+    StaticMutability, TraitItem, Type, UseTree, Visibility,
+};
+
+/// The maximum number of `pub use` declarations that are followed when
+/// resolving a re-export.
+const MAX_IMPORT_DEPTH: usize = 8;
+
+/// The maximum nesting depth of modules, to reject crates that would
+/// otherwise make the collector recurse too deeply.
+const MAX_MODULE_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+    Module,
+    Function,
+    Struct,
+    Union,
+    Field,
+    Enum,
+    Variant,
+    Trait,
+    TraitItem,
+    Method,
+    Constant,
+    Static,
+    TypeAlias,
+    Macro,
+    Reexport,
+}
+
+/// A public item of a crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiItem {
+    pub kind: ItemKind,
+    /// The signature of the item, without attributes and argument names.
+    pub signature: String,
+    /// Whether fields or variants can be added to the struct or enum without
+    /// breaking users, because it is `#[non_exhaustive]` or has private fields.
+    pub non_exhaustive: bool,
+    /// Whether the trait item has a default implementation.
+    pub has_default: bool,
+}
+
+impl ApiItem {
+    fn new(kind: ItemKind, signature: String) -> Self {
+        Self {
+            kind,
+            signature,
+            non_exhaustive: false,
+            has_default: false,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Missing source file `{0}`")]
+    MissingFile(String),
+    #[error("Source file `{0}` is included as a module more than once")]
+    DuplicateModuleFile(String),
+    #[error("Module `{0}` is nested too deeply")]
+    ModuleTooDeep(String),
+    #[error("Failed to parse `{path}`: {source}")]
+    Parse {
+        path: String,
+        #[source]
+        source: syn::Error,
+    },
+}
+
+/// The public API surface of a crate, as a map from the public paths of the
+/// items (e.g. `de::Deserializer::deserialize_any`) to the items.
+#[derive(Debug, Default)]
+pub struct ApiSurface {
+    items: BTreeMap<String, ApiItem>,
+}
+
+impl ApiSurface {
+    /// Extracts the public API surface from the source files of a crate.
+    ///
+    /// `files` maps the paths of the source files, relative to the crate
+    /// root, to their contents, and `root` is the path of the library root
+    /// file (usually `src/lib.rs`). Modules whose files are missing are
+    /// treated as empty, since they might be generated by a build script.
+    pub fn from_sources(root: &str, files: &HashMap<String, String>) -> Result<Self, Error> {
+        if !files.contains_key(root) {
+            return Err(Error::MissingFile(root.to_string()));
+        }
+
+        let mut collector = Collector {
+            files,
+            parsed: HashSet::new(),
+            modules: BTreeMap::new(),
+            impls: Vec::new(),
+        };
+        collector.collect_file(root, Vec::new(), parent(root))?;
+        let modules = collector.into_modules();
+
+        let mut exporter = Exporter {
+            modules: &modules,
+            items: BTreeMap::new(),
+            stack: HashSet::new(),
+        };
+        exporter.export_module(&[], "");
+
+        Ok(Self {
+            items: exporter.items,
+        })
+    }
+
+    pub fn items(&self) -> &BTreeMap<String, ApiItem> {
+        &self.items
+    }
+
+    pub fn get(&self, path: &str) -> Option<&ApiItem> {
+        self.items.get(path)
+    }
+}
+
+/// An item definition of a module, before visibility and re-exports are
+/// resolved.
+#[derive(Debug)]
+struct Definition {
+    public: bool,
+    item: ApiItem,
+    /// The fields, variants, trait items and inherent methods of the item.
+    members: BTreeMap<String, ApiItem>,
+}
+
+impl Definition {
+    fn new(public: bool, item: ApiItem) -> Self {
+        Self {
+            public,
+            item,
+            members: BTreeMap::new(),
+        }
+    }
+}
+
+/// A `pub use` declaration.
+#[derive(Debug)]
+struct Import {
+    path: Vec<String>,
+    /// The name the item is imported as, or `None` for glob imports.
+    name: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Module {
+    items: BTreeMap<String, Definition>,
+    /// The child modules, and whether they are public.
+    modules: BTreeMap<String, bool>,
+    imports: Vec<Import>,
+}
+
+type ModulePath = Vec<String>;
+
+/// Collects the item definitions of all modules of a crate.
+struct Collector<'a> {
+    files: &'a HashMap<String, String>,
+    /// The files that were already parsed, to detect module cycles.
+    parsed: HashSet<String>,
+    modules: BTreeMap<ModulePath, Module>,
+    /// The public members of inherent `impl` blocks, by module and type name.
+    impls: Vec<(ModulePath, String, BTreeMap<String, ApiItem>)>,
+}
+
+impl Collector<'_> {
+    /// Parses the file of a module. `dir` is the directory in which the
+    /// files of child modules are located.
+    fn collect_file(&mut self, path: &str, module: ModulePath, dir: String) -> Result<(), Error> {
+        self.modules.entry(module.clone()).or_default();
+
+        let Some(contents) = self.files.get(path) else {
+            return Ok(());
+        };
+
+        if !self.parsed.insert(path.to_string()) {
+            return Err(Error::DuplicateModuleFile(path.to_string()));
+        }
+
+        let file = syn::parse_file(contents).map_err(|source| Error::Parse {
+            path: path.to_string(),
+            source,
+        })?;
+
+        self.collect_items(&file.items, &module, &dir, &parent(path))
+    }
+
+    /// Collects the items of a module. `path_base` is the directory that
+    /// `#[path]` attributes are relative to.
+    fn collect_items(
+        &mut self,
+        items: &[Item],
+        module: &ModulePath,
+        dir: &str,
+        path_base: &str,
+    ) -> Result<(), Error> {
+        for item in items {
+            match item {
+                Item::Mod(item) if !is_hidden(&item.attrs) => {
+                    let name = item.ident.to_string();
+                    let public = is_public(&item.vis);
+                    self.module_mut(module).modules.insert(name.clone(), public);
+
+                    let mut child = module.clone();
+                    child.push(name.clone());
+                    if child.len() > MAX_MODULE_DEPTH {
+                        return Err(Error::ModuleTooDeep(child.join("::")));
+                    }
+
+                    let child_dir = join(dir, &name);
+                    if let Some((_, items)) = &item.content {
+                        self.modules.entry(child.clone()).or_default();
+                        self.collect_items(items, &child, &child_dir, &child_dir)?;
+                    } else if let Some(path) = path_attr(&item.attrs) {
+                        let path = join(path_base, &path);
+                        let dir = if path.ends_with("/mod.rs") {
+                            parent(&path)
+                        } else {
+                            path.trim_end_matches(".rs").to_string()
+                        };
+                        self.collect_file(&path, child, dir)?;
+                    } else {
+                        let file = join(dir, &format!("{name}.rs"));
+                        let path = if self.files.contains_key(&file) {
+                            file
+                        } else {
+                            join(&child_dir, "mod.rs")
+                        };
+                        self.collect_file(&path, child, child_dir)?;
+                    }
+                }
+                Item::Use(item) if is_public(&item.vis) && !is_hidden(&item.attrs) => {
+                    let mut imports = Vec::new();
+                    flatten_use_tree(&item.tree, Vec::new(), &mut imports);
+                    self.module_mut(module).imports.extend(imports);
+                }
+                Item::Impl(item) if item.trait_.is_none() && !is_hidden(&item.attrs) => {
+                    let Type::Path(self_ty) = &*item.self_ty else {
+                        continue;
+                    };
+                    let Some(segment) = self_ty.path.segments.last() else {
+                        continue;
+                    };
+
+                    let members = impl_members(&item.items);
+                    if !members.is_empty() {
+                        let name = segment.ident.to_string();
+                        self.impls.push((module.clone(), name, members));
+                    }
+                }
+                Item::Macro(item) if is_macro_export(&item.attrs) => {
+                    if let Some(ident) = &item.ident {
+                        let name = ident.to_string();
+                        let signature = format!("macro_rules! {name}");
+                        let item = ApiItem::new(ItemKind::Macro, signature);
+
+                        // `#[macro_export]` macros are always exported at the crate root
+                        let definition = Definition::new(true, item);
+                        self.module_mut(&[]).items.insert(name, definition);
+                    }
+                }
+                Item::ForeignMod(item) => {
+                    for item in &item.items {
+                        if let Some((name, definition)) = foreign_definition(item) {
+                            self.module_mut(module).items.insert(name, definition);
+                        }
+                    }
+                }
+                item => {
+                    if let Some((name, definition)) = definition(item) {
+                        self.module_mut(module).items.insert(name, definition);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn module_mut(&mut self, module: &[String]) -> &mut Module {
+        self.modules.entry(module.to_vec()).or_default()
+    }
+
+    /// Attaches the members of the inherent `impl` blocks to their types,
+    /// which are looked up in the module of the `impl` block first, and in
+    /// all other modules if the name is unique.
+    fn into_modules(mut self) -> BTreeMap<ModulePath, Module> {
+        for (module, name, members) in std::mem::take(&mut self.impls) {
+            let local = self
+                .modules
+                .get(&module)
+                .is_some_and(|m| m.items.contains_key(&name));
+
+            let module = if local {
+                Some(module)
+            } else {
+                let mut candidates = self
+                    .modules
+                    .iter()
+                    .filter(|(_, m)| m.items.contains_key(&name))
+                    .map(|(path, _)| path);
+
+                match (candidates.next(), candidates.next()) {
+                    (Some(path), None) => Some(path.clone()),
+                    _ => None,
+                }
+            };
+
+            let definition = module
+                .and_then(|module| self.modules.get_mut(&module))
+                .and_then(|module| module.items.get_mut(&name));
+
+            if let Some(definition) = definition {
+                definition.members.extend(members);
+            }
+        }
+
+        self.modules
+    }
+}
+
+/// Returns the definition for items that are not modules, imports, `impl`
+/// blocks or macros.
+fn definition(item: &Item) -> Option<(String, Definition)> {
+    let (name, definition) = match item {
+        Item::Fn(item) if !is_hidden(&item.attrs) => {
+            let item_ = ApiItem::new(ItemKind::Function, fn_signature(&item.sig));
+            (
+                &item.sig.ident,
+                Definition::new(is_public(&item.vis), item_),
+            )
+        }
+        Item::Struct(item) if !is_hidden(&item.attrs) => {
+            let (generics, where_clause) = (&item.generics, &item.generics.where_clause);
+            let signature = format!("struct {}{}", item.ident, tidy(generics));
+            let signature = with_where_clause(signature, where_clause);
+            let mut item_ = ApiItem::new(ItemKind::Struct, signature);
+
+            let fields = field_members(&item.fields);
+            let has_private_fields = fields.len() < item.fields.len();
+            item_.non_exhaustive = has_private_fields || is_non_exhaustive(&item.attrs);
+
+            let mut definition = Definition::new(is_public(&item.vis), item_);
+            definition.members = fields;
+            (&item.ident, definition)
+        }
+        Item::Union(item) if !is_hidden(&item.attrs) => {
+            let (generics, where_clause) = (&item.generics, &item.generics.where_clause);
+            let signature = format!("union {}{}", item.ident, tidy(generics));
+            let signature = with_where_clause(signature, where_clause);
+            let item_ = ApiItem::new(ItemKind::Union, signature);
+
+            let fields = Fields::Named(item.fields.clone());
+            let mut definition = Definition::new(is_public(&item.vis), item_);
+            definition.members = field_members(&fields);
+            (&item.ident, definition)
+        }
+        Item::Enum(item) if !is_hidden(&item.attrs) => {
+            let (generics, where_clause) = (&item.generics, &item.generics.where_clause);
+            let signature = format!("enum {}{}", item.ident, tidy(generics));
+            let signature = with_where_clause(signature, where_clause);
+            let mut item_ = ApiItem::new(ItemKind::Enum, signature);
+            item_.non_exhaustive = is_non_exhaustive(&item.attrs);
+
+            let mut definition = Definition::new(is_public(&item.vis), item_);
+            for variant in &item.variants {
+                if is_hidden(&variant.attrs) {
+                    continue;
+                }
+
+                let mut signature =
+                    format!("{}{}", variant.ident, fields_signature(&variant.fields));
+                if let Some((_, discriminant)) = &variant.discriminant {
+                    signature = format!("{signature} = {}", tidy(discriminant));
+                }
+
+                let mut member = ApiItem::new(ItemKind::Variant, signature);
+                member.non_exhaustive = is_non_exhaustive(&variant.attrs);
+                definition.members.insert(variant.ident.to_string(), member);
+            }
+            (&item.ident, definition)
+        }
+        Item::Trait(item) if !is_hidden(&item.attrs) => {
+            let mut signature = format!("trait {}{}", item.ident, tidy(&item.generics));
+            if item.auto_token.is_some() {
+                signature = format!("auto {signature}");
+            }
+            if item.unsafety.is_some() {
+                signature = format!("unsafe {signature}");
+            }
+            if !item.supertraits.is_empty() {
+                signature = format!("{signature}: {}", tidy(&item.supertraits));
+            }
+            let signature = with_where_clause(signature, &item.generics.where_clause);
+            let item_ = ApiItem::new(ItemKind::Trait, signature);
+
+            let mut definition = Definition::new(is_public(&item.vis), item_);
+            definition.members = trait_members(&item.items);
+            (&item.ident, definition)
+        }
+        Item::Const(item) if !is_hidden(&item.attrs) => {
+            let signature = format!("const {}: {}", item.ident, tidy(&item.ty));
+            let item_ = ApiItem::new(ItemKind::Constant, signature);
+            (&item.ident, Definition::new(is_public(&item.vis), item_))
+        }
+        Item::Static(item) if !is_hidden(&item.attrs) => {
+            let keyword = match item.mutability {
+                StaticMutability::Mut(_) => "static mut",
+                _ => "static",
+            };
+            let signature = format!("{keyword} {}: {}", item.ident, tidy(&item.ty));
+            let item_ = ApiItem::new(ItemKind::Static, signature);
+            (&item.ident, Definition::new(is_public(&item.vis), item_))
+        }
+        Item::Type(item) if !is_hidden(&item.attrs) => {
+            let signature = format!(
+                "type {}{} = {}",
+                item.ident,
+                tidy(&item.generics),
+                tidy(&item.ty)
+            );
+            let item_ = ApiItem::new(ItemKind::TypeAlias, signature);
+            (&item.ident, Definition::new(is_public(&item.vis), item_))
+        }
+        _ => return None,
+    };
+
+    Some((name.to_string(), definition))
+}
+
+fn foreign_definition(item: &ForeignItem) -> Option<(String, Definition)> {
+    let (name, definition) = match item {
+        ForeignItem::Fn(item) if !is_hidden(&item.attrs) => {
+            let item_ = ApiItem::new(ItemKind::Function, fn_signature(&item.sig));
+            (
+                &item.sig.ident,
+                Definition::new(is_public(&item.vis), item_),
+            )
+        }
+        ForeignItem::Static(item) if !is_hidden(&item.attrs) => {
+            let signature = format!("static {}: {}", item.ident, tidy(&item.ty));
+            let item_ = ApiItem::new(ItemKind::Static, signature);
+            (&item.ident, Definition::new(is_public(&item.vis), item_))
+        }
+        _ => return None,
+    };
+
+    Some((name.to_string(), definition))
+}
+
+/// Returns the public fields, by name or by index for tuple structs.
+fn field_members(fields: &Fields) -> BTreeMap<String, ApiItem> {
+    fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| is_public(&field.vis) && !is_hidden(&field.attrs))
+        .map(|(index, field)| {
+            let name = match &field.ident {
+                Some(ident) => ident.to_string(),
+                None => index.to_string(),
+            };
+            (name, ApiItem::new(ItemKind::Field, tidy(&field.ty)))
+        })
+        .collect()
+}
+
+fn fields_signature(fields: &Fields) -> String {
+    match fields {
+        Fields::Named(fields) => {
+            let fields = fields
+                .named
+                .iter()
+                .filter_map(|field| Some((field.ident.as_ref()?, &field.ty)))
+                .map(|(ident, ty)| format!("{ident}: {}", tidy(ty)))
+                .collect::<Vec<_>>();
+            format!(" {{ {} }}", fields.join(", "))
+        }
+        Fields::Unnamed(fields) => {
+            let fields = fields
+                .unnamed
+                .iter()
+                .map(|field| tidy(&field.ty))
+                .collect::<Vec<_>>();
+            format!("({})", fields.join(", "))
+        }
+        Fields::Unit => String::new(),
+    }
+}
+
+fn trait_members(items: &[TraitItem]) -> BTreeMap<String, ApiItem> {
+    let mut members = BTreeMap::new();
+    for item in items {
+        let (name, signature, has_default) = match item {
+            TraitItem::Fn(item) if !is_hidden(&item.attrs) => {
+                let signature = fn_signature(&item.sig);
+                (&item.sig.ident, signature, item.default.is_some())
+            }
+            TraitItem::Type(item) if !is_hidden(&item.attrs) => {
+                let mut signature = format!("type {}{}", item.ident, tidy(&item.generics));
+                if !item.bounds.is_empty() {
+                    signature = format!("{signature}: {}", tidy(&item.bounds));
+                }
+                (&item.ident, signature, item.default.is_some())
+            }
+            TraitItem::Const(item) if !is_hidden(&item.attrs) => {
+                let signature = format!("const {}: {}", item.ident, tidy(&item.ty));
+                (&item.ident, signature, item.default.is_some())
+            }
+            _ => continue,
+        };
+
+        let mut member = ApiItem::new(ItemKind::TraitItem, signature);
+        member.has_default = has_default;
+        members.insert(name.to_string(), member);
+    }
+
+    members
+}
+
+fn impl_members(items: &[ImplItem]) -> BTreeMap<String, ApiItem> {
+    let mut members = BTreeMap::new();
+    for item in items {
+        let (name, member) = match item {
+            ImplItem::Fn(item) if is_public(&item.vis) && !is_hidden(&item.attrs) => {
+                let member = ApiItem::new(ItemKind::Method, fn_signature(&item.sig));
+                (&item.sig.ident, member)
+            }
+            ImplItem::Const(item) if is_public(&item.vis) && !is_hidden(&item.attrs) => {
+                let signature = format!("const {}: {}", item.ident, tidy(&item.ty));
+                (&item.ident, ApiItem::new(ItemKind::Constant, signature))
+            }
+            _ => continue,
+        };
+
+        members.insert(name.to_string(), member);
+    }
+
+    members
+}
+
+/// Renders a function signature without the argument names, since they
+/// don't affect users of the function.
+fn fn_signature(sig: &Signature) -> String {
+    let mut signature = String::new();
+    if sig.constness.is_some() {
+        signature.push_str("const ");
+    }
+    if sig.asyncness.is_some() {
+        signature.push_str("async ");
+    }
+    if sig.unsafety.is_some() {
+        signature.push_str("unsafe ");
+    }
+    if let Some(abi) = &sig.abi {
+        signature.push_str(&tidy(abi));
+        signature.push(' ');
+    }
+
+    let inputs = sig
+        .inputs
+        .iter()
+        .map(|input| match input {
+            FnArg::Receiver(receiver) => {
+                let mut receiver = receiver.clone();
+                receiver.attrs.clear();
+                tidy(receiver)
+            }
+            FnArg::Typed(pat_type) => tidy(&pat_type.ty),
+        })
+        .collect::<Vec<_>>();
+
+    signature.push_str(&format!(
+        "fn {}{}({})",
+        sig.ident,
+        tidy(&sig.generics),
+        inputs.join(", ")
+    ));
+
+    if let ReturnType::Type(_, ty) = &sig.output {
+        signature.push_str(&format!(" -> {}", tidy(ty)));
+    }
+
+    with_where_clause(signature, &sig.generics.where_clause)
+}
+
+fn with_where_clause(signature: String, where_clause: &Option<syn::WhereClause>) -> String {
+    match where_clause {
+        Some(where_clause) => format!("{signature} {}", tidy(where_clause)),
+        None => signature,
+    }
+}
+
+fn flatten_use_tree(tree: &UseTree, prefix: Vec<String>, imports: &mut Vec<Import>) {
+    match tree {
+        UseTree::Path(tree) => {
+            let mut prefix = prefix;
+            prefix.push(tree.ident.to_string());
+            flatten_use_tree(&tree.tree, prefix, imports);
+        }
+        UseTree::Name(tree) => {
+            let name = tree.ident.to_string();
+            if name == "self" {
+                let name = prefix.last().cloned();
+                imports.push(Import { path: prefix, name });
+            } else {
+                let mut path = prefix;
+                path.push(name.clone());
+                imports.push(Import {
+                    path,
+                    name: Some(name),
+                });
+            }
+        }
+        UseTree::Rename(tree) => {
+            let mut path = prefix;
+            if tree.ident != "self" {
+                path.push(tree.ident.to_string());
+            }
+            let name = Some(tree.rename.to_string());
+            imports.push(Import { path, name });
+        }
+        UseTree::Glob(_) => {
+            imports.push(Import {
+                path: prefix,
+                name: None,
+            });
+        }
+        UseTree::Group(group) => {
+            for tree in &group.items {
+                flatten_use_tree(tree, prefix.clone(), imports);
+            }
+        }
+    }
+}
+
+/// The target of a `pub use` declaration.
+enum Target<'a> {
+    Item(&'a Definition),
+    Module(ModulePath),
+}
+
+/// Builds the public API surface from the collected modules.
+struct Exporter<'a> {
+    modules: &'a BTreeMap<ModulePath, Module>,
+    items: BTreeMap<String, ApiItem>,
+    /// The modules that are currently being exported, to avoid infinite
+    /// recursion for cyclic glob imports.
+    stack: HashSet<ModulePath>,
+}
+
+impl<'a> Exporter<'a> {
+    fn export_module(&mut self, path: &[String], prefix: &str) {
+        let modules = self.modules;
+        let Some(module) = modules.get(path) else {
+            return;
+        };
+
+        if !self.stack.insert(path.to_vec()) {
+            return;
+        }
+
+        for (name, definition) in &module.items {
+            if definition.public {
+                self.export_definition(&join_path(prefix, name), definition);
+            }
+        }
+
+        for (name, public) in &module.modules {
+            if *public {
+                let mut child = path.to_vec();
+                child.push(name.clone());
+                self.export_child_module(&child, &join_path(prefix, name));
+            }
+        }
+
+        for import in &module.imports {
+            let target = self.resolve(path, &import.path, 0);
+            match (&import.name, target) {
+                (Some(name), Some(Target::Item(definition))) => {
+                    self.export_definition(&join_path(prefix, name), definition);
+                }
+                (Some(name), Some(Target::Module(target))) => {
+                    self.export_child_module(&target, &join_path(prefix, name));
+                }
+                (Some(name), None) => {
+                    let signature = format!("pub use {}", import.path.join("::"));
+                    let item = ApiItem::new(ItemKind::Reexport, signature);
+                    self.items.insert(join_path(prefix, name), item);
+                }
+                (None, Some(Target::Module(target))) => {
+                    self.export_module(&target, prefix);
+                }
+                (None, _) => {
+                    let signature = format!("pub use {}::*", import.path.join("::"));
+                    let path = join_path(prefix, &format!("{}::*", import.path.join("::")));
+                    let item = ApiItem::new(ItemKind::Reexport, signature);
+                    self.items.insert(path, item);
+                }
+            }
+        }
+
+        self.stack.remove(path);
+    }
+
+    fn export_child_module(&mut self, path: &[String], prefix: &str) {
+        let name = path.last().map(String::as_str).unwrap_or_default();
+        let item = ApiItem::new(ItemKind::Module, format!("mod {name}"));
+        self.items.entry(prefix.to_string()).or_insert(item);
+        self.export_module(path, prefix);
+    }
+
+    fn export_definition(&mut self, path: &str, definition: &Definition) {
+        self.items.insert(path.to_string(), definition.item.clone());
+        for (name, member) in &definition.members {
+            self.items.insert(join_path(path, name), member.clone());
+        }
+    }
+
+    /// Resolves the path of a `use` declaration in the given module to an
+    /// item or module of the crate.
+    fn resolve(&self, module: &[String], path: &[String], depth: usize) -> Option<Target<'a>> {
+        let (first, rest) = path.split_first()?;
+
+        let mut current = match first.as_str() {
+            "crate" => Vec::new(),
+            "self" => module.to_vec(),
+            "super" => module.split_last().map(|(_, parent)| parent.to_vec())?,
+            _ => module.to_vec(),
+        };
+
+        let segments = match first.as_str() {
+            "crate" | "self" | "super" => rest,
+            _ => path,
+        };
+
+        let Some((last, segments)) = segments.split_last() else {
+            return Some(Target::Module(current));
+        };
+
+        for segment in segments {
+            if segment == "super" {
+                current.pop()?;
+                continue;
+            }
+
+            current.push(segment.clone());
+            if !self.modules.contains_key(&current) {
+                return None;
+            }
+        }
+
+        let modules = self.modules;
+        let module_ = modules.get(&current)?;
+        if let Some(definition) = module_.items.get(last) {
+            return Some(Target::Item(definition));
+        }
+
+        let mut child = current.clone();
+        child.push(last.clone());
+        if self.modules.contains_key(&child) {
+            return Some(Target::Module(child));
+        }
+
+        if depth < MAX_IMPORT_DEPTH {
+            let import = module_
+                .imports
+                .iter()
+                .find(|import| import.name.as_ref() == Some(last))?;
+
+            return self.resolve(&current, &import.path, depth + 1);
+        }
+
+        None
+    }
+}
+
+fn is_public(vis: &Visibility) -> bool {
+    matches!(vis, Visibility::Public(_))
+}
+
+/// Returns whether the item is `#[doc(hidden)]` or only exists in tests.
+fn is_hidden(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| match &attr.meta {
+        Meta::List(list) if list.path.is_ident("doc") => list.tokens.to_string() == "hidden",
+        Meta::List(list) if list.path.is_ident("cfg") => list.tokens.to_string() == "test",
+        _ => false,
+    })
+}
+
+fn is_non_exhaustive(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("non_exhaustive"))
+}
+
+fn is_macro_export(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("macro_export"))
+}
+
+fn path_attr(attrs: &[Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr| match &attr.meta {
+        Meta::NameValue(meta) if meta.path.is_ident("path") => match &meta.value {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(lit),
+                ..
+            }) => Some(lit.value()),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+fn join_path(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{prefix}::{name}")
+    }
+}
+
+/// Returns the directory of a file path.
+fn parent(path: &str) -> String {
+    path.rsplit_once('/')
+        .map(|(parent, _)| parent.to_string())
+        .unwrap_or_default()
+}
+
+/// Joins two relative file paths, resolving `.` and `..` components.
+fn join(base: &str, path: &str) -> String {
+    let mut components: Vec<&str> = base.split('/').filter(|c| !c.is_empty()).collect();
+    for component in path.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+
+    components.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn surface(files: &[(&str, &str)]) -> Vec<String> {
+        let files = files
+            .iter()
+            .map(|(path, contents)| (path.to_string(), contents.to_string()))
+            .collect();
+
+        let surface = ApiSurface::from_sources("src/lib.rs", &files).unwrap();
+        surface
+            .items()
+            .iter()
+            .map(|(path, item)| format!("{path}: {}", item.signature))
+            .collect()
+    }
+
+    #[test]
+    fn test_items() {
+        let lib = r#"
+            pub fn parse<'a, T: Into<String>>(input: &'a str, options: T) -> Result<Value<'a>, Error> { todo!() }
+            pub async unsafe fn raw(ptr: *const u8) {}
+            fn private() {}
+            pub(crate) fn internal() {}
+
+            #[non_exhaustive]
+            pub struct Options { pub strict: bool, limit: usize }
+            pub struct Point(pub i32, pub i32);
+
+            pub enum Value<'a> { Null, Str(&'a str), Map { len: usize } }
+            pub trait Visitor: Sized {
+                type Output;
+                fn visit(&mut self, value: Value<'_>) -> Self::Output;
+                fn done(self) {}
+            }
+
+            impl Options {
+                pub fn new() -> Self { todo!() }
+                fn helper(&self) {}
+            }
+
+            pub const MAX: usize = 8;
+            pub static mut COUNTER: u64 = 0;
+            pub type Result<T> = std::result::Result<T, Error>;
+
+            #[doc(hidden)]
+            pub fn __private() {}
+
+            #[macro_export]
+            macro_rules! value { () => {} }
+        "#;
+
+        insta::assert_debug_snapshot!(surface(&[("src/lib.rs", lib)]), @r#"
+        [
+            "COUNTER: static mut COUNTER: u64",
+            "MAX: const MAX: usize",
+            "Options: struct Options",
+            "Options::new: fn new() -> Self",
+            "Options::strict: bool",
+            "Point: struct Point",
+            "Point::0: i32",
+            "Point::1: i32",
+            "Result: type Result<T> = std::result::Result<T, Error>",
+            "Value: enum Value<'a>",
+            "Value::Map: Map { len: usize }",
+            "Value::Null: Null",
+            "Value::Str: Str(&'a str)",
+            "Visitor: trait Visitor: Sized",
+            "Visitor::Output: type Output",
+            "Visitor::done: fn done(self)",
+            "Visitor::visit: fn visit(&mut self, Value<'_>) -> Self::Output",
+            "parse: fn parse<'a, T: Into<String>>(&'a str, T) -> Result<Value<'a>, Error>",
+            "raw: async unsafe fn raw(*const u8)",
+            "value: macro_rules! value",
+        ]
+        "#);
+    }
+
+    #[test]
+    fn test_modules_and_reexports() {
+        let lib = r#"
+            pub mod de;
+            mod error;
+            mod inline {
+                pub mod nested;
+                pub struct Inline;
+            }
+            #[path = "platform/unix.rs"]
+            pub mod sys;
+            pub mod generated;
+
+            pub use crate::error::{Error, Result as ErrorResult};
+            pub use inline::*;
+            pub use serde_derive::Deserialize;
+        "#;
+
+        let de = "pub trait Deserialize {} pub use self::value::*; mod value;";
+        let de_value = "pub struct ValueDeserializer;";
+        let error = "pub struct Error; pub type Result = (); impl Error { pub fn kind(&self) {} }";
+        let nested = "pub fn nested() {}";
+        let sys = "pub fn fd() -> i32 { 0 }";
+
+        insta::assert_debug_snapshot!(surface(&[
+            ("src/lib.rs", lib),
+            ("src/de/mod.rs", de),
+            ("src/de/value.rs", de_value),
+            ("src/error.rs", error),
+            ("src/inline/nested.rs", nested),
+            ("src/platform/unix.rs", sys),
+        ]), @r#"
+        [
+            "Deserialize: pub use serde_derive::Deserialize",
+            "Error: struct Error",
+            "Error::kind: fn kind(&self)",
+            "ErrorResult: type Result = ()",
+            "Inline: struct Inline",
+            "de: mod de",
+            "de::Deserialize: trait Deserialize",
+            "de::ValueDeserializer: struct ValueDeserializer",
+            "generated: mod generated",
+            "nested: mod nested",
+            "nested::nested: fn nested()",
+            "sys: mod sys",
+            "sys::fd: fn fd() -> i32",
+        ]
+        "#);
+    }
+
+    #[test]
+    fn test_missing_root() {
+        let files = HashMap::new();
+        let error = ApiSurface::from_sources("src/lib.rs", &files).unwrap_err();
+        assert_eq!(error.to_string(), "Missing source file `src/lib.rs`");
+    }
+
+    #[test]
+    fn test_module_cycles() {
+        let lib = r#"#[path = "lib.rs"] pub mod again;"#;
+        let files = HashMap::from([("src/lib.rs".to_string(), lib.to_string())]);
+        let error = ApiSurface::from_sources("src/lib.rs", &files).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Source file `src/lib.rs` is included as a module more than once"
+        );
+
+        let files = HashMap::from([
+            ("src/lib.rs".to_string(), "pub mod a;".to_string()),
+            (
+                "src/a.rs".to_string(),
+                r#"#[path = "b.rs"] pub mod b;"#.to_string(),
+            ),
+            (
+                "src/b.rs".to_string(),
+                r#"#[path = "a.rs"] pub mod a;"#.to_string(),
+            ),
+        ]);
+        let error = ApiSurface::from_sources("src/lib.rs", &files).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Source file `src/a.rs` is included as a module more than once"
+        );
+    }
+
+    #[test]
+    fn test_module_depth() {
+        let lib = "pub mod m { ".repeat(40) + &"}".repeat(40);
+        let files = HashMap::from([("src/lib.rs".to_string(), lib)]);
+        let error = ApiSurface::from_sources("src/lib.rs", &files).unwrap_err();
+        assert!(error.to_string().ends_with("is nested too deeply"));
+    }
+
+    #[test]
+    fn test_join() {
+        assert_eq!(join("src", "foo.rs"), "src/foo.rs");
+        assert_eq!(join("src/foo", "../bar/mod.rs"), "src/bar/mod.rs");
+        assert_eq!(join("", "./src/lib.rs"), "src/lib.rs");
+    }
+}
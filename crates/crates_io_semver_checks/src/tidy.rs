@@ -0,0 +1,54 @@
+use quote::ToTokens;
+
+/// Renders the tokens as a string, with the whitespace that `proc-macro2`
+/// inserts between all tokens removed where `rustfmt` would not put it.
+pub fn tidy(tokens: impl ToTokens) -> String {
+    let string = tokens
+        .to_token_stream()
+        .to_string()
+        .replace(" :: ", "::")
+        .replace(":: ", "::")
+        .replace(" ,", ",")
+        .replace(" ;", ";")
+        .replace(" : ", ": ")
+        .replace(" < ", "<")
+        .replace("< ", "<")
+        .replace(" >", ">")
+        .replace("& ", "&")
+        .replace("* const ", "*const ")
+        .replace("* mut ", "*mut ");
+
+    // Remove the whitespace between a name and its parentheses, e.g. in
+    // `Fn (u8)`, but keep it in `-> (u8, u8)` or `(u8, (u8, u8))`.
+    let mut result = String::with_capacity(string.len());
+    let mut previous = None;
+    let mut chars = string.chars().peekable();
+    while let Some(char) = chars.next() {
+        let is_word = previous.is_some_and(|c: char| c.is_alphanumeric() || c == '_');
+        if char == ' ' && is_word && chars.peek() == Some(&'(') {
+            continue;
+        }
+
+        result.push(char);
+        previous = Some(char);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tidy() {
+        let ty: syn::Type = syn::parse_quote!(&'a mut Vec<(u8, Box<dyn Fn(u8) -> (u8, u8)>)>);
+        assert_eq!(tidy(ty), "&'a mut Vec<(u8, Box<dyn Fn(u8) -> (u8, u8)>)>");
+
+        let ty: syn::Type = syn::parse_quote!(std::collections::HashMap<String, T>);
+        assert_eq!(tidy(ty), "std::collections::HashMap<String, T>");
+
+        let ty: syn::Type = syn::parse_quote!(impl Iterator<Item = &'static str>);
+        assert_eq!(tidy(ty), "impl Iterator<Item = &'static str>");
+    }
+}
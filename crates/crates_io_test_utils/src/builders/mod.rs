@@ -6,4 +6,4 @@ mod version;
 pub use self::dependency::DependencyBuilder;
 pub use self::krate::CrateBuilder;
 pub use self::publish::PublishBuilder;
-pub use self::version::VersionBuilder;
+pub use self::version::{VersionBuilder, VersionDependency};
@@ -1,24 +1,28 @@
 use crates_io_database::models::versions_published_by;
-use crates_io_database::models::{Crate, NewVersion, Version};
+use crates_io_database::models::{Crate, DependencyKind, NewPendingVersion, NewVersion, Version};
 use crates_io_database::schema::dependencies;
 use std::collections::BTreeMap;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeDelta, Utc};
 use diesel::prelude::*;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 
 /// A builder to create version records for the purpose of inserting directly into the database.
 pub struct VersionBuilder {
     created_at: Option<DateTime<Utc>>,
-    dependencies: Vec<(i32, Option<&'static str>)>,
+    dependencies: Vec<VersionDependency>,
     features: BTreeMap<String, Vec<String>>,
     license: Option<String>,
     num: semver::Version,
     size: i32,
     yanked: bool,
+    pending: bool,
     checksum: String,
     links: Option<String>,
     rust_version: Option<String>,
+    edition: Option<String>,
+    has_lib: Option<bool>,
+    bin_names: Vec<String>,
 }
 
 #[allow(dead_code)]
@@ -42,9 +46,13 @@ impl VersionBuilder {
             num,
             size: 0,
             yanked: false,
+            pending: false,
             checksum: String::new(),
             links: None,
             rust_version: None,
+            edition: None,
+            has_lib: None,
+            bin_names: Vec::new(),
         }
     }
 
@@ -67,8 +75,18 @@ impl VersionBuilder {
     }
 
     /// Adds a dependency to this version.
-    pub fn dependency(mut self, dependency: &Crate, target: Option<&'static str>) -> Self {
-        self.dependencies.push((dependency.id, target));
+    ///
+    /// Pass a `&Crate` for a plain dependency, or a [`VersionDependency`] to
+    /// also set the version requirement, kind, target or features.
+    pub fn dependency(mut self, dependency: impl Into<VersionDependency>) -> Self {
+        self.dependencies.push(dependency.into());
+        self
+    }
+
+    /// Adds a feature to this version.
+    pub fn feature(mut self, name: &str, values: &[&str]) -> Self {
+        let values = values.iter().map(ToString::to_string).collect();
+        self.features.insert(name.to_string(), values);
         self
     }
 
@@ -77,6 +95,11 @@ impl VersionBuilder {
         Self { yanked, ..self }
     }
 
+    /// Sets whether the version is still waiting for publish approval.
+    pub fn pending(self, pending: bool) -> Self {
+        Self { pending, ..self }
+    }
+
     /// Sets the version's size.
     pub fn size(mut self, size: i32) -> Self {
         self.size = size;
@@ -89,6 +112,24 @@ impl VersionBuilder {
         self
     }
 
+    /// Sets the version's `edition` value.
+    pub fn edition(mut self, edition: &str) -> Self {
+        self.edition = Some(edition.to_owned());
+        self
+    }
+
+    /// Sets the version's `has_lib` value.
+    pub fn has_lib(mut self, has_lib: bool) -> Self {
+        self.has_lib = Some(has_lib);
+        self
+    }
+
+    /// Adds a binary target name to this version.
+    pub fn bin_name(mut self, bin_name: &str) -> Self {
+        self.bin_names.push(bin_name.to_owned());
+        self
+    }
+
     pub async fn build(
         self,
         crate_id: i32,
@@ -98,6 +139,11 @@ impl VersionBuilder {
         use diesel::insert_into;
 
         let version = self.num.to_string();
+        let bin_names = self
+            .bin_names
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
 
         let new_version = NewVersion::builder(crate_id, &version)
             .features(serde_json::to_value(&self.features)?)
@@ -107,6 +153,9 @@ impl VersionBuilder {
             .checksum(&self.checksum)
             .maybe_links(self.links.as_deref())
             .maybe_rust_version(self.rust_version.as_deref())
+            .maybe_edition(self.edition.as_deref())
+            .maybe_has_lib(self.has_lib)
+            .maybe_bin_names((!bin_names.is_empty()).then_some(bin_names.as_slice()))
             .yanked(self.yanked)
             .maybe_created_at(self.created_at.as_ref())
             .build();
@@ -114,18 +163,28 @@ impl VersionBuilder {
         let vers = new_version.save(connection).await?;
         versions_published_by::insert(vers.id, "someone@example.com", connection).await?;
 
+        if self.pending {
+            NewPendingVersion {
+                version_id: vers.id,
+                expires_at: Utc::now() + TimeDelta::days(3),
+            }
+            .insert(connection)
+            .await?;
+        }
+
         let new_deps = self
             .dependencies
             .into_iter()
-            .map(|(crate_id, target)| {
+            .map(|dependency| {
                 (
                     dependencies::version_id.eq(vers.id),
-                    dependencies::req.eq(">= 0"),
-                    dependencies::crate_id.eq(crate_id),
-                    dependencies::target.eq(target),
-                    dependencies::optional.eq(false),
-                    dependencies::default_features.eq(false),
-                    dependencies::features.eq(Vec::<String>::new()),
+                    dependencies::req.eq(dependency.req),
+                    dependencies::crate_id.eq(dependency.crate_id),
+                    dependencies::target.eq(dependency.target),
+                    dependencies::optional.eq(dependency.optional),
+                    dependencies::default_features.eq(dependency.default_features),
+                    dependencies::features.eq(dependency.features),
+                    dependencies::kind.eq(dependency.kind),
                 )
             })
             .collect::<Vec<_>>();
@@ -162,3 +221,71 @@ impl<'a> From<&'a str> for VersionBuilder {
         VersionBuilder::new(num)
     }
 }
+
+/// A builder for a dependency of a version created by a [`VersionBuilder`].
+pub struct VersionDependency {
+    crate_id: i32,
+    req: String,
+    kind: DependencyKind,
+    optional: bool,
+    default_features: bool,
+    target: Option<String>,
+    features: Vec<String>,
+}
+
+impl VersionDependency {
+    /// Creates a normal, non-optional dependency on the given crate.
+    pub fn new(krate: &Crate) -> Self {
+        VersionDependency {
+            crate_id: krate.id,
+            req: ">= 0".to_string(),
+            kind: DependencyKind::Normal,
+            optional: false,
+            default_features: false,
+            target: None,
+            features: Vec::new(),
+        }
+    }
+
+    /// Sets the version requirement of the dependency.
+    pub fn req(mut self, req: &str) -> Self {
+        self.req = req.to_string();
+        self
+    }
+
+    /// Sets the kind of the dependency.
+    pub fn kind(self, kind: DependencyKind) -> Self {
+        Self { kind, ..self }
+    }
+
+    /// Sets whether the dependency is optional.
+    pub fn optional(self, optional: bool) -> Self {
+        Self { optional, ..self }
+    }
+
+    /// Sets whether the default features of the dependency are enabled.
+    pub fn default_features(self, default_features: bool) -> Self {
+        Self {
+            default_features,
+            ..self
+        }
+    }
+
+    /// Sets the target platform of the dependency, e.g. `cfg(windows)`.
+    pub fn target(mut self, target: &str) -> Self {
+        self.target = Some(target.to_string());
+        self
+    }
+
+    /// Adds a feature of the dependency that is enabled.
+    pub fn feature(mut self, feature: &str) -> Self {
+        self.features.push(feature.to_string());
+        self
+    }
+}
+
+impl From<&Crate> for VersionDependency {
+    fn from(krate: &Crate) -> Self {
+        VersionDependency::new(krate)
+    }
+}
@@ -0,0 +1,3 @@
+ALTER TABLE versions
+    DROP COLUMN docs_rs_status,
+    DROP COLUMN docs_rs_status_checked_at;
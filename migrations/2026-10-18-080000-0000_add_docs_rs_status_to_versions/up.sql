@@ -0,0 +1,6 @@
+ALTER TABLE versions
+    ADD COLUMN docs_rs_status INTEGER,
+    ADD COLUMN docs_rs_status_checked_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN versions.docs_rs_status IS 'Cached status of the docs.rs documentation build (0 = success, 1 = failure, 2 = queued). NULL if the status has not been checked yet.';
+COMMENT ON COLUMN versions.docs_rs_status_checked_at IS 'Time at which the docs.rs documentation build status was last checked.';
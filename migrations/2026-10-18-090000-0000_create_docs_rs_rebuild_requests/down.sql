@@ -0,0 +1 @@
+DROP TABLE docs_rs_rebuild_requests;
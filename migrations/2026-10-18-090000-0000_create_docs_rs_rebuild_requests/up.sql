@@ -0,0 +1,29 @@
+CREATE TABLE docs_rs_rebuild_requests (
+    id BIGSERIAL PRIMARY KEY,
+    version_id INTEGER NOT NULL REFERENCES versions (id) ON DELETE CASCADE,
+    requested_by INTEGER REFERENCES users (id) ON DELETE SET NULL,
+    requested_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    status INTEGER NOT NULL DEFAULT 0,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_attempt_at TIMESTAMPTZ,
+    next_attempt_at TIMESTAMPTZ,
+    response TEXT
+);
+
+COMMENT ON TABLE docs_rs_rebuild_requests IS 'History of docs.rs rebuild requests for crate versions';
+COMMENT ON COLUMN docs_rs_rebuild_requests.id IS 'Unique identifier of the rebuild request';
+COMMENT ON COLUMN docs_rs_rebuild_requests.version_id IS 'The version for which a docs rebuild was requested';
+COMMENT ON COLUMN docs_rs_rebuild_requests.requested_by IS 'The user who requested the rebuild';
+COMMENT ON COLUMN docs_rs_rebuild_requests.requested_at IS 'Date and time when the rebuild was requested';
+COMMENT ON COLUMN docs_rs_rebuild_requests.status IS '0 = pending, 1 = queued, 2 = rate limited, 3 = rejected, 4 = failed';
+COMMENT ON COLUMN docs_rs_rebuild_requests.attempts IS 'Number of times the request was sent to docs.rs';
+COMMENT ON COLUMN docs_rs_rebuild_requests.last_attempt_at IS 'Date and time of the last attempt to send the request to docs.rs';
+COMMENT ON COLUMN docs_rs_rebuild_requests.next_attempt_at IS 'Date and time after which a rate limited request will be retried';
+COMMENT ON COLUMN docs_rs_rebuild_requests.response IS 'Error message returned by docs.rs, if any';
+
+CREATE INDEX docs_rs_rebuild_requests_version_id_idx
+    ON docs_rs_rebuild_requests (version_id);
+
+CREATE INDEX docs_rs_rebuild_requests_next_attempt_at_idx
+    ON docs_rs_rebuild_requests (next_attempt_at)
+    WHERE status = 2;
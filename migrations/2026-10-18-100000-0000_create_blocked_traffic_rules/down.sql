@@ -0,0 +1 @@
+DROP TABLE blocked_traffic_rules;
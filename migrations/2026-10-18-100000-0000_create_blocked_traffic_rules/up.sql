@@ -0,0 +1,26 @@
+CREATE TABLE blocked_traffic_rules (
+    id SERIAL PRIMARY KEY,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ,
+    ip_range CIDR,
+    header_name TEXT,
+    header_pattern TEXT,
+    route_pattern TEXT,
+    message TEXT,
+    reason TEXT NOT NULL,
+    CONSTRAINT blocked_traffic_rules_has_condition_check
+        CHECK (ip_range IS NOT NULL OR header_name IS NOT NULL OR route_pattern IS NOT NULL),
+    CONSTRAINT blocked_traffic_rules_header_check
+        CHECK ((header_name IS NULL) = (header_pattern IS NULL))
+);
+
+COMMENT ON TABLE blocked_traffic_rules IS 'Rules for blocking incoming requests, periodically reloaded by the web servers. A request is blocked if it matches all conditions of a rule.';
+COMMENT ON COLUMN blocked_traffic_rules.id IS 'Unique identifier of the rule';
+COMMENT ON COLUMN blocked_traffic_rules.created_at IS 'Date and time when the rule was created';
+COMMENT ON COLUMN blocked_traffic_rules.expires_at IS 'Date and time after which the rule is no longer applied. NULL if the rule does not expire.';
+COMMENT ON COLUMN blocked_traffic_rules.ip_range IS 'IP address range that the client IP address has to be in';
+COMMENT ON COLUMN blocked_traffic_rules.header_name IS 'Name of the request header that has to match `header_pattern`';
+COMMENT ON COLUMN blocked_traffic_rules.header_pattern IS 'Pattern that the value of the `header_name` header has to match. `*` matches any sequence of characters.';
+COMMENT ON COLUMN blocked_traffic_rules.route_pattern IS 'Pattern that the route of the request has to match (e.g. `/api/v1/crates/{name}/{version}/download`). `*` matches any sequence of characters.';
+COMMENT ON COLUMN blocked_traffic_rules.message IS 'Custom message that is returned to blocked clients. NULL to use the default message.';
+COMMENT ON COLUMN blocked_traffic_rules.reason IS 'Internal note explaining why the rule was created';
@@ -0,0 +1,3 @@
+-- Speeds up looking up crates by the names of their binary targets.
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_versions_bin_names
+ON versions USING GIN (bin_names);
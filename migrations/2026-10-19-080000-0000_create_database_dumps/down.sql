@@ -0,0 +1,9 @@
+DROP TRIGGER trigger_deleted_crates_record_dump_tombstone ON deleted_crates;
+DROP TRIGGER trigger_crate_owners_record_dump_tombstone ON crate_owners;
+DROP TRIGGER trigger_version_downloads_record_dump_tombstone ON version_downloads;
+DROP TRIGGER trigger_dependencies_record_dump_tombstone ON dependencies;
+DROP TRIGGER trigger_versions_record_dump_tombstone ON versions;
+DROP TRIGGER trigger_crates_record_dump_tombstone ON crates;
+DROP FUNCTION record_dump_tombstone();
+DROP TABLE dump_tombstones;
+DROP TABLE database_dumps;
@@ -0,0 +1,77 @@
+CREATE TABLE database_dumps (
+    id SERIAL PRIMARY KEY,
+    kind INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    since TIMESTAMPTZ,
+    base_id INTEGER REFERENCES database_dumps (id) ON DELETE SET NULL,
+    path TEXT NOT NULL
+);
+
+COMMENT ON TABLE database_dumps IS 'Public database dumps that have been uploaded to the static file storage';
+COMMENT ON COLUMN database_dumps.id IS 'Unique identifier of the database dump';
+COMMENT ON COLUMN database_dumps.kind IS '0 = full snapshot, 1 = incremental';
+COMMENT ON COLUMN database_dumps.created_at IS 'Date and time at which the data of the dump was exported';
+COMMENT ON COLUMN database_dumps.since IS 'For incremental dumps: the export time of the previous dump that the changes are relative to';
+COMMENT ON COLUMN database_dumps.base_id IS 'For incremental dumps: the most recent full snapshot at the time the increment was created';
+COMMENT ON COLUMN database_dumps.path IS 'Path of the dump archive in the static file storage';
+
+CREATE INDEX database_dumps_kind_created_at_idx
+    ON database_dumps (kind, created_at);
+
+CREATE TABLE dump_tombstones (
+    id BIGSERIAL PRIMARY KEY,
+    table_name TEXT NOT NULL,
+    primary_key JSONB NOT NULL,
+    deleted_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE dump_tombstones IS 'Rows that were deleted from tables included in incremental database dumps';
+COMMENT ON COLUMN dump_tombstones.id IS 'Unique identifier of the tombstone';
+COMMENT ON COLUMN dump_tombstones.table_name IS 'Name of the table that the row was deleted from';
+COMMENT ON COLUMN dump_tombstones.primary_key IS 'Primary key columns of the deleted row';
+COMMENT ON COLUMN dump_tombstones.deleted_at IS 'Date and time when the row was deleted';
+
+CREATE INDEX dump_tombstones_deleted_at_idx
+    ON dump_tombstones (deleted_at);
+
+-- Records a tombstone for the deleted row. The trigger arguments are the
+-- names of the primary key columns of the table.
+CREATE FUNCTION record_dump_tombstone() RETURNS trigger AS $$
+DECLARE
+    row_data JSONB := to_jsonb(OLD);
+    primary_key JSONB := '{}';
+BEGIN
+    FOR i IN 0 .. TG_NARGS - 1 LOOP
+        primary_key := primary_key || jsonb_build_object(TG_ARGV[i], row_data -> TG_ARGV[i]);
+    END LOOP;
+
+    INSERT INTO dump_tombstones (table_name, primary_key)
+    VALUES (TG_TABLE_NAME, primary_key);
+
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_crates_record_dump_tombstone
+    AFTER DELETE ON crates
+    FOR EACH ROW EXECUTE FUNCTION record_dump_tombstone('id');
+
+CREATE TRIGGER trigger_versions_record_dump_tombstone
+    AFTER DELETE ON versions
+    FOR EACH ROW EXECUTE FUNCTION record_dump_tombstone('id');
+
+CREATE TRIGGER trigger_dependencies_record_dump_tombstone
+    AFTER DELETE ON dependencies
+    FOR EACH ROW EXECUTE FUNCTION record_dump_tombstone('id');
+
+CREATE TRIGGER trigger_version_downloads_record_dump_tombstone
+    AFTER DELETE ON version_downloads
+    FOR EACH ROW EXECUTE FUNCTION record_dump_tombstone('version_id', 'date');
+
+CREATE TRIGGER trigger_crate_owners_record_dump_tombstone
+    AFTER DELETE ON crate_owners
+    FOR EACH ROW EXECUTE FUNCTION record_dump_tombstone('crate_id', 'owner_id', 'owner_kind');
+
+CREATE TRIGGER trigger_deleted_crates_record_dump_tombstone
+    AFTER DELETE ON deleted_crates
+    FOR EACH ROW EXECUTE FUNCTION record_dump_tombstone('id');
@@ -0,0 +1,12 @@
+CREATE TABLE user_feed_tokens (
+    user_id INTEGER PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    token BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE user_feed_tokens IS 'Secret tokens that give feed readers access to the personalized updates feed of a user';
+COMMENT ON COLUMN user_feed_tokens.user_id IS 'The user whose updates feed can be accessed with this token';
+COMMENT ON COLUMN user_feed_tokens.token IS 'SHA-256 hash of the secret feed token';
+COMMENT ON COLUMN user_feed_tokens.created_at IS 'Date and time when the token was generated';
+
+CREATE UNIQUE INDEX user_feed_tokens_token_idx ON user_feed_tokens (token);
@@ -0,0 +1,12 @@
+CREATE TABLE version_semver_checks (
+    version_id INTEGER PRIMARY KEY REFERENCES versions (id) ON DELETE CASCADE,
+    baseline_version_id INTEGER NOT NULL REFERENCES versions (id) ON DELETE CASCADE,
+    breaking_changes JSONB NOT NULL,
+    checked_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE version_semver_checks IS 'Results of comparing the public API of a version against the previous semver-compatible version';
+COMMENT ON COLUMN version_semver_checks.version_id IS 'The version that was checked';
+COMMENT ON COLUMN version_semver_checks.baseline_version_id IS 'The previous semver-compatible version that the API was compared against';
+COMMENT ON COLUMN version_semver_checks.breaking_changes IS 'The detected changes that likely break users of the baseline version, as a JSON array';
+COMMENT ON COLUMN version_semver_checks.checked_at IS 'Date and time when the check was performed';
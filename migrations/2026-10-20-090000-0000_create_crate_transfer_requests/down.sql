@@ -0,0 +1,2 @@
+DROP TABLE crate_transfer_request_events;
+DROP TABLE crate_transfer_requests;
@@ -0,0 +1,40 @@
+CREATE TABLE crate_transfer_requests (
+    id SERIAL PRIMARY KEY,
+    crate_id INTEGER NOT NULL REFERENCES crates (id) ON DELETE CASCADE,
+    requester_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    status INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    resolved_at TIMESTAMPTZ
+);
+
+COMMENT ON TABLE crate_transfer_requests IS 'Requests by users to take over the ownership of a crate';
+COMMENT ON COLUMN crate_transfer_requests.id IS 'Unique identifier of the transfer request';
+COMMENT ON COLUMN crate_transfer_requests.crate_id IS 'The crate that the requester wants to take over';
+COMMENT ON COLUMN crate_transfer_requests.requester_id IS 'The user that filed the transfer request';
+COMMENT ON COLUMN crate_transfer_requests.reason IS 'Explanation by the requester why the crate should be transferred to them';
+COMMENT ON COLUMN crate_transfer_requests.status IS 'The status of the request (0=pending, 1=accepted by the owners, 2=rejected by the owners, 3=withdrawn by the requester, 4=approved by the crates.io team, 5=denied by the crates.io team)';
+COMMENT ON COLUMN crate_transfer_requests.created_at IS 'Date and time when the request was filed';
+COMMENT ON COLUMN crate_transfer_requests.resolved_at IS 'Date and time when the request left the pending status';
+
+-- Only allow one pending request per crate and requester
+CREATE UNIQUE INDEX crate_transfer_requests_pending_idx ON crate_transfer_requests (crate_id, requester_id) WHERE status = 0;
+
+CREATE TABLE crate_transfer_request_events (
+    id BIGSERIAL PRIMARY KEY,
+    request_id INTEGER NOT NULL REFERENCES crate_transfer_requests (id) ON DELETE CASCADE,
+    action INTEGER NOT NULL,
+    actor_id INTEGER REFERENCES users (id) ON DELETE SET NULL,
+    note TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE crate_transfer_request_events IS 'Audit log of all steps of the crate transfer requests';
+COMMENT ON COLUMN crate_transfer_request_events.id IS 'Unique identifier of the event';
+COMMENT ON COLUMN crate_transfer_request_events.request_id IS 'The transfer request that the event belongs to';
+COMMENT ON COLUMN crate_transfer_request_events.action IS 'The action that was performed (0=created, 1=accepted, 2=rejected, 3=withdrawn, 4=approved, 5=denied)';
+COMMENT ON COLUMN crate_transfer_request_events.actor_id IS 'The user that performed the action';
+COMMENT ON COLUMN crate_transfer_request_events.note IS 'Optional note explaining the action';
+COMMENT ON COLUMN crate_transfer_request_events.created_at IS 'Date and time when the action was performed';
+
+CREATE INDEX crate_transfer_request_events_request_id_idx ON crate_transfer_request_events (request_id);
@@ -0,0 +1,16 @@
+CREATE TABLE scheduled_crate_deletions (
+    crate_id INTEGER PRIMARY KEY REFERENCES crates (id) ON DELETE CASCADE,
+    requested_by INTEGER REFERENCES users (id) ON DELETE SET NULL,
+    requested_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    scheduled_for TIMESTAMPTZ NOT NULL,
+    message VARCHAR
+);
+
+COMMENT ON TABLE scheduled_crate_deletions IS 'Crates that were deleted by their owners, but are kept around until the grace period ends';
+COMMENT ON COLUMN scheduled_crate_deletions.crate_id IS 'The crate that is scheduled for deletion';
+COMMENT ON COLUMN scheduled_crate_deletions.requested_by IS 'ID of the user who requested the deletion, or NULL if the user was deleted';
+COMMENT ON COLUMN scheduled_crate_deletions.requested_at IS 'Date and time when the deletion was requested';
+COMMENT ON COLUMN scheduled_crate_deletions.scheduled_for IS 'Date and time when the crate will be deleted permanently';
+COMMENT ON COLUMN scheduled_crate_deletions.message IS 'Optional message left by the user who requested the deletion';
+
+CREATE INDEX scheduled_crate_deletions_scheduled_for_idx ON scheduled_crate_deletions (scheduled_for);
@@ -0,0 +1,3 @@
+ALTER TABLE crates
+    DROP COLUMN status,
+    DROP COLUMN successor;
@@ -0,0 +1,6 @@
+ALTER TABLE crates
+    ADD COLUMN status INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN successor VARCHAR;
+
+COMMENT ON COLUMN crates.status IS 'The maintenance status of the crate (0=active, 1=passively maintained, 2=deprecated, 3=archived)';
+COMMENT ON COLUMN crates.successor IS 'Name of the crate that replaces this crate, if it is deprecated or archived';
@@ -0,0 +1,2 @@
+DROP TABLE keyword_aliases;
+DROP TABLE category_proposals;
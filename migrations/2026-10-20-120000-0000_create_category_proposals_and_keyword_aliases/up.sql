@@ -0,0 +1,41 @@
+CREATE TABLE category_proposals (
+    id SERIAL PRIMARY KEY,
+    slug VARCHAR NOT NULL,
+    name VARCHAR NOT NULL,
+    description VARCHAR NOT NULL,
+    proposed_by INTEGER REFERENCES users (id) ON DELETE SET NULL,
+    status INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    resolved_by INTEGER REFERENCES users (id) ON DELETE SET NULL,
+    resolved_at TIMESTAMPTZ
+);
+
+COMMENT ON TABLE category_proposals IS 'Proposals by users for new categories, which are reviewed by the crates.io team';
+COMMENT ON COLUMN category_proposals.id IS 'Unique identifier of the proposal';
+COMMENT ON COLUMN category_proposals.slug IS 'The slug of the proposed category, using `::` to separate it from the slug of its parent category';
+COMMENT ON COLUMN category_proposals.name IS 'The display name of the proposed category, without the name of its parent category';
+COMMENT ON COLUMN category_proposals.description IS 'Description of the proposed category';
+COMMENT ON COLUMN category_proposals.proposed_by IS 'The user that proposed the category';
+COMMENT ON COLUMN category_proposals.status IS 'The status of the proposal (0=pending, 1=approved, 2=rejected)';
+COMMENT ON COLUMN category_proposals.created_at IS 'Date and time when the category was proposed';
+COMMENT ON COLUMN category_proposals.resolved_by IS 'The admin that approved or rejected the proposal';
+COMMENT ON COLUMN category_proposals.resolved_at IS 'Date and time when the proposal was approved or rejected';
+
+-- Only allow one pending proposal per slug
+CREATE UNIQUE INDEX category_proposals_pending_slug_idx ON category_proposals (slug) WHERE status = 0;
+
+-- Categories of approved proposals are kept when syncing `categories.toml`
+CREATE INDEX category_proposals_approved_slug_idx ON category_proposals (slug) WHERE status = 1;
+
+CREATE TABLE keyword_aliases (
+    alias VARCHAR PRIMARY KEY,
+    keyword_id INTEGER NOT NULL REFERENCES keywords (id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE keyword_aliases IS 'Keywords that were merged into other keywords';
+COMMENT ON COLUMN keyword_aliases.alias IS 'The name of the merged keyword, which is resolved to `keyword_id` from now on';
+COMMENT ON COLUMN keyword_aliases.keyword_id IS 'The keyword that the alias was merged into';
+COMMENT ON COLUMN keyword_aliases.created_at IS 'Date and time when the keywords were merged';
+
+CREATE INDEX keyword_aliases_keyword_id_idx ON keyword_aliases (keyword_id);
@@ -0,0 +1,4 @@
+DROP TABLE pending_versions;
+
+ALTER TABLE crates
+    DROP COLUMN requires_publish_approval;
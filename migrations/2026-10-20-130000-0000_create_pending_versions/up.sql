@@ -0,0 +1,17 @@
+ALTER TABLE crates
+    ADD COLUMN requires_publish_approval BOOLEAN NOT NULL DEFAULT false;
+
+COMMENT ON COLUMN crates.requires_publish_approval IS 'Whether new versions of the crate have to be approved by a second owner before they are published';
+
+CREATE TABLE pending_versions (
+    version_id INTEGER PRIMARY KEY REFERENCES versions (id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+COMMENT ON TABLE pending_versions IS 'Versions that were uploaded, but are waiting for the approval of a second owner';
+COMMENT ON COLUMN pending_versions.version_id IS 'The version that is waiting for approval';
+COMMENT ON COLUMN pending_versions.created_at IS 'Date and time when the version was uploaded';
+COMMENT ON COLUMN pending_versions.expires_at IS 'Date and time when the version will be deleted if it has not been approved';
+
+CREATE INDEX pending_versions_expires_at_idx ON pending_versions (expires_at);
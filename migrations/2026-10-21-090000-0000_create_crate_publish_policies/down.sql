@@ -0,0 +1,5 @@
+ALTER TABLE trustpub_tokens
+    DROP COLUMN github_config_ids,
+    DROP COLUMN gitlab_config_ids;
+
+DROP TABLE crate_publish_policies;
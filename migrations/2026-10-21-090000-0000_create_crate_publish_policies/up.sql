@@ -0,0 +1,25 @@
+CREATE TABLE crate_publish_policies (
+    crate_id INTEGER PRIMARY KEY REFERENCES crates (id) ON DELETE CASCADE,
+    allowed_publishers INTEGER[] NOT NULL DEFAULT '{}',
+    require_crate_scoped_tokens BOOLEAN NOT NULL DEFAULT false,
+    trustpub_github_config_ids INTEGER[] NOT NULL DEFAULT '{}',
+    trustpub_gitlab_config_ids INTEGER[] NOT NULL DEFAULT '{}',
+    max_token_age_days INTEGER CHECK (max_token_age_days > 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE crate_publish_policies IS 'Restrictions on who can publish new versions of a crate, and how';
+COMMENT ON COLUMN crate_publish_policies.crate_id IS 'The crate that the policy applies to';
+COMMENT ON COLUMN crate_publish_policies.allowed_publishers IS 'IDs of the users that are allowed to publish the crate, or an empty array if all owners are allowed to publish';
+COMMENT ON COLUMN crate_publish_policies.require_crate_scoped_tokens IS 'Whether API tokens need a crate scope naming this crate exactly, instead of a wildcard pattern';
+COMMENT ON COLUMN crate_publish_policies.trustpub_github_config_ids IS 'IDs of the GitHub Trusted Publishing configs that are allowed to publish the crate';
+COMMENT ON COLUMN crate_publish_policies.trustpub_gitlab_config_ids IS 'IDs of the GitLab Trusted Publishing configs that are allowed to publish the crate';
+COMMENT ON COLUMN crate_publish_policies.max_token_age_days IS 'Maximum age of API tokens in days, or NULL if tokens of any age can be used';
+COMMENT ON COLUMN crate_publish_policies.updated_at IS 'Date and time when the policy was last changed';
+
+ALTER TABLE trustpub_tokens
+    ADD COLUMN github_config_ids INTEGER[] NOT NULL DEFAULT '{}',
+    ADD COLUMN gitlab_config_ids INTEGER[] NOT NULL DEFAULT '{}';
+
+COMMENT ON COLUMN trustpub_tokens.github_config_ids IS 'IDs of the GitHub Trusted Publishing configs that matched when the token was issued';
+COMMENT ON COLUMN trustpub_tokens.gitlab_config_ids IS 'IDs of the GitLab Trusted Publishing configs that matched when the token was issued';
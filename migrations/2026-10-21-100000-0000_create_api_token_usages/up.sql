@@ -0,0 +1,25 @@
+CREATE TABLE api_token_usages (
+    id BIGSERIAL PRIMARY KEY,
+    api_token_id INTEGER NOT NULL REFERENCES api_tokens (id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    endpoint VARCHAR NOT NULL,
+    crate_name VARCHAR,
+    ip INET NOT NULL,
+    country VARCHAR,
+    asn BIGINT,
+    user_agent VARCHAR
+);
+
+COMMENT ON TABLE api_token_usages IS 'Log of requests that were authenticated with an API token. Old entries are deleted by the `delete_old_api_token_usages` background job.';
+COMMENT ON COLUMN api_token_usages.id IS 'Unique identifier of the `api_token_usages` row';
+COMMENT ON COLUMN api_token_usages.api_token_id IS 'The API token that was used';
+COMMENT ON COLUMN api_token_usages.created_at IS 'Date and time when the token was used';
+COMMENT ON COLUMN api_token_usages.endpoint IS 'HTTP method and route of the request, e.g. `PUT /api/v1/crates/new`';
+COMMENT ON COLUMN api_token_usages.crate_name IS 'Name of the crate that the request was for, if any';
+COMMENT ON COLUMN api_token_usages.ip IS 'IP address of the client';
+COMMENT ON COLUMN api_token_usages.country IS 'Two-letter country code of the client, as determined by the CDN';
+COMMENT ON COLUMN api_token_usages.asn IS 'Autonomous system number of the client network, as determined by the CDN';
+COMMENT ON COLUMN api_token_usages.user_agent IS 'User agent of the client';
+
+CREATE INDEX api_token_usages_api_token_id_created_at_idx ON api_token_usages (api_token_id, created_at);
+CREATE INDEX api_token_usages_created_at_idx ON api_token_usages (created_at);
@@ -0,0 +1,6 @@
+ALTER TABLE api_tokens
+    ADD COLUMN parent_id INTEGER REFERENCES api_tokens (id) ON DELETE CASCADE;
+
+COMMENT ON COLUMN api_tokens.parent_id IS 'ID of the refresh token that this short-lived token was exchanged for, or NULL for regular tokens';
+
+CREATE INDEX api_tokens_parent_id_idx ON api_tokens (parent_id) WHERE parent_id IS NOT NULL;
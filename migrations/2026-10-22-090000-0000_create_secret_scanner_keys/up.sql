@@ -0,0 +1,12 @@
+CREATE TABLE secret_scanner_keys (
+    key_identifier VARCHAR PRIMARY KEY,
+    scanner VARCHAR NOT NULL,
+    public_key VARCHAR NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE secret_scanner_keys IS 'Public keys of secret scanners that are allowed to report leaked tokens via the `/api/secret-scanning/revoke` endpoint.';
+COMMENT ON COLUMN secret_scanner_keys.key_identifier IS 'Identifier of the key, sent by the scanner in the `Secret-Scanning-Key-Identifier` header';
+COMMENT ON COLUMN secret_scanner_keys.scanner IS 'Name of the scanner, used as the reporter in the notification emails, e.g. `GitLab`';
+COMMENT ON COLUMN secret_scanner_keys.public_key IS 'PEM-encoded ECDSA P-256 public key that is used to verify the request signatures';
+COMMENT ON COLUMN secret_scanner_keys.created_at IS 'Date and time when the key was registered';
@@ -0,0 +1,2 @@
+DROP TABLE account_recovery_tokens;
+DROP TABLE account_lock_events;
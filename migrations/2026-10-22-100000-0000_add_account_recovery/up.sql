@@ -0,0 +1,38 @@
+CREATE TABLE account_lock_events (
+    id BIGSERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    action INTEGER NOT NULL,
+    actor_id INTEGER REFERENCES users (id) ON DELETE SET NULL,
+    reason TEXT NOT NULL,
+    locked_until TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE account_lock_events IS 'Audit log of all account locks and unlocks';
+COMMENT ON COLUMN account_lock_events.id IS 'Unique identifier of the event';
+COMMENT ON COLUMN account_lock_events.user_id IS 'The user whose account was locked or unlocked';
+COMMENT ON COLUMN account_lock_events.action IS 'The action that was performed (0=locked by an admin, 1=unlocked by an admin, 2=locked by the account owner, 3=unlocked via account recovery)';
+COMMENT ON COLUMN account_lock_events.actor_id IS 'The user that performed the action';
+COMMENT ON COLUMN account_lock_events.reason IS 'Explanation why the account was locked or unlocked';
+COMMENT ON COLUMN account_lock_events.locked_until IS 'Date and time when the lock expires, or NULL if the lock does not expire';
+COMMENT ON COLUMN account_lock_events.created_at IS 'Date and time when the action was performed';
+
+CREATE INDEX account_lock_events_user_id_idx ON account_lock_events (user_id);
+
+CREATE TABLE account_recovery_tokens (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    token BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+COMMENT ON TABLE account_recovery_tokens IS 'Secret tokens that are sent to the verified email address of a user to recover their account';
+COMMENT ON COLUMN account_recovery_tokens.id IS 'Unique identifier of the token';
+COMMENT ON COLUMN account_recovery_tokens.user_id IS 'The user whose account can be recovered with this token';
+COMMENT ON COLUMN account_recovery_tokens.token IS 'SHA-256 hash of the secret recovery token';
+COMMENT ON COLUMN account_recovery_tokens.created_at IS 'Date and time when the token was generated';
+COMMENT ON COLUMN account_recovery_tokens.expires_at IS 'Date and time when the token expires';
+
+CREATE UNIQUE INDEX account_recovery_tokens_token_idx ON account_recovery_tokens (token);
+CREATE INDEX account_recovery_tokens_user_id_idx ON account_recovery_tokens (user_id);
@@ -0,0 +1,46 @@
+DROP TRIGGER trigger_crates_record_dump_tombstone ON crates;
+DROP TRIGGER trigger_versions_record_dump_tombstone ON versions;
+DROP TRIGGER trigger_dependencies_record_dump_tombstone ON dependencies;
+DROP TRIGGER trigger_version_downloads_record_dump_tombstone ON version_downloads;
+DROP TRIGGER trigger_crate_owners_record_dump_tombstone ON crate_owners;
+DROP TRIGGER trigger_deleted_crates_record_dump_tombstone ON deleted_crates;
+
+CREATE OR REPLACE FUNCTION record_dump_tombstone() RETURNS trigger AS $$
+DECLARE
+    row_data JSONB := to_jsonb(OLD);
+    primary_key JSONB := '{}';
+BEGIN
+    FOR i IN 0 .. TG_NARGS - 1 LOOP
+        primary_key := primary_key || jsonb_build_object(TG_ARGV[i], row_data -> TG_ARGV[i]);
+    END LOOP;
+
+    INSERT INTO dump_tombstones (table_name, primary_key)
+    VALUES (TG_TABLE_NAME, primary_key);
+
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_crates_record_dump_tombstone
+    AFTER DELETE ON crates
+    FOR EACH ROW EXECUTE FUNCTION record_dump_tombstone('id');
+
+CREATE TRIGGER trigger_versions_record_dump_tombstone
+    AFTER DELETE ON versions
+    FOR EACH ROW EXECUTE FUNCTION record_dump_tombstone('id');
+
+CREATE TRIGGER trigger_dependencies_record_dump_tombstone
+    AFTER DELETE ON dependencies
+    FOR EACH ROW EXECUTE FUNCTION record_dump_tombstone('id');
+
+CREATE TRIGGER trigger_version_downloads_record_dump_tombstone
+    AFTER DELETE ON version_downloads
+    FOR EACH ROW EXECUTE FUNCTION record_dump_tombstone('version_id', 'date');
+
+CREATE TRIGGER trigger_crate_owners_record_dump_tombstone
+    AFTER DELETE ON crate_owners
+    FOR EACH ROW EXECUTE FUNCTION record_dump_tombstone('crate_id', 'owner_id', 'owner_kind');
+
+CREATE TRIGGER trigger_deleted_crates_record_dump_tombstone
+    AFTER DELETE ON deleted_crates
+    FOR EACH ROW EXECUTE FUNCTION record_dump_tombstone('id');
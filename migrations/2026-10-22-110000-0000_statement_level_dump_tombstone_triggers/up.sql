@@ -0,0 +1,54 @@
+-- Records tombstones for all rows deleted by a statement at once. The
+-- trigger arguments are the names of the primary key columns of the table.
+--
+-- Row-level triggers were too slow for bulk deletes, like the ones of the
+-- `archive_version_downloads` background job.
+CREATE OR REPLACE FUNCTION record_dump_tombstone() RETURNS trigger AS $$
+BEGIN
+    INSERT INTO dump_tombstones (table_name, primary_key)
+    SELECT TG_TABLE_NAME, (
+        SELECT jsonb_object_agg(key, value)
+        FROM jsonb_each(to_jsonb(old_rows))
+        WHERE key = ANY (TG_ARGV)
+    )
+    FROM old_rows;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER trigger_crates_record_dump_tombstone ON crates;
+CREATE TRIGGER trigger_crates_record_dump_tombstone
+    AFTER DELETE ON crates
+    REFERENCING OLD TABLE AS old_rows
+    FOR EACH STATEMENT EXECUTE FUNCTION record_dump_tombstone('id');
+
+DROP TRIGGER trigger_versions_record_dump_tombstone ON versions;
+CREATE TRIGGER trigger_versions_record_dump_tombstone
+    AFTER DELETE ON versions
+    REFERENCING OLD TABLE AS old_rows
+    FOR EACH STATEMENT EXECUTE FUNCTION record_dump_tombstone('id');
+
+DROP TRIGGER trigger_dependencies_record_dump_tombstone ON dependencies;
+CREATE TRIGGER trigger_dependencies_record_dump_tombstone
+    AFTER DELETE ON dependencies
+    REFERENCING OLD TABLE AS old_rows
+    FOR EACH STATEMENT EXECUTE FUNCTION record_dump_tombstone('id');
+
+DROP TRIGGER trigger_version_downloads_record_dump_tombstone ON version_downloads;
+CREATE TRIGGER trigger_version_downloads_record_dump_tombstone
+    AFTER DELETE ON version_downloads
+    REFERENCING OLD TABLE AS old_rows
+    FOR EACH STATEMENT EXECUTE FUNCTION record_dump_tombstone('version_id', 'date');
+
+DROP TRIGGER trigger_crate_owners_record_dump_tombstone ON crate_owners;
+CREATE TRIGGER trigger_crate_owners_record_dump_tombstone
+    AFTER DELETE ON crate_owners
+    REFERENCING OLD TABLE AS old_rows
+    FOR EACH STATEMENT EXECUTE FUNCTION record_dump_tombstone('crate_id', 'owner_id', 'owner_kind');
+
+DROP TRIGGER trigger_deleted_crates_record_dump_tombstone ON deleted_crates;
+CREATE TRIGGER trigger_deleted_crates_record_dump_tombstone
+    AFTER DELETE ON deleted_crates
+    REFERENCING OLD TABLE AS old_rows
+    FOR EACH STATEMENT EXECUTE FUNCTION record_dump_tombstone('id');
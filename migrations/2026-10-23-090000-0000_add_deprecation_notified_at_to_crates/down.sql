@@ -0,0 +1,2 @@
+ALTER TABLE crates
+    DROP COLUMN deprecation_notified_at;
@@ -0,0 +1,4 @@
+ALTER TABLE crates
+    ADD COLUMN deprecation_notified_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN crates.deprecation_notified_at IS 'When the owners of dependent crates were notified about the deprecation of the crate, or NULL if they have not been notified yet';
@@ -0,0 +1,23 @@
+CREATE TABLE api_token_usage_baselines (
+    api_token_id INTEGER PRIMARY KEY REFERENCES api_tokens (id) ON DELETE CASCADE,
+    countries TEXT[] NOT NULL DEFAULT '{}',
+    asns BIGINT[] NOT NULL DEFAULT '{}',
+    endpoints TEXT[] NOT NULL DEFAULT '{}'
+);
+
+COMMENT ON TABLE api_token_usage_baselines IS 'Countries, networks and endpoints that an API token was used from or for. Unlike `api_token_usages`, these are never deleted, so that unusual usages can be detected even after the usage log entries were deleted.';
+COMMENT ON COLUMN api_token_usage_baselines.api_token_id IS 'The API token that was used';
+COMMENT ON COLUMN api_token_usage_baselines.countries IS 'Two-letter country codes of the clients that used the token';
+COMMENT ON COLUMN api_token_usage_baselines.asns IS 'Autonomous system numbers of the client networks that used the token';
+COMMENT ON COLUMN api_token_usage_baselines.endpoints IS 'HTTP methods and routes of the requests that the token was used for.';
+
+-- Tokens without any usage log entries get no baseline, so that their next
+-- usage silently becomes the baseline, like the first usage of a new token.
+INSERT INTO api_token_usage_baselines (api_token_id, countries, asns, endpoints)
+SELECT
+    api_tokens.id,
+    COALESCE(ARRAY(SELECT DISTINCT country FROM api_token_usages WHERE api_token_id = api_tokens.id AND country IS NOT NULL), '{}'),
+    COALESCE(ARRAY(SELECT DISTINCT asn FROM api_token_usages WHERE api_token_id = api_tokens.id AND asn IS NOT NULL), '{}'),
+    COALESCE(ARRAY(SELECT DISTINCT endpoint FROM api_token_usages WHERE api_token_id = api_tokens.id), '{}')
+FROM api_tokens
+WHERE EXISTS (SELECT 1 FROM api_token_usages WHERE api_token_id = api_tokens.id);
@@ -0,0 +1,17 @@
+CREATE TABLE pending_github_links (
+    user_id INTEGER PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    gh_id INTEGER NOT NULL,
+    cancel_token BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    effective_at TIMESTAMPTZ NOT NULL
+);
+
+COMMENT ON TABLE pending_github_links IS 'GitHub accounts that will replace the GitHub account of a user after a waiting period, as part of the account recovery';
+COMMENT ON COLUMN pending_github_links.user_id IS 'The user whose GitHub account will be replaced';
+COMMENT ON COLUMN pending_github_links.gh_id IS 'The ID of the GitHub account that will be linked to the user';
+COMMENT ON COLUMN pending_github_links.cancel_token IS 'SHA-256 hash of the secret token that is sent to the verified email address of the user to cancel the link';
+COMMENT ON COLUMN pending_github_links.created_at IS 'Date and time when the link was requested';
+COMMENT ON COLUMN pending_github_links.effective_at IS 'Date and time after which the GitHub account can be used to log in to the account of the user';
+
+CREATE UNIQUE INDEX pending_github_links_gh_id_idx ON pending_github_links (gh_id);
+CREATE UNIQUE INDEX pending_github_links_cancel_token_idx ON pending_github_links (cancel_token);
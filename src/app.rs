@@ -1,5 +1,6 @@
 //! Application-wide components in a struct accessible from each request
 
+use crate::blocked_traffic::BlockedTrafficRules;
 use crate::config;
 use crate::db::{ConnectionConfig, connection_url, make_manager_config};
 use std::collections::HashMap;
@@ -8,7 +9,9 @@ use std::sync::Arc;
 use crate::email::Emails;
 use crate::metrics::{InstanceMetrics, ServiceMetrics};
 use crate::rate_limiter::{LimitedAction, RateLimiter, RateLimiterConfig};
+use crate::read_rate_limiter::{ReadRateLimiter, ReadRouteGroup};
 use crate::storage::{Storage, StorageConfig};
+use crate::typosquat;
 use axum::extract::{FromRef, FromRequestParts, State};
 use bon::Builder;
 use crates_io_github::GitHubClient;
@@ -22,6 +25,7 @@ use diesel_async::pooled_connection::AsyncDieselConnectionManager;
 use diesel_async::pooled_connection::deadpool::Pool as DeadpoolPool;
 use oauth2::basic::BasicClient;
 use oauth2::{EndpointNotSet, EndpointSet};
+use tokio::sync::OnceCell;
 use tracing::{instrument, warn};
 
 type DeadpoolResult = Result<
@@ -72,6 +76,19 @@ pub struct App {
 
     /// Rate limit select actions.
     pub rate_limiter: RateLimiter,
+
+    /// Per-client rate limits for read-only endpoints.
+    pub read_rate_limiter: ReadRateLimiter,
+
+    /// Rules for blocking incoming requests, periodically reloaded from
+    /// the database.
+    #[builder(default)]
+    pub blocked_traffic_rules: BlockedTrafficRules,
+
+    /// A lazily initialised cache of the most popular crates, used to
+    /// suggest crate names for misspelled search queries.
+    #[builder(skip)]
+    typosquat_cache: OnceCell<Result<typosquat::Cache, typosquat::CacheError>>,
 }
 
 impl<S: app_builder::State> AppBuilder<S> {
@@ -165,6 +182,16 @@ impl<S: app_builder::State> AppBuilder<S> {
     {
         self.rate_limiter(RateLimiter::new(config))
     }
+
+    pub fn read_rate_limiter_from_config(
+        self,
+        config: HashMap<ReadRouteGroup, RateLimiterConfig>,
+    ) -> AppBuilder<app_builder::SetReadRateLimiter<S>>
+    where
+        S::ReadRateLimiter: app_builder::IsUnset,
+    {
+        self.read_rate_limiter(ReadRateLimiter::new(config))
+    }
 }
 
 pub fn create_database_pool(config: &config::DbPoolConfig) -> DeadpoolPool<AsyncPgConnection> {
@@ -252,6 +279,22 @@ impl App {
             }
         }
     }
+
+    /// Returns the typosquatting cache used for search suggestions,
+    /// initialising it if required.
+    ///
+    /// Like in the background worker, a failure to initialise the cache is
+    /// not retried.
+    pub(crate) async fn typosquat_cache(
+        &self,
+        conn: &mut AsyncPgConnection,
+    ) -> Result<&typosquat::Cache, typosquat::CacheError> {
+        self.typosquat_cache
+            .get_or_init(|| typosquat::Cache::new(Vec::new(), conn))
+            .await
+            .as_ref()
+            .map_err(|e| e.clone())
+    }
 }
 
 #[derive(Clone, FromRequestParts, Deref)]
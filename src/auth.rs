@@ -1,21 +1,28 @@
 use crate::controllers;
 use crate::controllers::util::RequestPartsExt;
+use crate::middleware::app::RequestApp;
 use crate::middleware::log_request::RequestLogExt;
-use crate::models::token::{CrateScope, EndpointScope};
-use crate::models::{ApiToken, User};
+use crate::middleware::real_ip::RealIp;
+use crate::models::token::{CrateScope, EndpointScope, NewApiTokenUsage};
+use crate::models::{ApiToken, CratePublishPolicy, User};
 use crate::util::errors::{
     AppResult, BoxedAppError, InsecurelyGeneratedTokenRevoked, account_locked, custom, forbidden,
     internal,
 };
 use crate::util::token::HashedToken;
-use axum::extract::FromRequestParts;
-use chrono::Utc;
+use crate::worker::jobs;
+use axum::extract::{FromRequestParts, MatchedPath};
+use chrono::{DateTime, TimeDelta, Utc};
+use crates_io_real_ip::process_location_headers;
 use crates_io_session::SessionExtension;
-use diesel_async::AsyncPgConnection;
+use crates_io_worker::BackgroundJob;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection};
 use http::request::Parts;
 use http::{StatusCode, header};
+use ipnetwork::IpNetwork;
 use secrecy::{ExposeSecret, SecretString};
-use tracing::instrument;
+use tracing::{instrument, warn};
 
 pub struct AuthHeader(SecretString);
 
@@ -144,6 +151,16 @@ impl AuthCheck {
                 ));
             }
 
+            if is_refresh_token(token) && self.endpoint_scope != Some(EndpointScope::TokenExchange)
+            {
+                let error_message = "Refresh token used for a different endpoint";
+                parts.request_log().add("cause", error_message);
+
+                return Err(forbidden(
+                    "this token can only be used to create short-lived API tokens",
+                ));
+            }
+
             if !self.endpoint_scope_matches(token.endpoint_scopes.as_ref()) {
                 let error_message = "Endpoint scope mismatch";
                 parts.request_log().add("cause", error_message);
@@ -161,6 +178,24 @@ impl AuthCheck {
                     "this token does not have the required permissions to perform this action",
                 ));
             }
+
+            if let Some(crate_name) = &self.crate_name
+                && let Some(policy) =
+                    CratePublishPolicy::find_by_crate_name(conn, crate_name).await?
+            {
+                // Short-lived tokens are only as fresh as the refresh token
+                // that they were exchanged for.
+                let created_at = token.root_created_at(conn).await?;
+                if let Some(error_message) =
+                    token_policy_violation(&policy, token, created_at, crate_name)
+                {
+                    parts.request_log().add("cause", "Publish policy violation");
+
+                    return Err(forbidden(error_message));
+                }
+            }
+
+            record_token_usage(parts, conn, token, self.crate_name.as_deref()).await;
         }
 
         Ok(auth)
@@ -345,6 +380,117 @@ async fn authenticate(parts: &Parts, conn: &mut AsyncPgConnection) -> AppResult<
     return Err(forbidden("this action requires authentication"));
 }
 
+/// Returns `true` if the token can be exchanged for short-lived tokens, which
+/// means that it can't be used for anything else.
+fn is_refresh_token(token: &ApiToken) -> bool {
+    token
+        .endpoint_scopes
+        .as_ref()
+        .is_some_and(|scopes| scopes.contains(&EndpointScope::TokenExchange))
+}
+
+/// Records the usage of an API token, and enqueues an alert email if the
+/// token was used from a new country or network, or for a new endpoint.
+///
+/// Failures are only logged, since they should not fail the request itself,
+/// e.g. when the request is served from a read-only database replica.
+#[instrument(skip_all)]
+async fn record_token_usage(
+    parts: &Parts,
+    conn: &mut AsyncPgConnection,
+    token: &ApiToken,
+    crate_name: Option<&str>,
+) {
+    let Some(real_ip) = parts.extensions.get::<RealIp>() else {
+        return;
+    };
+
+    let endpoint = match parts.extensions.get::<MatchedPath>() {
+        Some(path) => format!("{} {}", parts.method, path.as_str()),
+        None => format!("{} {}", parts.method, parts.uri.path()),
+    };
+
+    let location = process_location_headers(&parts.headers);
+
+    let user_agent = parts
+        .headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok());
+
+    // The usages of short-lived tokens are recorded for the refresh token that
+    // they were exchanged for, so that they share the same usage history.
+    let usage = NewApiTokenUsage {
+        api_token_id: token.root_id(),
+        endpoint: &endpoint,
+        crate_name,
+        ip: IpNetwork::from(**real_ip),
+        country: location.country.as_deref(),
+        asn: location.asn.map(i64::from),
+        user_agent,
+    };
+
+    let alerts_enabled = parts.app().config.api_token_usage_alerts;
+
+    let result = conn
+        .transaction(|conn| {
+            async move {
+                let anomalies = match alerts_enabled {
+                    true => usage.anomalies(conn).await?,
+                    false => Vec::new(),
+                };
+
+                let usage_id = usage.insert(conn).await?;
+                usage.update_baseline(conn).await?;
+
+                if !anomalies.is_empty() {
+                    let job = jobs::SendApiTokenUsageAlert::new(usage_id, anomalies);
+                    job.enqueue(conn).await?;
+                }
+
+                Ok::<_, anyhow::Error>(())
+            }
+            .scope_boxed()
+        })
+        .await;
+
+    if let Err(error) = result {
+        warn!("Failed to record API token usage: {error}");
+    }
+}
+
+/// Checks the token against the token restrictions of a crate's publish
+/// policy, and returns an error message if the token does not satisfy them.
+fn token_policy_violation(
+    policy: &CratePublishPolicy,
+    token: &ApiToken,
+    created_at: DateTime<Utc>,
+    crate_name: &str,
+) -> Option<String> {
+    if policy.require_crate_scoped_tokens {
+        let has_crate_scope = token.crate_scopes.as_ref().is_some_and(|scopes| {
+            scopes
+                .iter()
+                .any(|scope| !scope.is_wildcard() && scope.matches(crate_name))
+        });
+
+        if !has_crate_scope {
+            return Some(format!(
+                "the publish policy of this crate only allows API tokens with a crate scope for `{crate_name}`"
+            ));
+        }
+    }
+
+    if let Some(max_token_age_days) = policy.max_token_age_days
+        && created_at < Utc::now() - TimeDelta::days(max_token_age_days.into())
+    {
+        return Some(format!(
+            "the publish policy of this crate only allows API tokens that were created within the last {max_token_age_days} days"
+        ));
+    }
+
+    None
+}
+
 fn ensure_not_locked(user: &User) -> AppResult<()> {
     if let Some(reason) = &user.account_lock_reason {
         let still_locked = user
@@ -0,0 +1,153 @@
+use anyhow::{Context, anyhow};
+use chrono::{Duration, Utc};
+use crates_io::db;
+use crates_io::models::{BlockedTrafficRule, NewBlockedTrafficRule};
+use crates_io::schema::blocked_traffic_rules;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use ipnetwork::IpNetwork;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "blocked-traffic",
+    about = "Manage the rules for blocking incoming requests.",
+    after_help = "Changes are picked up by the web servers within a minute, without a redeploy."
+)]
+pub enum Command {
+    /// List the blocked traffic rules
+    List {
+        /// Include expired rules
+        #[arg(long)]
+        all: bool,
+    },
+    /// Add a new blocked traffic rule. Requests are blocked if they match
+    /// all of the given conditions.
+    Add(AddOpts),
+    /// Remove a blocked traffic rule
+    Remove {
+        /// The ID of the rule
+        id: i32,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+#[group(id = "condition", required = true, multiple = true, args = ["ip", "header", "route"])]
+pub struct AddOpts {
+    /// IP address or CIDR range of the clients to block, e.g. `192.0.2.0/24`
+    #[arg(long)]
+    ip: Option<IpNetwork>,
+
+    /// Header condition in the form `Name=pattern`, e.g. `User-Agent=curl/*`.
+    /// `*` matches any sequence of characters.
+    #[arg(long, value_parser = parse_header)]
+    header: Option<(String, String)>,
+
+    /// Route pattern, e.g. `/api/v1/crates/{name}/{version}/download`.
+    /// `*` matches any sequence of characters.
+    #[arg(long)]
+    route: Option<String>,
+
+    /// Custom message returned to blocked clients
+    #[arg(long)]
+    message: Option<String>,
+
+    /// Internal note explaining why the rule is needed
+    #[arg(long)]
+    reason: String,
+
+    /// The amount of time (in hours) after which the rule expires
+    #[arg(long)]
+    expires_in: Option<i64>,
+}
+
+fn parse_header(value: &str) -> anyhow::Result<(String, String)> {
+    let (name, pattern) = value
+        .split_once('=')
+        .ok_or_else(|| anyhow!("expected `Name=pattern`"))?;
+
+    http::HeaderName::try_from(name).context("invalid header name")?;
+
+    Ok((name.to_string(), pattern.to_string()))
+}
+
+pub async fn run(command: Command) -> anyhow::Result<()> {
+    let mut conn = db::oneoff_connection()
+        .await
+        .context("Failed to establish database connection")?;
+
+    match command {
+        Command::List { all } => {
+            let rules = if all {
+                BlockedTrafficRule::load_all(&mut conn).await?
+            } else {
+                BlockedTrafficRule::load_active(&mut conn).await?
+            };
+
+            if rules.is_empty() {
+                println!("No blocked traffic rules found");
+            }
+
+            for rule in rules {
+                print_rule(&rule);
+            }
+        }
+        Command::Add(opts) => {
+            let (header_name, header_pattern) = opts.header.unzip();
+            let expires_at = opts
+                .expires_in
+                .map(|hours| Utc::now() + Duration::hours(hours));
+
+            let rule = NewBlockedTrafficRule::builder()
+                .maybe_expires_at(expires_at)
+                .maybe_ip_range(opts.ip)
+                .maybe_header_name(header_name.as_deref())
+                .maybe_header_pattern(header_pattern.as_deref())
+                .maybe_route_pattern(opts.route.as_deref())
+                .maybe_message(opts.message.as_deref())
+                .reason(&opts.reason)
+                .build()
+                .insert(&mut conn)
+                .await
+                .context("Failed to add blocked traffic rule")?;
+
+            println!("Added blocked traffic rule:");
+            print_rule(&rule);
+        }
+        Command::Remove { id } => {
+            let deleted = diesel::delete(blocked_traffic_rules::table.find(id))
+                .execute(&mut conn)
+                .await
+                .context("Failed to remove blocked traffic rule")?;
+
+            if deleted == 0 {
+                anyhow::bail!("Blocked traffic rule {id} not found");
+            }
+
+            println!("Removed blocked traffic rule {id}");
+        }
+    }
+
+    Ok(())
+}
+
+fn print_rule(rule: &BlockedTrafficRule) {
+    println!("#{} (created at {})", rule.id, rule.created_at);
+    if let Some(ip_range) = &rule.ip_range {
+        println!("  ip:      {ip_range}");
+    }
+    if let (Some(name), Some(pattern)) = (&rule.header_name, &rule.header_pattern) {
+        println!("  header:  {name}={pattern}");
+    }
+    if let Some(route_pattern) = &rule.route_pattern {
+        println!("  route:   {route_pattern}");
+    }
+    if let Some(message) = &rule.message {
+        println!("  message: {message}");
+    }
+    match rule.expires_at {
+        Some(expires_at) if rule.is_expired() => println!("  expired: {expires_at}"),
+        Some(expires_at) => println!("  expires: {expires_at}"),
+        None => {}
+    }
+    println!("  reason:  {}", rule.reason);
+}
@@ -26,7 +26,12 @@ pub enum Command {
     },
     CleanProcessedLogFiles,
     DailyDbMaintenance,
+    DeleteOldApiTokenUsages,
+    DocsRsRetryRebuildRequests,
+    DocsRsSyncBuildStatuses,
     DumpDb,
+    DumpDbIncremental,
+    ExpirePendingVersions,
     /// Generate OpenGraph images for the specified crates
     GenerateOgImage {
         /// Crate names to generate OpenGraph images for
@@ -39,6 +44,7 @@ pub enum Command {
         dry_run: bool,
     },
     ProcessCdnLogQueue(jobs::ProcessCdnLogQueue),
+    ProcessScheduledCrateDeletions,
     SendTokenExpiryNotifications,
     SquashIndex,
     SyncAdmins {
@@ -86,9 +92,24 @@ pub async fn run(command: Command) -> Result<()> {
         Command::DailyDbMaintenance => {
             jobs::DailyDbMaintenance.enqueue(&mut conn).await?;
         }
+        Command::DeleteOldApiTokenUsages => {
+            jobs::DeleteOldApiTokenUsages.enqueue(&mut conn).await?;
+        }
+        Command::DocsRsRetryRebuildRequests => {
+            jobs::DocsRsRetryRebuildRequests.enqueue(&mut conn).await?;
+        }
+        Command::DocsRsSyncBuildStatuses => {
+            jobs::DocsRsSyncBuildStatuses.enqueue(&mut conn).await?;
+        }
         Command::DumpDb => {
             jobs::DumpDb.enqueue(&mut conn).await?;
         }
+        Command::DumpDbIncremental => {
+            jobs::DumpDbIncremental.enqueue(&mut conn).await?;
+        }
+        Command::ExpirePendingVersions => {
+            jobs::ExpirePendingVersions.enqueue(&mut conn).await?;
+        }
         Command::GenerateOgImage { names } => {
             for name in names {
                 jobs::GenerateOgImage::new(name).enqueue(&mut conn).await?;
@@ -107,6 +128,11 @@ pub async fn run(command: Command) -> Result<()> {
         Command::ProcessCdnLogQueue(job) => {
             job.enqueue(&mut conn).await?;
         }
+        Command::ProcessScheduledCrateDeletions => {
+            jobs::ProcessScheduledCrateDeletions
+                .enqueue(&mut conn)
+                .await?;
+        }
         Command::SendTokenExpiryNotifications => {
             jobs::SendTokenExpiryNotifications
                 .enqueue(&mut conn)
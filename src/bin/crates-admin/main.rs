@@ -2,6 +2,7 @@
 extern crate tracing;
 
 mod analyze_crates;
+mod blocked_traffic;
 mod default_versions;
 mod delete_crate;
 mod delete_version;
@@ -11,8 +12,10 @@ mod migrate;
 mod populate;
 mod render_og_images;
 mod render_readmes;
+mod secret_scanner_keys;
 mod sync_index;
 mod test_email;
+mod transfer_requests;
 mod upload_index;
 mod verify_token;
 mod yank_version;
@@ -36,6 +39,12 @@ enum Command {
     EnqueueJob(enqueue_job::Command),
     #[clap(subcommand)]
     DefaultVersions(default_versions::Command),
+    #[clap(subcommand)]
+    BlockedTraffic(blocked_traffic::Command),
+    #[clap(subcommand)]
+    TransferRequests(transfer_requests::Command),
+    #[clap(subcommand)]
+    SecretScannerKeys(secret_scanner_keys::Command),
 }
 
 #[tokio::main]
@@ -66,6 +75,9 @@ async fn main() -> anyhow::Result<()> {
         Command::YankVersion(opts) => yank_version::run(opts).await,
         Command::EnqueueJob(command) => enqueue_job::run(command).await,
         Command::DefaultVersions(opts) => default_versions::run(opts).await,
+        Command::BlockedTraffic(command) => blocked_traffic::run(command).await,
+        Command::TransferRequests(command) => transfer_requests::run(command).await,
+        Command::SecretScannerKeys(command) => secret_scanner_keys::run(command).await,
     }
 }
 
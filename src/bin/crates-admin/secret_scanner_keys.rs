@@ -0,0 +1,111 @@
+use anyhow::Context;
+use crates_io::db;
+use crates_io::models::{NewSecretScannerKey, SecretScannerKey};
+use crates_io::schema::secret_scanner_keys;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use p256::PublicKey;
+use p256::pkcs8::EncodePublicKey;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "secret-scanner-keys",
+    about = "Manage the public keys of secret scanners that can report leaked tokens.",
+    after_help = "Scanners use the key to sign requests to `POST /api/secret-scanning/revoke`."
+)]
+pub enum Command {
+    /// List the registered secret scanner keys
+    List,
+    /// Register a new public key for a secret scanner
+    Add {
+        /// Name of the scanner, shown in the notification emails, e.g. `GitLab`
+        #[arg(long)]
+        scanner: String,
+
+        /// Path to the PEM-encoded ECDSA P-256 public key
+        #[arg(long)]
+        public_key: PathBuf,
+
+        /// Identifier of the key (default: hex-encoded SHA256 hash of the
+        /// DER-encoded public key)
+        #[arg(long)]
+        key_identifier: Option<String>,
+    },
+    /// Remove a secret scanner key
+    Remove {
+        /// The identifier of the key
+        key_identifier: String,
+    },
+}
+
+pub async fn run(command: Command) -> anyhow::Result<()> {
+    let mut conn = db::oneoff_connection()
+        .await
+        .context("Failed to establish database connection")?;
+
+    match command {
+        Command::List => {
+            let keys = SecretScannerKey::load_all(&mut conn).await?;
+
+            if keys.is_empty() {
+                println!("No secret scanner keys found");
+            }
+
+            for key in keys {
+                print_key(&key);
+            }
+        }
+        Command::Add {
+            scanner,
+            public_key,
+            key_identifier,
+        } => {
+            let pem = std::fs::read_to_string(&public_key)
+                .with_context(|| format!("Failed to read {}", public_key.display()))?;
+
+            let parsed = PublicKey::from_str(&pem).context("Failed to parse public key")?;
+
+            let key_identifier = match key_identifier {
+                Some(key_identifier) => key_identifier,
+                None => {
+                    let der = parsed.to_public_key_der()?;
+                    hex::encode(Sha256::digest(der.as_bytes()))
+                }
+            };
+
+            let key = NewSecretScannerKey::builder()
+                .key_identifier(&key_identifier)
+                .scanner(&scanner)
+                .public_key(pem.trim())
+                .build()
+                .insert(&mut conn)
+                .await
+                .context("Failed to add secret scanner key")?;
+
+            println!("Added secret scanner key:");
+            print_key(&key);
+        }
+        Command::Remove { key_identifier } => {
+            let deleted = diesel::delete(secret_scanner_keys::table.find(&key_identifier))
+                .execute(&mut conn)
+                .await
+                .context("Failed to remove secret scanner key")?;
+
+            if deleted == 0 {
+                anyhow::bail!("Secret scanner key {key_identifier} not found");
+            }
+
+            println!("Removed secret scanner key {key_identifier}");
+        }
+    }
+
+    Ok(())
+}
+
+fn print_key(key: &SecretScannerKey) {
+    println!("{} (created at {})", key.key_identifier, key.created_at);
+    println!("  scanner: {}", key.scanner);
+}
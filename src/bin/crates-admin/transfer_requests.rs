@@ -0,0 +1,196 @@
+use crate::dialoguer;
+use anyhow::{Context, bail};
+use chrono::{Duration, Utc};
+use crates_io::db;
+use crates_io::models::{CrateTransferAction, CrateTransferRequest, CrateTransferStatus, User};
+use crates_io::schema::{crates, users};
+use crates_io::worker::jobs;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "transfer-requests",
+    about = "Manage the requests of users to take over the ownership of crates.",
+    after_help = "Owners can accept or reject requests themselves. Requests that the owners \
+        did not respond to can be approved or denied by the crates.io team."
+)]
+pub enum Command {
+    /// List the pending transfer requests
+    List,
+    /// Approve a pending transfer request. This removes all existing owners
+    /// of the crate and makes the requester the only owner.
+    Approve {
+        /// The ID of the transfer request
+        id: i32,
+
+        /// Your GitHub username.
+        #[arg(long)]
+        approved_by: String,
+
+        /// An optional note explaining the decision, which is sent to the
+        /// requester.
+        #[arg(long)]
+        note: Option<String>,
+
+        /// The amount of time (in days) that the owners have to respond to
+        /// a request before it can be approved.
+        #[arg(long, env = "CRATE_TRANSFER_SILENCE_PERIOD", default_value = "30")]
+        silence_period: i64,
+
+        /// Don't ask for confirmation: yes, we are sure. Best for scripting.
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Deny a pending transfer request
+    Deny {
+        /// The ID of the transfer request
+        id: i32,
+
+        /// Your GitHub username.
+        #[arg(long)]
+        denied_by: String,
+
+        /// An optional note explaining the decision, which is sent to the
+        /// requester.
+        #[arg(long)]
+        note: Option<String>,
+    },
+}
+
+pub async fn run(command: Command) -> anyhow::Result<()> {
+    let mut conn = db::oneoff_connection()
+        .await
+        .context("Failed to establish database connection")?;
+
+    match command {
+        Command::List => {
+            let requests = CrateTransferRequest::pending(&mut conn).await?;
+            if requests.is_empty() {
+                println!("No pending transfer requests found");
+            }
+
+            for request in requests {
+                print_request(&request, &mut conn).await?;
+            }
+        }
+        Command::Approve {
+            id,
+            approved_by,
+            note,
+            silence_period,
+            yes,
+        } => {
+            let request = load_pending_request(id, &mut conn).await?;
+
+            let elapsed = Utc::now() - request.created_at;
+            if elapsed < Duration::days(silence_period) {
+                bail!(
+                    "Transfer request {id} was filed {} days ago, but can only be approved after {silence_period} days",
+                    elapsed.num_days()
+                );
+            }
+
+            print_request(&request, &mut conn).await?;
+
+            let prompt = "Do you want to transfer the crate and remove all existing owners?";
+            if !yes && !dialoguer::confirm(prompt).await? {
+                return Ok(());
+            }
+
+            let action = CrateTransferAction::Approved;
+            resolve(&request, action, &approved_by, note.as_deref(), &mut conn).await?;
+            println!("Approved transfer request {id}");
+        }
+        Command::Deny {
+            id,
+            denied_by,
+            note,
+        } => {
+            let request = load_pending_request(id, &mut conn).await?;
+
+            let action = CrateTransferAction::Denied;
+            resolve(&request, action, &denied_by, note.as_deref(), &mut conn).await?;
+            println!("Denied transfer request {id}");
+        }
+    }
+
+    Ok(())
+}
+
+async fn load_pending_request(
+    id: i32,
+    conn: &mut AsyncPgConnection,
+) -> anyhow::Result<CrateTransferRequest> {
+    let request = CrateTransferRequest::find(conn, id)
+        .await
+        .optional()
+        .context("Failed to load transfer request")?;
+
+    let Some(request) = request else {
+        bail!("Transfer request {id} not found");
+    };
+
+    if request.status != CrateTransferStatus::Pending {
+        bail!("Transfer request {id} is not pending anymore");
+    }
+
+    Ok(request)
+}
+
+async fn resolve(
+    request: &CrateTransferRequest,
+    action: CrateTransferAction,
+    actor: &str,
+    note: Option<&str>,
+    conn: &mut AsyncPgConnection,
+) -> anyhow::Result<()> {
+    let actor = User::find_by_login(conn, actor)
+        .await
+        .context("Failed to look up acting user from the database")?;
+
+    let event = request
+        .resolve(conn, action, actor.id, note)
+        .await
+        .context("Failed to resolve transfer request")?;
+
+    let Some(event) = event else {
+        bail!("Transfer request {} is not pending anymore", request.id);
+    };
+
+    jobs::SendCrateTransferNotifications::new(&event)
+        .enqueue(conn)
+        .await
+        .context("Failed to enqueue notification job")?;
+
+    Ok(())
+}
+
+async fn print_request(
+    request: &CrateTransferRequest,
+    conn: &mut AsyncPgConnection,
+) -> anyhow::Result<()> {
+    let krate: String = crates::table
+        .find(request.crate_id)
+        .select(crates::name)
+        .first(conn)
+        .await?;
+
+    let requester: String = users::table
+        .find(request.requester_id)
+        .select(users::gh_login)
+        .first(conn)
+        .await?;
+
+    let age = (Utc::now() - request.created_at).num_days();
+    println!(
+        "#{} (created at {}, {age} days ago)",
+        request.id, request.created_at
+    );
+    println!("  crate:     {krate}");
+    println!("  requester: {requester}");
+    println!("  reason:    {}", request.reason);
+
+    Ok(())
+}
@@ -18,6 +18,9 @@ use tower::Layer;
 
 const CORE_THREADS: usize = 4;
 
+/// How often the `blocked_traffic_rules` are reloaded from the database.
+const BLOCKED_TRAFFIC_RULES_RELOAD_INTERVAL: Duration = Duration::from_secs(30);
+
 fn main() -> anyhow::Result<()> {
     let _sentry = crates_io::sentry::init();
 
@@ -44,6 +47,7 @@ fn main() -> anyhow::Result<()> {
         .emails(emails)
         .storage_from_config(&config.storage)
         .rate_limiter_from_config(config.rate_limiter.clone())
+        .read_rate_limiter_from_config(config.read_rate_limiter.clone())
         .config(Arc::new(config))
         .build();
 
@@ -73,6 +77,9 @@ fn main() -> anyhow::Result<()> {
 
     // Block the main thread until the server has shutdown
     rt.block_on(async {
+        // Start the background task periodically reloading the blocked traffic rules.
+        tokio::spawn(reload_blocked_traffic_rules(app.clone()));
+
         // Create a `TcpListener` using tokio.
         let listener = TcpListener::bind((app.config.ip, app.config.port)).await?;
 
@@ -113,6 +120,24 @@ async fn shutdown_signal() {
     }
 }
 
+async fn reload_blocked_traffic_rules(app: Arc<App>) {
+    let mut interval = tokio::time::interval(BLOCKED_TRAFFIC_RULES_RELOAD_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        let result = async {
+            let mut conn = app.db_read_prefer_primary().await?;
+            let num_rules = app.blocked_traffic_rules.reload(&mut conn).await?;
+            Ok::<_, anyhow::Error>(num_rules)
+        };
+
+        match result.await {
+            Ok(num_rules) => debug!("Reloaded {num_rules} blocked traffic rules"),
+            Err(err) => error!(?err, "Failed to reload blocked traffic rules"),
+        }
+    }
+}
+
 fn log_instance_metrics_thread(app: Arc<App>) {
     // Only run the thread if the configuration is provided
     let interval = match app.config.instance_metrics_log_every_seconds {
@@ -0,0 +1,195 @@
+//! Runtime-managed rules for blocking incoming requests.
+//!
+//! In addition to the static `BLOCKED_TRAFFIC`, `BLOCKED_IPS` and
+//! `BLOCKED_ROUTES` settings, operators can add rules to the
+//! `blocked_traffic_rules` table via `crates-admin blocked-traffic`. The
+//! rules are cached in memory and periodically reloaded by the server, so
+//! that they take effect without a redeploy.
+
+use crate::models::BlockedTrafficRule;
+use diesel_async::AsyncPgConnection;
+use http::HeaderMap;
+use parking_lot::RwLock;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+/// The in-memory copy of the active [`BlockedTrafficRule`]s.
+#[derive(Debug, Default)]
+pub struct BlockedTrafficRules {
+    rules: RwLock<Arc<Vec<BlockedTrafficRule>>>,
+}
+
+impl BlockedTrafficRules {
+    /// Replaces the cached rules with the currently active rules from the
+    /// database.
+    pub async fn reload(&self, conn: &mut AsyncPgConnection) -> diesel::QueryResult<usize> {
+        let rules = BlockedTrafficRule::load_active(conn).await?;
+        let num_rules = rules.len();
+        *self.rules.write() = Arc::new(rules);
+        Ok(num_rules)
+    }
+
+    /// Returns the first non-expired rule that matches the request, if any.
+    pub fn find_match(
+        &self,
+        ip: IpAddr,
+        headers: &HeaderMap,
+        route: &str,
+    ) -> Option<BlockedTrafficRule> {
+        let rules = self.rules.read().clone();
+        rules
+            .iter()
+            .find(|rule| !rule.is_expired() && matches(rule, ip, headers, route))
+            .cloned()
+    }
+}
+
+fn matches(rule: &BlockedTrafficRule, ip: IpAddr, headers: &HeaderMap, route: &str) -> bool {
+    if let Some(ip_range) = &rule.ip_range
+        && !ip_range.contains(ip)
+    {
+        return false;
+    }
+
+    if let (Some(header_name), Some(header_pattern)) = (&rule.header_name, &rule.header_pattern) {
+        let has_matching_value = headers
+            .get_all(header_name.as_str())
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .any(|value| wildcard_match(header_pattern, value));
+
+        if !has_matching_value {
+            return false;
+        }
+    }
+
+    if let Some(route_pattern) = &rule.route_pattern
+        && !wildcard_match(route_pattern, route)
+    {
+        return false;
+    }
+
+    true
+}
+
+/// Matches `value` against `pattern`, where `*` in the pattern matches any
+/// (possibly empty) sequence of characters. All other characters have to
+/// match exactly.
+fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let Some((first, rest)) = pattern.split_once('*') else {
+        return pattern == value;
+    };
+
+    let Some(mut remaining) = value.strip_prefix(first) else {
+        return false;
+    };
+
+    let mut parts = rest.split('*').peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            // The last part has to match the end of the value
+            return remaining.len() >= part.len() && remaining.ends_with(part);
+        }
+
+        match remaining.find(part) {
+            Some(index) => remaining = &remaining[index + part.len()..],
+            None => return false,
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use http::HeaderValue;
+
+    fn rule() -> BlockedTrafficRule {
+        BlockedTrafficRule {
+            id: 1,
+            created_at: Utc::now(),
+            expires_at: None,
+            ip_range: None,
+            header_name: None,
+            header_pattern: None,
+            route_pattern: None,
+            message: None,
+            reason: "testing".to_string(),
+        }
+    }
+
+    fn rules(rules: Vec<BlockedTrafficRule>) -> BlockedTrafficRules {
+        BlockedTrafficRules {
+            rules: RwLock::new(Arc::new(rules)),
+        }
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("curl/7.54.0", "curl/7.54.0"));
+        assert!(!wildcard_match("curl/7.54.0", "curl/7.54.1"));
+        assert!(wildcard_match("curl/*", "curl/7.54.0"));
+        assert!(!wildcard_match("curl/*", "wget/1.0"));
+        assert!(wildcard_match("*bot*", "my-bot/1.0"));
+        assert!(wildcard_match("*bot*", "bot"));
+        assert!(!wildcard_match("*bot*", "b-o-t"));
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match(
+            "/api/v1/crates/*/download",
+            "/api/v1/crates/{name}/{version}/download"
+        ));
+        assert!(!wildcard_match(
+            "/api/*/download",
+            "/api/v1/crates/{name}/{version}/readme"
+        ));
+        assert!(wildcard_match("a*a", "aa"));
+        assert!(!wildcard_match("a*a", "a"));
+    }
+
+    #[test]
+    fn test_ip_range() {
+        let rules = rules(vec![BlockedTrafficRule {
+            ip_range: Some("10.0.0.0/8".parse().unwrap()),
+            ..rule()
+        }]);
+
+        let headers = HeaderMap::new();
+        let blocked = "10.1.2.3".parse().unwrap();
+        assert!(rules.find_match(blocked, &headers, "/").is_some());
+        let allowed = "192.168.0.1".parse().unwrap();
+        assert!(rules.find_match(allowed, &headers, "/").is_none());
+    }
+
+    #[test]
+    fn test_all_conditions_must_match() {
+        let rules = rules(vec![BlockedTrafficRule {
+            header_name: Some("User-Agent".to_string()),
+            header_pattern: Some("scraper/*".to_string()),
+            route_pattern: Some("/api/v1/crates".to_string()),
+            ..rule()
+        }]);
+
+        let ip = "127.0.0.1".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("user-agent", HeaderValue::from_static("scraper/1.0"));
+        assert!(rules.find_match(ip, &headers, "/api/v1/crates").is_some());
+        assert!(rules.find_match(ip, &headers, "/api/v1/summary").is_none());
+
+        let headers = HeaderMap::new();
+        assert!(rules.find_match(ip, &headers, "/api/v1/crates").is_none());
+    }
+
+    #[test]
+    fn test_expired_rules_are_ignored() {
+        let rules = rules(vec![BlockedTrafficRule {
+            expires_at: Some(Utc::now() - Duration::minutes(1)),
+            route_pattern: Some("*".to_string()),
+            ..rule()
+        }]);
+
+        let ip = "127.0.0.1".parse().unwrap();
+        assert!(rules.find_match(ip, &HeaderMap::new(), "/").is_none());
+    }
+}
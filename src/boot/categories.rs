@@ -2,7 +2,9 @@
 // Runs when the server is started.
 
 use anyhow::{Context, Result};
-use crates_io_database::schema::categories;
+use crates_io_database::models::CategoryProposalStatus;
+use crates_io_database::schema::{categories, category_proposals};
+use diesel::dsl::{exists, not};
 use diesel::pg::upsert::excluded;
 use diesel::prelude::*;
 use diesel_async::scoped_futures::ScopedFutureExt;
@@ -116,8 +118,15 @@ pub async fn sync_with_connection(toml_str: &str, conn: &mut AsyncPgConnection)
                 .get_results(conn)
                 .await?;
 
+            // Categories that were added through approved category proposals
+            // are not part of the TOML file, but must be kept nonetheless.
+            let approved = category_proposals::table
+                .filter(category_proposals::slug.eq(categories::slug))
+                .filter(category_proposals::status.eq(CategoryProposalStatus::Approved));
+
             diesel::delete(categories::table)
                 .filter(categories::slug.ne_all(slugs))
+                .filter(not(exists(approved)))
                 .execute(conn)
                 .await?;
 
@@ -3,6 +3,7 @@ use url::Url;
 
 use crate::Env;
 use crate::rate_limiter::{LimitedAction, RateLimiterConfig};
+use crate::read_rate_limiter::ReadRouteGroup;
 use crate::util::gh_token_encryption::GitHubTokenEncryption;
 
 use super::base::Base;
@@ -47,6 +48,9 @@ pub struct Server {
     pub max_dependencies: usize,
     pub max_features: usize,
     pub rate_limiter: HashMap<LimitedAction, RateLimiterConfig>,
+    /// Per-client rate limits for read-only endpoints. Route groups without
+    /// an entry are not rate limited.
+    pub read_rate_limiter: HashMap<ReadRouteGroup, RateLimiterConfig>,
     pub new_version_rate_limit: Option<u32>,
     pub blocked_traffic: Vec<(String, Vec<String>)>,
     pub blocked_ips: HashSet<IpAddr>,
@@ -56,6 +60,22 @@ pub struct Server {
     pub allowed_origins: AllowedOrigins,
     pub downloads_persist_interval: Duration,
     pub ownership_invitations_expiration: chrono::Duration,
+
+    /// The amount of time that crates deleted by their owners are kept
+    /// around before they are deleted permanently.
+    pub crate_deletion_grace_period: chrono::Duration,
+
+    /// The amount of time that versions requiring publish approval can
+    /// wait for the approval of a second owner before they are deleted.
+    pub pending_version_expiration: chrono::Duration,
+
+    /// The amount of time that the usage log entries of API tokens are
+    /// kept around before they are deleted.
+    pub api_token_usage_retention: chrono::Duration,
+
+    /// Whether token owners are emailed when an API token is used from a
+    /// new country or network, or for a new endpoint.
+    pub api_token_usage_alerts: bool,
     pub metrics_authorization_token: Option<String>,
     pub instance_metrics_log_every_seconds: Option<u64>,
     pub blocked_routes: HashSet<String>,
@@ -111,6 +131,9 @@ impl Server {
     ///
     /// - `Config::max_upload_size`: 10MiB
     /// - `Config::ownership_invitations_expiration_days`: 30
+    /// - `Config::crate_deletion_grace_period`: 24 hours
+    /// - `Config::pending_version_expiration`: 72 hours
+    /// - `Config::api_token_usage_retention`: 90 days
     ///
     /// Pulls values from the following environment variables:
     ///
@@ -133,6 +156,13 @@ impl Server {
     ///   by an operator (e.g. `/crates/{crate_id}/{version}/download`).
     /// - `DISABLE_TOKEN_CREATION`: If set to any non-empty value, disables API token creation
     ///   and uses the value as the error message returned to users.
+    /// - `CRATE_DELETION_GRACE_PERIOD_HOURS`: The number of hours that crates deleted by their
+    ///   owners can be restored before they are deleted permanently. Defaults to 24.
+    /// - `PENDING_VERSION_EXPIRATION_HOURS`: The number of hours that versions requiring publish
+    ///   approval can wait for the approval of a second owner. Defaults to 72.
+    /// - `READ_RATE_LIMITER_{GROUP}_RATE_SECONDS` and `READ_RATE_LIMITER_{GROUP}_BURST`: The
+    ///   per-client rate limits of the read-only route groups defined in
+    ///   `src/read_rate_limiter.rs`. A burst of `0` disables rate limiting for the group.
     ///
     /// # Panics
     ///
@@ -176,6 +206,29 @@ impl Server {
             );
         }
 
+        // Same for the read-only route groups. See `src/read_rate_limiter.rs`
+        // for their definition.
+        let mut read_rate_limiter = HashMap::new();
+        for group in ReadRouteGroup::VARIANTS {
+            let env_var_key = group.env_var_key();
+            let burst = var_parsed(&format!("READ_RATE_LIMITER_{env_var_key}_BURST"))?
+                .unwrap_or_else(|| group.default_burst());
+            if burst <= 0 {
+                continue;
+            }
+
+            read_rate_limiter.insert(
+                *group,
+                RateLimiterConfig {
+                    rate: Duration::from_secs(
+                        var_parsed(&format!("READ_RATE_LIMITER_{env_var_key}_RATE_SECONDS"))?
+                            .unwrap_or_else(|| group.default_rate_seconds()),
+                    ),
+                    burst,
+                },
+            );
+        }
+
         let storage = StorageConfig::from_environment();
 
         // `sha256-dbf9FMl76C7BnK1CC3eWb3pvsQAUaTYSHAlBy9tNTG0=` refers to
@@ -221,6 +274,7 @@ impl Server {
             max_dependencies: DEFAULT_MAX_DEPENDENCIES,
             max_features: DEFAULT_MAX_FEATURES,
             rate_limiter,
+            read_rate_limiter,
             new_version_rate_limit: var_parsed("MAX_NEW_VERSIONS_DAILY")?,
             blocked_traffic: blocked_traffic(),
             blocked_ips,
@@ -232,6 +286,16 @@ impl Server {
                 .map(Duration::from_millis)
                 .unwrap_or(Duration::from_secs(60)),
             ownership_invitations_expiration: chrono::Duration::days(30),
+            crate_deletion_grace_period: chrono::Duration::hours(
+                var_parsed("CRATE_DELETION_GRACE_PERIOD_HOURS")?.unwrap_or(24),
+            ),
+            pending_version_expiration: chrono::Duration::hours(
+                var_parsed("PENDING_VERSION_EXPIRATION_HOURS")?.unwrap_or(72),
+            ),
+            api_token_usage_retention: chrono::Duration::days(
+                var_parsed("API_TOKEN_USAGE_RETENTION_DAYS")?.unwrap_or(90),
+            ),
+            api_token_usage_alerts: var_parsed("API_TOKEN_USAGE_ALERTS")?.unwrap_or(true),
             metrics_authorization_token: var("METRICS_AUTHORIZATION_TOKEN")?,
             instance_metrics_log_every_seconds: var_parsed("INSTANCE_METRICS_LOG_EVERY_SECONDS")?,
             blocked_routes: HashSet::from_iter(list("BLOCKED_ROUTES")?),
@@ -2,13 +2,16 @@ pub mod helpers;
 pub mod util;
 
 pub mod admin;
+pub mod binary;
 pub mod category;
+pub mod category_proposal;
 pub mod crate_owner_invitation;
 pub mod git;
 pub mod github;
 pub mod keyword;
 pub mod krate;
 pub mod metrics;
+pub mod secret_scanning;
 pub mod session;
 pub mod site_metadata;
 pub mod summary;
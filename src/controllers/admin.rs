@@ -1,16 +1,17 @@
 use crate::{
     app::AppState,
     auth::AuthCheck,
-    models::{OwnerKind, User},
+    models::{AccountLockAction, AccountLockEvent, NewAccountLockEvent, OwnerKind, User},
     schema::*,
-    util::errors::{AppResult, custom},
+    util::errors::{AppResult, BoxedAppError, bad_request, custom},
 };
 use axum::{Json, extract::Path};
 use chrono::{DateTime, Utc};
 use diesel::{dsl::count_star, prelude::*};
-use diesel_async::RunQueryDsl;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use http::{StatusCode, request::Parts};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, HasQuery)]
 #[diesel(
@@ -66,15 +67,7 @@ pub async fn list(
 ) -> AppResult<Json<AdminListResponse>> {
     let mut conn = state.db_read().await?;
 
-    let auth = AuthCheck::default().check(&req, &mut conn).await?;
-    let logged_in_user = auth.user();
-
-    if !logged_in_user.is_admin {
-        return Err(custom(
-            StatusCode::FORBIDDEN,
-            "must be an admin to use this route",
-        ));
-    }
+    authorize_admin(&req, &mut conn).await?;
 
     let (user, verified, user_email) = users::table
         .left_join(emails::table)
@@ -169,3 +162,158 @@ fn rev_deps_subquery() -> _ {
         .single_value()
         .assume_not_null()
 }
+
+#[derive(Debug, Deserialize)]
+pub struct LockUserRequest {
+    /// Explanation why the account is locked, which is shown to the user.
+    reason: String,
+    /// Date and time when the lock expires. The lock does not expire if this
+    /// is not set.
+    until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UnlockUserRequest {
+    /// Explanation why the account is unlocked, for the audit log.
+    reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountLockEventsResponse {
+    events: Vec<EncodableAccountLockEvent>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EncodableAccountLockEvent {
+    action: AccountLockAction,
+    actor: Option<String>,
+    reason: String,
+    locked_until: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+}
+
+/// Handles the `PUT /api/private/admin/users/{username}/lock` endpoint.
+pub async fn lock_user(
+    state: AppState,
+    Path(username): Path<String>,
+    req: Parts,
+    Json(body): Json<LockUserRequest>,
+) -> AppResult<Json<AccountLockEventsResponse>> {
+    let mut conn = state.db_write().await?;
+    let admin = authorize_admin(&req, &mut conn).await?;
+    let user = User::find_by_login(&mut conn, &username).await?;
+
+    let reason = body.reason.trim();
+    if reason.is_empty() {
+        return Err(bad_request("a reason for the lock is required"));
+    }
+
+    if body.until.is_some_and(|until| until <= Utc::now()) {
+        return Err(bad_request("the lock expiry must be in the future"));
+    }
+
+    NewAccountLockEvent::builder()
+        .user_id(user.id)
+        .action(AccountLockAction::Locked)
+        .actor_id(admin.id)
+        .reason(reason)
+        .maybe_locked_until(body.until)
+        .build()
+        .apply(&mut conn)
+        .await?;
+
+    lock_events_response(&mut conn, user.id).await
+}
+
+/// Handles the `DELETE /api/private/admin/users/{username}/lock` endpoint.
+pub async fn unlock_user(
+    state: AppState,
+    Path(username): Path<String>,
+    req: Parts,
+    Json(body): Json<UnlockUserRequest>,
+) -> AppResult<Json<AccountLockEventsResponse>> {
+    let mut conn = state.db_write().await?;
+    let admin = authorize_admin(&req, &mut conn).await?;
+    let user = User::find_by_login(&mut conn, &username).await?;
+
+    let reason = body.reason.trim();
+    if reason.is_empty() {
+        return Err(bad_request("a reason for the unlock is required"));
+    }
+
+    if user.account_lock_reason.is_none() {
+        return Err(bad_request("the account is not locked"));
+    }
+
+    NewAccountLockEvent::builder()
+        .user_id(user.id)
+        .action(AccountLockAction::Unlocked)
+        .actor_id(admin.id)
+        .reason(reason)
+        .build()
+        .apply(&mut conn)
+        .await?;
+
+    lock_events_response(&mut conn, user.id).await
+}
+
+/// Handles the `GET /api/private/admin/users/{username}/lock_events` endpoint.
+pub async fn list_lock_events(
+    state: AppState,
+    Path(username): Path<String>,
+    req: Parts,
+) -> AppResult<Json<AccountLockEventsResponse>> {
+    let mut conn = state.db_read().await?;
+    authorize_admin(&req, &mut conn).await?;
+    let user = User::find_by_login(&mut conn, &username).await?;
+
+    lock_events_response(&mut conn, user.id).await
+}
+
+/// Checks that the request was made by a crates.io admin with a cookie
+/// session, and returns the admin user.
+pub(crate) async fn authorize_admin(
+    req: &Parts,
+    conn: &mut AsyncPgConnection,
+) -> Result<User, BoxedAppError> {
+    let auth = AuthCheck::only_cookie().check(req, conn).await?;
+    let user = auth.user();
+
+    if !user.is_admin {
+        return Err(custom(
+            StatusCode::FORBIDDEN,
+            "must be an admin to use this route",
+        ));
+    }
+
+    Ok(user.clone())
+}
+
+async fn lock_events_response(
+    conn: &mut AsyncPgConnection,
+    user_id: i32,
+) -> AppResult<Json<AccountLockEventsResponse>> {
+    let events = AccountLockEvent::for_user(conn, user_id).await?;
+
+    let actor_ids = events.iter().filter_map(|event| event.actor_id);
+    let actors: HashMap<i32, String> = users::table
+        .filter(users::id.eq_any(actor_ids.collect::<Vec<_>>()))
+        .select((users::id, users::gh_login))
+        .load(conn)
+        .await?
+        .into_iter()
+        .collect();
+
+    let events = events
+        .into_iter()
+        .map(|event| EncodableAccountLockEvent {
+            action: event.action,
+            actor: event.actor_id.and_then(|id| actors.get(&id).cloned()),
+            reason: event.reason,
+            locked_until: event.locked_until,
+            created_at: event.created_at,
+        })
+        .collect();
+
+    Ok(Json(AccountLockEventsResponse { events }))
+}
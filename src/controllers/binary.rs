@@ -0,0 +1,96 @@
+//! Endpoint for looking up crates by the names of their binary targets
+
+use crate::app::AppState;
+use crate::models::{Crate, TopVersions, Version};
+use crate::schema::{crate_downloads, crates, default_versions, recent_crate_downloads, versions};
+use crate::util::errors::AppResult;
+use crate::views::EncodableCrate;
+use axum::Json;
+use axum::extract::Path;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde::Serialize;
+
+/// The maximum number of crates returned for a binary name.
+const MAX_CRATES: i64 = 100;
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct GetResponse {
+    /// The crates whose default version ships a binary with the given name,
+    /// sorted by their number of downloads.
+    crates: Vec<EncodableCrate>,
+}
+
+/// Find the crates that ship a binary with the given name.
+///
+/// Only the default version of each crate is taken into account, which makes
+/// this endpoint useful to answer questions like "which crate installs the
+/// `foo` command with `cargo install`?".
+#[utoipa::path(
+    get,
+    path = "/api/v1/binaries/{name}",
+    params(
+        ("name" = String, Path, description = "Name of the binary", example = "rg"),
+    ),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response", body = inline(GetResponse))),
+)]
+pub async fn find_binary(
+    Path(name): Path<String>,
+    state: AppState,
+) -> AppResult<Json<GetResponse>> {
+    let mut conn = state.db_read().await?;
+
+    #[allow(clippy::type_complexity)]
+    let data: Vec<(Crate, String, bool, Option<i32>, i64, Option<i64>)> = crates::table
+        .inner_join(default_versions::table)
+        .inner_join(versions::table.on(default_versions::version_id.eq(versions::id)))
+        .inner_join(crate_downloads::table)
+        .left_join(recent_crate_downloads::table)
+        .filter(versions::bin_names.contains(vec![Some(name.as_str())]))
+        .select((
+            Crate::as_select(),
+            versions::num,
+            versions::yanked,
+            default_versions::num_versions,
+            crate_downloads::downloads,
+            recent_crate_downloads::downloads.nullable(),
+        ))
+        .order((crate_downloads::downloads.desc(), crates::name.asc()))
+        .limit(MAX_CRATES)
+        .load(&mut conn)
+        .await?;
+
+    let krates = data.iter().map(|(krate, ..)| krate).collect::<Vec<_>>();
+    let versions: Vec<Version> = Version::belonging_to(&krates)
+        .filter(versions::yanked.eq(false))
+        .filter(Version::is_published())
+        .select(Version::as_select())
+        .load(&mut conn)
+        .await?;
+    let top_versions = versions
+        .grouped_by(&krates)
+        .into_iter()
+        .map(TopVersions::from_versions);
+
+    let crates = top_versions
+        .zip(data)
+        .map(
+            |(top_versions, (krate, num, yanked, num_versions, downloads, recent_downloads))| {
+                let exact_match = krate.name == name;
+                EncodableCrate::from_minimal(
+                    krate,
+                    Some(&num),
+                    num_versions.unwrap_or_default(),
+                    Some(yanked),
+                    Some(&top_versions),
+                    exact_match,
+                    downloads,
+                    Some(recent_downloads.unwrap_or(0)),
+                )
+            },
+        )
+        .collect();
+
+    Ok(Json(GetResponse { crates }))
+}
@@ -0,0 +1,281 @@
+//! Endpoints for proposing new categories, which are reviewed by the
+//! crates.io team
+
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::admin::authorize_admin;
+use crate::models::{
+    Category, CategoryProposal, CategoryProposalStatus, NewCategoryProposal, User,
+};
+use crate::schema::users;
+use crate::util::errors::{AppResult, bad_request, not_found};
+use crate::views::EncodableCategoryProposal;
+use axum::Json;
+use axum::extract::Path;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use http::StatusCode;
+use http::request::Parts;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The maximum length of the name of a proposed category.
+const MAX_NAME_LENGTH: usize = 64;
+
+/// The maximum length of the description of a proposed category.
+const MAX_DESCRIPTION_LENGTH: usize = 1000;
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct CategoryProposalResponse {
+    pub category_proposal: EncodableCategoryProposal,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ListCategoryProposalsResponse {
+    /// The category proposals, most recent first.
+    pub category_proposals: Vec<EncodableCategoryProposal>,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CreateCategoryProposal {
+    /// The slug of the new category.
+    ///
+    /// Subcategories are separated from their parent category by `::`, and
+    /// the parent category has to exist already.
+    #[schema(example = "game-development::physics")]
+    slug: String,
+
+    /// The name of the new category, without the name of its parent
+    /// category.
+    #[schema(example = "Physics")]
+    name: String,
+
+    /// A description of the crates that belong in the new category.
+    #[schema(example = "Physics engines for games.")]
+    description: String,
+}
+
+#[derive(Deserialize)]
+pub struct ResolveCategoryProposal {
+    action: ResolveCategoryProposalAction,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResolveCategoryProposalAction {
+    Approve,
+    Reject,
+}
+
+/// Propose a new category.
+///
+/// The proposal is reviewed by the crates.io team, and the category becomes
+/// available to all crates once it is approved.
+#[utoipa::path(
+    post,
+    path = "/api/v1/category_proposals",
+    request_body = inline(CreateCategoryProposal),
+    security(
+        ("cookie" = []),
+    ),
+    tag = "categories",
+    responses((status = 201, description = "Successful Response", body = inline(CategoryProposalResponse))),
+)]
+pub async fn create_category_proposal(
+    app: AppState,
+    parts: Parts,
+    Json(body): Json<CreateCategoryProposal>,
+) -> AppResult<(StatusCode, Json<CategoryProposalResponse>)> {
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::only_cookie().check(&parts, &mut conn).await?;
+    let user = auth.user();
+
+    let slug = body.slug.trim();
+    if !CategoryProposal::valid_slug(slug) {
+        return Err(bad_request(format_args!(
+            "invalid category slug `{slug}`: slugs may only contain lowercase letters, \
+            digits and dashes, and subcategories are separated by `::`"
+        )));
+    }
+
+    let name = body.name.trim();
+    if name.is_empty() || name.contains("::") {
+        return Err(bad_request(
+            "the category name must not be empty or contain `::`",
+        ));
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(bad_request(format_args!(
+            "the category name must not be longer than {MAX_NAME_LENGTH} characters"
+        )));
+    }
+
+    let description = body.description.trim();
+    if description.is_empty() {
+        return Err(bad_request("a description of the category is required"));
+    }
+    if description.chars().count() > MAX_DESCRIPTION_LENGTH {
+        return Err(bad_request(format_args!(
+            "the description must not be longer than {MAX_DESCRIPTION_LENGTH} characters"
+        )));
+    }
+
+    if user.verified_email(&mut conn).await?.is_none() {
+        return Err(bad_request(
+            "a verified email address is required to propose a category",
+        ));
+    }
+
+    check_category_availability(slug, &mut conn).await?;
+
+    let new_proposal = NewCategoryProposal {
+        slug,
+        name,
+        description,
+        proposed_by: user.id,
+    };
+
+    let Some(proposal) = new_proposal.insert(&mut conn).await? else {
+        return Err(bad_request(format_args!(
+            "there is already a pending proposal for the category `{slug}`"
+        )));
+    };
+
+    let category_proposal = encode_proposals(vec![proposal], &mut conn).await?.remove(0);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CategoryProposalResponse { category_proposal }),
+    ))
+}
+
+/// List category proposals.
+///
+/// Admins can see all proposals, while other users can only see their own
+/// proposals.
+#[utoipa::path(
+    get,
+    path = "/api/v1/category_proposals",
+    security(
+        ("cookie" = []),
+    ),
+    tag = "categories",
+    responses((status = 200, description = "Successful Response", body = inline(ListCategoryProposalsResponse))),
+)]
+pub async fn list_category_proposals(
+    app: AppState,
+    parts: Parts,
+) -> AppResult<Json<ListCategoryProposalsResponse>> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let auth = AuthCheck::only_cookie().check(&parts, &mut conn).await?;
+    let user = auth.user();
+
+    let proposals = if user.is_admin {
+        CategoryProposal::all(&mut conn).await?
+    } else {
+        CategoryProposal::for_user(&mut conn, user.id).await?
+    };
+
+    let category_proposals = encode_proposals(proposals, &mut conn).await?;
+
+    Ok(Json(ListCategoryProposalsResponse { category_proposals }))
+}
+
+/// Handles the `PUT /api/private/admin/category_proposals/{id}` endpoint.
+///
+/// Admins can `approve` a pending proposal, which adds the category to the
+/// `categories` table, or `reject` it.
+pub async fn resolve_category_proposal(
+    app: AppState,
+    Path(id): Path<i32>,
+    parts: Parts,
+    Json(body): Json<ResolveCategoryProposal>,
+) -> AppResult<Json<CategoryProposalResponse>> {
+    let mut conn = app.db_write().await?;
+    let user = authorize_admin(&parts, &mut conn).await?;
+
+    let proposal = CategoryProposal::find(&mut conn, id)
+        .await
+        .optional()?
+        .ok_or_else(not_found)?;
+
+    if proposal.status != CategoryProposalStatus::Pending {
+        return Err(bad_request("the category proposal is not pending anymore"));
+    }
+
+    let resolved = match body.action {
+        ResolveCategoryProposalAction::Approve => {
+            // The category might have been added to `categories.toml` in
+            // the meantime, or the parent category might have been removed.
+            check_category_availability(&proposal.slug, &mut conn).await?;
+            proposal.approve(&mut conn, user.id).await?.is_some()
+        }
+        ResolveCategoryProposalAction::Reject => proposal.reject(&mut conn, user.id).await?,
+    };
+
+    if !resolved {
+        return Err(bad_request("the category proposal is not pending anymore"));
+    }
+
+    let proposal = CategoryProposal::find(&mut conn, proposal.id).await?;
+    let category_proposal = encode_proposals(vec![proposal], &mut conn).await?.remove(0);
+
+    Ok(Json(CategoryProposalResponse { category_proposal }))
+}
+
+/// Checks that the proposed category does not exist yet, and that the
+/// parent category of a proposed subcategory exists.
+async fn check_category_availability(slug: &str, conn: &mut AsyncPgConnection) -> AppResult<()> {
+    if category_exists(slug, conn).await? {
+        return Err(bad_request(format_args!(
+            "the category `{slug}` already exists"
+        )));
+    }
+
+    if let Some((parent_slug, _)) = slug.rsplit_once("::")
+        && !category_exists(parent_slug, conn).await?
+    {
+        return Err(bad_request(format_args!(
+            "the parent category `{parent_slug}` does not exist"
+        )));
+    }
+
+    Ok(())
+}
+
+async fn category_exists(slug: &str, conn: &mut AsyncPgConnection) -> QueryResult<bool> {
+    diesel::select(diesel::dsl::exists(Category::by_slug(slug)))
+        .get_result(conn)
+        .await
+}
+
+/// Loads the users who proposed the given categories, and encodes the
+/// proposals for the API response.
+async fn encode_proposals(
+    proposals: Vec<CategoryProposal>,
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<Vec<EncodableCategoryProposal>> {
+    let user_ids = proposals
+        .iter()
+        .filter_map(|proposal| proposal.proposed_by)
+        .collect::<Vec<_>>();
+
+    let users = User::query()
+        .filter(users::id.eq_any(user_ids))
+        .load(conn)
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user))
+        .collect::<HashMap<_, _>>();
+
+    let proposals = proposals
+        .into_iter()
+        .map(|proposal| {
+            let proposed_by = proposal.proposed_by.and_then(|id| users.get(&id)).cloned();
+
+            EncodableCategoryProposal::from(proposal, proposed_by)
+        })
+        .collect();
+
+    Ok(proposals)
+}
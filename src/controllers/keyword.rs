@@ -1,12 +1,15 @@
 use crate::app::AppState;
+use crate::controllers::admin::authorize_admin;
 use crate::controllers::helpers::pagination::{PaginationOptions, PaginationQueryParams};
 use crate::controllers::helpers::{Paginate, pagination::Paginated};
 use crate::models::Keyword;
-use crate::util::errors::AppResult;
+use crate::schema::keywords;
+use crate::util::errors::{AppResult, bad_request, not_found};
 use crate::views::EncodableKeyword;
 use axum::Json;
 use axum::extract::{FromRequestParts, Path, Query};
 use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
 use http::request::Parts;
 use serde::{Deserialize, Serialize};
 
@@ -51,8 +54,6 @@ pub async fn list_keywords(
     params: ListQueryParams,
     req: Parts,
 ) -> AppResult<Json<ListResponse>> {
-    use crate::schema::keywords;
-
     let mut query = Keyword::query().into_boxed();
 
     query = match &params.sort {
@@ -95,3 +96,48 @@ pub async fn find_keyword(
     let keyword = EncodableKeyword::from(kw);
     Ok(Json(GetResponse { keyword }))
 }
+
+#[derive(Debug, Deserialize)]
+pub struct MergeKeywordRequest {
+    /// The keyword that the keyword in the path is merged into.
+    into: String,
+}
+
+/// Handles the `PUT /api/private/admin/keywords/{keyword}/merge` endpoint.
+///
+/// Merges a keyword into another keyword, e.g. `async` into `asynchronous`.
+/// The crates with the merged keyword are moved over to the other keyword,
+/// and the merged keyword is kept as an alias, so that it resolves to the
+/// other keyword when crates are published with it in the future.
+pub async fn merge_keyword(
+    state: AppState,
+    Path(name): Path<String>,
+    parts: Parts,
+    Json(body): Json<MergeKeywordRequest>,
+) -> AppResult<Json<GetResponse>> {
+    let mut conn = state.db_write().await?;
+    authorize_admin(&parts, &mut conn).await?;
+
+    // Aliases are not resolved here, since they can't be merged again.
+    let source = Keyword::query()
+        .filter(keywords::keyword.eq(name.to_lowercase()))
+        .first(&mut conn)
+        .await
+        .optional()?
+        .ok_or_else(not_found)?;
+
+    let target = Keyword::find_by_keyword(&mut conn, &body.into)
+        .await
+        .optional()?
+        .ok_or_else(|| bad_request(format_args!("the keyword `{}` does not exist", body.into)))?;
+
+    if source.id == target.id {
+        return Err(bad_request("a keyword can't be merged into itself"));
+    }
+
+    source.merge_into(&mut conn, &target).await?;
+
+    let keyword = Keyword::find_by_keyword(&mut conn, &target.keyword).await?;
+    let keyword = EncodableKeyword::from(keyword);
+    Ok(Json(GetResponse { keyword }))
+}
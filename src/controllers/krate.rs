@@ -13,8 +13,10 @@ pub mod follow;
 pub mod metadata;
 pub mod owners;
 pub mod publish;
+pub mod publish_policy;
 pub mod rev_deps;
 pub mod search;
+pub mod transfer_requests;
 pub mod update;
 pub mod versions;
 
@@ -3,15 +3,16 @@ use crate::auth::AuthCheck;
 use crate::controllers::helpers::authorization::Rights;
 use crate::controllers::krate::CratePath;
 use crate::email::EmailMessage;
-use crate::models::NewDeletedCrate;
-use crate::schema::{crate_downloads, crates, dependencies};
-use crate::util::errors::{AppResult, BoxedAppError, custom};
+use crate::models::{NewScheduledCrateDeletion, ScheduledCrateDeletion, User};
+use crate::schema::{crate_downloads, dependencies};
+use crate::util::errors::{AppResult, BoxedAppError, bad_request, custom, not_found};
+use crate::views::EncodableScheduledCrateDeletion;
 use crate::worker::jobs;
+use axum::Json;
 use axum::extract::rejection::QueryRejection;
 use axum::extract::{FromRequestParts, Query};
 use bigdecimal::ToPrimitive;
-use chrono::{TimeDelta, Utc};
-use crates_io_database::schema::deleted_crates;
+use chrono::{SecondsFormat, TimeDelta, Utc};
 use crates_io_worker::BackgroundJob;
 use diesel::prelude::*;
 use diesel_async::scoped_futures::ScopedFutureExt;
@@ -19,11 +20,10 @@ use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
 use http::StatusCode;
 use http::request::Parts;
 use minijinja::context;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tracing::error;
 
 pub const DOWNLOADS_PER_MONTH_LIMIT: u64 = 1000;
-const AVAILABLE_AFTER: TimeDelta = TimeDelta::hours(24);
 
 #[derive(Debug, Deserialize, FromRequestParts, utoipa::IntoParams)]
 #[from_request(via(Query), rejection(QueryRejection))]
@@ -40,8 +40,11 @@ impl DeleteQueryParams {
 
 /// Delete a crate.
 ///
-/// The crate is immediately deleted from the database, and with a small delay
-/// from the git and sparse index, and the crate file storage.
+/// The crate is scheduled for deletion and immediately removed from the git
+/// and sparse index, and from search results. All other data of the crate is
+/// kept intact until the grace period ends, and the owners of the crate can
+/// cancel the deletion until then. Afterwards the crate is deleted from the
+/// database and the crate file storage.
 ///
 /// The crate can only be deleted by the owner of the crate, and only if the
 /// crate has been published for less than 72 hours, or if the crate has a
@@ -109,38 +112,26 @@ pub async fn delete_crate(
         return Err(custom(StatusCode::UNPROCESSABLE_ENTITY, msg));
     }
 
+    let scheduled_for = Utc::now() + app.config.crate_deletion_grace_period;
+
     let crate_name = krate.name.clone();
     conn.transaction(|conn| {
         async move {
-            diesel::delete(crates::table.find(krate.id))
-                .execute(conn)
-                .await?;
-
-            let deleted_at = Utc::now();
-            let available_at = deleted_at + AVAILABLE_AFTER;
-
-            let deleted_crate = NewDeletedCrate::builder(&krate.name)
-                .created_at(&created_at)
-                .deleted_at(&deleted_at)
-                .deleted_by(user.id)
-                .available_at(&available_at)
-                .maybe_message(params.message())
-                .build();
-
-            diesel::insert_into(deleted_crates::table)
-                .values(deleted_crate)
-                .execute(conn)
-                .await?;
+            let deletion = NewScheduledCrateDeletion {
+                crate_id: krate.id,
+                requested_by: Some(user.id),
+                scheduled_for,
+                message: params.message(),
+            };
+
+            if deletion.insert(conn).await?.is_none() {
+                return Err(bad_request("this crate is already scheduled for deletion"));
+            }
 
             let git_index_job = jobs::SyncToGitIndex::new(&krate.name);
             let sparse_index_job = jobs::SyncToSparseIndex::new(&krate.name);
-            let delete_from_storage_job = jobs::DeleteCrateFromStorage::new(path.name);
 
-            tokio::try_join!(
-                git_index_job.enqueue(conn),
-                sparse_index_job.enqueue(conn),
-                delete_from_storage_job.enqueue(conn),
-            )?;
+            tokio::try_join!(git_index_job.enqueue(conn), sparse_index_job.enqueue(conn))?;
 
             Ok::<_, BoxedAppError>(())
         }
@@ -154,7 +145,9 @@ pub async fn delete_crate(
                 "crate_deletion",
                 context! {
                     user => user.gh_login,
-                    krate => crate_name
+                    krate => crate_name,
+                    scheduled_for => scheduled_for.to_rfc3339_opts(SecondsFormat::Secs, true),
+                    domain => app.emails.domain,
                 },
             )?;
 
@@ -171,6 +164,103 @@ pub async fn delete_crate(
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct GetScheduledDeletionResponse {
+    pub scheduled_deletion: EncodableScheduledCrateDeletion,
+}
+
+/// Get the scheduled deletion of a crate.
+///
+/// Only owners of the crate can see whether it is scheduled for deletion.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/scheduled_deletion",
+    params(CratePath),
+    security(("cookie" = [])),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response", body = inline(GetScheduledDeletionResponse))),
+)]
+pub async fn get_scheduled_deletion(
+    path: CratePath,
+    parts: Parts,
+    app: AppState,
+) -> AppResult<Json<GetScheduledDeletionResponse>> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let auth = AuthCheck::only_cookie().check(&parts, &mut conn).await?;
+    let krate = path.load_crate(&mut conn).await?;
+
+    let user = auth.user();
+    let owners = krate.owners(&mut conn).await?;
+    if Rights::get(user, &*app.github, &owners, &app.config.gh_token_encryption).await?
+        < Rights::Full
+    {
+        let msg = "only owners have permission to see scheduled deletions";
+        return Err(custom(StatusCode::FORBIDDEN, msg));
+    }
+
+    let deletion = ScheduledCrateDeletion::find(&mut conn, krate.id)
+        .await?
+        .ok_or_else(not_found)?;
+
+    let requested_by = match deletion.requested_by {
+        Some(user_id) => User::find(&mut conn, user_id).await.optional()?,
+        None => None,
+    };
+
+    let scheduled_deletion = EncodableScheduledCrateDeletion::from(deletion, requested_by);
+    Ok(Json(GetScheduledDeletionResponse { scheduled_deletion }))
+}
+
+/// Cancel the scheduled deletion of a crate.
+///
+/// The crate is added back to the git and sparse index, and to search
+/// results.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/crates/{name}/scheduled_deletion",
+    params(CratePath),
+    security(("cookie" = [])),
+    tag = "crates",
+    responses((status = 204, description = "Successful Response")),
+)]
+pub async fn cancel_scheduled_deletion(
+    path: CratePath,
+    parts: Parts,
+    app: AppState,
+) -> AppResult<StatusCode> {
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::only_cookie().check(&parts, &mut conn).await?;
+    let krate = path.load_crate(&mut conn).await?;
+
+    let user = auth.user();
+    let owners = krate.owners(&mut conn).await?;
+    if Rights::get(user, &*app.github, &owners, &app.config.gh_token_encryption).await?
+        < Rights::Full
+    {
+        let msg = "only owners have permission to cancel scheduled deletions";
+        return Err(custom(StatusCode::FORBIDDEN, msg));
+    }
+
+    conn.transaction(|conn| {
+        async move {
+            if !ScheduledCrateDeletion::cancel(conn, krate.id).await? {
+                return Err(bad_request("this crate is not scheduled for deletion"));
+            }
+
+            let git_index_job = jobs::SyncToGitIndex::new(&krate.name);
+            let sparse_index_job = jobs::SyncToSparseIndex::new(&krate.name);
+
+            tokio::try_join!(git_index_job.enqueue(conn), sparse_index_job.enqueue(conn))?;
+
+            Ok::<_, BoxedAppError>(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 async fn get_crate_downloads(conn: &mut AsyncPgConnection, crate_id: i32) -> QueryResult<u64> {
     let downloads = crate_downloads::table
         .find(crate_id)
@@ -93,6 +93,7 @@ pub async fn get_crate_downloads(
 
     let mut versions: Vec<Version> = Version::query()
         .filter(versions::crate_id.eq(crate_id))
+        .filter(FullVersion::is_published())
         .load(&mut conn)
         .await?;
 
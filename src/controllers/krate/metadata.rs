@@ -307,6 +307,7 @@ fn _load_versions_and_publishers<'a>(
     version_num: Option<&'a str>,
 ) -> BoxFuture<'a, AppResult<Option<Vec<VersionsAndPublishers>>>> {
     let mut query = Version::belonging_to(&krate)
+        .filter(Version::is_published())
         .left_outer_join(users::table)
         .select(<(Version, Option<User>)>::as_select())
         .order_by(versions::id.desc())
@@ -194,6 +194,7 @@ async fn modify_owners(
         .await?;
 
     let user = auth.user();
+    let is_token_auth = auth.api_token_id().is_some();
 
     let (msg, emails) = conn
         .transaction(|conn| {
@@ -224,6 +225,15 @@ async fn modify_owners(
                     }
                 }
 
+                // Otherwise a compromised API token could be used to add a
+                // second account that approves the publishes of the first one.
+                if krate.requires_publish_approval && is_token_auth {
+                    return Err(custom(
+                        StatusCode::FORBIDDEN,
+                        "the owners of crates that require publish approval can only be modified from the crates.io website",
+                    ));
+                }
+
                 // The set of emails to send out after invite processing is complete and
                 // the database transaction has committed.
                 let mut emails = Vec::with_capacity(logins.len());
@@ -3,8 +3,8 @@
 use crate::app::AppState;
 use crate::auth::{AuthCheck, AuthHeader, Authentication};
 use crate::worker::jobs::{
-    self, AnalyzeCrateFile, CheckTyposquat, GenerateOgImage, SendPublishNotificationsJob,
-    UpdateDefaultVersion,
+    self, AnalyzeCrateFile, CheckSemverCompatibility, CheckTyposquat, GenerateOgImage,
+    SendPendingVersionNotifications, SendPublishNotificationsJob, UpdateDefaultVersion,
 };
 use axum::Json;
 use axum::body::{Body, Bytes};
@@ -34,8 +34,9 @@ use tracing::{error, instrument};
 use url::Url;
 
 use crate::models::{
-    Category, Crate, DependencyKind, Keyword, NewCrate, NewVersion, NewVersionOwnerAction,
-    VersionAction, default_versions::Version as DefaultVersion,
+    Category, Crate, CratePublishPolicy, DependencyKind, Keyword, NewCrate, NewPendingVersion,
+    NewVersion, NewVersionOwnerAction, ScheduledCrateDeletion, VersionAction,
+    default_versions::Version as DefaultVersion,
 };
 
 use crate::controllers::helpers::authorization::Rights;
@@ -61,14 +62,20 @@ const MAX_DESCRIPTION_LENGTH: usize = 1000;
 
 enum AuthType {
     Regular(Box<Authentication>),
-    TrustPub(Option<TrustpubData>),
+    TrustPub {
+        data: Option<TrustpubData>,
+        /// IDs of the GitHub configs that matched when the token was issued.
+        github_config_ids: Vec<i32>,
+        /// IDs of the GitLab configs that matched when the token was issued.
+        gitlab_config_ids: Vec<i32>,
+    },
 }
 
 impl AuthType {
     fn user(&self) -> Option<&User> {
         match self {
             AuthType::Regular(auth) => Some(auth.user()),
-            AuthType::TrustPub(_) => None,
+            AuthType::TrustPub { .. } => None,
         }
     }
 
@@ -79,11 +86,46 @@ impl AuthType {
     fn trustpub_data(&self) -> Option<&TrustpubData> {
         match self {
             AuthType::Regular(_) => None,
-            AuthType::TrustPub(data) => data.as_ref(),
+            AuthType::TrustPub { data, .. } => data.as_ref(),
         }
     }
 }
 
+/// Checks that the publish policy of an existing crate allows publishing
+/// with the given authentication.
+///
+/// The restrictions on API tokens are enforced by [`AuthCheck`] already.
+fn check_publish_policy(policy: &CratePublishPolicy, auth: &AuthType) -> AppResult<()> {
+    match auth {
+        AuthType::Regular(auth) => {
+            if policy.requires_trusted_publishing() {
+                return Err(forbidden(
+                    "The publish policy of this crate only allows publishing new versions using Trusted Publishing (see https://crates.io/docs/trusted-publishing).",
+                ));
+            }
+
+            if !policy.allows_publisher(auth.user_id()) {
+                return Err(forbidden(
+                    "The publish policy of this crate does not allow you to publish new versions.",
+                ));
+            }
+        }
+        AuthType::TrustPub {
+            github_config_ids,
+            gitlab_config_ids,
+            ..
+        } => {
+            if !policy.allows_trustpub_configs(github_config_ids, gitlab_config_ids) {
+                return Err(forbidden(
+                    "The publish policy of this crate does not allow publishing new versions using this Trusted Publishing config.",
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// Publish a new crate/version.
 ///
 /// Used by `cargo publish` to publish a new crate or to publish a new version of an
@@ -186,11 +228,23 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
 
         let hashed_token = trustpub_token.sha256();
 
-        let (crate_ids, trustpub_data): (Vec<Option<i32>>, Option<TrustpubData>) =
+        type TokenRow = (
+            Vec<Option<i32>>,
+            Option<TrustpubData>,
+            Vec<Option<i32>>,
+            Vec<Option<i32>>,
+        );
+
+        let (crate_ids, trustpub_data, github_config_ids, gitlab_config_ids): TokenRow =
             trustpub_tokens::table
                 .filter(trustpub_tokens::hashed_token.eq(hashed_token.as_slice()))
                 .filter(trustpub_tokens::expires_at.gt(now))
-                .select((trustpub_tokens::crate_ids, trustpub_tokens::trustpub_data))
+                .select((
+                    trustpub_tokens::crate_ids,
+                    trustpub_tokens::trustpub_data,
+                    trustpub_tokens::github_config_ids,
+                    trustpub_tokens::gitlab_config_ids,
+                ))
                 .get_result(&mut conn)
                 .await
                 .optional()?
@@ -202,7 +256,11 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
             return Err(forbidden(error));
         }
 
-        AuthType::TrustPub(trustpub_data)
+        AuthType::TrustPub {
+            data: trustpub_data,
+            github_config_ids: github_config_ids.into_iter().flatten().collect(),
+            gitlab_config_ids: gitlab_config_ids.into_iter().flatten().collect(),
+        }
     } else {
         let endpoint_scope = match existing_crate {
             Some(_) => EndpointScope::PublishUpdate,
@@ -228,6 +286,23 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
         ));
     }
 
+    if let Some(existing_crate) = &existing_crate
+        && let Some(policy) = CratePublishPolicy::find(&mut conn, existing_crate.id).await?
+    {
+        check_publish_policy(&policy, &auth)?;
+    }
+
+    if let Some(existing_crate) = &existing_crate
+        && ScheduledCrateDeletion::find(&mut conn, existing_crate.id)
+            .await?
+            .is_some()
+    {
+        let name = &existing_crate.name;
+        return Err(bad_request(format!(
+            "crate `{name}` is scheduled for deletion. Cancel the deletion before publishing new versions."
+        )));
+    }
+
     let verified_email_address = if let Some(user) = auth.user() {
         let verified_email_address = user.verified_email(&mut conn).await?;
         Some(verified_email_address.ok_or_else(|| verified_email_error(&app.config.domain_name))?)
@@ -556,6 +631,19 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
         // Link this new version to all dependencies
         add_dependencies(conn, &deps, version.id).await?;
 
+        // Versions of crates that require publish approval are only published
+        // once a second owner approved them.
+        let pending_version = if krate.requires_publish_approval {
+            let new_pending_version = NewPendingVersion {
+                version_id: version.id,
+                expires_at: Utc::now() + app.config.pending_version_expiration,
+            };
+
+            Some(new_pending_version.insert(conn).await?)
+        } else {
+            None
+        };
+
         let existing_default_version = default_versions::table
             .inner_join(versions::table)
             .filter(default_versions::crate_id.eq(krate.id))
@@ -581,7 +669,7 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
                 yanked: false,
             };
 
-            if existing_default_version < &published_default_version {
+            if pending_version.is_none() && existing_default_version < &published_default_version {
                 diesel::update(default_versions::table)
                     .filter(default_versions::crate_id.eq(krate.id))
                     .set(default_versions::version_id.eq(version.id))
@@ -630,34 +718,12 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
         }
 
         // Upload crate tarball
-        app.storage.upload_crate_file(&krate.name, &version_string, tarball_bytes)
-            .await
-            .map_err(|e| internal(format!("failed to upload crate: {e}")))?;
-
-        let git_index_job = jobs::SyncToGitIndex::new(&krate.name);
-        let sparse_index_job = jobs::SyncToSparseIndex::new(&krate.name);
-        let publish_notifications_job = SendPublishNotificationsJob::new(version.id);
-        let crate_feed_job = jobs::rss::SyncCrateFeed::new(krate.name.clone());
-        let updates_feed_job = jobs::rss::SyncUpdatesFeed;
-        let analyze_crate_file_job = AnalyzeCrateFile::new(version.id);
-
-        tokio::try_join!(
-            git_index_job.enqueue(conn),
-            sparse_index_job.enqueue(conn),
-            publish_notifications_job.enqueue(conn),
-            crate_feed_job.enqueue(conn).or_else(async |error| {
-                error!("Failed to enqueue `rss::SyncCrateFeed` job: {error}");
-                Ok::<_, EnqueueError>(None)
-            }),
-            updates_feed_job.enqueue(conn).or_else(async |error| {
-                error!("Failed to enqueue `rss::SyncUpdatesFeed` job: {error}");
-                Ok::<_, EnqueueError>(None)
-            }),
-            analyze_crate_file_job.enqueue(conn).or_else(async |error| {
-                error!("Failed to enqueue `AnalyzeCrateFile` job: {error}");
-                Ok::<_, EnqueueError>(None)
-            }),
-        )?;
+        if pending_version.is_some() {
+            app.storage.upload_pending_crate_file(&krate.name, &version_string, tarball_bytes).await
+        } else {
+            app.storage.upload_crate_file(&krate.name, &version_string, tarball_bytes).await
+        }
+        .map_err(|e| internal(format!("failed to upload crate: {e}")))?;
 
         // Enqueue OG image generation job if not handled by UpdateDefaultVersion
         if existing_default_version.is_none() {
@@ -667,30 +733,25 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
             }
         };
 
-        // Experiment: check new crates for potential typosquatting.
-        if existing_crate.is_none() {
-            let crates_feed_job = jobs::rss::SyncCratesFeed;
-            let typosquat_job = CheckTyposquat::new(&krate.name);
-
-            tokio::try_join!(
-                crates_feed_job.enqueue(conn).or_else(async |error| {
-                    error!("Failed to enqueue `rss::SyncCratesFeed` job: {error}");
-                    Ok::<_, EnqueueError>(None)
-                }),
-                typosquat_job.enqueue(conn).or_else(async |error| {
-                    error!("Failed to enqueue `CheckTyposquat` job: {error}");
-                    Ok::<_, EnqueueError>(None)
-                }),
-            )?;
+        let mut other_warnings = Vec::new();
+        if let Some(pending_version) = &pending_version {
+            SendPendingVersionNotifications::new(version.id).enqueue(conn).await?;
+
+            let expires_at = pending_version.expires_at.to_rfc3339_opts(SecondsFormat::Secs, true);
+            other_warnings.push(format!(
+                "{name}@{version_string} has to be approved by another owner of the crate before it is published. \
+                If it is not approved, it will be deleted at {expires_at}."
+            ));
+        } else {
+            enqueue_publish_jobs(conn, &krate, version.id, existing_crate.is_none()).await?;
         }
 
-        // The `other` field on `PublishWarnings` was introduced to handle a temporary warning
-        // that is no longer needed. As such, crates.io currently does not return any `other`
-        // warnings at this time, but if we need to, the field is available.
+        // The `other` field on `PublishWarnings` is currently only used to tell the user that
+        // the version is waiting for approval.
         let warnings = PublishWarnings {
             invalid_categories: vec![],
             invalid_badges: vec![],
-            other: vec![],
+            other: other_warnings,
         };
 
         Ok(Json(GoodCrate {
@@ -709,6 +770,84 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
     }.scope_boxed()).await
 }
 
+/// Enqueues the background jobs that make a new version available, e.g. by
+/// syncing it to the index, and that notify the owners about it.
+///
+/// For crates that require publish approval, this happens once a second
+/// owner approved the version.
+pub async fn enqueue_publish_jobs(
+    conn: &mut AsyncPgConnection,
+    krate: &Crate,
+    version_id: i32,
+    new_crate: bool,
+) -> Result<(), EnqueueError> {
+    let git_index_job = jobs::SyncToGitIndex::new(&krate.name);
+    let sparse_index_job = jobs::SyncToSparseIndex::new(&krate.name);
+    let publish_notifications_job = SendPublishNotificationsJob::new(version_id);
+    let crate_feed_job = jobs::rss::SyncCrateFeed::new(krate.name.clone());
+    let updates_feed_job = jobs::rss::SyncUpdatesFeed;
+    let analyze_crate_file_job = AnalyzeCrateFile::new(version_id);
+
+    tokio::try_join!(
+        git_index_job.enqueue(conn),
+        sparse_index_job.enqueue(conn),
+        publish_notifications_job.enqueue(conn),
+        crate_feed_job.enqueue(conn).or_else(async |error| {
+            error!("Failed to enqueue `rss::SyncCrateFeed` job: {error}");
+            Ok::<_, EnqueueError>(None)
+        }),
+        updates_feed_job.enqueue(conn).or_else(async |error| {
+            error!("Failed to enqueue `rss::SyncUpdatesFeed` job: {error}");
+            Ok::<_, EnqueueError>(None)
+        }),
+        analyze_crate_file_job.enqueue(conn).or_else(async |error| {
+            error!("Failed to enqueue `AnalyzeCrateFile` job: {error}");
+            Ok::<_, EnqueueError>(None)
+        }),
+    )?;
+
+    // Regenerate the feeds of the owners, keywords and categories of the crate.
+    match jobs::rss::SyncCrateGroupFeed::for_crate(conn, krate.id).await {
+        Ok(group_feed_jobs) => {
+            for job in group_feed_jobs {
+                if let Err(error) = job.enqueue(conn).await {
+                    error!("Failed to enqueue `rss::SyncCrateGroupFeed` job: {error}");
+                }
+            }
+        }
+        Err(error) => {
+            error!("Failed to load crate groups for `rss::SyncCrateGroupFeed` jobs: {error}")
+        }
+    }
+
+    // Compare the public API against the previous semver-compatible version.
+    if !new_crate {
+        let semver_check_job = CheckSemverCompatibility::new(version_id);
+        if let Err(error) = semver_check_job.enqueue(conn).await {
+            error!("Failed to enqueue `CheckSemverCompatibility` job: {error}");
+        }
+    }
+
+    // Experiment: check new crates for potential typosquatting.
+    if new_crate {
+        let crates_feed_job = jobs::rss::SyncCratesFeed;
+        let typosquat_job = CheckTyposquat::new(&krate.name);
+
+        tokio::try_join!(
+            crates_feed_job.enqueue(conn).or_else(async |error| {
+                error!("Failed to enqueue `rss::SyncCratesFeed` job: {error}");
+                Ok::<_, EnqueueError>(None)
+            }),
+            typosquat_job.enqueue(conn).or_else(async |error| {
+                error!("Failed to enqueue `CheckTyposquat` job: {error}");
+                Ok::<_, EnqueueError>(None)
+            }),
+        )?;
+    }
+
+    Ok(())
+}
+
 /// Counts the number of versions for `crate_id` that were published within
 /// the last 24 hours.
 async fn count_versions_published_today(
@@ -0,0 +1,366 @@
+//! Endpoints for managing the publish policy of a crate
+
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::krate::CratePath;
+use crate::email::EmailMessage;
+use crate::middleware::real_ip::RealIp;
+use crate::models::token::EndpointScope;
+use crate::models::{Crate, CratePublishPolicy, NewCratePublishPolicy, OwnerKind, User};
+use crate::schema::{
+    crate_owners, emails, trustpub_configs_github, trustpub_configs_gitlab, users,
+};
+use crate::util::errors::{AppResult, bad_request, custom};
+use anyhow::Context;
+use axum::{Extension, Json};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use http::StatusCode;
+use http::request::Parts;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PublishPolicy {
+    /// The GitHub logins of the owners that are allowed to publish new
+    /// versions, or an empty list if all owners are allowed to publish.
+    ///
+    /// This does not apply to Trusted Publishing.
+    #[serde(default)]
+    #[schema(example = json!(["ghost"]))]
+    pub allowed_publishers: Vec<String>,
+
+    /// Whether API tokens need a crate scope that names this crate exactly.
+    ///
+    /// Tokens with wildcard crate scopes, or without any crate scopes, can't
+    /// be used for this crate if this is enabled.
+    #[serde(default)]
+    pub require_crate_scoped_tokens: bool,
+
+    /// IDs of the GitHub Trusted Publishing configs that are allowed to
+    /// publish new versions.
+    ///
+    /// If any Trusted Publishing configs are listed, new versions can only
+    /// be published via Trusted Publishing from these configs.
+    #[serde(default)]
+    #[schema(example = json!([42]))]
+    pub trustpub_github_config_ids: Vec<i32>,
+
+    /// IDs of the GitLab Trusted Publishing configs that are allowed to
+    /// publish new versions.
+    #[serde(default)]
+    #[schema(example = json!([]))]
+    pub trustpub_gitlab_config_ids: Vec<i32>,
+
+    /// The maximum age of API tokens in days, or `null` if tokens of any age
+    /// can be used.
+    #[serde(default)]
+    #[schema(example = 90)]
+    pub max_token_age_days: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PublishPolicyResponse {
+    /// The publish policy of the crate.
+    pub publish_policy: PublishPolicy,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct UpdatePublishPolicyRequest {
+    /// The new publish policy of the crate.
+    ///
+    /// The existing policy is replaced completely. Omitted fields don't
+    /// restrict publishing.
+    pub publish_policy: PublishPolicy,
+}
+
+/// The user owners of a crate, with their email addresses.
+struct UserOwner {
+    id: i32,
+    gh_login: String,
+    email: Option<String>,
+}
+
+/// Get the publish policy of a crate.
+///
+/// Only owners of the crate can see its publish policy.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/publish_policy",
+    params(CratePath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response", body = inline(PublishPolicyResponse))),
+)]
+pub async fn get_publish_policy(
+    app: AppState,
+    path: CratePath,
+    req: Parts,
+) -> AppResult<Json<PublishPolicyResponse>> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let krate = path.load_crate(&mut conn).await?;
+
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::TrustedPublishing)
+        .for_crate(&krate.name)
+        .check(&req, &mut conn)
+        .await?;
+
+    let owners = load_user_owners(&mut conn, &krate).await?;
+    if !owners.iter().any(|owner| owner.id == auth.user_id()) {
+        let msg = "only owners have permission to see the publish policy";
+        return Err(custom(StatusCode::FORBIDDEN, msg));
+    }
+
+    let policy = CratePublishPolicy::find(&mut conn, krate.id).await?;
+    let publish_policy = encode_policy(&mut conn, policy.as_ref()).await?;
+
+    Ok(Json(PublishPolicyResponse { publish_policy }))
+}
+
+/// Replace the publish policy of a crate.
+///
+/// All owners of the crate are notified when the policy changes. Changes
+/// that relax the existing policy, including removing it, require cookie
+/// authentication.
+#[utoipa::path(
+    put,
+    path = "/api/v1/crates/{name}/publish_policy",
+    params(CratePath),
+    request_body = inline(UpdatePublishPolicyRequest),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response", body = inline(PublishPolicyResponse))),
+)]
+pub async fn update_publish_policy(
+    app: AppState,
+    path: CratePath,
+    req: Parts,
+    Extension(real_ip): Extension<RealIp>,
+    Json(body): Json<UpdatePublishPolicyRequest>,
+) -> AppResult<Json<PublishPolicyResponse>> {
+    let mut conn = app.db_write().await?;
+    let krate = path.load_crate(&mut conn).await?;
+
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::TrustedPublishing)
+        .for_crate(&krate.name)
+        .check(&req, &mut conn)
+        .await?;
+
+    auth.reject_legacy_tokens()?;
+
+    let user = auth.user();
+    let owners = load_user_owners(&mut conn, &krate).await?;
+    if !owners.iter().any(|owner| owner.id == user.id) {
+        let msg = "only owners have permission to modify crate settings";
+        return Err(custom(StatusCode::FORBIDDEN, msg));
+    }
+
+    let requested = body.publish_policy;
+
+    let mut allowed_publishers = requested
+        .allowed_publishers
+        .iter()
+        .map(|login| {
+            owners
+                .iter()
+                .find(|owner| owner.gh_login.eq_ignore_ascii_case(login))
+                .map(|owner| owner.id)
+                .ok_or_else(|| bad_request(format!("`{login}` is not an owner of this crate")))
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+    allowed_publishers.sort_unstable();
+    allowed_publishers.dedup();
+
+    let mut github_config_ids = requested.trustpub_github_config_ids;
+    github_config_ids.sort_unstable();
+    github_config_ids.dedup();
+
+    let known_github_config_ids: Vec<i32> = trustpub_configs_github::table
+        .filter(trustpub_configs_github::crate_id.eq(krate.id))
+        .filter(trustpub_configs_github::id.eq_any(&github_config_ids))
+        .select(trustpub_configs_github::id)
+        .load(&mut conn)
+        .await?;
+
+    if let Some(id) = github_config_ids
+        .iter()
+        .find(|id| !known_github_config_ids.contains(id))
+    {
+        let msg = format!("GitHub Trusted Publishing config {id} does not belong to this crate");
+        return Err(bad_request(msg));
+    }
+
+    let mut gitlab_config_ids = requested.trustpub_gitlab_config_ids;
+    gitlab_config_ids.sort_unstable();
+    gitlab_config_ids.dedup();
+
+    let known_gitlab_config_ids: Vec<i32> = trustpub_configs_gitlab::table
+        .filter(trustpub_configs_gitlab::crate_id.eq(krate.id))
+        .filter(trustpub_configs_gitlab::id.eq_any(&gitlab_config_ids))
+        .select(trustpub_configs_gitlab::id)
+        .load(&mut conn)
+        .await?;
+
+    if let Some(id) = gitlab_config_ids
+        .iter()
+        .find(|id| !known_gitlab_config_ids.contains(id))
+    {
+        let msg = format!("GitLab Trusted Publishing config {id} does not belong to this crate");
+        return Err(bad_request(msg));
+    }
+
+    if requested.max_token_age_days.is_some_and(|days| days < 1) {
+        return Err(bad_request("`max_token_age_days` must be at least 1"));
+    }
+
+    let new_policy = NewCratePublishPolicy {
+        crate_id: krate.id,
+        allowed_publishers: &allowed_publishers,
+        require_crate_scoped_tokens: requested.require_crate_scoped_tokens,
+        trustpub_github_config_ids: &github_config_ids,
+        trustpub_gitlab_config_ids: &gitlab_config_ids,
+        max_token_age_days: requested.max_token_age_days,
+    };
+
+    let old_policy = CratePublishPolicy::find(&mut conn, krate.id).await?;
+
+    // A compromised API token should not be enough to get around the
+    // restrictions of the policy.
+    if auth.api_token_id().is_some()
+        && old_policy
+            .as_ref()
+            .is_some_and(|old_policy| new_policy.is_weaker_than(old_policy))
+    {
+        let msg = "publish policies can only be relaxed from the crates.io website";
+        return Err(custom(StatusCode::FORBIDDEN, msg));
+    }
+
+    let old_policy = encode_policy(&mut conn, old_policy.as_ref()).await?;
+
+    let policy = if new_policy.is_empty() {
+        CratePublishPolicy::delete(&mut conn, krate.id).await?;
+        None
+    } else {
+        Some(new_policy.upsert(&mut conn).await?)
+    };
+
+    let publish_policy = encode_policy(&mut conn, policy.as_ref()).await?;
+
+    if publish_policy != old_policy {
+        info!(
+            target: "audit",
+            action = "publish_policy_change",
+            krate.name = %krate.name,
+            network.client.ip = %*real_ip,
+            usr.id = user.id,
+            usr.name = %user.gh_login,
+            "User {} changed the publish policy of crate {} to {publish_policy:?}",
+            user.gh_login,
+            krate.name
+        );
+
+        for owner in &owners {
+            let Some(email_address) = &owner.email else {
+                continue;
+            };
+
+            let email = PublishPolicyChangedEmail {
+                recipient: &owner.gh_login,
+                auth_user: user,
+                krate: &krate,
+                publish_policy: &publish_policy,
+            };
+
+            if let Err(err) = email.send(&app, email_address).await {
+                warn!("Failed to send publish policy notification to {email_address}: {err}");
+            }
+        }
+    }
+
+    Ok(Json(PublishPolicyResponse { publish_policy }))
+}
+
+/// Loads the user owners of the crate, together with their verified email
+/// addresses.
+async fn load_user_owners(
+    conn: &mut AsyncPgConnection,
+    krate: &Crate,
+) -> QueryResult<Vec<UserOwner>> {
+    let owners: Vec<(i32, String, Option<String>)> = crate_owners::table
+        .inner_join(users::table)
+        .left_join(emails::table.on(users::id.eq(emails::user_id).and(emails::verified.eq(true))))
+        .filter(crate_owners::crate_id.eq(krate.id))
+        .filter(crate_owners::deleted.eq(false))
+        .filter(crate_owners::owner_kind.eq(OwnerKind::User))
+        .select((users::id, users::gh_login, emails::email.nullable()))
+        .load(conn)
+        .await?;
+
+    let owners = owners
+        .into_iter()
+        .map(|(id, gh_login, email)| UserOwner {
+            id,
+            gh_login,
+            email,
+        })
+        .collect();
+
+    Ok(owners)
+}
+
+async fn encode_policy(
+    conn: &mut AsyncPgConnection,
+    policy: Option<&CratePublishPolicy>,
+) -> QueryResult<PublishPolicy> {
+    let Some(policy) = policy else {
+        return Ok(PublishPolicy::default());
+    };
+
+    let allowed_publishers = users::table
+        .filter(users::id.eq_any(&policy.allowed_publishers))
+        .order(users::gh_login)
+        .select(users::gh_login)
+        .load(conn)
+        .await?;
+
+    Ok(PublishPolicy {
+        allowed_publishers,
+        require_crate_scoped_tokens: policy.require_crate_scoped_tokens,
+        trustpub_github_config_ids: policy.trustpub_github_config_ids.clone(),
+        trustpub_gitlab_config_ids: policy.trustpub_gitlab_config_ids.clone(),
+        max_token_age_days: policy.max_token_age_days,
+    })
+}
+
+#[derive(Serialize)]
+struct PublishPolicyChangedEmail<'a> {
+    /// The GitHub login of the email recipient.
+    recipient: &'a str,
+    /// The user who changed the policy.
+    auth_user: &'a User,
+    /// The crate for which the policy was changed.
+    krate: &'a Crate,
+    /// The new publish policy of the crate.
+    publish_policy: &'a PublishPolicy,
+}
+
+impl PublishPolicyChangedEmail<'_> {
+    async fn send(&self, state: &AppState, email_address: &str) -> anyhow::Result<()> {
+        let email = EmailMessage::from_template("publish_policy_changed", self);
+        let email = email.context("Failed to render email template")?;
+
+        state
+            .emails
+            .send(email_address, email)
+            .await
+            .context("Failed to send email")
+    }
+}
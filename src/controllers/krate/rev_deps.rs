@@ -1,15 +1,36 @@
 use crate::app::AppState;
 use crate::controllers::helpers::pagination::PaginationOptions;
 use crate::controllers::krate::CratePath;
-use crate::models::{CrateName, User, Version, VersionOwnerAction};
+use crate::models::{
+    CrateName, DependencyKind, ReverseDependencyFilter, ReverseDependencyRequirement, User,
+    Version, VersionOwnerAction,
+};
 use crate::util::errors::AppResult;
+use crate::util::semver::compatibility_range;
 use crate::views::{EncodableDependency, EncodableVersion};
 use axum::Json;
-use crates_io_database::schema::{crates, users, versions};
+use axum::extract::FromRequestParts;
+use axum_extra::extract::Query;
+use crates_io_database::schema::{crates, default_versions, users, versions};
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use http::request::Parts;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Deserialize, FromRequestParts, utoipa::IntoParams)]
+#[from_request(via(Query))]
+#[into_params(parameter_in = Query)]
+pub struct RevDepsQueryParams {
+    /// Only include dependents with this kind of dependency on the crate
+    /// (`normal`, `build` or `dev`).
+    #[param(value_type = Option<String>, example = "normal")]
+    kind: Option<DependencyKind>,
+
+    /// Only include dependents with (`true`) or without (`false`) an
+    /// optional dependency on the crate.
+    optional: Option<bool>,
+}
 
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct RevDepsResponse {
@@ -27,19 +48,46 @@ pub struct RevDepsResponse {
 pub struct RevDepsMeta {
     #[schema(example = 32)]
     total: i64,
+
+    /// The number of dependents whose version requirement matches the
+    /// default version of the crate.
+    #[schema(example = 24)]
+    matching_default_version: i64,
+
+    /// The number of dependents whose version requirement only matches an
+    /// older major version of the crate, grouped by major version and
+    /// sorted from newest to oldest.
+    #[schema(inline)]
+    older_majors: Vec<OlderMajor>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct OlderMajor {
+    /// The major version, following the semver compatibility rules
+    /// (e.g. `1`, `0.9` or `0.0.3`).
+    #[schema(example = "0.9")]
+    major: String,
+
+    /// The number of dependents stuck on this major version.
+    #[schema(example = 8)]
+    dependents: i64,
 }
 
 /// List reverse dependencies of a crate.
+///
+/// Each dependency is annotated with whether its version requirement matches
+/// the default version of the crate.
 #[utoipa::path(
     get,
     path = "/api/v1/crates/{name}/reverse_dependencies",
-    params(CratePath),
+    params(CratePath, RevDepsQueryParams),
     tag = "crates",
     responses((status = 200, description = "Successful Response", body = inline(RevDepsResponse))),
 )]
 pub async fn list_reverse_dependencies(
     app: AppState,
     path: CratePath,
+    params: RevDepsQueryParams,
     req: Parts,
 ) -> AppResult<Json<RevDepsResponse>> {
     let mut conn = app.db_read().await?;
@@ -50,14 +98,45 @@ pub async fn list_reverse_dependencies(
 
     let offset = pagination_options.offset().unwrap_or_default();
     let limit = pagination_options.per_page;
-    let (rev_deps, total) = krate.reverse_dependencies(&mut conn, offset, limit).await?;
+    let filter = ReverseDependencyFilter {
+        kind: params.kind,
+        optional: params.optional,
+    };
+    let rev_deps = krate
+        .reverse_dependencies(&mut conn, filter, offset, limit)
+        .await?;
 
-    let rev_deps: Vec<_> = rev_deps
+    let default_version = load_default_version(&mut conn, krate.id).await?;
+
+    let dependencies: Vec<_> = rev_deps
+        .dependencies
         .into_iter()
-        .map(|dep| EncodableDependency::from_reverse_dep(dep, &krate.name))
+        .map(|dep| {
+            let matches = matches_version(&dep.dependency.req, default_version.as_ref());
+            let mut dep = EncodableDependency::from_reverse_dep(dep, &krate.name);
+            dep.matches_default_version = Some(matches);
+            dep
+        })
+        .collect();
+
+    let crate_versions: Vec<semver::Version> = versions::table
+        .filter(versions::crate_id.eq(krate.id))
+        .filter(versions::yanked.eq(false))
+        .filter(Version::is_published())
+        .select(versions::num)
+        .load::<String>(&mut conn)
+        .await?
+        .iter()
+        .filter_map(|num| semver::Version::parse(num).ok())
         .collect();
 
-    let version_ids: Vec<i32> = rev_deps.iter().map(|dep| dep.version_id).collect();
+    let (matching_default_version, older_majors) = summarize_requirements(
+        &rev_deps.requirements,
+        default_version.as_ref(),
+        &crate_versions,
+    );
+
+    let version_ids: Vec<i32> = dependencies.iter().map(|dep| dep.version_id).collect();
 
     let versions_and_publishers: Vec<(Version, CrateName, Option<User>)> = versions::table
         .filter(versions::id.eq_any(version_ids))
@@ -83,8 +162,138 @@ pub async fn list_reverse_dependencies(
         .collect::<Vec<_>>();
 
     Ok(Json(RevDepsResponse {
-        dependencies: rev_deps,
+        dependencies,
         versions,
-        meta: RevDepsMeta { total },
+        meta: RevDepsMeta {
+            total: rev_deps.total,
+            matching_default_version,
+            older_majors,
+        },
     }))
 }
+
+async fn load_default_version(
+    conn: &mut AsyncPgConnection,
+    crate_id: i32,
+) -> QueryResult<Option<semver::Version>> {
+    let num: Option<String> = default_versions::table
+        .inner_join(versions::table.on(default_versions::version_id.eq(versions::id)))
+        .filter(default_versions::crate_id.eq(crate_id))
+        .select(versions::num)
+        .get_result(conn)
+        .await
+        .optional()?;
+
+    Ok(num.and_then(|num| semver::Version::parse(&num).ok()))
+}
+
+fn matches_version(req: &str, version: Option<&semver::Version>) -> bool {
+    let Some(version) = version else {
+        return false;
+    };
+
+    semver::VersionReq::parse(req).is_ok_and(|req| req.matches(version))
+}
+
+/// Counts the dependents whose requirement matches the default version, and
+/// the dependents that are stuck on an older major version of the crate.
+///
+/// A dependent is considered stuck on an older major version if the highest
+/// non-yanked version matching its requirement is older than the default
+/// version and not semver compatible with it.
+fn summarize_requirements(
+    requirements: &[ReverseDependencyRequirement],
+    default_version: Option<&semver::Version>,
+    versions: &[semver::Version],
+) -> (i64, Vec<OlderMajor>) {
+    let Some(default_version) = default_version else {
+        return (0, Vec::new());
+    };
+
+    let default_major = compatibility_range(default_version);
+
+    let mut matching = 0;
+    let mut older_majors = BTreeMap::new();
+    for requirement in requirements {
+        let Ok(req) = semver::VersionReq::parse(&requirement.req) else {
+            continue;
+        };
+
+        if req.matches(default_version) {
+            matching += requirement.dependents;
+            continue;
+        }
+
+        let highest_match = versions.iter().filter(|v| req.matches(v)).max();
+        if let Some(version) = highest_match {
+            let major = compatibility_range(version);
+            if version < default_version && major != default_major {
+                *older_majors.entry(major).or_default() += requirement.dependents;
+            }
+        }
+    }
+
+    let older_majors = older_majors
+        .into_iter()
+        .rev()
+        .map(|((major, minor, patch), dependents)| OlderMajor {
+            major: match (major, minor) {
+                (0, 0) => format!("0.0.{patch}"),
+                (0, _) => format!("0.{minor}"),
+                _ => major.to_string(),
+            },
+            dependents,
+        })
+        .collect();
+
+    (matching, older_majors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn requirement(req: &str, dependents: i64) -> ReverseDependencyRequirement {
+        ReverseDependencyRequirement {
+            req: req.to_string(),
+            dependents,
+        }
+    }
+
+    #[test]
+    fn test_summarize_requirements() {
+        let versions = [
+            "0.0.3", "0.9.0", "0.9.5", "1.0.0", "1.4.0", "2.0.0", "2.1.0",
+        ]
+        .iter()
+        .map(|v| semver::Version::parse(v).unwrap())
+        .collect::<Vec<_>>();
+        let default_version = semver::Version::parse("2.1.0").unwrap();
+
+        let requirements = [
+            requirement("^2", 3),
+            requirement("2.0", 1),
+            requirement("=2.0.0", 2),
+            requirement("^1.0", 4),
+            requirement("^1.2", 1),
+            requirement("0.9", 5),
+            requirement("=0.0.3", 1),
+            requirement("^3", 7),
+            requirement("not a requirement", 9),
+        ];
+
+        let (matching, older_majors) =
+            summarize_requirements(&requirements, Some(&default_version), &versions);
+        assert_eq!(matching, 4);
+
+        let older_majors = older_majors
+            .iter()
+            .map(|m| (m.major.as_str(), m.dependents))
+            .collect::<Vec<_>>();
+        assert_eq!(older_majors, [("1", 5), ("0.9", 5), ("0.0.3", 1)]);
+
+        let (matching, older_majors) = summarize_requirements(&requirements, None, &versions);
+        assert_eq!(matching, 0);
+        assert!(older_majors.is_empty());
+    }
+}
@@ -4,20 +4,22 @@ use crate::auth::AuthCheck;
 use axum::Json;
 use axum::extract::FromRequestParts;
 use axum_extra::extract::Query;
+use chrono::{DateTime, NaiveDate, Utc};
 use derive_more::Deref;
 use diesel::alias;
-use diesel::dsl::{InnerJoinQuerySource, LeftJoinQuerySource, exists};
+use diesel::dsl::{InnerJoinQuerySource, LeftJoinQuerySource, count_star, exists, not, sql};
 use diesel::prelude::*;
-use diesel::sql_types::Bool;
+use diesel::sql_types::{Array, Bool, Integer};
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use diesel_full_text_search::{configuration::TsConfigurationByName, *};
 use http::request::Parts;
 use serde::{Deserialize, Serialize};
-use tracing::{Instrument, info_span};
+use tracing::{Instrument, info_span, warn};
 use utoipa::IntoParams;
 
 use crate::app::AppState;
 use crate::controllers::helpers::Paginate;
+use crate::licenses::parse_license_expr;
 use crate::models::{Crate, CrateOwner, OwnerKind, TopVersions, Version};
 use crate::schema::*;
 use crate::util::errors::{AppResult, bad_request};
@@ -27,7 +29,7 @@ use crate::controllers::helpers::pagination::{Page, PaginationOptions, Paginatio
 use crate::models::krate::ALL_COLUMNS;
 use crate::util::RequestUtils;
 use crate::util::string_excl_null::StringExclNull;
-use crates_io_diesel_helpers::{array_agg, canon_crate_name, lower};
+use crates_io_diesel_helpers::{canon_crate_name, lower, similarity};
 
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct ListResponse {
@@ -50,6 +52,61 @@ pub struct ListMeta {
     /// Query string to the previous page of results, if any.
     #[schema(example = "?page=1")]
     prev_page: Option<String>,
+
+    /// Facet counts over all crates that match the query.
+    ///
+    /// Only included if `include_facets=yes` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(inline)]
+    facets: Option<Facets>,
+
+    /// The name of an existing crate that the search query might have been
+    /// meant to find, if the query does not match a crate name exactly.
+    ///
+    /// Only included on the first page of results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "serde")]
+    suggestion: Option<String>,
+}
+
+/// The maximum number of categories and licenses returned as facets.
+const MAX_FACET_VALUES: i64 = 10;
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct Facets {
+    /// The most common categories of the matching crates.
+    categories: Vec<FacetCount>,
+
+    /// The most common licenses of the default versions of the matching
+    /// crates.
+    licenses: Vec<FacetCount>,
+
+    /// The editions of the default versions of the matching crates.
+    editions: Vec<FacetCount>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct FacetCount {
+    /// The facet value, e.g. a category slug or a license expression.
+    #[schema(example = "MIT OR Apache-2.0")]
+    value: String,
+
+    /// The number of matching crates with this value.
+    #[schema(example = 42)]
+    count: i64,
+}
+
+impl FacetCount {
+    fn from_rows(rows: Vec<(Option<String>, i64)>) -> Vec<Self> {
+        rows.into_iter()
+            .filter_map(|(value, count)| {
+                Some(Self {
+                    value: value?,
+                    count,
+                })
+            })
+            .collect()
+    }
 }
 
 /// Returns a list of crates.
@@ -58,6 +115,9 @@ pub struct ListMeta {
 /// - Alphabetical listing of crates
 /// - List of crates under a specific owner
 /// - Listing a user's followed crates
+///
+/// All filters can be combined, in which case only crates matching all of
+/// them are returned.
 #[utoipa::path(
     get,
     path = "/api/v1/crates",
@@ -190,6 +250,7 @@ pub async fn list_crates(
         .gather(&req)?;
 
     let explicit_page = matches!(pagination.page, Page::Numeric(_));
+    let first_page = matches!(pagination.page, Page::Unspecified | Page::Numeric(1));
 
     // To avoid breaking existing users, seek-based pagination is only used if an explicit page has
     // not been provided. This way clients relying on meta.next_page will use the faster seek-based
@@ -232,11 +293,24 @@ pub async fn list_crates(
         )
     };
 
+    let facets = match filter_params.include_facets() {
+        true => Some(filter_params.load_facets(&mut conn).await?),
+        false => None,
+    };
+
+    let suggestion = match &filter_params.q_string {
+        Some(q_string) if first_page && !data.iter().any(|r| r.exact_match) => {
+            suggest_crate_name(&app, q_string, &mut conn).await?
+        }
+        _ => None,
+    };
+
     let crates = data.iter().map(|r| &r.krate).collect::<Vec<_>>();
 
     let span = info_span!("db.query", message = "SELECT ... FROM versions");
     let versions: Vec<Version> = Version::belonging_to(&crates)
         .filter(versions::yanked.eq(false))
+        .filter(Version::is_published())
         .select(Version::as_select())
         .load(&mut conn)
         .instrument(span)
@@ -268,10 +342,52 @@ pub async fn list_crates(
             total,
             next_page,
             prev_page,
+            facets,
+            suggestion,
         },
     }))
 }
 
+/// Returns the name of an existing crate that the search query might have
+/// been meant to find.
+///
+/// Popular crates that the query looks like a typo of are preferred, using
+/// the same checks as the typosquatting detection. Otherwise, the crate with
+/// the most similar name according to `pg_trgm` is returned, if any.
+async fn suggest_crate_name(
+    app: &AppState,
+    q_string: &str,
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<Option<String>> {
+    let q_string = q_string.trim();
+    if q_string.is_empty() {
+        return Ok(None);
+    }
+
+    match app.typosquat_cache(conn).await {
+        Ok(cache) => {
+            if let Some(name) = cache.suggest(q_string) {
+                return Ok(Some(name));
+            }
+        }
+        Err(error) => warn!(%error, "Failed to initialise the typosquatting cache"),
+    }
+
+    let span = info_span!("db.query", message = "SELECT name FROM crates");
+    crates::table
+        .filter(Crate::similar_name(q_string))
+        .filter(diesel::dsl::not(Crate::with_name(q_string)))
+        .select(crates::name)
+        .order((
+            similarity(canon_crate_name(crates::name), canon_crate_name(q_string)).desc(),
+            crates::name.asc(),
+        ))
+        .first::<String>(conn)
+        .instrument(span)
+        .await
+        .optional()
+}
+
 #[derive(Debug, Deserialize, FromRequestParts, IntoParams)]
 #[from_request(via(Query))]
 #[into_params(parameter_in = Query)]
@@ -285,6 +401,9 @@ pub struct ListQueryParams {
     sort: Option<String>,
 
     /// A search query string.
+    ///
+    /// If no crates match the query, crates with names similar to the query
+    /// are returned instead, to account for typos.
     #[serde(rename = "q")]
     #[param(inline)]
     q_string: Option<StringExclNull>,
@@ -309,33 +428,71 @@ pub struct ListQueryParams {
     #[param(inline)]
     keyword: Option<StringExclNull>,
 
-    /// If set, only return crates with names that start with the given letter
-    /// (ignored if `all_keywords` or `keyword` are set).
+    /// If set, only return crates with names that start with the given letter.
     #[param(inline)]
     letter: Option<StringExclNull>,
 
-    /// If set, only crates owned by the given crates.io user ID are returned
-    /// (ignored if `all_keywords`, `keyword`, or `letter` are set).
+    /// If set, only crates owned by the given crates.io user ID are returned.
     user_id: Option<i32>,
 
-    /// If set, only crates owned by the given crates.io team ID are returned
-    /// (ignored if `all_keywords`, `keyword`, `letter`, or `user_id` are set).
+    /// If set, only crates owned by the given crates.io team ID are returned.
     team_id: Option<i32>,
 
-    /// If set, only crates owned by users the current user follows are returned
-    /// (ignored if `all_keywords`, `keyword`, `letter`, `user_id`,
-    /// or `team_id` are set).
+    /// If set, only crates owned by users the current user follows are returned.
     ///
     /// The exact value of this parameter is ignored, but it must not be empty.
     #[param(example = "yes")]
     following: Option<String>,
 
-    /// If set, only crates with the specified names are returned (ignored
-    /// if `all_keywords`, `keyword`, `letter`, `user_id`, `team_id`,
-    /// or `following` are set).
+    /// If set, only crates with the specified names are returned.
     #[serde(rename = "ids[]", default)]
     #[param(inline)]
     ids: Vec<StringExclNull>,
+
+    /// If set, only return crates that can be used under the licenses in
+    /// the given SPDX expression, based on the license of their default
+    /// version.
+    ///
+    /// For example, `MIT OR Apache-2.0` matches crates licensed under
+    /// `MIT`, `Apache-2.0` or `MIT OR Apache-2.0`, but not `GPL-3.0` or
+    /// `MIT AND GPL-3.0`.
+    #[param(inline, example = "MIT OR Apache-2.0")]
+    license: Option<StringExclNull>,
+
+    /// If set, only return crates whose default version declares a
+    /// `rust-version` that is less than or equal to the given version.
+    ///
+    /// Crates without a `rust-version` are not returned.
+    #[param(inline, example = "1.70")]
+    rust_version: Option<StringExclNull>,
+
+    /// If set, only return crates whose default version uses the given
+    /// Rust edition.
+    #[param(inline, example = "2021")]
+    edition: Option<StringExclNull>,
+
+    /// If set, only return crates whose default version does (or does not)
+    /// contain a library target.
+    has_lib: Option<bool>,
+
+    /// If set, only return crates whose default version does (or does not)
+    /// contain binary targets.
+    has_bin: Option<bool>,
+
+    /// If set, only return crates whose default version contains a binary
+    /// target with exactly the given name.
+    #[param(inline, example = "rg")]
+    bin: Option<StringExclNull>,
+
+    /// If set, only return crates that have been updated since the given
+    /// date (`YYYY-MM-DD`) or RFC 3339 timestamp.
+    #[param(inline, example = "2024-01-01")]
+    updated_since: Option<StringExclNull>,
+
+    /// Set to `yes` to include facet counts of all matching crates in the
+    /// response metadata.
+    #[param(example = "yes")]
+    include_facets: Option<String>,
 }
 
 impl ListQueryParams {
@@ -343,6 +500,10 @@ impl ListQueryParams {
         let include_yanked = self.include_yanked.as_ref();
         include_yanked.map(|s| s == "yes").unwrap_or(true)
     }
+
+    pub fn include_facets(&self) -> bool {
+        self.include_facets.as_deref() == Some("yes")
+    }
 }
 
 #[derive(Deref)]
@@ -351,6 +512,12 @@ struct FilterParams {
     search_params: ListQueryParams,
     letter: Option<char>,
     auth_user_id: Option<i32>,
+    licenses: Option<Vec<String>>,
+    rust_version: Option<Vec<i32>>,
+    updated_since: Option<DateTime<Utc>>,
+    /// Whether `q` is matched by `pg_trgm` similarity instead of full-text
+    /// search, because the full-text search did not find anything.
+    fuzzy: bool,
 }
 
 impl FilterParams {
@@ -370,17 +537,112 @@ impl FilterParams {
             None => None,
         };
 
-        Ok(Self {
+        let licenses = match &search_params.license {
+            Some(license) => Some(matching_licenses(license, conn).await?),
+            None => None,
+        };
+
+        let rust_version = match &search_params.rust_version {
+            Some(rust_version) => Some(parse_rust_version(rust_version)?),
+            None => None,
+        };
+
+        let updated_since = match &search_params.updated_since {
+            Some(updated_since) => Some(parse_updated_since(updated_since)?),
+            None => None,
+        };
+
+        let mut filter_params = Self {
             search_params,
             letter,
             auth_user_id,
+            licenses,
+            rust_version,
+            updated_since,
+            fuzzy: false,
+        };
+
+        if filter_params
+            .q_string
+            .as_deref()
+            .is_some_and(|q| !q.is_empty())
+        {
+            let query = filter_params.make_query();
+            let has_matches: bool = diesel::select(exists(query)).get_result(conn).await?;
+            filter_params.fuzzy = !has_matches;
+        }
+
+        Ok(filter_params)
+    }
+}
+
+/// Returns all distinct licenses of default versions that can be used under
+/// the licenses in the given SPDX expression.
+///
+/// Evaluating license expressions is not possible in SQL, so this loads the
+/// distinct license values and evaluates them here instead.
+async fn matching_licenses(license: &str, conn: &mut AsyncPgConnection) -> AppResult<Vec<String>> {
+    let expr = parse_license_expr(license)
+        .map_err(|err| bad_request(format!("invalid `license` value: {err}")))?;
+
+    let allowed = expr.requirements().map(|r| &r.req).collect::<Vec<_>>();
+
+    let licenses: Vec<String> = default_versions::table
+        .inner_join(versions::table)
+        .filter(versions::license.is_not_null())
+        .select(versions::license.assume_not_null())
+        .distinct()
+        .load(conn)
+        .await?;
+
+    Ok(licenses
+        .into_iter()
+        .filter(|license| {
+            parse_license_expr(license)
+                .is_ok_and(|expr| expr.evaluate(|req| allowed.contains(&req)))
         })
+        .collect())
+}
+
+/// Parses a `rust_version` ceiling like `1.70` into its numeric parts,
+/// padded to `[major, minor, patch]`.
+fn parse_rust_version(rust_version: &str) -> AppResult<Vec<i32>> {
+    const ERROR: &str = "invalid `rust_version` value, expected e.g. `1.70`";
+
+    let mut parts = rust_version
+        .split('.')
+        .map(|part| part.parse::<i32>().map_err(|_| bad_request(ERROR)))
+        .collect::<AppResult<Vec<_>>>()?;
+
+    if parts.len() > 3 {
+        return Err(bad_request(ERROR));
     }
+
+    parts.resize(3, 0);
+    Ok(parts)
+}
+
+fn parse_updated_since(updated_since: &str) -> AppResult<DateTime<Utc>> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(updated_since) {
+        return Ok(date_time.to_utc());
+    }
+
+    NaiveDate::parse_from_str(updated_since, "%Y-%m-%d")
+        .map(|date| date.and_time(Default::default()).and_utc())
+        .map_err(|_| {
+            bad_request("invalid `updated_since` value, expected a date like `2024-01-01`")
+        })
 }
 
 impl FilterParams {
     fn make_query(&self) -> crates::BoxedQuery<'_, diesel::pg::Pg> {
-        let mut query = crates::table.into_boxed();
+        // Crates that are scheduled for deletion are hidden from search
+        let mut query = crates::table
+            .filter(not(exists(
+                scheduled_crate_deletions::table
+                    .filter(scheduled_crate_deletions::crate_id.eq(crates::id)),
+            )))
+            .into_boxed();
 
         if let Some(q_string) = &self.q_string
             && !q_string.is_empty()
@@ -389,10 +651,13 @@ impl FilterParams {
                 TsConfigurationByName("english"),
                 q_string.as_str(),
             );
-            query = query.filter(
-                q.matches(crates::textsearchable_index_col)
-                    .or(Crate::loosly_matches_name(q_string.as_str())),
-            );
+            query = match self.fuzzy {
+                true => query.filter(Crate::similar_name(q_string.as_str())),
+                false => query.filter(
+                    q.matches(crates::textsearchable_index_col)
+                        .or(Crate::loosly_matches_name(q_string.as_str())),
+                ),
+            };
         }
 
         if let Some(cat) = &self.category {
@@ -410,33 +675,49 @@ impl FilterParams {
             );
         }
 
+        // Aliases of merged keywords match the crates of the keyword that
+        // they were merged into.
         if let Some(kws) = &self.all_keywords {
-            let names: Vec<_> = kws
-                .split_whitespace()
-                .map(|name| name.to_lowercase())
-                .collect();
+            for name in kws.split_whitespace() {
+                let name = name.to_lowercase();
+                let aliased = keyword_aliases::table
+                    .filter(keyword_aliases::alias.eq(name.clone()))
+                    .select(keyword_aliases::keyword_id);
 
-            query = query.filter(
-                crates_keywords::table
-                    .inner_join(keywords::table)
-                    .filter(crates_keywords::crate_id.eq(crates::id))
-                    .select(array_agg(keywords::keyword))
-                    .single_value()
-                    .contains(names),
-            );
+                query = query.filter(
+                    crates::id.eq_any(
+                        crates_keywords::table
+                            .select(crates_keywords::crate_id)
+                            .inner_join(keywords::table)
+                            .filter(keywords::keyword.eq(name).or(keywords::id.eq_any(aliased))),
+                    ),
+                );
+            }
         } else if let Some(kw) = &self.keyword {
+            let aliased = keyword_aliases::table
+                .filter(keyword_aliases::alias.eq(lower(kw.as_str())))
+                .select(keyword_aliases::keyword_id);
+
             query = query.filter(
                 crates::id.eq_any(
                     crates_keywords::table
                         .select(crates_keywords::crate_id)
                         .inner_join(keywords::table)
-                        .filter(lower(keywords::keyword).eq(lower(kw.as_str()))),
+                        .filter(
+                            lower(keywords::keyword)
+                                .eq(lower(kw.as_str()))
+                                .or(keywords::id.eq_any(aliased)),
+                        ),
                 ),
             );
-        } else if let Some(letter) = self.letter {
+        }
+
+        if let Some(letter) = self.letter {
             let pattern = format!("{}%", letter.to_lowercase());
             query = query.filter(canon_crate_name(crates::name).like(pattern));
-        } else if let Some(user_id) = self.user_id {
+        }
+
+        if let Some(user_id) = self.user_id {
             query = query.filter(
                 crates::id.eq_any(
                     CrateOwner::by_owner_kind(OwnerKind::User)
@@ -444,7 +725,9 @@ impl FilterParams {
                         .filter(crate_owners::owner_id.eq(user_id)),
                 ),
             );
-        } else if let Some(team_id) = self.team_id {
+        }
+
+        if let Some(team_id) = self.team_id {
             query = query.filter(
                 crates::id.eq_any(
                     CrateOwner::by_owner_kind(OwnerKind::Team)
@@ -452,7 +735,9 @@ impl FilterParams {
                         .filter(crate_owners::owner_id.eq(team_id)),
                 ),
             );
-        } else if let Some(user_id) = self.auth_user_id {
+        }
+
+        if let Some(user_id) = self.auth_user_id {
             query = query.filter(
                 crates::id.eq_any(
                     follows::table
@@ -460,10 +745,20 @@ impl FilterParams {
                         .filter(follows::user_id.eq(user_id)),
                 ),
             );
-        } else if !self.ids.is_empty() {
+        }
+
+        if !self.ids.is_empty() {
             query = query.filter(crates::name.eq_any(self.ids.iter().map(|s| s.as_str())));
         }
 
+        if let Some(updated_since) = self.updated_since {
+            query = query.filter(crates::updated_at.ge(updated_since));
+        }
+
+        if let Some(default_version_query) = self.make_default_version_query() {
+            query = query.filter(crates::id.eq_any(default_version_query));
+        }
+
         if !self.include_yanked() {
             query = query.filter(exists(
                 versions::table
@@ -475,6 +770,105 @@ impl FilterParams {
         query
     }
 
+    /// Returns a query selecting the IDs of all crates whose default version
+    /// matches the version-based filters, or `None` if no such filter is set.
+    fn make_default_version_query(&self) -> Option<DefaultVersionQuery<'_>> {
+        let mut query = default_versions::table
+            .inner_join(versions::table)
+            .select(default_versions::crate_id)
+            .into_boxed();
+
+        let mut has_filter = false;
+
+        if let Some(licenses) = &self.licenses {
+            query = query.filter(versions::license.eq_any(licenses));
+            has_filter = true;
+        }
+
+        if let Some(rust_version) = &self.rust_version {
+            // Older versions might have been published with `rust-version`
+            // values that can not be converted to an integer array.
+            query = query.filter(
+                sql::<Bool>(
+                    "CASE WHEN versions.rust_version ~ '^\\d{1,9}(\\.\\d{1,9}){0,2}$' \
+                     THEN string_to_array(versions.rust_version, '.')::int[] <= ",
+                )
+                .bind::<Array<Integer>, _>(rust_version.clone())
+                .sql(" ELSE false END"),
+            );
+            has_filter = true;
+        }
+
+        if let Some(edition) = &self.edition {
+            query = query.filter(versions::edition.eq(edition.as_str()));
+            has_filter = true;
+        }
+
+        if let Some(has_lib) = self.has_lib {
+            query = query.filter(versions::has_lib.eq(has_lib));
+            has_filter = true;
+        }
+
+        if let Some(has_bin) = self.has_bin {
+            let operator = if has_bin { ">" } else { "=" };
+            let condition = format!("coalesce(cardinality(versions.bin_names), 0) {operator} 0");
+            query = query.filter(sql::<Bool>(&condition));
+            has_filter = true;
+        }
+
+        if let Some(bin) = &self.bin {
+            query = query.filter(versions::bin_names.contains(vec![Some(bin.as_str())]));
+            has_filter = true;
+        }
+
+        has_filter.then_some(query)
+    }
+
+    async fn load_facets(&self, conn: &mut AsyncPgConnection) -> QueryResult<Facets> {
+        let span = info_span!("db.query", message = "SELECT ... FROM crates_categories");
+        let categories = crates_categories::table
+            .inner_join(categories::table)
+            .filter(crates_categories::crate_id.eq_any(self.make_query().select(crates::id)))
+            .group_by(categories::slug)
+            .select((categories::slug.nullable(), count_star()))
+            .order((count_star().desc(), categories::slug.asc()))
+            .limit(MAX_FACET_VALUES)
+            .load(conn)
+            .instrument(span)
+            .await?;
+
+        let span = info_span!("db.query", message = "SELECT ... FROM versions");
+        let licenses = default_versions::table
+            .inner_join(versions::table)
+            .filter(default_versions::crate_id.eq_any(self.make_query().select(crates::id)))
+            .filter(versions::license.is_not_null())
+            .group_by(versions::license)
+            .select((versions::license, count_star()))
+            .order((count_star().desc(), versions::license.asc()))
+            .limit(MAX_FACET_VALUES)
+            .load(conn)
+            .instrument(span)
+            .await?;
+
+        let span = info_span!("db.query", message = "SELECT ... FROM versions");
+        let editions = default_versions::table
+            .inner_join(versions::table)
+            .filter(default_versions::crate_id.eq_any(self.make_query().select(crates::id)))
+            .filter(versions::edition.is_not_null())
+            .group_by(versions::edition)
+            .select((versions::edition, count_star()))
+            .order(versions::edition.desc())
+            .load(conn)
+            .instrument(span)
+            .await?;
+
+        Ok(Facets {
+            categories: FacetCount::from_rows(categories),
+            licenses: FacetCount::from_rows(licenses),
+            editions: FacetCount::from_rows(editions),
+        })
+    }
+
     fn seek_after(&self, seek_payload: &seek::SeekPayload) -> BoxedCondition<'_> {
         use seek::*;
 
@@ -746,6 +1140,15 @@ type QuerySource = LeftJoinQuerySource<
     diesel::dsl::Eq<default_versions::version_id, versions::id>,
 >;
 
+type DefaultVersionQuery<'a> = diesel::dsl::IntoBoxed<
+    'a,
+    diesel::dsl::Select<
+        diesel::dsl::InnerJoin<default_versions::table, versions::table>,
+        default_versions::crate_id,
+    >,
+    diesel::pg::Pg,
+>;
+
 type BoxedCondition<'a> = Box<
     dyn BoxableExpression<QuerySource, diesel::pg::Pg, SqlType = diesel::sql_types::Nullable<Bool>>
         + 'a,
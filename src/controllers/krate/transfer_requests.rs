@@ -0,0 +1,335 @@
+//! Endpoints for requesting the ownership of a crate from its current owners
+
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::helpers::authorization::Rights;
+use crate::controllers::krate::CratePath;
+use crate::models::{
+    Crate, CrateTransferAction, CrateTransferRequest, CrateTransferRequestEvent,
+    CrateTransferStatus, NewCrateTransferRequest, User,
+};
+use crate::schema::users;
+use crate::util::errors::{AppResult, bad_request, custom, not_found};
+use crate::views::{EncodableCrateTransferRequest, EncodableCrateTransferRequestEvent};
+use crate::worker::jobs;
+use axum::Json;
+use axum::extract::{FromRequestParts, Path};
+use crates_io_worker::BackgroundJob as _;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use http::StatusCode;
+use http::request::Parts;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::IntoParams;
+
+/// The maximum length of the reason for a transfer request.
+const MAX_REASON_LENGTH: usize = 2000;
+
+#[derive(Deserialize, FromRequestParts, IntoParams)]
+#[into_params(parameter_in = Path)]
+#[from_request(via(Path))]
+pub struct TransferRequestPath {
+    /// Name of the crate
+    pub name: String,
+    /// ID of the transfer request
+    pub id: i32,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct TransferRequestResponse {
+    pub transfer_request: EncodableCrateTransferRequest,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ListTransferRequestsResponse {
+    /// The transfer requests for this crate, most recent first.
+    pub transfer_requests: Vec<EncodableCrateTransferRequest>,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CreateTransferRequest {
+    /// Explanation why the crate should be transferred to you, e.g. because
+    /// it looks abandoned and you would like to maintain it.
+    #[schema(
+        example = "The crate has not been updated in five years and I would like to maintain it."
+    )]
+    reason: String,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct RespondToTransferRequest {
+    /// The response to the transfer request.
+    ///
+    /// Owners of the crate can `accept` or `reject` the request, while the
+    /// requester can `withdraw` it.
+    action: TransferRequestResponseAction,
+
+    /// Optional note explaining the response.
+    #[schema(example = json!(null))]
+    #[serde(default)]
+    note: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferRequestResponseAction {
+    Accept,
+    Reject,
+    Withdraw,
+}
+
+impl From<TransferRequestResponseAction> for CrateTransferAction {
+    fn from(action: TransferRequestResponseAction) -> Self {
+        match action {
+            TransferRequestResponseAction::Accept => CrateTransferAction::Accepted,
+            TransferRequestResponseAction::Reject => CrateTransferAction::Rejected,
+            TransferRequestResponseAction::Withdraw => CrateTransferAction::Withdrawn,
+        }
+    }
+}
+
+/// Request to become an owner of a crate.
+///
+/// The owners of the crate are notified about the request and can accept or
+/// reject it. If the owners don't respond, the crates.io team can decide to
+/// transfer the crate after a waiting period.
+#[utoipa::path(
+    post,
+    path = "/api/v1/crates/{name}/transfer_requests",
+    params(CratePath),
+    request_body = inline(CreateTransferRequest),
+    security(
+        ("cookie" = []),
+    ),
+    tag = "owners",
+    responses((status = 201, description = "Successful Response", body = inline(TransferRequestResponse))),
+)]
+pub async fn create_transfer_request(
+    app: AppState,
+    path: CratePath,
+    parts: Parts,
+    Json(body): Json<CreateTransferRequest>,
+) -> AppResult<(StatusCode, Json<TransferRequestResponse>)> {
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::only_cookie().check(&parts, &mut conn).await?;
+    let user = auth.user();
+
+    let reason = body.reason.trim();
+    if reason.is_empty() {
+        return Err(bad_request("a reason for the transfer request is required"));
+    }
+    if reason.chars().count() > MAX_REASON_LENGTH {
+        return Err(bad_request(format_args!(
+            "the reason must not be longer than {MAX_REASON_LENGTH} characters"
+        )));
+    }
+
+    let krate = path.load_crate(&mut conn).await?;
+
+    let owners = krate.owners(&mut conn).await?;
+    let encryption = &app.config.gh_token_encryption;
+    if Rights::get(user, &*app.github, &owners, encryption).await? == Rights::Full {
+        return Err(bad_request("you are already an owner of this crate"));
+    }
+
+    if user.verified_email(&mut conn).await?.is_none() {
+        return Err(bad_request(
+            "a verified email address is required to request the ownership of a crate",
+        ));
+    }
+
+    let new_request = NewCrateTransferRequest {
+        crate_id: krate.id,
+        requester_id: user.id,
+        reason,
+    };
+
+    let Some((request, event)) = new_request.create(&mut conn).await? else {
+        return Err(bad_request(
+            "you already have a pending transfer request for this crate",
+        ));
+    };
+
+    jobs::SendCrateTransferNotifications::new(&event)
+        .enqueue(&mut conn)
+        .await?;
+
+    let transfer_request = encode_requests(vec![request], &mut conn).await?.remove(0);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(TransferRequestResponse { transfer_request }),
+    ))
+}
+
+/// List the transfer requests for a crate.
+///
+/// Owners of the crate can see all transfer requests, while other users
+/// can only see their own requests.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/transfer_requests",
+    params(CratePath),
+    security(
+        ("cookie" = []),
+    ),
+    tag = "owners",
+    responses((status = 200, description = "Successful Response", body = inline(ListTransferRequestsResponse))),
+)]
+pub async fn list_transfer_requests(
+    app: AppState,
+    path: CratePath,
+    parts: Parts,
+) -> AppResult<Json<ListTransferRequestsResponse>> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let auth = AuthCheck::only_cookie().check(&parts, &mut conn).await?;
+    let user = auth.user();
+
+    let krate = path.load_crate(&mut conn).await?;
+    let is_owner = is_owner(&app, user, &krate, &mut conn).await?;
+
+    let mut requests = CrateTransferRequest::for_crate(&mut conn, krate.id).await?;
+    if !is_owner {
+        requests.retain(|request| request.requester_id == user.id);
+    }
+
+    let transfer_requests = encode_requests(requests, &mut conn).await?;
+
+    Ok(Json(ListTransferRequestsResponse { transfer_requests }))
+}
+
+/// Respond to a transfer request.
+///
+/// Owners of the crate can accept or reject a pending request. Accepting
+/// a request adds the requester as an additional owner of the crate. The
+/// requester can withdraw their own pending request.
+#[utoipa::path(
+    put,
+    path = "/api/v1/crates/{name}/transfer_requests/{id}",
+    params(TransferRequestPath),
+    request_body = inline(RespondToTransferRequest),
+    security(
+        ("cookie" = []),
+    ),
+    tag = "owners",
+    responses((status = 200, description = "Successful Response", body = inline(TransferRequestResponse))),
+)]
+pub async fn respond_to_transfer_request(
+    app: AppState,
+    path: TransferRequestPath,
+    parts: Parts,
+    Json(body): Json<RespondToTransferRequest>,
+) -> AppResult<Json<TransferRequestResponse>> {
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::only_cookie().check(&parts, &mut conn).await?;
+    let user = auth.user();
+
+    let krate = super::load_crate(&mut conn, &path.name).await?;
+
+    let request = CrateTransferRequest::find(&mut conn, path.id)
+        .await
+        .optional()?
+        .filter(|request| request.crate_id == krate.id)
+        .ok_or_else(not_found)?;
+
+    match body.action {
+        TransferRequestResponseAction::Withdraw => {
+            if request.requester_id != user.id {
+                let message = "only the requester can withdraw a transfer request";
+                return Err(custom(StatusCode::FORBIDDEN, message));
+            }
+        }
+        TransferRequestResponseAction::Accept | TransferRequestResponseAction::Reject => {
+            if !is_owner(&app, user, &krate, &mut conn).await? {
+                let message = "only owners have permission to respond to transfer requests";
+                return Err(custom(StatusCode::FORBIDDEN, message));
+            }
+        }
+    }
+
+    if request.status != CrateTransferStatus::Pending {
+        return Err(bad_request("the transfer request is not pending anymore"));
+    }
+
+    let note = body
+        .note
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+    let action = body.action.into();
+    let Some(event) = request.resolve(&mut conn, action, user.id, note).await? else {
+        return Err(bad_request("the transfer request is not pending anymore"));
+    };
+
+    jobs::SendCrateTransferNotifications::new(&event)
+        .enqueue(&mut conn)
+        .await?;
+
+    let request = CrateTransferRequest::find(&mut conn, request.id).await?;
+    let transfer_request = encode_requests(vec![request], &mut conn).await?.remove(0);
+
+    Ok(Json(TransferRequestResponse { transfer_request }))
+}
+
+/// Returns `true` if the user has full owner rights for the crate. Members of
+/// owner teams are not allowed to give away crates.
+async fn is_owner(
+    app: &AppState,
+    user: &User,
+    krate: &Crate,
+    conn: &mut AsyncPgConnection,
+) -> AppResult<bool> {
+    let owners = krate.owners(conn).await?;
+    let encryption = &app.config.gh_token_encryption;
+    Ok(Rights::get(user, &*app.github, &owners, encryption).await? == Rights::Full)
+}
+
+/// Loads the events and users of the given requests, and encodes them for
+/// the API response.
+async fn encode_requests(
+    requests: Vec<CrateTransferRequest>,
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<Vec<EncodableCrateTransferRequest>> {
+    let request_ids = requests
+        .iter()
+        .map(|request| request.id)
+        .collect::<Vec<_>>();
+    let events = CrateTransferRequestEvent::for_requests(conn, &request_ids).await?;
+
+    let user_ids = requests
+        .iter()
+        .map(|request| request.requester_id)
+        .chain(events.iter().filter_map(|event| event.actor_id))
+        .collect::<Vec<_>>();
+
+    let users = User::query()
+        .filter(users::id.eq_any(user_ids))
+        .load(conn)
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user))
+        .collect::<HashMap<_, _>>();
+
+    let mut events_by_request = HashMap::<i32, Vec<_>>::new();
+    for event in events {
+        let actor = event.actor_id.and_then(|id| users.get(&id)).cloned();
+        events_by_request
+            .entry(event.request_id)
+            .or_default()
+            .push(EncodableCrateTransferRequestEvent::from(event, actor));
+    }
+
+    let requests = requests
+        .into_iter()
+        .filter_map(|request| {
+            let requester = users.get(&request.requester_id)?.clone();
+            let events = events_by_request.remove(&request.id).unwrap_or_default();
+            Some(EncodableCrateTransferRequest::from(
+                request, requester, events,
+            ))
+        })
+        .collect();
+
+    Ok(requests)
+}
@@ -1,18 +1,20 @@
 use crate::app::AppState;
-use crate::auth::AuthCheck;
+use crate::auth::{AuthCheck, Authentication};
 use crate::controllers::krate::CratePath;
 use crate::email::EmailMessage;
 use crate::middleware::real_ip::RealIp;
 use crate::models::token::EndpointScope;
-use crate::models::{Crate, User};
+use crate::models::{Crate, CrateStatus, User};
 use crate::schema::*;
-use crate::util::errors::{AppResult, crate_not_found, custom};
+use crate::util::errors::{AppResult, bad_request, crate_not_found, custom};
 use crate::views::EncodableCrate;
+use crate::worker::jobs;
 use anyhow::Context;
 use axum::{Extension, Json};
+use crates_io_worker::BackgroundJob;
 use diesel::prelude::*;
 use diesel_async::scoped_futures::ScopedFutureExt;
-use diesel_async::{AsyncConnection, RunQueryDsl};
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
 use http::{StatusCode, request::Parts};
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
@@ -30,6 +32,31 @@ pub struct PatchRequestCrate {
     /// Whether this crate can only be published via Trusted Publishing.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub trustpub_only: Option<bool>,
+
+    /// Whether new versions of this crate have to be approved by a second
+    /// owner before they are published.
+    ///
+    /// Disabling this setting requires cookie authentication, so that it
+    /// can't be disabled with an API token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requires_publish_approval: Option<bool>,
+
+    /// The maintenance status of the crate.
+    ///
+    /// One of `active`, `passively_maintained`, `deprecated` or `archived`.
+    ///
+    /// Changing the status requires cookie authentication.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "deprecated")]
+    pub status: Option<CrateStatus>,
+
+    /// The name of the crate that replaces this crate.
+    ///
+    /// Can only be set together with the `deprecated` or `archived` status.
+    /// Setting the status without a successor removes the previous one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = json!(null))]
+    pub successor: Option<String>,
 }
 
 #[derive(Debug, Serialize, utoipa::ToSchema)]
@@ -74,20 +101,20 @@ pub async fn update_crate(
     auth.reject_legacy_tokens()?;
 
     // Update crate settings in a transaction
-    conn.transaction(|conn| {
-        update_inner(conn, &app, &krate, auth.user(), &real_ip, body).scope_boxed()
-    })
-    .await
+    conn.transaction(|conn| update_inner(conn, &app, &krate, &auth, &real_ip, body).scope_boxed())
+        .await
 }
 
 async fn update_inner(
-    conn: &mut diesel_async::AsyncPgConnection,
+    conn: &mut AsyncPgConnection,
     app: &AppState,
     krate: &Crate,
-    user: &User,
+    auth: &Authentication,
     real_ip: &RealIp,
     body: PatchRequest,
 ) -> AppResult<Json<PatchResponse>> {
+    let user = auth.user();
+
     // Query user owners to check permissions and send emails
     let user_owners = crate_owners::table
         .inner_join(users::table)
@@ -145,6 +172,112 @@ async fn update_inner(
         }
     }
 
+    // Update requires_publish_approval if provided
+    if let Some(requires_publish_approval) = body.krate.requires_publish_approval
+        && requires_publish_approval != krate.requires_publish_approval
+    {
+        // A compromised API token should not be enough to disable the
+        // protection that the setting provides.
+        if !requires_publish_approval && auth.api_token_id().is_some() {
+            let msg = "publish approval can only be disabled from the crates.io website";
+            return Err(custom(StatusCode::FORBIDDEN, msg));
+        }
+
+        if requires_publish_approval && user_owners.len() < 2 {
+            return Err(bad_request(
+                "publish approval requires the crate to have at least two owners",
+            ));
+        }
+
+        diesel::update(crates::table)
+            .filter(crates::id.eq(krate.id))
+            .set(crates::requires_publish_approval.eq(requires_publish_approval))
+            .execute(conn)
+            .await?;
+
+        info!(
+            target: "audit",
+            action = "requires_publish_approval_change",
+            krate.name = %krate.name,
+            network.client.ip = %**real_ip,
+            usr.id = user.id,
+            usr.name = %user.gh_login,
+            "User {} set requires_publish_approval={requires_publish_approval} for crate {}",
+            user.gh_login,
+            krate.name
+        );
+
+        for (_, gh_login, email_address, email_verified) in &user_owners {
+            if *email_verified {
+                let email = PublishApprovalChangedEmail {
+                    recipient: gh_login,
+                    auth_user: user,
+                    krate,
+                    requires_publish_approval,
+                };
+
+                if let Err(err) = email.send(app, email_address).await {
+                    warn!(
+                        "Failed to send requires_publish_approval notification to {email_address}: {err}"
+                    );
+                }
+            }
+        }
+    }
+
+    // Update the maintenance status if provided
+    if let Some(status) = body.krate.status {
+        // The status is shown to all users of the crate, and deprecating a
+        // crate notifies the owners of all dependent crates, so this should
+        // not be possible with a compromised API token.
+        if auth.api_token_id().is_some() {
+            let msg = "the maintenance status can only be changed from the crates.io website";
+            return Err(custom(StatusCode::FORBIDDEN, msg));
+        }
+
+        let successor = match body.krate.successor.as_deref() {
+            Some(successor) => Some(load_successor(conn, krate, status, successor).await?),
+            None => None,
+        };
+
+        if status != krate.status || successor != krate.successor {
+            diesel::update(crates::table)
+                .filter(crates::id.eq(krate.id))
+                .set((crates::status.eq(status), crates::successor.eq(&successor)))
+                .execute(conn)
+                .await?;
+
+            info!(
+                target: "audit",
+                action = "status_change",
+                krate.name = %krate.name,
+                network.client.ip = %**real_ip,
+                usr.id = user.id,
+                usr.name = %user.gh_login,
+                "User {} set status={} (successor: {successor:?}) for crate {}",
+                user.gh_login,
+                status.as_str(),
+                krate.name
+            );
+
+            // The status is included in the index, so that cargo can warn
+            // about deprecated dependencies
+            let git_index_job = jobs::SyncToGitIndex::new(&krate.name);
+            let sparse_index_job = jobs::SyncToSparseIndex::new(&krate.name);
+            tokio::try_join!(git_index_job.enqueue(conn), sparse_index_job.enqueue(conn))?;
+
+            if status == CrateStatus::Deprecated && krate.status != CrateStatus::Deprecated {
+                jobs::SendCrateDeprecationNotifications::new(krate.id)
+                    .enqueue(conn)
+                    .await?;
+            }
+        }
+    } else if body.krate.successor.is_some() {
+        return Err(bad_request(
+            "the successor can only be set together with the status",
+        ));
+    }
+
     // Reload the crate to get updated data
     let (krate, downloads, recent_downloads, default_version, yanked, num_versions): (
         Crate,
@@ -190,6 +323,33 @@ async fn update_inner(
     }))
 }
 
+/// Looks up the successor crate with the given name, and returns its
+/// canonical name.
+async fn load_successor(
+    conn: &mut AsyncPgConnection,
+    krate: &Crate,
+    status: CrateStatus,
+    name: &str,
+) -> AppResult<String> {
+    if !matches!(status, CrateStatus::Deprecated | CrateStatus::Archived) {
+        return Err(bad_request(
+            "a successor can only be set for deprecated or archived crates",
+        ));
+    }
+
+    let successor = Crate::by_name(name)
+        .first::<Crate>(conn)
+        .await
+        .optional()?
+        .ok_or_else(|| bad_request(format!("the successor crate `{name}` does not exist")))?;
+
+    if successor.id == krate.id {
+        return Err(bad_request("a crate can't be its own successor"));
+    }
+
+    Ok(successor.name)
+}
+
 #[derive(Serialize)]
 struct TrustpubOnlyChangedEmail<'a> {
     /// The GitHub login of the email recipient.
@@ -214,3 +374,28 @@ impl TrustpubOnlyChangedEmail<'_> {
             .context("Failed to send email")
     }
 }
+
+#[derive(Serialize)]
+struct PublishApprovalChangedEmail<'a> {
+    /// The GitHub login of the email recipient.
+    recipient: &'a str,
+    /// The user who changed the setting.
+    auth_user: &'a User,
+    /// The crate for which the setting was changed.
+    krate: &'a Crate,
+    /// The new value of the requires_publish_approval flag.
+    requires_publish_approval: bool,
+}
+
+impl PublishApprovalChangedEmail<'_> {
+    async fn send(&self, state: &AppState, email_address: &str) -> anyhow::Result<()> {
+        let email = EmailMessage::from_template("publish_approval_changed", self);
+        let email = email.context("Failed to render email template")?;
+
+        state
+            .emails
+            .send(email_address, email)
+            .await
+            .context("Failed to send email")
+    }
+}
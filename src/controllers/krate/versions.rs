@@ -145,6 +145,7 @@ async fn list(
     let make_base_query = || {
         let mut query = versions::table
             .filter(versions::crate_id.eq(crate_id))
+            .filter(Version::is_published())
             .left_outer_join(users::table)
             .select(<(Version, Option<User>)>::as_select())
             .into_boxed();
@@ -205,6 +206,7 @@ async fn list(
             versions::table
                 .filter(versions::crate_id.eq(crate_id))
                 .filter(not(versions::yanked))
+                .filter(Version::is_published())
                 .select(versions::num)
                 .order(versions::semver_ord.desc())
                 .load_stream::<String>(conn)
@@ -0,0 +1,353 @@
+//! Shared functionality for handling leaked token reports from secret
+//! scanners.
+//!
+//! GitHub secret scanning has its own endpoint in
+//! [`crate::controllers::github::secret_scanning`], since GitHub publishes
+//! its public keys via its API. Other scanners can report leaked tokens via
+//! the generic [`revoke`] endpoint, after their public key has been
+//! registered in the `secret_scanner_keys` table.
+
+use crate::app::AppState;
+use crate::email::EmailMessage;
+use crate::models::{ApiToken, SecretScannerKey, User};
+use crate::schema::{api_tokens, crate_owners, crates, emails};
+use crate::util::errors::{AppResult, BoxedAppError, bad_request};
+use crate::util::token::HashedToken;
+use anyhow::{Context, anyhow};
+use axum::Json;
+use axum::body::Bytes;
+use base64::{Engine, engine::general_purpose};
+use crates_io_database::models::OwnerKind;
+use crates_io_database::schema::trustpub_tokens;
+use crates_io_trustpub::access_token::AccessToken;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use futures_util::TryStreamExt;
+use http::{HeaderMap, HeaderValue};
+use minijinja::context;
+use p256::PublicKey;
+use p256::ecdsa::VerifyingKey;
+use p256::ecdsa::signature::Verifier;
+use serde::{Deserialize, Serialize};
+use serde_json as json;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::str::FromStr;
+use tracing::{debug, warn};
+
+const KEY_IDENTIFIER_HEADER: &str = "Secret-Scanning-Key-Identifier";
+const SIGNATURE_HEADER: &str = "Secret-Scanning-Signature";
+
+/// Verifies a base64-encoded ASN.1 DER ECDSA signature of the `payload`
+/// against a PEM-encoded P-256 public key.
+pub(crate) fn verify_signature(
+    public_key: &str,
+    signature: &HeaderValue,
+    payload: &[u8],
+) -> Result<(), BoxedAppError> {
+    let sig = general_purpose::STANDARD
+        .decode(signature)
+        .map_err(|e| bad_request(format!("failed to decode signature as base64: {e:?}")))?;
+    let sig = p256::ecdsa::Signature::from_der(&sig)
+        .map_err(|e| bad_request(format!("failed to parse signature from ASN.1 DER: {e:?}")))?;
+
+    let public_key =
+        PublicKey::from_str(public_key).map_err(|_| bad_request("cannot parse public key"))?;
+
+    VerifyingKey::from(public_key)
+        .verify(payload, &sig)
+        .map_err(|e| bad_request(format!("invalid signature: {e:?}")))?;
+
+    Ok(())
+}
+
+/// Verifies that the request was signed with one of the registered secret
+/// scanner keys, and returns the matching key.
+async fn verify_scanner_signature(
+    headers: &HeaderMap,
+    conn: &mut AsyncPgConnection,
+    json: &[u8],
+) -> Result<SecretScannerKey, BoxedAppError> {
+    let req_key_id = headers
+        .get(KEY_IDENTIFIER_HEADER)
+        .ok_or_else(|| bad_request(format!("missing HTTP header: {KEY_IDENTIFIER_HEADER}")))?
+        .to_str()
+        .map_err(|e| bad_request(format!("failed to decode HTTP header: {e:?}")))?;
+
+    let sig = headers
+        .get(SIGNATURE_HEADER)
+        .ok_or_else(|| bad_request(format!("missing HTTP header: {SIGNATURE_HEADER}")))?;
+
+    let Some(key) = SecretScannerKey::find(conn, req_key_id).await? else {
+        return Err(bad_request(format!("unknown key id {req_key_id}")));
+    };
+
+    verify_signature(&key.public_key, sig, json)?;
+
+    debug!(
+        key_id = %key.key_identifier, scanner = %key.scanner,
+        "Secret alert request validated",
+    );
+    Ok(key)
+}
+
+/// A leaked token that was reported by a secret scanner.
+#[derive(Deserialize, Serialize)]
+pub(crate) struct SecretAlert {
+    pub token: String,
+    pub r#type: String,
+    pub url: String,
+    pub source: String,
+}
+
+/// Revokes an API token or Trusted Publishing token and notifies the token
+/// owner that the token was reported by the given `reporter`.
+pub(crate) async fn alert_revoke_token(
+    state: &AppState,
+    alert: &SecretAlert,
+    reporter: &str,
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<SecretAlertFeedbackLabel> {
+    // First, try to handle as a Trusted Publishing token
+    if let Ok(token) = alert.token.parse::<AccessToken>() {
+        let hashed_token = token.sha256();
+
+        // Delete the token and return crate_ids for notifications
+        let crate_ids = diesel::delete(trustpub_tokens::table)
+            .filter(trustpub_tokens::hashed_token.eq(hashed_token.as_slice()))
+            .returning(trustpub_tokens::crate_ids)
+            .get_result::<Vec<Option<i32>>>(conn)
+            .await
+            .optional()?;
+
+        let Some(crate_ids) = crate_ids else {
+            debug!("Unknown Trusted Publishing token received (false positive)");
+            return Ok(SecretAlertFeedbackLabel::FalsePositive);
+        };
+
+        warn!("Active Trusted Publishing token received and revoked (true positive)");
+
+        // Send notification emails to all affected crate owners
+        let actual_crate_ids: Vec<i32> = crate_ids.into_iter().flatten().collect();
+        let result =
+            send_trustpub_notification_emails(&actual_crate_ids, alert, reporter, state, conn)
+                .await;
+        if let Err(error) = result {
+            warn!(
+                "Failed to send trusted publishing token exposure notifications for crates {actual_crate_ids:?}: {error}",
+            );
+        }
+
+        return Ok(SecretAlertFeedbackLabel::TruePositive);
+    }
+
+    // If not a Trusted Publishing token or not found, try as a regular API token
+    let hashed_token = HashedToken::hash(&alert.token);
+
+    // Not using `ApiToken::find_by_api_token()` in order to preserve `last_used_at`
+    let token = ApiToken::query()
+        .filter(api_tokens::token.eq(hashed_token))
+        .get_result(conn)
+        .await
+        .optional()?;
+
+    let Some(token) = token else {
+        debug!("Unknown token received (false positive)");
+        return Ok(SecretAlertFeedbackLabel::FalsePositive);
+    };
+
+    if token.revoked {
+        debug!(
+            token_id = %token.id, user_id = %token.user_id,
+            "Already revoked API token received (true positive)",
+        );
+        return Ok(SecretAlertFeedbackLabel::TruePositive);
+    }
+
+    diesel::update(&token)
+        .set(api_tokens::revoked.eq(true))
+        .execute(conn)
+        .await?;
+
+    warn!(
+        token_id = %token.id, user_id = %token.user_id,
+        "Active API token received and revoked (true positive)",
+    );
+
+    if let Err(error) = send_notification_email(&token, alert, reporter, state, conn).await {
+        warn!(
+            token_id = %token.id, user_id = %token.user_id, ?error,
+            "Failed to send email notification",
+        )
+    }
+
+    Ok(SecretAlertFeedbackLabel::TruePositive)
+}
+
+async fn send_notification_email(
+    token: &ApiToken,
+    alert: &SecretAlert,
+    reporter: &str,
+    state: &AppState,
+    conn: &mut AsyncPgConnection,
+) -> anyhow::Result<()> {
+    let user = User::find(conn, token.user_id)
+        .await
+        .context("Failed to find user")?;
+
+    let Some(recipient) = user.email(conn).await? else {
+        return Err(anyhow!("No address found"));
+    };
+
+    let email = EmailMessage::from_template(
+        "token_exposed",
+        context! {
+            domain => state.config.domain_name,
+            reporter,
+            source => alert.source,
+            token_name => token.name,
+            url => &alert.url,
+        },
+    )?;
+
+    state.emails.send(&recipient, email).await?;
+
+    Ok(())
+}
+
+async fn send_trustpub_notification_emails(
+    crate_ids: &[i32],
+    alert: &SecretAlert,
+    reporter: &str,
+    state: &AppState,
+    conn: &mut AsyncPgConnection,
+) -> anyhow::Result<()> {
+    // Build a mapping from crate_id to crate_name directly from the query
+    let crate_id_to_name: HashMap<i32, String> = crates::table
+        .select((crates::id, crates::name))
+        .filter(crates::id.eq_any(crate_ids))
+        .load_stream::<(i32, String)>(conn)
+        .await?
+        .try_fold(HashMap::new(), |mut map, (id, name)| {
+            map.insert(id, name);
+            std::future::ready(Ok(map))
+        })
+        .await
+        .context("Failed to query crate names")?;
+
+    // Then, get all verified owner emails for these crates
+    let owner_emails = crate_owners::table
+        .filter(crate_owners::crate_id.eq_any(crate_ids))
+        .filter(crate_owners::owner_kind.eq(OwnerKind::User)) // OwnerKind::User
+        .filter(crate_owners::deleted.eq(false))
+        .inner_join(emails::table.on(crate_owners::owner_id.eq(emails::user_id)))
+        .filter(emails::verified.eq(true))
+        .select((crate_owners::crate_id, emails::email))
+        .order((emails::email, crate_owners::crate_id))
+        .load::<(i32, String)>(conn)
+        .await
+        .context("Failed to query crate owners")?;
+
+    // Group by email address to send one notification per user
+    let mut notifications: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+    for (crate_id, email) in owner_emails {
+        if let Some(crate_name) = crate_id_to_name.get(&crate_id) {
+            notifications
+                .entry(email)
+                .or_default()
+                .insert(crate_name.clone());
+        }
+    }
+
+    // Send notifications in sorted order by email for consistent testing
+    for (email, crate_names) in notifications {
+        let message = EmailMessage::from_template(
+            "trustpub_token_exposed",
+            context! {
+                domain => state.config.domain_name,
+                reporter,
+                source => alert.source,
+                crate_names,
+                url => alert.url
+            },
+        );
+
+        let Ok(email_template) = message.inspect_err(|error| {
+            warn!(
+                %email, ?crate_names, ?error,
+                "Failed to create trusted publishing token exposure email template"
+            );
+        }) else {
+            continue;
+        };
+
+        if let Err(error) = state.emails.send(&email, email_template).await {
+            warn!(
+                %email, ?crate_names, ?error,
+                "Failed to send trusted publishing token exposure notification"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SecretAlertFeedback {
+    pub token_raw: String,
+    pub token_type: String,
+    pub label: SecretAlertFeedbackLabel,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretAlertFeedbackLabel {
+    TruePositive,
+    FalsePositive,
+}
+
+/// Parses the alerts in the request body, revokes the reported tokens and
+/// returns the feedback for the scanner.
+pub(crate) async fn handle_alerts(
+    state: &AppState,
+    body: &[u8],
+    reporter: &str,
+    conn: &mut AsyncPgConnection,
+) -> AppResult<Vec<SecretAlertFeedback>> {
+    let alerts: Vec<SecretAlert> = json::from_slice(body)
+        .map_err(|e| bad_request(format!("invalid secret alert request: {e:?}")))?;
+
+    let mut feedback = Vec::with_capacity(alerts.len());
+    for alert in alerts {
+        let label = alert_revoke_token(state, &alert, reporter, conn).await?;
+        feedback.push(SecretAlertFeedback {
+            token_raw: alert.token,
+            token_type: alert.r#type,
+            label,
+        });
+    }
+
+    Ok(feedback)
+}
+
+/// Handles the `POST /api/secret-scanning/revoke` route.
+///
+/// The request body uses the same format as GitHub secret scanning alerts.
+/// The body has to be signed with a registered secret scanner key, whose
+/// identifier is passed in the `Secret-Scanning-Key-Identifier` header, and
+/// the base64-encoded signature in the `Secret-Scanning-Signature` header.
+pub async fn revoke(
+    state: AppState,
+    headers: HeaderMap,
+    body: Bytes,
+) -> AppResult<Json<Vec<SecretAlertFeedback>>> {
+    let mut conn = state.db_write().await?;
+
+    let key = verify_scanner_signature(&headers, &mut conn, &body)
+        .await
+        .map_err(|e| bad_request(format!("failed to verify request signature: {e:?}")))?;
+
+    let feedback = handle_alerts(&state, &body, &key.scanner, &mut conn).await?;
+
+    Ok(Json(feedback))
+}
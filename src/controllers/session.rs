@@ -1,15 +1,19 @@
 use crate::app::AppState;
+use crate::controllers::user::account_recovery::{
+    RECOVERY_SESSION_KEY, unlock_self_locked_account,
+};
 use crate::email::EmailMessage;
 use crate::email::Emails;
 use crate::middleware::log_request::RequestLogExt;
-use crate::models::{NewEmail, NewOauthGithub, NewUser, User};
-use crate::schema::users;
+use crate::models::{NewEmail, NewOauthGithub, NewUser, PendingGitHubLink, User};
+use crate::schema::{api_tokens, oauth_github, pending_github_links, users};
 use crate::util::diesel::is_read_only_error;
-use crate::util::errors::{AppResult, bad_request, server_error};
+use crate::util::errors::{AppResult, BoxedAppError, bad_request, forbidden, server_error};
 use crate::util::oauth::ReqwestClient;
 use crate::views::EncodableMe;
 use axum::Json;
 use axum::extract::{FromRequestParts, Query};
+use chrono::{TimeDelta, Utc};
 use crates_io_github::GitHubUser;
 use crates_io_session::SessionExtension;
 use diesel::prelude::*;
@@ -22,6 +26,13 @@ use secrecy::ExposeSecret;
 use serde::{Deserialize, Serialize};
 use tracing::{error, warn};
 
+/// How long a different GitHub account has to wait before it replaces the
+/// previous GitHub account of a user via account recovery.
+const GITHUB_LINK_WAITING_PERIOD: TimeDelta = TimeDelta::days(7);
+
+const GITHUB_ACCOUNT_IN_USE: &str =
+    "This GitHub account is already linked to another crates.io account.";
+
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct BeginResponse {
     #[schema(
@@ -134,7 +145,16 @@ pub async fn authorize_session(
     let ghuser = app.github.current_user(token).await?;
 
     let mut conn = app.db_write().await?;
-    let user = save_user_to_database(&ghuser, &encrypted_token, &app.emails, &mut conn).await?;
+    let user = match session.remove(RECOVERY_SESSION_KEY) {
+        Some(user_id) => {
+            let user_id = user_id
+                .parse()
+                .map_err(|_| bad_request("invalid account recovery session"))?;
+
+            link_github_account(user_id, &ghuser, &encrypted_token, &app.emails, &mut conn).await?
+        }
+        None => log_in_github_user(&ghuser, &encrypted_token, &app.emails, &mut conn).await?,
+    };
 
     // Log in by setting a cookie and the middleware authentication
     session.insert("user_id".to_string(), user.id.to_string());
@@ -142,6 +162,27 @@ pub async fn authorize_session(
     super::user::me::get_authenticated_user(app, req).await
 }
 
+/// Looks up or creates the user for the GitHub account.
+///
+/// If the GitHub account is waiting to be linked to an existing user via
+/// account recovery, the login is rejected until the waiting period is over.
+/// Afterwards, the GitHub account replaces the previous GitHub account of
+/// that user.
+pub async fn log_in_github_user(
+    gh_user: &GitHubUser,
+    encrypted_token: &[u8],
+    emails: &Emails,
+    conn: &mut AsyncPgConnection,
+) -> AppResult<User> {
+    match PendingGitHubLink::find_by_gh_id(conn, gh_user.id).await? {
+        Some(link) if link.is_effective() => {
+            Ok(apply_pending_github_link(&link, gh_user, encrypted_token, conn).await?)
+        }
+        Some(link) => Err(github_link_pending(&link)),
+        None => Ok(save_user_to_database(gh_user, encrypted_token, emails, conn).await?),
+    }
+}
+
 pub async fn save_user_to_database(
     user: &GitHubUser,
     encrypted_token: &[u8],
@@ -231,6 +272,168 @@ async fn create_or_update_user(
     .await
 }
 
+/// Links the GitHub account to an existing user as part of the account
+/// recovery flow, e.g. if the previous GitHub account of the user was deleted
+/// or compromised.
+///
+/// If the user is already linked to the GitHub account, only the account
+/// details are updated, and the account is unlocked if it was locked by its
+/// owner. A different GitHub account only replaces the previous
+/// one after a waiting period, and the user is notified via their verified
+/// email address with a link to cancel the change. Until then, an error is
+/// returned and the user is not logged in.
+pub async fn link_github_account(
+    user_id: i32,
+    gh_user: &GitHubUser,
+    encrypted_token: &[u8],
+    emails: &Emails,
+    conn: &mut AsyncPgConnection,
+) -> AppResult<User> {
+    if let Some(existing_user) = find_user_by_gh_id(conn, gh_user.id).await?
+        && existing_user.id != user_id
+    {
+        return Err(bad_request(GITHUB_ACCOUNT_IN_USE));
+    }
+
+    if let Some(link) = PendingGitHubLink::find_by_gh_id(conn, gh_user.id).await?
+        && link.user_id != user_id
+    {
+        return Err(bad_request(GITHUB_ACCOUNT_IN_USE));
+    }
+
+    let user = User::find(conn, user_id).await?;
+    if user.gh_id == gh_user.id {
+        let user = conn
+            .transaction(|conn| {
+                async move {
+                    let user =
+                        update_github_account(user_id, gh_user, encrypted_token, conn).await?;
+                    unlock_self_locked_account(conn, user_id).await?;
+                    QueryResult::Ok(user)
+                }
+                .scope_boxed()
+            })
+            .await?;
+
+        return Ok(user);
+    }
+
+    let Some(recipient) = user.verified_email(conn).await? else {
+        let detail = "A verified email address is required to link a different GitHub account.";
+        return Err(bad_request(detail));
+    };
+
+    let effective_at = Utc::now() + GITHUB_LINK_WAITING_PERIOD;
+    let (link, plaintext) =
+        PendingGitHubLink::create(conn, user_id, gh_user.id, effective_at).await?;
+
+    let email = EmailMessage::from_template(
+        "github_link_pending",
+        context! {
+            user_name => user.gh_login,
+            gh_login => gh_user.login,
+            effective_at => link.effective_at.format("%F at %T UTC").to_string(),
+            domain => emails.domain,
+            token => plaintext.expose_secret(),
+        },
+    );
+
+    match email {
+        Ok(email) => {
+            if let Err(error) = emails.send(&recipient, email).await {
+                warn!("Failed to send GitHub link notification to {recipient}: {error}");
+            }
+        }
+        Err(error) => warn!("Failed to render GitHub link notification: {error}"),
+    }
+
+    Err(github_link_pending(&link))
+}
+
+fn github_link_pending(link: &PendingGitHubLink) -> BoxedAppError {
+    let effective_at = link.effective_at.format("%F at %T UTC");
+    let detail = format!(
+        "This GitHub account will be linked to your crates.io account on {effective_at}. Please log in again after that."
+    );
+    forbidden(detail)
+}
+
+/// Replaces the previous GitHub account of the user after the waiting period
+/// of the pending link is over, revokes all API tokens of the user, and
+/// unlocks the account if it was locked by its owner.
+async fn apply_pending_github_link(
+    link: &PendingGitHubLink,
+    gh_user: &GitHubUser,
+    encrypted_token: &[u8],
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<User> {
+    conn.transaction(|conn| {
+        async move {
+            diesel::delete(pending_github_links::table.find(link.user_id))
+                .execute(conn)
+                .await?;
+
+            diesel::update(api_tokens::table)
+                .filter(api_tokens::user_id.eq(link.user_id))
+                .filter(api_tokens::revoked.eq(false))
+                .set(api_tokens::revoked.eq(true))
+                .execute(conn)
+                .await?;
+
+            let user = update_github_account(link.user_id, gh_user, encrypted_token, conn).await?;
+            unlock_self_locked_account(conn, link.user_id).await?;
+
+            Ok(user)
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
+/// Saves the GitHub account details of the user, and removes any other
+/// GitHub accounts from the `oauth_github` table.
+async fn update_github_account(
+    user_id: i32,
+    gh_user: &GitHubUser,
+    encrypted_token: &[u8],
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<User> {
+    conn.transaction(|conn| {
+        async move {
+            let user = diesel::update(users::table.find(user_id))
+                .set((
+                    users::gh_id.eq(gh_user.id),
+                    users::gh_login.eq(&gh_user.login),
+                    users::gh_avatar.eq(gh_user.avatar_url.as_deref()),
+                    users::gh_encrypted_token.eq(encrypted_token),
+                ))
+                .returning(User::as_returning())
+                .get_result(conn)
+                .await?;
+
+            diesel::delete(oauth_github::table)
+                .filter(oauth_github::user_id.eq(user_id))
+                .filter(oauth_github::account_id.ne(gh_user.id as i64))
+                .execute(conn)
+                .await?;
+
+            NewOauthGithub::builder()
+                .user_id(user.id)
+                .account_id(user.gh_id as i64)
+                .encrypted_token(encrypted_token)
+                .login(&user.gh_login)
+                .maybe_avatar(user.gh_avatar.as_deref())
+                .build()
+                .insert_or_update(conn)
+                .await?;
+
+            Ok(user)
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
 async fn find_user_by_gh_id(conn: &mut AsyncPgConnection, gh_id: i32) -> QueryResult<Option<User>> {
     User::query()
         .filter(users::gh_id.eq(gh_id))
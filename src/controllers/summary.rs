@@ -160,6 +160,7 @@ fn encode_crates(
     let future = Version::query()
         .filter(versions::crate_id.eq_any(crate_ids))
         .filter(versions::yanked.eq(false))
+        .filter(Version::is_published())
         .load(conn);
 
     async move {
@@ -7,7 +7,7 @@ use anyhow::Context;
 use crate::app::AppState;
 use crate::auth::AuthCheck;
 use crate::middleware::real_ip::RealIp;
-use crate::models::token::{CrateScope, EndpointScope};
+use crate::models::token::{ApiTokenUsage, CrateScope, EndpointScope};
 use crate::util::errors::{AppResult, bad_request, custom};
 use crate::util::token::PlainToken;
 use axum::Json;
@@ -15,7 +15,7 @@ use axum::extract::{Path, Query};
 use axum::response::{IntoResponse, Response};
 use axum_extra::json;
 use axum_extra::response::ErasedJson;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeDelta, Utc};
 use diesel::data_types::PgInterval;
 use diesel::dsl::{IntervalDsl, now};
 use diesel::prelude::*;
@@ -68,6 +68,7 @@ pub async fn list_api_tokens(
     let tokens: Vec<ApiToken> = ApiToken::belonging_to(user)
         .select(ApiToken::as_select())
         .filter(api_tokens::revoked.eq(false))
+        .filter(api_tokens::parent_id.is_null())
         .filter(
             api_tokens::expired_at.is_null().or(api_tokens::expired_at
                 .assume_not_null()
@@ -150,6 +151,7 @@ pub async fn create_api_token(
 
     let max_token_per_user = 500;
     let count: i64 = ApiToken::belonging_to(user)
+        .filter(api_tokens::parent_id.is_null())
         .count()
         .get_result(&mut conn)
         .await?;
@@ -222,8 +224,14 @@ pub async fn create_api_token(
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct GetResponse {
     pub api_token: ApiToken,
+
+    /// The most recent uses of the API token, most recent first.
+    pub recent_usages: Vec<ApiTokenUsage>,
 }
 
+/// The maximum number of usage log entries returned by `find_api_token`.
+const MAX_RECENT_USAGES: i64 = 100;
+
 /// Find API token by id.
 #[utoipa::path(
     get,
@@ -252,7 +260,12 @@ pub async fn find_api_token(
         .first(&mut conn)
         .await?;
 
-    Ok(Json(GetResponse { api_token }))
+    let recent_usages = ApiTokenUsage::recent(&mut conn, api_token.id, MAX_RECENT_USAGES).await?;
+
+    Ok(Json(GetResponse {
+        api_token,
+        recent_usages,
+    }))
 }
 
 /// Revoke API token.
@@ -311,6 +324,189 @@ pub async fn revoke_current_api_token(app: AppState, req: Parts) -> AppResult<Re
     Ok(StatusCode::NO_CONTENT.into_response())
 }
 
+/// The incoming serialization format for token exchanges.
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct ExchangeApiToken {
+    /// The endpoint scopes of the new token, or `null` to use the endpoint
+    /// scopes of the refresh token.
+    #[schema(example = json!(["publish-update"]))]
+    endpoint_scopes: Option<Vec<String>>,
+
+    /// The crate scopes of the new token, or `null` to use the crate scopes
+    /// of the refresh token.
+    #[schema(example = json!(["serde"]))]
+    crate_scopes: Option<Vec<String>>,
+
+    /// The lifetime of the new token in minutes.
+    ///
+    /// Defaults to 15 minutes, and can be at most 60 minutes.
+    #[schema(example = 15)]
+    expires_in_minutes: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct ExchangeApiTokenRequest {
+    api_token: ExchangeApiToken,
+}
+
+/// The default lifetime of tokens created by `exchange_api_token`.
+const DEFAULT_EXCHANGED_TOKEN_MINUTES: i64 = 15;
+
+/// The maximum lifetime of tokens created by `exchange_api_token`.
+const MAX_EXCHANGED_TOKEN_MINUTES: i64 = 60;
+
+/// The maximum number of unexpired tokens that can be created from a single
+/// refresh token.
+const MAX_ACTIVE_EXCHANGED_TOKENS: i64 = 100;
+
+/// Exchange a refresh token for a short-lived API token.
+///
+/// The request has to be authenticated with an API token that has the
+/// `token-exchange` endpoint scope. The new token can only have a subset of
+/// the scopes of the refresh token, and stops working when the refresh token
+/// is revoked.
+#[utoipa::path(
+    post,
+    path = "/api/v1/tokens/exchange",
+    request_body = inline(ExchangeApiTokenRequest),
+    security(("api_token" = [])),
+    tag = "api_tokens",
+    responses((status = 200, description = "Successful Response", body = inline(CreateResponse))),
+)]
+pub async fn exchange_api_token(
+    app: AppState,
+    parts: Parts,
+    Json(body): Json<ExchangeApiTokenRequest>,
+) -> AppResult<Json<CreateResponse>> {
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::TokenExchange)
+        .allow_any_crate_scope()
+        .check(&parts, &mut conn)
+        .await?;
+
+    auth.reject_legacy_tokens()?;
+
+    let Some(refresh_token) = auth.api_token() else {
+        return Err(bad_request(
+            "only API tokens can be exchanged for short-lived API tokens",
+        ));
+    };
+
+    let request = body.api_token;
+
+    let refresh_endpoint_scopes = refresh_token.endpoint_scopes.as_deref().unwrap_or_default();
+    let endpoint_scopes = match request.endpoint_scopes {
+        Some(scopes) => scopes
+            .into_iter()
+            .map(|scope| EndpointScope::try_from(scope.as_bytes()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_err| bad_request("invalid endpoint scope"))?,
+        None => refresh_endpoint_scopes
+            .iter()
+            .copied()
+            .filter(|scope| *scope != EndpointScope::TokenExchange)
+            .collect(),
+    };
+
+    if endpoint_scopes.is_empty() {
+        return Err(bad_request("short-lived API tokens need an endpoint scope"));
+    }
+
+    if endpoint_scopes.contains(&EndpointScope::TokenExchange) {
+        return Err(bad_request(
+            "short-lived API tokens can't be exchanged for other API tokens",
+        ));
+    }
+
+    if !endpoint_scopes
+        .iter()
+        .all(|scope| refresh_endpoint_scopes.contains(scope))
+    {
+        return Err(bad_request(
+            "endpoint scopes must be a subset of the endpoint scopes of the refresh token",
+        ));
+    }
+
+    let crate_scopes = match request.crate_scopes {
+        Some(scopes) => Some(
+            scopes
+                .into_iter()
+                .map(CrateScope::try_from)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_err| bad_request("invalid crate scope"))?,
+        ),
+        None => refresh_token.crate_scopes.clone(),
+    };
+
+    if let Some(refresh_crate_scopes) = &refresh_token.crate_scopes
+        && !refresh_crate_scopes.is_empty()
+    {
+        let is_subset = crate_scopes.as_ref().is_some_and(|scopes| {
+            !scopes.is_empty()
+                && scopes.iter().all(|scope| {
+                    refresh_crate_scopes
+                        .iter()
+                        .any(|refresh_scope| refresh_scope.covers(scope))
+                })
+        });
+
+        if !is_subset {
+            return Err(bad_request(
+                "crate scopes must be a subset of the crate scopes of the refresh token",
+            ));
+        }
+    }
+
+    let minutes = request
+        .expires_in_minutes
+        .unwrap_or(DEFAULT_EXCHANGED_TOKEN_MINUTES);
+
+    if !(1..=MAX_EXCHANGED_TOKEN_MINUTES).contains(&minutes) {
+        return Err(bad_request(format!(
+            "`expires_in_minutes` must be between 1 and {MAX_EXCHANGED_TOKEN_MINUTES}"
+        )));
+    }
+
+    let mut expired_at = Utc::now() + TimeDelta::minutes(minutes);
+    if let Some(refresh_expired_at) = refresh_token.expired_at {
+        expired_at = expired_at.min(refresh_expired_at);
+    }
+
+    let active_tokens: i64 = api_tokens::table
+        .filter(api_tokens::parent_id.eq(refresh_token.id))
+        .filter(api_tokens::revoked.eq(false))
+        .filter(api_tokens::expired_at.gt(now))
+        .count()
+        .get_result(&mut conn)
+        .await?;
+
+    if active_tokens >= MAX_ACTIVE_EXCHANGED_TOKENS {
+        return Err(bad_request(format!(
+            "maximum active short-lived tokens per refresh token is: {MAX_ACTIVE_EXCHANGED_TOKENS}"
+        )));
+    }
+
+    let plaintext = PlainToken::generate();
+
+    let new_token = crate::models::token::NewApiToken::builder()
+        .user_id(refresh_token.user_id)
+        .name(format!("{} (short-lived)", refresh_token.name))
+        .token(plaintext.hashed())
+        .maybe_crate_scopes(crate_scopes)
+        .endpoint_scopes(endpoint_scopes)
+        .expired_at(expired_at)
+        .parent_id(refresh_token.id)
+        .build();
+
+    let api_token = EncodableApiTokenWithToken {
+        token: new_token.insert(&mut conn).await?,
+        plaintext: plaintext.expose_secret().to_string(),
+    };
+
+    Ok(Json(CreateResponse { api_token }))
+}
+
 async fn send_creation_email(
     emails: &crate::Emails,
     recipient: &str,
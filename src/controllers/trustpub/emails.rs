@@ -50,6 +50,7 @@ mod tests {
     use super::*;
     use chrono::Utc;
     use claims::assert_ok;
+    use crates_io_database::models::CrateStatus;
     use insta::assert_snapshot;
 
     fn test_user() -> User {
@@ -80,6 +81,9 @@ mod tests {
             max_upload_size: None,
             max_features: None,
             trustpub_only: false,
+            status: CrateStatus::Active,
+            successor: None,
+            requires_publish_approval: false,
         }
     }
 
@@ -6,9 +6,9 @@ use crate::controllers::trustpub::github_configs::json;
 use crate::util::errors::{AppResult, bad_request, custom, forbidden, server_error};
 use anyhow::Context;
 use axum::Json;
-use crates_io_database::models::OwnerKind;
 use crates_io_database::models::token::EndpointScope;
 use crates_io_database::models::trustpub::{GitHubConfig, NewGitHubConfig};
+use crates_io_database::models::{CratePublishPolicy, OwnerKind};
 use crates_io_database::schema::{crate_owners, emails, users};
 use crates_io_github::GitHubError;
 use crates_io_trustpub::github::validation::{
@@ -85,6 +85,22 @@ pub async fn create_trustpub_github_config(
         return Err(forbidden(message));
     }
 
+    // Trusted Publishing configs can publish new versions, so they must only
+    // be created by the owners that are allowed to publish the crate.
+    if let Some(policy) = CratePublishPolicy::find(&mut conn, krate.id).await?
+        && !policy.allows_publisher(auth_user.id)
+    {
+        let message = "The publish policy of this crate does not allow you to create Trusted Publishing configs";
+        return Err(forbidden(message));
+    }
+
+    // Otherwise a compromised API token could be used to publish versions
+    // without an uploader, which the owner of the token could then approve.
+    if krate.requires_publish_approval && auth.api_token_id().is_some() {
+        let message = "Trusted Publishing configs for crates that require publish approval can only be created on the crates.io website";
+        return Err(forbidden(message));
+    }
+
     // Lookup `repository_owner_id` via GitHub API
 
     let owner = &json_config.repository_owner;
@@ -6,9 +6,9 @@ use crate::controllers::trustpub::gitlab_configs::json;
 use crate::util::errors::{AppResult, bad_request, custom, forbidden};
 use anyhow::Context;
 use axum::Json;
-use crates_io_database::models::OwnerKind;
 use crates_io_database::models::token::EndpointScope;
 use crates_io_database::models::trustpub::{GitLabConfig, NewGitLabConfig};
+use crates_io_database::models::{CratePublishPolicy, OwnerKind};
 use crates_io_database::schema::{crate_owners, emails, users};
 use crates_io_trustpub::gitlab::validation::{
     validate_environment, validate_namespace, validate_project, validate_workflow_filepath,
@@ -83,6 +83,22 @@ pub async fn create_trustpub_gitlab_config(
         return Err(forbidden(message));
     }
 
+    // Trusted Publishing configs can publish new versions, so they must only
+    // be created by the owners that are allowed to publish the crate.
+    if let Some(policy) = CratePublishPolicy::find(&mut conn, krate.id).await?
+        && !policy.allows_publisher(auth_user.id)
+    {
+        let message = "The publish policy of this crate does not allow you to create Trusted Publishing configs";
+        return Err(forbidden(message));
+    }
+
+    // Otherwise a compromised API token could be used to publish versions
+    // without an uploader, which the owner of the token could then approve.
+    if krate.requires_publish_approval && auth.api_token_id().is_some() {
+        let message = "Trusted Publishing configs for crates that require publish approval can only be created on the crates.io website";
+        return Err(forbidden(message));
+    }
+
     // Save the new GitLab OIDC config to the database
 
     let new_config = NewGitLabConfig {
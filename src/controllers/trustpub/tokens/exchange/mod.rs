@@ -212,6 +212,11 @@ async fn handle_github_token_inner(
         .map(|config| config.crate_id)
         .collect::<Vec<_>>();
 
+    let config_ids = repo_configs
+        .iter()
+        .map(|config| config.id)
+        .collect::<Vec<_>>();
+
     let new_token = AccessToken::generate();
 
     let trustpub_data = TrustpubData::GitHub {
@@ -225,6 +230,8 @@ async fn handle_github_token_inner(
         hashed_token: &new_token.sha256(),
         crate_ids: &crate_ids,
         trustpub_data: Some(&trustpub_data),
+        github_config_ids: &config_ids,
+        gitlab_config_ids: &[],
     };
 
     new_token_model.insert(conn).await?;
@@ -375,6 +382,11 @@ async fn handle_gitlab_token_inner(
         .map(|config| config.crate_id)
         .collect::<Vec<_>>();
 
+    let config_ids = repo_configs
+        .iter()
+        .map(|config| config.id)
+        .collect::<Vec<_>>();
+
     let new_token = AccessToken::generate();
 
     let trustpub_data = TrustpubData::GitLab {
@@ -388,6 +400,8 @@ async fn handle_gitlab_token_inner(
         hashed_token: &new_token.sha256(),
         crate_ids: &crate_ids,
         trustpub_data: Some(&trustpub_data),
+        github_config_ids: &[],
+        gitlab_config_ids: &config_ids,
     };
 
     new_token_model.insert(conn).await?;
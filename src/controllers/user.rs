@@ -1,8 +1,10 @@
+pub mod account_recovery;
 pub mod email_notifications;
 pub mod email_verification;
 pub mod me;
 pub mod other;
 pub mod update;
+pub mod updates_feed;
 
 pub use email_verification::resend_email_verification;
 pub use update::update_user;
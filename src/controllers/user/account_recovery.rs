@@ -0,0 +1,278 @@
+//! Endpoints for locking and recovering user accounts.
+//!
+//! Since users can only log in via GitHub, they lose access to their
+//! crates.io account if their GitHub account is renamed, deleted or
+//! compromised. The account recovery flow sends a one-time link to the
+//! verified email address of the account. After following the link, the
+//! next GitHub login in the same session is linked to the recovered account.
+//! A different GitHub account only replaces the previous one after a waiting
+//! period, which can be cancelled via the verified email address.
+
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::helpers::OkResponse;
+use crate::email::EmailMessage;
+use crate::models::{
+    AccountLockAction, AccountLockEvent, AccountRecoveryToken, NewAccountLockEvent,
+    PendingGitHubLink, User,
+};
+use crate::schema::{account_recovery_tokens, api_tokens, emails, users};
+use crate::util::errors::{AppResult, bad_request};
+use crate::util::token::HashedToken;
+use axum::Json;
+use axum::extract::Path;
+use chrono::{TimeDelta, Utc};
+use crates_io_diesel_helpers::lower;
+use crates_io_session::SessionExtension;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use http::request::Parts;
+use minijinja::context;
+use secrecy::ExposeSecret;
+use serde::Deserialize;
+use tracing::warn;
+
+/// The session key that marks the session as recovering the account of the
+/// given user.
+pub(crate) const RECOVERY_SESSION_KEY: &str = "account_recovery_user_id";
+
+/// The lock reason shown to users that locked their own account.
+const SELF_LOCK_REASON: &str =
+    "The account was locked by its owner. It can be unlocked via account recovery.";
+
+/// How long a recovery link can be used.
+const RECOVERY_TOKEN_LIFETIME: TimeDelta = TimeDelta::hours(1);
+
+/// The minimum time between two recovery emails for the same account.
+const RECOVERY_EMAIL_INTERVAL: TimeDelta = TimeDelta::minutes(5);
+
+const INVALID_TOKEN: &str = "invalid or expired account recovery token";
+
+const INVALID_CANCEL_TOKEN: &str = "invalid or already used cancel token";
+
+/// Lock the account of the authenticated user.
+///
+/// This is an emergency measure for users that suspect that their account
+/// was compromised. All API tokens of the user are revoked and the current
+/// session is ended. The account can only be unlocked again via account
+/// recovery, which requires access to the verified email address.
+#[utoipa::path(
+    post,
+    path = "/api/v1/me/lock",
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "users",
+    responses((status = 200, description = "Successful Response", body = inline(OkResponse))),
+)]
+pub async fn lock_authenticated_user(
+    app: AppState,
+    session: SessionExtension,
+    req: Parts,
+) -> AppResult<OkResponse> {
+    let mut conn = app.db_write().await?;
+    let auth = AuthCheck::default().check(&req, &mut conn).await?;
+    let user = auth.user();
+
+    let Some(recipient) = user.verified_email(&mut conn).await? else {
+        let detail = "A verified email address is required to lock your account, since it is needed to recover the account afterwards.";
+        return Err(bad_request(detail));
+    };
+
+    conn.transaction(|conn| {
+        async move {
+            NewAccountLockEvent::builder()
+                .user_id(user.id)
+                .action(AccountLockAction::SelfLocked)
+                .actor_id(user.id)
+                .reason(SELF_LOCK_REASON)
+                .build()
+                .apply(conn)
+                .await?;
+
+            diesel::update(api_tokens::table)
+                .filter(api_tokens::user_id.eq(user.id))
+                .filter(api_tokens::revoked.eq(false))
+                .set(api_tokens::revoked.eq(true))
+                .execute(conn)
+                .await?;
+
+            Ok::<_, diesel::result::Error>(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    session.remove("user_id");
+
+    let email = EmailMessage::from_template(
+        "account_locked",
+        context! {
+            user_name => user.gh_login,
+        },
+    );
+
+    match email {
+        Ok(email) => {
+            if let Err(error) = app.emails.send(&recipient, email).await {
+                warn!("Failed to send account lock notification to {recipient}: {error}");
+            }
+        }
+        Err(error) => warn!("Failed to render account lock notification: {error}"),
+    }
+
+    Ok(OkResponse::new())
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct AccountRecoveryRequest {
+    /// The verified email address of the account.
+    #[schema(example = "ferris@example.com")]
+    email: String,
+}
+
+/// Request an account recovery email.
+///
+/// If the email address is the verified email address of an account, a
+/// one-time recovery link is sent to it. To avoid revealing which email
+/// addresses are in use, the response is the same in all cases.
+#[utoipa::path(
+    post,
+    path = "/api/private/account_recovery",
+    request_body = inline(AccountRecoveryRequest),
+    tag = "users",
+    responses((status = 200, description = "Successful Response", body = inline(OkResponse))),
+)]
+pub async fn request_account_recovery(
+    app: AppState,
+    Json(body): Json<AccountRecoveryRequest>,
+) -> AppResult<OkResponse> {
+    let mut conn = app.db_write().await?;
+
+    let recipients: Vec<(User, String)> = users::table
+        .inner_join(emails::table)
+        .filter(lower(emails::email).eq(body.email.trim().to_lowercase()))
+        .filter(emails::verified.eq(true))
+        .select((User::as_select(), emails::email))
+        .load(&mut conn)
+        .await?;
+
+    for (user, recipient) in recipients {
+        let since = Utc::now() - RECOVERY_EMAIL_INTERVAL;
+        if AccountRecoveryToken::exists_since(&mut conn, user.id, since).await? {
+            continue;
+        }
+
+        let expires_at = Utc::now() + RECOVERY_TOKEN_LIFETIME;
+        let (_, plaintext) = AccountRecoveryToken::generate(&mut conn, user.id, expires_at).await?;
+
+        let email = EmailMessage::from_template(
+            "account_recovery",
+            context! {
+                user_name => user.gh_login,
+                domain => app.emails.domain,
+                token => plaintext.expose_secret(),
+            },
+        );
+
+        match email {
+            Ok(email) => {
+                if let Err(error) = app.emails.send(&recipient, email).await {
+                    warn!("Failed to send account recovery email to {recipient}: {error}");
+                }
+            }
+            Err(error) => warn!("Failed to render account recovery email: {error}"),
+        }
+    }
+
+    Ok(OkResponse::new())
+}
+
+/// Confirm an account recovery.
+///
+/// This uses up the recovery token, and links the next GitHub login in the
+/// current session to the recovered account. If the account was locked by
+/// its owner, it is only unlocked once that GitHub account is linked, so that
+/// existing sessions of the previous GitHub account stay locked until then.
+/// Accounts that were locked by the crates.io team stay locked.
+#[utoipa::path(
+    put,
+    path = "/api/private/account_recovery/{token}",
+    params(
+        ("token" = String, Path, description = "Secret recovery token sent to the verified email address"),
+    ),
+    tag = "users",
+    responses((status = 200, description = "Successful Response", body = inline(OkResponse))),
+)]
+pub async fn confirm_account_recovery(
+    app: AppState,
+    Path(token): Path<String>,
+    session: SessionExtension,
+) -> AppResult<OkResponse> {
+    let mut conn = app.db_write().await?;
+
+    let token = HashedToken::parse(&token).map_err(|_| bad_request(INVALID_TOKEN))?;
+    let recovery_token = AccountRecoveryToken::consume(&mut conn, &token)
+        .await?
+        .ok_or_else(|| bad_request(INVALID_TOKEN))?;
+
+    let user_id = recovery_token.user_id.to_string();
+    session.insert(RECOVERY_SESSION_KEY.to_string(), user_id);
+
+    Ok(OkResponse::new())
+}
+
+/// Unlocks the account if it was locked by its owner, after a GitHub account
+/// was linked to it via account recovery.
+pub(crate) async fn unlock_self_locked_account(
+    conn: &mut AsyncPgConnection,
+    user_id: i32,
+) -> QueryResult<()> {
+    let latest_event = AccountLockEvent::latest(conn, user_id).await?;
+    if latest_event.is_some_and(|event| event.action == AccountLockAction::SelfLocked) {
+        NewAccountLockEvent::builder()
+            .user_id(user_id)
+            .action(AccountLockAction::Recovered)
+            .actor_id(user_id)
+            .reason("Unlocked via account recovery")
+            .build()
+            .apply(conn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Cancel a pending GitHub account link.
+///
+/// A different GitHub account that was linked via account recovery only
+/// replaces the previous one after a waiting period. The link can be
+/// cancelled until then with the secret token that was sent to the verified
+/// email address. This also invalidates any unused recovery tokens of the
+/// account.
+#[utoipa::path(
+    delete,
+    path = "/api/private/account_recovery/github_link/{token}",
+    params(
+        ("token" = String, Path, description = "Secret cancel token sent to the verified email address"),
+    ),
+    tag = "users",
+    responses((status = 200, description = "Successful Response", body = inline(OkResponse))),
+)]
+pub async fn cancel_github_link(app: AppState, Path(token): Path<String>) -> AppResult<OkResponse> {
+    let mut conn = app.db_write().await?;
+
+    let token = HashedToken::parse(&token).map_err(|_| bad_request(INVALID_CANCEL_TOKEN))?;
+    let link = PendingGitHubLink::cancel(&mut conn, &token)
+        .await?
+        .ok_or_else(|| bad_request(INVALID_CANCEL_TOKEN))?;
+
+    diesel::delete(account_recovery_tokens::table)
+        .filter(account_recovery_tokens::user_id.eq(link.user_id))
+        .execute(&mut conn)
+        .await?;
+
+    Ok(OkResponse::new())
+}
@@ -104,6 +104,7 @@ pub async fn get_authenticated_user_updates(
         .inner_join(crates::table)
         .left_outer_join(users::table)
         .filter(crates::id.eq_any(followed_crates))
+        .filter(Version::is_published())
         .order(versions::created_at.desc())
         .select(<(Version, CrateName, Option<User>)>::as_select())
         .pages_pagination(PaginationOptions::builder().gather(&req)?);
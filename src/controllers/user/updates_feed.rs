@@ -0,0 +1,226 @@
+//! Endpoints for the personalized updates feed of a user.
+//!
+//! The feed contains the same versions as `GET /api/v1/me/updates`, but as an
+//! RSS or Atom feed. Since feed readers can't use the session cookie, the feed
+//! is authenticated with a secret token in the URL instead, which the user can
+//! regenerate or revoke at any time.
+
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::models::{Follow, User, UserFeedToken, Version};
+use crate::schema::{crates, follows, versions};
+use crate::util::errors::{AppResult, forbidden, server_error};
+use crate::util::token::{HashedToken, PlainToken};
+use crate::worker::jobs::rss::{VersionUpdate, channel_to_atom};
+use axum::Json;
+use axum::extract::FromRequestParts;
+use axum::response::{IntoResponse, Response};
+use axum_extra::extract::Query;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use http::request::Parts;
+use http::{StatusCode, header};
+use secrecy::ExposeSecret;
+use serde::{Deserialize, Serialize};
+
+/// The number of versions to include in the feed.
+const NUM_ITEMS: i64 = 50;
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct FeedTokenResponse {
+    #[schema(inline)]
+    pub feed_token: EncodableFeedToken,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct EncodableFeedToken {
+    /// The secret feed token.
+    ///
+    /// This is the only time the token is returned by the API.
+    #[schema(example = "cioAbCdEfGhIjKlMnOpQrStUvWxYz0123456")]
+    pub token: String,
+
+    /// The date and time when the token was generated.
+    #[schema(example = "2017-01-06T14:23:11Z")]
+    pub created_at: DateTime<Utc>,
+
+    /// The URL of the RSS variant of the feed, including the token.
+    pub rss_url: String,
+
+    /// The URL of the Atom variant of the feed, including the token.
+    pub atom_url: String,
+}
+
+/// Generate a new secret token for the updates feed of the authenticated user.
+///
+/// Any previously generated feed token is revoked.
+#[utoipa::path(
+    put,
+    path = "/api/v1/me/updates/feed_token",
+    security(("cookie" = [])),
+    tag = "users",
+    responses((status = 200, description = "Successful Response", body = inline(FeedTokenResponse))),
+)]
+pub async fn regenerate_feed_token(
+    app: AppState,
+    req: Parts,
+) -> AppResult<Json<FeedTokenResponse>> {
+    let mut conn = app.db_write().await?;
+    let user_id = AuthCheck::only_cookie()
+        .check(&req, &mut conn)
+        .await?
+        .user_id();
+
+    let (feed_token, plaintext) = UserFeedToken::regenerate(&mut conn, user_id).await?;
+
+    let domain = &app.config.domain_name;
+    let feed_token = EncodableFeedToken {
+        rss_url: feed_url(domain, "rss", &plaintext),
+        atom_url: feed_url(domain, "atom", &plaintext),
+        token: plaintext.expose_secret().to_string(),
+        created_at: feed_token.created_at,
+    };
+
+    Ok(Json(FeedTokenResponse { feed_token }))
+}
+
+/// Revoke the secret token for the updates feed of the authenticated user.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/me/updates/feed_token",
+    security(("cookie" = [])),
+    tag = "users",
+    responses((status = 204, description = "Successful Response")),
+)]
+pub async fn revoke_feed_token(app: AppState, req: Parts) -> AppResult<Response> {
+    let mut conn = app.db_write().await?;
+    let user_id = AuthCheck::only_cookie()
+        .check(&req, &mut conn)
+        .await?
+        .user_id();
+
+    UserFeedToken::revoke(&mut conn, user_id).await?;
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+#[derive(Debug, Deserialize, FromRequestParts, utoipa::IntoParams)]
+#[from_request(via(Query))]
+#[into_params(parameter_in = Query)]
+pub struct FeedQueryParams {
+    /// The secret feed token of the user.
+    token: String,
+}
+
+/// Get the updates feed of a user as RSS.
+///
+/// The feed contains the latest versions of the crates that the user
+/// follows. It is authenticated with the secret feed token of the user.
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/updates/rss",
+    params(FeedQueryParams),
+    tag = "versions",
+    responses((status = 200, description = "Successful Response", content_type = "text/xml")),
+)]
+pub async fn get_updates_rss_feed(app: AppState, params: FeedQueryParams) -> AppResult<Response> {
+    let channel = load_updates_channel(&app, &params.token).await?;
+
+    let buffer = channel
+        .pretty_write_to(Vec::new(), b' ', 4)
+        .map_err(server_error)?;
+
+    let content_type = "text/xml; charset=UTF-8";
+    Ok(([(header::CONTENT_TYPE, content_type)], buffer).into_response())
+}
+
+/// Get the updates feed of a user as Atom.
+///
+/// The feed contains the latest versions of the crates that the user
+/// follows. It is authenticated with the secret feed token of the user.
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/updates/atom",
+    params(FeedQueryParams),
+    tag = "versions",
+    responses((status = 200, description = "Successful Response", content_type = "application/atom+xml")),
+)]
+pub async fn get_updates_atom_feed(app: AppState, params: FeedQueryParams) -> AppResult<Response> {
+    let channel = load_updates_channel(&app, &params.token).await?;
+
+    let self_url = format!(
+        "https://{}/api/v1/me/updates/atom?token={}",
+        app.config.domain_name, params.token
+    );
+    let feed = channel_to_atom(&channel, self_url);
+
+    let config = atom_syndication::WriteConfig {
+        write_document_declaration: true,
+        indent_size: Some(4),
+    };
+    let buffer = feed
+        .write_with_config(Vec::new(), config)
+        .map_err(server_error)?;
+
+    let content_type = "application/atom+xml; charset=UTF-8";
+    Ok(([(header::CONTENT_TYPE, content_type)], buffer).into_response())
+}
+
+fn feed_url(domain: &str, format: &str, token: &PlainToken) -> String {
+    let token = token.expose_secret();
+    format!("https://{domain}/api/v1/me/updates/{format}?token={token}")
+}
+
+/// Looks up the user that the feed token belongs to and builds the RSS
+/// channel with the latest versions of the crates they follow.
+async fn load_updates_channel(app: &AppState, token: &str) -> AppResult<rss::Channel> {
+    const INVALID_TOKEN: &str = "invalid or revoked feed token";
+
+    let token = HashedToken::parse(token).map_err(|_| forbidden(INVALID_TOKEN))?;
+
+    let mut conn = app.db_read_prefer_primary().await?;
+    let feed_token = UserFeedToken::find_by_token(&mut conn, &token).await?;
+    let feed_token = feed_token.ok_or_else(|| forbidden(INVALID_TOKEN))?;
+
+    let user = User::find(&mut conn, feed_token.user_id).await?;
+    let version_updates = load_version_updates(&user, &mut conn).await?;
+
+    let domain = &app.config.domain_name;
+    let items = version_updates
+        .into_iter()
+        .map(|u| u.into_rss_item(domain))
+        .collect();
+
+    let namespaces = vec![("crates".to_string(), "https://crates.io/".to_string())];
+    let namespaces = namespaces.into_iter().collect();
+
+    let login = &user.gh_login;
+    Ok(rss::Channel {
+        title: format!("crates.io: updates of crates followed by {login}"),
+        link: format!("https://{domain}/dashboard"),
+        description: format!(
+            "Recent releases of crates followed by {login} on the crates.io package registry"
+        ),
+        language: Some("en".to_string()),
+        namespaces,
+        items,
+        ..Default::default()
+    })
+}
+
+/// Loads the latest versions of the crates that the user follows.
+async fn load_version_updates(
+    user: &User,
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<Vec<VersionUpdate>> {
+    let followed_crates = Follow::belonging_to(user).select(follows::crate_id);
+
+    VersionUpdate::query()
+        .filter(crates::id.eq_any(followed_crates))
+        .filter(Version::is_published())
+        .order(versions::created_at.desc())
+        .limit(NUM_ITEMS)
+        .load(conn)
+        .await
+}
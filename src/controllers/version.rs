@@ -1,9 +1,12 @@
+pub mod approval;
 pub mod authors;
 pub mod dependencies;
+pub mod dependency_tree;
 pub mod docs;
 pub mod downloads;
 pub mod metadata;
 pub mod readme;
+pub mod semver_check;
 pub mod update;
 pub mod yank;
 
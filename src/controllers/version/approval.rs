@@ -0,0 +1,244 @@
+//! Endpoints for approving and rejecting versions of crates that require
+//! publish approval
+
+use super::CrateVersionPath;
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::helpers::OkResponse;
+use crate::controllers::helpers::authorization::Rights;
+use crate::controllers::krate::CratePath;
+use crate::controllers::krate::publish::enqueue_publish_jobs;
+use crate::models::token::EndpointScope;
+use crate::models::{
+    NewVersionOwnerAction, OwnerKind, PendingVersion, User, Version, VersionAction,
+};
+use crate::schema::{crate_owners, pending_versions, users, versions};
+use crate::util::errors::{AppResult, bad_request, custom, internal};
+use crate::views::EncodablePublicUser;
+use crate::worker::jobs::{ExpirePendingVersions, UpdateDefaultVersion};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use http::StatusCode;
+use http::request::Parts;
+use serde::Serialize;
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ListPendingVersionsResponse {
+    /// The versions that are waiting for approval, oldest first.
+    #[schema(inline)]
+    pub pending_versions: Vec<EncodablePendingVersion>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct EncodablePendingVersion {
+    /// The version number.
+    #[schema(example = "1.0.0")]
+    pub num: String,
+
+    /// The user who uploaded the version, or `null` if it was uploaded
+    /// via Trusted Publishing.
+    pub published_by: Option<EncodablePublicUser>,
+
+    /// The date and time when the version was uploaded.
+    #[schema(example = "2019-12-13T13:46:41Z")]
+    pub created_at: DateTime<Utc>,
+
+    /// The date and time when the version will be deleted if it has not
+    /// been approved.
+    #[schema(example = "2019-12-16T13:46:41Z")]
+    pub expires_at: DateTime<Utc>,
+}
+
+/// List the versions of a crate that are waiting for approval.
+///
+/// Versions of crates that require publish approval are only published once
+/// a second owner approved them.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/pending_versions",
+    params(CratePath),
+    tag = "versions",
+    responses((status = 200, description = "Successful Response", body = inline(ListPendingVersionsResponse))),
+)]
+pub async fn list_pending_versions(
+    state: AppState,
+    path: CratePath,
+) -> AppResult<Json<ListPendingVersionsResponse>> {
+    let mut conn = state.db_read_prefer_primary().await?;
+    let krate = path.load_crate(&mut conn).await?;
+
+    let pending_versions: Vec<(PendingVersion, String, Option<User>)> = pending_versions::table
+        .inner_join(versions::table)
+        .left_join(users::table.on(versions::published_by.eq(users::id.nullable())))
+        .filter(versions::crate_id.eq(krate.id))
+        .order(pending_versions::created_at.asc())
+        .select((
+            PendingVersion::as_select(),
+            versions::num,
+            Option::<User>::as_select(),
+        ))
+        .load(&mut conn)
+        .await?;
+
+    let pending_versions = pending_versions
+        .into_iter()
+        .map(|(pending, num, published_by)| EncodablePendingVersion {
+            num,
+            published_by: published_by.map(Into::into),
+            created_at: pending.created_at,
+            expires_at: pending.expires_at,
+        })
+        .collect();
+
+    Ok(Json(ListPendingVersionsResponse { pending_versions }))
+}
+
+/// Approve a crate version that is waiting for approval.
+///
+/// The version is published once it is approved. It can only be approved by
+/// an owner who did not upload it, and who was already an owner of the crate
+/// when it was uploaded. Approvals require cookie authentication, so that a
+/// single compromised API token can't be used to both upload and approve a
+/// version.
+#[utoipa::path(
+    put,
+    path = "/api/v1/crates/{name}/{version}/approve",
+    params(CrateVersionPath),
+    security(("cookie" = [])),
+    tag = "versions",
+    responses((status = 200, description = "Successful Response", body = inline(OkResponse))),
+)]
+pub async fn approve_version(
+    state: AppState,
+    path: CrateVersionPath,
+    req: Parts,
+) -> AppResult<OkResponse> {
+    let mut conn = state.db_write().await?;
+    let (version, krate) = path.load_version_and_crate(&mut conn).await?;
+
+    let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+
+    let user = auth.user();
+    let owners = krate.owners(&mut conn).await?;
+    let encryption = &state.config.gh_token_encryption;
+    if Rights::get(user, &*state.github, &owners, encryption).await? < Rights::Full {
+        let msg = "only owners have permission to approve versions";
+        return Err(custom(StatusCode::FORBIDDEN, msg));
+    }
+
+    let pending_version = load_pending_version(&mut conn, &version).await?;
+
+    if version.published_by == Some(user.id) {
+        let msg = "versions have to be approved by an owner who did not upload them";
+        return Err(custom(StatusCode::FORBIDDEN, msg));
+    }
+
+    // Owners who were added after the version was uploaded could have been
+    // invited by whoever uploaded it, so they can't approve it.
+    let owner_since: DateTime<Utc> = crate_owners::table
+        .filter(crate_owners::crate_id.eq(krate.id))
+        .filter(crate_owners::owner_id.eq(user.id))
+        .filter(crate_owners::owner_kind.eq(OwnerKind::User))
+        .filter(crate_owners::deleted.eq(false))
+        .select(crate_owners::created_at)
+        .first(&mut conn)
+        .await?;
+
+    if owner_since > pending_version.created_at {
+        let msg = "versions can only be approved by owners who were already owners when the version was uploaded";
+        return Err(custom(StatusCode::FORBIDDEN, msg));
+    }
+
+    let user_id = user.id;
+
+    conn.transaction(|conn| {
+        async move {
+            if !PendingVersion::approve(conn, version.id).await? {
+                return Err(bad_request("this version is not waiting for approval"));
+            }
+
+            NewVersionOwnerAction::builder()
+                .version_id(version.id)
+                .user_id(user_id)
+                .action(VersionAction::Approve)
+                .build()
+                .insert(conn)
+                .await?;
+
+            UpdateDefaultVersion::new(krate.id).enqueue(conn).await?;
+            enqueue_publish_jobs(conn, &krate, version.id, false).await?;
+
+            state
+                .storage
+                .publish_pending_crate_file(&krate.name, &version.num)
+                .await
+                .map_err(|e| internal(format!("failed to publish crate file: {e}")))?;
+
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    Ok(OkResponse::new())
+}
+
+/// Reject a crate version that is waiting for approval.
+///
+/// Rejected versions are deleted.
+#[utoipa::path(
+    put,
+    path = "/api/v1/crates/{name}/{version}/reject",
+    params(CrateVersionPath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "versions",
+    responses((status = 200, description = "Successful Response", body = inline(OkResponse))),
+)]
+pub async fn reject_version(
+    state: AppState,
+    path: CrateVersionPath,
+    req: Parts,
+) -> AppResult<OkResponse> {
+    let mut conn = state.db_write().await?;
+    let (version, krate) = path.load_version_and_crate(&mut conn).await?;
+
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::PublishUpdate)
+        .for_crate(&krate.name)
+        .check(&req, &mut conn)
+        .await?;
+
+    let user = auth.user();
+    let owners = krate.owners(&mut conn).await?;
+    let encryption = &state.config.gh_token_encryption;
+    if Rights::get(user, &*state.github, &owners, encryption).await? < Rights::Full {
+        let msg = "only owners have permission to reject versions";
+        return Err(custom(StatusCode::FORBIDDEN, msg));
+    }
+
+    load_pending_version(&mut conn, &version).await?;
+
+    if !PendingVersion::reject(&mut conn, version.id).await? {
+        return Err(bad_request("this version is not waiting for approval"));
+    }
+
+    ExpirePendingVersions.enqueue(&mut conn).await?;
+
+    Ok(OkResponse::new())
+}
+
+async fn load_pending_version(
+    conn: &mut AsyncPgConnection,
+    version: &Version,
+) -> AppResult<PendingVersion> {
+    PendingVersion::find(conn, version.id)
+        .await?
+        .ok_or_else(|| bad_request("this version is not waiting for approval"))
+}
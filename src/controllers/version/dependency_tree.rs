@@ -0,0 +1,787 @@
+//! Endpoint for resolving the full transitive dependency tree of a version.
+//!
+//! The resolution is a simplified version of what cargo does when it
+//! generates a `Cargo.lock` file: every dependency requirement is resolved to
+//! the highest non-yanked version that matches it, unless a version of the
+//! same crate that matches the requirement was already selected. Features are
+//! unified per selected version, and optional dependencies are only included
+//! if they are enabled by a feature. Dev-dependencies are never included.
+
+use super::CrateVersionPath;
+use crate::app::AppState;
+use crate::licenses::parse_license_expr;
+use crate::models::{Dependency, DependencyKind, Version};
+use crate::schema::{crates, dependencies, versions};
+use crate::util::errors::{AppResult, bad_request};
+use axum::Json;
+use axum::extract::FromRequestParts;
+use axum_extra::extract::Query;
+use cfg_expr::targets::{TargetInfo, get_builtin_target_by_triple};
+use cfg_expr::{Expression, Predicate};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use semver::VersionReq;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+/// The maximum number of crate versions in a resolved dependency tree.
+///
+/// This protects the server from spending too much time on resolving
+/// pathologically large dependency trees.
+const MAX_PACKAGES: usize = 1000;
+
+#[derive(Debug, Deserialize, FromRequestParts, utoipa::IntoParams)]
+#[from_request(via(Query))]
+#[into_params(parameter_in = Query)]
+pub struct DependencyTreeQueryParams {
+    /// Comma-separated list of features to enable on the requested version.
+    features: Option<String>,
+
+    /// Whether to enable the default features of the requested version.
+    ///
+    /// Defaults to `true`.
+    default_features: Option<bool>,
+
+    /// The target triple to resolve platform-specific dependencies for,
+    /// e.g. `x86_64-unknown-linux-gnu`.
+    ///
+    /// Defaults to including the dependencies of all platforms.
+    target: Option<String>,
+
+    /// Comma-separated list of SPDX license identifiers that are acceptable,
+    /// e.g. `MIT,Apache-2.0`.
+    ///
+    /// Crates with a license expression that can't be satisfied by these
+    /// licenses are reported as license-incompatible. Crates without a valid
+    /// SPDX license expression are always reported.
+    licenses: Option<String>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct DependencyTreeResponse {
+    /// The crate versions in the dependency tree, starting with the
+    /// requested version.
+    pub packages: Vec<ResolvedPackage>,
+
+    #[schema(inline)]
+    pub meta: DependencyTreeMeta,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ResolvedPackage {
+    /// The name of the crate.
+    #[schema(example = "serde")]
+    pub name: String,
+
+    /// The selected version of the crate.
+    #[schema(example = "1.0.210")]
+    pub version: String,
+
+    /// The license expression of the version.
+    #[schema(example = "MIT OR Apache-2.0")]
+    pub license: Option<String>,
+
+    /// Whether the version has been yanked.
+    pub yanked: bool,
+
+    /// The features that are enabled on the version.
+    #[schema(example = json!(["default", "std"]))]
+    pub features: Vec<String>,
+
+    /// The dependencies of the version that are part of the tree.
+    pub dependencies: Vec<ResolvedDependency>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ResolvedDependency {
+    /// The name of the dependency.
+    #[schema(example = "serde_derive")]
+    pub name: String,
+
+    /// The selected version of the dependency.
+    #[schema(example = "1.0.210")]
+    pub version: String,
+
+    /// The kind of the dependency (normal or build).
+    #[schema(value_type = String, example = "normal")]
+    pub kind: DependencyKind,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct DependencyTreeMeta {
+    /// The number of crate versions in the tree, excluding the requested
+    /// version itself.
+    #[schema(example = 42)]
+    pub total: usize,
+
+    /// Crates that appear with more than one version in the tree.
+    pub duplicates: Vec<DuplicateCrate>,
+
+    /// Yanked crate versions in the tree.
+    pub yanked: Vec<PackageRef>,
+
+    /// Crate versions in the tree with a license that is not acceptable.
+    pub license_incompatible: Vec<PackageRef>,
+
+    /// Dependency requirements that could not be resolved to any
+    /// non-yanked version.
+    pub unresolved: Vec<UnresolvedDependency>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct DuplicateCrate {
+    /// The name of the crate.
+    #[schema(example = "syn")]
+    pub name: String,
+
+    /// The versions of the crate in the tree.
+    #[schema(example = json!(["1.0.109", "2.0.77"]))]
+    pub versions: Vec<String>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct PackageRef {
+    /// The name of the crate.
+    #[schema(example = "serde")]
+    pub name: String,
+
+    /// The version of the crate.
+    #[schema(example = "1.0.210")]
+    pub version: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct UnresolvedDependency {
+    /// The name of the dependency.
+    #[schema(example = "serde")]
+    pub name: String,
+
+    /// The version requirement that could not be resolved.
+    #[schema(example = "^2")]
+    pub req: String,
+
+    /// The crate version that declares the dependency.
+    pub required_by: String,
+}
+
+/// Resolve the dependency tree of a crate version.
+///
+/// The tree is resolved from the dependencies and features that were
+/// published for each version, similar to how cargo generates a
+/// `Cargo.lock` file. Dev-dependencies are not included.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/{version}/dependency_tree",
+    params(CrateVersionPath, DependencyTreeQueryParams),
+    tag = "versions",
+    responses((status = 200, description = "Successful Response", body = inline(DependencyTreeResponse))),
+)]
+pub async fn get_version_dependency_tree(
+    state: AppState,
+    path: CrateVersionPath,
+    params: DependencyTreeQueryParams,
+) -> AppResult<Json<DependencyTreeResponse>> {
+    let target = match params.target.as_deref() {
+        Some(triple) => Some(
+            get_builtin_target_by_triple(triple)
+                .ok_or_else(|| bad_request(format!("unknown target `{triple}`")))?,
+        ),
+        None => None,
+    };
+
+    let allowed_licenses = params.licenses.as_deref().map(split_list);
+
+    let mut conn = state.db_read().await?;
+    let (version, krate) = path.load_version_and_crate(&mut conn).await?;
+
+    let mut root_features = params
+        .features
+        .as_deref()
+        .map(split_list)
+        .unwrap_or_default();
+    if params.default_features.unwrap_or(true) {
+        root_features.push("default".to_string());
+    }
+
+    let mut resolver = Resolver::new(&mut conn, target);
+    let root = resolver.add_root(version, krate.name).await?;
+    root.validate_features(&root_features)?;
+    resolver.resolve(root_features).await?;
+
+    Ok(Json(resolver.into_response(allowed_licenses.as_deref())))
+}
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// A version of a crate that could be selected by the resolver.
+#[derive(Clone)]
+struct Candidate {
+    id: i32,
+    num: String,
+    semver: semver::Version,
+    yanked: bool,
+    license: Option<String>,
+    features: BTreeMap<String, Vec<String>>,
+}
+
+impl Candidate {
+    fn new(
+        id: i32,
+        num: String,
+        yanked: bool,
+        license: Option<String>,
+        features: serde_json::Value,
+    ) -> Self {
+        Self {
+            id,
+            semver: semver::Version::parse(&num).unwrap_or(semver::Version::new(0, 0, 0)),
+            num,
+            yanked,
+            license,
+            features: serde_json::from_value(features).unwrap_or_default(),
+        }
+    }
+}
+
+impl From<Version> for Candidate {
+    fn from(version: Version) -> Self {
+        let Version {
+            id,
+            num,
+            yanked,
+            license,
+            features,
+            ..
+        } = version;
+
+        Self::new(id, num, yanked, license, features)
+    }
+}
+
+/// A dependency of a selected version that needs to be resolved.
+struct DependencyRequest {
+    crate_id: i32,
+    crate_name: String,
+    req: String,
+    kind: DependencyKind,
+    features: Vec<String>,
+}
+
+/// A crate version that was selected by the resolver.
+struct Package {
+    crate_id: i32,
+    name: String,
+    candidate: Candidate,
+    dependencies: Vec<(Dependency, String)>,
+    /// Whether [`Self::dependencies`] was loaded from the database yet.
+    dependencies_loaded: bool,
+    /// The enabled features of this version.
+    features: BTreeSet<String>,
+    /// The names of the enabled optional dependencies.
+    enabled_dependencies: BTreeSet<String>,
+    /// Additional features that are enabled on dependencies via
+    /// `dep/feature` entries in the feature map.
+    dependency_features: BTreeMap<String, BTreeSet<String>>,
+    /// The selected versions of the dependencies, as indices into
+    /// [`Resolver::packages`].
+    edges: Vec<(usize, DependencyKind)>,
+    activated: bool,
+}
+
+impl Package {
+    fn is_optional_dependency(&self, name: &str) -> bool {
+        self.dependencies.iter().any(|(dep, crate_name)| {
+            dep.optional && dep.explicit_name.as_deref().unwrap_or(crate_name) == name
+        })
+    }
+
+    fn validate_features(&self, features: &[String]) -> AppResult<()> {
+        let unknown_feature = features.iter().find(|feature| {
+            *feature != "default"
+                && !feature.contains('/')
+                && !self.candidate.features.contains_key(*feature)
+                && !self.is_optional_dependency(feature)
+        });
+
+        match unknown_feature {
+            Some(feature) => Err(bad_request(format!("unknown feature `{feature}`"))),
+            None => Ok(()),
+        }
+    }
+
+    /// Enables the given features and everything they imply.
+    ///
+    /// Returns `true` if anything changed.
+    fn enable_features(&mut self, features: Vec<String>) -> bool {
+        let mut changed = false;
+
+        let mut stack = features;
+        while let Some(feature) = stack.pop() {
+            if let Some(dependency) = feature.strip_prefix("dep:") {
+                changed |= self.enabled_dependencies.insert(dependency.to_string());
+            } else if let Some((dependency, dependency_feature)) = feature.split_once('/') {
+                // `dep?/feature` only enables the feature if the optional
+                // dependency is enabled by something else.
+                let (dependency, weak) = match dependency.strip_suffix('?') {
+                    Some(dependency) => (dependency, true),
+                    None => (dependency, false),
+                };
+                if !weak {
+                    changed |= self.enabled_dependencies.insert(dependency.to_string());
+                }
+                changed |= self
+                    .dependency_features
+                    .entry(dependency.to_string())
+                    .or_default()
+                    .insert(dependency_feature.to_string());
+            } else if let Some(values) = self.candidate.features.get(&feature) {
+                if self.features.insert(feature) {
+                    changed = true;
+                    stack.extend(values.iter().cloned());
+                }
+            } else if self.is_optional_dependency(&feature) {
+                // Optional dependencies that are not referenced with `dep:`
+                // have an implicit feature of the same name.
+                changed |= self.enabled_dependencies.insert(feature.clone());
+                changed |= self.features.insert(feature);
+            }
+        }
+
+        changed
+    }
+
+    /// Returns the dependencies that are enabled with the current features
+    /// and that apply to the given target.
+    fn dependency_requests(&self, target: Option<&TargetInfo>) -> Vec<DependencyRequest> {
+        self.dependencies
+            .iter()
+            .filter(|(dep, _)| dep.kind != DependencyKind::Dev)
+            .filter(|(dep, _)| target_matches(dep.target.as_deref(), target))
+            .filter_map(|(dep, crate_name)| {
+                let name = dep.explicit_name.as_deref().unwrap_or(crate_name);
+                if dep.optional && !self.enabled_dependencies.contains(name) {
+                    return None;
+                }
+
+                let mut features = dep.features.clone();
+                if dep.default_features {
+                    features.push("default".to_string());
+                }
+                if let Some(extra_features) = self.dependency_features.get(name) {
+                    features.extend(extra_features.iter().cloned());
+                }
+
+                Some(DependencyRequest {
+                    crate_id: dep.crate_id,
+                    crate_name: crate_name.clone(),
+                    req: dep.req.clone(),
+                    kind: dep.kind,
+                    features,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Checks whether a dependency with the given `target` applies to the
+/// requested target. If no target was requested, all dependencies apply.
+fn target_matches(dependency_target: Option<&str>, target: Option<&TargetInfo>) -> bool {
+    let (Some(dependency_target), Some(target)) = (dependency_target, target) else {
+        return true;
+    };
+
+    if !dependency_target.starts_with("cfg(") {
+        return dependency_target == target.triple.as_ref();
+    }
+
+    let Ok(expression) = Expression::parse(dependency_target) else {
+        return false;
+    };
+
+    expression.eval(|predicate| match predicate {
+        Predicate::Target(predicate) => predicate.matches(target),
+        _ => false,
+    })
+}
+
+struct Resolver<'a> {
+    conn: &'a mut AsyncPgConnection,
+    target: Option<&'static TargetInfo>,
+    /// The versions of each crate, ordered from highest to lowest.
+    candidates: HashMap<i32, Vec<Candidate>>,
+    packages: Vec<Package>,
+    unresolved: Vec<UnresolvedDependency>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(conn: &'a mut AsyncPgConnection, target: Option<&'static TargetInfo>) -> Self {
+        Self {
+            conn,
+            target,
+            candidates: HashMap::new(),
+            packages: Vec::new(),
+            unresolved: Vec::new(),
+        }
+    }
+
+    async fn add_root(&mut self, version: Version, name: String) -> AppResult<&Package> {
+        let crate_id = version.crate_id;
+        let index = self.add_package(crate_id, name, version.into())?;
+        self.load_dependencies(&[index]).await?;
+        Ok(&self.packages[index])
+    }
+
+    fn add_package(
+        &mut self,
+        crate_id: i32,
+        name: String,
+        candidate: Candidate,
+    ) -> AppResult<usize> {
+        if self.packages.len() >= MAX_PACKAGES {
+            let message =
+                format!("the dependency tree has more than {MAX_PACKAGES} crate versions");
+            return Err(bad_request(message));
+        }
+
+        self.packages.push(Package {
+            crate_id,
+            name,
+            candidate,
+            dependencies: Vec::new(),
+            dependencies_loaded: false,
+            features: BTreeSet::new(),
+            enabled_dependencies: BTreeSet::new(),
+            dependency_features: BTreeMap::new(),
+            edges: Vec::new(),
+            activated: false,
+        });
+
+        Ok(self.packages.len() - 1)
+    }
+
+    /// Loads the dependencies of the given packages with a single query.
+    async fn load_dependencies(&mut self, indices: &[usize]) -> QueryResult<()> {
+        let version_ids = indices
+            .iter()
+            .map(|&index| self.packages[index].candidate.id)
+            .collect::<Vec<_>>();
+
+        let dependencies: Vec<(Dependency, String)> = dependencies::table
+            .inner_join(crates::table)
+            .filter(dependencies::version_id.eq_any(&version_ids))
+            .select((Dependency::as_select(), crates::name))
+            .order(dependencies::id)
+            .load(self.conn)
+            .await?;
+
+        let mut dependencies_by_version = HashMap::<i32, Vec<_>>::new();
+        for (dependency, crate_name) in dependencies {
+            dependencies_by_version
+                .entry(dependency.version_id)
+                .or_default()
+                .push((dependency, crate_name));
+        }
+
+        for &index in indices {
+            let package = &mut self.packages[index];
+            let version_id = package.candidate.id;
+            package.dependencies = dependencies_by_version
+                .remove(&version_id)
+                .unwrap_or_default();
+            package.dependencies_loaded = true;
+        }
+
+        Ok(())
+    }
+
+    /// Loads the versions of all crates that are requested by the given
+    /// dependency requests, and that were not loaded yet, with a single query.
+    async fn load_candidates(&mut self, requests: &[DependencyRequest]) -> QueryResult<()> {
+        let mut crate_ids = requests
+            .iter()
+            .map(|request| request.crate_id)
+            .filter(|crate_id| !self.candidates.contains_key(crate_id))
+            .collect::<Vec<_>>();
+        if crate_ids.is_empty() {
+            return Ok(());
+        }
+
+        crate_ids.sort_unstable();
+        crate_ids.dedup();
+
+        let versions: Vec<(i32, i32, String, bool, Option<String>, serde_json::Value)> =
+            versions::table
+                .filter(versions::crate_id.eq_any(&crate_ids))
+                .filter(Version::is_published())
+                .order((
+                    versions::crate_id,
+                    versions::semver_ord.desc().nulls_last(),
+                    versions::id.desc(),
+                ))
+                .select((
+                    versions::crate_id,
+                    versions::id,
+                    versions::num,
+                    versions::yanked,
+                    versions::license,
+                    versions::features,
+                ))
+                .load(self.conn)
+                .await?;
+
+        for crate_id in crate_ids {
+            self.candidates.insert(crate_id, Vec::new());
+        }
+
+        for (crate_id, id, num, yanked, license, features) in versions {
+            let candidate = Candidate::new(id, num, yanked, license, features);
+            if let Some(candidates) = self.candidates.get_mut(&crate_id) {
+                candidates.push(candidate);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Selects the version that a dependency request resolves to, preferring
+    /// versions that were already selected for other dependents.
+    ///
+    /// The candidates of the requested crate have to be loaded with
+    /// [`Self::load_candidates`] first.
+    fn select(&mut self, request: &DependencyRequest) -> AppResult<Option<usize>> {
+        let Ok(req) = VersionReq::parse(&request.req) else {
+            return Ok(None);
+        };
+
+        let selected = self.packages.iter().position(|package| {
+            package.crate_id == request.crate_id && req.matches(&package.candidate.semver)
+        });
+        if selected.is_some() {
+            return Ok(selected);
+        }
+
+        let candidate = self
+            .candidates
+            .get(&request.crate_id)
+            .into_iter()
+            .flatten()
+            .find(|candidate| !candidate.yanked && req.matches(&candidate.semver))
+            .cloned();
+
+        let Some(candidate) = candidate else {
+            return Ok(None);
+        };
+
+        let name = request.crate_name.clone();
+        let index = self.add_package(request.crate_id, name, candidate)?;
+        Ok(Some(index))
+    }
+
+    /// Resolves the dependency tree of the root version with the given
+    /// features enabled.
+    async fn resolve(&mut self, root_features: Vec<String>) -> AppResult<()> {
+        let mut queue = VecDeque::from([(0, root_features)]);
+        while let Some((index, features)) = queue.pop_front() {
+            // Load the dependencies of all queued packages at once, instead
+            // of one query per package.
+            if !self.packages[index].dependencies_loaded {
+                let mut indices = std::iter::once(index)
+                    .chain(queue.iter().map(|&(index, _)| index))
+                    .filter(|&index| !self.packages[index].dependencies_loaded)
+                    .collect::<Vec<_>>();
+                indices.sort_unstable();
+                indices.dedup();
+                self.load_dependencies(&indices).await?;
+            }
+
+            let package = &mut self.packages[index];
+            let changed = package.enable_features(features);
+            if package.activated && !changed {
+                continue;
+            }
+            package.activated = true;
+
+            let requests = package.dependency_requests(self.target);
+            self.load_candidates(&requests).await?;
+            for request in requests {
+                let Some(dependency_index) = self.select(&request)? else {
+                    let package = &self.packages[index];
+                    let unresolved = UnresolvedDependency {
+                        name: request.crate_name,
+                        req: request.req,
+                        required_by: format!("{} {}", package.name, package.candidate.num),
+                    };
+                    if !self.unresolved.contains(&unresolved) {
+                        self.unresolved.push(unresolved);
+                    }
+                    continue;
+                };
+
+                let edge = (dependency_index, request.kind);
+                let edges = &mut self.packages[index].edges;
+                if !edges.contains(&edge) {
+                    edges.push(edge);
+                }
+
+                queue.push_back((dependency_index, request.features));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn into_response(self, allowed_licenses: Option<&[String]>) -> DependencyTreeResponse {
+        let packages = &self.packages;
+        let package_ref = |package: &Package| PackageRef {
+            name: package.name.clone(),
+            version: package.candidate.num.clone(),
+        };
+
+        // The requested version comes first, all other versions are sorted
+        // by name and version.
+        let mut order = (0..packages.len()).collect::<Vec<_>>();
+        order[1..].sort_by(|&a, &b| {
+            let (a, b) = (&packages[a], &packages[b]);
+            (&a.name, &a.candidate.semver).cmp(&(&b.name, &b.candidate.semver))
+        });
+
+        let mut versions_by_name = BTreeMap::<&str, Vec<String>>::new();
+        for &index in &order {
+            let package = &packages[index];
+            let versions = versions_by_name.entry(&package.name).or_default();
+            versions.push(package.candidate.num.clone());
+        }
+
+        let duplicates = versions_by_name
+            .into_iter()
+            .filter(|(_, versions)| versions.len() > 1)
+            .map(|(name, versions)| DuplicateCrate {
+                name: name.to_string(),
+                versions,
+            })
+            .collect();
+
+        let yanked = order
+            .iter()
+            .map(|&index| &packages[index])
+            .filter(|package| package.candidate.yanked)
+            .map(package_ref)
+            .collect();
+
+        let license_incompatible = order
+            .iter()
+            .map(|&index| &packages[index])
+            .filter(|package| {
+                !is_license_allowed(package.candidate.license.as_deref(), allowed_licenses)
+            })
+            .map(package_ref)
+            .collect();
+
+        let resolved_packages = order
+            .iter()
+            .map(|&index| {
+                let package = &packages[index];
+
+                let mut dependencies = package
+                    .edges
+                    .iter()
+                    .map(|&(index, kind)| ResolvedDependency {
+                        name: packages[index].name.clone(),
+                        version: packages[index].candidate.num.clone(),
+                        kind,
+                    })
+                    .collect::<Vec<_>>();
+                dependencies.sort_by(|a, b| a.name.cmp(&b.name));
+
+                ResolvedPackage {
+                    name: package.name.clone(),
+                    version: package.candidate.num.clone(),
+                    license: package.candidate.license.clone(),
+                    yanked: package.candidate.yanked,
+                    features: package.features.iter().cloned().collect(),
+                    dependencies,
+                }
+            })
+            .collect();
+
+        DependencyTreeResponse {
+            packages: resolved_packages,
+            meta: DependencyTreeMeta {
+                total: packages.len() - 1,
+                duplicates,
+                yanked,
+                license_incompatible,
+                unresolved: self.unresolved,
+            },
+        }
+    }
+}
+
+/// Checks whether the license expression can be satisfied by the allowed
+/// licenses. Missing or invalid license expressions are never allowed.
+fn is_license_allowed(license: Option<&str>, allowed_licenses: Option<&[String]>) -> bool {
+    let Some(Ok(expression)) = license.map(parse_license_expr) else {
+        return false;
+    };
+
+    let Some(allowed_licenses) = allowed_licenses else {
+        return true;
+    };
+
+    expression.evaluate(|req| {
+        req.license.id().is_some_and(|id| {
+            allowed_licenses
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(id.name))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_matches() {
+        let linux = get_builtin_target_by_triple("x86_64-unknown-linux-gnu");
+        let windows = get_builtin_target_by_triple("x86_64-pc-windows-msvc");
+
+        assert!(target_matches(None, linux));
+        assert!(target_matches(Some("cfg(windows)"), None));
+        assert!(target_matches(Some("cfg(unix)"), linux));
+        assert!(!target_matches(Some("cfg(unix)"), windows));
+        assert!(target_matches(
+            Some("cfg(target_os = \"windows\")"),
+            windows
+        ));
+        assert!(target_matches(
+            Some("cfg(not(target_os = \"windows\"))"),
+            linux
+        ));
+        assert!(target_matches(Some("x86_64-pc-windows-msvc"), windows));
+        assert!(!target_matches(Some("x86_64-pc-windows-msvc"), linux));
+        assert!(!target_matches(Some("cfg(test)"), linux));
+        assert!(!target_matches(Some("cfg(invalid"), linux));
+    }
+
+    #[test]
+    fn test_is_license_allowed() {
+        let allowed = vec!["MIT".to_string(), "Apache-2.0".to_string()];
+        let allowed = Some(allowed.as_slice());
+
+        assert!(is_license_allowed(Some("MIT"), None));
+        assert!(is_license_allowed(Some("MIT OR Apache-2.0"), allowed));
+        assert!(is_license_allowed(Some("MIT/Apache-2.0"), allowed));
+        assert!(is_license_allowed(Some("GPL-3.0 OR MIT"), allowed));
+        assert!(!is_license_allowed(Some("GPL-3.0"), allowed));
+        assert!(!is_license_allowed(Some("MIT AND GPL-3.0"), allowed));
+        assert!(!is_license_allowed(Some("apache 2.0"), None));
+        assert!(!is_license_allowed(None, None));
+    }
+}
@@ -1,17 +1,44 @@
-//! Endpoint for triggering a docs.rs rebuild
+//! Endpoints for triggering docs.rs rebuilds and listing previous rebuild
+//! requests
 
 use super::CrateVersionPath;
 use crate::app::AppState;
 use crate::auth::AuthCheck;
 use crate::controllers::helpers::authorization::Rights;
+use crate::models::{Crate, DocsRsRebuildRequest, NewDocsRsRebuildRequest, User};
+use crate::schema::{docs_rs_rebuild_requests, users};
 use crate::util::errors::{AppResult, custom, server_error};
+use crate::views::EncodableDocsRsRebuildRequest;
 use crate::worker::jobs;
+use axum::Json;
 use crates_io_worker::BackgroundJob as _;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
 use http::StatusCode;
 use http::request::Parts;
+use serde::Serialize;
 use tracing::error;
 
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct RebuildResponse {
+    /// The rebuild request that was created, or the already existing
+    /// request if a rebuild for this version is still in flight.
+    pub rebuild_request: EncodableDocsRsRebuildRequest,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ListRebuildsResponse {
+    /// The rebuild requests for this version, most recent first.
+    pub rebuild_requests: Vec<EncodableDocsRsRebuildRequest>,
+}
+
 /// Trigger a rebuild for the crate documentation on docs.rs.
+///
+/// If a previous rebuild request for this version has not been sent to
+/// docs.rs yet, or is waiting to be retried because docs.rs rate limited
+/// it, no new request is created and the existing request is returned
+/// instead.
 #[utoipa::path(
     post,
     path = "/api/v1/crates/{name}/{version}/rebuild_docs",
@@ -20,35 +47,124 @@ use tracing::error;
         ("cookie" = []),
     ),
     tag = "versions",
-    responses((status = 201, description = "Successful Response")),
+    responses(
+        (status = 200, description = "A rebuild request for this version is already in flight", body = inline(RebuildResponse)),
+        (status = 201, description = "Successful Response", body = inline(RebuildResponse)),
+    ),
 )]
 pub async fn rebuild_version_docs(
     app: AppState,
     path: CrateVersionPath,
     req: Parts,
-) -> AppResult<StatusCode> {
+) -> AppResult<(StatusCode, Json<RebuildResponse>)> {
     let mut conn = app.db_write().await?;
     let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
 
     // validate if version & crate exist
-    let (_, krate) = path.load_version_and_crate(&mut conn).await?;
+    let (version, krate) = path.load_version_and_crate(&mut conn).await?;
 
     // Check that the user is an owner of the crate, or a team member (= publish rights)
     let user = auth.user();
-    let owners = krate.owners(&mut conn).await?;
+    let message = "user doesn't have permission to trigger a docs rebuild";
+    check_rights(&app, user, &krate, message, &mut conn).await?;
+
+    let (status, request) = conn
+        .transaction(|conn| {
+            async move {
+                if let Some(request) =
+                    DocsRsRebuildRequest::find_in_flight(conn, version.id).await?
+                {
+                    return Ok::<_, anyhow::Error>((StatusCode::OK, request));
+                }
+
+                let request = NewDocsRsRebuildRequest {
+                    version_id: version.id,
+                    requested_by: Some(user.id),
+                }
+                .insert(conn)
+                .await?;
+
+                jobs::DocsRsQueueRebuild::new(krate.name, version.num)
+                    .with_request_id(request.id)
+                    .enqueue(conn)
+                    .await?;
+
+                Ok((StatusCode::CREATED, request))
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(|error| {
+            error!("docs_rs_queue_rebuild: Failed to create background job: {error}");
+            server_error("failed to create background job")
+        })?;
+
+    let requested_by = load_user(request.requested_by, &mut conn).await?;
+    let rebuild_request = EncodableDocsRsRebuildRequest::from(request, requested_by);
+
+    Ok((status, Json(RebuildResponse { rebuild_request })))
+}
+
+/// List the docs.rs rebuild requests for a crate version.
+///
+/// Only owners of the crate can see the rebuild requests.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/{version}/rebuild_docs",
+    params(CrateVersionPath),
+    security(
+        ("cookie" = []),
+    ),
+    tag = "versions",
+    responses((status = 200, description = "Successful Response", body = inline(ListRebuildsResponse))),
+)]
+pub async fn list_version_docs_rebuilds(
+    app: AppState,
+    path: CrateVersionPath,
+    req: Parts,
+) -> AppResult<Json<ListRebuildsResponse>> {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+
+    let (version, krate) = path.load_version_and_crate(&mut conn).await?;
+    let message = "user doesn't have permission to list docs rebuilds";
+    check_rights(&app, auth.user(), &krate, message, &mut conn).await?;
+
+    let rebuild_requests = docs_rs_rebuild_requests::table
+        .left_outer_join(users::table)
+        .select(<(DocsRsRebuildRequest, Option<User>)>::as_select())
+        .filter(docs_rs_rebuild_requests::version_id.eq(version.id))
+        .order(docs_rs_rebuild_requests::requested_at.desc())
+        .load::<(DocsRsRebuildRequest, Option<User>)>(&mut conn)
+        .await?
+        .into_iter()
+        .map(|(request, user)| EncodableDocsRsRebuildRequest::from(request, user))
+        .collect();
+
+    Ok(Json(ListRebuildsResponse { rebuild_requests }))
+}
+
+async fn check_rights(
+    app: &AppState,
+    user: &User,
+    krate: &Crate,
+    message: &'static str,
+    conn: &mut AsyncPgConnection,
+) -> AppResult<()> {
+    let owners = krate.owners(conn).await?;
     let encryption = &app.config.gh_token_encryption;
     if Rights::get(user, &*app.github, &owners, encryption).await? < Rights::Publish {
-        return Err(custom(
-            StatusCode::FORBIDDEN,
-            "user doesn't have permission to trigger a docs rebuild",
-        ));
+        return Err(custom(StatusCode::FORBIDDEN, message));
     }
 
-    let job = jobs::DocsRsQueueRebuild::new(path.name, path.version);
-    job.enqueue(&mut conn).await.map_err(|error| {
-        error!("docs_rs_queue_rebuild: Failed to create background job: {error}");
-        server_error("failed to create background job")
-    })?;
+    Ok(())
+}
 
-    Ok(StatusCode::CREATED)
+async fn load_user(id: Option<i32>, conn: &mut AsyncPgConnection) -> QueryResult<Option<User>> {
+    let Some(id) = id else { return Ok(None) };
+    User::query()
+        .filter(users::id.eq(id))
+        .first(conn)
+        .await
+        .optional()
 }
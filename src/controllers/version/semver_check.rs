@@ -0,0 +1,78 @@
+use super::CrateVersionPath;
+use crate::app::AppState;
+use crate::models::VersionSemverCheck;
+use crate::schema::versions;
+use crate::util::errors::AppResult;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde::Serialize;
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct SemverCheckResponse {
+    /// The result of the semver check, or `null` if the version has not
+    /// been checked.
+    #[schema(inline)]
+    pub semver_check: Option<EncodableSemverCheck>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct EncodableSemverCheck {
+    /// The previous semver-compatible version that the public API was
+    /// compared against.
+    #[schema(example = "1.0.2")]
+    pub baseline_version: String,
+
+    /// The changes that likely break users of the baseline version.
+    ///
+    /// Each change has the public `path` of the item, the `item` kind, the
+    /// `kind` of change, and the `old` and `new` signatures of the item.
+    #[schema(value_type = Vec<Object>)]
+    pub breaking_changes: serde_json::Value,
+
+    /// The date and time when the check was performed.
+    #[schema(example = "2019-12-13T13:46:41Z")]
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Get the result of the semver compatibility check of a crate version.
+///
+/// When a new version of an existing crate is published, its public API is
+/// compared against the previous semver-compatible version of the crate.
+/// The check only looks at the source code, so the reported changes might
+/// include false positives.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/{version}/semver_check",
+    params(CrateVersionPath),
+    tag = "versions",
+    responses((status = 200, description = "Successful Response", body = inline(SemverCheckResponse))),
+)]
+pub async fn get_version_semver_check(
+    state: AppState,
+    path: CrateVersionPath,
+) -> AppResult<Json<SemverCheckResponse>> {
+    let mut conn = state.db_read().await?;
+    let version = path.load_version(&mut conn).await?;
+
+    let Some(check) = VersionSemverCheck::find(&mut conn, version.id).await? else {
+        return Ok(Json(SemverCheckResponse { semver_check: None }));
+    };
+
+    let baseline_version = versions::table
+        .find(check.baseline_version_id)
+        .select(versions::num)
+        .first(&mut conn)
+        .await?;
+
+    let semver_check = EncodableSemverCheck {
+        baseline_version,
+        breaking_changes: check.breaking_changes,
+        checked_at: check.checked_at,
+    };
+
+    Ok(Json(SemverCheckResponse {
+        semver_check: Some(semver_check),
+    }))
+}
@@ -2,8 +2,8 @@
 //! and is used by the corresponding background jobs to generate the
 //! index files.
 
-use crate::models::{Crate, Dependency, Version};
-use crate::schema::crates;
+use crate::models::{Crate, CrateStatus, Dependency, ScheduledCrateDeletion, Version};
+use crate::schema::{crates, pending_versions, versions};
 use anyhow::Context;
 use crates_io_index::features::split_features;
 use diesel::prelude::*;
@@ -28,6 +28,16 @@ pub async fn get_index_data(
         return Ok(None);
     };
 
+    // Crates that are scheduled for deletion are removed from the index
+    // right away, and are added back if the deletion is cancelled.
+    if ScheduledCrateDeletion::find(conn, krate.id)
+        .await?
+        .is_some()
+    {
+        debug!("Crate is scheduled for deletion");
+        return Ok(None);
+    }
+
     debug!("Gathering remaining index data");
     let crates = index_metadata(&krate, conn, include_pubtime)
         .await
@@ -61,7 +71,11 @@ pub async fn index_metadata(
     conn: &mut AsyncPgConnection,
     include_pubtime: bool,
 ) -> QueryResult<Vec<crates_io_index::Crate>> {
+    // Versions that are waiting for approval are not published yet.
+    let pending_versions = pending_versions::table.select(pending_versions::version_id);
+
     let mut versions: Vec<Version> = Version::belonging_to(krate)
+        .filter(versions::id.ne_all(pending_versions))
         .select(Version::as_select())
         .load(conn)
         .await?;
@@ -130,6 +144,8 @@ pub async fn index_metadata(
                 links: version.links,
                 rust_version: version.rust_version,
                 pubtime: include_pubtime.then_some(version.created_at),
+                status: (krate.status != CrateStatus::Active).then(|| krate.status.as_str().into()),
+                successor: krate.successor.clone(),
                 features2,
                 v,
             };
@@ -186,7 +202,7 @@ mod tests {
             .version(
                 VersionBuilder::new("2.0.0")
                     .created_at(created_at_2)
-                    .dependency(&fooo, None),
+                    .dependency(&fooo),
             )
             .version(VersionBuilder::new("1.0.1").checksum("0123456789abcdef"))
             .expect_build(&mut conn)
@@ -18,6 +18,7 @@ static ALLOC: Jemalloc = Jemalloc;
 
 pub mod app;
 pub mod auth;
+pub mod blocked_traffic;
 pub mod boot;
 pub mod certs;
 pub mod cloudfront;
@@ -32,6 +33,7 @@ pub mod metrics;
 pub mod middleware;
 pub mod openapi;
 pub mod rate_limiter;
+pub mod read_rate_limiter;
 mod router;
 pub mod sentry;
 pub mod sqs;
@@ -45,6 +45,12 @@ metrics! {
         pub response_times: HistogramVec["endpoint"],
         /// Nmber of responses per status code
         pub responses_by_status_code_total: IntCounterVec["status"],
+
+        /// Number of requests blocked by each of the `blocked_traffic_rules`
+        pub blocked_traffic_rule_hits_total: IntCounterVec["rule"],
+
+        /// Number of requests rejected by the per-client read rate limits
+        pub read_rate_limited_requests_total: IntCounterVec["group"],
     }
 
     // All instance metrics will be prefixed with this namespace.
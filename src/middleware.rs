@@ -6,6 +6,7 @@ mod debug;
 mod ember_html;
 pub mod log_request;
 pub mod normalize_path;
+mod read_rate_limit;
 pub mod real_ip;
 mod require_user_agent;
 mod static_or_continue;
@@ -68,6 +69,10 @@ pub fn apply_axum_middleware(state: AppState, router: Router<()>) -> Router {
             require_user_agent::require_user_agent,
         ))
         .layer(from_fn_with_state(state.clone(), block_traffic::middleware))
+        .layer(from_fn_with_state(
+            state.clone(),
+            read_rate_limit::middleware,
+        ))
         .layer(from_fn_with_state(
             state.clone(),
             common_headers::add_common_headers,
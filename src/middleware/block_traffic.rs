@@ -14,6 +14,8 @@ pub async fn middleware(
     req: Request,
     next: Next,
 ) -> Result<impl IntoResponse, Response> {
+    block_by_rules(&real_ip, matched_path.as_ref(), &state, &req)
+        .map_err(IntoResponse::into_response)?;
     block_by_ip(&real_ip, &state, req.headers()).map_err(IntoResponse::into_response)?;
     block_by_header(&state, &req).map_err(IntoResponse::into_response)?;
     block_routes(matched_path.as_ref(), &state).map_err(IntoResponse::into_response)?;
@@ -21,6 +23,44 @@ pub async fn middleware(
     Ok(next.run(req).await)
 }
 
+/// Middleware that blocks requests matching one of the `blocked_traffic_rules`
+///
+/// The rules are managed with `crates-admin blocked-traffic` and periodically
+/// reloaded by the server. Route patterns are matched against the route
+/// template (e.g. `/api/v1/crates/{name}/{version}/download`), or against the
+/// request path if the request did not match any route.
+pub fn block_by_rules(
+    real_ip: &RealIp,
+    matched_path: Option<&MatchedPath>,
+    state: &AppState,
+    req: &Request,
+) -> Result<(), (StatusCode, String)> {
+    let route = matched_path.map_or(req.uri().path(), |path| path.as_str());
+    let headers = req.headers();
+
+    let Some(rule) = state
+        .blocked_traffic_rules
+        .find_match(**real_ip, headers, route)
+    else {
+        return Ok(());
+    };
+
+    let rule_id = rule.id.to_string();
+    state
+        .instance_metrics
+        .blocked_traffic_rule_hits_total
+        .with_label_values(&[&rule_id])
+        .inc();
+
+    req.request_log()
+        .add("cause", format!("blocked by traffic rule {rule_id}"));
+
+    match rule.message {
+        Some(message) => Err((StatusCode::FORBIDDEN, message)),
+        None => Err(rejection_response_from(state, headers)),
+    }
+}
+
 /// Middleware that blocks requests if a header matches the given list
 ///
 /// To use, set the `BLOCKED_TRAFFIC` environment variable to a comma-separated list of pairs
@@ -61,7 +101,7 @@ pub fn block_by_ip(
     Ok(())
 }
 
-fn rejection_response_from(state: &AppState, headers: &HeaderMap) -> impl IntoResponse {
+fn rejection_response_from(state: &AppState, headers: &HeaderMap) -> (StatusCode, String) {
     let domain_name = &state.config.domain_name;
 
     // Heroku should always set this header
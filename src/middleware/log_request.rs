@@ -69,6 +69,7 @@ pub async fn log_requests(
         .as_ref()
         .map(|p| Cow::Borrowed(&p.0.0))
         .unwrap_or_else(|| Cow::Owned(request_metadata.uri.to_string()));
+    let url = redact_url(&url);
 
     let matched_path = request_metadata
         .matched_path
@@ -106,6 +107,36 @@ pub async fn log_requests(
     response
 }
 
+/// Query parameters that contain secrets, like the tokens of the
+/// personalized updates feeds, and must not end up in the logs.
+const SECRET_QUERY_PARAMS: &[&str] = &["token"];
+
+/// Replaces the values of secret query parameters in the `url`.
+fn redact_url(url: &str) -> Cow<'_, str> {
+    let is_secret = |pair: &str| {
+        let key = pair.split_once('=').map_or(pair, |(key, _)| key);
+        SECRET_QUERY_PARAMS.contains(&key)
+    };
+
+    let Some((path, query)) = url.split_once('?') else {
+        return Cow::Borrowed(url);
+    };
+    if !query.split('&').any(is_secret) {
+        return Cow::Borrowed(url);
+    }
+
+    let query = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, _)) if is_secret(pair) => Cow::Owned(format!("{key}=[REDACTED]")),
+            _ => Cow::Borrowed(pair),
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+
+    Cow::Owned(format!("{path}?{query}"))
+}
+
 #[derive(Clone, Debug, Deref, Default)]
 pub struct RequestLog(Arc<Mutex<Vec<(&'static str, String)>>>);
 
@@ -129,3 +160,74 @@ impl<T: RequestPartsExt> RequestLogExt for T {
             .expect("Failed to find `RequestLog` request extension")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::body::Body;
+    use axum::extract::ConnectInfo;
+    use axum::middleware::from_fn;
+    use axum::routing::get;
+    use std::io;
+    use std::net::SocketAddr;
+    use tower::ServiceExt;
+
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_redact_url() {
+        assert_eq!(redact_url("/api/v1/crates"), "/api/v1/crates");
+        assert_eq!(
+            redact_url("/api/v1/crates?q=token"),
+            "/api/v1/crates?q=token"
+        );
+        assert_eq!(
+            redact_url("/api/v1/me/updates/rss?token=secret"),
+            "/api/v1/me/updates/rss?token=[REDACTED]"
+        );
+        assert_eq!(
+            redact_url("/feed?a=1&token=secret&token&b=token"),
+            "/feed?a=1&token=[REDACTED]&token&b=token"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_secret_query_params_are_not_logged() {
+        let buffer = LogBuffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let app = Router::new()
+            .route("/api/v1/me/updates/rss", get(async || "feed"))
+            .layer(from_fn(log_requests))
+            .layer(from_fn(crate::middleware::real_ip::middleware));
+
+        let mut request = Request::get("/api/v1/me/updates/rss?token=secret-feed-token")
+            .body(Body::empty())
+            .unwrap();
+        let socket_addr = SocketAddr::from(([127, 0, 0, 1], 8888));
+        request.extensions_mut().insert(ConnectInfo(socket_addr));
+        app.oneshot(request).await.unwrap();
+
+        let logs = String::from_utf8(buffer.0.lock().clone()).unwrap();
+        assert!(logs.contains("GET /api/v1/me/updates/rss?token=[REDACTED] → 200 OK"));
+        assert!(!logs.contains("secret-feed-token"));
+    }
+}
@@ -0,0 +1,140 @@
+//! Per-client rate limiting for read-only endpoints.
+//!
+//! See [`crate::read_rate_limiter`] for the rate limiter implementation.
+
+use crate::app::AppState;
+use crate::auth::AuthHeader;
+use crate::middleware::log_request::RequestLogExt;
+use crate::middleware::real_ip::RealIp;
+use crate::read_rate_limiter::{ClientKey, RateLimitStatus, ReadRouteGroup};
+use crate::schema::api_tokens;
+use crate::util::errors::custom;
+use crate::util::token::HashedToken;
+use axum::extract::{Extension, MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use diesel::dsl::now;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::request::Parts;
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, header};
+use secrecy::ExposeSecret;
+use std::time::Duration;
+
+const RATE_LIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+const RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+const RATE_LIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+
+/// Applies the per-client rate limits of the [`ReadRouteGroup`] that the
+/// request was routed to.
+///
+/// Clients are identified by their API token, if the request is
+/// authenticated with a valid one, or by their real IP address otherwise.
+/// Every response of a rate limited route includes `RateLimit-Limit`,
+/// `RateLimit-Remaining` and `RateLimit-Reset` headers. If the limit is
+/// exceeded, the request is rejected with a `429 Too Many Requests` response
+/// including a `Retry-After` header.
+pub async fn middleware(
+    Extension(real_ip): Extension<RealIp>,
+    matched_path: Option<MatchedPath>,
+    state: AppState,
+    req: Request,
+    next: Next,
+) -> Response {
+    if req.method() != Method::GET && req.method() != Method::HEAD {
+        return next.run(req).await;
+    }
+
+    let Some(group) = matched_path
+        .as_ref()
+        .and_then(|path| ReadRouteGroup::from_route(path.as_str()))
+    else {
+        return next.run(req).await;
+    };
+
+    if !state.read_rate_limiter.is_enabled(group) {
+        return next.run(req).await;
+    }
+
+    let (parts, body) = req.into_parts();
+    let client = match find_token_id(&state, &parts).await {
+        Some(token_id) => ClientKey::Token(token_id),
+        None => ClientKey::Ip(*real_ip),
+    };
+    let req = Request::from_parts(parts, body);
+
+    let Some(status) = state.read_rate_limiter.check(group, client).await else {
+        return next.run(req).await;
+    };
+
+    let mut response = match status.retry_after {
+        None => next.run(req).await,
+        Some(retry_after) => {
+            state
+                .instance_metrics
+                .read_rate_limited_requests_total
+                .with_label_values(&[group.as_str()])
+                .inc();
+
+            req.request_log().add(
+                "cause",
+                format!("read rate limit exceeded ({})", group.as_str()),
+            );
+
+            too_many_requests(retry_after)
+        }
+    };
+
+    add_rate_limit_headers(response.headers_mut(), &status);
+    response
+}
+
+/// Returns the ID of the API token that the request is authenticated with,
+/// if the token is valid.
+///
+/// Invalid tokens and database errors fall back to IP-based rate limiting.
+/// The actual authentication of the request is left to the endpoint.
+async fn find_token_id(state: &AppState, parts: &Parts) -> Option<i32> {
+    let auth_header = AuthHeader::optional_from_request_parts(parts)
+        .await
+        .ok()??;
+    let token = HashedToken::parse(auth_header.token().expose_secret()).ok()?;
+
+    let mut conn = state.db_read().await.ok()?;
+    api_tokens::table
+        .filter(api_tokens::token.eq(&token))
+        .filter(api_tokens::revoked.eq(false))
+        .filter(
+            api_tokens::expired_at
+                .is_null()
+                .or(api_tokens::expired_at.gt(now)),
+        )
+        .select(api_tokens::id)
+        .first(&mut conn)
+        .await
+        .ok()
+}
+
+fn too_many_requests(retry_after: Duration) -> Response {
+    let retry_after = ceil_secs(retry_after);
+    let detail = format!(
+        "You have made too many requests. Please try again in {retry_after} seconds \
+         or email help@crates.io to have your limit increased."
+    );
+
+    let mut response = custom(StatusCode::TOO_MANY_REQUESTS, detail).into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    response
+}
+
+fn add_rate_limit_headers(headers: &mut HeaderMap, status: &RateLimitStatus) {
+    headers.insert(RATE_LIMIT_LIMIT, HeaderValue::from(status.limit));
+    headers.insert(RATE_LIMIT_REMAINING, HeaderValue::from(status.remaining));
+    headers.insert(RATE_LIMIT_RESET, HeaderValue::from(ceil_secs(status.reset)));
+}
+
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
@@ -0,0 +1,307 @@
+//! In-memory rate limiting for read-only API endpoints.
+//!
+//! In contrast to the [`RateLimiter`](crate::rate_limiter::RateLimiter),
+//! which is backed by the database and limits actions of authenticated
+//! users, this rate limiter is used for high-volume read traffic. Each
+//! client gets a token bucket per [`ReadRouteGroup`], keyed on the API token
+//! if the request is authenticated with one, or on the real client IP
+//! otherwise.
+//!
+//! The buckets are stored in memory, so the limits apply per server
+//! instance.
+
+use crate::rate_limiter::RateLimiterConfig;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The maximum number of buckets that are kept in memory. If the cache is
+/// full, the least recently used buckets are evicted.
+const MAX_BUCKETS: u64 = 100_000;
+
+/// A group of read-only endpoints that share a rate limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReadRouteGroup {
+    /// `GET /api/v1/crates`
+    ListCrates,
+    /// `GET /api/v1/crates/{name}/reverse_dependencies`
+    ReverseDependencies,
+    /// `GET /api/v1/crates/{name}/downloads` and
+    /// `GET /api/v1/crates/{name}/{version}/downloads`
+    Downloads,
+    /// `GET /api/v1/crates/{name}/{version}/dependency_tree`
+    DependencyTree,
+}
+
+impl ReadRouteGroup {
+    pub const VARIANTS: &[Self] = &[
+        Self::ListCrates,
+        Self::ReverseDependencies,
+        Self::Downloads,
+        Self::DependencyTree,
+    ];
+
+    /// Returns the route group of a matched route pattern, if the route is
+    /// rate limited.
+    pub fn from_route(route: &str) -> Option<Self> {
+        match route {
+            "/api/v1/crates" => Some(Self::ListCrates),
+            "/api/v1/crates/{name}/reverse_dependencies" => Some(Self::ReverseDependencies),
+            "/api/v1/crates/{name}/downloads" | "/api/v1/crates/{name}/{version}/downloads" => {
+                Some(Self::Downloads)
+            }
+            "/api/v1/crates/{name}/{version}/dependency_tree" => Some(Self::DependencyTree),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ListCrates => "list_crates",
+            Self::ReverseDependencies => "reverse_dependencies",
+            Self::Downloads => "downloads",
+            Self::DependencyTree => "dependency_tree",
+        }
+    }
+
+    pub fn default_rate_seconds(&self) -> u64 {
+        match self {
+            // Resolving a dependency tree can take many database queries
+            Self::DependencyTree => 10,
+            _ => 1,
+        }
+    }
+
+    pub fn default_burst(&self) -> i32 {
+        match self {
+            Self::ListCrates => 60,
+            Self::ReverseDependencies => 30,
+            Self::Downloads => 60,
+            Self::DependencyTree => 10,
+        }
+    }
+
+    pub fn env_var_key(&self) -> &'static str {
+        match self {
+            Self::ListCrates => "LIST_CRATES",
+            Self::ReverseDependencies => "REVERSE_DEPENDENCIES",
+            Self::Downloads => "DOWNLOADS",
+            Self::DependencyTree => "DEPENDENCY_TREE",
+        }
+    }
+}
+
+/// Identifies the client that a bucket belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClientKey {
+    /// The ID of the API token that the request was authenticated with.
+    Token(i32),
+    /// The real IP address of the client.
+    Ip(IpAddr),
+}
+
+/// The state of a client's bucket after a request was counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    /// The maximum number of requests that can be made in a burst.
+    pub limit: u32,
+    /// The number of requests that can still be made right now.
+    pub remaining: u32,
+    /// The time until the bucket is completely refilled.
+    pub reset: Duration,
+    /// The time until the next request is allowed, if the request was
+    /// rejected.
+    pub retry_after: Option<Duration>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl Bucket {
+    fn take(&mut self, config: &RateLimiterConfig, now: Instant) -> RateLimitStatus {
+        let burst = config.burst.max(1) as f64;
+        let rate = config.rate.as_secs_f64();
+
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed / rate).min(burst);
+        self.last_refill = now;
+
+        let retry_after = if self.tokens >= 1. {
+            self.tokens -= 1.;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1. - self.tokens) * rate))
+        };
+
+        RateLimitStatus {
+            limit: burst as u32,
+            remaining: self.tokens.floor() as u32,
+            reset: Duration::from_secs_f64((burst - self.tokens) * rate),
+            retry_after,
+        }
+    }
+}
+
+type BucketCache =
+    moka::future::Cache<(ReadRouteGroup, ClientKey), Arc<parking_lot::Mutex<Bucket>>>;
+
+pub struct ReadRateLimiter {
+    config: HashMap<ReadRouteGroup, RateLimiterConfig>,
+    buckets: BucketCache,
+}
+
+impl ReadRateLimiter {
+    pub fn new(config: HashMap<ReadRouteGroup, RateLimiterConfig>) -> Self {
+        // Buckets that have not been used for longer than it takes to
+        // completely refill them are equivalent to new buckets, so they
+        // can be safely evicted.
+        let time_to_idle = config
+            .values()
+            .map(|config| config.rate * config.burst.max(1) as u32)
+            .max()
+            .unwrap_or_default()
+            .max(Duration::from_secs(1));
+
+        let buckets = moka::future::CacheBuilder::new(MAX_BUCKETS)
+            .time_to_idle(time_to_idle)
+            .build();
+
+        Self { config, buckets }
+    }
+
+    /// Returns `true` if requests to the given route group are rate limited.
+    pub fn is_enabled(&self, group: ReadRouteGroup) -> bool {
+        self.config.contains_key(&group)
+    }
+
+    /// Counts a request of `client` to the given route group.
+    ///
+    /// Returns `None` if the route group is not rate limited.
+    pub async fn check(&self, group: ReadRouteGroup, client: ClientKey) -> Option<RateLimitStatus> {
+        self.check_at(group, client, Instant::now()).await
+    }
+
+    async fn check_at(
+        &self,
+        group: ReadRouteGroup,
+        client: ClientKey,
+        now: Instant,
+    ) -> Option<RateLimitStatus> {
+        let config = self.config.get(&group)?;
+
+        let bucket = self
+            .buckets
+            .get_with((group, client), async {
+                let tokens = config.burst.max(1) as f64;
+                let bucket = Bucket {
+                    tokens,
+                    last_refill: now,
+                };
+                Arc::new(parking_lot::Mutex::new(bucket))
+            })
+            .await;
+
+        let status = bucket.lock().take(config, now);
+        Some(status)
+    }
+}
+
+impl std::fmt::Debug for ReadRateLimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReadRateLimiter")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(rate: Duration, burst: i32) -> ReadRateLimiter {
+        let config = RateLimiterConfig { rate, burst };
+        ReadRateLimiter::new(HashMap::from([(ReadRouteGroup::ListCrates, config)]))
+    }
+
+    #[tokio::test]
+    async fn test_burst_then_reject() {
+        let limiter = limiter(Duration::from_secs(1), 3);
+        let client = ClientKey::Ip([127, 0, 0, 1].into());
+        let group = ReadRouteGroup::ListCrates;
+        let now = Instant::now();
+
+        for remaining in [2, 1, 0] {
+            let status = limiter.check_at(group, client, now).await.unwrap();
+            assert_eq!(status.remaining, remaining);
+            assert_eq!(status.limit, 3);
+            assert_eq!(status.retry_after, None);
+        }
+
+        let status = limiter.check_at(group, client, now).await.unwrap();
+        assert_eq!(status.remaining, 0);
+        assert_eq!(status.retry_after, Some(Duration::from_secs(1)));
+        assert_eq!(status.reset, Duration::from_secs(3));
+
+        // Half a second later, the next token is not available yet
+        let now = now + Duration::from_millis(500);
+        let status = limiter.check_at(group, client, now).await.unwrap();
+        assert_eq!(status.retry_after, Some(Duration::from_millis(500)));
+
+        // A second later, a new token has been added to the bucket
+        let now = now + Duration::from_millis(500);
+        let status = limiter.check_at(group, client, now).await.unwrap();
+        assert_eq!(status.retry_after, None);
+        assert_eq!(status.remaining, 0);
+    }
+
+    #[tokio::test]
+    async fn test_clients_have_separate_buckets() {
+        let limiter = limiter(Duration::from_secs(60), 1);
+        let group = ReadRouteGroup::ListCrates;
+        let now = Instant::now();
+
+        let ip = ClientKey::Ip([127, 0, 0, 1].into());
+        let token = ClientKey::Token(42);
+
+        let status = limiter.check_at(group, ip, now).await.unwrap();
+        assert_eq!(status.retry_after, None);
+        let status = limiter.check_at(group, ip, now).await.unwrap();
+        assert!(status.retry_after.is_some());
+
+        let status = limiter.check_at(group, token, now).await.unwrap();
+        assert_eq!(status.retry_after, None);
+    }
+
+    #[tokio::test]
+    async fn test_unconfigured_groups_are_not_limited() {
+        let limiter = limiter(Duration::from_secs(60), 1);
+        let client = ClientKey::Ip([127, 0, 0, 1].into());
+
+        assert!(!limiter.is_enabled(ReadRouteGroup::Downloads));
+        let status = limiter.check(ReadRouteGroup::Downloads, client).await;
+        assert_eq!(status, None);
+    }
+
+    #[test]
+    fn test_from_route() {
+        assert_eq!(
+            ReadRouteGroup::from_route("/api/v1/crates"),
+            Some(ReadRouteGroup::ListCrates)
+        );
+        assert_eq!(
+            ReadRouteGroup::from_route("/api/v1/crates/{name}/{version}/downloads"),
+            Some(ReadRouteGroup::Downloads)
+        );
+        assert_eq!(
+            ReadRouteGroup::from_route("/api/v1/crates/{name}/{version}/dependency_tree"),
+            Some(ReadRouteGroup::DependencyTree)
+        );
+        assert_eq!(ReadRouteGroup::from_route("/api/v1/crates/{name}"), None);
+    }
+}
@@ -1,5 +1,5 @@
 use axum::response::IntoResponse;
-use axum::routing::{get, post};
+use axum::routing::{get, post, put};
 use axum::{Json, Router};
 use http::{Method, StatusCode};
 use utoipa_axum::routes;
@@ -34,17 +34,35 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
             krate::update::update_crate,
             krate::delete::delete_crate
         ))
+        .routes(routes!(
+            krate::publish_policy::get_publish_policy,
+            krate::publish_policy::update_publish_policy
+        ))
+        .routes(routes!(
+            krate::delete::get_scheduled_deletion,
+            krate::delete::cancel_scheduled_deletion
+        ))
         .routes(routes!(
             version::metadata::find_version,
             version::update::update_version
         ))
         .routes(routes!(version::readme::get_version_readme))
         .routes(routes!(version::dependencies::get_version_dependencies))
+        .routes(routes!(
+            version::dependency_tree::get_version_dependency_tree
+        ))
+        .routes(routes!(version::semver_check::get_version_semver_check))
+        .routes(routes!(version::approval::approve_version))
+        .routes(routes!(version::approval::reject_version))
         .routes(routes!(version::downloads::get_version_downloads))
-        .routes(routes!(version::docs::rebuild_version_docs))
+        .routes(routes!(
+            version::docs::rebuild_version_docs,
+            version::docs::list_version_docs_rebuilds
+        ))
         .routes(routes!(version::authors::get_version_authors))
         .routes(routes!(krate::downloads::get_crate_downloads))
         .routes(routes!(krate::versions::list_versions))
+        .routes(routes!(version::approval::list_pending_versions))
         .routes(routes!(
             krate::follow::follow_crate,
             krate::follow::unfollow_crate
@@ -53,19 +71,42 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
         .routes(routes!(krate::owners::get_team_owners))
         .routes(routes!(krate::owners::get_user_owners))
         .routes(routes!(krate::rev_deps::list_reverse_dependencies))
+        .routes(routes!(
+            krate::transfer_requests::list_transfer_requests,
+            krate::transfer_requests::create_transfer_request
+        ))
+        .routes(routes!(
+            krate::transfer_requests::respond_to_transfer_request
+        ))
+        .routes(routes!(binary::find_binary))
         .routes(routes!(keyword::list_keywords))
         .routes(routes!(keyword::find_keyword))
         .routes(routes!(category::list_categories))
         .routes(routes!(category::find_category))
         .routes(routes!(category::list_category_slugs))
+        .routes(routes!(
+            category_proposal::list_category_proposals,
+            category_proposal::create_category_proposal
+        ))
         .routes(routes!(user::other::find_user, user::update::update_user))
         .routes(routes!(user::other::get_user_stats))
         .routes(routes!(team::find_team))
         .routes(routes!(user::me::get_authenticated_user))
         .routes(routes!(user::me::get_authenticated_user_updates))
+        .routes(routes!(user::account_recovery::lock_authenticated_user))
+        .routes(routes!(user::account_recovery::request_account_recovery))
+        .routes(routes!(user::account_recovery::confirm_account_recovery))
+        .routes(routes!(user::account_recovery::cancel_github_link))
+        .routes(routes!(
+            user::updates_feed::regenerate_feed_token,
+            user::updates_feed::revoke_feed_token
+        ))
+        .routes(routes!(user::updates_feed::get_updates_rss_feed))
+        .routes(routes!(user::updates_feed::get_updates_atom_feed))
         .routes(routes!(token::list_api_tokens, token::create_api_token))
         .routes(routes!(token::find_api_token, token::revoke_api_token))
         .routes(routes!(token::revoke_current_api_token))
+        .routes(routes!(token::exchange_api_token))
         .routes(routes!(
             crate_owner_invitation::list_crate_owner_invitations_for_user
         ))
@@ -111,11 +152,31 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
         .route("/api/private/metrics/{kind}", get(metrics::prometheus))
         // Listing a user's crates for admin/support purposes
         .route("/api/private/admin_list/{username}", get(admin::list))
+        // Locking and unlocking user accounts by admins
+        .route(
+            "/api/private/admin/users/{username}/lock",
+            put(admin::lock_user).delete(admin::unlock_user),
+        )
+        .route(
+            "/api/private/admin/users/{username}/lock_events",
+            get(admin::list_lock_events),
+        )
+        // Curation of categories and keywords by admins
+        .route(
+            "/api/private/admin/category_proposals/{id}",
+            put(category_proposal::resolve_category_proposal),
+        )
+        .route(
+            "/api/private/admin/keywords/{keyword}/merge",
+            put(keyword::merge_keyword),
+        )
         // Alerts from GitHub scanning for exposed API tokens
         .route(
             "/api/github/secret-scanning/verify",
             post(github::secret_scanning::verify),
-        );
+        )
+        // Alerts from other secret scanners with a registered public key
+        .route("/api/secret-scanning/revoke", post(secret_scanning::revoke));
 
     // Only serve the local checkout of the git index in development mode.
     // In production, for crates.io, cargo gets the index from
@@ -21,6 +21,7 @@ use tokio::io::AsyncWriteExt;
 use tracing::{instrument, warn};
 
 const PREFIX_CRATES: &str = "crates";
+const PREFIX_PENDING_CRATES: &str = "pending-crates";
 const PREFIX_READMES: &str = "readmes";
 const PREFIX_OG_IMAGES: &str = "og-images";
 const DEFAULT_REGION: &str = "us-west-1";
@@ -227,6 +228,11 @@ impl Storage {
         apply_cdn_prefix(&self.cdn_prefix, &feed_id.into()).replace('+', "%2B")
     }
 
+    /// Returns the URL of the Atom variant of an uploaded feed.
+    pub fn atom_feed_url(&self, feed_id: &FeedId<'_>) -> String {
+        apply_cdn_prefix(&self.cdn_prefix, &feed_id.atom_path()).replace('+', "%2B")
+    }
+
     /// Deletes all crate files for the given crate, returning the paths that were deleted.
     #[instrument(skip(self))]
     pub async fn delete_all_crate_files(&self, name: &str) -> Result<Vec<Path>> {
@@ -247,6 +253,12 @@ impl Storage {
         self.store.delete(&path).await
     }
 
+    #[instrument(skip(self))]
+    pub async fn delete_pending_crate_file(&self, name: &str, version: &str) -> Result<()> {
+        let path = pending_crate_file_path(name, version);
+        self.store.delete(&path).await
+    }
+
     #[instrument(skip(self))]
     pub async fn delete_readme(&self, name: &str, version: &str) -> Result<()> {
         let path = readme_path(name, version);
@@ -260,10 +272,12 @@ impl Storage {
         self.store.delete(&path).await
     }
 
+    /// Deletes the RSS feed and its Atom variant.
     #[instrument(skip(self))]
     pub async fn delete_feed(&self, feed_id: &FeedId<'_>) -> Result<()> {
         let path = feed_id.into();
-        self.store.delete(&path).await
+        self.store.delete(&path).await?;
+        self.store.delete(&feed_id.atom_path()).await
     }
 
     #[instrument(skip(self, bytes))]
@@ -278,6 +292,33 @@ impl Storage {
         Ok(())
     }
 
+    /// Uploads the crate file of a version that is waiting for approval.
+    ///
+    /// Pending crate files are stored outside of the `crates` prefix, so
+    /// that they are not available via the CDN until the version is approved
+    /// and [`Self::publish_pending_crate_file()`] is called.
+    #[instrument(skip(self, bytes))]
+    pub async fn upload_pending_crate_file(
+        &self,
+        name: &str,
+        version: &str,
+        bytes: Bytes,
+    ) -> Result<()> {
+        let path = pending_crate_file_path(name, version);
+        self.store.put(&path, bytes.into()).await?;
+        Ok(())
+    }
+
+    /// Moves the crate file of an approved version from the pending prefix
+    /// to its public location.
+    #[instrument(skip(self))]
+    pub async fn publish_pending_crate_file(&self, name: &str, version: &str) -> Result<()> {
+        let path = pending_crate_file_path(name, version);
+        let bytes = self.store.get(&path).await?.bytes().await?;
+        self.upload_crate_file(name, version, bytes).await?;
+        self.store.delete(&path).await
+    }
+
     #[instrument(skip(self))]
     pub async fn download_crate_file(
         &self,
@@ -333,6 +374,28 @@ impl Storage {
         Ok(())
     }
 
+    #[instrument(skip(self, feed))]
+    pub async fn upload_atom_feed(
+        &self,
+        feed_id: &FeedId<'_>,
+        feed: &atom_syndication::Feed,
+    ) -> anyhow::Result<()> {
+        let path = feed_id.atom_path();
+
+        let config = atom_syndication::WriteConfig {
+            write_document_declaration: true,
+            indent_size: Some(4),
+        };
+        let buffer = feed.write_with_config(Vec::new(), config)?;
+        let payload = PutPayload::from_bytes(buffer.into());
+
+        let content_type = "application/atom+xml; charset=UTF-8";
+        let attributes = self.attrs([(Attribute::ContentType, content_type)]);
+        let opts = attributes.into();
+        self.store.put_opts(&path, payload, opts).await?;
+        Ok(())
+    }
+
     #[instrument(skip(self, content))]
     pub async fn sync_index(&self, name: &str, content: Option<String>) -> Result<()> {
         let path = crates_io_index::Repository::relative_index_file_for_url(name).into();
@@ -418,6 +481,10 @@ fn crate_file_path(name: &str, version: &str) -> Path {
     format!("{PREFIX_CRATES}/{name}/{name}-{version}.crate").into()
 }
 
+fn pending_crate_file_path(name: &str, version: &str) -> Path {
+    format!("{PREFIX_PENDING_CRATES}/{name}/{name}-{version}.crate").into()
+}
+
 fn readme_path(name: &str, version: &str) -> Path {
     format!("{PREFIX_READMES}/{name}/{name}-{version}.html").into()
 }
@@ -438,18 +505,53 @@ fn apply_cdn_prefix(cdn_prefix: &Option<String>, path: &Path) -> String {
 
 #[derive(Debug)]
 pub enum FeedId<'a> {
-    Crate { name: &'a str },
+    Crate {
+        name: &'a str,
+    },
     Crates,
     Updates,
+    /// Releases of all crates owned by a user.
+    User {
+        login: &'a str,
+    },
+    /// Releases of all crates owned by a team.
+    Team {
+        login: &'a str,
+    },
+    /// Releases of all crates with a keyword.
+    Keyword {
+        keyword: &'a str,
+    },
+    /// Releases of all crates in a category or its subcategories.
+    Category {
+        slug: &'a str,
+    },
+}
+
+impl FeedId<'_> {
+    /// The path of the feed relative to the `rss/` and `atom/` directories,
+    /// without file extension.
+    fn relative_path(&self) -> String {
+        match self {
+            FeedId::Crate { name } => format!("crates/{name}"),
+            FeedId::Crates => "crates".into(),
+            FeedId::Updates => "updates".into(),
+            FeedId::User { login } => format!("users/{login}"),
+            FeedId::Team { login } => format!("teams/{login}"),
+            FeedId::Keyword { keyword } => format!("keywords/{keyword}"),
+            FeedId::Category { slug } => format!("categories/{slug}"),
+        }
+    }
+
+    /// Returns the path of the Atom variant of the feed.
+    pub fn atom_path(&self) -> Path {
+        format!("atom/{}.xml", self.relative_path()).into()
+    }
 }
 
 impl From<&FeedId<'_>> for Path {
     fn from(feed_id: &FeedId<'_>) -> Path {
-        match feed_id {
-            FeedId::Crate { name } => format!("rss/crates/{name}.xml").into(),
-            FeedId::Crates => "rss/crates.xml".into(),
-            FeedId::Updates => "rss/updates.xml".into(),
-        }
+        format!("rss/{}.xml", feed_id.relative_path()).into()
     }
 }
 
@@ -662,6 +764,23 @@ mod tests {
         assert_eq!(stored_files(&s.store).await, expected_files);
     }
 
+    #[tokio::test]
+    async fn publish_pending_crate_file() {
+        let s = Storage::from_config(&StorageConfig::in_memory());
+
+        s.upload_pending_crate_file("foo", "1.2.3", Bytes::new())
+            .await
+            .unwrap();
+
+        let expected_files = vec!["pending-crates/foo/foo-1.2.3.crate"];
+        assert_eq!(stored_files(&s.store).await, expected_files);
+
+        s.publish_pending_crate_file("foo", "1.2.3").await.unwrap();
+
+        let expected_files = vec!["crates/foo/foo-1.2.3.crate"];
+        assert_eq!(stored_files(&s.store).await, expected_files);
+    }
+
     #[tokio::test]
     async fn upload_readme() {
         let s = Storage::from_config(&StorageConfig::in_memory());
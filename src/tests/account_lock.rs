@@ -54,3 +54,117 @@ async fn expired_account_lock() {
 
     user.get::<serde_json::Value>(URL).await.good();
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn admin_lock_and_unlock() -> anyhow::Result<()> {
+    use insta::assert_json_snapshot;
+    use serde_json::json;
+
+    const LOCK_URL: &str = "/api/private/admin/users/foo/lock";
+    const EVENTS_URL: &str = "/api/private/admin/users/foo/lock_events";
+
+    let (app, _anon, user) = TestApp::init().with_user().await;
+
+    let admin = app.db_new_admin("admin").await;
+
+    let body = json!({ "reason": "spam" }).to_string();
+    let response = user.put::<()>(LOCK_URL, body.clone()).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"must be an admin to use this route"}]}"#);
+
+    let response = admin
+        .put::<()>(LOCK_URL, json!({ "reason": " " }).to_string())
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"a reason for the lock is required"}]}"#);
+
+    let body = json!({ "reason": "spam", "until": "2020-01-01T00:00:00Z" }).to_string();
+    let response = admin.put::<()>(LOCK_URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the lock expiry must be in the future"}]}"#);
+
+    let body = json!({ "reason": "spam", "until": "2099-12-12T12:12:12Z" }).to_string();
+    let response = admin.put::<()>(LOCK_URL, body).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let response = user.get::<()>(URL).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"This account is locked until 2099-12-12 at 12:12:12 UTC. Reason: spam"}]}"#);
+
+    let body = json!({ "reason": "appeal accepted" }).to_string();
+    let response = admin.delete_with_body::<()>(LOCK_URL, body.clone()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    user.get::<serde_json::Value>(URL).await.good();
+
+    let response = admin.delete_with_body::<()>(LOCK_URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the account is not locked"}]}"#);
+
+    let response = admin.get::<()>(EVENTS_URL).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json(), {
+        ".events[].created_at" => "[datetime]",
+    });
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn self_lock_revokes_tokens() {
+    let (app, _anon, user, token) = TestApp::init().with_token().await;
+
+    let response = token.post::<()>("/api/v1/me/lock", "").await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_snapshot!(app.emails_snapshot().await);
+
+    let response = user.get::<()>(URL).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"This account is indefinitely locked. Reason: The account was locked by its owner. It can be unlocked via account recovery."}]}"#);
+
+    // Unlocking the account does not restore the revoked tokens
+    let mut conn = app.db_conn().await;
+    lock_account(
+        &app,
+        user.as_model().id,
+        Some(Utc::now() - Duration::days(1)),
+    )
+    .await;
+    let revoked = {
+        use crates_io::schema::api_tokens;
+        use diesel::prelude::*;
+        use diesel_async::RunQueryDsl;
+
+        api_tokens::table
+            .select(api_tokens::revoked)
+            .get_result::<bool>(&mut conn)
+            .await
+            .unwrap()
+    };
+    assert!(revoked);
+
+    let response = token.get::<()>(URL).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn self_lock_requires_verified_email() {
+    use crates_io::schema::emails;
+    use diesel::prelude::*;
+    use diesel_async::RunQueryDsl;
+
+    let (app, _anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    diesel::update(emails::table)
+        .set(emails::verified.eq(false))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let response = user.post::<()>("/api/v1/me/lock", "").await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"A verified email address is required to lock your account, since it is needed to recover the account afterwards."}]}"#);
+
+    user.get::<serde_json::Value>(URL).await.good();
+}
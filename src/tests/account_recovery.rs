@@ -0,0 +1,323 @@
+use crate::TestApp;
+use crate::util::github::next_gh_id;
+use crate::util::{MockAnonymousUser, MockTokenUser, RequestHelper};
+use chrono::{TimeDelta, Utc};
+use claims::{assert_err, assert_ok};
+use crates_io::controllers::session;
+use crates_io::models::{OauthGithub, PendingGitHubLink, User};
+use crates_io::schema::{api_tokens, emails, oauth_github, pending_github_links};
+use crates_io_github::GitHubUser;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use insta::assert_snapshot;
+use quoted_printable::{ParseMode, decode};
+use serde_json::json;
+
+const URL: &str = "/api/private/account_recovery";
+
+impl MockAnonymousUser {
+    async fn request_recovery(&self, email: &str) {
+        let body = json!({ "email": email }).to_string();
+        self.post::<serde_json::Value>(URL, body).await.good();
+    }
+}
+
+fn extract_token_from_cancel_email(emails: &[String]) -> String {
+    let body = emails
+        .iter()
+        .rfind(|m| m.contains("Subject: crates.io: A different GitHub account will be linked"))
+        .expect("missing email");
+
+    // The cancel link is too long to fit on one line of the encoded email
+    let body = decode(body, ParseMode::Robust).unwrap();
+    let body = String::from_utf8_lossy(&body);
+
+    let before_token = "/account-recovery/cancel/";
+    let before_pos = body.find(before_token).unwrap() + before_token.len();
+    let after_pos = before_pos + body[before_pos..].find(char::is_whitespace).unwrap();
+    body[before_pos..after_pos].to_string()
+}
+
+fn extract_token_from_recovery_email(emails: &[String]) -> String {
+    let body = emails
+        .iter()
+        .rfind(|m| m.contains("Subject: crates.io: Recover your account"))
+        .expect("missing email");
+
+    let before_token = "/account-recovery/";
+    let before_pos = body.find(before_token).unwrap() + before_token.len();
+    let after_pos = before_pos + body[before_pos..].find(char::is_whitespace).unwrap();
+    body[before_pos..after_pos].to_string()
+}
+
+async fn token_revoked(conn: &mut AsyncPgConnection, token: &MockTokenUser) -> QueryResult<bool> {
+    api_tokens::table
+        .find(token.as_model().id)
+        .select(api_tokens::revoked)
+        .get_result(conn)
+        .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn recovery_unlocks_self_locked_account() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let emails = &app.as_inner().emails;
+
+    let response = user.post::<()>("/api/v1/me/lock", "").await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let response = user.get::<()>("/api/v1/me").await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    // Email addresses are matched case-insensitively
+    anon.request_recovery("FOO@example.com").await;
+    assert_eq!(app.emails().await.len(), 2);
+
+    // Repeated requests don't send more emails
+    anon.request_recovery("foo@example.com").await;
+    assert_eq!(app.emails().await.len(), 2);
+
+    let token = extract_token_from_recovery_email(&app.emails().await);
+    let response = anon.put::<()>(&format!("{URL}/{token}"), "").await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    // Existing sessions stay locked until the recovery session has logged in
+    // with GitHub
+    let response = user.get::<()>("/api/v1/me").await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    let gh_user = GitHubUser {
+        id: user.as_model().gh_id,
+        login: "foo".to_string(),
+        name: None,
+        email: None,
+        avatar_url: None,
+    };
+    let user_id = user.as_model().id;
+    let result = session::link_github_account(user_id, &gh_user, b"token", emails, &mut conn).await;
+    assert_ok!(result);
+
+    user.get::<serde_json::Value>("/api/v1/me").await.good();
+
+    // Recovery tokens can only be used once
+    let response = anon.put::<()>(&format!("{URL}/{token}"), "").await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid or expired account recovery token"}]}"#);
+
+    assert_snapshot!(app.emails_snapshot().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn recovery_keeps_self_lock_until_github_link_is_applied() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let emails = &app.as_inner().emails;
+    let user_id = user.as_model().id;
+
+    let response = user.post::<()>("/api/v1/me/lock", "").await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    anon.request_recovery("foo@example.com").await;
+    let token = extract_token_from_recovery_email(&app.emails().await);
+    let response = anon.put::<()>(&format!("{URL}/{token}"), "").await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let gh_user = GitHubUser {
+        id: next_gh_id(),
+        login: "foo-new".to_string(),
+        name: None,
+        email: None,
+        avatar_url: None,
+    };
+    let result = session::link_github_account(user_id, &gh_user, b"token", emails, &mut conn).await;
+    assert_err!(result);
+
+    // The previous GitHub account might be compromised, so its sessions stay
+    // locked during the waiting period
+    let response = user.get::<()>("/api/v1/me").await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    diesel::update(pending_github_links::table)
+        .set(pending_github_links::effective_at.eq(Utc::now() - TimeDelta::minutes(1)))
+        .execute(&mut conn)
+        .await?;
+
+    let result = session::log_in_github_user(&gh_user, b"token", emails, &mut conn).await;
+    assert_ok!(result);
+
+    user.get::<serde_json::Value>("/api/v1/me").await.good();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn recovery_keeps_admin_lock() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+
+    let admin = app.db_new_admin("admin").await;
+
+    let body = json!({ "reason": "malware" }).to_string();
+    let response = admin
+        .put::<()>("/api/private/admin/users/foo/lock", body)
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    anon.request_recovery("foo@example.com").await;
+    let token = extract_token_from_recovery_email(&app.emails().await);
+    let response = anon.put::<()>(&format!("{URL}/{token}"), "").await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let response = user.get::<()>("/api/v1/me").await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"This account is indefinitely locked. Reason: malware"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn recovery_requires_verified_email() {
+    let (app, anon, _user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    anon.request_recovery("unknown@example.com").await;
+    assert_eq!(app.emails().await.len(), 0);
+
+    diesel::update(emails::table)
+        .set(emails::verified.eq(false))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    anon.request_recovery("foo@example.com").await;
+    assert_eq!(app.emails().await.len(), 0);
+
+    let response = anon.put::<()>(&format!("{URL}/invalid"), "").await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid or expired account recovery token"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn link_github_account() -> anyhow::Result<()> {
+    let (app, _anon, user, token) = TestApp::init().with_token().await;
+    let mut conn = app.db_conn().await;
+    let emails = &app.as_inner().emails;
+    let user_id = user.as_model().id;
+
+    let other_user = app.db_new_user("bar").await;
+
+    // A GitHub account that is linked to another user can't be used
+    let gh_user = GitHubUser {
+        id: other_user.as_model().gh_id,
+        login: "bar".to_string(),
+        name: None,
+        email: None,
+        avatar_url: None,
+    };
+    let result = session::link_github_account(user_id, &gh_user, &[], emails, &mut conn).await;
+    assert_err!(result);
+
+    // The details of the same GitHub account are updated immediately
+    let gh_user = GitHubUser {
+        id: user.as_model().gh_id,
+        login: "foo-renamed".to_string(),
+        name: None,
+        email: None,
+        avatar_url: None,
+    };
+    let result = session::link_github_account(user_id, &gh_user, b"token", emails, &mut conn).await;
+    let linked_user = assert_ok!(result);
+    assert_eq!(linked_user.id, user_id);
+    assert_eq!(linked_user.gh_login, "foo-renamed");
+    assert!(!token_revoked(&mut conn, &token).await?);
+
+    // A different GitHub account is only linked after the waiting period
+    let gh_user = GitHubUser {
+        id: next_gh_id(),
+        login: "foo-new".to_string(),
+        name: None,
+        email: None,
+        avatar_url: None,
+    };
+    let result = session::link_github_account(user_id, &gh_user, b"token", emails, &mut conn).await;
+    let error = assert_err!(result).to_string();
+    assert!(error.starts_with("This GitHub account will be linked to your crates.io account on "));
+
+    let user = User::find(&mut conn, user_id).await?;
+    assert_ne!(user.gh_id, gh_user.id);
+    assert_snapshot!(app.emails_snapshot().await);
+
+    let result = session::log_in_github_user(&gh_user, b"token", emails, &mut conn).await;
+    assert_err!(result);
+
+    diesel::update(pending_github_links::table)
+        .set(pending_github_links::effective_at.eq(Utc::now() - TimeDelta::minutes(1)))
+        .execute(&mut conn)
+        .await?;
+
+    let result = session::log_in_github_user(&gh_user, b"token", emails, &mut conn).await;
+    let linked_user = assert_ok!(result);
+    assert_eq!(linked_user.id, user_id);
+    assert_eq!(linked_user.gh_id, gh_user.id);
+    assert_eq!(linked_user.gh_login, "foo-new");
+
+    let oauth_accounts: Vec<OauthGithub> = oauth_github::table
+        .filter(oauth_github::user_id.eq(user_id))
+        .select(OauthGithub::as_select())
+        .load(&mut conn)
+        .await?;
+    assert_eq!(oauth_accounts.len(), 1);
+    assert_eq!(oauth_accounts[0].account_id, gh_user.id as i64);
+    assert_eq!(oauth_accounts[0].login, "foo-new");
+
+    // Linking a different GitHub account revokes all API tokens
+    assert!(token_revoked(&mut conn, &token).await?);
+
+    let pending_link = PendingGitHubLink::find_by_gh_id(&mut conn, gh_user.id).await?;
+    assert!(pending_link.is_none());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cancel_github_link() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let emails = &app.as_inner().emails;
+    let user_id = user.as_model().id;
+
+    anon.request_recovery("foo@example.com").await;
+
+    let gh_user = GitHubUser {
+        id: next_gh_id(),
+        login: "foo-new".to_string(),
+        name: None,
+        email: None,
+        avatar_url: None,
+    };
+    let result = session::link_github_account(user_id, &gh_user, b"token", emails, &mut conn).await;
+    assert_err!(result);
+
+    let token = extract_token_from_cancel_email(&app.emails().await);
+    let response = anon
+        .delete::<()>(&format!("{URL}/github_link/{token}"))
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let pending_link = PendingGitHubLink::find_by_gh_id(&mut conn, gh_user.id).await?;
+    assert!(pending_link.is_none());
+
+    // Cancelling also invalidates the unused recovery token
+    let recovery_token = extract_token_from_recovery_email(&app.emails().await);
+    let response = anon.put::<()>(&format!("{URL}/{recovery_token}"), "").await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+
+    let response = anon
+        .delete::<()>(&format!("{URL}/github_link/{token}"))
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid or already used cancel token"}]}"#);
+
+    let user = User::find(&mut conn, user_id).await?;
+    assert_ne!(user.gh_id, gh_user.id);
+
+    Ok(())
+}
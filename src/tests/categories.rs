@@ -1,5 +1,6 @@
 use claims::assert_ok;
-use crates_io::schema::categories;
+use crates_io::models::NewCategoryProposal;
+use crates_io::schema::{categories, users};
 use crates_io_test_db::TestDatabase;
 use diesel::*;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
@@ -101,3 +102,45 @@ async fn test_real_categories() {
     const TOML: &str = include_str!("../boot/categories.toml");
     assert_ok!(crates_io::boot::categories::sync_with_connection(TOML, &mut conn).await);
 }
+
+#[tokio::test]
+async fn sync_keeps_approved_proposals() {
+    let test_db = TestDatabase::new();
+    let mut conn = test_db.async_connect().await;
+
+    crates_io::boot::categories::sync_with_connection(ALGORITHMS, &mut conn)
+        .await
+        .unwrap();
+
+    let user_id = diesel::insert_into(users::table)
+        .values((
+            users::name.eq("foo"),
+            users::gh_login.eq("foo"),
+            users::gh_id.eq(42),
+            users::gh_encrypted_token.eq(&[]),
+        ))
+        .returning(users::id)
+        .get_result::<i32>(&mut conn)
+        .await
+        .unwrap();
+
+    let new_proposal = NewCategoryProposal {
+        slug: "algorithms::sorting",
+        name: "Sorting",
+        description: "Sorting algorithms",
+        proposed_by: user_id,
+    };
+    let proposal = new_proposal.insert(&mut conn).await.unwrap().unwrap();
+    let category = proposal.approve(&mut conn, user_id).await.unwrap().unwrap();
+    assert_eq!(category.category, "Algorithms::Sorting");
+
+    crates_io::boot::categories::sync_with_connection(ALGORITHMS_AND_ANOTHER, &mut conn)
+        .await
+        .unwrap();
+
+    let categories = select_slugs(&mut conn).await;
+    assert_eq!(
+        categories,
+        vec!["algorithms", "algorithms::sorting", "another"]
+    );
+}
@@ -1,10 +1,10 @@
 use crate::builders::CrateBuilder;
 use crate::util::TestApp;
 use bytes::Buf;
-use crates_io::worker::jobs::DumpDb;
+use crates_io::worker::jobs::{DumpDb, DumpDbIncremental};
 use crates_io_worker::BackgroundJob;
 use flate2::read::GzDecoder;
-use insta::{assert_debug_snapshot, assert_snapshot};
+use insta::{assert_debug_snapshot, assert_json_snapshot, assert_snapshot};
 use object_store::ObjectStoreExt;
 use regex::Regex;
 use std::io::{Cursor, Read};
@@ -14,6 +14,9 @@ use tar::Archive;
 static PATH_DATE_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}-\d{6}").unwrap());
 
+static INCREMENT_PATH_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^db-dump-incremental/\d{4}-\d{2}-\d{2}-\d{6}").unwrap());
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_dump_db_job() -> anyhow::Result<()> {
     let (app, _, _, token) = TestApp::full().with_token().await;
@@ -28,6 +31,24 @@ async fn test_dump_db_job() -> anyhow::Result<()> {
     app.run_pending_background_jobs().await;
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    db-dump-parquet/categories.parquet
+    db-dump-parquet/crate_downloads.parquet
+    db-dump-parquet/crate_owners.parquet
+    db-dump-parquet/crates.parquet
+    db-dump-parquet/crates_categories.parquet
+    db-dump-parquet/crates_keywords.parquet
+    db-dump-parquet/default_versions.parquet
+    db-dump-parquet/deleted_crates.parquet
+    db-dump-parquet/dependencies.parquet
+    db-dump-parquet/keyword_aliases.parquet
+    db-dump-parquet/keywords.parquet
+    db-dump-parquet/metadata.parquet
+    db-dump-parquet/pending_versions.parquet
+    db-dump-parquet/reserved_crate_names.parquet
+    db-dump-parquet/teams.parquet
+    db-dump-parquet/users.parquet
+    db-dump-parquet/version_downloads.parquet
+    db-dump-parquet/versions.parquet
     db-dump.tar.gz
     db-dump.zip
     ");
@@ -59,11 +80,13 @@ async fn test_dump_db_job() -> anyhow::Result<()> {
         "YYYY-MM-DD-HHMMSS/data/users.csv",
         "YYYY-MM-DD-HHMMSS/data/crates_categories.csv",
         "YYYY-MM-DD-HHMMSS/data/crates_keywords.csv",
+        "YYYY-MM-DD-HHMMSS/data/keyword_aliases.csv",
         "YYYY-MM-DD-HHMMSS/data/crate_owners.csv",
         "YYYY-MM-DD-HHMMSS/data/deleted_crates.csv",
         "YYYY-MM-DD-HHMMSS/data/versions.csv",
         "YYYY-MM-DD-HHMMSS/data/default_versions.csv",
         "YYYY-MM-DD-HHMMSS/data/dependencies.csv",
+        "YYYY-MM-DD-HHMMSS/data/pending_versions.csv",
         "YYYY-MM-DD-HHMMSS/data/version_downloads.csv",
     ]
     "#);
@@ -92,11 +115,13 @@ async fn test_dump_db_job() -> anyhow::Result<()> {
         "data/users.csv",
         "data/crates_categories.csv",
         "data/crates_keywords.csv",
+        "data/keyword_aliases.csv",
         "data/crate_owners.csv",
         "data/deleted_crates.csv",
         "data/versions.csv",
         "data/default_versions.csv",
         "data/dependencies.csv",
+        "data/pending_versions.csv",
         "data/version_downloads.csv",
     ]
     "#);
@@ -104,6 +129,116 @@ async fn test_dump_db_job() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_dump_db_incremental_job() -> anyhow::Result<()> {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+    let user_id = token.as_model().user_id;
+
+    CrateBuilder::new("test-crate", user_id)
+        .expect_build(&mut conn)
+        .await;
+
+    // Without a full dump to start from, no increment is created
+    DumpDbIncremental.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+    assert!(app.stored_files().await.is_empty());
+
+    DumpDb.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    CrateBuilder::new("other-crate", user_id)
+        .expect_build(&mut conn)
+        .await;
+
+    DumpDbIncremental.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let stored_files = app.stored_files().await;
+    let stored_files = stored_files
+        .iter()
+        .map(|path| INCREMENT_PATH_RE.replace(path, "db-dump-incremental/YYYY-MM-DD-HHMMSS"))
+        .collect::<Vec<_>>();
+    assert_snapshot!(stored_files.join("\n"), @r"
+    db-dump-incremental/YYYY-MM-DD-HHMMSS.tar.gz
+    db-dump-incremental/manifest.json
+    db-dump-parquet/categories.parquet
+    db-dump-parquet/crate_downloads.parquet
+    db-dump-parquet/crate_owners.parquet
+    db-dump-parquet/crates.parquet
+    db-dump-parquet/crates_categories.parquet
+    db-dump-parquet/crates_keywords.parquet
+    db-dump-parquet/default_versions.parquet
+    db-dump-parquet/deleted_crates.parquet
+    db-dump-parquet/dependencies.parquet
+    db-dump-parquet/keyword_aliases.parquet
+    db-dump-parquet/keywords.parquet
+    db-dump-parquet/metadata.parquet
+    db-dump-parquet/pending_versions.parquet
+    db-dump-parquet/reserved_crate_names.parquet
+    db-dump-parquet/teams.parquet
+    db-dump-parquet/users.parquet
+    db-dump-parquet/version_downloads.parquet
+    db-dump-parquet/versions.parquet
+    db-dump.tar.gz
+    db-dump.zip
+    ");
+
+    let path = object_store::path::Path::parse("db-dump-incremental/manifest.json")?;
+    let result = app.as_inner().storage.as_inner().get(&path).await?;
+    let manifest: serde_json::Value = serde_json::from_slice(&result.bytes().await?)?;
+    assert_json_snapshot!(manifest, {
+        ".**.timestamp" => "[datetime]",
+        ".**.since" => "[datetime]",
+        ".increments[].base" => "[datetime]",
+        ".increments[].path" => "[path]",
+    }, @r#"
+    {
+      "base": {
+        "path": "db-dump.tar.gz",
+        "timestamp": "[datetime]"
+      },
+      "increments": [
+        {
+          "base": "[datetime]",
+          "path": "[path]",
+          "since": "[datetime]",
+          "timestamp": "[datetime]"
+        }
+      ]
+    }
+    "#);
+
+    let path = manifest["increments"][0]["path"].as_str().unwrap();
+    let path = object_store::path::Path::parse(path)?;
+    let result = app.as_inner().storage.as_inner().get(&path).await?;
+    let bytes = result.bytes().await?;
+
+    let mut tar = Archive::new(GzDecoder::new(bytes.reader()));
+    let mut files = tar
+        .entries()?
+        .map(|entry| {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().display().to_string();
+            let path = PATH_DATE_RE.replace(&path, "YYYY-MM-DD-HHMMSS").to_string();
+            let mut content = String::new();
+            entry.read_to_string(&mut content).unwrap();
+            (path, content)
+        })
+        .collect::<std::collections::BTreeMap<_, _>>();
+
+    assert!(files.contains_key("YYYY-MM-DD-HHMMSS/data/tombstones.csv"));
+
+    let crates = files.remove("YYYY-MM-DD-HHMMSS/data/crates.csv").unwrap();
+    assert!(crates.contains("other-crate"));
+
+    let metadata = files.remove("YYYY-MM-DD-HHMMSS/metadata.json").unwrap();
+    let metadata: serde_json::Value = serde_json::from_str(&metadata)?;
+    assert!(metadata["since"].is_string());
+
+    Ok(())
+}
+
 fn tar_paths<R: Read>(archive: &mut Archive<R>) -> Vec<String> {
     archive
         .entries()
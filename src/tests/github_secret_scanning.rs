@@ -47,7 +47,7 @@ JQYV5+q0s3+JnBSLb1/sx/lEDzmMVZQIZQrACUHFW4UVdmox2NvmNWyy
 -----END PRIVATE KEY-----"#;
 
 /// Public key (corresponds to the private key above)
-static PUBLIC_KEY: &str = r#"-----BEGIN PUBLIC KEY-----
+pub(crate) static PUBLIC_KEY: &str = r#"-----BEGIN PUBLIC KEY-----
 MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEWCpG5E2yK///JJYNQjGBkCMLZXzX
 eCUGFefqtLN/iZwUi29f7Mf5RA85jFWUCGUKwAlBxVuFFXZqMdjb5jVssg==
 -----END PUBLIC KEY-----"#;
@@ -60,7 +60,7 @@ static SIGNING_KEY: LazyLock<SigningKey> =
     LazyLock::new(|| SigningKey::from_pkcs8_pem(PRIVATE_KEY).unwrap());
 
 /// Generate a signature for the payload using our private key
-fn sign_payload(payload: &[u8]) -> String {
+pub(crate) fn sign_payload(payload: &[u8]) -> String {
     let signature: Signature = SIGNING_KEY.sign(payload);
     general_purpose::STANDARD.encode(signature.to_der())
 }
@@ -74,7 +74,7 @@ fn generate_trustpub_token() -> (String, Vec<u8>) {
 }
 
 /// Create a new Trusted Publishing token in the database
-async fn insert_trustpub_token(
+pub(crate) async fn insert_trustpub_token(
     conn: &mut diesel_async::AsyncPgConnection,
     crate_ids: &[i32],
 ) -> QueryResult<String> {
@@ -85,6 +85,8 @@ async fn insert_trustpub_token(
         hashed_token: &hashed_token,
         crate_ids,
         trustpub_data: None,
+        github_config_ids: &[],
+        gitlab_config_ids: &[],
     };
 
     new_token.insert(conn).await?;
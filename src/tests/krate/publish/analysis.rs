@@ -43,6 +43,12 @@ pub fn multiply(a: i32, b: i32) -> i32 {
     a * b
 }
 
+/// Reads a value through a raw pointer
+pub fn read(ptr: *const i32) -> i32 {
+    // SAFETY: the caller guarantees that `ptr` is valid
+    unsafe { *ptr }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,6 +65,11 @@ mod tests {
 }
 "#;
 
+const BUILD_RS_CONTENT: &str = r#"fn main() {
+    println!("cargo::rerun-if-changed=build.rs");
+}
+"#;
+
 const INTEGRATION_TEST_CONTENT: &str = r#"//! Integration tests for analysis_test crate
 
 use analysis_test::{add, multiply};
@@ -90,6 +101,7 @@ async fn test_crate_files_are_analyzed() {
         .readme(README_CONTENT)
         .add_file("analysis_test-1.0.0/src/main.rs", MAIN_RS_CONTENT)
         .add_file("analysis_test-1.0.0/src/lib.rs", LIB_RS_CONTENT)
+        .add_file("analysis_test-1.0.0/build.rs", BUILD_RS_CONTENT)
         .add_file(
             "analysis_test-1.0.0/tests/integration_test.rs",
             INTEGRATION_TEST_CONTENT,
@@ -0,0 +1,468 @@
+use crate::builders::{CrateBuilder, DependencyBuilder, PublishBuilder};
+use crate::util::{MockTokenUser, RequestHelper, TestApp};
+use chrono::{TimeDelta, Utc};
+use crates_io::models::CrateOwner;
+use crates_io::models::token::EndpointScope;
+use crates_io::schema::{crates, pending_versions, versions};
+use crates_io::worker::jobs::ExpirePendingVersions;
+use crates_io_database::models::trustpub::NewToken;
+use crates_io_trustpub::access_token::AccessToken;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use insta::{assert_json_snapshot, assert_snapshot};
+use secrecy::ExposeSecret;
+use serde_json::json;
+
+async fn enable_publish_approval(conn: &mut AsyncPgConnection, name: &str) {
+    diesel::update(crates::table)
+        .filter(crates::name.eq(name))
+        .set(crates::requires_publish_approval.eq(true))
+        .execute(conn)
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pending_version_is_published_after_approval() -> anyhow::Result<()> {
+    let (app, anon, user, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+
+    let reviewer = app.db_new_user("bar").await;
+    let crate_id: i32 = crates::table
+        .filter(crates::name.eq("foo"))
+        .select(crates::id)
+        .first(&mut conn)
+        .await?;
+
+    CrateOwner::builder()
+        .crate_id(crate_id)
+        .user_id(reviewer.as_model().id)
+        .created_by(user.as_model().id)
+        .build()
+        .insert(&mut conn)
+        .await?;
+
+    enable_publish_approval(&mut conn, "foo").await;
+
+    let response = token
+        .publish_crate(PublishBuilder::new("foo", "1.1.0"))
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let json = response.json();
+    assert_eq!(json["crate"]["max_version"], "1.0.0");
+    assert_eq!(json["crate"]["default_version"], "1.0.0");
+    let warning = json["warnings"]["other"][0].as_str().unwrap();
+    assert!(warning.starts_with("foo@1.1.0 has to be approved by another owner"));
+
+    // The pending version is neither in the index nor downloadable
+    let index_versions = app.crates_from_index_head("foo");
+    assert_eq!(index_versions.len(), 1);
+    let stored_files = app.stored_files().await;
+    assert!(stored_files.contains(&"pending-crates/foo/foo-1.1.0.crate".to_string()));
+    assert!(!stored_files.contains(&"crates/foo/foo-1.1.0.crate".to_string()));
+
+    // All owners are notified about the pending version
+    let subject = "Subject: crates.io: foo@1.1.0 is waiting for approval";
+    let emails = app.emails().await;
+    assert_eq!(
+        emails
+            .iter()
+            .filter(|email| email.contains(subject))
+            .count(),
+        2
+    );
+
+    let response = anon.get::<()>("/api/v1/crates/foo/pending_versions").await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let json = response.json();
+    assert_eq!(json["pending_versions"][0]["num"], "1.1.0");
+    assert_eq!(json["pending_versions"][0]["published_by"]["login"], "foo");
+
+    // The uploader can't approve their own version
+    let url = "/api/v1/crates/foo/1.1.0/approve";
+    let response = user.put::<()>(url, &[] as &[u8]).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"versions have to be approved by an owner who did not upload them"}]}"#);
+
+    let response = reviewer.put::<()>(url, &[] as &[u8]).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    app.run_pending_background_jobs().await;
+
+    let response = reviewer.put::<()>(url, &[] as &[u8]).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this version is not waiting for approval"}]}"#);
+
+    let index_versions = app.crates_from_index_head("foo");
+    assert_eq!(index_versions.len(), 2);
+    assert_eq!(index_versions[1].vers, "1.1.0");
+
+    let stored_files = app.stored_files().await;
+    assert!(stored_files.contains(&"crates/foo/foo-1.1.0.crate".to_string()));
+    assert!(!stored_files.contains(&"pending-crates/foo/foo-1.1.0.crate".to_string()));
+
+    let json = anon.show_crate("foo").await;
+    assert_eq!(json.krate.default_version.as_deref(), Some("1.1.0"));
+
+    let json = anon.show_version("foo", "1.1.0").await;
+    let actions = json.version.audit_actions;
+    assert_eq!(actions.len(), 2);
+    assert_eq!(actions[1].action, "approve");
+    assert_eq!(actions[1].user.login, "bar");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejected_version_is_deleted() -> anyhow::Result<()> {
+    let (app, anon, user, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+
+    let krate = CrateBuilder::new("bar", user.as_model().id)
+        .build(&mut conn)
+        .await?;
+    let other = app.db_new_user("other").await;
+    CrateOwner::builder()
+        .crate_id(krate.id)
+        .user_id(other.as_model().id)
+        .created_by(user.as_model().id)
+        .build()
+        .insert(&mut conn)
+        .await?;
+
+    enable_publish_approval(&mut conn, "foo").await;
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.1.0"))
+        .await
+        .good();
+
+    // Only owners can reject versions
+    let url = "/api/v1/crates/foo/1.1.0/reject";
+    let response = other.put::<()>(url, &[] as &[u8]).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    // Versions that are not pending can't be rejected
+    let response = token
+        .put::<()>("/api/v1/crates/foo/1.0.0/reject", &[] as &[u8])
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+
+    // The uploader can reject their own version
+    let response = token.put::<()>(url, &[] as &[u8]).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    app.run_pending_background_jobs().await;
+
+    let response = anon.get::<()>("/api/v1/crates/foo/1.1.0").await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+
+    let stored_files = app.stored_files().await;
+    assert!(!stored_files.contains(&"pending-crates/foo/foo-1.1.0.crate".to_string()));
+
+    let index_versions = app.crates_from_index_head("foo");
+    assert_eq!(index_versions.len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn expired_version_is_deleted() -> anyhow::Result<()> {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+    enable_publish_approval(&mut conn, "foo").await;
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.1.0"))
+        .await
+        .good();
+
+    // Pending versions that have not expired yet are kept
+    ExpirePendingVersions.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let versions: Vec<String> = versions::table
+        .select(versions::num)
+        .load(&mut conn)
+        .await?;
+    assert_eq!(versions.len(), 2);
+
+    diesel::update(pending_versions::table)
+        .set(pending_versions::expires_at.eq(chrono::Utc::now()))
+        .execute(&mut conn)
+        .await?;
+
+    ExpirePendingVersions.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let versions: Vec<String> = versions::table
+        .select(versions::num)
+        .load(&mut conn)
+        .await?;
+    assert_eq!(versions, vec!["1.0.0"]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn update_requires_publish_approval() {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let krate = CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let url = "/api/v1/crates/foo";
+    let enable = json!({ "crate": { "requires_publish_approval": true } }).to_string();
+    let disable = json!({ "crate": { "requires_publish_approval": false } }).to_string();
+
+    let response = user.patch::<()>(url, enable.clone()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"publish approval requires the crate to have at least two owners"}]}"#);
+
+    let reviewer = app.db_new_user("bar").await;
+    CrateOwner::builder()
+        .crate_id(krate.id)
+        .user_id(reviewer.as_model().id)
+        .created_by(user.as_model().id)
+        .build()
+        .insert(&mut conn)
+        .await
+        .unwrap();
+
+    let response = user.patch::<()>(url, enable).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(response.json()["crate"]["requires_publish_approval"], true);
+
+    // API tokens can't be used to disable publish approval
+    let token = user
+        .db_new_scoped_token(
+            "test-token",
+            None,
+            Some(vec![EndpointScope::TrustedPublishing]),
+            None,
+        )
+        .await;
+    let response = token.patch::<()>(url, disable.clone()).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"publish approval can only be disabled from the crates.io website"}]}"#);
+
+    let response = user.patch::<()>(url, disable).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(response.json()["crate"]["requires_publish_approval"], false);
+
+    assert_snapshot!(app.emails_snapshot().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn owners_can_only_be_modified_with_cookie_auth() {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let krate = CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let reviewer = app.db_new_user("bar").await;
+    CrateOwner::builder()
+        .crate_id(krate.id)
+        .user_id(reviewer.as_model().id)
+        .created_by(user.as_model().id)
+        .build()
+        .insert(&mut conn)
+        .await
+        .unwrap();
+
+    enable_publish_approval(&mut conn, "foo").await;
+
+    // Otherwise a compromised token could invite a second account that
+    // approves the versions published with the token
+    app.db_new_user("accomplice").await;
+    let token = user
+        .db_new_scoped_token(
+            "test-token",
+            None,
+            Some(vec![EndpointScope::ChangeOwners]),
+            None,
+        )
+        .await;
+
+    let url = "/api/v1/crates/foo/owners";
+    let body = json!({ "owners": ["accomplice"] }).to_string();
+    let response = token.put::<()>(url, body.clone()).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the owners of crates that require publish approval can only be modified from the crates.io website"}]}"#);
+
+    let response = token
+        .delete_with_body::<()>(url, json!({ "owners": ["bar"] }).to_string())
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    let response = user.put::<()>(url, body).await;
+    assert_snapshot!(response.status(), @"200 OK");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn trustpub_versions_cannot_be_approved_with_a_token() -> anyhow::Result<()> {
+    let (app, _, user, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    let krate = CrateBuilder::new("foo", user.as_model().id)
+        .build(&mut conn)
+        .await?;
+
+    let reviewer = app.db_new_user("bar").await;
+    CrateOwner::builder()
+        .crate_id(krate.id)
+        .user_id(reviewer.as_model().id)
+        .created_by(user.as_model().id)
+        .build()
+        .insert(&mut conn)
+        .await?;
+
+    enable_publish_approval(&mut conn, "foo").await;
+
+    // Otherwise a compromised token could add a config for a repository of
+    // the attacker and publish versions without an uploader
+    let body = json!({
+        "github_config": {
+            "crate": "foo",
+            "repository_owner": "rust-lang",
+            "repository_owner_id": null,
+            "repository_name": "foo-rs",
+            "workflow_filename": "publish.yml",
+            "environment": null,
+        }
+    });
+    let url = "/api/v1/trusted_publishing/github_configs";
+    let response = token.post::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"Trusted Publishing configs for crates that require publish approval can only be created on the crates.io website"}]}"#);
+
+    // Versions published via Trusted Publishing have no uploader, so they
+    // can't be approved with an API token of any owner either
+    let access_token = AccessToken::generate();
+    NewToken {
+        expires_at: Utc::now() + TimeDelta::minutes(30),
+        hashed_token: access_token.sha256().as_slice(),
+        crate_ids: &[krate.id],
+        trustpub_data: None,
+        github_config_ids: &[],
+        gitlab_config_ids: &[],
+    }
+    .insert(&mut conn)
+    .await?;
+
+    let access_token = access_token.finalize().expose_secret().to_string();
+    let trustpub_client = MockTokenUser::with_auth_header(access_token, app.clone());
+    trustpub_client
+        .publish_crate(PublishBuilder::new("foo", "1.1.0"))
+        .await
+        .good();
+
+    let url = "/api/v1/crates/foo/1.1.0/approve";
+    let response = token.put::<()>(url, &[] as &[u8]).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action can only be performed on the crates.io website"}]}"#);
+
+    let pending = pending_versions::table
+        .inner_join(versions::table)
+        .filter(versions::num.eq("1.1.0"))
+        .count()
+        .get_result::<i64>(&mut conn)
+        .await?;
+    assert_eq!(pending, 1);
+
+    let response = reviewer.put::<()>(url, &[] as &[u8]).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pending_versions_are_not_treated_as_published() -> anyhow::Result<()> {
+    let (app, anon, user, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+
+    let reviewer = app.db_new_user("bar").await;
+    let crate_id: i32 = crates::table
+        .filter(crates::name.eq("foo"))
+        .select(crates::id)
+        .first(&mut conn)
+        .await?;
+
+    CrateOwner::builder()
+        .crate_id(crate_id)
+        .user_id(reviewer.as_model().id)
+        .created_by(user.as_model().id)
+        .build()
+        .insert(&mut conn)
+        .await?;
+
+    enable_publish_approval(&mut conn, "foo").await;
+
+    for num in ["1.1.0", "0.9.0"] {
+        token
+            .publish_crate(PublishBuilder::new("foo", num))
+            .await
+            .good();
+    }
+
+    let dep = DependencyBuilder::new("foo").version_req("^1");
+    let pb = PublishBuilder::new("dep-v1", "1.0.0").dependency(dep);
+    token.publish_crate(pb).await.good();
+
+    let dep = DependencyBuilder::new("foo").version_req("^0.9");
+    let pb = PublishBuilder::new("dep-v0", "1.0.0").dependency(dep);
+    token.publish_crate(pb).await.good();
+
+    let json = anon.show_crate("foo").await;
+    assert_eq!(json.krate.max_version, "1.0.0");
+    assert_eq!(json.krate.newest_version, "1.0.0");
+
+    let response = anon.get::<()>("/api/v1/crates/foo/versions").await;
+    let json = response.json();
+    assert_eq!(json["versions"].as_array().unwrap().len(), 1);
+    assert_eq!(json["versions"][0]["num"], "1.0.0");
+    assert_eq!(json["meta"]["total"], 1);
+
+    let url = "/api/v1/crates/dep-v1/1.0.0/dependency_tree";
+    let response = anon.get::<()>(url).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let json = response.json();
+    let foo = json["packages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|package| package["name"] == "foo")
+        .unwrap();
+    assert_eq!(foo["version"], "1.0.0");
+
+    // `^0.9` only matches the pending version, so it does not count as an
+    // older major version that is still in use
+    let url = "/api/v1/crates/foo/reverse_dependencies";
+    let response = anon.get::<()>(url).await;
+    let json = response.json();
+    assert_eq!(json["meta"]["total"], 2);
+    assert_json_snapshot!(json["meta"]["older_majors"], @"[]");
+
+    Ok(())
+}
@@ -71,11 +71,16 @@ async fn new_krate_with_bearer_token() {
     });
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/foo_new.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/foo_new/foo_new-1.0.0.crate
     index/fo/o_/foo_new
     rss/crates.xml
     rss/crates/foo_new.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 }
 
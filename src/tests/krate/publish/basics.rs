@@ -23,11 +23,16 @@ async fn new_krate() {
     assert_json_snapshot!(crates);
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/foo_new.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/foo_new/foo_new-1.0.0.crate
     index/fo/o_/foo_new
     rss/crates.xml
     rss/crates/foo_new.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 
     let email: String = versions_published_by::table
@@ -53,11 +58,16 @@ async fn new_krate_with_token() {
     });
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/foo_new.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/foo_new/foo_new-1.0.0.crate
     index/fo/o_/foo_new
     rss/crates.xml
     rss/crates/foo_new.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 }
 
@@ -74,11 +84,16 @@ async fn new_krate_weird_version() {
     });
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/foo_weird.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/foo_weird/foo_weird-0.0.0-pre.crate
     index/fo/o_/foo_weird
     rss/crates.xml
     rss/crates/foo_weird.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 }
 
@@ -102,12 +117,17 @@ async fn new_krate_twice() {
     assert_json_snapshot!(crates);
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/foo_twice.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/foo_twice/foo_twice-0.99.0.crate
     crates/foo_twice/foo_twice-2.0.0.crate
     index/fo/o_/foo_twice
     rss/crates.xml
     rss/crates/foo_twice.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 }
 
@@ -133,12 +153,17 @@ async fn new_krate_twice_alt() {
     assert_json_snapshot!(crates);
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/foo_twice.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/foo_twice/foo_twice-0.99.0.crate
     crates/foo_twice/foo_twice-2.0.0.crate
     index/fo/o_/foo_twice
     rss/crates.xml
     rss/crates/foo_twice.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 }
 
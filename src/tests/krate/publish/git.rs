@@ -12,10 +12,15 @@ async fn new_krate_git_upload_with_conflicts() {
     token.publish_crate(crate_to_publish).await.good();
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/foo_conflicts.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/foo_conflicts/foo_conflicts-1.0.0.crate
     index/fo/o_/foo_conflicts
     rss/crates.xml
     rss/crates/foo_conflicts.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 }
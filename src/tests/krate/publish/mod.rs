@@ -1,4 +1,5 @@
 mod analysis;
+mod approval;
 mod audit_action;
 mod auth;
 mod basics;
@@ -15,6 +16,7 @@ mod keywords;
 mod links;
 mod manifest;
 mod max_size;
+mod policy;
 mod rate_limit;
 mod readme;
 mod similar_names;
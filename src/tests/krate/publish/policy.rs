@@ -0,0 +1,404 @@
+use crate::builders::{CrateBuilder, PublishBuilder};
+use crate::util::{MockTokenUser, RequestHelper, TestApp};
+use chrono::{TimeDelta, Utc};
+use crates_io::models::CrateOwner;
+use crates_io::models::token::{CrateScope, EndpointScope};
+use crates_io::schema::api_tokens;
+use crates_io_database::models::trustpub::{NewGitHubConfig, NewToken};
+use crates_io_trustpub::access_token::AccessToken;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use http::StatusCode;
+use insta::assert_snapshot;
+use secrecy::ExposeSecret;
+use serde_json::json;
+
+const URL: &str = "/api/v1/crates/foo/publish_policy";
+
+async fn new_github_config(conn: &mut AsyncPgConnection, crate_id: i32, workflow: &str) -> i32 {
+    let config = NewGitHubConfig {
+        crate_id,
+        repository_owner: "rust-lang",
+        repository_owner_id: 42,
+        repository_name: "foo-rs",
+        workflow_filename: workflow,
+        environment: None,
+    };
+
+    config.insert(conn).await.unwrap().id
+}
+
+async fn new_trustpub_token(
+    conn: &mut AsyncPgConnection,
+    crate_id: i32,
+    github_config_id: i32,
+) -> String {
+    let token = AccessToken::generate();
+    let hashed_token = token.sha256();
+
+    let new_token = NewToken {
+        expires_at: Utc::now() + TimeDelta::minutes(30),
+        hashed_token: hashed_token.as_slice(),
+        crate_ids: &[crate_id],
+        trustpub_data: None,
+        github_config_ids: &[github_config_id],
+        gitlab_config_ids: &[],
+    };
+
+    new_token.insert(conn).await.unwrap();
+
+    token.finalize().expose_secret().to_string()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn update_publish_policy() {
+    let (app, anon, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let krate = CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let other = app.db_new_user("bar").await;
+    CrateOwner::builder()
+        .crate_id(krate.id)
+        .user_id(other.as_model().id)
+        .created_by(user.as_model().id)
+        .build()
+        .insert(&mut conn)
+        .await
+        .unwrap();
+
+    let config_id = new_github_config(&mut conn, krate.id, "publish.yml").await;
+
+    let response = anon.get::<()>(URL).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    let response = user.get::<()>(URL).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_snapshot!(response.text(), @r#"{"publish_policy":{"allowed_publishers":[],"require_crate_scoped_tokens":false,"trustpub_github_config_ids":[],"trustpub_gitlab_config_ids":[],"max_token_age_days":null}}"#);
+
+    let body = json!({ "publish_policy": { "allowed_publishers": ["baz"] } });
+    let response = user.put::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"`baz` is not an owner of this crate"}]}"#);
+
+    let body = json!({ "publish_policy": { "trustpub_github_config_ids": [config_id + 1] } });
+    let response = user.put::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+
+    let body = json!({ "publish_policy": { "max_token_age_days": 0 } });
+    let response = user.put::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"`max_token_age_days` must be at least 1"}]}"#);
+
+    let body = json!({
+        "publish_policy": {
+            "allowed_publishers": ["FOO"],
+            "require_crate_scoped_tokens": true,
+            "trustpub_github_config_ids": [config_id],
+            "max_token_age_days": 90,
+        }
+    });
+    let response = user.put::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let json = response.json();
+    assert_eq!(json["publish_policy"]["allowed_publishers"], json!(["foo"]));
+    assert_eq!(
+        json["publish_policy"]["trustpub_github_config_ids"],
+        json!([config_id])
+    );
+
+    // Other owners can see the policy
+    let response = other.get::<()>(URL).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(response.json(), json);
+
+    // Submitting the same policy again does not send any emails
+    let response = user.put::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let body = json!({ "publish_policy": {} });
+    let response = other.put::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_snapshot!(response.text(), @r#"{"publish_policy":{"allowed_publishers":[],"require_crate_scoped_tokens":false,"trustpub_github_config_ids":[],"trustpub_gitlab_config_ids":[],"max_token_age_days":null}}"#);
+
+    assert_snapshot!(app.emails_snapshot().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn relaxing_publish_policy_requires_cookie_auth() {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let krate = CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let other = app.db_new_user("bar").await;
+    CrateOwner::builder()
+        .crate_id(krate.id)
+        .user_id(other.as_model().id)
+        .created_by(user.as_model().id)
+        .build()
+        .insert(&mut conn)
+        .await
+        .unwrap();
+
+    let config_id = new_github_config(&mut conn, krate.id, "publish.yml").await;
+    let other_config_id = new_github_config(&mut conn, krate.id, "release.yml").await;
+
+    let crate_scopes = Some(vec![CrateScope::try_from("foo").unwrap()]);
+    let endpoint_scopes = Some(vec![EndpointScope::TrustedPublishing]);
+    let token = user
+        .db_new_scoped_token("test-token", crate_scopes, endpoint_scopes, None)
+        .await;
+
+    // Creating a policy makes publishing more restrictive, so tokens can do that
+    let body = json!({
+        "publish_policy": {
+            "allowed_publishers": ["foo"],
+            "require_crate_scoped_tokens": true,
+            "trustpub_github_config_ids": [config_id],
+            "max_token_age_days": 30,
+        }
+    });
+    let response = token.put::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let relaxed_policies = [
+        json!({}),
+        json!({
+            "require_crate_scoped_tokens": true,
+            "trustpub_github_config_ids": [config_id],
+            "max_token_age_days": 30,
+        }),
+        json!({
+            "allowed_publishers": ["foo", "bar"],
+            "require_crate_scoped_tokens": true,
+            "trustpub_github_config_ids": [config_id],
+            "max_token_age_days": 30,
+        }),
+        json!({
+            "allowed_publishers": ["foo"],
+            "trustpub_github_config_ids": [config_id],
+            "max_token_age_days": 30,
+        }),
+        json!({
+            "allowed_publishers": ["foo"],
+            "require_crate_scoped_tokens": true,
+            "trustpub_github_config_ids": [config_id, other_config_id],
+            "max_token_age_days": 30,
+        }),
+        json!({
+            "allowed_publishers": ["foo"],
+            "require_crate_scoped_tokens": true,
+            "trustpub_github_config_ids": [config_id],
+            "max_token_age_days": 60,
+        }),
+    ];
+
+    for policy in relaxed_policies {
+        let body = json!({ "publish_policy": policy });
+        let response = token.put::<()>(URL, body.to_string()).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN, "{policy}");
+        assert_eq!(
+            response.json()["errors"][0]["detail"],
+            "publish policies can only be relaxed from the crates.io website"
+        );
+    }
+
+    // Tightening the policy further is fine
+    let body = json!({
+        "publish_policy": {
+            "allowed_publishers": ["foo"],
+            "require_crate_scoped_tokens": true,
+            "trustpub_github_config_ids": [config_id],
+            "max_token_age_days": 7,
+        }
+    });
+    let response = token.put::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let body = json!({ "publish_policy": {} });
+    let response = user.put::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn allowed_publishers() {
+    let (app, _, user, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    let krate = CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let other = app.db_new_user("bar").await;
+    CrateOwner::builder()
+        .crate_id(krate.id)
+        .user_id(other.as_model().id)
+        .created_by(user.as_model().id)
+        .build()
+        .insert(&mut conn)
+        .await
+        .unwrap();
+    let other_token = other.db_new_token("other-token").await;
+
+    let body = json!({ "publish_policy": { "allowed_publishers": ["bar"] } });
+    let response = user.put::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let response = token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"The publish policy of this crate does not allow you to publish new versions."}]}"#);
+
+    let response = other_token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    // Trusted Publishing configs would allow other owners to publish anyway
+    let body = json!({
+        "github_config": {
+            "crate": "foo",
+            "repository_owner": "rust-lang",
+            "repository_owner_id": null,
+            "repository_name": "foo-rs",
+            "workflow_filename": "publish.yml",
+            "environment": null,
+        }
+    });
+    let url = "/api/v1/trusted_publishing/github_configs";
+    let response = user.post::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"The publish policy of this crate does not allow you to create Trusted Publishing configs"}]}"#);
+
+    let body = json!({
+        "gitlab_config": {
+            "crate": "foo",
+            "namespace": "rust-lang",
+            "project": "foo-rs",
+            "workflow_filepath": ".gitlab-ci.yml",
+            "environment": null,
+        }
+    });
+    let url = "/api/v1/trusted_publishing/gitlab_configs";
+    let response = user.post::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn require_crate_scoped_tokens() {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let body = json!({ "publish_policy": { "require_crate_scoped_tokens": true } });
+    let response = user.put::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let endpoint_scopes = Some(vec![EndpointScope::PublishUpdate]);
+
+    let unscoped_token = user
+        .db_new_scoped_token("unscoped", None, endpoint_scopes.clone(), None)
+        .await;
+    let response = unscoped_token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the publish policy of this crate only allows API tokens with a crate scope for `foo`"}]}"#);
+
+    let crate_scopes = Some(vec![CrateScope::try_from("fo*").unwrap()]);
+    let wildcard_token = user
+        .db_new_scoped_token("wildcard", crate_scopes, endpoint_scopes.clone(), None)
+        .await;
+    let response = wildcard_token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    let crate_scopes = Some(vec![CrateScope::try_from("foo").unwrap()]);
+    let scoped_token = user
+        .db_new_scoped_token("scoped", crate_scopes, endpoint_scopes, None)
+        .await;
+    let response = scoped_token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn max_token_age_days() {
+    let (app, _, user, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let body = json!({ "publish_policy": { "max_token_age_days": 30 } });
+    let response = user.put::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    diesel::update(api_tokens::table.find(token.as_model().id))
+        .set(api_tokens::created_at.eq(Utc::now() - TimeDelta::days(31)))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let response = token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the publish policy of this crate only allows API tokens that were created within the last 30 days"}]}"#);
+
+    let new_token = user.db_new_token("new-token").await;
+    let response = new_token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn trusted_publishing_configs() {
+    let (app, _, user, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    let krate = CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let allowed_config_id = new_github_config(&mut conn, krate.id, "publish.yml").await;
+    let other_config_id = new_github_config(&mut conn, krate.id, "other.yml").await;
+
+    let body = json!({ "publish_policy": { "trustpub_github_config_ids": [allowed_config_id] } });
+    let response = user.put::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let response = token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"The publish policy of this crate only allows publishing new versions using Trusted Publishing (see https://crates.io/docs/trusted-publishing)."}]}"#);
+
+    let other_token = new_trustpub_token(&mut conn, krate.id, other_config_id).await;
+    let client = MockTokenUser::with_auth_header(other_token, app.clone());
+    let response = client
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"The publish policy of this crate does not allow publishing new versions using this Trusted Publishing config."}]}"#);
+
+    let allowed_token = new_trustpub_token(&mut conn, krate.id, allowed_config_id).await;
+    let client = MockTokenUser::with_auth_header(allowed_token, app);
+    let response = client
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+}
@@ -72,11 +72,16 @@ async fn publish_new_crate_ratelimit_expires() {
     token.publish_crate(crate_to_publish).await.good();
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/rate_limited.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/rate_limited/rate_limited-1.0.0.crate
     index/ra/te/rate_limited
     rss/crates.xml
     rss/crates/rate_limited.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 
     let json = anon.show_crate("rate_limited").await;
@@ -113,11 +118,16 @@ async fn publish_new_crate_override_loosens_ratelimit() {
     token.publish_crate(crate_to_publish).await.good();
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/rate_limited1.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/rate_limited1/rate_limited1-1.0.0.crate
     index/ra/te/rate_limited1
     rss/crates.xml
     rss/crates/rate_limited1.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 
     let json = anon.show_crate("rate_limited1").await;
@@ -127,6 +137,11 @@ async fn publish_new_crate_override_loosens_ratelimit() {
     token.publish_crate(crate_to_publish).await.good();
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/rate_limited1.xml
+    atom/crates/rate_limited2.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/rate_limited1/rate_limited1-1.0.0.crate
     crates/rate_limited2/rate_limited2-1.0.0.crate
     index/ra/te/rate_limited1
@@ -135,6 +150,7 @@ async fn publish_new_crate_override_loosens_ratelimit() {
     rss/crates/rate_limited1.xml
     rss/crates/rate_limited2.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 
     let json = anon.show_crate("rate_limited2").await;
@@ -147,6 +163,11 @@ async fn publish_new_crate_override_loosens_ratelimit() {
         .assert_rate_limited(LimitedAction::PublishNew);
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/rate_limited1.xml
+    atom/crates/rate_limited2.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/rate_limited1/rate_limited1-1.0.0.crate
     crates/rate_limited2/rate_limited2-1.0.0.crate
     index/ra/te/rate_limited1
@@ -155,6 +176,7 @@ async fn publish_new_crate_override_loosens_ratelimit() {
     rss/crates/rate_limited1.xml
     rss/crates/rate_limited2.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 
     let response = anon.get::<()>("/api/v1/crates/rate_limited3").await;
@@ -192,11 +214,16 @@ async fn publish_new_crate_expired_override_ignored() {
     token.publish_crate(crate_to_publish).await.good();
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/rate_limited1.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/rate_limited1/rate_limited1-1.0.0.crate
     index/ra/te/rate_limited1
     rss/crates.xml
     rss/crates/rate_limited1.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 
     let json = anon.show_crate("rate_limited1").await;
@@ -209,11 +236,16 @@ async fn publish_new_crate_expired_override_ignored() {
         .assert_rate_limited(LimitedAction::PublishNew);
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/rate_limited1.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/rate_limited1/rate_limited1-1.0.0.crate
     index/ra/te/rate_limited1
     rss/crates.xml
     rss/crates/rate_limited1.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 
     let response = anon.get::<()>("/api/v1/crates/rate_limited2").await;
@@ -251,11 +283,16 @@ async fn publish_existing_crate_rate_limited() {
     let json = anon.show_crate("rate_limited1").await;
     assert_eq!(json.krate.max_version, "1.0.0");
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/rate_limited1.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/rate_limited1/rate_limited1-1.0.0.crate
     index/ra/te/rate_limited1
     rss/crates.xml
     rss/crates/rate_limited1.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 
     // Uploading the first update to the crate works
@@ -265,12 +302,17 @@ async fn publish_existing_crate_rate_limited() {
     let json = anon.show_crate("rate_limited1").await;
     assert_eq!(json.krate.max_version, "1.0.1");
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/rate_limited1.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/rate_limited1/rate_limited1-1.0.0.crate
     crates/rate_limited1/rate_limited1-1.0.1.crate
     index/ra/te/rate_limited1
     rss/crates.xml
     rss/crates/rate_limited1.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 
     // Uploading the second update to the crate is rate limited
@@ -284,12 +326,17 @@ async fn publish_existing_crate_rate_limited() {
     let json = anon.show_crate("rate_limited1").await;
     assert_eq!(json.krate.max_version, "1.0.1");
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/rate_limited1.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/rate_limited1/rate_limited1-1.0.0.crate
     crates/rate_limited1/rate_limited1-1.0.1.crate
     index/ra/te/rate_limited1
     rss/crates.xml
     rss/crates/rate_limited1.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 
     // Reset the rate limit by updating the database timestamp
@@ -310,6 +357,10 @@ async fn publish_existing_crate_rate_limited() {
     let json = anon.show_crate("rate_limited1").await;
     assert_eq!(json.krate.max_version, "1.0.2");
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/rate_limited1.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/rate_limited1/rate_limited1-1.0.0.crate
     crates/rate_limited1/rate_limited1-1.0.1.crate
     crates/rate_limited1/rate_limited1-1.0.2.crate
@@ -317,6 +368,7 @@ async fn publish_existing_crate_rate_limited() {
     rss/crates.xml
     rss/crates/rate_limited1.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 }
 
@@ -15,12 +15,17 @@ async fn new_krate_with_readme() {
     });
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/foo_readme.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/foo_readme/foo_readme-1.0.0.crate
     index/fo/o_/foo_readme
     readmes/foo_readme/foo_readme-1.0.0.html
     rss/crates.xml
     rss/crates/foo_readme.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 }
 
@@ -37,11 +42,16 @@ async fn new_krate_with_empty_readme() {
     });
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/foo_readme.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/foo_readme/foo_readme-1.0.0.crate
     index/fo/o_/foo_readme
     rss/crates.xml
     rss/crates/foo_readme.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 }
 
@@ -58,12 +68,17 @@ async fn new_krate_with_readme_and_plus_version() {
     });
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/foo_readme.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/foo_readme/foo_readme-1.0.0+foo.crate
     index/fo/o_/foo_readme
     readmes/foo_readme/foo_readme-1.0.0+foo.html
     rss/crates.xml
     rss/crates/foo_readme.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 }
 
@@ -151,6 +151,8 @@ async fn new_token(conn: &mut AsyncPgConnection, crate_id: i32) -> QueryResult<S
         hashed_token: hashed_token.as_slice(),
         crate_ids: &[crate_id],
         trustpub_data: None,
+        github_config_ids: &[],
+        gitlab_config_ids: &[],
     };
 
     new_token.insert(conn).await?;
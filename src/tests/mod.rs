@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 use std::sync::LazyLock;
 
 mod account_lock;
+mod account_recovery;
 mod authentication;
 mod blocked_routes;
 pub use crates_io_test_utils::builders;
@@ -26,7 +27,9 @@ mod openapi;
 mod owners;
 mod pagination;
 mod read_only_mode;
+mod read_rate_limit;
 mod routes;
+mod secret_scanning;
 mod server;
 mod team;
 mod token;
@@ -0,0 +1,120 @@
+use crate::builders::CrateBuilder;
+use crate::util::{MockRequestExt, RequestHelper, TestApp};
+use crates_io::read_rate_limiter::ReadRouteGroup;
+use http::{StatusCode, header};
+use insta::assert_json_snapshot;
+use std::time::Duration;
+
+const LONG_RATE: Duration = Duration::from_secs(60);
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_crates_is_rate_limited() {
+    let (_app, anon) = TestApp::init()
+        .with_read_rate_limit(ReadRouteGroup::ListCrates, LONG_RATE, 2)
+        .empty()
+        .await;
+
+    let response = anon.get::<()>("/api/v1/crates").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["ratelimit-limit"], "2");
+    assert_eq!(response.headers()["ratelimit-remaining"], "1");
+    assert_eq!(response.headers()["ratelimit-reset"], "60");
+
+    let response = anon.get::<()>("/api/v1/crates").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["ratelimit-remaining"], "0");
+
+    let response = anon.get::<()>("/api/v1/crates").await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()[header::RETRY_AFTER], "60");
+    assert_eq!(response.headers()["ratelimit-remaining"], "0");
+    assert_json_snapshot!(response.json());
+
+    // Other route groups are not affected
+    let response = anon.get::<()>("/api/v1/summary").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("ratelimit-limit").is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn clients_are_limited_per_ip() {
+    let (_app, anon) = TestApp::init()
+        .with_read_rate_limit(ReadRouteGroup::ListCrates, LONG_RATE, 1)
+        .empty()
+        .await;
+
+    let request = |ip: &str| {
+        let mut request = anon.get_request("/api/v1/crates");
+        request.header("x-forwarded-for", ip);
+        request
+    };
+
+    let response = anon.run::<()>(request("1.2.3.4")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = anon.run::<()>(request("1.2.3.4")).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    let response = anon.run::<()>(request("5.6.7.8")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn clients_are_limited_per_token() {
+    let (app, anon, _cookie, token) = TestApp::init()
+        .with_read_rate_limit(ReadRouteGroup::ReverseDependencies, LONG_RATE, 1)
+        .with_token()
+        .await;
+
+    let mut conn = app.db_conn().await;
+    CrateBuilder::new("foo", token.as_model().user_id)
+        .expect_build(&mut conn)
+        .await;
+
+    let url = "/api/v1/crates/foo/reverse_dependencies";
+
+    let response = token.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = token.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // Anonymous requests from the same IP address use a separate bucket
+    let response = anon.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rate_limits_are_disabled_by_default() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+
+    let mut conn = app.db_conn().await;
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    for _ in 0..5 {
+        let response = anon.get::<()>("/api/v1/crates/foo/downloads").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("ratelimit-limit").is_none());
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dependency_tree_is_rate_limited() {
+    let (app, anon, user) = TestApp::init()
+        .with_read_rate_limit(ReadRouteGroup::DependencyTree, LONG_RATE, 1)
+        .with_user()
+        .await;
+
+    let mut conn = app.db_conn().await;
+    CrateBuilder::new("foo", user.as_model().id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let url = "/api/v1/crates/foo/1.0.0/dependency_tree";
+
+    let response = anon.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = anon.get::<()>(url).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+}
@@ -0,0 +1,59 @@
+use crate::builders::{CrateBuilder, VersionBuilder};
+use crate::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::assert_json_snapshot;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn find_binary() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    CrateBuilder::new("ripgrep", user.id)
+        .version(VersionBuilder::new("1.0.0").bin_name("rg"))
+        .downloads(100)
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("rg-fork", user.id)
+        .version(
+            VersionBuilder::new("1.0.0")
+                .bin_name("rg")
+                .bin_name("rg-fork"),
+        )
+        .expect_build(&mut conn)
+        .await;
+
+    // Only the default version is taken into account
+    CrateBuilder::new("old-rg", user.id)
+        .version(VersionBuilder::new("1.0.0").bin_name("rg"))
+        .version(VersionBuilder::new("2.0.0").bin_name("old-rg"))
+        .expect_build(&mut conn)
+        .await;
+
+    let response = anon.get::<()>("/api/v1/binaries/rg").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), {
+        ".crates[].created_at" => "[datetime]",
+        ".crates[].updated_at" => "[datetime]",
+    });
+
+    let response = anon.get::<()>("/api/v1/binaries/rg-fork").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.json();
+    let names = json["crates"].as_array().unwrap().iter();
+    let names = names
+        .map(|c| c["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["rg-fork"]);
+
+    let response = anon.get::<()>("/api/v1/binaries/unknown").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json(), @r#"
+    {
+      "crates": []
+    }
+    "#);
+
+    Ok(())
+}
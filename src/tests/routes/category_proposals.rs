@@ -0,0 +1,173 @@
+use crate::new_category;
+use crate::util::insta::assert_json_snapshot;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::schema::categories;
+use diesel_async::RunQueryDsl;
+use insta::assert_snapshot;
+use serde_json::json;
+
+const URL: &str = "/api/v1/category_proposals";
+
+fn proposal_body(slug: &str, name: &str, description: &str) -> String {
+    json!({ "slug": slug, "name": name, "description": description }).to_string()
+}
+
+fn action_body(action: &str) -> String {
+    json!({ "action": action }).to_string()
+}
+
+fn admin_url(id: i64) -> String {
+    format!("/api/private/admin/category_proposals/{id}")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn category_proposal_approved_by_admin() {
+    let (app, _anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    diesel::insert_into(categories::table)
+        .values(new_category("Algorithms", "algorithms", "Algorithm crates"))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let admin = app.db_new_admin("admin").await;
+
+    let other = app.db_new_user("other").await;
+
+    let body = proposal_body("algorithms::sorting", " Sorting ", "Sorting algorithms");
+    let response = user.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"201 Created");
+    let json = response.json();
+    assert_json_snapshot!(json, {
+        ".category_proposal.id" => "[id]",
+        ".**.created_at" => "[datetime]",
+    }, @r#"
+    {
+      "category_proposal": {
+        "created_at": "[datetime]",
+        "description": "Sorting algorithms",
+        "id": "[id]",
+        "name": "Sorting",
+        "proposed_by": {
+          "avatar": null,
+          "id": 1,
+          "login": "foo",
+          "name": null,
+          "url": "https://github.com/foo"
+        },
+        "resolved_at": null,
+        "slug": "algorithms::sorting",
+        "status": "pending"
+      }
+    }
+    "#);
+
+    let id = json["category_proposal"]["id"].as_i64().unwrap();
+
+    // Admins can see all proposals, other users only their own
+    let response = admin.get::<()>(URL).await;
+    assert_eq!(response.json()["category_proposals"][0]["id"], id);
+    let response = user.get::<()>(URL).await;
+    assert_eq!(response.json()["category_proposals"][0]["id"], id);
+    let response = other.get::<()>(URL).await;
+    assert_snapshot!(response.text(), @r#"{"category_proposals":[]}"#);
+
+    // Only admins can approve proposals
+    let response = user.put::<()>(&admin_url(id), action_body("approve")).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"must be an admin to use this route"}]}"#);
+
+    let response = admin
+        .put::<()>(&admin_url(id), action_body("approve"))
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let json = response.json();
+    assert_eq!(json["category_proposal"]["status"], "approved");
+    assert!(json["category_proposal"]["resolved_at"].is_string());
+
+    let response = admin.put::<()>(&admin_url(id), action_body("reject")).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the category proposal is not pending anymore"}]}"#);
+
+    let response = user
+        .get::<()>("/api/v1/categories/algorithms::sorting")
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let json = response.json();
+    assert_eq!(json["category"]["category"], "Sorting");
+    assert_eq!(json["category"]["description"], "Sorting algorithms");
+    assert_eq!(
+        json["category"]["parent_categories"][0]["slug"],
+        "algorithms"
+    );
+
+    // The category can't be proposed again
+    let body = proposal_body("algorithms::sorting", "Sorting", "Sorting algorithms");
+    let response = other.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the category `algorithms::sorting` already exists"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn category_proposal_rejected_by_admin() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+
+    let admin = app.db_new_admin("admin").await;
+
+    let body = proposal_body("sorting", "Sorting", "Sorting algorithms");
+    let response = user.post::<()>(URL, body.clone()).await;
+    assert_snapshot!(response.status(), @"201 Created");
+    let id = response.json()["category_proposal"]["id"].as_i64().unwrap();
+
+    // Only one pending proposal per category is allowed
+    let response = admin.post::<()>(URL, body.clone()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"there is already a pending proposal for the category `sorting`"}]}"#);
+
+    let response = anon.put::<()>(&admin_url(id), action_body("reject")).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    let response = admin.put::<()>(&admin_url(id), action_body("reject")).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(response.json()["category_proposal"]["status"], "rejected");
+
+    let response = anon.get::<()>("/api/v1/categories/sorting").await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+
+    // After a rejection, the category can be proposed again
+    let response = admin.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"201 Created");
+
+    let response = admin.put::<()>(&admin_url(-1), action_body("reject")).await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn create_category_proposal_validation() {
+    let (_app, anon, user) = TestApp::init().with_user().await;
+
+    let body = proposal_body("sorting", "Sorting", "Sorting algorithms");
+    let response = anon.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    let body = proposal_body("Sorting Algorithms", "Sorting", "Sorting algorithms");
+    let response = user.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid category slug `Sorting Algorithms`: slugs may only contain lowercase letters, digits and dashes, and subcategories are separated by `::`"}]}"#);
+
+    let body = proposal_body("sorting", "Algorithms::Sorting", "Sorting algorithms");
+    let response = user.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the category name must not be empty or contain `::`"}]}"#);
+
+    let body = proposal_body("sorting", "Sorting", " ");
+    let response = user.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"a description of the category is required"}]}"#);
+
+    let body = proposal_body("algorithms::sorting", "Sorting", "Sorting algorithms");
+    let response = user.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the parent category `algorithms` does not exist"}]}"#);
+}
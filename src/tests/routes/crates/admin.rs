@@ -2,9 +2,6 @@ use crate::{
     builders::{CrateBuilder, VersionBuilder},
     util::{RequestHelper, TestApp},
 };
-use crates_io::schema::users;
-use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
 use insta::{assert_json_snapshot, assert_snapshot};
 
 #[tokio::test(flavor = "multi_thread")]
@@ -32,13 +29,7 @@ async fn index_include_yanked() -> anyhow::Result<()> {
     let mut conn = app.db_conn().await;
     let user = user.as_model();
 
-    let admin = app.db_new_user("admin").await;
-
-    diesel::update(admin.as_model())
-        .set(users::is_admin.eq(true))
-        .execute(&mut conn)
-        .await
-        .unwrap();
+    let admin = app.db_new_admin("admin").await;
 
     let crate_1 = CrateBuilder::new("unyanked", user.id)
         .description("My Fun Crate")
@@ -57,7 +48,7 @@ async fn index_include_yanked() -> anyhow::Result<()> {
         .await;
 
     CrateBuilder::new("someone_elses_crate", admin.as_model().id)
-        .version(VersionBuilder::new("1.0.0").dependency(&crate_1, None))
+        .version(VersionBuilder::new("1.0.0").dependency(&crate_1))
         .expect_build(&mut conn)
         .await;
 
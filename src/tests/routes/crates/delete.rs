@@ -1,4 +1,5 @@
 use crate::builders::{DependencyBuilder, PublishBuilder};
+use crate::util::insta::assert_json_snapshot;
 use crate::util::{RequestHelper, Response, TestApp};
 use axum::RequestPartsExt;
 use bigdecimal::ToPrimitive;
@@ -6,8 +7,10 @@ use chrono::{TimeDelta, Utc};
 use claims::{assert_none, assert_some};
 use crates_io::controllers::krate::delete::{DOWNLOADS_PER_MONTH_LIMIT, DeleteQueryParams};
 use crates_io::models::OwnerKind;
-use crates_io::schema::{crate_downloads, crates};
+use crates_io::schema::{crate_downloads, crates, scheduled_crate_deletions};
+use crates_io::worker::jobs;
 use crates_io_database::schema::crate_owners;
+use crates_io_worker::BackgroundJob;
 use diesel::prelude::*;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use http::{Request, StatusCode};
@@ -52,11 +55,16 @@ async fn test_happy_path_new_crate() -> anyhow::Result<()> {
     assert_crate_exists(&anon, "foo", true).await;
     assert!(upstream.crate_exists("foo")?);
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/foo.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/foo/foo-1.0.0.crate
     index/3/f/foo
     rss/crates.xml
     rss/crates/foo.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 
     let response = delete_crate(&user, "foo").await;
@@ -65,12 +73,22 @@ async fn test_happy_path_new_crate() -> anyhow::Result<()> {
 
     assert_snapshot!(app.emails_snapshot().await);
 
+    // Assert that the crate is hidden from the index, but still exists
+    assert_crate_exists(&anon, "foo", true).await;
+    assert!(!upstream.crate_exists("foo")?);
+
+    finalize_deletion(&app, &mut conn, "foo").await?;
+
     // Assert that the crate no longer exists
     assert_crate_exists(&anon, "foo", false).await;
     assert!(!upstream.crate_exists("foo")?);
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/updates.xml
+    atom/users/foo.xml
     rss/crates.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 
     Ok(())
@@ -89,11 +107,16 @@ async fn test_happy_path_old_crate() -> anyhow::Result<()> {
     assert_crate_exists(&anon, "foo", true).await;
     assert!(upstream.crate_exists("foo")?);
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/foo.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/foo/foo-1.0.0.crate
     index/3/f/foo
     rss/crates.xml
     rss/crates/foo.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 
     let response = delete_crate(&user, "foo").await;
@@ -102,12 +125,22 @@ async fn test_happy_path_old_crate() -> anyhow::Result<()> {
 
     assert_snapshot!(app.emails_snapshot().await);
 
+    // Assert that the crate is hidden from the index, but still exists
+    assert_crate_exists(&anon, "foo", true).await;
+    assert!(!upstream.crate_exists("foo")?);
+
+    finalize_deletion(&app, &mut conn, "foo").await?;
+
     // Assert that the crate no longer exists
     assert_crate_exists(&anon, "foo", false).await;
     assert!(!upstream.crate_exists("foo")?);
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/updates.xml
+    atom/users/foo.xml
     rss/crates.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 
     Ok(())
@@ -126,11 +159,16 @@ async fn test_happy_path_really_old_crate() -> anyhow::Result<()> {
     assert_crate_exists(&anon, "foo", true).await;
     assert!(upstream.crate_exists("foo")?);
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/crates/foo.xml
+    atom/updates.xml
+    atom/users/foo.xml
     crates/foo/foo-1.0.0.crate
     index/3/f/foo
     rss/crates.xml
     rss/crates/foo.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 
     let response = delete_crate(&user, "foo").await;
@@ -139,12 +177,22 @@ async fn test_happy_path_really_old_crate() -> anyhow::Result<()> {
 
     assert_snapshot!(app.emails_snapshot().await);
 
+    // Assert that the crate is hidden from the index, but still exists
+    assert_crate_exists(&anon, "foo", true).await;
+    assert!(!upstream.crate_exists("foo")?);
+
+    finalize_deletion(&app, &mut conn, "foo").await?;
+
     // Assert that the crate no longer exists
     assert_crate_exists(&anon, "foo", false).await;
     assert!(!upstream.crate_exists("foo")?);
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    atom/updates.xml
+    atom/users/foo.xml
     rss/crates.xml
     rss/updates.xml
+    rss/users/foo.xml
     ");
 
     Ok(())
@@ -295,6 +343,92 @@ async fn test_rev_deps() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_cancel_scheduled_deletion() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+    let upstream = app.upstream_index();
+
+    publish_crate(&user, "foo").await;
+
+    let url = "/api/v1/crates/foo/scheduled_deletion";
+    let response = user.get::<()>(url).await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+
+    let response = delete_crate(&user, "foo").await;
+    assert_snapshot!(response.status(), @"204 No Content");
+
+    // Assert that the crate is hidden from the index and search
+    assert!(!upstream.crate_exists("foo")?);
+    let response = anon.get::<()>("/api/v1/crates?q=foo").await;
+    assert_eq!(response.json()["meta"]["total"], 0);
+
+    let response = user.get::<()>(url).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json(), {
+        ".**.requested_at" => "[datetime]",
+        ".**.scheduled_for" => "[datetime]",
+    }, @r#"
+    {
+      "scheduled_deletion": {
+        "message": null,
+        "requested_at": "[datetime]",
+        "requested_by": {
+          "avatar": null,
+          "id": 1,
+          "login": "foo",
+          "name": null,
+          "url": "https://github.com/foo"
+        },
+        "scheduled_for": "[datetime]"
+      }
+    }
+    "#);
+
+    // The crate can't be deleted or published to during the grace period
+    let response = delete_crate(&user, "foo").await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this crate is already scheduled for deletion"}]}"#);
+
+    let response = user
+        .publish_crate(PublishBuilder::new("foo", "1.1.0"))
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `foo` is scheduled for deletion. Cancel the deletion before publishing new versions."}]}"#);
+
+    // The crate is not deleted before the grace period ends
+    jobs::ProcessScheduledCrateDeletions
+        .enqueue(&mut conn)
+        .await?;
+    app.run_pending_background_jobs().await;
+    assert_crate_exists(&anon, "foo", true).await;
+
+    // Only owners can cancel the deletion
+    let other = app.db_new_user("bar").await;
+    let response = other.delete::<()>(url).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only owners have permission to cancel scheduled deletions"}]}"#);
+
+    let response = user.delete::<()>(url).await;
+    assert_snapshot!(response.status(), @"204 No Content");
+    app.run_pending_background_jobs().await;
+
+    // Assert that the crate is back in the index and search
+    assert!(upstream.crate_exists("foo")?);
+    let response = anon.get::<()>("/api/v1/crates?q=foo").await;
+    assert_eq!(response.json()["meta"]["total"], 1);
+
+    let response = user.delete::<()>(url).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this crate is not scheduled for deletion"}]}"#);
+
+    // Finalizing without a scheduled deletion does nothing
+    finalize_deletion(&app, &mut conn, "foo").await?;
+    assert_crate_exists(&anon, "foo", true).await;
+
+    Ok(())
+}
+
 // Publishes a crate with the given name and a single `v1.0.0` version.
 async fn publish_crate(user: &impl RequestHelper, name: &str) {
     let pb = PublishBuilder::new(name, "1.0.0");
@@ -337,6 +471,29 @@ async fn adjust_downloads(
     Ok(())
 }
 
+// Moves the end of the grace period of a scheduled crate deletion into the
+// past, and runs the background job that finalizes the deletion.
+async fn finalize_deletion(
+    app: &TestApp,
+    conn: &mut AsyncPgConnection,
+    name: &str,
+) -> anyhow::Result<()> {
+    let crate_id = crates::table
+        .filter(crates::name.eq(name))
+        .select(crates::id);
+
+    diesel::update(scheduled_crate_deletions::table)
+        .filter(scheduled_crate_deletions::crate_id.eq_any(crate_id))
+        .set(scheduled_crate_deletions::scheduled_for.eq(Utc::now() - TimeDelta::minutes(1)))
+        .execute(conn)
+        .await?;
+
+    jobs::ProcessScheduledCrateDeletions.enqueue(conn).await?;
+    app.run_pending_background_jobs().await;
+
+    Ok(())
+}
+
 // Performs the `DELETE` request to delete the crate, and runs any pending
 // background jobs, then returns the response.
 async fn delete_crate(user: &impl RequestHelper, name: &str) -> Response<()> {
@@ -207,6 +207,270 @@ async fn index_queries() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn combined_filters() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    let other_user = new_user("bar").insert(&mut conn).await?;
+
+    CrateBuilder::new("cli_tool", user.id)
+        .keyword("cli")
+        .version(
+            VersionBuilder::new("1.0.0")
+                .license("MIT OR Apache-2.0")
+                .rust_version("1.70")
+                .edition("2021")
+                .has_lib(false)
+                .bin_name("cli-tool"),
+        )
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("cli_lib", user.id)
+        .keyword("cli")
+        .version(
+            VersionBuilder::new("1.0.0")
+                .license("MIT")
+                .rust_version("1.80.1")
+                .edition("2024")
+                .has_lib(true),
+        )
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("cli_gpl", user.id)
+        .keyword("cli")
+        .version(
+            VersionBuilder::new("1.0.0")
+                .license("MIT AND GPL-3.0")
+                .edition("2021"),
+        )
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("cli_other", other_user.id)
+        .keyword("cli")
+        .version(VersionBuilder::new("1.0.0").license("MIT"))
+        .expect_build(&mut conn)
+        .await;
+
+    let names = |list: &crate::CrateList| {
+        let mut names = list
+            .crates
+            .iter()
+            .map(|c| c.name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+
+    // Owner and keyword filters are combined instead of being exclusive
+    let query = format!("keyword=cli&user_id={}", user.id);
+    for json in search_both(&anon, &query).await {
+        assert_eq!(names(&json), ["cli_gpl", "cli_lib", "cli_tool"]);
+    }
+
+    for json in search_both(&anon, "keyword=cli&license=MIT%20OR%20Apache-2.0").await {
+        assert_eq!(names(&json), ["cli_lib", "cli_other", "cli_tool"]);
+    }
+
+    for json in search_both(&anon, "license=Apache-2.0").await {
+        assert_eq!(names(&json), ["cli_tool"]);
+    }
+
+    for json in search_both(&anon, "rust_version=1.75").await {
+        assert_eq!(names(&json), ["cli_tool"]);
+    }
+
+    for json in search_both(&anon, "rust_version=1.80.1").await {
+        assert_eq!(names(&json), ["cli_lib", "cli_tool"]);
+    }
+
+    for json in search_both(&anon, "edition=2021&letter=c").await {
+        assert_eq!(names(&json), ["cli_gpl", "cli_tool"]);
+    }
+
+    for json in search_both(&anon, "has_lib=true").await {
+        assert_eq!(names(&json), ["cli_lib"]);
+    }
+
+    for json in search_both(&anon, "has_bin=true").await {
+        assert_eq!(names(&json), ["cli_tool"]);
+    }
+
+    for json in search_both(&anon, "has_bin=false&license=MIT").await {
+        assert_eq!(names(&json), ["cli_lib", "cli_other"]);
+    }
+
+    for json in search_both(&anon, "bin=cli-tool").await {
+        assert_eq!(names(&json), ["cli_tool"]);
+    }
+
+    for json in search_both(&anon, "bin=cli").await {
+        assert_eq!(names(&json), Vec::<String>::new());
+    }
+
+    update(crates::table)
+        .filter(crates::name.eq("cli_gpl"))
+        .set(crates::updated_at.eq(sql::<Timestamptz>("'2020-01-01T00:00:00Z'")))
+        .execute(&mut conn)
+        .await?;
+
+    for json in search_both(&anon, "keyword=cli&updated_since=2021-01-01").await {
+        assert_eq!(names(&json), ["cli_lib", "cli_other", "cli_tool"]);
+    }
+
+    for json in search_both(&anon, "updated_since=2019-12-31T00:00:00Z&edition=2021").await {
+        assert_eq!(names(&json), ["cli_gpl", "cli_tool"]);
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invalid_filter_values() {
+    let (_app, anon) = TestApp::init().empty().await;
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates", "license=not%20a%20license")
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates", "rust_version=1.70-beta")
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid `rust_version` value, expected e.g. `1.70`"}]}"#);
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates", "updated_since=yesterday")
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid `updated_since` value, expected a date like `2024-01-01`"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn facets() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    let cats = vec![
+        new_category("Category 1", "cat1", "Category 1 crates"),
+        new_category("Category 2", "cat2", "Category 2 crates"),
+    ];
+
+    insert_into(categories::table)
+        .values(cats)
+        .execute(&mut conn)
+        .await?;
+
+    let krate = CrateBuilder::new("foo", user.id)
+        .version(VersionBuilder::new("1.0.0").license("MIT").edition("2021"))
+        .expect_build(&mut conn)
+        .await;
+    Category::update_crate(&mut conn, krate.id, &["cat1", "cat2"]).await?;
+
+    let krate = CrateBuilder::new("bar", user.id)
+        .version(VersionBuilder::new("1.0.0").license("MIT").edition("2024"))
+        .expect_build(&mut conn)
+        .await;
+    Category::update_crate(&mut conn, krate.id, &["cat1"]).await?;
+
+    CrateBuilder::new("baz", user.id)
+        .version(VersionBuilder::new("1.0.0").license("Apache-2.0"))
+        .expect_build(&mut conn)
+        .await;
+
+    // Facets are only included if requested
+    let json = anon.get::<serde_json::Value>("/api/v1/crates").await.good();
+    assert_eq!(json["meta"].get("facets"), None);
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates", "include_facets=yes&per_page=1")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json()["meta"]["facets"]);
+
+    // Facets take the filters into account
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates", "include_facets=yes&license=MIT")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.json()["meta"]["facets"]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn fuzzy_search() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    CrateBuilder::new("serde", user.id)
+        .description("A generic serialization/deserialization framework")
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("serde_json", user.id)
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("tokio", user.id)
+        .expect_build(&mut conn)
+        .await;
+
+    // Crates with similar names are only returned if nothing matches directly
+    for json in search_both(&anon, "q=serde").await {
+        let names = json
+            .crates
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["serde", "serde_json"]);
+    }
+
+    for json in search_both(&anon, "q=serdr").await {
+        let names = json
+            .crates
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["serde", "serde_json"]);
+        assert_eq!(json.meta.total, 2);
+    }
+
+    let json = anon.get_with_query::<()>("/api/v1/crates", "q=serdr").await;
+    assert_eq!(json.json()["meta"]["suggestion"], "serde");
+
+    let json = anon
+        .get_with_query::<()>("/api/v1/crates", "q=tokio-rt")
+        .await;
+    assert_eq!(json.json()["meta"]["suggestion"], "tokio");
+
+    // No suggestions for exact matches
+    let json = anon.get_with_query::<()>("/api/v1/crates", "q=tokio").await;
+    assert_eq!(json.json()["meta"].get("suggestion"), None);
+
+    // No suggestions if nothing is similar enough
+    let json = anon
+        .get_with_query::<()>("/api/v1/crates", "q=zzzzzz")
+        .await;
+    assert_eq!(json.json()["meta"]["total"], 0);
+    assert_eq!(json.json()["meta"].get("suggestion"), None);
+
+    // Suggestions are only included on the first page
+    let json = anon
+        .get_with_query::<()>("/api/v1/crates", "q=serdr&page=2&per_page=1")
+        .await;
+    assert_eq!(json.json()["meta"].get("suggestion"), None);
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn search_includes_crates_where_name_is_stopword() -> anyhow::Result<()> {
     let (app, anon, user) = TestApp::init().with_user().await;
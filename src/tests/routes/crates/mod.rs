@@ -7,5 +7,6 @@ mod new;
 pub mod owners;
 mod read;
 mod reverse_dependencies;
+mod transfer_requests;
 mod update;
 pub mod versions;
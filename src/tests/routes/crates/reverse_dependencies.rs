@@ -1,6 +1,8 @@
-use crate::builders::{CrateBuilder, VersionBuilder};
+use crate::builders::{CrateBuilder, VersionBuilder, VersionDependency};
 use crate::util::{RequestHelper, TestApp};
+use crates_io::models::DependencyKind;
 use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::{Value, json};
 
 #[tokio::test(flavor = "multi_thread")]
 async fn reverse_dependencies() {
@@ -13,11 +15,11 @@ async fn reverse_dependencies() {
         .await;
 
     CrateBuilder::new("c2", user.id)
-        .version(VersionBuilder::new("1.0.0").dependency(&c1, None))
+        .version(VersionBuilder::new("1.0.0").dependency(&c1))
         .version(
             VersionBuilder::new("1.1.0")
-                .dependency(&c1, None)
-                .dependency(&c1, Some("foo")),
+                .dependency(&c1)
+                .dependency(VersionDependency::new(&c1).target("foo")),
         )
         .expect_build(&mut conn)
         .await;
@@ -55,7 +57,7 @@ async fn reverse_dependencies_when_old_version_doesnt_depend_but_new_does() {
 
     CrateBuilder::new("c2", user.id)
         .version("1.0.0")
-        .version(VersionBuilder::new("2.0.0").dependency(&c1, None))
+        .version(VersionBuilder::new("2.0.0").dependency(&c1))
         .expect_build(&mut conn)
         .await;
 
@@ -81,7 +83,7 @@ async fn reverse_dependencies_when_old_version_depended_but_new_doesnt() {
         .await;
 
     CrateBuilder::new("c2", user.id)
-        .version(VersionBuilder::new("1.0.0").dependency(&c1, None))
+        .version(VersionBuilder::new("1.0.0").dependency(&c1))
         .version("2.0.0")
         .expect_build(&mut conn)
         .await;
@@ -113,7 +115,7 @@ async fn prerelease_versions_not_included_in_reverse_dependencies() {
         .await;
 
     CrateBuilder::new("c3", user.id)
-        .version(VersionBuilder::new("1.0.0").dependency(&c1, None))
+        .version(VersionBuilder::new("1.0.0").dependency(&c1))
         .version("1.1.0-pre")
         .expect_build(&mut conn)
         .await;
@@ -141,7 +143,7 @@ async fn yanked_versions_not_included_in_reverse_dependencies() {
 
     CrateBuilder::new("c2", user.id)
         .version("1.0.0")
-        .version(VersionBuilder::new("2.0.0").dependency(&c1, None))
+        .version(VersionBuilder::new("2.0.0").dependency(&c1))
         .expect_build(&mut conn)
         .await;
 
@@ -189,7 +191,7 @@ async fn reverse_dependencies_includes_published_by_user_when_present() {
         .expect_build(&mut conn)
         .await;
     CrateBuilder::new("c2", user.id)
-        .version(VersionBuilder::new("2.0.0").dependency(&c1, None))
+        .version(VersionBuilder::new("2.0.0").dependency(&c1))
         .expect_build(&mut conn)
         .await;
 
@@ -204,7 +206,7 @@ async fn reverse_dependencies_includes_published_by_user_when_present() {
 
     // c3's version will have the published by info recorded
     CrateBuilder::new("c3", user.id)
-        .version(VersionBuilder::new("3.0.0").dependency(&c1, None))
+        .version(VersionBuilder::new("3.0.0").dependency(&c1))
         .expect_build(&mut conn)
         .await;
 
@@ -233,7 +235,7 @@ async fn reverse_dependencies_query_supports_u64_version_number_parts() {
     // The crate that depends on c1...
     CrateBuilder::new("c2", user.id)
         // ...has a patch version at the limits of what the semver crate supports
-        .version(VersionBuilder::new(&large_but_valid_version_number).dependency(&c1, None))
+        .version(VersionBuilder::new(&large_but_valid_version_number).dependency(&c1))
         .expect_build(&mut conn)
         .await;
 
@@ -247,6 +249,101 @@ async fn reverse_dependencies_query_supports_u64_version_number_parts() {
     });
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn reverse_dependencies_with_requirements_and_filters() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    let lib = CrateBuilder::new("lib", user.id)
+        .version("0.9.0")
+        .version("1.0.0")
+        .version("1.2.0")
+        .version("2.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let dependents = [
+        ("a", "^2", DependencyKind::Normal, false),
+        ("b", "^1.0", DependencyKind::Normal, false),
+        ("c", "^1.1", DependencyKind::Build, false),
+        ("d", "^0.9", DependencyKind::Normal, true),
+        ("e", "^2.0", DependencyKind::Dev, false),
+    ];
+
+    for (name, req, kind, optional) in dependents {
+        let dependency = VersionDependency::new(&lib)
+            .req(req)
+            .kind(kind)
+            .optional(optional)
+            .default_features(true);
+
+        CrateBuilder::new(name, user.id)
+            .version(VersionBuilder::new("1.0.0").dependency(dependency))
+            .expect_build(&mut conn)
+            .await;
+    }
+
+    let summarize = |json: Value| {
+        let mut dependencies = json["dependencies"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|dep| {
+                let version_id = &dep["version_id"];
+                let name = json["versions"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .find(|v| &v["id"] == version_id)
+                    .map(|v| v["crate"].clone());
+
+                json!([name, dep["req"], dep["matches_default_version"]])
+            })
+            .collect::<Vec<_>>();
+        dependencies.sort_by_key(|dep| dep[0].to_string());
+
+        json!({ "dependencies": dependencies, "meta": json["meta"] })
+    };
+
+    let response = anon
+        .get::<()>("/api/v1/crates/lib/reverse_dependencies")
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(summarize(response.json()));
+
+    let response = anon
+        .get::<()>("/api/v1/crates/lib/reverse_dependencies?kind=normal")
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(summarize(response.json()));
+
+    let response = anon
+        .get::<()>("/api/v1/crates/lib/reverse_dependencies?optional=true")
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(summarize(response.json()));
+
+    // The summary covers the dependents on all pages
+    let first_page = anon
+        .get::<Value>("/api/v1/crates/lib/reverse_dependencies?per_page=2")
+        .await
+        .good();
+    let last_page = anon
+        .get::<Value>("/api/v1/crates/lib/reverse_dependencies?per_page=2&page=3")
+        .await
+        .good();
+    assert_eq!(last_page["dependencies"].as_array().unwrap().len(), 1);
+    assert_eq!(first_page["meta"], last_page["meta"]);
+
+    let response = anon
+        .get::<()>("/api/v1/crates/lib/reverse_dependencies?kind=unknown")
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_unknown_crate() {
     let (_, anon) = TestApp::init().empty().await;
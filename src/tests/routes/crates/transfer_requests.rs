@@ -0,0 +1,383 @@
+use crate::builders::{CrateBuilder, PublishBuilder};
+use crate::util::insta::assert_json_snapshot;
+use crate::util::{MockTokenUser, RequestHelper, TestApp};
+use chrono::{TimeDelta, Utc};
+use crates_io::models::{CrateTransferAction, CrateTransferRequest};
+use crates_io::schema::{crate_owner_invitations, crate_publish_policies, trustpub_configs_github};
+use crates_io_database::models::trustpub::{NewGitHubConfig, NewToken};
+use crates_io_database::models::{NewCrateOwnerInvitation, NewCratePublishPolicy};
+use crates_io_trustpub::access_token::AccessToken;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use insta::assert_snapshot;
+use secrecy::ExposeSecret;
+use serde_json::json;
+
+const URL: &str = "/api/v1/crates/foo/transfer_requests";
+
+fn reason_body(reason: &str) -> String {
+    json!({ "reason": reason }).to_string()
+}
+
+fn action_body(action: &str, note: Option<&str>) -> String {
+    json!({ "action": action, "note": note }).to_string()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn transfer_request_accepted_by_owner() {
+    let (app, anon, owner) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let requester = app.db_new_user("bar").await;
+    let other = app.db_new_user("baz").await;
+
+    let response = requester
+        .post::<()>(URL, reason_body("I'd like to help"))
+        .await;
+    assert_snapshot!(response.status(), @"201 Created");
+    let json = response.json();
+    assert_json_snapshot!(json, {
+        ".transfer_request.id" => "[id]",
+        ".**.created_at" => "[datetime]",
+    }, @r#"
+    {
+      "transfer_request": {
+        "created_at": "[datetime]",
+        "events": [
+          {
+            "action": "created",
+            "actor": {
+              "avatar": null,
+              "id": 2,
+              "login": "bar",
+              "name": null,
+              "url": "https://github.com/bar"
+            },
+            "created_at": "[datetime]",
+            "note": null
+          }
+        ],
+        "id": "[id]",
+        "reason": "I'd like to help",
+        "requester": {
+          "avatar": null,
+          "id": 2,
+          "login": "bar",
+          "name": null,
+          "url": "https://github.com/bar"
+        },
+        "resolved_at": null,
+        "status": "pending"
+      }
+    }
+    "#);
+
+    let id = json["transfer_request"]["id"].as_i64().unwrap();
+    let url = format!("{URL}/{id}");
+
+    // Only owners and the requester can see the request
+    let response = owner.get::<()>(URL).await;
+    assert_eq!(response.json()["transfer_requests"][0]["id"], id);
+    let response = requester.get::<()>(URL).await;
+    assert_eq!(response.json()["transfer_requests"][0]["id"], id);
+    let response = other.get::<()>(URL).await;
+    assert_snapshot!(response.text(), @r#"{"transfer_requests":[]}"#);
+    let response = anon.get::<()>(URL).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    // Only owners can accept the request
+    let response = requester.put::<()>(&url, action_body("accept", None)).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only owners have permission to respond to transfer requests"}]}"#);
+
+    // Only the requester can withdraw the request
+    let response = owner.put::<()>(&url, action_body("withdraw", None)).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only the requester can withdraw a transfer request"}]}"#);
+
+    let response = owner
+        .put::<()>(&url, action_body("accept", Some("Welcome!")))
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let json = response.json();
+    assert_json_snapshot!(json["transfer_request"]["events"][1], {
+        ".created_at" => "[datetime]",
+    }, @r#"
+    {
+      "action": "accepted",
+      "actor": {
+        "avatar": null,
+        "id": 1,
+        "login": "foo",
+        "name": null,
+        "url": "https://github.com/foo"
+      },
+      "created_at": "[datetime]",
+      "note": "Welcome!"
+    }
+    "#);
+    assert_eq!(json["transfer_request"]["status"], "accepted");
+    assert!(json["transfer_request"]["resolved_at"].is_string());
+
+    // The requester is now an owner of the crate
+    let response = anon.get::<()>("/api/v1/crates/foo/owners").await;
+    let mut logins = response.json()["users"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|user| user["login"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    logins.sort();
+    assert_eq!(logins, ["bar", "foo"]);
+
+    // Resolved requests can't be changed anymore
+    let response = owner.put::<()>(&url, action_body("reject", None)).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the transfer request is not pending anymore"}]}"#);
+
+    app.run_pending_background_jobs().await;
+    assert_snapshot!(app.emails_snapshot().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn transfer_request_rejected_and_withdrawn() {
+    let (app, _, owner) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let requester = app.db_new_user("bar").await;
+
+    let response = requester.post::<()>(URL, reason_body("Please")).await;
+    assert_snapshot!(response.status(), @"201 Created");
+    let id = response.json()["transfer_request"]["id"].as_i64().unwrap();
+
+    let response = owner
+        .put::<()>(&format!("{URL}/{id}"), action_body("reject", None))
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(response.json()["transfer_request"]["status"], "rejected");
+
+    // A new request can be filed after the previous one was resolved
+    let response = requester
+        .post::<()>(URL, reason_body("Pretty please"))
+        .await;
+    assert_snapshot!(response.status(), @"201 Created");
+    let id = response.json()["transfer_request"]["id"].as_i64().unwrap();
+
+    let response = requester
+        .put::<()>(&format!("{URL}/{id}"), action_body("withdraw", None))
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(response.json()["transfer_request"]["status"], "withdrawn");
+
+    let response = owner.get::<()>(URL).await;
+    let statuses = response.json()["transfer_requests"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|request| request["status"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(statuses, ["withdrawn", "rejected"]);
+
+    // The requester did not become an owner
+    let response = owner.get::<()>("/api/v1/crates/foo/owners").await;
+    assert_eq!(response.json()["users"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn transfer_request_validation() {
+    let (app, anon, owner) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let requester = app.db_new_user("bar").await;
+
+    let response = anon.post::<()>(URL, reason_body("I'd like to help")).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    let response = owner.post::<()>(URL, reason_body("I'd like to help")).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"you are already an owner of this crate"}]}"#);
+
+    let response = requester.post::<()>(URL, reason_body("  ")).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"a reason for the transfer request is required"}]}"#);
+
+    let url = "/api/v1/crates/unknown/transfer_requests";
+    let response = requester
+        .post::<()>(url, reason_body("I'd like to help"))
+        .await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+
+    let response = requester
+        .post::<()>(URL, reason_body("I'd like to help"))
+        .await;
+    assert_snapshot!(response.status(), @"201 Created");
+
+    let response = requester
+        .post::<()>(URL, reason_body("I'd like to help"))
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"you already have a pending transfer request for this crate"}]}"#);
+
+    let response = owner
+        .put::<()>(&format!("{URL}/0"), action_body("accept", None))
+        .await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn transfer_request_approved_by_admin() {
+    let (app, anon, owner) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let requester = app.db_new_user("bar").await;
+    let admin = app.db_new_user("admin").await;
+
+    let response = requester.post::<()>(URL, reason_body("Abandoned")).await;
+    assert_snapshot!(response.status(), @"201 Created");
+    let id = response.json()["transfer_request"]["id"].as_i64().unwrap();
+
+    // Approval by the crates.io team happens via `crates-admin transfer-requests approve`
+    let request = CrateTransferRequest::find(&mut conn, id as i32)
+        .await
+        .unwrap();
+    let action = CrateTransferAction::Approved;
+    let admin_id = admin.as_model().id;
+    let event = request.resolve(&mut conn, action, admin_id, Some("Abandoned"));
+    assert!(event.await.unwrap().is_some());
+
+    // The requester replaced all existing owners
+    let response = anon.get::<()>("/api/v1/crates/foo/owners").await;
+    let owners = response.json()["users"].clone();
+    assert_eq!(owners.as_array().unwrap().len(), 1);
+    assert_eq!(owners[0]["login"], "bar");
+
+    // The request can't be resolved a second time
+    let action = CrateTransferAction::Denied;
+    let event = request.resolve(&mut conn, action, admin_id, None);
+    assert!(event.await.unwrap().is_none());
+
+    let response = requester.get::<()>(URL).await;
+    assert_eq!(
+        response.json()["transfer_requests"][0]["status"],
+        "approved"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn transfer_request_approval_revokes_previous_publishing_access() {
+    let (app, _, owner) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let krate = CrateBuilder::new("foo", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let requester = app.db_new_user("bar").await;
+    let invitee = app.db_new_user("baz").await;
+    let admin = app.db_new_user("admin").await;
+
+    let config = NewGitHubConfig {
+        crate_id: krate.id,
+        repository_owner: "rust-lang",
+        repository_owner_id: 42,
+        repository_name: "foo-rs",
+        workflow_filename: "publish.yml",
+        environment: None,
+    };
+    let config_id = config.insert(&mut conn).await.unwrap().id;
+
+    let token = AccessToken::generate();
+    let hashed_token = token.sha256();
+    let new_token = NewToken {
+        expires_at: Utc::now() + TimeDelta::minutes(30),
+        hashed_token: hashed_token.as_slice(),
+        crate_ids: &[krate.id],
+        trustpub_data: None,
+        github_config_ids: &[config_id],
+        gitlab_config_ids: &[],
+    };
+    new_token.insert(&mut conn).await.unwrap();
+    let token = token.finalize().expose_secret().to_string();
+
+    let invitation = NewCrateOwnerInvitation {
+        invited_user_id: invitee.as_model().id,
+        invited_by_user_id: owner.as_model().id,
+        crate_id: krate.id,
+        expires_at: Utc::now() + TimeDelta::days(30),
+    };
+    invitation.create(&mut conn).await.unwrap();
+
+    let policy = NewCratePublishPolicy {
+        crate_id: krate.id,
+        allowed_publishers: &[owner.as_model().id],
+        require_crate_scoped_tokens: true,
+        trustpub_github_config_ids: &[config_id],
+        trustpub_gitlab_config_ids: &[],
+        max_token_age_days: None,
+    };
+    policy.upsert(&mut conn).await.unwrap();
+
+    let response = requester.post::<()>(URL, reason_body("Abandoned")).await;
+    assert_snapshot!(response.status(), @"201 Created");
+    let id = response.json()["transfer_request"]["id"].as_i64().unwrap();
+
+    let request = CrateTransferRequest::find(&mut conn, id as i32)
+        .await
+        .unwrap();
+    let action = CrateTransferAction::Approved;
+    let admin_id = admin.as_model().id;
+    let event = request.resolve(&mut conn, action, admin_id, Some("Abandoned"));
+    assert!(event.await.unwrap().is_some());
+
+    let configs: i64 = trustpub_configs_github::table
+        .filter(trustpub_configs_github::crate_id.eq(krate.id))
+        .count()
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(configs, 0);
+
+    let invitations: i64 = crate_owner_invitations::table
+        .filter(crate_owner_invitations::crate_id.eq(krate.id))
+        .count()
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(invitations, 0);
+
+    let policies: i64 = crate_publish_policies::table
+        .filter(crate_publish_policies::crate_id.eq(krate.id))
+        .count()
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(policies, 0);
+
+    // Trusted Publishing tokens issued before the transfer can't be used anymore
+    let oidc_token_client = MockTokenUser::with_auth_header(token, app.clone());
+    let pb = PublishBuilder::new("foo", "1.1.0");
+    let response = oidc_token_client.publish_crate(pb).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    // The new owner can publish without being restricted by the old policy
+    let response = requester
+        .publish_crate(PublishBuilder::new("foo", "1.1.0"))
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+}
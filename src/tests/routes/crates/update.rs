@@ -1,7 +1,9 @@
-use crate::builders::CrateBuilder;
+use crate::builders::{CrateBuilder, VersionBuilder};
 use crate::util::{RequestHelper, TestApp};
+use claims::assert_none;
 use crates_io::models::token::{CrateScope, EndpointScope};
 use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
 
 #[tokio::test(flavor = "multi_thread")]
 async fn test_enable_trustpub_only() {
@@ -159,6 +161,129 @@ async fn test_update_nonexistent_crate() {
     assert_eq!(app.emails().await.len(), 0);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_deprecate_crate() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+    let upstream = app.upstream_index();
+
+    let owner_id = user.as_model().id;
+    let foo = CrateBuilder::new("foo", owner_id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+    CrateBuilder::new("foo-ng", owner_id)
+        .expect_build(&mut conn)
+        .await;
+
+    // `bar` depends on `foo` and is owned by another user
+    let other = app.db_new_user("baz").await;
+    CrateBuilder::new("bar", other.as_model().id)
+        .version(VersionBuilder::new("1.0.0").dependency(&foo))
+        .expect_build(&mut conn)
+        .await;
+
+    let url = "/api/v1/crates/foo";
+    let body = json!({ "crate": { "status": "deprecated", "successor": "FOO_NG" } });
+    let response = user.patch::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let json = response.json();
+    assert_json_snapshot!(json["crate"]["status"], @r#""deprecated""#);
+    assert_json_snapshot!(json["crate"]["successor"], @r#""foo-ng""#);
+
+    app.run_pending_background_jobs().await;
+
+    // The status is included in the index entries
+    let entries = upstream.crates_from_index_head("foo")?;
+    assert_eq!(entries[0].status.as_deref(), Some("deprecated"));
+    assert_eq!(entries[0].successor.as_deref(), Some("foo-ng"));
+
+    // ... and in the crate metadata and search results
+    let response = anon.get::<()>(url).await;
+    assert_eq!(response.json()["crate"]["status"], "deprecated");
+    let response = anon.get::<()>("/api/v1/crates?q=foo").await;
+    let json = response.json();
+    let krates = json["crates"].as_array().unwrap();
+    let krate = krates.iter().find(|krate| krate["name"] == "foo").unwrap();
+    assert_eq!(krate["status"], "deprecated");
+    assert_eq!(krate["successor"], "foo-ng");
+
+    // The owners of dependent crates are notified
+    assert_snapshot!(app.emails_snapshot().await);
+
+    // Setting the status without a successor removes the successor
+    let body = json!({ "crate": { "status": "active" } });
+    let response = user.patch::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let json = response.json();
+    assert_json_snapshot!(json["crate"]["status"], @r#""active""#);
+    assert_json_snapshot!(json["crate"]["successor"], @"null");
+
+    app.run_pending_background_jobs().await;
+
+    let entries = upstream.crates_from_index_head("foo")?;
+    assert_none!(&entries[0].status);
+    assert_none!(&entries[0].successor);
+
+    // Deprecating the crate again does not send the notifications again
+    let emails = app.emails().await.len();
+    let body = json!({ "crate": { "status": "deprecated" } });
+    let response = user.patch::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    app.run_pending_background_jobs().await;
+    assert_eq!(app.emails().await.len(), emails);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_update_status_validation() {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let owner_id = user.as_model().id;
+    CrateBuilder::new("foo", owner_id)
+        .expect_build(&mut conn)
+        .await;
+
+    let url = "/api/v1/crates/foo";
+
+    let body = json!({ "crate": { "successor": "bar" } });
+    let response = user.patch::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the successor can only be set together with the status"}]}"#);
+
+    let body = json!({ "crate": { "status": "passively_maintained", "successor": "bar" } });
+    let response = user.patch::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"a successor can only be set for deprecated or archived crates"}]}"#);
+
+    let body = json!({ "crate": { "status": "archived", "successor": "bar" } });
+    let response = user.patch::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the successor crate `bar` does not exist"}]}"#);
+
+    let body = json!({ "crate": { "status": "deprecated", "successor": "foo" } });
+    let response = user.patch::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"a crate can't be its own successor"}]}"#);
+
+    let body = json!({ "crate": { "status": "unmaintained" } });
+    let response = user.patch::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"422 Unprocessable Entity");
+
+    // Archiving without a successor is fine
+    let body = json!({ "crate": { "status": "archived" } });
+    let response = user.patch::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(response.json()["crate"]["status"], "archived");
+
+    // Only deprecations trigger notifications
+    app.run_pending_background_jobs().await;
+    assert_eq!(app.emails().await.len(), 0);
+}
+
 mod auth {
     use super::*;
 
@@ -211,6 +336,29 @@ mod auth {
         assert!(!app.emails().await.is_empty());
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn token_user_cannot_change_status() {
+        let (app, user) = prepare().await;
+        let token = user
+            .db_new_scoped_token(
+                "test-token",
+                None,
+                Some(vec![EndpointScope::TrustedPublishing]),
+                None,
+            )
+            .await;
+
+        let url = format!("/api/v1/crates/{}", CRATE_NAME);
+        let body = serde_json::json!({ "crate": { "status": "deprecated" } });
+        let response = token.patch::<()>(&url, body.to_string()).await;
+        assert_snapshot!(response.status(), @"403 Forbidden");
+        assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the maintenance status can only be changed from the crates.io website"}]}"#);
+
+        let response = user.get::<()>(&url).await;
+        assert_eq!(response.json()["crate"]["status"], "active");
+        assert_eq!(app.emails().await.len(), 0);
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn token_user_with_incorrect_endpoint_scope() {
         let (app, user) = prepare().await;
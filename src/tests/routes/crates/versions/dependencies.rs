@@ -22,7 +22,7 @@ async fn dependencies() {
         .expect_build(&mut conn)
         .await;
     VersionBuilder::new("1.0.0")
-        .dependency(&c2, None)
+        .dependency(&c2)
         .expect_build(c1.id, user.id, &mut conn)
         .await;
 
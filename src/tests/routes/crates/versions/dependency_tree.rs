@@ -0,0 +1,138 @@
+use crate::builders::{CrateBuilder, VersionBuilder, VersionDependency};
+use crate::util::{RequestHelper, TestApp};
+use crates_io::models::{Crate, DependencyKind};
+use insta::{assert_json_snapshot, assert_snapshot};
+
+fn dep(krate: &Crate, req: &str) -> VersionDependency {
+    VersionDependency::new(krate)
+        .req(req)
+        .default_features(true)
+}
+
+async fn create_crates(app: &TestApp) -> anyhow::Result<()> {
+    let mut conn = app.db_conn().await;
+    let user = app.db_new_user("foo").await;
+    let user_id = user.as_model().id;
+
+    let serde_derive = CrateBuilder::new("serde_derive", user_id)
+        .version(VersionBuilder::new("1.1.0").license("MIT OR Apache-2.0"))
+        .expect_build(&mut conn)
+        .await;
+
+    let serde = CrateBuilder::new("serde", user_id)
+        .version(VersionBuilder::new("1.0.0").license("MIT OR Apache-2.0"))
+        .version(
+            VersionBuilder::new("1.1.0")
+                .license("MIT OR Apache-2.0")
+                .feature("derive", &["dep:serde_derive"])
+                .dependency(dep(&serde_derive, "=1.1.0").optional(true)),
+        )
+        .version(VersionBuilder::new("1.2.0").yanked(true))
+        .expect_build(&mut conn)
+        .await;
+
+    let itoa = CrateBuilder::new("itoa", user_id)
+        .version(VersionBuilder::new("1.0.0").license("MIT"))
+        .expect_build(&mut conn)
+        .await;
+
+    let log = CrateBuilder::new("log", user_id)
+        .version(VersionBuilder::new("0.3.9").license("MIT"))
+        .version(VersionBuilder::new("0.4.20").license("MIT"))
+        .expect_build(&mut conn)
+        .await;
+
+    let serde_json = CrateBuilder::new("serde_json", user_id)
+        .version(
+            VersionBuilder::new("1.0.0")
+                .license("GPL-3.0")
+                .dependency(dep(&serde, "^1.0"))
+                .dependency(dep(&log, "^0.3"))
+                .dependency(dep(&itoa, "^2")),
+        )
+        .expect_build(&mut conn)
+        .await;
+
+    let winapi = CrateBuilder::new("winapi", user_id)
+        .version(VersionBuilder::new("0.3.9").license("MIT"))
+        .expect_build(&mut conn)
+        .await;
+
+    let cc = CrateBuilder::new("cc", user_id)
+        .version(VersionBuilder::new("1.0.0"))
+        .expect_build(&mut conn)
+        .await;
+
+    let testing = CrateBuilder::new("testing", user_id)
+        .version(VersionBuilder::new("1.0.0").license("MIT"))
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("app", user_id)
+        .version(
+            VersionBuilder::new("1.0.0")
+                .license("MIT")
+                .feature("default", &["json"])
+                .feature("json", &["dep:serde_json"])
+                .feature("derive", &["serde/derive"])
+                .dependency(dep(&serde, "^1"))
+                .dependency(dep(&serde_json, "^1").optional(true))
+                .dependency(dep(&winapi, "^0.3").target("cfg(windows)"))
+                .dependency(dep(&cc, "^1").kind(DependencyKind::Build))
+                .dependency(dep(&testing, "^1").kind(DependencyKind::Dev))
+                .dependency(dep(&log, "^0.4")),
+        )
+        .expect_build(&mut conn)
+        .await;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dependency_tree() -> anyhow::Result<()> {
+    let (app, anon) = TestApp::init().empty().await;
+    create_crates(&app).await?;
+
+    let url = "/api/v1/crates/app/1.0.0/dependency_tree?licenses=MIT,Apache-2.0";
+    let response = anon.get::<()>(url).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dependency_tree_with_features_and_target() -> anyhow::Result<()> {
+    let (app, anon) = TestApp::init().empty().await;
+    create_crates(&app).await?;
+
+    let url = "/api/v1/crates/app/1.0.0/dependency_tree?default_features=false&features=derive&target=x86_64-unknown-linux-gnu";
+    let response = anon.get::<()>(url).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dependency_tree_errors() -> anyhow::Result<()> {
+    let (app, anon) = TestApp::init().empty().await;
+    create_crates(&app).await?;
+
+    let url = "/api/v1/crates/app/1.0.0/dependency_tree?features=unknown";
+    let response = anon.get::<()>(url).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"unknown feature `unknown`"}]}"#);
+
+    let url = "/api/v1/crates/app/1.0.0/dependency_tree?target=unknown-target";
+    let response = anon.get::<()>(url).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"unknown target `unknown-target`"}]}"#);
+
+    let url = "/api/v1/crates/app/2.0.0/dependency_tree";
+    let response = anon.get::<()>(url).await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `app` does not have a version `2.0.0`"}]}"#);
+
+    Ok(())
+}
@@ -1,8 +1,16 @@
 use crate::builders::{CrateBuilder, VersionBuilder};
 use crate::util::{RequestHelper as _, TestApp};
+use chrono::{Duration, Utc};
+use crates_io::schema::docs_rs_rebuild_requests;
+use crates_io::worker::jobs;
 use crates_io_database::models::NewUser;
-use crates_io_docs_rs::MockDocsRsClient;
-use insta::assert_snapshot;
+use crates_io_docs_rs::{DocsRsError, MockDocsRsClient};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use insta::{assert_json_snapshot, assert_snapshot};
+use mockall::Sequence;
+use serde_json::Value;
 
 #[tokio::test(flavor = "multi_thread")]
 async fn test_trigger_rebuild_ok() -> anyhow::Result<()> {
@@ -89,3 +97,232 @@ async fn test_trigger_rebuild_unknown_crate_doesnt_queue_job() -> anyhow::Result
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_trigger_rebuild_deduplicates_requests() -> anyhow::Result<()> {
+    let mut docs_rs_mock = MockDocsRsClient::new();
+    docs_rs_mock
+        .expect_rebuild_docs()
+        .returning(|_, _| Ok(()))
+        .times(1);
+
+    let (app, _client, cookie_client) =
+        TestApp::full().with_docs_rs(docs_rs_mock).with_user().await;
+
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("krate", cookie_client.as_model().id)
+        .version(VersionBuilder::new("0.1.0"))
+        .build(&mut conn)
+        .await?;
+
+    let response = cookie_client
+        .post::<()>("/api/v1/crates/krate/0.1.0/rebuild_docs", "")
+        .await;
+    assert_snapshot!(response.status(), @"201 Created");
+    let first_id = response.json()["rebuild_request"]["id"].clone();
+
+    // The first request has not been sent to docs.rs yet, so the second
+    // request is deduplicated.
+    let response = cookie_client
+        .post::<()>("/api/v1/crates/krate/0.1.0/rebuild_docs", "")
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(response.json()["rebuild_request"]["id"], first_id);
+
+    app.run_pending_background_jobs().await;
+
+    let response = cookie_client
+        .get::<()>("/api/v1/crates/krate/0.1.0/rebuild_docs")
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json(), {
+        ".rebuild_requests[].id" => "[id]",
+        ".rebuild_requests[].requested_by.id" => "[id]",
+        ".rebuild_requests[].requested_at" => "[datetime]",
+        ".rebuild_requests[].last_attempt_at" => "[datetime]",
+    });
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_trigger_rebuild_rate_limited() -> anyhow::Result<()> {
+    let mut sequence = Sequence::new();
+    let mut docs_rs_mock = MockDocsRsClient::new();
+    docs_rs_mock
+        .expect_rebuild_docs()
+        .returning(|_, _| Err(DocsRsError::RateLimited))
+        .times(1)
+        .in_sequence(&mut sequence);
+    docs_rs_mock
+        .expect_rebuild_docs()
+        .returning(|_, _| Ok(()))
+        .times(1)
+        .in_sequence(&mut sequence);
+
+    let (app, _client, cookie_client) =
+        TestApp::full().with_docs_rs(docs_rs_mock).with_user().await;
+
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("krate", cookie_client.as_model().id)
+        .version(VersionBuilder::new("0.1.0"))
+        .build(&mut conn)
+        .await?;
+
+    let response = cookie_client
+        .post::<()>("/api/v1/crates/krate/0.1.0/rebuild_docs", "")
+        .await;
+    assert_snapshot!(response.status(), @"201 Created");
+
+    // The rate limited job is not failing, but the request is scheduled
+    // for another attempt later.
+    app.run_pending_background_jobs().await;
+
+    let response = cookie_client
+        .get::<()>("/api/v1/crates/krate/0.1.0/rebuild_docs")
+        .await;
+    let json = response.json();
+    let request = &json["rebuild_requests"][0];
+    assert_eq!(request["status"], "rate_limited");
+    assert_eq!(request["attempts"], 1);
+    assert!(request["next_attempt_at"].is_string());
+
+    // Further rebuild requests are deduplicated while the first one is
+    // waiting for its next attempt.
+    let response = cookie_client
+        .post::<()>("/api/v1/crates/krate/0.1.0/rebuild_docs", "")
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    // The request is not retried before `next_attempt_at`…
+    jobs::DocsRsRetryRebuildRequests.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    // … but afterwards.
+    diesel::update(docs_rs_rebuild_requests::table)
+        .set(docs_rs_rebuild_requests::next_attempt_at.eq(Utc::now() - Duration::minutes(1)))
+        .execute(&mut conn)
+        .await?;
+
+    jobs::DocsRsRetryRebuildRequests.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let response = cookie_client
+        .get::<()>("/api/v1/crates/krate/0.1.0/rebuild_docs")
+        .await;
+    let json = response.json();
+    assert_eq!(json["rebuild_requests"].as_array().unwrap().len(), 1);
+    let request = &json["rebuild_requests"][0];
+    assert_eq!(request["status"], "queued");
+    assert_eq!(request["attempts"], 2);
+    assert_eq!(request["next_attempt_at"], Value::Null);
+    assert_eq!(request["response"], Value::Null);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_trigger_rebuild_rejected() -> anyhow::Result<()> {
+    let mut docs_rs_mock = MockDocsRsClient::new();
+    docs_rs_mock
+        .expect_rebuild_docs()
+        .returning(|_, _| Err(DocsRsError::BadRequest("already queued".into())))
+        .times(1);
+
+    let (app, _client, cookie_client) =
+        TestApp::full().with_docs_rs(docs_rs_mock).with_user().await;
+
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("krate", cookie_client.as_model().id)
+        .version(VersionBuilder::new("0.1.0"))
+        .build(&mut conn)
+        .await?;
+
+    let response = cookie_client
+        .post::<()>("/api/v1/crates/krate/0.1.0/rebuild_docs", "")
+        .await;
+    assert_snapshot!(response.status(), @"201 Created");
+
+    app.run_pending_background_jobs().await;
+
+    let response = cookie_client
+        .get::<()>("/api/v1/crates/krate/0.1.0/rebuild_docs")
+        .await;
+    let json = response.json();
+    let request = &json["rebuild_requests"][0];
+    assert_eq!(request["status"], "rejected");
+    assert_eq!(request["response"], "already queued");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_trigger_rebuild_without_docs_rs() -> anyhow::Result<()> {
+    let (app, _client, cookie_client) = TestApp::full().with_user().await;
+
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("krate", cookie_client.as_model().id)
+        .version(VersionBuilder::new("0.1.0"))
+        .build(&mut conn)
+        .await?;
+
+    let response = cookie_client
+        .post::<()>("/api/v1/crates/krate/0.1.0/rebuild_docs", "")
+        .await;
+    assert_snapshot!(response.status(), @"201 Created");
+
+    app.run_pending_background_jobs().await;
+
+    let response = cookie_client
+        .get::<()>("/api/v1/crates/krate/0.1.0/rebuild_docs")
+        .await;
+    let json = response.json();
+    let request = &json["rebuild_requests"][0];
+    assert_eq!(request["status"], "failed");
+    assert_eq!(request["response"], "docs.rs is not configured");
+
+    // The failed request doesn't block further rebuild requests
+    let response = cookie_client
+        .post::<()>("/api/v1/crates/krate/0.1.0/rebuild_docs", "")
+        .await;
+    assert_snapshot!(response.status(), @"201 Created");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_list_rebuilds_permission_failed() -> anyhow::Result<()> {
+    let (app, anon, cookie_client) = TestApp::full().with_user().await;
+
+    let mut conn = app.db_conn().await;
+
+    let other_user = NewUser::builder()
+        .gh_id(111)
+        .gh_login("other_user")
+        .gh_encrypted_token(&[])
+        .build()
+        .insert(&mut conn)
+        .await?;
+
+    CrateBuilder::new("krate", other_user.id)
+        .version(VersionBuilder::new("0.1.0"))
+        .build(&mut conn)
+        .await?;
+
+    let response = cookie_client
+        .get::<()>("/api/v1/crates/krate/0.1.0/rebuild_docs")
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"user doesn't have permission to list docs rebuilds"}]}"#);
+
+    let response = anon
+        .get::<()>("/api/v1/crates/krate/0.1.0/rebuild_docs")
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    Ok(())
+}
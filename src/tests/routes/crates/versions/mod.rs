@@ -1,5 +1,6 @@
 mod authors;
 pub mod dependencies;
+mod dependency_tree;
 mod docs;
 pub mod download;
 mod list;
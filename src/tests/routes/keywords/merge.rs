@@ -0,0 +1,90 @@
+use crate::builders::CrateBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::models::Keyword;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use insta::assert_snapshot;
+use serde_json::json;
+
+const URL: &str = "/api/private/admin/keywords/async/merge";
+
+fn merge_body(into: &str) -> String {
+    json!({ "into": into }).to_string()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn merge_keyword() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user_id = user.as_model().id;
+
+    let admin = app.db_new_admin("admin").await;
+
+    CrateBuilder::new("foo", user_id)
+        .keyword("async")
+        .expect_build(&mut conn)
+        .await;
+    CrateBuilder::new("bar", user_id)
+        .keyword("async")
+        .keyword("asynchronous")
+        .expect_build(&mut conn)
+        .await;
+    CrateBuilder::new("baz", user_id)
+        .keyword("asynchronous")
+        .expect_build(&mut conn)
+        .await;
+
+    let response = user.put::<()>(URL, merge_body("asynchronous")).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"must be an admin to use this route"}]}"#);
+
+    let response = admin.put::<()>(URL, merge_body("async")).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"a keyword can't be merged into itself"}]}"#);
+
+    let response = admin.put::<()>(URL, merge_body("unknown")).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the keyword `unknown` does not exist"}]}"#);
+
+    let response = admin.put::<()>(URL, merge_body("asynchronous")).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let json = response.json();
+    assert_eq!(json["keyword"]["keyword"], "asynchronous");
+    assert_eq!(json["keyword"]["crates_cnt"], 3);
+
+    // The merged keyword is an alias now
+    let response = anon.get::<()>("/api/v1/keywords/async").await;
+    assert_eq!(response.json()["keyword"]["keyword"], "asynchronous");
+
+    let response = admin.put::<()>(URL, merge_body("asynchronous")).await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+
+    let response = anon.get::<()>("/api/v1/crates?keyword=asynchronous").await;
+    assert_eq!(response.json()["meta"]["total"], 3);
+
+    // Searching for the alias finds the crates of the merged keyword
+    let response = anon.get::<()>("/api/v1/crates?keyword=Async").await;
+    assert_eq!(response.json()["meta"]["total"], 3);
+
+    let response = anon.get::<()>("/api/v1/crates?all_keywords=async").await;
+    assert_eq!(response.json()["meta"]["total"], 3);
+
+    let response = anon
+        .get::<()>("/api/v1/crates?all_keywords=async%20asynchronous")
+        .await;
+    assert_eq!(response.json()["meta"]["total"], 3);
+
+    // New crates with the alias are associated with the merged keyword
+    let krate = CrateBuilder::new("qux", user_id)
+        .expect_build(&mut conn)
+        .await;
+    Keyword::update_crate(&mut conn, krate.id, &["Async", "asynchronous"]).await?;
+
+    let keyword = Keyword::find_by_keyword(&mut conn, "asynchronous").await?;
+    assert_eq!(keyword.crates_cnt, 4);
+
+    let keywords: Vec<Keyword> = Keyword::query().load(&mut conn).await?;
+    assert_eq!(keywords.len(), 1);
+
+    Ok(())
+}
@@ -2,3 +2,4 @@ mod email_notifications;
 pub mod get;
 pub mod tokens;
 mod updates;
+mod updates_feed;
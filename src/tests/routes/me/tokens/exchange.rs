@@ -0,0 +1,264 @@
+use crate::builders::{CrateBuilder, PublishBuilder};
+use crate::util::{MockTokenUser, RequestHelper, TestApp};
+use chrono::{TimeDelta, Utc};
+use crates_io::models::token::{CrateScope, EndpointScope};
+use crates_io::schema::{api_token_usages, api_tokens};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
+
+const URL: &str = "/api/v1/tokens/exchange";
+
+fn crate_scopes(patterns: &[&str]) -> Option<Vec<CrateScope>> {
+    let scopes = patterns
+        .iter()
+        .map(|pattern| CrateScope::try_from(*pattern));
+    Some(scopes.collect::<Result<_, _>>().unwrap())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn exchange_and_publish() {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let endpoint_scopes = vec![EndpointScope::TokenExchange, EndpointScope::PublishUpdate];
+    let refresh_token = user
+        .db_new_scoped_token(
+            "release",
+            crate_scopes(&["foo*"]),
+            Some(endpoint_scopes),
+            None,
+        )
+        .await;
+
+    // The refresh token itself can't be used to publish
+    let response = refresh_token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this token can only be used to create short-lived API tokens"}]}"#);
+
+    let body = json!({ "api_token": { "crate_scopes": ["foo"], "expires_in_minutes": 5 } });
+    let response = refresh_token.post::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let json = response.json();
+    assert_json_snapshot!(json, {
+        ".api_token.id" => "[id]",
+        ".api_token.token" => "[token]",
+        ".api_token.created_at" => "[datetime]",
+        ".api_token.expired_at" => "[datetime]",
+    }, @r#"
+    {
+      "api_token": {
+        "crate_scopes": [
+          "foo"
+        ],
+        "created_at": "[datetime]",
+        "endpoint_scopes": [
+          "publish-update"
+        ],
+        "expired_at": "[datetime]",
+        "id": "[id]",
+        "last_used_at": null,
+        "name": "release (short-lived)",
+        "token": "[token]"
+      }
+    }
+    "#);
+
+    let plaintext = json["api_token"]["token"].as_str().unwrap().to_string();
+    let token = MockTokenUser::with_auth_header(plaintext, app.clone());
+
+    let response = token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    // Short-lived tokens can't be exchanged again
+    let response = token
+        .post::<()>(URL, json!({ "api_token": {} }).to_string())
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    // Short-lived tokens are not listed with the other tokens of the user
+    let response = user.get::<()>("/api/v1/me/tokens").await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let names = response.json()["api_tokens"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|token| token["name"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["release"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn exchanged_tokens_inherit_age_and_usages_of_refresh_token() {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+    CrateBuilder::new("bar", user.as_model().id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let body = json!({ "publish_policy": { "max_token_age_days": 30 } });
+    let response = user
+        .put::<()>("/api/v1/crates/foo/publish_policy", body.to_string())
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let endpoint_scopes = vec![EndpointScope::TokenExchange, EndpointScope::PublishUpdate];
+    let refresh_token = user
+        .db_new_scoped_token("release", None, Some(endpoint_scopes), None)
+        .await;
+    let refresh_token_id = refresh_token.as_model().id;
+
+    diesel::update(api_tokens::table.find(refresh_token_id))
+        .set(api_tokens::created_at.eq(Utc::now() - TimeDelta::days(31)))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let body = json!({ "api_token": {} });
+    let response = refresh_token.post::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let plaintext = response.json()["api_token"]["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let token = MockTokenUser::with_auth_header(plaintext, app.clone());
+
+    // Exchanging a new token does not get around the maximum token age
+    let response = token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"the publish policy of this crate only allows API tokens that were created within the last 30 days"}]}"#);
+
+    let response = token
+        .publish_crate(PublishBuilder::new("bar", "1.1.0"))
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    // The usages of the short-lived token are recorded for the refresh token
+    let token_ids: Vec<i32> = api_token_usages::table
+        .select(api_token_usages::api_token_id)
+        .load(&mut conn)
+        .await
+        .unwrap();
+    assert!(!token_ids.is_empty());
+    assert!(token_ids.iter().all(|id| *id == refresh_token_id));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn exchange_validation() {
+    let (_, _, user, legacy_token) = TestApp::init().with_token().await;
+
+    let endpoint_scopes = vec![EndpointScope::TokenExchange, EndpointScope::PublishUpdate];
+    let refresh_token = user
+        .db_new_scoped_token(
+            "release",
+            crate_scopes(&["foo*"]),
+            Some(endpoint_scopes),
+            None,
+        )
+        .await;
+
+    let publish_token = user
+        .db_new_scoped_token(
+            "publish",
+            None,
+            Some(vec![EndpointScope::PublishUpdate]),
+            None,
+        )
+        .await;
+
+    let body = json!({ "api_token": {} }).to_string();
+
+    let response = user.post::<()>(URL, body.clone()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only API tokens can be exchanged for short-lived API tokens"}]}"#);
+
+    let response = legacy_token.post::<()>(URL, body.clone()).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    let response = publish_token.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    let body = json!({ "api_token": { "endpoint_scopes": ["yank"] } });
+    let response = refresh_token.post::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"endpoint scopes must be a subset of the endpoint scopes of the refresh token"}]}"#);
+
+    let body = json!({ "api_token": { "endpoint_scopes": ["token-exchange"] } });
+    let response = refresh_token.post::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"short-lived API tokens can't be exchanged for other API tokens"}]}"#);
+
+    let body = json!({ "api_token": { "crate_scopes": ["bar"] } });
+    let response = refresh_token.post::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate scopes must be a subset of the crate scopes of the refresh token"}]}"#);
+
+    let body = json!({ "api_token": { "crate_scopes": ["*"] } });
+    let response = refresh_token.post::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+
+    let body = json!({ "api_token": { "crate_scopes": [] } });
+    let response = refresh_token.post::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+
+    let body = json!({ "api_token": { "expires_in_minutes": 61 } });
+    let response = refresh_token.post::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"`expires_in_minutes` must be between 1 and 60"}]}"#);
+
+    let body = json!({ "api_token": { "crate_scopes": ["foo-*"] } });
+    let response = refresh_token.post::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn revoking_refresh_token_revokes_exchanged_tokens() {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let endpoint_scopes = vec![EndpointScope::TokenExchange, EndpointScope::PublishUpdate];
+    let refresh_token = user
+        .db_new_scoped_token("release", None, Some(endpoint_scopes), None)
+        .await;
+
+    let response = refresh_token
+        .post::<()>(URL, json!({ "api_token": {} }).to_string())
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let plaintext = response.json()["api_token"]["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let token = MockTokenUser::with_auth_header(plaintext, app.clone());
+
+    let url = format!("/api/v1/me/tokens/{}", refresh_token.as_model().id);
+    let response = user.delete::<()>(&url).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let response = token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"authentication failed"}]}"#);
+}
@@ -1,5 +1,7 @@
 pub mod create;
 pub mod delete;
 pub mod delete_current;
+pub mod exchange;
 pub mod get;
 pub mod list;
+pub mod usage;
@@ -0,0 +1,215 @@
+use crate::builders::CrateBuilder;
+use crate::util::{MockRequestExt, RequestHelper, TestApp};
+use chrono::{TimeDelta, Utc};
+use crates_io::schema::{api_token_usage_baselines, api_token_usages, api_tokens};
+use crates_io::worker::jobs;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::Method;
+use insta::{assert_json_snapshot, assert_snapshot};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn show_recent_usages() {
+    let (_, _, _, token) = TestApp::init().with_token().await;
+    let url = format!("/api/v1/me/tokens/{}", token.as_model().id);
+
+    let mut request = token.get_request(&url);
+    request.header("CloudFront-Viewer-Country", "de");
+    request.header("CloudFront-Viewer-ASN", "16509");
+    let response = token.run::<()>(request).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let response = token.get::<()>(&url).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json()["recent_usages"], {
+        "[].created_at" => "[datetime]",
+    }, @r#"
+    [
+      {
+        "asn": null,
+        "country": null,
+        "crate_name": null,
+        "created_at": "[datetime]",
+        "endpoint": "GET /api/v1/me/tokens/{id}",
+        "ip": "127.0.0.1",
+        "user_agent": "conduit-test"
+      },
+      {
+        "asn": 16509,
+        "country": "DE",
+        "crate_name": null,
+        "created_at": "[datetime]",
+        "endpoint": "GET /api/v1/me/tokens/{id}",
+        "ip": "127.0.0.1",
+        "user_agent": "conduit-test"
+      }
+    ]
+    "#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn alert_on_unusual_usage() {
+    let (app, _, user, token) = TestApp::full()
+        .with_config(|config| config.api_token_usage_alerts = true)
+        .with_token()
+        .await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let url = format!("/api/v1/me/tokens/{}", token.as_model().id);
+
+    // The first usage is the baseline and does not trigger an alert
+    let mut request = token.get_request(&url);
+    request.header("CloudFront-Viewer-Country", "DE");
+    request.header("CloudFront-Viewer-ASN", "3320");
+    assert_snapshot!(token.run::<()>(request).await.status(), @"200 OK");
+
+    let mut request = token.get_request(&url);
+    request.header("CloudFront-Viewer-Country", "DE");
+    request.header("CloudFront-Viewer-ASN", "3320");
+    assert_snapshot!(token.run::<()>(request).await.status(), @"200 OK");
+
+    app.run_pending_background_jobs().await;
+    assert_eq!(app.emails().await.len(), 0);
+
+    let mut request = token.get_request(&url);
+    request.header("CloudFront-Viewer-Country", "US");
+    request.header("CloudFront-Viewer-ASN", "16509");
+    assert_snapshot!(token.run::<()>(request).await.status(), @"200 OK");
+
+    let mut request = token.request_builder(Method::PUT, "/api/v1/crates/foo/follow");
+    request.header("CloudFront-Viewer-Country", "DE");
+    request.header("CloudFront-Viewer-ASN", "3320");
+    assert_snapshot!(token.run::<()>(request).await.status(), @"200 OK");
+
+    app.run_pending_background_jobs().await;
+    assert_snapshot!(app.emails_snapshot().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn alert_after_usages_were_deleted() {
+    let (app, _, _, token) = TestApp::full()
+        .with_config(|config| config.api_token_usage_alerts = true)
+        .with_token()
+        .await;
+    let mut conn = app.db_conn().await;
+
+    let url = format!("/api/v1/me/tokens/{}", token.as_model().id);
+
+    let mut request = token.get_request(&url);
+    request.header("CloudFront-Viewer-Country", "DE");
+    request.header("CloudFront-Viewer-ASN", "3320");
+    assert_snapshot!(token.run::<()>(request).await.status(), @"200 OK");
+
+    diesel::update(api_token_usages::table)
+        .set(api_token_usages::created_at.eq(Utc::now() - TimeDelta::days(91)))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    jobs::DeleteOldApiTokenUsages
+        .enqueue(&mut conn)
+        .await
+        .unwrap();
+    app.run_pending_background_jobs().await;
+
+    let usages: i64 = api_token_usages::table
+        .count()
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(usages, 0);
+
+    // The baseline is kept, so known usages don't trigger an alert ...
+    let mut request = token.get_request(&url);
+    request.header("CloudFront-Viewer-Country", "DE");
+    request.header("CloudFront-Viewer-ASN", "3320");
+    assert_snapshot!(token.run::<()>(request).await.status(), @"200 OK");
+
+    app.run_pending_background_jobs().await;
+    assert_eq!(app.emails().await.len(), 0);
+
+    // ... but unknown ones still do
+    let mut request = token.get_request(&url);
+    request.header("CloudFront-Viewer-Country", "US");
+    request.header("CloudFront-Viewer-ASN", "3320");
+    assert_snapshot!(token.run::<()>(request).await.status(), @"200 OK");
+
+    app.run_pending_background_jobs().await;
+    assert_eq!(app.emails().await.len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn no_alert_without_baseline() {
+    let (app, _, _, token) = TestApp::full()
+        .with_config(|config| config.api_token_usage_alerts = true)
+        .with_token()
+        .await;
+    let mut conn = app.db_conn().await;
+
+    // Tokens that were used before their usages were recorded have no
+    // baseline, so their next usage becomes the baseline without an alert
+    diesel::update(api_tokens::table.find(token.as_model().id))
+        .set(api_tokens::last_used_at.eq(Utc::now() - TimeDelta::days(30)))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let url = format!("/api/v1/me/tokens/{}", token.as_model().id);
+    let mut request = token.get_request(&url);
+    request.header("CloudFront-Viewer-Country", "DE");
+    request.header("CloudFront-Viewer-ASN", "3320");
+    assert_snapshot!(token.run::<()>(request).await.status(), @"200 OK");
+
+    app.run_pending_background_jobs().await;
+    assert_eq!(app.emails().await.len(), 0);
+
+    let baselines: i64 = api_token_usage_baselines::table
+        .find(token.as_model().id)
+        .count()
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(baselines, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn delete_old_usages() {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    let url = format!("/api/v1/me/tokens/{}", token.as_model().id);
+    assert_snapshot!(token.get::<()>(&url).await.status(), @"200 OK");
+    assert_snapshot!(token.get::<()>(&url).await.status(), @"200 OK");
+
+    let oldest_id: i64 = api_token_usages::table
+        .select(api_token_usages::id)
+        .order(api_token_usages::id)
+        .first(&mut conn)
+        .await
+        .unwrap();
+
+    diesel::update(api_token_usages::table.find(oldest_id))
+        .set(api_token_usages::created_at.eq(Utc::now() - TimeDelta::days(91)))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    jobs::DeleteOldApiTokenUsages
+        .enqueue(&mut conn)
+        .await
+        .unwrap();
+    app.run_pending_background_jobs().await;
+
+    let ids: Vec<i64> = api_token_usages::table
+        .select(api_token_usages::id)
+        .load(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(ids.len(), 1);
+    assert!(!ids.contains(&oldest_id));
+}
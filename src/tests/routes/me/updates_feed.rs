@@ -0,0 +1,148 @@
+use crate::OkBool;
+use crate::builders::{CrateBuilder, VersionBuilder};
+use crate::util::insta::{self, assert_json_snapshot};
+use crate::util::{RequestHelper, TestApp};
+use chrono::{DateTime, Utc};
+use http::{StatusCode, header};
+use insta::assert_snapshot;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn feed_token_lifecycle() {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user_id = user.as_model().id;
+
+    CrateBuilder::new("foo", user_id)
+        .description("The foo crate")
+        .version(VersionBuilder::new("1.0.0").created_at(time("2024-06-20T10:13:54Z")))
+        .version(VersionBuilder::new("1.1.0").created_at(time("2024-06-22T08:30:01Z")))
+        .version(
+            VersionBuilder::new("1.2.0")
+                .created_at(time("2024-06-22T15:57:19Z"))
+                .pending(true),
+        )
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("bar", user_id)
+        .version(VersionBuilder::new("0.1.0").created_at(time("2024-06-21T17:03:45Z")))
+        .expect_build(&mut conn)
+        .await;
+
+    user.put::<OkBool>("/api/v1/crates/foo/follow", b"" as &[u8])
+        .await
+        .good();
+
+    let response = user
+        .put::<()>("/api/v1/me/updates/feed_token", b"" as &[u8])
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let json = response.json();
+    assert_json_snapshot!(json, {
+        ".feed_token.created_at" => "[datetime]",
+        ".feed_token.token" => insta::api_token_redaction(),
+        ".feed_token.rss_url" => "[url]",
+        ".feed_token.atom_url" => "[url]",
+    }, @r#"
+    {
+      "feed_token": {
+        "atom_url": "[url]",
+        "created_at": "[datetime]",
+        "rss_url": "[url]",
+        "token": "[token]"
+      }
+    }
+    "#);
+
+    let token = json["feed_token"]["token"].as_str().unwrap().to_string();
+    let rss_path = format!("/api/v1/me/updates/rss?token={token}");
+    let atom_path = format!("/api/v1/me/updates/atom?token={token}");
+    assert_eq!(
+        json["feed_token"]["rss_url"].as_str().unwrap(),
+        format!("https://crates.io{rss_path}")
+    );
+    assert_eq!(
+        json["feed_token"]["atom_url"].as_str().unwrap(),
+        format!("https://crates.io{atom_path}")
+    );
+
+    // The feeds can be read without a session
+    let response = anon.get::<()>(&rss_path).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_snapshot!(response.headers()[header::CONTENT_TYPE].to_str().unwrap(), @"text/xml; charset=UTF-8");
+    assert_snapshot!(response.text());
+
+    let response = anon.get::<()>(&atom_path).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_snapshot!(response.headers()[header::CONTENT_TYPE].to_str().unwrap(), @"application/atom+xml; charset=UTF-8");
+    assert!(
+        response
+            .text()
+            .contains("<title>New crate version published: foo v1.1.0</title>")
+    );
+    assert!(!response.text().contains("bar"));
+
+    // Regenerating the token revokes the previous one
+    let response = user
+        .put::<()>("/api/v1/me/updates/feed_token", b"" as &[u8])
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let new_token = response.json()["feed_token"]["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert_ne!(new_token, token);
+
+    let response = anon.get::<()>(&rss_path).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid or revoked feed token"}]}"#);
+
+    let new_rss_path = format!("/api/v1/me/updates/rss?token={new_token}");
+    let response = anon.get::<()>(&new_rss_path).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    // Revoking the token disables the feed
+    let response = user.delete::<()>("/api/v1/me/updates/feed_token").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = anon.get::<()>(&new_rss_path).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invalid_feed_token() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/api/v1/me/updates/rss?token=foo").await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid or revoked feed token"}]}"#);
+
+    let path = "/api/v1/me/updates/atom?token=cioAbCdEfGhIjKlMnOpQrStUvWxYz0123456";
+    let response = anon.get::<()>(path).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    let response = anon.get::<()>("/api/v1/me/updates/rss").await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn feed_token_requires_session() {
+    let (_, anon, _, token) = TestApp::init().with_token().await;
+
+    let response = anon
+        .put::<()>("/api/v1/me/updates/feed_token", b"" as &[u8])
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    let response = token
+        .put::<()>("/api/v1/me/updates/feed_token", b"" as &[u8])
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    let response = token.delete::<()>("/api/v1/me/updates/feed_token").await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+}
+
+fn time(time: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(time).unwrap().to_utc()
+}
@@ -11,7 +11,9 @@
 //! - testing output serialization of a route
 //! - testing query parameter combinations of a route
 
+pub mod binaries;
 pub mod categories;
+pub mod category_proposals;
 pub mod category_slugs;
 pub mod crates;
 pub mod keywords;
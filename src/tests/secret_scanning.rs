@@ -0,0 +1,167 @@
+use crate::builders::CrateBuilder;
+use crate::github_secret_scanning::{PUBLIC_KEY, insert_trustpub_token, sign_payload};
+use crate::util::MockRequestExt;
+use crate::util::insta::api_token_redaction;
+use crate::{RequestHelper, TestApp};
+use claims::assert_ok;
+use crates_io::models::{ApiToken, NewSecretScannerKey};
+use crates_io::schema::api_tokens;
+use crates_io_database::schema::trustpub_tokens;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use googletest::prelude::*;
+use insta::{assert_json_snapshot, assert_snapshot};
+
+static URL: &str = "/api/secret-scanning/revoke";
+
+static KEY_IDENTIFIER: &str = "internal-scanner-2026";
+
+/// Generate an alert with a given token
+fn alert_with_token(token: &str) -> Vec<u8> {
+    format!(
+        r#"[{{"token":"{token}","type":"cio_token","url":"https://scanner.example.com/leaks/1","source":"internal"}}]"#,
+    )
+    .into_bytes()
+}
+
+async fn register_scanner_key(conn: &mut AsyncPgConnection) {
+    NewSecretScannerKey::builder()
+        .key_identifier(KEY_IDENTIFIER)
+        .scanner("Internal Scanner")
+        .public_key(PUBLIC_KEY)
+        .build()
+        .insert(conn)
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn secret_alert_revokes_token() {
+    let (app, anon, user, token) = TestApp::init().with_token().await;
+    let mut conn = app.db_conn().await;
+    register_scanner_key(&mut conn).await;
+
+    let alert = alert_with_token(token.plaintext());
+    let mut request = anon.post_request(URL);
+    request.header("Secret-Scanning-Key-Identifier", KEY_IDENTIFIER);
+    request.header("Secret-Scanning-Signature", &sign_payload(&alert));
+    *request.body_mut() = alert.into();
+    let response = anon.run::<()>(request).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json(), {
+        "[].token_raw" => api_token_redaction()
+    });
+
+    // Ensure that the token was revoked
+    let tokens: Vec<ApiToken> = assert_ok!(
+        ApiToken::belonging_to(user.as_model())
+            .select(ApiToken::as_select())
+            .filter(api_tokens::revoked.eq(false))
+            .load(&mut conn)
+            .await
+    );
+    assert_that!(tokens, is_empty());
+
+    // Ensure that the owner was notified, mentioning the scanner name
+    assert_snapshot!(app.emails_snapshot().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn secret_alert_revokes_trustpub_token() {
+    let (app, anon, cookie) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    register_scanner_key(&mut conn).await;
+
+    let krate = CrateBuilder::new("foo", cookie.as_model().id)
+        .build(&mut conn)
+        .await
+        .unwrap();
+
+    let token = insert_trustpub_token(&mut conn, &[krate.id]).await.unwrap();
+
+    let alert = alert_with_token(&token);
+    let mut request = anon.post_request(URL);
+    request.header("Secret-Scanning-Key-Identifier", KEY_IDENTIFIER);
+    request.header("Secret-Scanning-Signature", &sign_payload(&alert));
+    *request.body_mut() = alert.into();
+    let response = anon.run::<()>(request).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json(), {
+        "[].token_raw" => api_token_redaction()
+    });
+
+    let count = trustpub_tokens::table
+        .count()
+        .get_result::<i64>(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+
+    assert_snapshot!(app.emails_snapshot().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn secret_alert_for_unknown_token() {
+    let (app, anon) = TestApp::init().empty().await;
+    let mut conn = app.db_conn().await;
+    register_scanner_key(&mut conn).await;
+
+    let alert = alert_with_token("some_token");
+    let mut request = anon.post_request(URL);
+    request.header("Secret-Scanning-Key-Identifier", KEY_IDENTIFIER);
+    request.header("Secret-Scanning-Signature", &sign_payload(&alert));
+    *request.body_mut() = alert.into();
+    let response = anon.run::<()>(request).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json());
+
+    assert_eq!(app.emails().await.len(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn secret_alert_invalid_signature_fails() {
+    let (app, anon, _, token) = TestApp::init().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    let alert = alert_with_token(token.plaintext());
+
+    // Unregistered key
+    let mut request = anon.post_request(URL);
+    request.header("Secret-Scanning-Key-Identifier", KEY_IDENTIFIER);
+    request.header("Secret-Scanning-Signature", &sign_payload(&alert));
+    *request.body_mut() = alert.clone().into();
+    let response = anon.run::<()>(request).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+
+    register_scanner_key(&mut conn).await;
+
+    // No headers
+    let mut request = anon.post_request(URL);
+    *request.body_mut() = alert.clone().into();
+    let response = anon.run::<()>(request).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+
+    // Only key identifier header
+    let mut request = anon.post_request(URL);
+    request.header("Secret-Scanning-Key-Identifier", KEY_IDENTIFIER);
+    *request.body_mut() = alert.clone().into();
+    let response = anon.run::<()>(request).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+
+    // Signature of a different payload
+    let mut request = anon.post_request(URL);
+    request.header("Secret-Scanning-Key-Identifier", KEY_IDENTIFIER);
+    request.header("Secret-Scanning-Signature", &sign_payload(b"[]"));
+    *request.body_mut() = alert.into();
+    let response = anon.run::<()>(request).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+
+    // Ensure that the token was not revoked
+    let revoked: bool = api_tokens::table
+        .select(api_tokens::revoked)
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+    assert!(!revoked);
+    assert_eq!(app.emails().await.len(), 0);
+}
@@ -3,6 +3,8 @@ use crate::util::*;
 use std::collections::HashSet;
 
 use ::insta::assert_json_snapshot;
+use chrono::{Duration, Utc};
+use crates_io::models::NewBlockedTrafficRule;
 use http::{Request, StatusCode, header};
 
 #[tokio::test(flavor = "multi_thread")]
@@ -112,3 +114,92 @@ async fn block_traffic_via_ip() {
     assert_eq!(resp.status(), StatusCode::FORBIDDEN);
     assert_json_snapshot!(resp.json());
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn block_traffic_via_rules() {
+    let (app, anon) = TestApp::init().empty().await;
+    let mut conn = app.db_conn().await;
+
+    let rule = NewBlockedTrafficRule::builder()
+        .header_name("User-Agent")
+        .header_pattern("scraper/*")
+        .route_pattern("/api/v1/crates")
+        .message("Please use the database dumps instead.")
+        .reason("testing")
+        .build()
+        .insert(&mut conn)
+        .await
+        .unwrap();
+
+    let blocked_traffic_rules = &app.as_inner().blocked_traffic_rules;
+
+    // Rules only take effect after they have been reloaded
+    let req = Request::get("/api/v1/crates")
+        .header(header::USER_AGENT, "scraper/1.0")
+        .body("")
+        .unwrap();
+    let resp = anon.run::<()>(req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let num_rules = blocked_traffic_rules.reload(&mut conn).await.unwrap();
+    assert_eq!(num_rules, 1);
+
+    let req = Request::get("/api/v1/crates")
+        .header(header::USER_AGENT, "scraper/1.0")
+        .body("")
+        .unwrap();
+    let resp = anon.run::<()>(req).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    assert_json_snapshot!(resp.json());
+
+    // Requests that only match some conditions of the rule are allowed
+    let req = Request::get("/api/v1/summary")
+        .header(header::USER_AGENT, "scraper/1.0")
+        .body("")
+        .unwrap();
+    let resp = anon.run::<()>(req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = anon.get::<()>("/api/v1/crates").await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let hits = app
+        .as_inner()
+        .instance_metrics
+        .blocked_traffic_rule_hits_total
+        .with_label_values(&[&rule.id.to_string()])
+        .get();
+    assert_eq!(hits, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn block_traffic_via_ip_range_rule() {
+    let (app, anon) = TestApp::init().empty().await;
+    let mut conn = app.db_conn().await;
+
+    NewBlockedTrafficRule::builder()
+        .ip_range("127.0.0.0/8".parse().unwrap())
+        .reason("testing")
+        .build()
+        .insert(&mut conn)
+        .await
+        .unwrap();
+
+    // Expired rules are ignored
+    NewBlockedTrafficRule::builder()
+        .route_pattern("*")
+        .expires_at(Utc::now() - Duration::hours(1))
+        .reason("testing")
+        .build()
+        .insert(&mut conn)
+        .await
+        .unwrap();
+
+    let blocked_traffic_rules = &app.as_inner().blocked_traffic_rules;
+    let num_rules = blocked_traffic_rules.reload(&mut conn).await.unwrap();
+    assert_eq!(num_rules, 1);
+
+    let resp = anon.get::<()>("/api/v1/crates").await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    assert_json_snapshot!(resp.json());
+}
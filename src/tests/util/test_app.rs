@@ -9,6 +9,7 @@ use crates_io::middleware::cargo_compat::StatusCodeConfig;
 use crates_io::models::NewEmail;
 use crates_io::models::token::{CrateScope, EndpointScope};
 use crates_io::rate_limiter::{LimitedAction, RateLimiterConfig};
+use crates_io::read_rate_limiter::ReadRouteGroup;
 use crates_io::storage::StorageConfig;
 use crates_io::util::gh_token_encryption::GitHubTokenEncryption;
 use crates_io::worker::{Environment, RunnerExt};
@@ -148,6 +149,32 @@ impl TestApp {
         }
     }
 
+    /// Create a new crates.io admin with a verified email address in the
+    /// database and return a mock user session.
+    ///
+    /// This method updates the database directly
+    pub async fn db_new_admin(&self, username: &str) -> MockCookieUser {
+        use crates_io::models::User;
+        use crates_io::schema::users;
+        use diesel::prelude::*;
+        use diesel_async::RunQueryDsl;
+
+        let admin = self.db_new_user(username).await;
+
+        let mut conn = self.db_conn().await;
+        let user = diesel::update(admin.as_model())
+            .set(users::is_admin.eq(true))
+            .returning(User::as_returning())
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+
+        MockCookieUser {
+            app: self.clone(),
+            user,
+        }
+    }
+
     /// Obtain a reference to the upstream repository ("the index")
     pub fn upstream_index(&self) -> &UpstreamIndex {
         assert_some!(self.0.index.as_ref())
@@ -189,6 +216,12 @@ impl TestApp {
         static INVITE_TOKEN_REGEX: LazyLock<Regex> =
             LazyLock::new(|| Regex::new(r"/accept-invite/\w+").unwrap());
 
+        static DATE_TIME_UTC_REGEX: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"\d{4}-\d{2}-\d{2} at \d{2}:\d{2}:\d{2} UTC").unwrap());
+
+        static RECOVERY_TOKEN_REGEX: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"/account-recovery/(cancel/)?\w+").unwrap());
+
         // MIME boundary strings are randomly generated alphanumeric strings
         static MIME_BOUNDARY_REGEX: LazyLock<Regex> =
             LazyLock::new(|| Regex::new(r"[A-Za-z0-9]{32,}").unwrap());
@@ -208,6 +241,9 @@ impl TestApp {
                 let email = DATE_TIME_REGEX.replace_all(&email, "[0000-00-00T00:00:00Z]");
                 let email = EMAIL_CONFIRM_REGEX.replace_all(&email, "/confirm/[confirm-token]");
                 let email = INVITE_TOKEN_REGEX.replace_all(&email, "/accept-invite/[invite-token]");
+                let email = DATE_TIME_UTC_REGEX.replace_all(&email, "[0000-00-00 at 00:00:00 UTC]");
+                let email = RECOVERY_TOKEN_REGEX
+                    .replace_all(&email, "/account-recovery/${1}[recovery-token]");
                 let email = MIME_BOUNDARY_REGEX.replace_all(&email, "[boundary]");
                 email.to_string()
             })
@@ -389,6 +425,14 @@ impl TestAppBuilder {
         })
     }
 
+    pub fn with_read_rate_limit(self, group: ReadRouteGroup, rate: Duration, burst: i32) -> Self {
+        self.with_config(|config| {
+            config
+                .read_rate_limiter
+                .insert(group, RateLimiterConfig { rate, burst });
+        })
+    }
+
     pub fn with_git_index(mut self) -> Self {
         self.index = Some(UpstreamIndex::new().unwrap());
         self
@@ -498,6 +542,7 @@ fn simple_config() -> config::Server {
         max_features: 10,
         max_dependencies: 10,
         rate_limiter: Default::default(),
+        read_rate_limiter: Default::default(),
         new_version_rate_limit: Some(10),
         blocked_traffic: Default::default(),
         blocked_ips: Default::default(),
@@ -507,6 +552,10 @@ fn simple_config() -> config::Server {
         allowed_origins: Default::default(),
         downloads_persist_interval: Duration::from_secs(1),
         ownership_invitations_expiration: chrono::Duration::days(30),
+        crate_deletion_grace_period: chrono::Duration::hours(24),
+        pending_version_expiration: chrono::Duration::hours(72),
+        api_token_usage_retention: chrono::Duration::days(90),
+        api_token_usage_alerts: false,
         metrics_authorization_token: None,
         instance_metrics_log_every_seconds: None,
         blocked_routes: HashSet::new(),
@@ -553,6 +602,7 @@ fn build_app(
         .emails(emails)
         .storage_from_config(&config.storage)
         .rate_limiter_from_config(config.rate_limiter.clone())
+        .read_rate_limiter_from_config(config.read_rate_limiter.clone())
         .config(Arc::new(config))
         .build();
 
@@ -0,0 +1,177 @@
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::schema::users;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::Value;
+
+const LIB_1_0_0: &str = r#"
+pub mod de;
+
+pub struct Config {
+    pub name: String,
+}
+
+pub fn parse(input: &str) -> u32 {
+    input.len() as u32
+}
+
+pub fn removed() {}
+"#;
+
+const LIB_1_0_1: &str = r#"
+pub mod de;
+
+pub struct Config {
+    pub name: String,
+}
+
+pub fn parse(text: &str) -> u32 {
+    text.len() as u32
+}
+
+pub fn removed() {}
+
+pub fn added() {}
+"#;
+
+const LIB_1_1_0: &str = r#"
+pub mod de;
+
+pub struct Config {
+    pub name: String,
+    pub verbose: bool,
+}
+
+pub fn parse(input: &str) -> Result<u32, ()> {
+    Ok(input.len() as u32)
+}
+"#;
+
+const DE_RS: &str = "pub trait Deserialize {}";
+
+fn publish_builder(version: &str, lib: &'static str) -> PublishBuilder {
+    PublishBuilder::new("foo", version)
+        .add_file(format!("foo-{version}/src/lib.rs"), lib)
+        .add_file(format!("foo-{version}/src/de.rs"), DE_RS)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_semver_check() -> anyhow::Result<()> {
+    let (app, anon, user, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    // Only keep the semver check emails
+    diesel::update(users::table.find(user.as_model().id))
+        .set(users::publish_notifications.eq(false))
+        .execute(&mut conn)
+        .await?;
+
+    token
+        .publish_crate(publish_builder("1.0.0", LIB_1_0_0))
+        .await
+        .good();
+
+    // The first version of a crate is not checked
+    let json: Value = anon
+        .get("/api/v1/crates/foo/1.0.0/semver_check")
+        .await
+        .good();
+    assert_json_snapshot!(json, @r#"
+    {
+      "semver_check": null
+    }
+    "#);
+
+    token
+        .publish_crate(publish_builder("1.0.1", LIB_1_0_1))
+        .await
+        .good();
+
+    let json: Value = anon
+        .get("/api/v1/crates/foo/1.0.1/semver_check")
+        .await
+        .good();
+    assert_json_snapshot!(json, { ".semver_check.checked_at" => "[datetime]" }, @r#"
+    {
+      "semver_check": {
+        "baseline_version": "1.0.0",
+        "breaking_changes": [],
+        "checked_at": "[datetime]"
+      }
+    }
+    "#);
+
+    assert_eq!(app.emails().await.len(), 0);
+
+    token
+        .publish_crate(publish_builder("1.1.0", LIB_1_1_0))
+        .await
+        .good();
+
+    let json: Value = anon
+        .get("/api/v1/crates/foo/1.1.0/semver_check")
+        .await
+        .good();
+    assert_json_snapshot!(json, { ".semver_check.checked_at" => "[datetime]" });
+
+    assert_snapshot!(app.emails_snapshot().await);
+
+    // New major versions are not checked
+    token
+        .publish_crate(publish_builder("2.0.0", DE_RS))
+        .await
+        .good();
+
+    let json: Value = anon
+        .get("/api/v1/crates/foo/2.0.0/semver_check")
+        .await
+        .good();
+    assert_eq!(json["semver_check"], Value::Null);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_semver_check_without_library() -> anyhow::Result<()> {
+    let (_, anon, _, token) = TestApp::full().with_token().await;
+
+    for version in ["1.0.0", "1.0.1"] {
+        let crate_to_publish = PublishBuilder::new("foo", version)
+            .add_file(format!("foo-{version}/src/main.rs"), "fn main() {}");
+        token.publish_crate(crate_to_publish).await.good();
+    }
+
+    let json: Value = anon
+        .get("/api/v1/crates/foo/1.0.1/semver_check")
+        .await
+        .good();
+    assert_eq!(json["semver_check"], Value::Null);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_semver_check_with_invalid_source() -> anyhow::Result<()> {
+    let (_, anon, _, token) = TestApp::full().with_token().await;
+
+    token
+        .publish_crate(publish_builder("1.0.0", LIB_1_0_0))
+        .await
+        .good();
+
+    // Source code that can't be parsed doesn't fail the background job
+    token
+        .publish_crate(publish_builder("1.0.1", "pub fn broken( {"))
+        .await
+        .good();
+
+    let json: Value = anon
+        .get("/api/v1/crates/foo/1.0.1/semver_check")
+        .await
+        .good();
+    assert_eq!(json["semver_check"], Value::Null);
+
+    Ok(())
+}
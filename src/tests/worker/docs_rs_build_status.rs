@@ -0,0 +1,102 @@
+use crate::builders::{CrateBuilder, VersionBuilder};
+use crate::util::{RequestHelper, TestApp};
+use claims::assert_some;
+use crates_io::worker::jobs;
+use crates_io_docs_rs::{DocsRsBuildStatus, DocsRsError, MockDocsRsClient};
+use crates_io_worker::BackgroundJob;
+use insta::assert_snapshot;
+use serde_json::Value;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sync_build_statuses() -> anyhow::Result<()> {
+    let mut docs_rs_mock = MockDocsRsClient::new();
+    docs_rs_mock
+        .expect_build_status()
+        .withf(|name, version| name == "krate" && version == "0.1.0")
+        .returning(|_, _| Ok(DocsRsBuildStatus::Success))
+        .times(1);
+
+    let (app, anon, user) = TestApp::full().with_docs_rs(docs_rs_mock).with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("krate", user.as_model().id)
+        .version(VersionBuilder::new("0.1.0"))
+        .build(&mut conn)
+        .await?;
+
+    let json: Value = anon.get("/api/v1/crates/krate/0.1.0").await.good();
+    assert_eq!(json["version"]["docs_rs_status"], Value::Null);
+
+    jobs::DocsRsSyncBuildStatuses.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let json: Value = anon.get("/api/v1/crates/krate/0.1.0").await.good();
+    assert_eq!(json["version"]["docs_rs_status"], "success");
+
+    // Versions with a final build status are not checked again
+    jobs::DocsRsSyncBuildStatuses.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    assert_eq!(app.emails().await.len(), 0);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_build_failure_notifies_owners_once() -> anyhow::Result<()> {
+    let mut docs_rs_mock = MockDocsRsClient::new();
+    docs_rs_mock
+        .expect_build_status()
+        .returning(|_, _| Ok(DocsRsBuildStatus::Failure))
+        .times(2);
+
+    let (app, anon, user) = TestApp::full().with_docs_rs(docs_rs_mock).with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let krate = CrateBuilder::new("krate", user.as_model().id)
+        .version(VersionBuilder::new("0.1.0"))
+        .build(&mut conn)
+        .await?;
+
+    let version = krate.find_version(&mut conn, "0.1.0").await?;
+    let version_id = assert_some!(version).id;
+
+    for _ in 0..2 {
+        jobs::DocsRsCheckBuildStatus::new(version_id)
+            .enqueue(&mut conn)
+            .await?;
+        app.run_pending_background_jobs().await;
+    }
+
+    let json: Value = anon.get("/api/v1/crates/krate/0.1.0").await.good();
+    assert_eq!(json["version"]["docs_rs_status"], "failure");
+
+    assert_snapshot!(app.emails_snapshot().await);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unexpected_error_keeps_status() -> anyhow::Result<()> {
+    let mut docs_rs_mock = MockDocsRsClient::new();
+    docs_rs_mock
+        .expect_build_status()
+        .returning(|_, _| Err(DocsRsError::Other(anyhow::anyhow!("oops"))))
+        .times(1);
+
+    let (app, anon, user) = TestApp::full().with_docs_rs(docs_rs_mock).with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("krate", user.as_model().id)
+        .version(VersionBuilder::new("0.1.0"))
+        .build(&mut conn)
+        .await?;
+
+    jobs::DocsRsSyncBuildStatuses.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let json: Value = anon.get("/api/v1/crates/krate/0.1.0").await.good();
+    assert_eq!(json["version"]["docs_rs_status"], Value::Null);
+
+    Ok(())
+}
@@ -1,3 +1,5 @@
+mod check_semver_compatibility;
+mod docs_rs_build_status;
 mod generate_og_image;
 mod git;
 mod readmes;
@@ -1,3 +1,4 @@
 mod sync_crate_feed;
+mod sync_crate_group_feed;
 mod sync_crates_feed;
 mod sync_updates_feed;
@@ -0,0 +1,120 @@
+use crate::builders::{CrateBuilder, PublishBuilder, VersionBuilder};
+use crate::new_category;
+use crate::util::{RequestHelper, TestApp};
+use chrono::{DateTime, Utc};
+use crates_io::schema::categories;
+use crates_io::worker::jobs;
+use crates_io::worker::jobs::rss::CrateGroup;
+use crates_io_worker::BackgroundJob;
+use diesel::insert_into;
+use diesel_async::RunQueryDsl;
+use insta::assert_snapshot;
+use object_store::ObjectStoreExt;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sync_crate_group_feed() -> anyhow::Result<()> {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user_id = user.as_model().id;
+
+    let other_user = app.db_new_user("bar").await;
+    let other_user_id = other_user.as_model().id;
+
+    CrateBuilder::new("foo", user_id)
+        .description("let's try & break this <item> ]]>")
+        .keyword("kw")
+        .version(VersionBuilder::new("1.0.0").created_at(time("2024-06-20T10:13:54Z")))
+        .version(VersionBuilder::new("1.1.0").created_at(time("2024-06-22T08:30:01Z")))
+        .version(
+            VersionBuilder::new("1.2.0")
+                .created_at(time("2024-06-22T15:57:19Z"))
+                .pending(true),
+        )
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("bar", other_user_id)
+        .keyword("kw")
+        .version(VersionBuilder::new("0.1.0").created_at(time("2024-06-21T17:03:45Z")))
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("baz", user_id)
+        .version(VersionBuilder::new("2.0.0").created_at(time("2024-06-21T17:01:33Z")))
+        .expect_build(&mut conn)
+        .await;
+
+    let login = "foo".to_string();
+    let job = jobs::rss::SyncCrateGroupFeed::new(CrateGroup::User { login });
+    job.enqueue(&mut conn).await?;
+
+    let keyword = "kw".to_string();
+    let job = jobs::rss::SyncCrateGroupFeed::new(CrateGroup::Keyword { keyword });
+    job.enqueue(&mut conn).await?;
+
+    app.run_pending_background_jobs().await;
+
+    assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/keywords/kw.xml
+    atom/users/foo.xml
+    rss/keywords/kw.xml
+    rss/users/foo.xml
+    ");
+
+    let store = app.as_inner().storage.as_inner();
+    let result = store.get(&"rss/users/foo.xml".into()).await?;
+    let bytes = result.bytes().await?;
+    let content = String::from_utf8(bytes.to_vec())?;
+    assert_snapshot!(content);
+
+    let result = store.get(&"atom/keywords/kw.xml".into()).await?;
+    let bytes = result.bytes().await?;
+    let content = String::from_utf8(bytes.to_vec())?;
+    assert_snapshot!(content);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn publish_syncs_crate_group_feeds() -> anyhow::Result<()> {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    insert_into(categories::table)
+        .values(vec![
+            new_category("Category 1", "cat1", "Category 1 crates"),
+            new_category("Category 1::Sub", "cat1::sub", "Subcategory crates"),
+        ])
+        .execute(&mut conn)
+        .await?;
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0")
+        .keyword("kw")
+        .category("cat1::sub");
+    token.publish_crate(crate_to_publish).await.good();
+
+    assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/categories/cat1.xml
+    atom/categories/cat1::sub.xml
+    atom/crates.xml
+    atom/crates/foo.xml
+    atom/keywords/kw.xml
+    atom/updates.xml
+    atom/users/foo.xml
+    crates/foo/foo-1.0.0.crate
+    index/3/f/foo
+    rss/categories/cat1.xml
+    rss/categories/cat1::sub.xml
+    rss/crates.xml
+    rss/crates/foo.xml
+    rss/keywords/kw.xml
+    rss/updates.xml
+    rss/users/foo.xml
+    ");
+
+    Ok(())
+}
+
+fn time(time: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(time).unwrap().to_utc()
+}
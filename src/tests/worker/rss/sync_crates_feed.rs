@@ -24,7 +24,10 @@ async fn test_sync_crates_feed() -> anyhow::Result<()> {
 
     app.run_pending_background_jobs().await;
 
-    assert_snapshot!(app.stored_files().await.join("\n"), @"rss/crates.xml");
+    assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/crates.xml
+    rss/crates.xml
+    ");
 
     let store = app.as_inner().storage.as_inner();
     let result = store.get(&"rss/crates.xml".into()).await?;
@@ -1,5 +1,6 @@
 use crate::util::TestApp;
-use chrono::DateTime;
+use chrono::{DateTime, TimeDelta, Utc};
+use crates_io::models::NewPendingVersion;
 use crates_io::schema::{crates, versions};
 use crates_io::worker::jobs;
 use crates_io_worker::BackgroundJob;
@@ -22,11 +23,24 @@ async fn test_sync_updates_feed() -> anyhow::Result<()> {
     create_version(&mut conn, "foo", "1.1.0", None, "2024-06-22T08:30:01Z").await?;
     create_version(&mut conn, "foo", "1.2.0", None, "2024-06-22T15:57:19Z").await?;
 
+    // Versions that are waiting for approval are not part of the feed
+    let pending_id =
+        create_version(&mut conn, "foo", "1.3.0", None, "2024-06-22T16:12:43Z").await?;
+    NewPendingVersion {
+        version_id: pending_id,
+        expires_at: Utc::now() + TimeDelta::days(3),
+    }
+    .insert(&mut conn)
+    .await?;
+
     jobs::rss::SyncUpdatesFeed.enqueue(&mut conn).await?;
 
     app.run_pending_background_jobs().await;
 
-    assert_snapshot!(app.stored_files().await.join("\n"), @"rss/updates.xml");
+    assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    atom/updates.xml
+    rss/updates.xml
+    ");
 
     let store = app.as_inner().storage.as_inner();
     let result = store.get(&"rss/updates.xml".into()).await?;
@@ -18,6 +18,8 @@ async fn test_expiry() -> anyhow::Result<()> {
         hashed_token: &[0xC0, 0xFF, 0xEE],
         crate_ids: &[1],
         trustpub_data: None,
+        github_config_ids: &[],
+        gitlab_config_ids: &[],
     };
     token.insert(&mut conn).await?;
 
@@ -26,6 +28,8 @@ async fn test_expiry() -> anyhow::Result<()> {
         hashed_token: &[0xBA, 0xAD, 0xF0, 0x0D],
         crate_ids: &[2],
         trustpub_data: None,
+        github_config_ids: &[],
+        gitlab_config_ids: &[],
     };
     token.insert(&mut conn).await?;
 
@@ -2,10 +2,14 @@ use diesel_async::AsyncPgConnection;
 use std::sync::Arc;
 use thiserror::Error;
 use tracing::{instrument, warn};
-use typomania::Harness;
 use typomania::checks::{Bitflips, Omitted, SwappedWords, Typos};
+use typomania::{Harness, Package};
 
-use super::{checks::Affixes, config, database::TopCrates};
+use super::{
+    checks::Affixes,
+    config,
+    database::{Crate, TopCrates},
+};
 
 static NOTIFICATION_EMAILS_ENV: &str = "TYPOSQUAT_NOTIFICATION_EMAILS";
 
@@ -85,6 +89,21 @@ impl Cache {
         self.harness.as_ref()
     }
 
+    /// Returns the name of a popular crate that `name` looks like a typo
+    /// of, if any.
+    pub fn suggest(&self, name: &str) -> Option<String> {
+        let harness = self.harness.as_ref()?;
+
+        let package: Box<dyn Package> = Box::new(Crate::without_owners());
+        match harness.check_package(name, package) {
+            Ok(squats) => squats.first().map(|squat| squat.package().to_string()),
+            Err(error) => {
+                warn!(%error, "Failed to check name against the typosquatting corpus");
+                None
+            }
+        }
+    }
+
     pub fn iter_emails(&self) -> impl Iterator<Item = &str> {
         self.emails.iter().map(String::as_str)
     }
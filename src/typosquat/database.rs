@@ -105,6 +105,14 @@ pub struct Crate {
 }
 
 impl Crate {
+    /// Creates a crate without any owners, e.g. for checking names that
+    /// do not belong to an existing crate.
+    pub fn without_owners() -> Self {
+        Self {
+            owners: HashSet::new(),
+        }
+    }
+
     /// Hydrates a crate and its owners from the database given the crate name.
     pub async fn from_name(conn: &mut AsyncPgConnection, name: &str) -> QueryResult<Option<Self>> {
         use crate::models;
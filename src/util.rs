@@ -8,5 +8,6 @@ pub mod gh_token_encryption;
 mod io_util;
 pub mod oauth;
 mod request_helpers;
+pub mod semver;
 pub mod string_excl_null;
 pub mod tracing;
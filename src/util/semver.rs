@@ -0,0 +1,23 @@
+/// Returns the part of the version that Cargo considers for semver
+/// compatibility, e.g. `(1, 0, 0)` for `1.2.3` or `(0, 2, 0)` for `0.2.3`.
+pub fn compatibility_range(version: &semver::Version) -> (u64, u64, u64) {
+    match (version.major, version.minor) {
+        (0, 0) => (0, 0, version.patch),
+        (0, minor) => (0, minor, 0),
+        (major, _) => (major, 0, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compatibility_range() {
+        let range = |version: &str| compatibility_range(&semver::Version::parse(version).unwrap());
+        assert_eq!(range("1.2.3"), (1, 0, 0));
+        assert_eq!(range("1.0.0"), (1, 0, 0));
+        assert_eq!(range("0.2.3"), (0, 2, 0));
+        assert_eq!(range("0.0.3"), (0, 0, 3));
+    }
+}
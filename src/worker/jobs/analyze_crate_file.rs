@@ -6,6 +6,7 @@ use anyhow::Context;
 use async_compression::tokio::bufread::GzipDecoder;
 use crates_io_database::schema::default_versions;
 use crates_io_linecount::{LinecountStats, PathDetails};
+use crates_io_tarball::Manifest;
 use crates_io_worker::BackgroundJob;
 use diesel::dsl::exists;
 use diesel::prelude::*;
@@ -115,22 +116,50 @@ async fn analyze_crate_tarball(
         let path = entry.path().context("Failed to get entry path")?;
         let path_details = PathDetails::from_path(&path);
 
+        if path_details.is_manifest() {
+            let mut contents = Vec::new();
+            let result = entry.read_to_end(&mut contents).await;
+            result.context("Failed to read entry contents")?;
+
+            linecount_stats.is_proc_macro = is_proc_macro_manifest(&contents);
+            continue;
+        }
+
+        let Some(language_type) = path_details.language_type() else {
+            continue;
+        };
+
+        let category = path_details.category();
+        let should_count = !path_details.should_ignore();
+
         // Check if this file should be counted for line statistics
-        if !path_details.should_ignore()
-            && let Some(language_type) = path_details.language_type()
-        {
+        if category.is_some() || should_count {
             // If this is a file that we want to count, read it and update the linecount stats.
             let mut contents = Vec::new();
             let result = entry.read_to_end(&mut contents).await;
             result.context("Failed to read entry contents")?;
 
-            linecount_stats.add_file(language_type, &contents);
+            if let Some(category) = category {
+                linecount_stats.add_category_file(category, language_type, &contents);
+            }
+
+            if should_count {
+                linecount_stats.add_file(language_type, &contents);
+            }
         }
     }
 
     Ok(linecount_stats)
 }
 
+/// Checks whether the `Cargo.toml` file declares a procedural macro library
+fn is_proc_macro_manifest(contents: &[u8]) -> bool {
+    Manifest::from_slice(contents)
+        .ok()
+        .and_then(|manifest| manifest.lib)
+        .is_some_and(|lib| lib.proc_macro)
+}
+
 /// Updates the linecount statistics for a version in the database
 #[instrument(skip(conn, linecount_stats))]
 async fn update_version_linecount_stats(
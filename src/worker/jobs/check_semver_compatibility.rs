@@ -0,0 +1,333 @@
+use crate::email::EmailMessage;
+use crate::models::{NewVersionSemverCheck, OwnerKind, Version};
+use crate::schema::{crate_owners, crates, emails, users, versions};
+use crate::storage::Storage;
+use crate::tasks::spawn_blocking;
+use crate::util::semver::compatibility_range;
+use crate::worker::Environment;
+use anyhow::{Context, bail};
+use async_compression::tokio::bufread::GzipDecoder;
+use crates_io_semver_checks::{ApiSurface, BreakingChange, compare};
+use crates_io_tarball::Manifest;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use futures_util::StreamExt;
+use minijinja::context;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::io::{AsyncBufRead, AsyncReadExt};
+use tokio_util::io::StreamReader;
+use tracing::{info, instrument, warn};
+
+/// The maximum number of breaking changes that are listed in the
+/// notification emails.
+const MAX_EMAIL_CHANGES: usize = 20;
+
+/// The maximum total size of the source files of a crate that are loaded
+/// into memory for the semver check.
+const MAX_SOURCE_SIZE: u64 = 20 * 1024 * 1024;
+
+/// Background job that compares the public API of a newly published version
+/// against the previous semver-compatible version of the crate, and warns
+/// the owners if it looks like the new version contains breaking changes.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CheckSemverCompatibility {
+    version_id: i32,
+}
+
+impl CheckSemverCompatibility {
+    pub fn new(version_id: i32) -> Self {
+        Self { version_id }
+    }
+}
+
+impl BackgroundJob for CheckSemverCompatibility {
+    const JOB_NAME: &'static str = "check_semver_compatibility";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(version_id = ?self.version_id))]
+    async fn run(&self, ctx: Self::Context) -> anyhow::Result<()> {
+        let version_id = self.version_id;
+
+        let mut conn = ctx.deadpool.get().await?;
+
+        let Some(details) = VersionDetails::for_version(version_id, &mut conn).await? else {
+            warn!("version_id={version_id} not found in database, skipping semver check");
+            return Ok(());
+        };
+
+        let krate = &details.krate;
+        let version = &details.version;
+
+        let Some((baseline_id, baseline)) = find_baseline(&details, &mut conn).await? else {
+            info!("No semver-compatible baseline found for {krate}@{version}, skipping check");
+            return Ok(());
+        };
+
+        info!("Comparing the public API of {krate}@{version} against {krate}@{baseline}…");
+
+        let storage = &ctx.storage;
+        let Some(old) = load_api_surface(krate, &baseline, storage).await? else {
+            info!("{krate}@{baseline} could not be analyzed, skipping semver check");
+            return Ok(());
+        };
+        let Some(new) = load_api_surface(krate, version, storage).await? else {
+            info!("{krate}@{version} could not be analyzed, skipping semver check");
+            return Ok(());
+        };
+
+        let changes = compare(&old, &new);
+
+        let breaking_changes = serde_json::to_value(&changes)?;
+        NewVersionSemverCheck {
+            version_id,
+            baseline_version_id: baseline_id,
+            breaking_changes: &breaking_changes,
+        }
+        .upsert(&mut conn)
+        .await
+        .context("Failed to save semver check result")?;
+
+        info!(
+            "Found {} possible breaking changes in {krate}@{version} compared to {krate}@{baseline}",
+            changes.len()
+        );
+
+        if !changes.is_empty() {
+            send_breaking_changes_emails(&ctx, &details, &baseline, &changes, &mut conn).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, HasQuery)]
+#[diesel(base_query = versions::table.inner_join(crates::table))]
+struct VersionDetails {
+    #[diesel(select_expression = crates::columns::id)]
+    crate_id: i32,
+    #[diesel(select_expression = crates::columns::name)]
+    krate: String,
+    #[diesel(select_expression = versions::columns::num)]
+    version: String,
+}
+
+impl VersionDetails {
+    async fn for_version(
+        version_id: i32,
+        conn: &mut AsyncPgConnection,
+    ) -> QueryResult<Option<Self>> {
+        VersionDetails::query()
+            .filter(versions::id.eq(version_id))
+            .first(conn)
+            .await
+            .optional()
+    }
+}
+
+/// Finds the highest published, non-yanked, non-prerelease version that is
+/// older than the given version and semver-compatible with it.
+///
+/// Prerelease versions are never checked, since they don't promise any
+/// compatibility.
+async fn find_baseline(
+    details: &VersionDetails,
+    conn: &mut AsyncPgConnection,
+) -> anyhow::Result<Option<(i32, String)>> {
+    let version = semver::Version::parse(&details.version)?;
+    if !version.pre.is_empty() {
+        return Ok(None);
+    }
+
+    let versions: Vec<(i32, String)> = versions::table
+        .filter(versions::crate_id.eq(details.crate_id))
+        .filter(versions::yanked.eq(false))
+        .filter(Version::is_published())
+        .select((versions::id, versions::num))
+        .load(conn)
+        .await?;
+
+    let baseline = versions
+        .into_iter()
+        .filter_map(|(id, num)| Some((id, semver::Version::parse(&num).ok()?)))
+        .filter(|(_, num)| num.pre.is_empty() && *num < version)
+        .filter(|(_, num)| compatibility_range(num) == compatibility_range(&version))
+        .max_by(|(_, a), (_, b)| a.cmp(b))
+        .map(|(id, num)| (id, num.to_string()));
+
+    Ok(baseline)
+}
+
+/// Downloads the crate file of a version and extracts the public API surface
+/// of its library target.
+///
+/// Returns `None` if the crate file does not exist, has no library target,
+/// or can't be analyzed, e.g. because its source code can't be parsed. Since
+/// retrying wouldn't help in these cases, only download failures are
+/// returned as errors.
+#[instrument(skip(storage))]
+async fn load_api_surface(
+    krate: &str,
+    version: &str,
+    storage: &Storage,
+) -> anyhow::Result<Option<ApiSurface>> {
+    let stream = match storage.download_crate_file(krate, version).await {
+        Ok(stream) => stream,
+        Err(object_store::Error::NotFound { .. }) => return Ok(None),
+        Err(err) => return Err(err).context("Failed to download crate file"),
+    };
+
+    match extract_api_surface(krate, version, StreamReader::new(stream)).await {
+        Ok(surface) => Ok(surface),
+        Err(error) => {
+            warn!("Failed to extract the public API of {krate}@{version}: {error:#}");
+            Ok(None)
+        }
+    }
+}
+
+/// Extracts the public API surface of the library target from a crate file,
+/// or returns `None` if it has no library target.
+async fn extract_api_surface<R: AsyncBufRead + Unpin>(
+    krate: &str,
+    version: &str,
+    reader: R,
+) -> anyhow::Result<Option<ApiSurface>> {
+    let decoder = GzipDecoder::new(reader);
+
+    let mut archive = tokio_tar::Archive::new(decoder);
+
+    let entries = archive.entries();
+    let mut entries = entries.context("Failed to read tarball entries")?;
+
+    let prefix = format!("{krate}-{version}/");
+
+    let mut manifest = None;
+    let mut files = HashMap::new();
+    let mut total_size = 0;
+    while let Some(entry) = entries.next().await {
+        let mut entry = entry.context("Failed to read tarball entry")?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let path = entry.path().context("Failed to get entry path")?;
+        let path = path.to_string_lossy().to_string();
+        let Some(path) = path.strip_prefix(&prefix) else {
+            continue;
+        };
+
+        if path != "Cargo.toml" && !path.ends_with(".rs") {
+            continue;
+        }
+
+        // Read at most one byte more than allowed, to detect oversized files
+        // without reading them completely.
+        let limit = MAX_SOURCE_SIZE - total_size + 1;
+        let mut contents = Vec::new();
+        (&mut entry)
+            .take(limit)
+            .read_to_end(&mut contents)
+            .await
+            .context("Failed to read tarball entry")?;
+
+        total_size += contents.len() as u64;
+        if total_size > MAX_SOURCE_SIZE {
+            bail!("The source files are larger than {MAX_SOURCE_SIZE} bytes");
+        }
+
+        // Skip files that are not valid UTF-8
+        let Ok(contents) = String::from_utf8(contents) else {
+            continue;
+        };
+
+        if path == "Cargo.toml" {
+            manifest = Some(contents);
+        } else {
+            files.insert(path.to_string(), contents);
+        }
+    }
+
+    let manifest = manifest.context("Crate file contains no `Cargo.toml` file")?;
+    let manifest = Manifest::from_slice(manifest.as_bytes()).context("Failed to parse manifest")?;
+
+    let root = manifest
+        .lib
+        .and_then(|lib| lib.path)
+        .unwrap_or_else(|| "src/lib.rs".to_string());
+    let root = root.trim_start_matches("./");
+
+    if !files.contains_key(root) {
+        return Ok(None);
+    }
+
+    // Parsing the source files can take a while for large crates
+    let root = root.to_string();
+    let surface = spawn_blocking(move || ApiSurface::from_sources(&root, &files)).await??;
+    Ok(Some(surface))
+}
+
+async fn send_breaking_changes_emails(
+    ctx: &Environment,
+    details: &VersionDetails,
+    baseline: &str,
+    changes: &[BreakingChange],
+    conn: &mut AsyncPgConnection,
+) -> anyhow::Result<()> {
+    let krate = &details.krate;
+    let version = &details.version;
+
+    let recipients = crate_owners::table
+        .filter(crate_owners::deleted.eq(false))
+        .filter(crate_owners::owner_kind.eq(OwnerKind::User))
+        .filter(crate_owners::crate_id.eq(details.crate_id))
+        .inner_join(users::table)
+        .inner_join(emails::table.on(users::id.eq(emails::user_id)))
+        .filter(emails::verified.eq(true))
+        .select((users::gh_login, emails::email))
+        .load::<(String, String)>(conn)
+        .await?;
+
+    let num_changes = changes.len();
+    let changes = changes
+        .iter()
+        .take(MAX_EMAIL_CHANGES)
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+    for (recipient, email_address) in recipients {
+        let email = EmailMessage::from_template(
+            "possible_semver_break",
+            context! {
+                recipient => recipient,
+                krate => krate,
+                version => version,
+                baseline => baseline,
+                changes => changes,
+                num_changes => num_changes,
+                domain => ctx.config.domain_name,
+            },
+        );
+
+        let result = match email {
+            Ok(email) => ctx
+                .emails
+                .send(&email_address, email)
+                .await
+                .map_err(Into::into),
+            Err(err) => Err(anyhow::Error::from(err)),
+        };
+
+        if let Err(err) = result {
+            warn!(
+                "Failed to send semver check notification for {krate}@{version} to {email_address}: {err}"
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -42,9 +42,9 @@ impl BackgroundJob for DeleteCrateFromStorage {
                 result.context("Failed to delete readme files from S3")
             },
             async {
-                info!("{name}: Deleting RSS feed from S3…");
+                info!("{name}: Deleting RSS and Atom feeds from S3…");
                 let result = ctx.storage.delete_feed(&feed_id).await;
-                result.context("Failed to delete RSS feed from S3")
+                result.context("Failed to delete RSS and Atom feeds from S3")
             },
             async {
                 info!("{name}: Deleting OG image from S3…");
@@ -63,7 +63,8 @@ impl BackgroundJob for DeleteCrateFromStorage {
                 .into_iter()
                 .chain(readme_paths.into_iter())
                 .chain(std::iter::once(format!("og-images/{name}.png").into()))
-                .chain(std::iter::once(object_store::path::Path::from(&feed_id))),
+                .chain(std::iter::once(object_store::path::Path::from(&feed_id)))
+                .chain(std::iter::once(feed_id.atom_path())),
         )
         .enqueue(&mut conn)
         .await?;
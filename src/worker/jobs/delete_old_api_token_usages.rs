@@ -0,0 +1,31 @@
+use crate::models::token::ApiTokenUsage;
+use crate::worker::Environment;
+use chrono::Utc;
+use crates_io_worker::BackgroundJob;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+/// Background job that deletes the usage log entries of API tokens that
+/// are older than the configured retention period.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DeleteOldApiTokenUsages;
+
+impl BackgroundJob for DeleteOldApiTokenUsages {
+    const JOB_NAME: &'static str = "delete_old_api_token_usages";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, ctx: Self::Context) -> anyhow::Result<()> {
+        let mut conn = ctx.deadpool.get().await?;
+
+        let cutoff = Utc::now() - ctx.config.api_token_usage_retention;
+        info!("Deleting API token usage log entries from before {cutoff}…");
+
+        let count = ApiTokenUsage::delete_older_than(&mut conn, cutoff).await?;
+        info!("Deleted {count} API token usage log entries");
+
+        Ok(())
+    }
+}
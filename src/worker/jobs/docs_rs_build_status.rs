@@ -0,0 +1,234 @@
+use crate::email::EmailMessage;
+use crate::models::{DocsRsStatus, OwnerKind};
+use crate::schema::{crate_owners, crates, emails, users, versions};
+use crate::worker::Environment;
+use anyhow::anyhow;
+use chrono::{Duration, Utc};
+use crates_io_docs_rs::{DocsRsBuildStatus, DocsRsError};
+use crates_io_worker::BackgroundJob;
+use diesel::dsl::now;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use minijinja::context;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+/// Versions older than this are not checked for their docs.rs build status
+/// anymore.
+const MAX_VERSION_AGE: Duration = Duration::days(7);
+
+/// Versions whose build status was checked more recently than this are
+/// skipped by [`DocsRsSyncBuildStatuses`].
+const MIN_CHECK_INTERVAL: Duration = Duration::minutes(10);
+
+/// The maximum number of versions that are checked in a single run of
+/// [`DocsRsSyncBuildStatuses`].
+const MAX_VERSIONS_PER_RUN: i64 = 1000;
+
+/// A periodic background job that enqueues [`DocsRsCheckBuildStatus`] jobs
+/// for all recently published versions whose docs.rs build has not
+/// finished yet.
+#[derive(Serialize, Deserialize)]
+pub struct DocsRsSyncBuildStatuses;
+
+impl BackgroundJob for DocsRsSyncBuildStatuses {
+    const JOB_NAME: &'static str = "docs_rs_sync_build_statuses";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, ctx: Self::Context) -> anyhow::Result<()> {
+        if ctx.docs_rs.is_none() {
+            warn!("docs.rs not configured, skipping build status sync");
+            return Ok(());
+        }
+
+        let mut conn = ctx.deadpool.get().await?;
+
+        let version_ids: Vec<i32> = versions::table
+            .filter(versions::created_at.gt(Utc::now() - MAX_VERSION_AGE))
+            .filter(versions::yanked.eq(false))
+            .filter(
+                versions::docs_rs_status
+                    .is_null()
+                    .or(versions::docs_rs_status.eq(DocsRsStatus::Queued)),
+            )
+            .filter(
+                versions::docs_rs_status_checked_at
+                    .is_null()
+                    .or(versions::docs_rs_status_checked_at.lt(Utc::now() - MIN_CHECK_INTERVAL)),
+            )
+            .order(versions::created_at.asc())
+            .limit(MAX_VERSIONS_PER_RUN)
+            .select(versions::id)
+            .load(&mut conn)
+            .await?;
+
+        info!(
+            "Enqueueing docs.rs build status checks for {} versions…",
+            version_ids.len()
+        );
+
+        for version_id in version_ids {
+            DocsRsCheckBuildStatus::new(version_id)
+                .enqueue(&mut conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A background job that fetches the docs.rs build status for a specific
+/// version and caches it in the `versions` table.
+///
+/// If the build failed, all owners of the crate are notified via email.
+#[derive(Serialize, Deserialize)]
+pub struct DocsRsCheckBuildStatus {
+    version_id: i32,
+}
+
+impl DocsRsCheckBuildStatus {
+    pub fn new(version_id: i32) -> Self {
+        Self { version_id }
+    }
+}
+
+impl BackgroundJob for DocsRsCheckBuildStatus {
+    const JOB_NAME: &'static str = "docs_rs_check_build_status";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, ctx: Self::Context) -> anyhow::Result<()> {
+        let version_id = self.version_id;
+
+        let Some(docs_rs) = ctx.docs_rs.as_ref() else {
+            warn!("docs.rs not configured, skipping build status check");
+            return Ok(());
+        };
+
+        let mut conn = ctx.deadpool.get().await?;
+
+        let Some(details) = VersionDetails::for_version(version_id, &mut conn).await? else {
+            warn!("Skipping docs.rs build status check for {version_id}: no version found");
+            return Ok(());
+        };
+
+        let name = &details.krate;
+        let version = &details.version;
+
+        let status = match docs_rs.build_status(name, version).await {
+            Ok(DocsRsBuildStatus::Success) => DocsRsStatus::Success,
+            Ok(DocsRsBuildStatus::Failure) => DocsRsStatus::Failure,
+            Ok(DocsRsBuildStatus::Queued) => DocsRsStatus::Queued,
+            Err(DocsRsError::RateLimited) => {
+                return Err(anyhow!(
+                    "docs.rs build status request was rate limited. retrying."
+                ));
+            }
+            Err(err) => {
+                error!(
+                    name,
+                    version,
+                    ?err,
+                    "couldn't fetch docs.rs build status. won't retry"
+                );
+                return Ok(());
+            }
+        };
+
+        info!("docs.rs build status for {name}@{version}: {status:?}");
+
+        diesel::update(versions::table.find(version_id))
+            .set((
+                versions::docs_rs_status.eq(status),
+                versions::docs_rs_status_checked_at.eq(now),
+            ))
+            .execute(&mut conn)
+            .await?;
+
+        // Only notify the owners once, when the status changes to `Failure`
+        if status == DocsRsStatus::Failure && details.status != Some(DocsRsStatus::Failure) {
+            send_build_failed_emails(&ctx, &details, &mut conn).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, HasQuery)]
+#[diesel(base_query = versions::table.inner_join(crates::table))]
+struct VersionDetails {
+    #[diesel(select_expression = crates::columns::id)]
+    crate_id: i32,
+    #[diesel(select_expression = crates::columns::name)]
+    krate: String,
+    #[diesel(select_expression = versions::columns::num)]
+    version: String,
+    #[diesel(select_expression = versions::columns::docs_rs_status)]
+    status: Option<DocsRsStatus>,
+}
+
+impl VersionDetails {
+    async fn for_version(
+        version_id: i32,
+        conn: &mut AsyncPgConnection,
+    ) -> QueryResult<Option<Self>> {
+        VersionDetails::query()
+            .filter(versions::id.eq(version_id))
+            .first(conn)
+            .await
+            .optional()
+    }
+}
+
+async fn send_build_failed_emails(
+    ctx: &Environment,
+    details: &VersionDetails,
+    conn: &mut AsyncPgConnection,
+) -> anyhow::Result<()> {
+    let krate = &details.krate;
+    let version = &details.version;
+
+    let recipients = crate_owners::table
+        .filter(crate_owners::deleted.eq(false))
+        .filter(crate_owners::owner_kind.eq(OwnerKind::User))
+        .filter(crate_owners::crate_id.eq(details.crate_id))
+        .inner_join(users::table)
+        .inner_join(emails::table.on(users::id.eq(emails::user_id)))
+        .filter(emails::verified.eq(true))
+        .select((users::gh_login, emails::email))
+        .load::<(String, String)>(conn)
+        .await?;
+
+    for (recipient, email_address) in recipients {
+        let email = EmailMessage::from_template(
+            "docs_build_failed",
+            context! {
+                recipient => recipient,
+                krate => krate,
+                version => version,
+                domain => ctx.config.domain_name,
+            },
+        );
+
+        let result = match email {
+            Ok(email) => ctx
+                .emails
+                .send(&email_address, email)
+                .await
+                .map_err(Into::into),
+            Err(err) => Err(anyhow::Error::from(err)),
+        };
+
+        if let Err(err) = result {
+            warn!(
+                "Failed to send docs build failure notification for {krate}@{version} to {email_address}: {err}"
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -1,21 +1,49 @@
+use crate::models::DocsRsRebuildStatus;
+use crate::schema::{crates, docs_rs_rebuild_requests, versions};
 use crate::worker::Environment;
 use anyhow::anyhow;
+use chrono::{DateTime, Duration, Utc};
 use crates_io_docs_rs::DocsRsError;
 use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tracing::{error, warn};
+use tracing::{error, info, warn};
+
+/// The number of times a rate limited rebuild request is sent to docs.rs
+/// before giving up.
+const MAX_ATTEMPTS: i32 = 8;
+
+/// The delay before the first retry of a rate limited rebuild request.
+/// Every following retry doubles the delay, up to [`MAX_RETRY_DELAY`].
+const BASE_RETRY_DELAY: Duration = Duration::minutes(5);
+
+const MAX_RETRY_DELAY: Duration = Duration::hours(6);
 
 /// A background job that queues a docs rebuild for a specific release
 #[derive(Serialize, Deserialize)]
 pub struct DocsRsQueueRebuild {
     name: String,
     version: String,
+    /// The `docs_rs_rebuild_requests` row that the outcome of this job is
+    /// recorded in, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    request_id: Option<i64>,
 }
 
 impl DocsRsQueueRebuild {
     pub fn new(name: String, version: String) -> Self {
-        Self { name, version }
+        Self {
+            name,
+            version,
+            request_id: None,
+        }
+    }
+
+    pub fn with_request_id(mut self, request_id: i64) -> Self {
+        self.request_id = Some(request_id);
+        self
     }
 }
 
@@ -28,32 +56,190 @@ impl BackgroundJob for DocsRsQueueRebuild {
     async fn run(&self, ctx: Self::Context) -> anyhow::Result<()> {
         let Some(docs_rs) = ctx.docs_rs.as_ref() else {
             warn!("docs.rs not configured, skipping rebuild");
+
+            // Without this, the request would stay in flight forever and
+            // block all further rebuild requests for the version.
+            if let Some(request_id) = self.request_id {
+                let mut conn = ctx.deadpool.get().await?;
+                let response = "docs.rs is not configured".to_string();
+                record_failure(request_id, response, &mut conn).await?;
+            }
+
             return Ok(());
         };
 
-        match docs_rs.rebuild_docs(&self.name, &self.version).await {
-            Ok(()) => Ok(()),
+        let result = docs_rs.rebuild_docs(&self.name, &self.version).await;
+
+        let Some(request_id) = self.request_id else {
+            return match result {
+                Err(DocsRsError::RateLimited) => {
+                    Err(anyhow!("docs rebuild request was rate limited. retrying."))
+                }
+                result => {
+                    self.log_result(&result);
+                    Ok(())
+                }
+            };
+        };
+
+        self.log_result(&result);
+
+        let mut conn = ctx.deadpool.get().await?;
+        record_attempt(request_id, result, &mut conn).await?;
+
+        Ok(())
+    }
+}
+
+impl DocsRsQueueRebuild {
+    fn log_result(&self, result: &Result<(), DocsRsError>) {
+        let name = &self.name;
+        let version = &self.version;
+
+        match result {
+            Ok(()) => {}
             Err(DocsRsError::BadRequest(msg)) => {
-                warn!(
-                    name = self.name,
-                    version = self.version,
-                    msg,
-                    "couldn't queue docs rebuild"
-                );
-                Ok(())
+                warn!(name, version, msg, "couldn't queue docs rebuild");
             }
             Err(DocsRsError::RateLimited) => {
-                Err(anyhow!("docs rebuild request was rate limited. retrying."))
+                warn!(name, version, "docs rebuild request was rate limited");
             }
             Err(err) => {
                 error!(
-                    name = self.name,
-                    version = self.version,
+                    name,
+                    version,
                     ?err,
                     "couldn't queue docs rebuild. won't retry"
                 );
-                Ok(())
             }
         }
     }
 }
+
+/// Records the outcome of an attempt to send a rebuild request to docs.rs.
+///
+/// Rate limited requests are scheduled for another attempt with an
+/// exponential backoff, until [`MAX_ATTEMPTS`] is reached.
+async fn record_attempt(
+    request_id: i64,
+    result: Result<(), DocsRsError>,
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<()> {
+    let attempts: i32 = docs_rs_rebuild_requests::table
+        .find(request_id)
+        .select(docs_rs_rebuild_requests::attempts)
+        .first(conn)
+        .await?;
+
+    let attempts = attempts + 1;
+    let now = Utc::now();
+
+    let (status, next_attempt_at, response) = match result {
+        Ok(()) => (DocsRsRebuildStatus::Queued, None, None),
+        Err(DocsRsError::BadRequest(msg)) => (DocsRsRebuildStatus::Rejected, None, Some(msg)),
+        Err(DocsRsError::RateLimited) if attempts < MAX_ATTEMPTS => {
+            let next_attempt_at = now + retry_delay(attempts);
+            let response = "rate limited by docs.rs".to_string();
+            (
+                DocsRsRebuildStatus::RateLimited,
+                Some(next_attempt_at),
+                Some(response),
+            )
+        }
+        Err(DocsRsError::RateLimited) => {
+            let response = format!("rate limited by docs.rs, gave up after {attempts} attempts");
+            (DocsRsRebuildStatus::Failed, None, Some(response))
+        }
+        Err(err) => (DocsRsRebuildStatus::Failed, None, Some(err.to_string())),
+    };
+
+    diesel::update(docs_rs_rebuild_requests::table.find(request_id))
+        .set((
+            docs_rs_rebuild_requests::status.eq(status),
+            docs_rs_rebuild_requests::attempts.eq(attempts),
+            docs_rs_rebuild_requests::last_attempt_at.eq(now),
+            docs_rs_rebuild_requests::next_attempt_at.eq(next_attempt_at),
+            docs_rs_rebuild_requests::response.eq(response),
+        ))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Marks a rebuild request as failed without sending it to docs.rs.
+async fn record_failure(
+    request_id: i64,
+    response: String,
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<()> {
+    diesel::update(docs_rs_rebuild_requests::table.find(request_id))
+        .set((
+            docs_rs_rebuild_requests::status.eq(DocsRsRebuildStatus::Failed),
+            docs_rs_rebuild_requests::next_attempt_at.eq(None::<DateTime<Utc>>),
+            docs_rs_rebuild_requests::response.eq(response),
+        ))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+fn retry_delay(attempts: i32) -> Duration {
+    let exponent = u32::try_from(attempts - 1).unwrap_or_default().min(16);
+    (BASE_RETRY_DELAY * 2i32.pow(exponent)).min(MAX_RETRY_DELAY)
+}
+
+/// A periodic background job that re-enqueues [`DocsRsQueueRebuild`] jobs
+/// for all rebuild requests that were rate limited by docs.rs and are due
+/// for another attempt.
+#[derive(Serialize, Deserialize)]
+pub struct DocsRsRetryRebuildRequests;
+
+impl BackgroundJob for DocsRsRetryRebuildRequests {
+    const JOB_NAME: &'static str = "docs_rs_retry_rebuild_requests";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, ctx: Self::Context) -> anyhow::Result<()> {
+        let mut conn = ctx.deadpool.get().await?;
+
+        let requests: Vec<(i64, String, String)> = docs_rs_rebuild_requests::table
+            .inner_join(versions::table.inner_join(crates::table))
+            .filter(docs_rs_rebuild_requests::status.eq(DocsRsRebuildStatus::RateLimited))
+            .filter(docs_rs_rebuild_requests::next_attempt_at.le(Utc::now()))
+            .select((docs_rs_rebuild_requests::id, crates::name, versions::num))
+            .load(&mut conn)
+            .await?;
+
+        info!(
+            "Retrying {} rate limited docs.rs rebuild requests…",
+            requests.len()
+        );
+
+        for (request_id, name, version) in requests {
+            DocsRsQueueRebuild::new(name, version)
+                .with_request_id(request_id)
+                .enqueue(&mut conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(1), Duration::minutes(5));
+        assert_eq!(retry_delay(2), Duration::minutes(10));
+        assert_eq!(retry_delay(3), Duration::minutes(20));
+        assert_eq!(retry_delay(7), Duration::hours(5) + Duration::minutes(20));
+        assert_eq!(retry_delay(8), Duration::hours(6));
+        assert_eq!(retry_delay(100), Duration::hours(6));
+    }
+}
@@ -1,14 +1,38 @@
+use crate::schema::{database_dumps, dump_tombstones};
 use crate::tasks::spawn_blocking;
 use crate::worker::Environment;
-use crates_io_database::models::CloudFrontDistribution;
+use chrono::{DateTime, Duration, Utc};
+use crates_io_database::models::{
+    CloudFrontDistribution, DatabaseDump, DatabaseDumpKind, NewDatabaseDump,
+};
 use crates_io_database_dump::{DumpDirectory, create_archives};
 use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use secrecy::ExposeSecret;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tracing::{info, warn};
 
+const TAR_PATH: &str = "db-dump.tar.gz";
+const ZIP_PATH: &str = "db-dump.zip";
+
+const PARQUET_PREFIX: &str = "db-dump-parquet";
+
+const INCREMENTAL_PREFIX: &str = "db-dump-incremental";
+const MANIFEST_PATH: &str = "db-dump-incremental/manifest.json";
+
+/// Incremental dumps include the changes of this period before the previous
+/// dump as well, since rows changed by transactions that were still running
+/// during the previous dump could otherwise be missed. Applying the same
+/// changes twice is harmless.
+const INCREMENTAL_OVERLAP: Duration = Duration::hours(1);
+
+/// Increments older than this are no longer listed in the manifest.
+const INCREMENTAL_RETENTION: Duration = Duration::days(30);
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct DumpDb;
 
@@ -19,16 +43,12 @@ impl BackgroundJob for DumpDb {
     type Context = Arc<Environment>;
 
     /// Create CSV dumps of the public information in the database, wrap them in a
-    /// tarball and upload to S3.
+    /// tarball and upload to S3. The CSV files are additionally converted
+    /// into one Parquet file per table and uploaded as well.
     async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
-        const TAR_PATH: &str = "db-dump.tar.gz";
-        const ZIP_PATH: &str = "db-dump.zip";
+        let database_url = dump_database_url(&env);
 
-        let db_config = &env.config.db;
-        let db_pool_config = db_config.replica.as_ref().unwrap_or(&db_config.primary);
-        let database_url = db_pool_config.url.clone();
-
-        let archives = spawn_blocking(move || {
+        let (directory, archives, parquet_files) = spawn_blocking(move || {
             let directory = DumpDirectory::create()?;
 
             info!("Exporting database…");
@@ -37,7 +57,12 @@ impl BackgroundJob for DumpDb {
             let export_dir = directory.path();
             info!(path = ?export_dir, "Creating tarball…");
             let tarball_prefix = PathBuf::from(directory.timestamp.format("%F-%H%M%S").to_string());
-            create_archives(export_dir, &tarball_prefix)
+            let archives = create_archives(export_dir, &tarball_prefix)?;
+
+            info!("Converting CSV files to Parquet…");
+            let parquet_files = directory.export_parquet(database_url.expose_secret())?;
+
+            Ok::<_, anyhow::Error>((directory, archives, parquet_files))
         })
         .await??;
 
@@ -66,6 +91,194 @@ impl BackgroundJob for DumpDb {
             warn!("Failed to invalidate CDN caches: {error}");
         }
 
+        info!("Uploading Parquet files…");
+        for file in &parquet_files {
+            let Some(file_name) = file.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+
+            let path = format!("{PARQUET_PREFIX}/{file_name}");
+            env.storage.upload_db_dump(&path, file).await?;
+
+            if let Err(error) = env.invalidate_cdns(&mut conn, dist, &path).await {
+                warn!("Failed to invalidate CDN caches: {error}");
+            }
+        }
+        info!("Parquet files uploaded");
+
+        let new_dump = NewDatabaseDump {
+            kind: DatabaseDumpKind::Full,
+            created_at: directory.timestamp,
+            since: None,
+            base_id: None,
+            path: TAR_PATH,
+        };
+        new_dump.insert(&mut conn).await?;
+
+        Ok(())
+    }
+}
+
+/// A background job that creates an incremental dump of the public
+/// information in the database, containing the changes since the previous
+/// incremental dump (or the most recent full dump, if there is none yet).
+///
+/// The increments are uploaded to `db-dump-incremental/`, together with a
+/// `manifest.json` file listing the available increments and the full dump
+/// they can be applied to.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DumpDbIncremental;
+
+impl BackgroundJob for DumpDbIncremental {
+    const JOB_NAME: &'static str = "dump_db_incremental";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        let Some(base) = DatabaseDump::latest(&mut conn, DatabaseDumpKind::Full).await? else {
+            warn!("No full database dump found, skipping incremental dump");
+            return Ok(());
+        };
+
+        let previous = DatabaseDump::latest(&mut conn, DatabaseDumpKind::Incremental).await?;
+        let previous_timestamp = previous.map_or(base.created_at, |previous| previous.created_at);
+        let since = previous_timestamp - INCREMENTAL_OVERLAP;
+        drop(conn);
+
+        let database_url = dump_database_url(&env);
+
+        let (archives, timestamp) = spawn_blocking(move || {
+            let directory = DumpDirectory::create()?;
+
+            info!(%since, "Exporting database changes…");
+            directory.populate_incremental(database_url.expose_secret(), since)?;
+
+            let export_dir = directory.path();
+            info!(path = ?export_dir, "Creating tarball…");
+            let tarball_prefix = PathBuf::from(directory.timestamp.format("%F-%H%M%S").to_string());
+            create_archives(export_dir, &tarball_prefix)
+                .map(|archives| (archives, directory.timestamp))
+        })
+        .await??;
+
+        let path = format!(
+            "{INCREMENTAL_PREFIX}/{}.tar.gz",
+            timestamp.format("%F-%H%M%S")
+        );
+
+        info!(%path, "Uploading tarball…");
+        env.storage
+            .upload_db_dump(&path, archives.tar.path())
+            .await?;
+        info!("Incremental database dump tarball uploaded");
+
+        let mut conn = env.deadpool.get().await?;
+
+        let new_dump = NewDatabaseDump {
+            kind: DatabaseDumpKind::Incremental,
+            created_at: timestamp,
+            since: Some(since),
+            base_id: Some(base.id),
+            path: &path,
+        };
+        new_dump.insert(&mut conn).await?;
+
+        info!("Uploading manifest…");
+        let manifest = Manifest::load(&mut conn).await?;
+        let manifest_file = tempfile::NamedTempFile::new()?;
+        serde_json::to_writer_pretty(manifest_file.as_file(), &manifest)?;
+        env.storage
+            .upload_db_dump(MANIFEST_PATH, manifest_file.path())
+            .await?;
+
+        info!("Invalidating CDN caches…");
+        let dist = CloudFrontDistribution::Static;
+        if let Err(error) = env.invalidate_cdns(&mut conn, dist, MANIFEST_PATH).await {
+            warn!("Failed to invalidate CDN caches: {error}");
+        }
+
+        // The older tombstones were included in the previous increments
+        // already, so they are no longer needed.
+        let deleted = diesel::delete(dump_tombstones::table)
+            .filter(dump_tombstones::deleted_at.lt(since))
+            .execute(&mut conn)
+            .await?;
+        info!("Deleted {deleted} tombstones that are no longer needed");
+
         Ok(())
     }
 }
+
+fn dump_database_url(env: &Environment) -> secrecy::SecretString {
+    let db_config = &env.config.db;
+    let db_pool_config = db_config.replica.as_ref().unwrap_or(&db_config.primary);
+    db_pool_config.url.clone()
+}
+
+/// The contents of the `db-dump-incremental/manifest.json` file.
+#[derive(Debug, Serialize)]
+struct Manifest {
+    /// The most recent full dump.
+    base: Option<ManifestEntry>,
+    /// The available increments, from oldest to newest.
+    increments: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct ManifestEntry {
+    path: String,
+    timestamp: DateTime<Utc>,
+    /// The time that the changes of an increment are relative to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    since: Option<DateTime<Utc>>,
+    /// The timestamp of the most recent full dump at the time an increment
+    /// was created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    base: Option<DateTime<Utc>>,
+}
+
+impl Manifest {
+    async fn load(conn: &mut AsyncPgConnection) -> QueryResult<Self> {
+        let base = DatabaseDump::latest(conn, DatabaseDumpKind::Full).await?;
+
+        let increments: Vec<DatabaseDump> = DatabaseDump::query()
+            .filter(database_dumps::kind.eq(DatabaseDumpKind::Incremental))
+            .filter(database_dumps::created_at.gt(Utc::now() - INCREMENTAL_RETENTION))
+            .order(database_dumps::created_at.asc())
+            .load(conn)
+            .await?;
+
+        let base_ids = increments.iter().filter_map(|dump| dump.base_id);
+        let base_timestamps: HashMap<i32, DateTime<Utc>> = database_dumps::table
+            .filter(database_dumps::id.eq_any(base_ids.collect::<Vec<_>>()))
+            .select((database_dumps::id, database_dumps::created_at))
+            .load(conn)
+            .await?
+            .into_iter()
+            .collect();
+
+        let increments = increments
+            .into_iter()
+            .map(|dump| ManifestEntry {
+                base: dump
+                    .base_id
+                    .and_then(|id| base_timestamps.get(&id).copied()),
+                path: dump.path,
+                timestamp: dump.created_at,
+                since: dump.since,
+            })
+            .collect();
+
+        let base = base.map(|dump| ManifestEntry {
+            path: dump.path,
+            timestamp: dump.created_at,
+            since: None,
+            base: None,
+        });
+
+        Ok(Self { base, increments })
+    }
+}
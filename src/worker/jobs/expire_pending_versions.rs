@@ -0,0 +1,70 @@
+use crate::models::PendingVersion;
+use crate::schema::{crates, versions};
+use crate::worker::Environment;
+use crate::worker::jobs;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Background job that deletes the pending versions that were not approved
+/// before they expired, or that were rejected by one of the owners.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExpirePendingVersions;
+
+impl BackgroundJob for ExpirePendingVersions {
+    const JOB_NAME: &'static str = "expire_pending_versions";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, ctx: Self::Context) -> anyhow::Result<()> {
+        let mut conn = ctx.deadpool.get().await?;
+
+        let pending_versions = PendingVersion::expired(&mut conn).await?;
+        info!(
+            "Deleting {} expired pending versions…",
+            pending_versions.len()
+        );
+
+        let mut paths = Vec::new();
+        for pending_version in pending_versions {
+            let version_id = pending_version.version_id;
+
+            let (crate_name, num): (String, String) = versions::table
+                .inner_join(crates::table)
+                .filter(versions::id.eq(version_id))
+                .select((crates::name, versions::num))
+                .first(&mut conn)
+                .await?;
+
+            info!("{crate_name}@{num}: Deleting expired pending version…");
+
+            // Deleting the version cascades to the `pending_versions` row.
+            diesel::delete(versions::table.find(version_id))
+                .execute(&mut conn)
+                .await?;
+
+            let storage = &ctx.storage;
+            if let Err(error) = storage.delete_pending_crate_file(&crate_name, &num).await {
+                warn!("{crate_name}@{num}: Failed to delete pending crate file: {error}");
+            }
+
+            match storage.delete_readme(&crate_name, &num).await {
+                Err(object_store::Error::NotFound { .. }) => {}
+                Err(error) => warn!("{crate_name}@{num}: Failed to delete readme file: {error}"),
+                Ok(_) => paths.push(storage.readme_location(&crate_name, &num)),
+            }
+        }
+
+        if !paths.is_empty() {
+            jobs::InvalidateCdns::new(paths.into_iter())
+                .enqueue(&mut conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
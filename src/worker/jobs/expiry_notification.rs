@@ -114,8 +114,9 @@ async fn handle_expiring_token(
 }
 
 /// Find tokens that will expire before the given date, but haven't expired yet
-/// and haven't been notified about their impending expiry. Revoked tokens are
-/// also ignored.
+/// and haven't been notified about their impending expiry. Revoked tokens and
+/// short-lived tokens that were exchanged for a refresh token are also
+/// ignored.
 ///
 /// This function returns at most `MAX_ROWS` tokens.
 pub async fn find_expiring_tokens(
@@ -124,6 +125,7 @@ pub async fn find_expiring_tokens(
 ) -> QueryResult<Vec<ApiToken>> {
     ApiToken::query()
         .filter(api_tokens::revoked.eq(false))
+        .filter(api_tokens::parent_id.is_null())
         .filter(api_tokens::expired_at.is_not_null())
         // Ignore already expired tokens
         .filter(api_tokens::expired_at.assume_not_null().gt(now))
@@ -251,4 +253,58 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_exchanged_tokens_are_ignored() -> anyhow::Result<()> {
+        let test_db = TestDatabase::new();
+        let mut conn = test_db.async_connect().await;
+
+        let user = NewUser::builder()
+            .gh_id(0)
+            .gh_login("a")
+            .gh_encrypted_token(&[])
+            .build()
+            .insert(&mut conn)
+            .await?;
+
+        NewEmail::builder()
+            .user_id(user.id)
+            .email("testuser@test.com")
+            .build()
+            .insert(&mut conn)
+            .await?;
+
+        let token = PlainToken::generate();
+        let refresh_token: ApiToken = diesel::insert_into(api_tokens::table)
+            .values((
+                api_tokens::user_id.eq(user.id),
+                api_tokens::name.eq("refresh_token"),
+                api_tokens::token.eq(token.hashed()),
+            ))
+            .returning(ApiToken::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        // Exchanged tokens always expire soon, so their owners would get an
+        // email for every exchange otherwise.
+        let token = PlainToken::generate();
+        diesel::insert_into(api_tokens::table)
+            .values((
+                api_tokens::user_id.eq(user.id),
+                api_tokens::name.eq("refresh_token"),
+                api_tokens::token.eq(token.hashed()),
+                api_tokens::parent_id.eq(refresh_token.id),
+                api_tokens::expired_at.eq(now.into_sql::<Timestamptz>().nullable() + 30.minutes()),
+            ))
+            .execute(&mut conn)
+            .await?;
+
+        let emails = Emails::new_in_memory();
+        check(&emails, &mut conn).await?;
+
+        let sent_mail = emails.mails_in_memory().await.unwrap();
+        assert_eq!(sent_mail.len(), 0);
+
+        Ok(())
+    }
 }
@@ -1,18 +1,27 @@
 mod analyze_crate_file;
 mod archive_version_downloads;
+mod check_semver_compatibility;
 mod daily_db_maintenance;
 mod delete_crate;
+mod delete_old_api_token_usages;
+mod docs_rs_build_status;
 mod docs_rs_queue_rebuild;
 mod downloads;
 pub mod dump_db;
+mod expire_pending_versions;
 mod expiry_notification;
 mod generate_og_image;
 mod index;
 mod index_version_downloads_archive;
 mod invalidate_cdns;
 mod process_cloudfront_invalidation_queue;
+mod process_scheduled_crate_deletions;
 mod readmes;
 pub mod rss;
+mod send_api_token_usage_alert;
+mod send_crate_deprecation_notifications;
+mod send_crate_transfer_notifications;
+mod send_pending_version_notifications;
 mod send_publish_notifications;
 mod sync_admins;
 pub mod trustpub;
@@ -21,13 +30,17 @@ mod update_default_version;
 
 pub use self::analyze_crate_file::AnalyzeCrateFile;
 pub use self::archive_version_downloads::ArchiveVersionDownloads;
+pub use self::check_semver_compatibility::CheckSemverCompatibility;
 pub use self::daily_db_maintenance::DailyDbMaintenance;
 pub use self::delete_crate::DeleteCrateFromStorage;
-pub use self::docs_rs_queue_rebuild::DocsRsQueueRebuild;
+pub use self::delete_old_api_token_usages::DeleteOldApiTokenUsages;
+pub use self::docs_rs_build_status::{DocsRsCheckBuildStatus, DocsRsSyncBuildStatuses};
+pub use self::docs_rs_queue_rebuild::{DocsRsQueueRebuild, DocsRsRetryRebuildRequests};
 pub use self::downloads::{
     CleanProcessedLogFiles, ProcessCdnLog, ProcessCdnLogQueue, UpdateDownloads,
 };
-pub use self::dump_db::DumpDb;
+pub use self::dump_db::{DumpDb, DumpDbIncremental};
+pub use self::expire_pending_versions::ExpirePendingVersions;
 pub use self::expiry_notification::SendTokenExpiryNotifications;
 pub use self::generate_og_image::GenerateOgImage;
 pub use self::index::{
@@ -36,7 +49,12 @@ pub use self::index::{
 pub use self::index_version_downloads_archive::IndexVersionDownloadsArchive;
 pub use self::invalidate_cdns::InvalidateCdns;
 pub use self::process_cloudfront_invalidation_queue::ProcessCloudfrontInvalidationQueue;
+pub use self::process_scheduled_crate_deletions::ProcessScheduledCrateDeletions;
 pub use self::readmes::RenderAndUploadReadme;
+pub use self::send_api_token_usage_alert::SendApiTokenUsageAlert;
+pub use self::send_crate_deprecation_notifications::SendCrateDeprecationNotifications;
+pub use self::send_crate_transfer_notifications::SendCrateTransferNotifications;
+pub use self::send_pending_version_notifications::SendPendingVersionNotifications;
 pub use self::send_publish_notifications::SendPublishNotificationsJob;
 pub use self::sync_admins::SyncAdmins;
 pub use self::typosquat::CheckTyposquat;
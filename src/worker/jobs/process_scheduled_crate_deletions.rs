@@ -0,0 +1,114 @@
+use crate::models::{NewDeletedCrate, ScheduledCrateDeletion};
+use crate::schema::{crates, deleted_crates, dependencies};
+use crate::worker::Environment;
+use crate::worker::jobs;
+use chrono::{DateTime, TimeDelta, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// The amount of time after which the name of a deleted crate can be used
+/// for a new crate.
+const AVAILABLE_AFTER: TimeDelta = TimeDelta::hours(24);
+
+/// Background job that permanently deletes the crates whose deletion grace
+/// period has ended.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProcessScheduledCrateDeletions;
+
+impl BackgroundJob for ProcessScheduledCrateDeletions {
+    const JOB_NAME: &'static str = "process_scheduled_crate_deletions";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, ctx: Self::Context) -> anyhow::Result<()> {
+        let mut conn = ctx.deadpool.get().await?;
+
+        let deletions = ScheduledCrateDeletion::due(&mut conn).await?;
+        info!("Processing {} scheduled crate deletions…", deletions.len());
+
+        for deletion in deletions {
+            let crate_id = deletion.crate_id;
+            if let Err(error) = process_deletion(&mut conn, deletion).await {
+                warn!(%crate_id, "Failed to process scheduled crate deletion: {error}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+async fn process_deletion(
+    conn: &mut AsyncPgConnection,
+    deletion: ScheduledCrateDeletion,
+) -> anyhow::Result<()> {
+    let (name, created_at): (String, DateTime<Utc>) = crates::table
+        .find(deletion.crate_id)
+        .select((crates::name, crates::created_at))
+        .first(conn)
+        .await?;
+
+    // Other crates might have started to depend on this crate during the
+    // grace period. Deleting it would break them, so the deletion is
+    // cancelled instead.
+    let rev_dep = dependencies::table
+        .filter(dependencies::crate_id.eq(deletion.crate_id))
+        .select(dependencies::id)
+        .first::<i32>(conn)
+        .await
+        .optional()?;
+
+    if rev_dep.is_some() {
+        warn!("{name}: Cancelling scheduled deletion because of new reverse dependencies");
+        ScheduledCrateDeletion::cancel(conn, deletion.crate_id).await?;
+        enqueue_index_jobs(conn, &name).await?;
+        return Ok(());
+    }
+
+    info!("{name}: Deleting crate from the database…");
+    conn.transaction(|conn| {
+        async {
+            diesel::delete(crates::table.find(deletion.crate_id))
+                .execute(conn)
+                .await?;
+
+            let deleted_at = Utc::now();
+            let available_at = deleted_at + AVAILABLE_AFTER;
+
+            let deleted_crate = NewDeletedCrate::builder(&name)
+                .created_at(&created_at)
+                .deleted_at(&deleted_at)
+                .maybe_deleted_by(deletion.requested_by)
+                .available_at(&available_at)
+                .maybe_message(deletion.message.as_deref())
+                .build();
+
+            diesel::insert_into(deleted_crates::table)
+                .values(deleted_crate)
+                .execute(conn)
+                .await?;
+
+            jobs::DeleteCrateFromStorage::new(name.clone())
+                .enqueue(conn)
+                .await?;
+
+            enqueue_index_jobs(conn, &name).await
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
+async fn enqueue_index_jobs(conn: &mut AsyncPgConnection, name: &str) -> anyhow::Result<()> {
+    let git_index_job = jobs::SyncToGitIndex::new(name);
+    let sparse_index_job = jobs::SyncToSparseIndex::new(name);
+
+    tokio::try_join!(git_index_job.enqueue(conn), sparse_index_job.enqueue(conn))?;
+
+    Ok(())
+}
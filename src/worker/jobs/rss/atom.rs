@@ -0,0 +1,188 @@
+//! Conversion of the RSS feeds into their Atom variants.
+
+use atom_syndication::{Entry, Feed, Link, Text};
+use chrono::DateTime;
+use std::collections::BTreeMap;
+
+/// Converts an RSS channel into the equivalent Atom feed.
+///
+/// `self_url` is the URL of the Atom feed itself, which is also used as the
+/// ID of the feed. The `updated` timestamp of the feed is the publish date of
+/// its most recent entry.
+pub fn channel_to_atom(channel: &rss::Channel, self_url: String) -> Feed {
+    let entries = channel
+        .items
+        .iter()
+        .filter_map(item_to_entry)
+        .collect::<Vec<_>>();
+
+    let updated = entries
+        .iter()
+        .map(|entry| entry.updated)
+        .max()
+        .unwrap_or_default();
+
+    let links = vec![
+        Link {
+            href: self_url.clone(),
+            rel: "self".to_string(),
+            mime_type: Some("application/atom+xml".to_string()),
+            ..Default::default()
+        },
+        Link {
+            href: channel.link.clone(),
+            rel: "alternate".to_string(),
+            mime_type: Some("text/html".to_string()),
+            ..Default::default()
+        },
+    ];
+
+    Feed {
+        title: Text::plain(&channel.title),
+        id: self_url,
+        updated,
+        subtitle: Some(Text::plain(&channel.description)),
+        links,
+        entries,
+        namespaces: channel.namespaces.clone(),
+        lang: channel.language.clone(),
+        ..Default::default()
+    }
+}
+
+/// Converts an RSS item into the equivalent Atom entry. Items without a
+/// link or a valid publish date are skipped, since Atom requires both.
+fn item_to_entry(item: &rss::Item) -> Option<Entry> {
+    let link = item.link.clone()?;
+    let published = DateTime::parse_from_rfc2822(item.pub_date.as_deref()?).ok()?;
+
+    let id = item
+        .guid
+        .as_ref()
+        .map_or_else(|| link.clone(), |guid| guid.value.clone());
+
+    let link = Link {
+        href: link,
+        rel: "alternate".to_string(),
+        mime_type: Some("text/html".to_string()),
+        ..Default::default()
+    };
+
+    let extensions = item
+        .extensions
+        .iter()
+        .map(|(namespace, extensions)| {
+            let extensions = extensions
+                .iter()
+                .map(|(name, values)| {
+                    (name.clone(), values.iter().map(convert_extension).collect())
+                })
+                .collect();
+            (namespace.clone(), extensions)
+        })
+        .collect();
+
+    Some(Entry {
+        title: Text::plain(item.title.clone().unwrap_or_default()),
+        id,
+        updated: published,
+        published: Some(published),
+        links: vec![link],
+        summary: item.description.as_deref().map(Text::plain),
+        extensions,
+        ..Default::default()
+    })
+}
+
+fn convert_extension(
+    extension: &rss::extension::Extension,
+) -> atom_syndication::extension::Extension {
+    let children = extension
+        .children
+        .iter()
+        .map(|(name, values)| (name.clone(), values.iter().map(convert_extension).collect()))
+        .collect::<BTreeMap<_, _>>();
+
+    atom_syndication::extension::Extension {
+        name: extension.name.clone(),
+        value: extension.value.clone(),
+        attrs: extension.attrs.clone(),
+        children,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+
+    #[test]
+    fn test_channel_to_atom() {
+        let version_extension = rss::extension::Extension {
+            name: "crates:version".into(),
+            value: Some("1.0.0".into()),
+            ..Default::default()
+        };
+        let extensions = [("version".to_string(), vec![version_extension])];
+        let extensions = [("crates".to_string(), extensions.into_iter().collect())];
+
+        let items = vec![
+            rss::Item {
+                guid: Some(rss::Guid {
+                    value: "https://crates.io/crates/foo/1.0.0".into(),
+                    permalink: true,
+                }),
+                title: Some("New crate version published: foo v1.0.0".into()),
+                link: Some("https://crates.io/crates/foo/1.0.0".into()),
+                description: Some("A <foo> crate".into()),
+                pub_date: Some("Sat, 22 Jun 2024 15:57:19 +0000".into()),
+                extensions: extensions.into_iter().collect(),
+                ..Default::default()
+            },
+            rss::Item {
+                title: Some("Item without publish date".into()),
+                link: Some("https://crates.io/crates/bar".into()),
+                ..Default::default()
+            },
+        ];
+
+        let namespaces = [("crates".to_string(), "https://crates.io/".to_string())];
+
+        let channel = rss::Channel {
+            title: "crates.io: foo releases".into(),
+            link: "https://crates.io/crates/foo".into(),
+            description: "Recent releases of the foo crate".into(),
+            language: Some("en".into()),
+            namespaces: namespaces.into_iter().collect(),
+            items,
+            ..Default::default()
+        };
+
+        let feed = channel_to_atom(&channel, "https://static.crates.io/atom/foo.xml".into());
+
+        let config = atom_syndication::WriteConfig {
+            write_document_declaration: false,
+            indent_size: Some(4),
+        };
+        let buffer = feed.write_with_config(Vec::new(), config).unwrap();
+        assert_snapshot!(String::from_utf8(buffer).unwrap(), @r#"
+        <feed xmlns="http://www.w3.org/2005/Atom" xmlns:crates="https://crates.io/" xml:lang="en">
+            <title>crates.io: foo releases</title>
+            <id>https://static.crates.io/atom/foo.xml</id>
+            <updated>2024-06-22T15:57:19+00:00</updated>
+            <link href="https://static.crates.io/atom/foo.xml" rel="self" type="application/atom+xml"/>
+            <link href="https://crates.io/crates/foo" rel="alternate" type="text/html"/>
+            <subtitle>Recent releases of the foo crate</subtitle>
+            <entry>
+                <title>New crate version published: foo v1.0.0</title>
+                <id>https://crates.io/crates/foo/1.0.0</id>
+                <updated>2024-06-22T15:57:19+00:00</updated>
+                <link href="https://crates.io/crates/foo/1.0.0" rel="alternate" type="text/html"/>
+                <published>2024-06-22T15:57:19+00:00</published>
+                <summary>A &lt;foo&gt; crate</summary>
+                <crates:version>1.0.0</crates:version>
+            </entry>
+        </feed>
+        "#);
+    }
+}
@@ -1,7 +1,105 @@
+use crate::schema::{crates, versions};
+use crate::storage::FeedId;
+use crate::worker::Environment;
+use chrono::{DateTime, Utc};
+use crates_io_database::models::CloudFrontDistribution;
+use diesel::prelude::*;
+use diesel_async::AsyncPgConnection;
+use object_store::path::Path;
+use tracing::{info, warn};
+
+mod atom;
 mod sync_crate_feed;
+mod sync_crate_group_feed;
 mod sync_crates_feed;
 mod sync_updates_feed;
 
+pub use atom::channel_to_atom;
 pub use sync_crate_feed::SyncCrateFeed;
+pub use sync_crate_group_feed::{CrateGroup, SyncCrateGroupFeed};
 pub use sync_crates_feed::SyncCratesFeed;
 pub use sync_updates_feed::SyncUpdatesFeed;
+
+/// Uploads an RSS feed and its Atom variant to storage, and invalidates the
+/// CDN caches for both of them.
+async fn upload_feed(
+    ctx: &Environment,
+    conn: &mut AsyncPgConnection,
+    feed_id: &FeedId<'_>,
+    channel: &rss::Channel,
+) -> anyhow::Result<()> {
+    info!("Uploading feed to storage…");
+    ctx.storage.upload_feed(feed_id, channel).await?;
+
+    let atom_feed = atom::channel_to_atom(channel, ctx.storage.atom_feed_url(feed_id));
+    ctx.storage.upload_atom_feed(feed_id, &atom_feed).await?;
+
+    let dist = CloudFrontDistribution::Static;
+    for path in [Path::from(feed_id), feed_id.atom_path()] {
+        if let Err(error) = ctx.invalidate_cdns(conn, dist, path.as_ref()).await {
+            warn!("Failed to invalidate CDN caches: {error}");
+        }
+    }
+
+    Ok(())
+}
+
+/// A published version, as listed in the RSS feeds.
+#[derive(Debug, HasQuery)]
+#[diesel(base_query = versions::table.inner_join(crates::table))]
+pub struct VersionUpdate {
+    #[diesel(select_expression = crates::columns::name)]
+    name: String,
+    #[diesel(select_expression = versions::columns::num)]
+    version: String,
+    #[diesel(select_expression = crates::columns::description)]
+    description: Option<String>,
+    #[diesel(select_expression = versions::columns::created_at)]
+    time: DateTime<Utc>,
+}
+
+impl VersionUpdate {
+    pub fn into_rss_item(self, domain: &str) -> rss::Item {
+        let title = format!(
+            "New crate version published: {} v{}",
+            self.name, self.version
+        );
+        let link = format!("https://{domain}/crates/{}/{}", self.name, self.version);
+        let pub_date = self.time.to_rfc2822();
+
+        let guid = rss::Guid {
+            value: link.clone(),
+            permalink: true,
+        };
+
+        let name_extension = rss::extension::Extension {
+            name: "crates:name".into(),
+            value: Some(self.name),
+            ..Default::default()
+        };
+
+        let version_extension = rss::extension::Extension {
+            name: "crates:version".into(),
+            value: Some(self.version),
+            ..Default::default()
+        };
+
+        let extensions = vec![
+            ("name".to_string(), vec![name_extension]),
+            ("version".to_string(), vec![version_extension]),
+        ];
+        let extensions = extensions.into_iter().collect();
+        let extensions = vec![("crates".to_string(), extensions)];
+        let extensions = extensions.into_iter().collect();
+
+        rss::Item {
+            guid: Some(guid),
+            title: Some(title),
+            link: Some(link),
+            description: self.description,
+            pub_date: Some(pub_date),
+            extensions,
+            ..Default::default()
+        }
+    }
+}
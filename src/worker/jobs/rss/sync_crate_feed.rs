@@ -1,14 +1,15 @@
+use super::VersionUpdate;
+use crate::models::Version;
 use crate::schema::{crates, versions};
 use crate::storage::FeedId;
 use crate::worker::Environment;
-use chrono::{Duration, Utc};
-use crates_io_database::models::CloudFrontDistribution;
+use chrono::Duration;
 use crates_io_worker::BackgroundJob;
 use diesel::prelude::*;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tracing::{info, warn};
+use tracing::info;
 
 /// Items younger than this will always be included in the feed.
 const ALWAYS_INCLUDE_AGE: Duration = Duration::hours(24);
@@ -58,7 +59,11 @@ impl BackgroundJob for SyncCrateFeed {
 
         let items = version_updates
             .into_iter()
-            .map(|u| u.into_rss_item(name, domain))
+            // The channel already describes the crate, so the items have no description
+            .map(|u| rss::Item {
+                description: None,
+                ..u.into_rss_item(domain)
+            })
             .collect();
 
         let namespaces = vec![("crates".to_string(), "https://crates.io/".to_string())];
@@ -77,14 +82,7 @@ impl BackgroundJob for SyncCrateFeed {
             ..Default::default()
         };
 
-        info!("Uploading feed to storage…");
-        ctx.storage.upload_feed(&feed_id, &channel).await?;
-
-        let dist = CloudFrontDistribution::Static;
-        let path = object_store::path::Path::from(&feed_id);
-        if let Err(error) = ctx.invalidate_cdns(&mut conn, dist, path.as_ref()).await {
-            warn!("Failed to invalidate CDN caches: {error}");
-        }
+        super::upload_feed(&ctx, &mut conn, &feed_id, &channel).await?;
 
         info!("Finished syncing updates feed");
         Ok(())
@@ -105,6 +103,7 @@ async fn load_version_updates(
 
     let updates = VersionUpdate::query()
         .filter(crates::name.eq(name))
+        .filter(Version::is_published())
         .filter(versions::created_at.gt(threshold_dt))
         .order(versions::created_at.desc())
         .load(conn)
@@ -117,67 +116,17 @@ async fn load_version_updates(
 
     VersionUpdate::query()
         .filter(crates::name.eq(name))
+        .filter(Version::is_published())
         .order(versions::created_at.desc())
         .limit(NUM_ITEMS)
         .load(conn)
         .await
 }
 
-#[derive(Debug, HasQuery)]
-#[diesel(base_query = versions::table.inner_join(crates::table))]
-struct VersionUpdate {
-    #[diesel(select_expression = versions::columns::num)]
-    version: String,
-    #[diesel(select_expression = versions::columns::created_at)]
-    time: chrono::DateTime<Utc>,
-}
-
-impl VersionUpdate {
-    fn into_rss_item(self, name: &str, domain: &str) -> rss::Item {
-        let title = format!("New crate version published: {} v{}", name, self.version);
-        let link = format!("https://{domain}/crates/{}/{}", name, self.version);
-        let pub_date = self.time.to_rfc2822();
-
-        let guid = rss::Guid {
-            value: link.clone(),
-            permalink: true,
-        };
-
-        let name_extension = rss::extension::Extension {
-            name: "crates:name".into(),
-            value: Some(name.to_string()),
-            ..Default::default()
-        };
-
-        let version_extension = rss::extension::Extension {
-            name: "crates:version".into(),
-            value: Some(self.version),
-            ..Default::default()
-        };
-
-        let extensions = vec![
-            ("name".to_string(), vec![name_extension]),
-            ("version".to_string(), vec![version_extension]),
-        ];
-        let extensions = extensions.into_iter().collect();
-        let extensions = vec![("crates".to_string(), extensions)];
-        let extensions = extensions.into_iter().collect();
-
-        rss::Item {
-            guid: Some(guid),
-            title: Some(title),
-            link: Some(link),
-            pub_date: Some(pub_date),
-            extensions,
-            ..Default::default()
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::DateTime;
+    use chrono::{DateTime, Utc};
     use claims::assert_ok;
     use crates_io_test_db::TestDatabase;
     use futures_util::future::join_all;
@@ -0,0 +1,325 @@
+use super::VersionUpdate;
+use crate::models::Version;
+use crate::schema::{
+    categories, crate_owners, crates_categories, crates_keywords, keywords, teams, users, versions,
+};
+use crate::storage::FeedId;
+use crate::worker::Environment;
+use chrono::Duration;
+use crates_io_database::models::OwnerKind;
+use crates_io_diesel_helpers::lower;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use tracing::info;
+
+/// Items younger than this will always be included in the feed.
+const ALWAYS_INCLUDE_AGE: Duration = Duration::hours(24);
+
+/// The number of items to include in the feed.
+///
+/// If there are less than this number of items in the database, the feed will
+/// contain fewer items. If there are more items in the database that are
+/// younger than [`ALWAYS_INCLUDE_AGE`], all of them will be included in
+/// the feed.
+const NUM_ITEMS: i64 = 25;
+
+/// A group of crates that has its own feed.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CrateGroup {
+    /// All crates owned by the user with this login.
+    User { login: String },
+    /// All crates owned by the team with this login.
+    Team { login: String },
+    /// All crates with this keyword.
+    Keyword { keyword: String },
+    /// All crates in the category with this slug, or its subcategories.
+    Category { slug: String },
+}
+
+impl CrateGroup {
+    fn feed_id(&self) -> FeedId<'_> {
+        match self {
+            CrateGroup::User { login } => FeedId::User { login },
+            CrateGroup::Team { login } => FeedId::Team { login },
+            CrateGroup::Keyword { keyword } => FeedId::Keyword { keyword },
+            CrateGroup::Category { slug } => FeedId::Category { slug },
+        }
+    }
+
+    fn title(&self) -> String {
+        match self {
+            CrateGroup::User { login } | CrateGroup::Team { login } => {
+                format!("crates.io: releases of crates owned by {login}")
+            }
+            CrateGroup::Keyword { keyword } => {
+                format!("crates.io: releases of crates with the {keyword} keyword")
+            }
+            CrateGroup::Category { slug } => {
+                format!("crates.io: releases of crates in the {slug} category")
+            }
+        }
+    }
+
+    fn description(&self) -> String {
+        match self {
+            CrateGroup::User { login } | CrateGroup::Team { login } => {
+                format!(
+                    "Recent releases of crates owned by {login} on the crates.io package registry"
+                )
+            }
+            CrateGroup::Keyword { keyword } => {
+                format!(
+                    "Recent releases of crates with the {keyword} keyword on the crates.io package registry"
+                )
+            }
+            CrateGroup::Category { slug } => {
+                format!(
+                    "Recent releases of crates in the {slug} category on the crates.io package registry"
+                )
+            }
+        }
+    }
+
+    fn link(&self, domain: &str) -> String {
+        match self {
+            CrateGroup::User { login } => format!("https://{domain}/users/{login}"),
+            CrateGroup::Team { login } => format!("https://{domain}/teams/{login}"),
+            CrateGroup::Keyword { keyword } => format!("https://{domain}/keywords/{keyword}"),
+            CrateGroup::Category { slug } => format!("https://{domain}/categories/{slug}"),
+        }
+    }
+
+    /// Loads the IDs of the crates in this group.
+    async fn crate_ids(&self, conn: &mut AsyncPgConnection) -> QueryResult<Vec<i32>> {
+        match self {
+            CrateGroup::User { login } => {
+                let user_ids = users::table
+                    .select(users::id)
+                    .filter(lower(users::gh_login).eq(login.to_lowercase()));
+
+                crate_owners::table
+                    .select(crate_owners::crate_id)
+                    .filter(crate_owners::owner_kind.eq(OwnerKind::User))
+                    .filter(crate_owners::owner_id.eq_any(user_ids))
+                    .filter(crate_owners::deleted.eq(false))
+                    .load(conn)
+                    .await
+            }
+            CrateGroup::Team { login } => {
+                let team_ids = teams::table
+                    .select(teams::id)
+                    .filter(lower(teams::login).eq(login.to_lowercase()));
+
+                crate_owners::table
+                    .select(crate_owners::crate_id)
+                    .filter(crate_owners::owner_kind.eq(OwnerKind::Team))
+                    .filter(crate_owners::owner_id.eq_any(team_ids))
+                    .filter(crate_owners::deleted.eq(false))
+                    .load(conn)
+                    .await
+            }
+            CrateGroup::Keyword { keyword } => {
+                crates_keywords::table
+                    .inner_join(keywords::table)
+                    .select(crates_keywords::crate_id)
+                    .filter(keywords::keyword.eq(keyword.to_lowercase()))
+                    .load(conn)
+                    .await
+            }
+            CrateGroup::Category { slug } => {
+                let subcategories = format!("{slug}::%");
+
+                crates_categories::table
+                    .inner_join(categories::table)
+                    .select(crates_categories::crate_id)
+                    .filter(
+                        categories::slug
+                            .eq(slug)
+                            .or(categories::slug.like(subcategories)),
+                    )
+                    .distinct()
+                    .load(conn)
+                    .await
+            }
+        }
+    }
+}
+
+/// A background job that regenerates the feed of a [`CrateGroup`].
+#[derive(Serialize, Deserialize)]
+pub struct SyncCrateGroupFeed {
+    group: CrateGroup,
+}
+
+impl SyncCrateGroupFeed {
+    pub fn new(group: CrateGroup) -> Self {
+        Self { group }
+    }
+
+    /// Returns the jobs that regenerate the feeds of all groups that the
+    /// given crate belongs to, i.e. the feeds of its owners, its keywords,
+    /// and its categories including their parent categories.
+    pub async fn for_crate(conn: &mut AsyncPgConnection, crate_id: i32) -> QueryResult<Vec<Self>> {
+        let owners = crate_owners::table
+            .filter(crate_owners::crate_id.eq(crate_id))
+            .filter(crate_owners::deleted.eq(false));
+
+        let user_logins: Vec<String> = users::table
+            .select(users::gh_login)
+            .filter(
+                users::id.eq_any(
+                    owners
+                        .select(crate_owners::owner_id)
+                        .filter(crate_owners::owner_kind.eq(OwnerKind::User)),
+                ),
+            )
+            .load(conn)
+            .await?;
+
+        let team_logins: Vec<String> = teams::table
+            .select(teams::login)
+            .filter(
+                teams::id.eq_any(
+                    owners
+                        .select(crate_owners::owner_id)
+                        .filter(crate_owners::owner_kind.eq(OwnerKind::Team)),
+                ),
+            )
+            .load(conn)
+            .await?;
+
+        let keywords: Vec<String> = crates_keywords::table
+            .inner_join(keywords::table)
+            .select(keywords::keyword)
+            .filter(crates_keywords::crate_id.eq(crate_id))
+            .load(conn)
+            .await?;
+
+        let category_slugs: Vec<String> = crates_categories::table
+            .inner_join(categories::table)
+            .select(categories::slug)
+            .filter(crates_categories::crate_id.eq(crate_id))
+            .load(conn)
+            .await?;
+
+        let mut groups = BTreeSet::new();
+        groups.extend(
+            user_logins
+                .into_iter()
+                .map(|login| CrateGroup::User { login }),
+        );
+        groups.extend(
+            team_logins
+                .into_iter()
+                .map(|login| CrateGroup::Team { login }),
+        );
+        groups.extend(
+            keywords
+                .into_iter()
+                .map(|keyword| CrateGroup::Keyword { keyword }),
+        );
+        for slug in category_slugs {
+            // `a::b::c` is also part of the `a` and `a::b` categories.
+            let parents = slug.match_indices("::").map(|(index, _)| &slug[..index]);
+            for parent in parents {
+                let slug = parent.to_string();
+                groups.insert(CrateGroup::Category { slug });
+            }
+            groups.insert(CrateGroup::Category { slug });
+        }
+
+        Ok(groups.into_iter().map(Self::new).collect())
+    }
+}
+
+impl BackgroundJob for SyncCrateGroupFeed {
+    const JOB_NAME: &'static str = "sync_crate_group_feed";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, ctx: Self::Context) -> anyhow::Result<()> {
+        let group = &self.group;
+        let feed_id = group.feed_id();
+        let domain = &ctx.config.domain_name;
+
+        info!(
+            ?group,
+            "Loading latest {NUM_ITEMS} version updates from the database…"
+        );
+        let mut conn = ctx.deadpool.get().await?;
+
+        let crate_ids = group.crate_ids(&mut conn).await?;
+        let version_updates = load_version_updates(&crate_ids, &mut conn).await?;
+
+        let link = rss::extension::atom::Link {
+            href: ctx.storage.feed_url(&feed_id),
+            rel: "self".to_string(),
+            mime_type: Some("application/rss+xml".to_string()),
+            ..Default::default()
+        };
+
+        let items = version_updates
+            .into_iter()
+            .map(|u| u.into_rss_item(domain))
+            .collect();
+
+        let namespaces = vec![("crates".to_string(), "https://crates.io/".to_string())];
+        let namespaces = namespaces.into_iter().collect();
+
+        let channel = rss::Channel {
+            title: group.title(),
+            link: group.link(domain),
+            description: group.description(),
+            language: Some("en".to_string()),
+            atom_ext: Some(rss::extension::atom::AtomExtension { links: vec![link] }),
+            namespaces,
+            items,
+            ..Default::default()
+        };
+
+        super::upload_feed(&ctx, &mut conn, &feed_id, &channel).await?;
+
+        info!(?group, "Finished syncing crate group feed");
+        Ok(())
+    }
+}
+
+/// Load the latest versions of the given crates from the database.
+///
+/// This function will load all versions from the database that are younger
+/// than [`ALWAYS_INCLUDE_AGE`]. If there are less than [`NUM_ITEMS`] versions
+/// then the list will be padded with older versions until [`NUM_ITEMS`] are
+/// returned.
+async fn load_version_updates(
+    crate_ids: &[i32],
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<Vec<VersionUpdate>> {
+    let threshold_dt = chrono::Utc::now().naive_utc() - ALWAYS_INCLUDE_AGE;
+
+    let updates = VersionUpdate::query()
+        .filter(versions::crate_id.eq_any(crate_ids))
+        .filter(Version::is_published())
+        .filter(versions::created_at.gt(threshold_dt))
+        .order(versions::created_at.desc())
+        .load(conn)
+        .await?;
+
+    let num_updates = updates.len();
+    if num_updates as i64 >= NUM_ITEMS {
+        return Ok(updates);
+    }
+
+    VersionUpdate::query()
+        .filter(versions::crate_id.eq_any(crate_ids))
+        .filter(Version::is_published())
+        .order(versions::created_at.desc())
+        .limit(NUM_ITEMS)
+        .load(conn)
+        .await
+}
@@ -2,13 +2,12 @@ use crate::schema::crates;
 use crate::storage::FeedId;
 use crate::worker::Environment;
 use chrono::{Duration, Utc};
-use crates_io_database::models::CloudFrontDistribution;
 use crates_io_worker::BackgroundJob;
 use diesel::prelude::*;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tracing::{info, warn};
+use tracing::info;
 
 #[derive(Serialize, Deserialize)]
 pub struct SyncCratesFeed;
@@ -64,14 +63,7 @@ impl BackgroundJob for SyncCratesFeed {
             ..Default::default()
         };
 
-        info!("Uploading feed to storage…");
-        ctx.storage.upload_feed(&feed_id, &channel).await?;
-
-        let dist = CloudFrontDistribution::Static;
-        let path = object_store::path::Path::from(&feed_id);
-        if let Err(error) = ctx.invalidate_cdns(&mut conn, dist, path.as_ref()).await {
-            warn!("Failed to invalidate CDN caches: {error}");
-        }
+        super::upload_feed(&ctx, &mut conn, &feed_id, &channel).await?;
 
         info!("Finished syncing crates feed");
         Ok(())
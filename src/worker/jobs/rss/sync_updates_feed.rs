@@ -1,14 +1,15 @@
-use crate::schema::{crates, versions};
+use super::VersionUpdate;
+use crate::models::Version;
+use crate::schema::versions;
 use crate::storage::FeedId;
 use crate::worker::Environment;
-use chrono::{Duration, Utc};
-use crates_io_database::models::CloudFrontDistribution;
+use chrono::Duration;
 use crates_io_worker::BackgroundJob;
 use diesel::prelude::*;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tracing::{info, warn};
+use tracing::info;
 
 #[derive(Serialize, Deserialize)]
 pub struct SyncUpdatesFeed;
@@ -64,14 +65,7 @@ impl BackgroundJob for SyncUpdatesFeed {
             ..Default::default()
         };
 
-        info!("Uploading feed to storage…");
-        ctx.storage.upload_feed(&feed_id, &channel).await?;
-
-        let dist = CloudFrontDistribution::Static;
-        let path = object_store::path::Path::from(&feed_id);
-        if let Err(error) = ctx.invalidate_cdns(&mut conn, dist, path.as_ref()).await {
-            warn!("Failed to invalidate CDN caches: {error}");
-        }
+        super::upload_feed(&ctx, &mut conn, &feed_id, &channel).await?;
 
         info!("Finished syncing updates feed");
         Ok(())
@@ -88,6 +82,7 @@ async fn load_version_updates(conn: &mut AsyncPgConnection) -> QueryResult<Vec<V
     let threshold_dt = chrono::Utc::now().naive_utc() - ALWAYS_INCLUDE_AGE;
 
     let updates = VersionUpdate::query()
+        .filter(Version::is_published())
         .filter(versions::created_at.gt(threshold_dt))
         .order(versions::created_at.desc())
         .load(conn)
@@ -99,74 +94,17 @@ async fn load_version_updates(conn: &mut AsyncPgConnection) -> QueryResult<Vec<V
     }
 
     VersionUpdate::query()
+        .filter(Version::is_published())
         .order(versions::created_at.desc())
         .limit(NUM_ITEMS)
         .load(conn)
         .await
 }
 
-#[derive(Debug, HasQuery)]
-#[diesel(base_query = versions::table.inner_join(crates::table))]
-struct VersionUpdate {
-    #[diesel(select_expression = crates::columns::name)]
-    name: String,
-    #[diesel(select_expression = versions::columns::num)]
-    version: String,
-    #[diesel(select_expression = crates::columns::description)]
-    description: Option<String>,
-    #[diesel(select_expression = versions::columns::created_at)]
-    time: chrono::DateTime<Utc>,
-}
-
-impl VersionUpdate {
-    fn into_rss_item(self, domain: &str) -> rss::Item {
-        let title = format!(
-            "New crate version published: {} v{}",
-            self.name, self.version
-        );
-        let link = format!("https://{domain}/crates/{}/{}", self.name, self.version);
-        let pub_date = self.time.to_rfc2822();
-
-        let guid = rss::Guid {
-            value: link.clone(),
-            permalink: true,
-        };
-
-        let name_extension = rss::extension::Extension {
-            name: "crates:name".into(),
-            value: Some(self.name),
-            ..Default::default()
-        };
-
-        let version_extension = rss::extension::Extension {
-            name: "crates:version".into(),
-            value: Some(self.version),
-            ..Default::default()
-        };
-
-        let extensions = vec![
-            ("name".to_string(), vec![name_extension]),
-            ("version".to_string(), vec![version_extension]),
-        ];
-        let extensions = extensions.into_iter().collect();
-        let extensions = vec![("crates".to_string(), extensions)];
-        let extensions = extensions.into_iter().collect();
-
-        rss::Item {
-            guid: Some(guid),
-            title: Some(title),
-            link: Some(link),
-            description: self.description,
-            pub_date: Some(pub_date),
-            extensions,
-            ..Default::default()
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::schema::crates;
     use chrono::{DateTime, Utc};
     use claims::assert_ok;
     use crates_io_test_db::TestDatabase;
@@ -0,0 +1,85 @@
+use crate::email::EmailMessage;
+use crate::models::token::{ApiTokenUsage, ApiTokenUsageAnomaly};
+use crate::schema::{api_tokens, emails, users};
+use crate::worker::Environment;
+use chrono::SecondsFormat;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use minijinja::context;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+/// Background job that notifies the owner of an API token that the token
+/// was used from a new country or network, or for a new endpoint.
+#[derive(Serialize, Deserialize)]
+pub struct SendApiTokenUsageAlert {
+    usage_id: i64,
+    anomalies: Vec<ApiTokenUsageAnomaly>,
+}
+
+impl SendApiTokenUsageAlert {
+    pub fn new(usage_id: i64, anomalies: Vec<ApiTokenUsageAnomaly>) -> Self {
+        Self {
+            usage_id,
+            anomalies,
+        }
+    }
+}
+
+impl BackgroundJob for SendApiTokenUsageAlert {
+    const JOB_NAME: &'static str = "send_api_token_usage_alert";
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, ctx: Self::Context) -> anyhow::Result<()> {
+        let usage_id = self.usage_id;
+        let mut conn = ctx.deadpool.get().await?;
+
+        let Some(usage) = ApiTokenUsage::find(&mut conn, usage_id).await? else {
+            info!("Skipping API token usage alert for {usage_id}: usage log entry was deleted");
+            return Ok(());
+        };
+
+        let recipient: Option<(String, String, Option<String>)> = api_tokens::table
+            .inner_join(users::table)
+            .left_join(
+                emails::table.on(users::id.eq(emails::user_id).and(emails::verified.eq(true))),
+            )
+            .filter(api_tokens::id.eq(usage.api_token_id))
+            .select((api_tokens::name, users::gh_login, emails::email.nullable()))
+            .first(&mut conn)
+            .await
+            .optional()?;
+
+        let Some((token_name, recipient, Some(email_address))) = recipient else {
+            info!("Skipping API token usage alert for {usage_id}: no verified email address");
+            return Ok(());
+        };
+
+        let email = EmailMessage::from_template(
+            "api_token_usage_alert",
+            context! {
+                recipient => recipient,
+                token_name => token_name,
+                new_country => self.anomalies.contains(&ApiTokenUsageAnomaly::NewCountry),
+                new_asn => self.anomalies.contains(&ApiTokenUsageAnomaly::NewAsn),
+                new_endpoint => self.anomalies.contains(&ApiTokenUsageAnomaly::NewEndpoint),
+                used_at => usage.created_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+                endpoint => usage.endpoint,
+                crate_name => usage.crate_name,
+                ip => usage.ip.ip().to_string(),
+                country => usage.country,
+                asn => usage.asn,
+                user_agent => usage.user_agent,
+                domain => ctx.config.domain_name,
+            },
+        )?;
+
+        info!("Sending API token usage alert for {usage_id} to {email_address}…");
+        ctx.emails.send(&email_address, email).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use crate::email::EmailMessage;
+use crate::models::{Crate, CrateStatus, OwnerKind};
+use crate::schema::{crate_owners, crates, default_versions, dependencies, emails, users};
+use crate::worker::Environment;
+use crates_io_worker::BackgroundJob;
+use diesel::dsl::now;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use minijinja::context;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing::{info, instrument, warn};
+
+/// Background job that notifies the owners of all crates that depend on a
+/// crate that was deprecated.
+///
+/// Only the default versions of the dependent crates are taken into account,
+/// and owners can opt out of these notifications per crate via the
+/// `email_notifications` setting.
+///
+/// The notifications are only sent once per crate, so that toggling the
+/// status back and forth does not send the same emails again.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SendCrateDeprecationNotifications {
+    crate_id: i32,
+}
+
+impl SendCrateDeprecationNotifications {
+    pub fn new(crate_id: i32) -> Self {
+        Self { crate_id }
+    }
+}
+
+impl BackgroundJob for SendCrateDeprecationNotifications {
+    const JOB_NAME: &'static str = "send_crate_deprecation_notifications";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(crate_id = ?self.crate_id))]
+    async fn run(&self, ctx: Self::Context) -> anyhow::Result<()> {
+        let mut conn = ctx.deadpool.get().await?;
+
+        let krate: Crate = Crate::query().find(self.crate_id).first(&mut conn).await?;
+
+        // The owners might have changed their mind in the meantime
+        if krate.status != CrateStatus::Deprecated {
+            info!("Crate {} is not deprecated anymore", krate.name);
+            return Ok(());
+        }
+
+        let updated = diesel::update(crates::table)
+            .filter(crates::id.eq(krate.id))
+            .filter(crates::deprecation_notified_at.is_null())
+            .set(crates::deprecation_notified_at.eq(now))
+            .execute(&mut conn)
+            .await?;
+
+        if updated == 0 {
+            info!(
+                "Deprecation notifications for {} were already sent",
+                krate.name
+            );
+            return Ok(());
+        }
+
+        let dependents: Vec<(String, String, String)> = dependencies::table
+            .inner_join(
+                default_versions::table
+                    .on(default_versions::version_id.eq(dependencies::version_id)),
+            )
+            .inner_join(crates::table.on(crates::id.eq(default_versions::crate_id)))
+            .inner_join(crate_owners::table.on(crate_owners::crate_id.eq(crates::id)))
+            .inner_join(users::table.on(users::id.eq(crate_owners::owner_id)))
+            .inner_join(emails::table.on(emails::user_id.eq(users::id)))
+            .filter(dependencies::crate_id.eq(krate.id))
+            .filter(crate_owners::deleted.eq(false))
+            .filter(crate_owners::owner_kind.eq(OwnerKind::User))
+            .filter(crate_owners::email_notifications.eq(true))
+            .filter(emails::verified.eq(true))
+            .select((users::gh_login, emails::email, crates::name))
+            .distinct()
+            .load(&mut conn)
+            .await?;
+
+        // Send a single email per user, listing all of their affected crates
+        let mut recipients = BTreeMap::<(String, String), Vec<String>>::new();
+        for (login, email_address, dependent) in dependents {
+            recipients
+                .entry((login, email_address))
+                .or_default()
+                .push(dependent);
+        }
+
+        info!(
+            "Sending deprecation notifications for {} to {} recipients…",
+            krate.name,
+            recipients.len()
+        );
+
+        for ((recipient, email_address), mut dependents) in recipients {
+            dependents.sort();
+
+            let email = EmailMessage::from_template(
+                "crate_deprecated",
+                context! {
+                    recipient => recipient,
+                    krate => krate.name,
+                    successor => krate.successor,
+                    dependents => dependents,
+                    domain => ctx.config.domain_name,
+                },
+            );
+
+            let result = match email {
+                Ok(email) => ctx
+                    .emails
+                    .send(&email_address, email)
+                    .await
+                    .map_err(Into::into),
+                Err(err) => Err(anyhow::Error::from(err)),
+            };
+
+            if let Err(err) = result {
+                warn!(
+                    "Failed to send deprecation notification for {} to {email_address}: {err}",
+                    krate.name
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use crate::email::EmailMessage;
+use crate::models::{
+    CrateTransferAction, CrateTransferRequest, CrateTransferRequestEvent, OwnerKind,
+};
+use crate::schema::{crate_owners, crates, emails, users};
+use crate::worker::Environment;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use minijinja::context;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{info, instrument, warn};
+
+/// Background job that notifies the people involved in a crate transfer
+/// request about an action that was performed on it.
+///
+/// New and withdrawn requests are sent to the owners of the crate, while
+/// all other actions are sent to the requester.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SendCrateTransferNotifications {
+    event_id: i64,
+}
+
+impl SendCrateTransferNotifications {
+    pub fn new(event: &CrateTransferRequestEvent) -> Self {
+        Self { event_id: event.id }
+    }
+}
+
+impl BackgroundJob for SendCrateTransferNotifications {
+    const JOB_NAME: &'static str = "send_crate_transfer_notifications";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(event_id = ?self.event_id))]
+    async fn run(&self, ctx: Self::Context) -> anyhow::Result<()> {
+        let mut conn = ctx.deadpool.get().await?;
+
+        let event = CrateTransferRequestEvent::find(&mut conn, self.event_id).await?;
+        let request = CrateTransferRequest::find(&mut conn, event.request_id).await?;
+
+        let krate: String = crates::table
+            .find(request.crate_id)
+            .select(crates::name)
+            .first(&mut conn)
+            .await?;
+
+        let requester: String = users::table
+            .find(request.requester_id)
+            .select(users::gh_login)
+            .first(&mut conn)
+            .await?;
+
+        let (template, recipients) = match event.action {
+            CrateTransferAction::Created | CrateTransferAction::Withdrawn => {
+                let recipients = load_owner_recipients(&request, &mut conn).await?;
+                ("crate_transfer_request", recipients)
+            }
+            _ => {
+                let recipients = load_user_recipient(request.requester_id, &mut conn).await?;
+                ("crate_transfer_request_resolved", recipients)
+            }
+        };
+
+        info!(
+            "Sending {template} notification for {krate} to {} recipients…",
+            recipients.len()
+        );
+
+        for (recipient, email_address) in recipients {
+            let email = EmailMessage::from_template(
+                template,
+                context! {
+                    recipient => recipient,
+                    krate => krate,
+                    requester => requester,
+                    reason => request.reason,
+                    action => event.action,
+                    note => event.note,
+                    domain => ctx.config.domain_name,
+                },
+            );
+
+            let result = match email {
+                Ok(email) => ctx
+                    .emails
+                    .send(&email_address, email)
+                    .await
+                    .map_err(Into::into),
+                Err(err) => Err(anyhow::Error::from(err)),
+            };
+
+            if let Err(err) = result {
+                warn!(
+                    "Failed to send crate transfer notification for {krate} to {email_address}: {err}"
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Loads the owners of the crate, except for the requester in case they
+/// became an owner in the meantime.
+async fn load_owner_recipients(
+    request: &CrateTransferRequest,
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<Vec<(String, String)>> {
+    crate_owners::table
+        .filter(crate_owners::deleted.eq(false))
+        .filter(crate_owners::owner_kind.eq(OwnerKind::User))
+        .filter(crate_owners::crate_id.eq(request.crate_id))
+        .filter(crate_owners::owner_id.ne(request.requester_id))
+        .inner_join(users::table)
+        .inner_join(emails::table.on(users::id.eq(emails::user_id)))
+        .filter(emails::verified.eq(true))
+        .select((users::gh_login, emails::email))
+        .load(conn)
+        .await
+}
+
+async fn load_user_recipient(
+    user_id: i32,
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<Vec<(String, String)>> {
+    users::table
+        .find(user_id)
+        .inner_join(emails::table)
+        .filter(emails::verified.eq(true))
+        .select((users::gh_login, emails::email))
+        .load(conn)
+        .await
+}
@@ -0,0 +1,110 @@
+use crate::email::EmailMessage;
+use crate::models::{OwnerKind, PendingVersion};
+use crate::schema::{crate_owners, crates, emails, users, versions};
+use crate::worker::Environment;
+use chrono::SecondsFormat;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use minijinja::context;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Background job that notifies all owners of a crate that a new version
+/// has been uploaded and is waiting for their approval.
+///
+/// These notifications are security relevant, so they are sent regardless
+/// of the `publish_notifications` setting of the owners.
+#[derive(Serialize, Deserialize)]
+pub struct SendPendingVersionNotifications {
+    version_id: i32,
+}
+
+impl SendPendingVersionNotifications {
+    pub fn new(version_id: i32) -> Self {
+        Self { version_id }
+    }
+}
+
+impl BackgroundJob for SendPendingVersionNotifications {
+    const JOB_NAME: &'static str = "send_pending_version_notifications";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, ctx: Self::Context) -> anyhow::Result<()> {
+        let version_id = self.version_id;
+        let mut conn = ctx.deadpool.get().await?;
+
+        let Some(pending_version) = PendingVersion::find(&mut conn, version_id).await? else {
+            info!("Skipping pending version notifications for {version_id}: not pending anymore");
+            return Ok(());
+        };
+
+        let (crate_id, krate, version, publisher): (i32, String, String, Option<String>) =
+            versions::table
+                .inner_join(crates::table)
+                .left_join(users::table)
+                .filter(versions::id.eq(version_id))
+                .select((
+                    crates::id,
+                    crates::name,
+                    versions::num,
+                    users::gh_login.nullable(),
+                ))
+                .first(&mut conn)
+                .await?;
+
+        let recipients = crate_owners::table
+            .filter(crate_owners::deleted.eq(false))
+            .filter(crate_owners::owner_kind.eq(OwnerKind::User))
+            .filter(crate_owners::crate_id.eq(crate_id))
+            .inner_join(users::table)
+            .inner_join(emails::table.on(users::id.eq(emails::user_id)))
+            .filter(emails::verified.eq(true))
+            .select((users::gh_login, emails::email))
+            .load::<(String, String)>(&mut conn)
+            .await?;
+
+        info!(
+            "Sending pending version notifications for {krate}@{version} to {} recipients…",
+            recipients.len()
+        );
+
+        let expires_at = pending_version
+            .expires_at
+            .to_rfc3339_opts(SecondsFormat::Secs, true);
+
+        for (recipient, email_address) in recipients {
+            let email = EmailMessage::from_template(
+                "version_pending_approval",
+                context! {
+                    recipient => recipient,
+                    krate => krate,
+                    version => version,
+                    publisher => publisher,
+                    expires_at => expires_at,
+                    domain => ctx.config.domain_name,
+                },
+            );
+
+            let result = match email {
+                Ok(email) => ctx
+                    .emails
+                    .send(&email_address, email)
+                    .await
+                    .map_err(Into::into),
+                Err(err) => Err(anyhow::Error::from(err)),
+            };
+
+            if let Err(err) = result {
+                warn!(
+                    "Failed to send pending version notification for {krate}@{version} to {email_address}: {err}"
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -22,12 +22,19 @@ impl RunnerExt for Runner<Arc<Environment>> {
         self.register_job_type::<jobs::AnalyzeCrateFile>()
             .register_job_type::<jobs::ArchiveVersionDownloads>()
             .register_job_type::<jobs::BulkSyncToGitIndex>()
+            .register_job_type::<jobs::CheckSemverCompatibility>()
             .register_job_type::<jobs::CheckTyposquat>()
             .register_job_type::<jobs::CleanProcessedLogFiles>()
             .register_job_type::<jobs::DailyDbMaintenance>()
+            .register_job_type::<jobs::DeleteOldApiTokenUsages>()
             .register_job_type::<jobs::DeleteCrateFromStorage>()
+            .register_job_type::<jobs::DocsRsCheckBuildStatus>()
             .register_job_type::<jobs::DocsRsQueueRebuild>()
+            .register_job_type::<jobs::DocsRsRetryRebuildRequests>()
+            .register_job_type::<jobs::DocsRsSyncBuildStatuses>()
             .register_job_type::<jobs::DumpDb>()
+            .register_job_type::<jobs::DumpDbIncremental>()
+            .register_job_type::<jobs::ExpirePendingVersions>()
             .register_job_type::<jobs::GenerateOgImage>()
             .register_job_type::<jobs::IndexVersionDownloadsArchive>()
             .register_job_type::<jobs::InvalidateCdns>()
@@ -35,6 +42,7 @@ impl RunnerExt for Runner<Arc<Environment>> {
             .register_job_type::<jobs::ProcessCdnLog>()
             .register_job_type::<jobs::ProcessCdnLogQueue>()
             .register_job_type::<jobs::ProcessCloudfrontInvalidationQueue>()
+            .register_job_type::<jobs::ProcessScheduledCrateDeletions>()
             .register_job_type::<jobs::RenderAndUploadReadme>()
             .register_job_type::<jobs::SquashIndex>()
             .register_job_type::<jobs::SyncAdmins>()
@@ -44,7 +52,12 @@ impl RunnerExt for Runner<Arc<Environment>> {
             .register_job_type::<jobs::UpdateDefaultVersion>()
             .register_job_type::<jobs::SendTokenExpiryNotifications>()
             .register_job_type::<jobs::SendPublishNotificationsJob>()
+            .register_job_type::<jobs::SendCrateTransferNotifications>()
+            .register_job_type::<jobs::SendCrateDeprecationNotifications>()
+            .register_job_type::<jobs::SendPendingVersionNotifications>()
+            .register_job_type::<jobs::SendApiTokenUsageAlert>()
             .register_job_type::<jobs::rss::SyncCrateFeed>()
+            .register_job_type::<jobs::rss::SyncCrateGroupFeed>()
             .register_job_type::<jobs::rss::SyncCratesFeed>()
             .register_job_type::<jobs::rss::SyncUpdatesFeed>()
             .register_job_type::<jobs::trustpub::DeleteExpiredJtis>()